@@ -0,0 +1,22 @@
+pub mod lute {
+  tonic::include_proto!("lute");
+}
+
+use anyhow::Result;
+use tonic::transport::Channel;
+
+pub async fn connect(lute_url: &str) -> Result<Channel> {
+  Ok(
+    tonic::transport::Endpoint::from_shared(lute_url.to_string())?
+      .connect()
+      .await?,
+  )
+}
+
+pub fn authorized<T>(message: T, api_key: &Option<String>) -> Result<tonic::Request<T>> {
+  let mut request = tonic::Request::new(message);
+  if let Some(api_key) = api_key {
+    request.metadata_mut().insert("x-api-key", api_key.parse()?);
+  }
+  Ok(request)
+}
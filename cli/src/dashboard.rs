@@ -0,0 +1,251 @@
+use crate::client::{
+  authorized,
+  lute::{
+    operations_service_client::OperationsServiceClient, OperationsDashboard, WatchDashboardRequest,
+  },
+};
+use anyhow::Result;
+use crossterm::{
+  event::{self, Event, KeyCode, KeyEventKind},
+  execute,
+  terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+  backend::CrosstermBackend,
+  layout::{Constraint, Direction, Layout},
+  style::{Color, Modifier, Style},
+  text::Line,
+  widgets::{Block, Borders, Paragraph, Row, Table},
+  Frame, Terminal,
+};
+use std::{
+  io::{stdout, Stdout},
+  time::Duration,
+};
+use tokio::{
+  spawn,
+  sync::mpsc::{unbounded_channel, UnboundedReceiver},
+};
+use tonic::transport::Channel;
+
+#[derive(Default)]
+struct DashboardState {
+  dashboard: Option<OperationsDashboard>,
+  error: Option<String>,
+}
+
+fn block(title: String) -> Block<'static> {
+  Block::default().borders(Borders::ALL).title(title)
+}
+
+fn header(cells: Vec<&'static str>) -> Row<'static> {
+  Row::new(cells).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn format_interval(seconds: u32) -> String {
+  match seconds {
+    s if s % 86400 == 0 => format!("{}d", s / 86400),
+    s if s % 3600 == 0 => format!("{}h", s / 3600),
+    s if s % 60 == 0 => format!("{}m", s / 60),
+    s => format!("{}s", s),
+  }
+}
+
+fn render(frame: &mut Frame, state: &DashboardState) {
+  let sections = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([
+      Constraint::Length(4),
+      Constraint::Min(8),
+      Constraint::Length(12),
+      Constraint::Length(1),
+    ])
+    .split(frame.size());
+  let middle = Layout::default()
+    .direction(Direction::Horizontal)
+    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+    .split(sections[1]);
+
+  let footer = match (&state.error, &state.dashboard) {
+    (Some(error), _) => Line::styled(
+      format!("Disconnected: {}. Press q to quit.", error),
+      Style::default().fg(Color::Red),
+    ),
+    (None, Some(dashboard)) => Line::from(format!(
+      "Updated {}. Press q to quit.",
+      dashboard.generated_at
+    )),
+    (None, None) => Line::from("Connecting..."),
+  };
+  frame.render_widget(Paragraph::new(footer), sections[3]);
+
+  let Some(dashboard) = &state.dashboard else {
+    return;
+  };
+
+  let crawler = dashboard.crawler.clone().unwrap_or_default();
+  frame.render_widget(
+    Paragraph::new(vec![
+      Line::from(format!(
+        "Status: {}    Queue: {}    Claimed: {}",
+        crawler.status().as_str_name().trim_start_matches("Crawler"),
+        crawler.size,
+        crawler.claimed_item_count
+      )),
+      Line::from(format!(
+        "Window: {} requests made, {} remaining",
+        crawler.window_request_count, crawler.remaining_window_requests
+      )),
+    ])
+    .block(block("Crawler".to_string())),
+    sections[0],
+  );
+
+  let subscriber_rows = dashboard.subscriber_lags.iter().map(|lag| {
+    let row = Row::new(vec![
+      lag.subscriber_id.clone(),
+      lag
+        .status()
+        .as_str_name()
+        .trim_start_matches("Subscriber")
+        .to_string(),
+      lag.events_behind.to_string(),
+      lag.oldest_unprocessed_at.clone().unwrap_or_default(),
+    ]);
+    if lag.exceeds_threshold {
+      row.style(Style::default().fg(Color::Red))
+    } else {
+      row
+    }
+  });
+  frame.render_widget(
+    Table::new(
+      subscriber_rows,
+      [
+        Constraint::Percentage(35),
+        Constraint::Percentage(15),
+        Constraint::Percentage(15),
+        Constraint::Percentage(35),
+      ],
+    )
+    .header(header(vec!["Subscriber", "Status", "Behind", "Oldest"]))
+    .block(block("Event subscribers".to_string())),
+    middle[0],
+  );
+
+  let job_rows = dashboard.recurring_jobs.iter().map(|job| {
+    Row::new(vec![
+      job.name.clone(),
+      job
+        .interval_seconds
+        .map(format_interval)
+        .unwrap_or_default(),
+      job.next_execution.clone(),
+      job.last_execution.clone().unwrap_or_default(),
+    ])
+  });
+  let job_count: u32 = dashboard.job_counts.values().sum();
+  frame.render_widget(
+    Table::new(
+      job_rows,
+      [
+        Constraint::Percentage(34),
+        Constraint::Percentage(10),
+        Constraint::Percentage(28),
+        Constraint::Percentage(28),
+      ],
+    )
+    .header(header(vec!["Job", "Every", "Next", "Last"]))
+    .block(block(format!("Scheduler ({} jobs queued)", job_count))),
+    middle[1],
+  );
+
+  let failure_rows = dashboard.recent_parser_failures.iter().map(|failure| {
+    Row::new(vec![
+      failure.last_attempted_at.clone(),
+      failure.file_name.clone(),
+      failure.error.clone(),
+    ])
+  });
+  frame.render_widget(
+    Table::new(
+      failure_rows,
+      [
+        Constraint::Length(20),
+        Constraint::Percentage(40),
+        Constraint::Percentage(60),
+      ],
+    )
+    .header(header(vec!["Attempted", "File", "Error"]))
+    .block(block("Recent parser failures".to_string())),
+    sections[2],
+  );
+}
+
+fn draw_loop(
+  terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+  receiver: &mut UnboundedReceiver<Result<OperationsDashboard, String>>,
+) -> Result<()> {
+  let mut state = DashboardState::default();
+  loop {
+    while let Ok(update) = receiver.try_recv() {
+      match update {
+        Ok(dashboard) => state.dashboard = Some(dashboard),
+        Err(error) => state.error = Some(error),
+      }
+    }
+    terminal.draw(|frame| render(frame, &state))?;
+    if event::poll(Duration::from_millis(250))? {
+      if let Event::Key(key) = event::read()? {
+        if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        {
+          return Ok(());
+        }
+      }
+    }
+  }
+}
+
+/**
+ * Shows snapshots from the operations dashboard stream until the user quits
+ */
+pub async fn run_dashboard(
+  channel: Channel,
+  api_key: &Option<String>,
+  interval_seconds: u32,
+) -> Result<()> {
+  let mut stream = OperationsServiceClient::new(channel)
+    .watch_dashboard(authorized(
+      WatchDashboardRequest {
+        interval_seconds: Some(interval_seconds),
+        parser_failure_limit: Some(10),
+      },
+      api_key,
+    )?)
+    .await?
+    .into_inner();
+
+  let (sender, mut receiver) = unbounded_channel();
+  spawn(async move {
+    loop {
+      let update = match stream.message().await {
+        Ok(Some(dashboard)) => Ok(dashboard),
+        Ok(None) => Err("stream ended".to_string()),
+        Err(status) => Err(status.message().to_string()),
+      };
+      let done = update.is_err();
+      if sender.send(update).is_err() || done {
+        break;
+      }
+    }
+  });
+
+  enable_raw_mode()?;
+  execute!(stdout(), EnterAlternateScreen)?;
+  let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+  let result = draw_loop(&mut terminal, &mut receiver);
+  disable_raw_mode()?;
+  execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+  terminal.show_cursor()?;
+  result
+}
@@ -0,0 +1,375 @@
+mod client;
+mod dashboard;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand, ValueEnum};
+use client::{
+  authorized, connect,
+  lute::{
+    album_service_client::AlbumServiceClient, auth_service_client::AuthServiceClient,
+    event_service_client::EventServiceClient, lookup_service_client::LookupServiceClient,
+    parser_service_client::ParserServiceClient, scheduler_service_client::SchedulerServiceClient,
+    AlbumSearchLookupQuery, AlbumSearchQuery, ApiKeyScope, CreateApiKeyRequest,
+    EnqueueRetriesRequest, EventStreamRequest, GetAggregatedFailureErrorsRequest,
+    LookupAlbumRequest, LookupLane, PageType, RevokeApiKeyRequest, SearchAlbumsRequest,
+    SearchPagination,
+  },
+};
+use dashboard::run_dashboard;
+use serde::Serialize;
+use tokio::sync::mpsc::unbounded_channel;
+use tonic::transport::Channel;
+
+/**
+ * Event schema version of the proto this tool is built against
+ */
+const EVENT_SCHEMA_VERSION: u32 = 4;
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 1024;
+
+#[derive(Parser, Debug)]
+#[command(
+  name = "lute-cli",
+  about = "Administer a lute instance over its gRPC API"
+)]
+struct Args {
+  #[arg(long, env = "LUTE_URL", default_value = "grpc://localhost:22000")]
+  lute_url: String,
+
+  /// Needed when the lute instance has auth enabled. Admin commands need an admin key.
+  #[arg(long, env = "LUTE_API_KEY")]
+  api_key: Option<String>,
+
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Look up an album by artist and album name, crawling it if it isn't known yet
+  Lookup {
+    artist: String,
+    album: String,
+    /// Queue the lookup behind interactive ones
+    #[arg(long, default_value_t = false)]
+    background: bool,
+    /// Give up on the lookup if it hasn't finished within this many seconds
+    #[arg(long)]
+    ttl_seconds: Option<u32>,
+  },
+  /// Search the album index
+  Search {
+    text: Option<String>,
+    #[arg(long)]
+    genre: Vec<String>,
+    #[arg(long)]
+    descriptor: Vec<String>,
+    #[arg(long)]
+    min_year: Option<u32>,
+    #[arg(long)]
+    max_year: Option<u32>,
+    #[arg(long, default_value_t = 20)]
+    limit: u32,
+    #[arg(long, default_value_t = 0)]
+    offset: u32,
+  },
+  /// Inspect and retry parser failures
+  #[command(subcommand)]
+  ParseFailures(ParseFailuresCommand),
+  /// Manage API keys
+  #[command(subcommand)]
+  ApiKeys(ApiKeysCommand),
+  /// List scheduled jobs
+  Jobs,
+  /// Print events from a stream as they are published
+  Tail {
+    #[arg(default_value = "parser")]
+    stream_id: String,
+    /// Subscriber the cursor is saved under, so a later tail continues where this one stopped
+    #[arg(long, default_value = "lute-cli")]
+    subscriber_id: String,
+    /// Entry id to start after, the subscriber's saved cursor when unset
+    #[arg(long)]
+    cursor: Option<String>,
+  },
+  /// Show crawler, event subscriber, parser and scheduler status, updated live
+  Dashboard {
+    #[arg(long, default_value_t = 2)]
+    interval_seconds: u32,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+enum ParseFailuresCommand {
+  /// Count failures by error
+  List {
+    #[arg(long, value_enum)]
+    page_type: Option<PageTypeArg>,
+  },
+  /// Parse every file that failed with the error again
+  Retry { error: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum ApiKeysCommand {
+  List,
+  /// Create a key, which is only printed this once
+  Create {
+    name: String,
+    #[arg(long, value_enum, default_value = "read-only")]
+    scope: ScopeArg,
+  },
+  Revoke {
+    id: String,
+  },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PageTypeArg {
+  Album,
+  Artist,
+  Chart,
+  AlbumSearchResult,
+  ListSegment,
+  BandcampSearchResult,
+  GenreTree,
+}
+
+impl From<PageTypeArg> for PageType {
+  fn from(val: PageTypeArg) -> Self {
+    match val {
+      PageTypeArg::Album => PageType::AlbumPage,
+      PageTypeArg::Artist => PageType::ArtistPage,
+      PageTypeArg::Chart => PageType::ChartPage,
+      PageTypeArg::AlbumSearchResult => PageType::AlbumSearchResultPage,
+      PageTypeArg::ListSegment => PageType::ListSegmentPage,
+      PageTypeArg::BandcampSearchResult => PageType::BandcampSearchResultPage,
+      PageTypeArg::GenreTree => PageType::GenreTreePage,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ScopeArg {
+  ReadOnly,
+  Admin,
+  ConnectorReplication,
+}
+
+impl From<ScopeArg> for ApiKeyScope {
+  fn from(val: ScopeArg) -> Self {
+    match val {
+      ScopeArg::ReadOnly => ApiKeyScope::ApiKeyReadOnly,
+      ScopeArg::Admin => ApiKeyScope::ApiKeyAdmin,
+      ScopeArg::ConnectorReplication => ApiKeyScope::ApiKeyConnectorReplication,
+    }
+  }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+  println!("{}", serde_json::to_string_pretty(value)?);
+  Ok(())
+}
+
+fn event_stream_request(
+  stream_id: &str,
+  subscriber_id: &str,
+  cursor: Option<String>,
+) -> EventStreamRequest {
+  EventStreamRequest {
+    stream_id: stream_id.to_string(),
+    subscriber_id: subscriber_id.to_string(),
+    cursor,
+    max_batch_size: Some(100),
+    flatten: false,
+    schema_version: Some(EVENT_SCHEMA_VERSION),
+    supported_event_types: vec![],
+  }
+}
+
+/**
+ * Follows the stream until interrupted, acknowledging each batch so the server sends the next
+ */
+async fn tail(
+  args: &Args,
+  channel: Channel,
+  stream_id: String,
+  subscriber_id: String,
+  cursor: Option<String>,
+) -> Result<()> {
+  let mut client = EventServiceClient::new(channel).max_decoding_message_size(MAX_MESSAGE_SIZE);
+  let (cursor_sender, mut cursor_receiver) = unbounded_channel::<String>();
+  let request_stream = async_stream::stream! {
+    yield event_stream_request(&stream_id, &subscriber_id, cursor);
+
+    while let Some(cursor) = cursor_receiver.recv().await {
+      yield event_stream_request(&stream_id, &subscriber_id, Some(cursor));
+    }
+  };
+
+  let mut event_stream = client
+    .stream(authorized(request_stream, &args.api_key)?)
+    .await?
+    .into_inner();
+
+  while let Some(reply) = event_stream.message().await? {
+    for item in &reply.items {
+      print_json(item)?;
+    }
+    cursor_sender.send(reply.cursor)?;
+  }
+
+  Ok(())
+}
+
+async fn run(args: &Args) -> Result<()> {
+  let channel = connect(&args.lute_url).await?;
+
+  match &args.command {
+    Command::Lookup {
+      artist,
+      album,
+      background,
+      ttl_seconds,
+    } => {
+      let reply = LookupServiceClient::new(channel)
+        .lookup_album(authorized(
+          LookupAlbumRequest {
+            query: Some(AlbumSearchLookupQuery {
+              artist_name: artist.clone(),
+              album_name: album.clone(),
+            }),
+            lane: Some(if *background {
+              LookupLane::Background
+            } else {
+              LookupLane::Interactive
+            } as i32),
+            priority: None,
+            ttl_seconds: *ttl_seconds,
+          },
+          &args.api_key,
+        )?)
+        .await?
+        .into_inner();
+      print_json(&reply.lookup)?;
+    }
+    Command::Search {
+      text,
+      genre,
+      descriptor,
+      min_year,
+      max_year,
+      limit,
+      offset,
+    } => {
+      let reply = AlbumServiceClient::new(channel)
+        .max_decoding_message_size(MAX_MESSAGE_SIZE)
+        .search_albums(authorized(
+          SearchAlbumsRequest {
+            query: Some(AlbumSearchQuery {
+              text: text.clone(),
+              include_primary_genres: genre.clone(),
+              include_descriptors: descriptor.clone(),
+              min_release_year: *min_year,
+              max_release_year: *max_year,
+              ..Default::default()
+            }),
+            pagination: Some(SearchPagination {
+              offset: Some(*offset),
+              limit: Some(*limit),
+            }),
+            ..Default::default()
+          },
+          &args.api_key,
+        )?)
+        .await?
+        .into_inner();
+      print_json(&reply)?;
+    }
+    Command::ParseFailures(ParseFailuresCommand::List { page_type }) => {
+      let reply = ParserServiceClient::new(channel)
+        .get_aggregated_failure_errors(authorized(
+          GetAggregatedFailureErrorsRequest {
+            page_type: page_type.map(|page_type| PageType::from(page_type) as i32),
+          },
+          &args.api_key,
+        )?)
+        .await?
+        .into_inner();
+      print_json(&reply.errors)?;
+    }
+    Command::ParseFailures(ParseFailuresCommand::Retry { error }) => {
+      ParserServiceClient::new(channel)
+        .enqueue_retries(authorized(
+          EnqueueRetriesRequest {
+            error: error.clone(),
+          },
+          &args.api_key,
+        )?)
+        .await?;
+      println!("Retries enqueued");
+    }
+    Command::ApiKeys(ApiKeysCommand::List) => {
+      let reply = AuthServiceClient::new(channel)
+        .list_api_keys(authorized((), &args.api_key)?)
+        .await?
+        .into_inner();
+      print_json(&reply.api_keys)?;
+    }
+    Command::ApiKeys(ApiKeysCommand::Create { name, scope }) => {
+      let reply = AuthServiceClient::new(channel)
+        .create_api_key(authorized(
+          CreateApiKeyRequest {
+            name: name.clone(),
+            scope: ApiKeyScope::from(*scope) as i32,
+          },
+          &args.api_key,
+        )?)
+        .await?
+        .into_inner();
+      print_json(&reply)?;
+    }
+    Command::ApiKeys(ApiKeysCommand::Revoke { id }) => {
+      AuthServiceClient::new(channel)
+        .revoke_api_key(authorized(
+          RevokeApiKeyRequest { id: id.clone() },
+          &args.api_key,
+        )?)
+        .await?;
+      println!("API key revoked");
+    }
+    Command::Jobs => {
+      let reply = SchedulerServiceClient::new(channel)
+        .max_decoding_message_size(MAX_MESSAGE_SIZE)
+        .get_jobs(authorized((), &args.api_key)?)
+        .await?
+        .into_inner();
+      print_json(&reply.jobs)?;
+    }
+    Command::Tail {
+      stream_id,
+      subscriber_id,
+      cursor,
+    } => {
+      tail(
+        args,
+        channel,
+        stream_id.clone(),
+        subscriber_id.clone(),
+        cursor.clone(),
+      )
+      .await?;
+    }
+    Command::Dashboard { interval_seconds } => {
+      run_dashboard(channel, &args.api_key, *interval_seconds).await?;
+    }
+  }
+
+  Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  let args = Args::parse();
+  run(&args).await
+}
@@ -0,0 +1,266 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use clap::{arg, Parser};
+use clickhouse::{Client, Row};
+use lute_clickhouse_connector::{
+  client::lute::{
+    bootstrap_service_client::BootstrapServiceClient, event::Event,
+    event_service_client::EventServiceClient, parsed_file_data::Data, BootstrapRequest,
+    EventStreamItem, EventStreamRequest,
+  },
+  models::*,
+  schema::run_migrations,
+};
+use serde::Serialize;
+use tokio::sync::mpsc::unbounded_channel;
+
+/**
+ * Event schema version of the proto this connector is built against
+ */
+const EVENT_SCHEMA_VERSION: u32 = 2;
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 1024;
+
+async fn insert_rows<T: Row + Serialize>(client: &Client, table: &str, rows: &[T]) -> Result<()> {
+  if rows.is_empty() {
+    return Ok(());
+  }
+  let mut insert = client.insert(table)?;
+  for row in rows {
+    insert.write(row).await?;
+  }
+  insert.end().await?;
+  Ok(())
+}
+
+async fn store_albums(client: &Client, rows: &AlbumRows) -> Result<()> {
+  insert_rows(client, "lute_albums", &rows.albums).await?;
+  insert_rows(client, "lute_tracks", &rows.tracks).await?;
+  insert_rows(client, "lute_album_genres", &rows.genres).await?;
+  Ok(())
+}
+
+fn album_rows(batch: &[EventStreamItem], updated_at: i64) -> AlbumRows {
+  let mut rows = AlbumRows::default();
+  for item in batch {
+    let event = item
+      .payload
+      .as_ref()
+      .and_then(|payload| payload.event.as_ref())
+      .and_then(|event| event.event.as_ref());
+    if let Some(Event::FileParsed(file_parsed_event)) = event {
+      if let Some(Data::Album(parsed_album)) = file_parsed_event
+        .data
+        .as_ref()
+        .and_then(|data| data.data.as_ref())
+      {
+        rows.push(&file_parsed_event.file_name, parsed_album, updated_at);
+      }
+    }
+  }
+  rows
+}
+
+async fn process_batch(client: &Client, batch: &[EventStreamItem]) -> Result<()> {
+  let events = batch
+    .iter()
+    .map(EventRow::from_item)
+    .collect::<Result<Vec<_>>>()?;
+  insert_rows(client, "lute_events", &events).await?;
+  store_albums(client, &album_rows(batch, Utc::now().timestamp_millis())).await?;
+  Ok(())
+}
+
+async fn get_cursor(client: &Client, subscriber_id: &str) -> Result<Option<String>> {
+  let row = client
+    .query("SELECT ?fields FROM lute_cursors FINAL WHERE subscriber_id = ?")
+    .bind(subscriber_id)
+    .fetch_optional::<CursorRow>()
+    .await?;
+  Ok(row.map(|row| row.cursor.to_string()))
+}
+
+/**
+ * Saved once a batch is stored, so a restart resumes after the last batch that made it in
+ */
+async fn save_cursor(client: &Client, subscriber_id: &str, cursor: &str) -> Result<()> {
+  insert_rows(
+    client,
+    "lute_cursors",
+    &[CursorRow {
+      subscriber_id: subscriber_id.to_string(),
+      cursor: cursor.parse()?,
+      updated_at: Utc::now().timestamp_millis(),
+    }],
+  )
+  .await
+}
+
+fn authorized<T>(message: T, api_key: &Option<String>) -> Result<tonic::Request<T>> {
+  let mut request = tonic::Request::new(message);
+  if let Some(api_key) = api_key {
+    request.metadata_mut().insert("x-api-key", api_key.parse()?);
+  }
+  Ok(request)
+}
+
+/**
+ * Stores a snapshot of every album, returning the cursor to stream events after. Much faster
+ * than replaying the stream from the start for a fresh database.
+ */
+async fn bootstrap(
+  api_key: &Option<String>,
+  lute_client: &mut BootstrapServiceClient<tonic::transport::Channel>,
+  client: &Client,
+) -> Result<String> {
+  let mut snapshot = lute_client
+    .bootstrap(authorized(
+      BootstrapRequest {
+        batch_size: Some(1000),
+      },
+      api_key,
+    )?)
+    .await?
+    .into_inner();
+
+  let mut album_count = 0;
+  while let Some(reply) = snapshot.message().await? {
+    if !reply.albums.is_empty() {
+      album_count += reply.albums.len();
+      let updated_at = Utc::now().timestamp_millis();
+      let mut rows = AlbumRows::default();
+      for (file_name, album) in reply.albums.into_iter().map(snapshot_album) {
+        rows.push(&file_name, &album, updated_at);
+      }
+      store_albums(client, &rows).await?;
+      println!("Stored {} snapshot albums", album_count);
+    }
+    if let Some(cursor) = reply.cursor {
+      return Ok(cursor);
+    }
+  }
+
+  Err(anyhow!("Bootstrap ended without a cursor"))
+}
+
+fn event_stream_request(
+  stream_id: &str,
+  subscriber_id: &str,
+  cursor: Option<String>,
+  max_batch_size: u32,
+) -> EventStreamRequest {
+  EventStreamRequest {
+    stream_id: stream_id.to_string(),
+    subscriber_id: subscriber_id.to_string(),
+    cursor,
+    max_batch_size: Some(max_batch_size),
+    flatten: true,
+    schema_version: Some(EVENT_SCHEMA_VERSION),
+    supported_event_types: vec![],
+  }
+}
+
+async fn subscribe(
+  args: &Args,
+  cursor: Option<String>,
+  lute_client: &mut EventServiceClient<tonic::transport::Channel>,
+  client: &Client,
+) -> Result<()> {
+  let (cursor_sender, mut cursor_receiver) = unbounded_channel::<String>();
+  let stream_id = args.stream_id.clone();
+  let subscriber_id = args.subscriber_id.clone();
+  let max_batch_size = args.max_batch_size;
+  let request_stream = async_stream::stream! {
+    yield event_stream_request(&stream_id, &subscriber_id, cursor, max_batch_size);
+
+    while let Some(cursor) = cursor_receiver.recv().await {
+      println!("Requesting batch with cursor: {}", cursor);
+      yield event_stream_request(&stream_id, &subscriber_id, Some(cursor), max_batch_size);
+    }
+  };
+
+  let response = lute_client
+    .stream(authorized(request_stream, &args.api_key)?)
+    .await?;
+  let mut event_stream = response.into_inner();
+
+  while let Some(reply) = event_stream.message().await? {
+    process_batch(client, &reply.items).await?;
+    save_cursor(client, &args.subscriber_id, &reply.cursor).await?;
+    cursor_sender.send(reply.cursor)?;
+  }
+
+  Ok(())
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+  #[arg(long, default_value = "grpc://localhost:22000")]
+  lute_url: String,
+
+  #[arg(long, default_value = "replication")]
+  stream_id: String,
+
+  #[arg(long)]
+  subscriber_id: String,
+
+  #[arg(long, default_value = "http://localhost:28123")]
+  clickhouse_url: String,
+
+  #[arg(long, default_value = "default")]
+  clickhouse_database: String,
+
+  #[arg(long, default_value = "default")]
+  clickhouse_user: String,
+
+  #[arg(long, default_value = "")]
+  clickhouse_password: String,
+
+  /// Events per stream batch, each batch is written with one insert per table
+  #[arg(long, default_value_t = 1000)]
+  max_batch_size: u32,
+
+  /// Needed when the lute instance has auth enabled, with the connector replication scope
+  #[arg(long)]
+  api_key: Option<String>,
+
+  /// Replay the stream from the start instead of loading a snapshot when the subscriber is new
+  #[arg(long, default_value_t = false)]
+  skip_bootstrap: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+  let args = Args::parse();
+  let client = Client::default()
+    .with_url(&args.clickhouse_url)
+    .with_database(&args.clickhouse_database)
+    .with_user(&args.clickhouse_user)
+    .with_password(&args.clickhouse_password);
+  run_migrations(&client)
+    .await
+    .expect("Failed to run migrations");
+
+  let channel = tonic::transport::Endpoint::from_shared(args.lute_url.clone())?
+    .connect()
+    .await
+    .expect("Failed to connect to lute instance");
+  let mut lute_client = EventServiceClient::new(channel.clone());
+
+  // The stored cursor is sent with the first request, so lute resumes from what ClickHouse has
+  let cursor = match get_cursor(&client, &args.subscriber_id).await? {
+    Some(cursor) => Some(cursor),
+    None if args.skip_bootstrap => None,
+    None => {
+      let mut bootstrap_client =
+        BootstrapServiceClient::new(channel).max_decoding_message_size(MAX_MESSAGE_SIZE);
+      let cursor = bootstrap(&args.api_key, &mut bootstrap_client, &client).await?;
+      save_cursor(&client, &args.subscriber_id, &cursor).await?;
+      Some(cursor)
+    }
+  };
+
+  subscribe(&args, cursor, &mut lute_client, &client).await?;
+
+  Ok(())
+}
@@ -0,0 +1,218 @@
+use crate::client::lute::{
+  Album, AlbumArtist, EventStreamItem, ParsedAlbum, ParsedArtistReference, ParsedCredit,
+  ParsedTrack,
+};
+use chrono::NaiveDate;
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+
+#[derive(Row, Serialize, Debug)]
+pub struct EventRow {
+  pub entry_id: u64,
+  pub stream_id: String,
+  pub event_type: String,
+  pub file_name: Option<String>,
+  pub correlation_id: Option<String>,
+  pub version: u32,
+  pub payload: String,
+}
+
+impl EventRow {
+  /**
+   * Expects the item to be flattened, which is where its event type comes from
+   */
+  pub fn from_item(item: &EventStreamItem) -> anyhow::Result<Self> {
+    let flat = item.flat.as_ref();
+    Ok(Self {
+      entry_id: item.entry_id.parse()?,
+      stream_id: item.stream_id.clone(),
+      event_type: flat.map(|flat| flat.event_type.clone()).unwrap_or_default(),
+      file_name: flat.and_then(|flat| flat.file_name.clone()),
+      correlation_id: flat.and_then(|flat| flat.correlation_id.clone()),
+      version: item
+        .payload
+        .as_ref()
+        .map(|payload| payload.version)
+        .unwrap_or_default(),
+      payload: serde_json::to_string(&item.payload)?,
+    })
+  }
+}
+
+#[derive(Row, Serialize, Debug)]
+pub struct AlbumRow {
+  pub file_name: String,
+  pub name: String,
+  pub rating: f32,
+  pub rating_count: u32,
+  pub artist_file_names: Vec<String>,
+  pub primary_genres: Vec<String>,
+  pub secondary_genres: Vec<String>,
+  pub descriptors: Vec<String>,
+  pub languages: Vec<String>,
+  /**
+   * Days since the unix epoch
+   */
+  pub release_date: Option<i32>,
+  /**
+   * Zero when unknown
+   */
+  pub release_year: u16,
+  pub is_various_artists: bool,
+  /**
+   * Milliseconds since the unix epoch
+   */
+  pub updated_at: i64,
+}
+
+#[derive(Row, Serialize, Debug)]
+pub struct TrackRow {
+  pub album_file_name: String,
+  pub name: String,
+  pub position: Option<String>,
+  pub duration_seconds: Option<u32>,
+  pub rating: Option<f32>,
+  pub updated_at: i64,
+}
+
+#[derive(Row, Serialize, Debug)]
+pub struct AlbumGenreRow {
+  pub album_file_name: String,
+  pub genre: String,
+  pub is_primary: bool,
+  pub release_year: u16,
+  pub rating: f32,
+  pub updated_at: i64,
+}
+
+#[derive(Row, Serialize, Deserialize, Debug)]
+pub struct CursorRow {
+  pub subscriber_id: String,
+  pub cursor: u64,
+  pub updated_at: i64,
+}
+
+fn release_date_days(release_date: &str) -> Option<i32> {
+  let date = NaiveDate::parse_from_str(release_date, "%Y-%m-%d").ok()?;
+  Some((date - NaiveDate::from_ymd_opt(1970, 1, 1)?).num_days() as i32)
+}
+
+fn release_year(release_date: &str) -> u16 {
+  release_date
+    .get(0..4)
+    .and_then(|year| year.parse().ok())
+    .unwrap_or_default()
+}
+
+/**
+ * Rows of every table an album is written to
+ */
+#[derive(Default, Debug)]
+pub struct AlbumRows {
+  pub albums: Vec<AlbumRow>,
+  pub tracks: Vec<TrackRow>,
+  pub genres: Vec<AlbumGenreRow>,
+}
+
+impl AlbumRows {
+  pub fn push(&mut self, file_name: &str, album: &ParsedAlbum, updated_at: i64) {
+    let year = album
+      .release_date
+      .as_deref()
+      .map(release_year)
+      .unwrap_or_default();
+    self.albums.push(AlbumRow {
+      file_name: file_name.to_string(),
+      name: album.name.clone(),
+      rating: album.rating,
+      rating_count: album.rating_count,
+      artist_file_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+      primary_genres: album.primary_genres.clone(),
+      secondary_genres: album.secondary_genres.clone(),
+      descriptors: album.descriptors.clone(),
+      languages: album.languages.clone(),
+      release_date: album.release_date.as_deref().and_then(release_date_days),
+      release_year: year,
+      is_various_artists: album.is_various_artists,
+      updated_at,
+    });
+    self
+      .tracks
+      .extend(album.tracks.iter().map(|track| TrackRow {
+        album_file_name: file_name.to_string(),
+        name: track.name.clone(),
+        position: track.position.clone(),
+        duration_seconds: track.duration_seconds,
+        rating: track.rating,
+        updated_at,
+      }));
+    let genres = album
+      .primary_genres
+      .iter()
+      .map(|genre| (genre, true))
+      .chain(album.secondary_genres.iter().map(|genre| (genre, false)));
+    self
+      .genres
+      .extend(genres.map(|(genre, is_primary)| AlbumGenreRow {
+        album_file_name: file_name.to_string(),
+        genre: genre.clone(),
+        is_primary,
+        release_year: year,
+        rating: album.rating,
+        updated_at,
+      }));
+  }
+}
+
+fn artist_reference(artist: AlbumArtist) -> ParsedArtistReference {
+  ParsedArtistReference {
+    name: artist.name,
+    file_name: artist.file_name,
+  }
+}
+
+/**
+ * Snapshot albums carry the same fields as parsed ones, so they're stored the same way
+ */
+pub fn snapshot_album(album: Album) -> (String, ParsedAlbum) {
+  (
+    album.file_name,
+    ParsedAlbum {
+      name: album.name,
+      rating: album.rating,
+      rating_count: album.rating_count,
+      artists: album.artists.into_iter().map(artist_reference).collect(),
+      primary_genres: album.primary_genres,
+      secondary_genres: album.secondary_genres,
+      descriptors: album.descriptors,
+      tracks: album
+        .tracks
+        .into_iter()
+        .map(|track| ParsedTrack {
+          name: track.name,
+          duration_seconds: track.duration_seconds,
+          rating: track.rating,
+          position: track.position,
+          artists: track.artists.into_iter().map(artist_reference).collect(),
+        })
+        .collect(),
+      release_date: album.release_date,
+      languages: album.languages,
+      credits: album
+        .credits
+        .into_iter()
+        .map(|credit| ParsedCredit {
+          artist: credit.artist.map(artist_reference),
+          roles: credit.roles,
+        })
+        .collect(),
+      cover_image_url: album.cover_image_url,
+      spotify_id: album.spotify_id,
+      is_various_artists: album.is_various_artists,
+    },
+  )
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use clickhouse::Client;
+
+/**
+ * Tables are append-only, so rows written again for a re-parsed album or a replayed batch
+ * replace the older ones when parts merge. Queries that need exact current state should read
+ * with FINAL.
+ */
+const STATEMENTS: [&str; 8] = [
+  "
+  CREATE TABLE IF NOT EXISTS lute_events (
+    entry_id UInt64,
+    stream_id LowCardinality(String),
+    event_type LowCardinality(String),
+    file_name Nullable(String),
+    correlation_id Nullable(String),
+    version UInt32,
+    payload String,
+    saved_at DateTime64(3) DEFAULT now64(3)
+  )
+  ENGINE = ReplacingMergeTree
+  ORDER BY (stream_id, entry_id)
+  ",
+  "
+  CREATE TABLE IF NOT EXISTS lute_albums (
+    file_name String,
+    name String,
+    rating Float32,
+    rating_count UInt32,
+    artist_file_names Array(String),
+    primary_genres Array(LowCardinality(String)),
+    secondary_genres Array(LowCardinality(String)),
+    descriptors Array(LowCardinality(String)),
+    languages Array(LowCardinality(String)),
+    release_date Nullable(Date32),
+    release_year UInt16,
+    is_various_artists Bool,
+    updated_at DateTime64(3)
+  )
+  ENGINE = ReplacingMergeTree(updated_at)
+  ORDER BY file_name
+  ",
+  "
+  CREATE TABLE IF NOT EXISTS lute_tracks (
+    album_file_name String,
+    name String,
+    position Nullable(String),
+    duration_seconds Nullable(UInt32),
+    rating Nullable(Float32),
+    updated_at DateTime64(3)
+  )
+  ENGINE = ReplacingMergeTree(updated_at)
+  ORDER BY (album_file_name, name)
+  ",
+  "
+  CREATE TABLE IF NOT EXISTS lute_album_genres (
+    album_file_name String,
+    genre LowCardinality(String),
+    is_primary Bool,
+    release_year UInt16,
+    rating Float32,
+    updated_at DateTime64(3)
+  )
+  ENGINE = ReplacingMergeTree(updated_at)
+  ORDER BY (genre, album_file_name, is_primary)
+  ",
+  // Album counts are unique counts, so albums written again aren't counted twice. A genre
+  // dropped from a re-parsed album stays counted.
+  "
+  CREATE TABLE IF NOT EXISTS lute_genre_aggregates (
+    genre LowCardinality(String),
+    release_year UInt16,
+    primary_albums AggregateFunction(uniq, String),
+    secondary_albums AggregateFunction(uniq, String)
+  )
+  ENGINE = AggregatingMergeTree
+  ORDER BY (genre, release_year)
+  ",
+  "
+  CREATE MATERIALIZED VIEW IF NOT EXISTS lute_genre_aggregates_mv
+  TO lute_genre_aggregates AS
+  SELECT
+    genre,
+    release_year,
+    uniqStateIf(album_file_name, is_primary) AS primary_albums,
+    uniqStateIf(album_file_name, NOT is_primary) AS secondary_albums
+  FROM lute_album_genres
+  GROUP BY genre, release_year
+  ",
+  "
+  CREATE VIEW IF NOT EXISTS lute_genres AS
+  SELECT
+    genre,
+    uniqMerge(primary_albums) AS primary_album_count,
+    uniqMerge(secondary_albums) AS secondary_album_count
+  FROM lute_genre_aggregates
+  GROUP BY genre
+  ",
+  "
+  CREATE TABLE IF NOT EXISTS lute_cursors (
+    subscriber_id String,
+    cursor UInt64,
+    updated_at DateTime64(3)
+  )
+  ENGINE = ReplacingMergeTree(updated_at)
+  ORDER BY subscriber_id
+  ",
+];
+
+pub async fn run_migrations(client: &Client) -> Result<()> {
+  for statement in STATEMENTS {
+    client.query(statement).execute().await?;
+  }
+  Ok(())
+}
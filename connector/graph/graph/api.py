@@ -31,6 +31,7 @@ async def root():
     return {
         "lute_cursors": {
             "build": await lute_client.get_subscriber_cursor("build"),
+            "lists": await lute_client.get_subscriber_cursor("lists"),
         }
     }
 
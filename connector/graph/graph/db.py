@@ -32,6 +32,18 @@ def setup_indexes():
         CREATE CONSTRAINT language_name IF NOT EXISTS FOR (l:Language)
         REQUIRE l.name IS UNIQUE
         """,
+        """
+        CREATE CONSTRAINT track_id IF NOT EXISTS FOR (t:Track)
+        REQUIRE t.id IS UNIQUE
+        """,
+        """
+        CREATE CONSTRAINT list_file_name IF NOT EXISTS FOR (l:List)
+        REQUIRE l.file_name IS UNIQUE
+        """,
+        """
+        CREATE CONSTRAINT duplicate_album_file_name IF NOT EXISTS FOR (d:DuplicateAlbum)
+        REQUIRE d.file_name IS UNIQUE
+        """,
         "CREATE INDEX album_name IF NOT EXISTS FOR (a:Album) ON (a.name)",
         "CREATE INDEX artist_name IF NOT EXISTS FOR (a:Artist) ON (a.name)",
         "CREATE INDEX credited_role IF NOT EXISTS FOR ()-[r:CREDITED]-() ON (r.role)",
@@ -42,10 +54,72 @@ def setup_indexes():
         gds.run_cypher(statement)
 
 
-def update_graph(albums: list[tuple[str, lute_pb2.ParsedAlbum]]):
+def track_id(album_file_name: str, track: lute_pb2.ParsedTrack | lute_pb2.Track) -> str:
+    """
+    Tracks have no file of their own, so they're keyed by their album and position
+    """
+    return f"{album_file_name}#{track.position if track.position else track.name}"
+
+
+def list_root_file_name(segment_file_name: str) -> str:
+    """
+    Segments are pages of a list, named after the list with their page number
+    """
+    return "/".join(segment_file_name.split("/")[:3])
+
+
+def find_duplicates(file_names: list[str]) -> set[str]:
+    result = gds.run_cypher(
+        """
+        UNWIND $file_names AS file_name
+        MATCH (d:DuplicateAlbum {file_name: file_name})
+        RETURN d.file_name AS file_name
+        """,
+        {"file_names": file_names},
+    )
+    return {row["file_name"] for row in result.to_dict("records")}
+
+
+def delete_album_relationships(file_names: list[str]):
+    """
+    Removes the tracks and relationships an album was parsed with. List memberships
+    come from list segments rather than the album, so they're kept.
+    """
+    gds.run_cypher(
+        """
+        UNWIND $file_names AS file_name
+        MATCH (:Album {file_name: file_name})-[:HAS_TRACK]->(t:Track)
+        DETACH DELETE t
+        """,
+        {"file_names": file_names},
+    )
+    gds.run_cypher(
+        """
+        UNWIND $file_names AS file_name
+        MATCH (:Album {file_name: file_name})-[r:ALBUM_ARTIST|CREDITED|GENRE|DESCRIPTOR|LANGUAGE]-()
+        DELETE r
+        """,
+        {"file_names": file_names},
+    )
+
+
+def update_graph(albums: list[tuple[str, lute_pb2.FlatParsedAlbum | lute_pb2.Album]]):
+    """
+    Replaces everything hanging off each album, so the graph holds albums as they were
+    last parsed and replaying events leaves it unchanged. Albums merged into another
+    as duplicates are skipped.
+    """
     start = time()
     relationship_count = 0
 
+    duplicates = find_duplicates([file_name for file_name, _ in albums])
+    albums = [
+        (file_name, album) for file_name, album in albums if file_name not in duplicates
+    ]
+    if not albums:
+        return
+    delete_album_relationships([file_name for file_name, _ in albums])
+
     logger.info(
         "Building graph update",
         extra={
@@ -81,6 +155,7 @@ def update_graph(albums: list[tuple[str, lute_pb2.ParsedAlbum]]):
             + len(album.secondary_genres)
             + len(album.descriptors)
             + len(album.languages)
+            + len(album.tracks)
         )
 
     gds.run_cypher(
@@ -125,7 +200,7 @@ def update_graph(albums: list[tuple[str, lute_pb2.ParsedAlbum]]):
         """
         UNWIND $albums AS album
         MERGE (a:Album {file_name: album.file_name})
-        ON CREATE SET a.name = album.name
+        SET a.name = album.name
         """,
         {
             "albums": [
@@ -236,8 +311,47 @@ def update_graph(albums: list[tuple[str, lute_pb2.ParsedAlbum]]):
         },
     )
 
+    tracks = [
+        {
+            "id": track_id(file_name, track),
+            "album_file_name": file_name,
+            "name": track.name,
+            "position": track.position if track.HasField("position") else None,
+            "rating": track.rating if track.HasField("rating") else None,
+            "duration_seconds": (
+                track.duration_seconds if track.HasField("duration_seconds") else None
+            ),
+            "artist_file_names": [artist.file_name for artist in track.artists],
+        }
+        for file_name, album in albums
+        for track in album.tracks
+    ]
+
+    gds.run_cypher(
+        """
+        UNWIND $tracks AS track
+        MATCH (album:Album {file_name: track.album_file_name})
+        MERGE (t:Track {id: track.id})
+        SET t.name = track.name,
+            t.position = track.position,
+            t.rating = track.rating,
+            t.duration_seconds = track.duration_seconds
+        MERGE (album)-[:HAS_TRACK]->(t)
+        WITH t, track
+        UNWIND track.artist_file_names AS artist_file_name
+        MATCH (artist:Artist {file_name: artist_file_name})
+        MERGE (artist)-[:TRACK_ARTIST]->(t)
+        """,
+        {"tracks": tracks},
+    )
+
     node_count = (
-        len(artists) + len(genres) + len(descriptors) + len(language) + len(albums)
+        len(artists)
+        + len(genres)
+        + len(descriptors)
+        + len(language)
+        + len(albums)
+        + len(tracks)
     )
     logger.info(
         "Graph updated",
@@ -252,6 +366,138 @@ def update_graph(albums: list[tuple[str, lute_pb2.ParsedAlbum]]):
     )
 
 
+def update_lists(segments: list[tuple[str, lute_pb2.ParsedListSegment]]):
+    """
+    Albums listed on a segment become members of its list, replacing the members the
+    segment had before. Albums that haven't been parsed yet are created with just their
+    file name, and filled in when they are. Duplicates count as their original.
+    """
+    gds.run_cypher(
+        """
+        UNWIND $segments AS segment
+        MERGE (l:List {file_name: segment.root_file_name})
+        SET l.name = segment.name
+        WITH l, segment
+        OPTIONAL MATCH (:Album)-[old:MEMBER_OF {segment: segment.file_name}]->(l)
+        DELETE old
+        WITH DISTINCT l, segment
+        UNWIND segment.albums AS album_file_name
+        OPTIONAL MATCH (d:DuplicateAlbum {file_name: album_file_name})
+        MERGE (album:Album {file_name: coalesce(d.duplicate_of, album_file_name)})
+        MERGE (album)-[:MEMBER_OF {segment: segment.file_name}]->(l)
+        """,
+        {
+            "segments": [
+                {
+                    "file_name": file_name,
+                    "root_file_name": list_root_file_name(file_name),
+                    "name": segment.name,
+                    "albums": list(segment.albums),
+                }
+                for file_name, segment in segments
+            ]
+        },
+    )
+    logger.info(
+        "Lists updated",
+        extra={
+            "props": {
+                "segment_count": len(segments),
+                "album_count": sum(len(segment.albums) for _, segment in segments),
+            }
+        },
+    )
+
+
+def update_list_statuses(statuses: list[tuple[str, str]]):
+    gds.run_cypher(
+        """
+        UNWIND $statuses AS status
+        MERGE (l:List {file_name: status.root_file_name})
+        SET l.lookup_status = status.status
+        """,
+        {
+            "statuses": [
+                {"root_file_name": root_file_name, "status": status}
+                for root_file_name, status in statuses
+            ]
+        },
+    )
+
+
+def delete_albums(file_names: list[str]):
+    """
+    Also drops the duplicate markers of the albums and of any merged into them, so those
+    come back as albums of their own if they're parsed again
+    """
+    delete_album_relationships(file_names)
+    gds.run_cypher(
+        """
+        UNWIND $file_names AS file_name
+        MATCH (a:Album {file_name: file_name})
+        DETACH DELETE a
+        """,
+        {"file_names": file_names},
+    )
+    gds.run_cypher(
+        """
+        UNWIND $file_names AS file_name
+        MATCH (d:DuplicateAlbum)
+        WHERE d.file_name = file_name OR d.duplicate_of = file_name
+        DELETE d
+        """,
+        {"file_names": file_names},
+    )
+    logger.info("Albums deleted", extra={"props": {"album_count": len(file_names)}})
+
+
+def merge_duplicates(duplicates: list[tuple[str, str]]):
+    """
+    Moves the list memberships of each duplicate onto its original and replaces the
+    duplicate's node with a marker, so later parses of the duplicate are ignored. An
+    original that used to be a duplicate itself loses its marker, and is filled in again
+    the next time it's parsed.
+    """
+    params = {
+        "duplicates": [
+            {"file_name": file_name, "duplicate_of": duplicate_of}
+            for file_name, duplicate_of in duplicates
+        ]
+    }
+    gds.run_cypher(
+        """
+        UNWIND $duplicates AS duplicate
+        MATCH (d:DuplicateAlbum {file_name: duplicate.duplicate_of})
+        DELETE d
+        """,
+        params,
+    )
+    gds.run_cypher(
+        """
+        UNWIND $duplicates AS duplicate
+        MATCH (:Album {file_name: duplicate.file_name})-[m:MEMBER_OF]->(l:List)
+        MERGE (original:Album {file_name: duplicate.duplicate_of})
+        MERGE (original)-[:MEMBER_OF {segment: coalesce(m.segment, l.file_name)}]->(l)
+        """,
+        params,
+    )
+    delete_album_relationships([file_name for file_name, _ in duplicates])
+    gds.run_cypher(
+        """
+        UNWIND $duplicates AS duplicate
+        MERGE (d:DuplicateAlbum {file_name: duplicate.file_name})
+        SET d.duplicate_of = duplicate.duplicate_of
+        WITH d
+        MATCH (a:Album {file_name: d.file_name})
+        DETACH DELETE a
+        """,
+        params,
+    )
+    logger.info(
+        "Duplicates merged", extra={"props": {"duplicate_count": len(duplicates)}}
+    )
+
+
 def generate_album_embeddings(
     embedding_key: str,
     weights: AlbumRelationWeights,
@@ -11,12 +11,16 @@ from graph.settings import LUTE_EVENT_SUBSCRIBER_PREFIX, LUTE_URL
 
 MAX_MESSAGE_LENGTH = 1024 * 1024 * 1024
 
+# Event schema version of the proto this connector is built against
+EVENT_SCHEMA_VERSION = 3
+
 
 class LuteClient:
     def __init__(self):
         self.channel: Optional[aio.Channel] = None
         self.album_service: Optional[lute_pb2_grpc.AlbumServiceStub] = None
         self.event_service: Optional[lute_pb2_grpc.EventServiceStub] = None
+        self.bootstrap_service: Optional[lute_pb2_grpc.BootstrapServiceStub] = None
 
     async def __aenter__(self):
         self.channel = aio.insecure_channel(
@@ -28,6 +32,7 @@ class LuteClient:
         )
         self.album_service = lute_pb2_grpc.AlbumServiceStub(self.channel)
         self.event_service = lute_pb2_grpc.EventServiceStub(self.channel)
+        self.bootstrap_service = lute_pb2_grpc.BootstrapServiceStub(self.channel)
         return self
 
     async def __aexit__(self, exc_type, exc_value, traceback):
@@ -54,8 +59,21 @@ class LuteClient:
 
         return None
 
+    async def bootstrap(self, batch_size=500) -> AsyncIterator[lute_pb2.BootstrapReply]:
+        if self.bootstrap_service is None:
+            raise ValueError("Client not initialized")
+
+        request = lute_pb2.BootstrapRequest(batch_size=batch_size)
+        async for reply in self.bootstrap_service.Bootstrap(request):
+            yield reply
+
     async def stream_events(
-        self, stream_id, subscriber_id, max_batch_size=250
+        self,
+        stream_id,
+        subscriber_id,
+        max_batch_size=250,
+        cursor=None,
+        event_types: Optional[list[str]] = None,
     ) -> AsyncIterator[list[lute_pb2.EventStreamItem]]:
         if self.event_service is None:
             raise ValueError("Client not initialized")
@@ -68,16 +86,23 @@ class LuteClient:
             yield lute_pb2.EventStreamRequest(
                 stream_id=stream_id,
                 subscriber_id=subscriber_id,
+                cursor=cursor,
                 max_batch_size=max_batch_size,
+                flatten=True,
+                schema_version=EVENT_SCHEMA_VERSION,
+                supported_event_types=event_types or [],
             )
 
             while True:
-                cursor = await queue.get()
+                next_cursor = await queue.get()
                 yield lute_pb2.EventStreamRequest(
                     stream_id=stream_id,
                     subscriber_id=subscriber_id,
-                    cursor=cursor,
+                    cursor=next_cursor,
                     max_batch_size=max_batch_size,
+                    flatten=True,
+                    schema_version=EVENT_SCHEMA_VERSION,
+                    supported_event_types=event_types or [],
                 )
                 await asyncio.sleep(0.25)
 
@@ -6,36 +6,135 @@ from graph.lute import LuteClient
 from graph.proto import lute_pb2
 
 
-def is_album_parsed_event(item: lute_pb2.EventStreamItem) -> bool:
-    return (
-        item.HasField("payload")
-        and item.payload.HasField("event")
-        and item.payload.event.HasField("file_parsed")
-        and item.payload.event.file_parsed.HasField("data")
-        and item.payload.event.file_parsed.data.HasField("album")
-    )
+def is_event_type(item: lute_pb2.EventStreamItem, event_type: str) -> bool:
+    return item.HasField("flat") and item.flat.event_type == event_type
+
+
+def is_list_segment_parsed_event(item: lute_pb2.EventStreamItem) -> bool:
+    data = item.payload.event.file_parsed.data
+    return is_event_type(item, "file_parsed") and data.HasField("list_segment")
+
+
+async def bootstrap_graph(client: LuteClient) -> str:
+    """
+    Loads a snapshot of every album into the graph, returning the cursor to stream
+    events after. Much faster than replaying the parser stream from the start.
+    """
+    album_count = 0
+    async for reply in client.bootstrap(500):
+        if reply.albums:
+            db.update_graph([(album.file_name, album) for album in reply.albums])
+            duplicates = [
+                (album.file_name, album.duplicate_of)
+                for album in reply.albums
+                if album.HasField("duplicate_of")
+            ]
+            if duplicates:
+                db.merge_duplicates(duplicates)
+            album_count += len(reply.albums)
+            logger.info(
+                "Stored snapshot albums", extra={"props": {"album_count": album_count}}
+            )
+        if reply.HasField("cursor"):
+            return reply.cursor
+
+    raise RuntimeError("Bootstrap ended without a cursor")
+
+
+async def sync_parsed_albums(client: LuteClient, cursor: str | None):
+    async for items in client.stream_events("parser", "build", 500, cursor):
+        logger.info("Received events", extra={"props": {"event_count": len(items)}})
+        parsed_albums = [
+            (item.flat.file_name, item.flat.album)
+            for item in items
+            if item.HasField("flat") and item.flat.HasField("album")
+        ]
+
+        if parsed_albums:
+            db.update_graph(parsed_albums)
+
+
+async def sync_album_changes(client: LuteClient, cursor: str | None):
+    async for items in client.stream_events(
+        "album",
+        "albums",
+        500,
+        cursor,
+        event_types=["album_deleted", "album_marked_duplicate"],
+    ):
+        deleted = [
+            item.flat.file_name
+            for item in items
+            if is_event_type(item, "album_deleted")
+        ]
+        duplicates = [
+            (
+                item.flat.file_name,
+                item.payload.event.album_marked_duplicate.duplicate_of,
+            )
+            for item in items
+            if is_event_type(item, "album_marked_duplicate")
+        ]
+
+        if deleted:
+            db.delete_albums(deleted)
+        if duplicates:
+            db.merge_duplicates(duplicates)
 
 
 async def run_graph_sync():
     async with LuteClient() as client:
-        async for items in client.stream_events("parser", "build", 500):
-            logger.info("Received events", extra={"props": {"event_count": len(items)}})
-            parsed_albums = [
+        cursor = None
+        album_cursor = None
+        if await client.get_subscriber_cursor("build") is None:
+            cursor = await bootstrap_graph(client)
+            # The snapshot already leaves out deleted albums and carries duplicates
+            if await client.get_subscriber_cursor("albums") is None:
+                album_cursor = cursor
+
+        await asyncio.gather(
+            sync_parsed_albums(client, cursor),
+            sync_album_changes(client, album_cursor),
+        )
+
+
+async def run_list_sync():
+    """
+    Lists aren't part of the bootstrap snapshot, so they're built by their own
+    subscriber reading the whole stream, limited to the event types lists come from
+    """
+    async with LuteClient() as client:
+        async for items in client.stream_events(
+            "all",
+            "lists",
+            500,
+            event_types=["file_parsed", "list_lookup_status_updated"],
+        ):
+            segments = [
+                (item.flat.file_name, item.payload.event.file_parsed.data.list_segment)
+                for item in items
+                if is_list_segment_parsed_event(item)
+            ]
+            statuses = [
                 (
-                    item.payload.event.file_parsed.file_name,
-                    item.payload.event.file_parsed.data.album,
+                    item.payload.event.list_lookup_status_updated.root_file_name,
+                    lute_pb2.ListLookupStatus.Name(
+                        item.payload.event.list_lookup_status_updated.status
+                    ),
                 )
                 for item in items
-                if is_album_parsed_event(item)
+                if is_event_type(item, "list_lookup_status_updated")
             ]
 
-            if parsed_albums:
-                db.update_graph(parsed_albums)
+            if segments:
+                db.update_lists(segments)
+            if statuses:
+                db.update_list_statuses(statuses)
 
 
 async def run():
     db.setup_indexes()
-    await asyncio.gather(api.run(), run_graph_sync())
+    await asyncio.gather(api.run(), run_graph_sync(), run_list_sync())
     db.disconnect()
 
 
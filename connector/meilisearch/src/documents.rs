@@ -0,0 +1,145 @@
+use crate::client::lute::ParsedAlbum;
+use serde::Serialize;
+use unidecode::unidecode;
+
+/**
+ * Meilisearch ids only allow ASCII letters, digits, hyphens and underscores, so file names are
+ * hex encoded
+ */
+pub fn document_id(file_name: &str) -> String {
+  file_name
+    .bytes()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AlbumDocument {
+  pub id: String,
+  pub file_name: String,
+  pub name: String,
+  /**
+   * Transliteration of the name, so Latin queries find albums titled in other scripts
+   */
+  pub ascii_name: String,
+  pub artist_names: Vec<String>,
+  pub artist_ascii_names: Vec<String>,
+  pub artist_file_names: Vec<String>,
+  pub primary_genres: Vec<String>,
+  pub secondary_genres: Vec<String>,
+  pub descriptors: Vec<String>,
+  pub languages: Vec<String>,
+  pub rating: f32,
+  pub rating_count: u32,
+  pub release_date: Option<String>,
+  pub release_year: Option<u16>,
+  pub is_various_artists: bool,
+  pub cover_image_url: Option<String>,
+}
+
+impl AlbumDocument {
+  pub fn new(file_name: &str, album: &ParsedAlbum) -> Self {
+    Self {
+      id: document_id(file_name),
+      file_name: file_name.to_string(),
+      name: album.name.clone(),
+      ascii_name: unidecode(&album.name),
+      artist_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.name.clone())
+        .collect(),
+      artist_ascii_names: album
+        .artists
+        .iter()
+        .map(|artist| unidecode(&artist.name))
+        .collect(),
+      artist_file_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+      primary_genres: album.primary_genres.clone(),
+      secondary_genres: album.secondary_genres.clone(),
+      descriptors: album.descriptors.clone(),
+      languages: album.languages.clone(),
+      rating: album.rating,
+      rating_count: album.rating_count,
+      release_date: album.release_date.clone(),
+      release_year: album
+        .release_date
+        .as_ref()
+        .and_then(|date| date.get(0..4))
+        .and_then(|year| year.parse().ok()),
+      is_various_artists: album.is_various_artists,
+      cover_image_url: album.cover_image_url.clone(),
+    }
+  }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ArtistDocument {
+  pub id: String,
+  pub file_name: String,
+  pub name: String,
+  pub ascii_name: String,
+}
+
+impl ArtistDocument {
+  /**
+   * Album artists and credited artists, once each
+   */
+  pub fn from_album(album: &ParsedAlbum) -> Vec<Self> {
+    let mut documents: Vec<Self> = vec![];
+    let artists = album.artists.iter().chain(
+      album
+        .credits
+        .iter()
+        .filter_map(|credit| credit.artist.as_ref()),
+    );
+    for artist in artists {
+      if documents
+        .iter()
+        .any(|document| document.file_name == artist.file_name)
+      {
+        continue;
+      }
+      documents.push(Self {
+        id: document_id(&artist.file_name),
+        file_name: artist.file_name.clone(),
+        name: artist.name.clone(),
+        ascii_name: unidecode(&artist.name),
+      });
+    }
+    documents
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::lute::{ParsedArtistReference, ParsedCredit};
+
+  #[test]
+  fn test_album_documents() {
+    assert_eq!(document_id("artist/a"), "6172746973742f61");
+    let artist = ParsedArtistReference {
+      name: "Пикник".to_string(),
+      file_name: "artist/piknik".to_string(),
+    };
+    let album = ParsedAlbum {
+      name: "Трип".to_string(),
+      artists: vec![artist.clone()],
+      credits: vec![ParsedCredit {
+        artist: Some(artist),
+        roles: vec!["Vocals".to_string()],
+      }],
+      release_date: Some("2001-05-01".to_string()),
+      ..Default::default()
+    };
+    let document = AlbumDocument::new("release/album/piknik/trip", &album);
+    assert_eq!(document.ascii_name, "Trip");
+    assert_eq!(document.release_year, Some(2001));
+    assert_eq!(ArtistDocument::from_album(&album).len(), 1);
+  }
+}
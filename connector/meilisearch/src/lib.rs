@@ -0,0 +1,3 @@
+pub mod client;
+pub mod documents;
+pub mod meilisearch;
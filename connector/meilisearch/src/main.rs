@@ -0,0 +1,305 @@
+use anyhow::{anyhow, Result};
+use clap::{arg, Parser};
+use lute_meilisearch_connector::{
+  client::lute::{
+    bootstrap_service_client::BootstrapServiceClient, event::Event,
+    event_service_client::EventServiceClient, parsed_file_data::Data, Album, AlbumArtist,
+    BootstrapRequest, EventStreamItem, EventStreamRequest, ParsedAlbum, ParsedArtistReference,
+    ParsedCredit, ParsedTrack,
+  },
+  documents::{document_id, AlbumDocument, ArtistDocument},
+  meilisearch::{Meilisearch, ALBUMS_INDEX, ARTISTS_INDEX},
+};
+use std::collections::HashMap;
+use tokio::sync::mpsc::unbounded_channel;
+
+/**
+ * Event schema version of the proto this connector is built against
+ */
+const EVENT_SCHEMA_VERSION: u32 = 2;
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 1024;
+
+/**
+ * Latest state of each file touched by a batch, None when it was deleted
+ */
+fn album_changes(batch: &[EventStreamItem]) -> HashMap<String, Option<ParsedAlbum>> {
+  let mut changes = HashMap::new();
+  for item in batch {
+    let event = item
+      .payload
+      .as_ref()
+      .and_then(|payload| payload.event.as_ref())
+      .and_then(|event| event.event.as_ref());
+    match event {
+      Some(Event::FileParsed(file_parsed_event)) => {
+        if let Some(Data::Album(parsed_album)) = file_parsed_event
+          .data
+          .as_ref()
+          .and_then(|data| data.data.as_ref())
+        {
+          changes.insert(
+            file_parsed_event.file_name.clone(),
+            Some(parsed_album.clone()),
+          );
+        }
+      }
+      Some(Event::FileDeleted(file_deleted_event)) => {
+        changes.insert(file_deleted_event.file_name.clone(), None);
+      }
+      _ => {}
+    }
+  }
+  changes
+}
+
+async fn index_albums(
+  meilisearch: &Meilisearch,
+  changes: HashMap<String, Option<ParsedAlbum>>,
+) -> Result<()> {
+  let mut albums = vec![];
+  let mut artists = vec![];
+  let mut deleted_ids = vec![];
+  for (file_name, album) in changes {
+    match album {
+      Some(album) => {
+        albums.push(AlbumDocument::new(&file_name, &album));
+        artists.extend(ArtistDocument::from_album(&album));
+      }
+      None => deleted_ids.push(document_id(&file_name)),
+    }
+  }
+  meilisearch.put_documents(ALBUMS_INDEX, &albums).await?;
+  meilisearch
+    .update_documents(ARTISTS_INDEX, &artists)
+    .await?;
+  // Deleted files are removed from both, since only their file name is known
+  meilisearch
+    .delete_documents(ALBUMS_INDEX, &deleted_ids)
+    .await?;
+  meilisearch
+    .delete_documents(ARTISTS_INDEX, &deleted_ids)
+    .await?;
+  Ok(())
+}
+
+fn artist_reference(artist: AlbumArtist) -> ParsedArtistReference {
+  ParsedArtistReference {
+    name: artist.name,
+    file_name: artist.file_name,
+  }
+}
+
+/**
+ * Snapshot albums carry the same fields as parsed ones, so they're indexed the same way
+ */
+fn snapshot_album(album: Album) -> (String, Option<ParsedAlbum>) {
+  (
+    album.file_name,
+    Some(ParsedAlbum {
+      name: album.name,
+      rating: album.rating,
+      rating_count: album.rating_count,
+      artists: album.artists.into_iter().map(artist_reference).collect(),
+      primary_genres: album.primary_genres,
+      secondary_genres: album.secondary_genres,
+      descriptors: album.descriptors,
+      tracks: album
+        .tracks
+        .into_iter()
+        .map(|track| ParsedTrack {
+          name: track.name,
+          duration_seconds: track.duration_seconds,
+          rating: track.rating,
+          position: track.position,
+          artists: track.artists.into_iter().map(artist_reference).collect(),
+        })
+        .collect(),
+      release_date: album.release_date,
+      languages: album.languages,
+      credits: album
+        .credits
+        .into_iter()
+        .map(|credit| ParsedCredit {
+          artist: credit.artist.map(artist_reference),
+          roles: credit.roles,
+        })
+        .collect(),
+      cover_image_url: album.cover_image_url,
+      spotify_id: album.spotify_id,
+      is_various_artists: album.is_various_artists,
+    }),
+  )
+}
+
+fn authorized<T>(message: T, api_key: &Option<String>) -> Result<tonic::Request<T>> {
+  let mut request = tonic::Request::new(message);
+  if let Some(api_key) = api_key {
+    request.metadata_mut().insert("x-api-key", api_key.parse()?);
+  }
+  Ok(request)
+}
+
+async fn get_subscriber_cursor(
+  subscriber_id: &str,
+  api_key: &Option<String>,
+  client: &mut EventServiceClient<tonic::transport::Channel>,
+) -> Result<Option<String>> {
+  let monitor = client
+    .get_monitor(authorized((), api_key)?)
+    .await?
+    .into_inner()
+    .monitor;
+  Ok(
+    monitor
+      .into_iter()
+      .flat_map(|monitor| monitor.subscribers)
+      .find(|subscriber| subscriber.id == subscriber_id)
+      .map(|subscriber| subscriber.cursor),
+  )
+}
+
+/**
+ * Indexes a snapshot of every album, returning the cursor to stream events after. Much faster
+ * than replaying the stream from the start for a fresh index.
+ */
+async fn bootstrap(
+  api_key: &Option<String>,
+  client: &mut BootstrapServiceClient<tonic::transport::Channel>,
+  meilisearch: &Meilisearch,
+) -> Result<String> {
+  let mut snapshot = client
+    .bootstrap(authorized(
+      BootstrapRequest {
+        batch_size: Some(1000),
+      },
+      api_key,
+    )?)
+    .await?
+    .into_inner();
+
+  let mut album_count = 0;
+  while let Some(reply) = snapshot.message().await? {
+    if !reply.albums.is_empty() {
+      album_count += reply.albums.len();
+      index_albums(
+        meilisearch,
+        reply.albums.into_iter().map(snapshot_album).collect(),
+      )
+      .await?;
+      println!("Indexed {} snapshot albums", album_count);
+    }
+    if let Some(cursor) = reply.cursor {
+      return Ok(cursor);
+    }
+  }
+
+  Err(anyhow!("Bootstrap ended without a cursor"))
+}
+
+fn event_stream_request(
+  stream_id: &str,
+  subscriber_id: &str,
+  cursor: Option<String>,
+) -> EventStreamRequest {
+  EventStreamRequest {
+    stream_id: stream_id.to_string(),
+    subscriber_id: subscriber_id.to_string(),
+    cursor,
+    max_batch_size: Some(500),
+    flatten: false,
+    schema_version: Some(EVENT_SCHEMA_VERSION),
+    supported_event_types: vec!["file_parsed".to_string(), "file_deleted".to_string()],
+  }
+}
+
+async fn subscribe(
+  args: &Args,
+  cursor: Option<String>,
+  client: &mut EventServiceClient<tonic::transport::Channel>,
+  meilisearch: &Meilisearch,
+) -> Result<()> {
+  let (cursor_sender, mut cursor_receiver) = unbounded_channel::<String>();
+  let stream_id = args.stream_id.clone();
+  let subscriber_id = args.subscriber_id.clone();
+  let request_stream = async_stream::stream! {
+    yield event_stream_request(&stream_id, &subscriber_id, cursor);
+
+    while let Some(cursor) = cursor_receiver.recv().await {
+      println!("Requesting batch with cursor: {}", cursor);
+      yield event_stream_request(&stream_id, &subscriber_id, Some(cursor));
+    }
+  };
+
+  let response = client
+    .stream(authorized(request_stream, &args.api_key)?)
+    .await?;
+  let mut event_stream = response.into_inner();
+
+  while let Some(reply) = event_stream.message().await? {
+    index_albums(meilisearch, album_changes(&reply.items)).await?;
+    cursor_sender.send(reply.cursor)?;
+  }
+
+  Ok(())
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+  #[arg(long, default_value = "grpc://localhost:22000")]
+  lute_url: String,
+
+  #[arg(long, default_value = "parser")]
+  stream_id: String,
+
+  #[arg(long)]
+  subscriber_id: String,
+
+  #[arg(long, default_value = "http://localhost:27700")]
+  meilisearch_url: String,
+
+  #[arg(long)]
+  meilisearch_api_key: Option<String>,
+
+  /// Needed when the lute instance has auth enabled, with the connector replication scope
+  #[arg(long)]
+  api_key: Option<String>,
+
+  /// Replay the stream from the start instead of indexing a snapshot when the subscriber is new
+  #[arg(long, default_value_t = false)]
+  skip_bootstrap: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+  let args = Args::parse();
+  let meilisearch = Meilisearch::new(
+    args.meilisearch_url.clone(),
+    args.meilisearch_api_key.clone(),
+  );
+  meilisearch
+    .setup_indexes()
+    .await
+    .expect("Failed to set up indexes");
+
+  let channel = tonic::transport::Endpoint::from_shared(args.lute_url.clone())?
+    .connect()
+    .await
+    .expect("Failed to connect to lute instance");
+  let mut client = EventServiceClient::new(channel.clone());
+
+  // A subscriber without a cursor hasn't synced yet, so it starts from a snapshot
+  let cursor = match get_subscriber_cursor(&args.subscriber_id, &args.api_key, &mut client).await? {
+    Some(_) => None,
+    None if args.skip_bootstrap => None,
+    None => {
+      let mut bootstrap_client =
+        BootstrapServiceClient::new(channel).max_decoding_message_size(MAX_MESSAGE_SIZE);
+      Some(bootstrap(&args.api_key, &mut bootstrap_client, &meilisearch).await?)
+    }
+  };
+
+  subscribe(&args, cursor, &mut client, &meilisearch).await?;
+
+  Ok(())
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+pub const ALBUMS_INDEX: &str = "albums";
+pub const ARTISTS_INDEX: &str = "artists";
+
+fn albums_settings() -> Value {
+  json!({
+    "searchableAttributes": [
+      "name",
+      "ascii_name",
+      "artist_names",
+      "artist_ascii_names",
+      "primary_genres",
+      "secondary_genres",
+      "descriptors"
+    ],
+    "filterableAttributes": [
+      "artist_file_names",
+      "primary_genres",
+      "secondary_genres",
+      "descriptors",
+      "languages",
+      "release_year",
+      "rating",
+      "rating_count",
+      "is_various_artists"
+    ],
+    "sortableAttributes": ["rating", "rating_count", "release_date"],
+    "rankingRules": [
+      "words",
+      "typo",
+      "proximity",
+      "attribute",
+      "sort",
+      "exactness",
+      "rating_count:desc"
+    ],
+    "typoTolerance": {
+      "enabled": true,
+      "minWordSizeForTypos": { "oneTypo": 4, "twoTypos": 8 },
+      "disableOnAttributes": ["descriptors"]
+    },
+    "faceting": {
+      "maxValuesPerFacet": 500,
+      "sortFacetValuesBy": { "*": "count" }
+    }
+  })
+}
+
+fn artists_settings() -> Value {
+  json!({
+    "searchableAttributes": ["name", "ascii_name"],
+    "typoTolerance": {
+      "enabled": true,
+      "minWordSizeForTypos": { "oneTypo": 4, "twoTypos": 8 }
+    }
+  })
+}
+
+/**
+ * The bits of the Meilisearch HTTP API the connector needs. Writes are queued as tasks that
+ * Meilisearch applies in order, so they aren't waited on.
+ */
+pub struct Meilisearch {
+  client: Client,
+  url: String,
+  api_key: Option<String>,
+}
+
+impl Meilisearch {
+  pub fn new(url: String, api_key: Option<String>) -> Self {
+    Self {
+      client: Client::new(),
+      url: url.trim_end_matches('/').to_string(),
+      api_key,
+    }
+  }
+
+  fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+    match &self.api_key {
+      Some(api_key) => request.bearer_auth(api_key),
+      None => request,
+    }
+  }
+
+  /**
+   * Creates the indexes if needed and applies their settings
+   */
+  pub async fn setup_indexes(&self) -> Result<()> {
+    for (index, settings) in [
+      (ALBUMS_INDEX, albums_settings()),
+      (ARTISTS_INDEX, artists_settings()),
+    ] {
+      self
+        .authorized(
+          self
+            .client
+            .patch(format!("{}/indexes/{}/settings", self.url, index))
+            .json(&settings),
+        )
+        .send()
+        .await?
+        .error_for_status()?;
+    }
+    Ok(())
+  }
+
+  pub async fn put_documents<T: Serialize>(&self, index: &str, documents: &[T]) -> Result<()> {
+    if documents.is_empty() {
+      return Ok(());
+    }
+    self
+      .authorized(
+        self
+          .client
+          .post(format!("{}/indexes/{}/documents", self.url, index))
+          .query(&[("primaryKey", "id")])
+          .json(documents),
+      )
+      .send()
+      .await?
+      .error_for_status()?;
+    Ok(())
+  }
+
+  /**
+   * Adds the documents or updates the fields they carry, leaving the rest of a stored document
+   * as it was
+   */
+  pub async fn update_documents<T: Serialize>(&self, index: &str, documents: &[T]) -> Result<()> {
+    if documents.is_empty() {
+      return Ok(());
+    }
+    self
+      .authorized(
+        self
+          .client
+          .put(format!("{}/indexes/{}/documents", self.url, index))
+          .query(&[("primaryKey", "id")])
+          .json(documents),
+      )
+      .send()
+      .await?
+      .error_for_status()?;
+    Ok(())
+  }
+
+  pub async fn delete_documents(&self, index: &str, ids: &[String]) -> Result<()> {
+    if ids.is_empty() {
+      return Ok(());
+    }
+    self
+      .authorized(
+        self
+          .client
+          .post(format!(
+            "{}/indexes/{}/documents/delete-batch",
+            self.url, index
+          ))
+          .json(ids),
+      )
+      .send()
+      .await?
+      .error_for_status()?;
+    Ok(())
+  }
+}
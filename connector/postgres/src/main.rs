@@ -1,12 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
 use clap::{arg, Parser};
 use diesel::{upsert::excluded, Connection, ExpressionMethods, PgConnection, RunQueryDsl};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use lute_postgres_connector::{
   client::lute::{
-    event::Event, event_service_client::EventServiceClient, parsed_file_data::Data,
-    EventStreamItem, EventStreamRequest,
+    bootstrap_service_client::BootstrapServiceClient, event_service_client::EventServiceClient,
+    Album, AlbumArtist, BootstrapRequest, EventStreamItem, EventStreamRequest, FlatParsedAlbum,
+    ParsedArtistReference, ParsedCredit, ParsedTrack,
   },
   models::*,
 };
@@ -15,6 +16,13 @@ use tokio::sync::mpsc::unbounded_channel;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/**
+ * Event schema version of the proto this connector is built against
+ */
+const EVENT_SCHEMA_VERSION: u32 = 2;
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 1024;
+
 fn run_migrations(
   connection: &mut PgConnection,
 ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
@@ -51,9 +59,71 @@ async fn store_lute_events(
   Ok(())
 }
 
+fn parsed_albums(batch: &Vec<EventStreamItem>) -> Vec<(String, FlatParsedAlbum)> {
+  batch
+    .iter()
+    .filter_map(|item| {
+      let flat = item.flat.as_ref()?;
+      Some((flat.file_name.clone()?, flat.album.clone()?))
+    })
+    .collect()
+}
+
+fn artist_reference(artist: AlbumArtist) -> ParsedArtistReference {
+  ParsedArtistReference {
+    name: artist.name,
+    file_name: artist.file_name,
+  }
+}
+
+/**
+ * Snapshot albums carry the same fields as parsed ones, so they're stored the same way
+ */
+fn snapshot_album(album: Album) -> (String, FlatParsedAlbum) {
+  (
+    album.file_name,
+    FlatParsedAlbum {
+      name: album.name,
+      rating: album.rating,
+      rating_count: album.rating_count,
+      artist_file_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+      artists: album.artists.into_iter().map(artist_reference).collect(),
+      primary_genres: album.primary_genres,
+      secondary_genres: album.secondary_genres,
+      descriptors: album.descriptors,
+      tracks: album
+        .tracks
+        .into_iter()
+        .map(|track| ParsedTrack {
+          name: track.name,
+          duration_seconds: track.duration_seconds,
+          rating: track.rating,
+          position: track.position,
+          artists: track.artists.into_iter().map(artist_reference).collect(),
+        })
+        .collect(),
+      release_date: album.release_date,
+      languages: album.languages,
+      credits: album
+        .credits
+        .into_iter()
+        .map(|credit| ParsedCredit {
+          artist: credit.artist.map(artist_reference),
+          roles: credit.roles,
+        })
+        .collect(),
+      is_various_artists: album.is_various_artists,
+    },
+  )
+}
+
 async fn store_albums(
   db_connection: &mut PgConnection,
-  batch: &Vec<EventStreamItem>,
+  albums: &Vec<(String, FlatParsedAlbum)>,
 ) -> Result<()> {
   let mut new_albums_map = HashMap::<String, LuteAlbum>::new();
   let mut new_artists_map = HashMap::<String, LuteArtist>::new();
@@ -61,129 +131,104 @@ async fn store_albums(
   let mut new_tracks_map = HashMap::<String, Vec<LuteTrack>>::new();
   let mut new_credits_map = HashMap::<String, Vec<LuteCredit>>::new();
 
-  for item in batch {
-    let payload = item.payload.as_ref();
-    if payload.is_none() {
-      continue;
-    }
-    let payload = payload.unwrap();
-    let event = payload.event.as_ref();
-    if event.is_none() {
-      continue;
-    }
-    let event = event.unwrap().event.as_ref().unwrap();
-
-    if let Event::FileParsed(file_parsed_event) = event {
-      let parsed_file_data = file_parsed_event.data.as_ref();
-      if parsed_file_data.is_none() {
-        continue;
-      }
-      let data = parsed_file_data.unwrap().data.as_ref();
-      if data.is_none() {
-        continue;
-      }
-      let data = data.unwrap();
-
-      if let Data::Album(parsed_album) = data {
-        let new_album = LuteAlbum {
-          file_name: file_parsed_event.file_name.clone(),
-          name: parsed_album.name.clone(),
-          rating: parsed_album.rating as f64,
-          rating_count: parsed_album.rating_count as i32,
-          primary_genres: parsed_album
-            .primary_genres
-            .iter()
-            .map(|g| Some(g.clone()))
-            .collect::<Vec<_>>(),
-          secondary_genres: parsed_album
-            .secondary_genres
-            .iter()
-            .map(|g| Some(g.clone()))
-            .collect::<Vec<_>>(),
-          descriptors: parsed_album
-            .descriptors
-            .iter()
-            .map(|g| Some(g.clone()))
-            .collect::<Vec<_>>(),
-          languages: parsed_album
-            .languages
-            .iter()
-            .map(|g| Some(g.clone()))
-            .collect::<Vec<_>>(),
-          release_date: parsed_album
-            .release_date
-            .clone()
-            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
-        };
-        let new_tracks = parsed_album
-          .tracks
-          .iter()
-          .map(|track| LuteTrack {
-            album_file_name: file_parsed_event.file_name.clone(),
-            name: track.name.clone(),
-            duration_seconds: track.duration_seconds.map(|d| d as i32),
-            rating: track.rating.map(|r| r as f64),
-            position: track.position.clone(),
-          })
-          .collect::<Vec<LuteTrack>>();
-        let new_album_artists = parsed_album
-          .artists
-          .iter()
-          .map(|artist| LuteAlbumArtist {
-            album_file_name: file_parsed_event.file_name.clone(),
-            artist_file_name: artist.file_name.clone(),
-          })
-          .collect::<Vec<LuteAlbumArtist>>();
-        let new_credits = parsed_album
-          .credits
+  for (album_file_name, parsed_album) in albums {
+    let new_album = LuteAlbum {
+      file_name: album_file_name.clone(),
+      name: parsed_album.name.clone(),
+      rating: parsed_album.rating as f64,
+      rating_count: parsed_album.rating_count as i32,
+      primary_genres: parsed_album
+        .primary_genres
+        .iter()
+        .map(|g| Some(g.clone()))
+        .collect::<Vec<_>>(),
+      secondary_genres: parsed_album
+        .secondary_genres
+        .iter()
+        .map(|g| Some(g.clone()))
+        .collect::<Vec<_>>(),
+      descriptors: parsed_album
+        .descriptors
+        .iter()
+        .map(|g| Some(g.clone()))
+        .collect::<Vec<_>>(),
+      languages: parsed_album
+        .languages
+        .iter()
+        .map(|g| Some(g.clone()))
+        .collect::<Vec<_>>(),
+      release_date: parsed_album
+        .release_date
+        .clone()
+        .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+    };
+    let new_tracks = parsed_album
+      .tracks
+      .iter()
+      .map(|track| LuteTrack {
+        album_file_name: album_file_name.clone(),
+        name: track.name.clone(),
+        duration_seconds: track.duration_seconds.map(|d| d as i32),
+        rating: track.rating.map(|r| r as f64),
+        position: track.position.clone(),
+      })
+      .collect::<Vec<LuteTrack>>();
+    let new_album_artists = parsed_album
+      .artists
+      .iter()
+      .map(|artist| LuteAlbumArtist {
+        album_file_name: album_file_name.clone(),
+        artist_file_name: artist.file_name.clone(),
+      })
+      .collect::<Vec<LuteAlbumArtist>>();
+    let new_credits = parsed_album
+      .credits
+      .iter()
+      .map(|parsed_credit| LuteCredit {
+        album_file_name: album_file_name.clone(),
+        artist_file_name: parsed_credit.artist.as_ref().unwrap().file_name.clone(),
+        roles: parsed_credit
+          .roles
           .iter()
-          .map(|parsed_credit| LuteCredit {
-            album_file_name: file_parsed_event.file_name.clone(),
-            artist_file_name: parsed_credit.artist.as_ref().unwrap().file_name.clone(),
-            roles: parsed_credit
-              .roles
-              .iter()
-              .map(|r| Some(r.clone()))
-              .collect::<Vec<_>>(),
-          })
-          .collect::<Vec<LuteCredit>>();
-        let mut new_artists = parsed_album
-          .artists
-          .iter()
-          .map(|artist| {
-            (
-              artist.file_name.clone(),
-              LuteArtist {
-                file_name: artist.file_name.clone(),
-                name: artist.name.clone(),
-              },
-            )
-          })
-          .collect::<HashMap<String, LuteArtist>>();
-        new_artists.extend(
-          parsed_album
-            .credits
-            .iter()
-            .map(|parsed_credit| {
-              let artist = parsed_credit.artist.as_ref().unwrap();
-              (
-                artist.file_name.clone(),
-                LuteArtist {
-                  file_name: artist.file_name.clone(),
-                  name: artist.name.clone(),
-                },
-              )
-            })
-            .collect::<HashMap<String, LuteArtist>>(),
-        );
-
-        new_albums_map.insert(file_parsed_event.file_name.clone(), new_album);
-        new_artists_map.extend(new_artists);
-        new_album_artists_map.insert(file_parsed_event.file_name.clone(), new_album_artists);
-        new_tracks_map.insert(file_parsed_event.file_name.clone(), new_tracks);
-        new_credits_map.insert(file_parsed_event.file_name.clone(), new_credits);
-      }
-    }
+          .map(|r| Some(r.clone()))
+          .collect::<Vec<_>>(),
+      })
+      .collect::<Vec<LuteCredit>>();
+    let mut new_artists = parsed_album
+      .artists
+      .iter()
+      .map(|artist| {
+        (
+          artist.file_name.clone(),
+          LuteArtist {
+            file_name: artist.file_name.clone(),
+            name: artist.name.clone(),
+          },
+        )
+      })
+      .collect::<HashMap<String, LuteArtist>>();
+    new_artists.extend(
+      parsed_album
+        .credits
+        .iter()
+        .map(|parsed_credit| {
+          let artist = parsed_credit.artist.as_ref().unwrap();
+          (
+            artist.file_name.clone(),
+            LuteArtist {
+              file_name: artist.file_name.clone(),
+              name: artist.name.clone(),
+            },
+          )
+        })
+        .collect::<HashMap<String, LuteArtist>>(),
+    );
+
+    new_albums_map.insert(album_file_name.clone(), new_album);
+    new_artists_map.extend(new_artists);
+    new_album_artists_map.insert(album_file_name.clone(), new_album_artists);
+    new_tracks_map.insert(album_file_name.clone(), new_tracks);
+    new_credits_map.insert(album_file_name.clone(), new_credits);
   }
 
   db_connection.transaction(|trx| {
@@ -283,7 +328,7 @@ async fn process_batch(
   batch: Vec<EventStreamItem>,
 ) -> Result<()> {
   store_lute_events(db_connection, &batch).await?;
-  store_albums(db_connection, &batch).await?;
+  store_albums(db_connection, &parsed_albums(&batch)).await?;
   Ok(())
 }
 
@@ -297,18 +342,85 @@ fn event_stream_request(
     subscriber_id: subscriber_id.to_string(),
     cursor,
     max_batch_size: Some(100),
+    flatten: true,
+    schema_version: Some(EVENT_SCHEMA_VERSION),
+    supported_event_types: vec![],
   }
 }
 
+fn authorized<T>(message: T, api_key: &Option<String>) -> Result<tonic::Request<T>> {
+  let mut request = tonic::Request::new(message);
+  if let Some(api_key) = api_key {
+    request.metadata_mut().insert("x-api-key", api_key.parse()?);
+  }
+  Ok(request)
+}
+
+async fn get_subscriber_cursor(
+  subscriber_id: &str,
+  api_key: &Option<String>,
+  client: &mut EventServiceClient<tonic::transport::Channel>,
+) -> Result<Option<String>> {
+  let monitor = client
+    .get_monitor(authorized((), api_key)?)
+    .await?
+    .into_inner()
+    .monitor;
+  Ok(
+    monitor
+      .into_iter()
+      .flat_map(|monitor| monitor.subscribers)
+      .find(|subscriber| subscriber.id == subscriber_id)
+      .map(|subscriber| subscriber.cursor),
+  )
+}
+
+/**
+ * Stores a snapshot of every album, returning the cursor to stream events after. Much faster
+ * than replaying the stream from the start for a fresh database.
+ */
+async fn bootstrap(
+  api_key: &Option<String>,
+  client: &mut BootstrapServiceClient<tonic::transport::Channel>,
+  db_connection: &mut PgConnection,
+) -> Result<String> {
+  let mut snapshot = client
+    .bootstrap(authorized(
+      BootstrapRequest {
+        batch_size: Some(500),
+      },
+      api_key,
+    )?)
+    .await?
+    .into_inner();
+
+  let mut album_count = 0;
+  while let Some(reply) = snapshot.message().await? {
+    if !reply.albums.is_empty() {
+      album_count += reply.albums.len();
+      let albums = reply.albums.into_iter().map(snapshot_album).collect();
+      store_albums(db_connection, &albums).await?;
+      println!("Stored {} snapshot albums", album_count);
+    }
+    if let Some(cursor) = reply.cursor {
+      return Ok(cursor);
+    }
+  }
+
+  Err(anyhow!("Bootstrap ended without a cursor"))
+}
+
 async fn subscribe(
   stream_id: String,
   subscriber_id: String,
+  cursor: Option<String>,
+  api_key: Option<String>,
   client: &mut EventServiceClient<tonic::transport::Channel>,
   db_connection: &mut PgConnection,
 ) -> Result<()> {
   let (cursor_sender, mut cursor_receiver) = unbounded_channel::<String>();
   let request_stream = async_stream::stream! {
-    yield event_stream_request(&stream_id, &subscriber_id, None);
+    yield event_stream_request(&stream_id, &subscriber_id, cursor);
 
     while let Some(cursor) = cursor_receiver.recv().await {
       println!("Requesting batch with cursor: {}", cursor);
@@ -316,7 +428,7 @@ async fn subscribe(
     }
   };
 
-  let response = client.stream(request_stream).await?;
+  let response = client.stream(authorized(request_stream, &api_key)?).await?;
   let mut event_stream = response.into_inner();
 
   while let Some(reply) = event_stream.message().await? {
@@ -340,6 +452,14 @@ struct Args {
 
   #[arg(long)]
   postgres_url: String,
+
+  /// Needed when the lute instance has auth enabled, with the connector replication scope
+  #[arg(long)]
+  api_key: Option<String>,
+
+  /// Replay the stream from the start instead of loading a snapshot when the subscriber is new
+  #[arg(long, default_value_t = false)]
+  skip_bootstrap: bool,
 }
 
 #[tokio::main]
@@ -348,13 +468,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
   let mut connection = establish_connection(&args.postgres_url);
   run_migrations(&mut connection).expect("Failed to run migrations");
 
-  let mut client = EventServiceClient::connect(args.lute_url)
+  let channel = tonic::transport::Endpoint::from_shared(args.lute_url)?
+    .connect()
     .await
     .expect("Failed to connect to lute instance");
+  let mut client = EventServiceClient::new(channel.clone());
+
+  // A subscriber without a cursor hasn't synced yet, so it starts from a snapshot
+  let cursor = match get_subscriber_cursor(&args.subscriber_id, &args.api_key, &mut client).await? {
+    Some(_) => None,
+    None if args.skip_bootstrap => None,
+    None => {
+      let mut bootstrap_client =
+        BootstrapServiceClient::new(channel).max_decoding_message_size(MAX_MESSAGE_SIZE);
+      Some(bootstrap(&args.api_key, &mut bootstrap_client, &mut connection).await?)
+    }
+  };
 
   subscribe(
     args.stream_id,
     args.subscriber_id,
+    cursor,
+    args.api_key,
     &mut client,
     &mut connection,
   )
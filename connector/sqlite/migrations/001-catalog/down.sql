@@ -0,0 +1,12 @@
+DROP TABLE cursors;
+DROP TABLE album_languages;
+DROP TABLE languages;
+DROP TABLE album_descriptors;
+DROP TABLE descriptors;
+DROP TABLE album_genres;
+DROP TABLE genres;
+DROP TABLE credits;
+DROP TABLE tracks;
+DROP TABLE album_artists;
+DROP TABLE albums;
+DROP TABLE artists;
@@ -0,0 +1,82 @@
+CREATE TABLE artists (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  file_name TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL
+);
+CREATE TABLE albums (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  file_name TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  rating REAL NOT NULL,
+  rating_count INTEGER NOT NULL,
+  release_date TEXT,
+  is_various_artists BOOLEAN NOT NULL DEFAULT 0,
+  cover_image_url TEXT,
+  spotify_id TEXT,
+  updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE album_artists (
+  album_id INTEGER NOT NULL,
+  artist_id INTEGER NOT NULL,
+  PRIMARY KEY (album_id, artist_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE
+);
+CREATE TABLE tracks (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  album_id INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  position TEXT,
+  duration_seconds INTEGER,
+  rating REAL,
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_tracks_album_id ON tracks(album_id);
+CREATE TABLE credits (
+  album_id INTEGER NOT NULL,
+  artist_id INTEGER NOT NULL,
+  role TEXT NOT NULL,
+  PRIMARY KEY (album_id, artist_id, role),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE
+);
+CREATE TABLE genres (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE album_genres (
+  album_id INTEGER NOT NULL,
+  genre_id INTEGER NOT NULL,
+  is_primary BOOLEAN NOT NULL,
+  PRIMARY KEY (album_id, genre_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (genre_id) REFERENCES genres(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_album_genres_genre_id ON album_genres(genre_id);
+CREATE TABLE descriptors (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE album_descriptors (
+  album_id INTEGER NOT NULL,
+  descriptor_id INTEGER NOT NULL,
+  PRIMARY KEY (album_id, descriptor_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (descriptor_id) REFERENCES descriptors(id) ON DELETE CASCADE
+);
+CREATE TABLE languages (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE album_languages (
+  album_id INTEGER NOT NULL,
+  language_id INTEGER NOT NULL,
+  PRIMARY KEY (album_id, language_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (language_id) REFERENCES languages(id) ON DELETE CASCADE
+);
+CREATE TABLE cursors (
+  subscriber_id TEXT PRIMARY KEY,
+  cursor TEXT NOT NULL,
+  updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
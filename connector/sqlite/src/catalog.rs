@@ -0,0 +1,280 @@
+use crate::client::lute::{ParsedAlbum, ParsedArtistReference};
+use anyhow::Result;
+use include_dir::{include_dir, Dir};
+use lazy_static::lazy_static;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use rusqlite_migration::Migrations;
+use std::path::Path;
+
+static MIGRATIONS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/migrations");
+
+lazy_static! {
+  static ref MIGRATIONS: Migrations<'static> = Migrations::from_directory(&MIGRATIONS_DIR).unwrap();
+}
+
+/**
+ * A change to the catalog taken from the replication stream
+ */
+#[derive(Debug, Clone)]
+pub enum CatalogChange {
+  PutAlbum {
+    file_name: String,
+    album: ParsedAlbum,
+  },
+  DeleteAlbum {
+    file_name: String,
+  },
+}
+
+fn put_named(tx: &Transaction, table: &str, name: &str) -> Result<i64> {
+  Ok(tx.query_row(
+    &format!(
+      "INSERT INTO {table} (name) VALUES (?1)
+      ON CONFLICT (name) DO UPDATE SET name = excluded.name
+      RETURNING id"
+    ),
+    params![name],
+    |row| row.get(0),
+  )?)
+}
+
+fn put_artist(tx: &Transaction, artist: &ParsedArtistReference) -> Result<i64> {
+  Ok(tx.query_row(
+    "INSERT INTO artists (file_name, name) VALUES (?1, ?2)
+    ON CONFLICT (file_name) DO UPDATE SET name = excluded.name
+    RETURNING id",
+    params![artist.file_name, artist.name],
+    |row| row.get(0),
+  )?)
+}
+
+/**
+ * Upserts the album and replaces everything hanging off it, so the file always holds the album
+ * as it was last parsed
+ */
+fn put_album(tx: &Transaction, file_name: &str, album: &ParsedAlbum) -> Result<()> {
+  let album_id: i64 = tx.query_row(
+    "INSERT INTO albums (
+      file_name, name, rating, rating_count, release_date, is_various_artists, cover_image_url,
+      spotify_id
+    )
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+    ON CONFLICT (file_name) DO UPDATE SET
+      name = excluded.name,
+      rating = excluded.rating,
+      rating_count = excluded.rating_count,
+      release_date = excluded.release_date,
+      is_various_artists = excluded.is_various_artists,
+      cover_image_url = excluded.cover_image_url,
+      spotify_id = excluded.spotify_id,
+      updated_at = CURRENT_TIMESTAMP
+    RETURNING id",
+    params![
+      file_name,
+      album.name,
+      album.rating,
+      album.rating_count,
+      album.release_date,
+      album.is_various_artists,
+      album.cover_image_url,
+      album.spotify_id,
+    ],
+    |row| row.get(0),
+  )?;
+  for table in [
+    "album_artists",
+    "tracks",
+    "credits",
+    "album_genres",
+    "album_descriptors",
+    "album_languages",
+  ] {
+    tx.execute(
+      &format!("DELETE FROM {table} WHERE album_id = ?1"),
+      params![album_id],
+    )?;
+  }
+
+  for artist in album.artists.iter() {
+    let artist_id = put_artist(tx, artist)?;
+    tx.execute(
+      "INSERT OR IGNORE INTO album_artists (album_id, artist_id) VALUES (?1, ?2)",
+      params![album_id, artist_id],
+    )?;
+  }
+  for track in album.tracks.iter() {
+    tx.execute(
+      "INSERT INTO tracks (album_id, name, position, duration_seconds, rating)
+      VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![
+        album_id,
+        track.name,
+        track.position,
+        track.duration_seconds,
+        track.rating
+      ],
+    )?;
+  }
+  for credit in album.credits.iter() {
+    let Some(artist) = credit.artist.as_ref() else {
+      continue;
+    };
+    let artist_id = put_artist(tx, artist)?;
+    for role in credit.roles.iter() {
+      tx.execute(
+        "INSERT OR IGNORE INTO credits (album_id, artist_id, role) VALUES (?1, ?2, ?3)",
+        params![album_id, artist_id, role],
+      )?;
+    }
+  }
+  let genres = album
+    .primary_genres
+    .iter()
+    .map(|genre| (genre, true))
+    .chain(album.secondary_genres.iter().map(|genre| (genre, false)));
+  for (genre, is_primary) in genres {
+    let genre_id = put_named(tx, "genres", genre)?;
+    tx.execute(
+      "INSERT OR IGNORE INTO album_genres (album_id, genre_id, is_primary) VALUES (?1, ?2, ?3)",
+      params![album_id, genre_id, is_primary],
+    )?;
+  }
+  for descriptor in album.descriptors.iter() {
+    let descriptor_id = put_named(tx, "descriptors", descriptor)?;
+    tx.execute(
+      "INSERT OR IGNORE INTO album_descriptors (album_id, descriptor_id) VALUES (?1, ?2)",
+      params![album_id, descriptor_id],
+    )?;
+  }
+  for language in album.languages.iter() {
+    let language_id = put_named(tx, "languages", language)?;
+    tx.execute(
+      "INSERT OR IGNORE INTO album_languages (album_id, language_id) VALUES (?1, ?2)",
+      params![album_id, language_id],
+    )?;
+  }
+  Ok(())
+}
+
+/**
+ * A standalone SQLite file holding the catalog, with the cursor it was synced to
+ */
+pub struct Catalog {
+  connection: Connection,
+}
+
+impl Catalog {
+  pub fn open(path: &Path) -> Result<Self> {
+    let mut connection = Connection::open(path)?;
+    connection.pragma_update(None, "journal_mode", "WAL")?;
+    connection.pragma_update(None, "foreign_keys", "ON")?;
+    MIGRATIONS.to_latest(&mut connection)?;
+    Ok(Self { connection })
+  }
+
+  pub fn get_cursor(&self, subscriber_id: &str) -> Result<Option<String>> {
+    Ok(
+      self
+        .connection
+        .query_row(
+          "SELECT cursor FROM cursors WHERE subscriber_id = ?1",
+          params![subscriber_id],
+          |row| row.get(0),
+        )
+        .optional()?,
+    )
+  }
+
+  /**
+   * Applies the changes and moves the cursor in one transaction, so the file never holds a
+   * batch without its cursor or the other way round
+   */
+  pub fn apply(
+    &mut self,
+    changes: &[CatalogChange],
+    subscriber_id: &str,
+    cursor: Option<&str>,
+  ) -> Result<()> {
+    let tx = self.connection.transaction()?;
+    for change in changes {
+      match change {
+        CatalogChange::PutAlbum { file_name, album } => put_album(&tx, file_name, album)?,
+        CatalogChange::DeleteAlbum { file_name } => {
+          tx.execute(
+            "DELETE FROM albums WHERE file_name = ?1",
+            params![file_name],
+          )?;
+        }
+      }
+    }
+    if let Some(cursor) = cursor {
+      tx.execute(
+        "INSERT INTO cursors (subscriber_id, cursor) VALUES (?1, ?2)
+        ON CONFLICT (subscriber_id) DO UPDATE SET
+          cursor = excluded.cursor,
+          updated_at = CURRENT_TIMESTAMP",
+        params![subscriber_id, cursor],
+      )?;
+    }
+    tx.commit()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn count(catalog: &Catalog, sql: &str) -> i64 {
+    catalog
+      .connection
+      .query_row(sql, [], |row| row.get(0))
+      .unwrap()
+  }
+
+  #[test]
+  fn test_apply_replaces_album_state() -> Result<()> {
+    let mut catalog = Catalog::open(Path::new(":memory:"))?;
+    let file_name = "release/album/bjork/vulnicura".to_string();
+    let album = ParsedAlbum {
+      name: "Vulnicura".to_string(),
+      artists: vec![ParsedArtistReference {
+        name: "Björk".to_string(),
+        file_name: "artist/bjork".to_string(),
+      }],
+      primary_genres: vec!["Art Pop".to_string()],
+      secondary_genres: vec!["Chamber Pop".to_string()],
+      ..Default::default()
+    };
+    catalog.apply(
+      &[CatalogChange::PutAlbum {
+        file_name: file_name.clone(),
+        album: album.clone(),
+      }],
+      "export",
+      Some("10"),
+    )?;
+    assert_eq!(count(&catalog, "SELECT COUNT(*) FROM album_genres"), 2);
+    assert_eq!(catalog.get_cursor("export")?, Some("10".to_string()));
+
+    catalog.apply(
+      &[CatalogChange::PutAlbum {
+        file_name: file_name.clone(),
+        album: ParsedAlbum {
+          secondary_genres: vec![],
+          ..album
+        },
+      }],
+      "export",
+      Some("11"),
+    )?;
+    assert_eq!(count(&catalog, "SELECT COUNT(*) FROM albums"), 1);
+    assert_eq!(count(&catalog, "SELECT COUNT(*) FROM album_genres"), 1);
+
+    catalog.apply(&[CatalogChange::DeleteAlbum { file_name }], "export", None)?;
+    assert_eq!(count(&catalog, "SELECT COUNT(*) FROM albums"), 0);
+    assert_eq!(count(&catalog, "SELECT COUNT(*) FROM album_artists"), 0);
+    assert_eq!(catalog.get_cursor("export")?, Some("11".to_string()));
+    Ok(())
+  }
+}
@@ -0,0 +1,3 @@
+pub mod lute {
+  tonic::include_proto!("lute");
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Result};
+use clap::{arg, Parser};
+use lute_sqlite_connector::{
+  catalog::{Catalog, CatalogChange},
+  client::lute::{
+    bootstrap_service_client::BootstrapServiceClient, event::Event,
+    event_service_client::EventServiceClient, parsed_file_data::Data, Album, AlbumArtist,
+    BootstrapRequest, EventStreamItem, EventStreamRequest, ParsedAlbum, ParsedArtistReference,
+    ParsedCredit, ParsedTrack,
+  },
+};
+use std::path::PathBuf;
+use tokio::sync::mpsc::unbounded_channel;
+
+/**
+ * Event schema version of the proto this connector is built against
+ */
+const EVENT_SCHEMA_VERSION: u32 = 2;
+
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 1024;
+
+fn catalog_changes(batch: &[EventStreamItem]) -> Vec<CatalogChange> {
+  batch
+    .iter()
+    .filter_map(
+      |item| match item.payload.as_ref()?.event.as_ref()?.event.as_ref()? {
+        Event::FileParsed(file_parsed_event) => {
+          match file_parsed_event.data.as_ref()?.data.as_ref()? {
+            Data::Album(parsed_album) => Some(CatalogChange::PutAlbum {
+              file_name: file_parsed_event.file_name.clone(),
+              album: parsed_album.clone(),
+            }),
+            _ => None,
+          }
+        }
+        Event::FileDeleted(file_deleted_event) => Some(CatalogChange::DeleteAlbum {
+          file_name: file_deleted_event.file_name.clone(),
+        }),
+        _ => None,
+      },
+    )
+    .collect()
+}
+
+fn artist_reference(artist: AlbumArtist) -> ParsedArtistReference {
+  ParsedArtistReference {
+    name: artist.name,
+    file_name: artist.file_name,
+  }
+}
+
+/**
+ * Snapshot albums carry the same fields as parsed ones, so they're stored the same way
+ */
+fn snapshot_album(album: Album) -> CatalogChange {
+  CatalogChange::PutAlbum {
+    file_name: album.file_name,
+    album: ParsedAlbum {
+      name: album.name,
+      rating: album.rating,
+      rating_count: album.rating_count,
+      artists: album.artists.into_iter().map(artist_reference).collect(),
+      primary_genres: album.primary_genres,
+      secondary_genres: album.secondary_genres,
+      descriptors: album.descriptors,
+      tracks: album
+        .tracks
+        .into_iter()
+        .map(|track| ParsedTrack {
+          name: track.name,
+          duration_seconds: track.duration_seconds,
+          rating: track.rating,
+          position: track.position,
+          artists: track.artists.into_iter().map(artist_reference).collect(),
+        })
+        .collect(),
+      release_date: album.release_date,
+      languages: album.languages,
+      credits: album
+        .credits
+        .into_iter()
+        .map(|credit| ParsedCredit {
+          artist: credit.artist.map(artist_reference),
+          roles: credit.roles,
+        })
+        .collect(),
+      cover_image_url: album.cover_image_url,
+      spotify_id: album.spotify_id,
+      is_various_artists: album.is_various_artists,
+    },
+  }
+}
+
+fn authorized<T>(message: T, api_key: &Option<String>) -> Result<tonic::Request<T>> {
+  let mut request = tonic::Request::new(message);
+  if let Some(api_key) = api_key {
+    request.metadata_mut().insert("x-api-key", api_key.parse()?);
+  }
+  Ok(request)
+}
+
+/**
+ * Stores a snapshot of every album, saving the cursor to stream events after with the last
+ * batch. Much faster than replaying the stream from the start for a new file.
+ */
+async fn bootstrap(
+  args: &Args,
+  client: &mut BootstrapServiceClient<tonic::transport::Channel>,
+  catalog: &mut Catalog,
+) -> Result<String> {
+  let mut snapshot = client
+    .bootstrap(authorized(
+      BootstrapRequest {
+        batch_size: Some(500),
+      },
+      &args.api_key,
+    )?)
+    .await?
+    .into_inner();
+
+  let mut album_count = 0;
+  while let Some(reply) = snapshot.message().await? {
+    album_count += reply.albums.len();
+    let changes = reply
+      .albums
+      .into_iter()
+      .map(snapshot_album)
+      .collect::<Vec<_>>();
+    catalog.apply(&changes, &args.subscriber_id, reply.cursor.as_deref())?;
+    if !changes.is_empty() {
+      println!("Stored {} snapshot albums", album_count);
+    }
+    if let Some(cursor) = reply.cursor {
+      return Ok(cursor);
+    }
+  }
+
+  Err(anyhow!("Bootstrap ended without a cursor"))
+}
+
+fn event_stream_request(
+  stream_id: &str,
+  subscriber_id: &str,
+  cursor: Option<String>,
+) -> EventStreamRequest {
+  EventStreamRequest {
+    stream_id: stream_id.to_string(),
+    subscriber_id: subscriber_id.to_string(),
+    cursor,
+    max_batch_size: Some(500),
+    flatten: false,
+    schema_version: Some(EVENT_SCHEMA_VERSION),
+    supported_event_types: vec![],
+  }
+}
+
+async fn subscribe(
+  args: &Args,
+  cursor: Option<String>,
+  client: &mut EventServiceClient<tonic::transport::Channel>,
+  catalog: &mut Catalog,
+) -> Result<()> {
+  let (cursor_sender, mut cursor_receiver) = unbounded_channel::<String>();
+  let stream_id = args.stream_id.clone();
+  let subscriber_id = args.subscriber_id.clone();
+  let request_stream = async_stream::stream! {
+    yield event_stream_request(&stream_id, &subscriber_id, cursor);
+
+    while let Some(cursor) = cursor_receiver.recv().await {
+      println!("Requesting batch with cursor: {}", cursor);
+      yield event_stream_request(&stream_id, &subscriber_id, Some(cursor));
+    }
+  };
+
+  let response = client
+    .stream(authorized(request_stream, &args.api_key)?)
+    .await?;
+  let mut event_stream = response.into_inner();
+
+  while let Some(reply) = event_stream.message().await? {
+    catalog.apply(
+      &catalog_changes(&reply.items),
+      &args.subscriber_id,
+      Some(&reply.cursor),
+    )?;
+    cursor_sender.send(reply.cursor)?;
+  }
+
+  Ok(())
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+  #[arg(long, default_value = "grpc://localhost:22000")]
+  lute_url: String,
+
+  #[arg(long, default_value = "replication")]
+  stream_id: String,
+
+  #[arg(long)]
+  subscriber_id: String,
+
+  /// File the catalog is written to, created if it doesn't exist
+  #[arg(long, default_value = "lute-catalog.db")]
+  db_path: PathBuf,
+
+  /// Needed when the lute instance has auth enabled, with the connector replication scope
+  #[arg(long)]
+  api_key: Option<String>,
+
+  /// Replay the stream from the start instead of loading a snapshot when the file is new
+  #[arg(long, default_value_t = false)]
+  skip_bootstrap: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+  let args = Args::parse();
+  let mut catalog = Catalog::open(&args.db_path).expect("Failed to open catalog");
+
+  let channel = tonic::transport::Endpoint::from_shared(args.lute_url.clone())?
+    .connect()
+    .await
+    .expect("Failed to connect to lute instance");
+  let mut client = EventServiceClient::new(channel.clone());
+
+  // The file's own cursor is sent with the first request, so a copied or restored file resumes
+  // from what it holds rather than from where lute last saw the subscriber
+  let cursor = match catalog.get_cursor(&args.subscriber_id)? {
+    Some(cursor) => Some(cursor),
+    None if args.skip_bootstrap => None,
+    None => {
+      let mut bootstrap_client =
+        BootstrapServiceClient::new(channel).max_decoding_message_size(MAX_MESSAGE_SIZE);
+      Some(bootstrap(&args, &mut bootstrap_client, &mut catalog).await?)
+    }
+  };
+
+  subscribe(&args, cursor, &mut client, &mut catalog).await?;
+
+  Ok(())
+}
@@ -3,6 +3,15 @@ use std::{env, path::PathBuf};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
   let mut config = prost_build::Config::new();
   config.protoc_arg("--experimental_allow_proto3_optional");
+  // JSON for the REST gateway, in the proto3 JSON field naming
+  config.message_attribute(
+    ".lute",
+    "#[derive(serde_derive::Serialize, serde_derive::Deserialize)] #[serde(rename_all = \"camelCase\", default)]",
+  );
+  config.enum_attribute(
+    ".lute",
+    "#[derive(serde_derive::Serialize, serde_derive::Deserialize)] #[serde(rename_all = \"camelCase\")]",
+  );
 
   tonic_build::configure()
     .file_descriptor_set_path(
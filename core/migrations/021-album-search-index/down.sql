@@ -0,0 +1,5 @@
+DROP INDEX idx_album_search_embeddings_key;
+DROP TABLE album_search_embeddings;
+DROP TABLE album_search_fts;
+DROP INDEX idx_album_search_documents_rating_count;
+DROP TABLE album_search_documents;
@@ -0,0 +1,28 @@
+CREATE TABLE album_search_documents (
+  file_name TEXT PRIMARY KEY,
+  json BLOB NOT NULL,
+  rating_count INTEGER NOT NULL DEFAULT 0,
+  release_year INTEGER,
+  is_duplicate INTEGER NOT NULL DEFAULT 0,
+  primary_genre_count INTEGER NOT NULL DEFAULT 0,
+  secondary_genre_count INTEGER NOT NULL DEFAULT 0,
+  descriptor_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX idx_album_search_documents_rating_count ON album_search_documents (rating_count);
+
+CREATE VIRTUAL TABLE album_search_fts USING fts5 (
+  file_name UNINDEXED,
+  name,
+  artist_names,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TABLE album_search_embeddings (
+  file_name TEXT NOT NULL,
+  key TEXT NOT NULL,
+  embedding BLOB NOT NULL,
+  PRIMARY KEY (file_name, key)
+);
+
+CREATE INDEX idx_album_search_embeddings_key ON album_search_embeddings (key);
@@ -0,0 +1,5 @@
+CREATE TABLE file_metadata (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  last_saved_at DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX idx_scheduler_job_runs_job_id;
+DROP TABLE scheduler_job_runs;
@@ -0,0 +1,12 @@
+CREATE TABLE scheduler_job_runs (
+  token TEXT PRIMARY KEY,
+  job_id TEXT NOT NULL,
+  job_name TEXT NOT NULL,
+  payload BLOB,
+  attempts INTEGER NOT NULL DEFAULT 1,
+  started_at DATETIME NOT NULL,
+  committed_at DATETIME,
+  interrupted_at DATETIME
+);
+
+CREATE INDEX idx_scheduler_job_runs_job_id ON scheduler_job_runs (job_id);
@@ -0,0 +1,5 @@
+CREATE TABLE artist_alternate_names (
+  artist_file_name TEXT NOT NULL,
+  name TEXT NOT NULL,
+  PRIMARY KEY (artist_file_name, name)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE events DROP COLUMN uncompressed_size;
+ALTER TABLE events DROP COLUMN compression;
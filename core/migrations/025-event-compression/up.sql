@@ -0,0 +1,2 @@
+ALTER TABLE events ADD COLUMN compression TEXT DEFAULT NULL;
+ALTER TABLE events ADD COLUMN uncompressed_size INTEGER DEFAULT NULL;
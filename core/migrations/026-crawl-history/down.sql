@@ -0,0 +1,2 @@
+DROP INDEX idx_crawl_history_file_name_crawled_at;
+DROP TABLE crawl_history;
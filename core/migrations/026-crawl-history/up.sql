@@ -0,0 +1,11 @@
+CREATE TABLE crawl_history (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  file_name TEXT NOT NULL,
+  crawled_at DATETIME NOT NULL,
+  outcome TEXT NOT NULL,
+  status_code INTEGER,
+  duration_ms INTEGER NOT NULL,
+  initiator TEXT
+);
+
+CREATE INDEX idx_crawl_history_file_name_crawled_at ON crawl_history (file_name, crawled_at);
@@ -0,0 +1 @@
+ALTER TABLE albums ADD COLUMN musicbrainz_id TEXT;
@@ -0,0 +1 @@
+ALTER TABLE file_metadata DROP COLUMN redaction_version;
@@ -0,0 +1,2 @@
+DROP INDEX idx_album_discogs_releases_refreshed_at;
+DROP TABLE album_discogs_releases;
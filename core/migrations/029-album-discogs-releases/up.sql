@@ -0,0 +1,11 @@
+CREATE TABLE album_discogs_releases (
+  album_id INTEGER PRIMARY KEY,
+  release_id INTEGER NOT NULL,
+  median_price REAL,
+  lowest_price REAL,
+  num_for_sale INTEGER NOT NULL,
+  refreshed_at DATETIME NOT NULL,
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_album_discogs_releases_refreshed_at ON album_discogs_releases (refreshed_at);
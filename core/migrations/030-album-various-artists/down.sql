@@ -0,0 +1,3 @@
+DROP INDEX idx_track_artists_artist_id;
+DROP TABLE track_artists;
+ALTER TABLE albums DROP COLUMN is_various_artists;
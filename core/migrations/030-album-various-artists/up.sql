@@ -0,0 +1,11 @@
+ALTER TABLE albums ADD COLUMN is_various_artists BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE TABLE track_artists (
+  track_id INTEGER NOT NULL,
+  artist_id INTEGER NOT NULL,
+  PRIMARY KEY (track_id, artist_id),
+  FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE,
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_track_artists_artist_id ON track_artists (artist_id);
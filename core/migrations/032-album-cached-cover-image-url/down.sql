@@ -0,0 +1 @@
+ALTER TABLE albums DROP COLUMN cached_cover_image_url;
@@ -0,0 +1 @@
+ALTER TABLE albums ADD COLUMN cached_cover_image_url TEXT;
@@ -0,0 +1,17 @@
+CREATE VIRTUAL TABLE album_search_fts_old USING fts5 (
+  file_name UNINDEXED,
+  name,
+  artist_names,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO album_search_fts_old (file_name, name, artist_names)
+SELECT
+  file_name,
+  name || ' ' || ascii_name,
+  artist_names || ' ' || artist_ascii_names
+FROM album_search_fts;
+
+DROP TABLE album_search_fts;
+
+ALTER TABLE album_search_fts_old RENAME TO album_search_fts;
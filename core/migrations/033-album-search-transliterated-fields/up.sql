@@ -0,0 +1,27 @@
+CREATE VIRTUAL TABLE album_search_fts_new USING fts5 (
+  file_name UNINDEXED,
+  name,
+  ascii_name,
+  artist_names,
+  artist_ascii_names,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Transliterations can't be computed in SQL, so the old columns, which hold both the original and
+-- transliterated names, stand in for them until albums are indexed again
+INSERT INTO album_search_fts_new (file_name, name, ascii_name, artist_names, artist_ascii_names)
+SELECT
+  f.file_name,
+  json_extract(d.json, '$.name'),
+  f.name,
+  (
+    SELECT group_concat(json_extract(a.value, '$.name'), ' ')
+    FROM json_each(d.json, '$.artists') a
+  ),
+  f.artist_names
+FROM album_search_fts f
+JOIN album_search_documents d ON d.file_name = f.file_name;
+
+DROP TABLE album_search_fts;
+
+ALTER TABLE album_search_fts_new RENAME TO album_search_fts;
@@ -0,0 +1 @@
+CREATE INDEX idx_events_created_at ON events (created_at);
@@ -0,0 +1 @@
+ALTER TABLE event_subscribers ADD COLUMN streams TEXT;
@@ -0,0 +1 @@
+DROP INDEX idx_file_metadata_last_saved_at;
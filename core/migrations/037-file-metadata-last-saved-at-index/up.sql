@@ -0,0 +1 @@
+CREATE INDEX idx_file_metadata_last_saved_at ON file_metadata (last_saved_at);
@@ -0,0 +1,3 @@
+DROP TABLE album_notes;
+DROP INDEX idx_album_tags_tag;
+DROP TABLE album_tags;
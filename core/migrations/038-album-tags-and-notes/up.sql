@@ -0,0 +1,14 @@
+CREATE TABLE album_tags (
+  album_file_name TEXT NOT NULL,
+  tag TEXT NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (album_file_name, tag)
+);
+
+CREATE INDEX idx_album_tags_tag ON album_tags (tag);
+
+CREATE TABLE album_notes (
+  album_file_name TEXT PRIMARY KEY,
+  notes TEXT NOT NULL,
+  updated_at DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX idx_listening_events_profile_id_listened_at;
+DROP TABLE listening_events;
@@ -0,0 +1,11 @@
+CREATE TABLE listening_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  profile_id TEXT NOT NULL,
+  album_file_name TEXT NOT NULL,
+  listened_at DATETIME NOT NULL,
+  source TEXT,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (profile_id, album_file_name, listened_at)
+);
+
+CREATE INDEX idx_listening_events_profile_id_listened_at ON listening_events (profile_id, listened_at);
@@ -0,0 +1,2 @@
+DROP INDEX idx_genre_edges_child_genre;
+DROP TABLE genre_edges;
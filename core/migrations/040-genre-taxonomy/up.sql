@@ -0,0 +1,7 @@
+CREATE TABLE genre_edges (
+  parent_genre TEXT NOT NULL,
+  child_genre TEXT NOT NULL,
+  PRIMARY KEY (parent_genre, child_genre)
+);
+
+CREATE INDEX idx_genre_edges_child_genre ON genre_edges (child_genre);
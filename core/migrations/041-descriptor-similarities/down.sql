@@ -0,0 +1 @@
+DROP TABLE descriptor_similarities;
@@ -0,0 +1,6 @@
+CREATE TABLE descriptor_similarities (
+  descriptor TEXT NOT NULL,
+  similar_descriptor TEXT NOT NULL,
+  similarity REAL NOT NULL,
+  PRIMARY KEY (descriptor, similar_descriptor)
+);
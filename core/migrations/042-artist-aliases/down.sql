@@ -0,0 +1,2 @@
+DROP INDEX idx_artist_aliases_canonical_file_name;
+DROP TABLE artist_aliases;
@@ -0,0 +1,6 @@
+CREATE TABLE artist_aliases (
+  alias_file_name TEXT NOT NULL PRIMARY KEY,
+  canonical_file_name TEXT NOT NULL
+);
+
+CREATE INDEX idx_artist_aliases_canonical_file_name ON artist_aliases (canonical_file_name);
@@ -0,0 +1,3 @@
+DROP INDEX idx_file_content_versions_saved_at;
+DROP INDEX idx_file_content_versions_file_name;
+DROP TABLE file_content_versions;
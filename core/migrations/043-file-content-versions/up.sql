@@ -0,0 +1,10 @@
+CREATE TABLE file_content_versions (
+  key TEXT NOT NULL PRIMARY KEY,
+  file_name TEXT NOT NULL,
+  saved_at DATETIME NOT NULL,
+  size_bytes INTEGER NOT NULL,
+  parsed_at DATETIME
+);
+
+CREATE INDEX idx_file_content_versions_file_name ON file_content_versions (file_name, saved_at);
+CREATE INDEX idx_file_content_versions_saved_at ON file_content_versions (saved_at);
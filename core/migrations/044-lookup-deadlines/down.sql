@@ -0,0 +1,2 @@
+ALTER TABLE list_lookups DROP COLUMN priority;
+ALTER TABLE list_lookups DROP COLUMN deadline;
@@ -0,0 +1,2 @@
+ALTER TABLE list_lookups ADD COLUMN priority INTEGER;
+ALTER TABLE list_lookups ADD COLUMN deadline DATETIME;
@@ -0,0 +1,3 @@
+DROP TABLE album_cluster_summaries;
+DROP INDEX idx_album_clusters_cluster_id;
+DROP TABLE album_clusters;
@@ -0,0 +1,16 @@
+CREATE TABLE album_clusters (
+  album_file_name TEXT PRIMARY KEY,
+  cluster_id INTEGER NOT NULL
+);
+
+CREATE INDEX idx_album_clusters_cluster_id ON album_clusters (cluster_id);
+
+CREATE TABLE album_cluster_summaries (
+  cluster_id INTEGER PRIMARY KEY,
+  embedding_key TEXT NOT NULL,
+  size INTEGER NOT NULL,
+  label TEXT NOT NULL,
+  primary_genres TEXT NOT NULL,
+  descriptors TEXT NOT NULL,
+  created_at DATETIME NOT NULL
+);
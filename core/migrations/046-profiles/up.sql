@@ -0,0 +1,4 @@
+CREATE TABLE profiles (
+  id TEXT PRIMARY KEY,
+  json TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX idx_spotify_tracks_album_file_name;
+DROP TABLE spotify_tracks;
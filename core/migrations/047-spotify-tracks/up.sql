@@ -0,0 +1,8 @@
+CREATE TABLE spotify_tracks (
+  spotify_id TEXT PRIMARY KEY,
+  album_file_name TEXT NOT NULL,
+  json TEXT NOT NULL,
+  embedding BLOB NOT NULL
+);
+
+CREATE INDEX idx_spotify_tracks_album_file_name ON spotify_tracks (album_file_name);
@@ -0,0 +1 @@
+ALTER TABLE album_discogs_releases RENAME COLUMN median_suggested_price TO median_price;
@@ -0,0 +1,130 @@
+use super::album_clustering::AlbumCluster;
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+pub struct AlbumClusterRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl AlbumClusterRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Replaces every stored assignment and cluster, each run clusters the whole catalog
+   */
+  #[instrument(skip_all, fields(assignments = assignments.len(), clusters = clusters.len()))]
+  pub async fn put_all(
+    &self,
+    assignments: Vec<(FileName, u32)>,
+    clusters: Vec<AlbumCluster>,
+  ) -> Result<()> {
+    let clusters = clusters
+      .into_iter()
+      .map(|cluster| {
+        Ok((
+          serde_json::to_string(&cluster.primary_genres)?,
+          serde_json::to_string(&cluster.descriptors)?,
+          cluster,
+        ))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM album_clusters", [])?;
+        tx.execute("DELETE FROM album_cluster_summaries", [])?;
+        {
+          let mut stmt =
+            tx.prepare("INSERT INTO album_clusters (album_file_name, cluster_id) VALUES (?, ?)")?;
+          for (file_name, cluster_id) in assignments {
+            stmt.execute(params![file_name.to_string(), cluster_id])?;
+          }
+        }
+        for (primary_genres, descriptors, cluster) in clusters {
+          tx.execute(
+            "
+            INSERT INTO album_cluster_summaries
+              (cluster_id, embedding_key, size, label, primary_genres, descriptors, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+            params![
+              cluster.id,
+              cluster.embedding_key,
+              cluster.size,
+              cluster.label,
+              primary_genres,
+              descriptors,
+              cluster.created_at
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album clusters");
+        anyhow!("Failed to put album clusters")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_clusters(&self) -> Result<Vec<AlbumCluster>> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT cluster_id, embedding_key, size, label, primary_genres, descriptors, created_at
+          FROM album_cluster_summaries
+          ORDER BY cluster_id
+          ",
+        )?;
+        let rows = stmt
+          .query_map([], |row| {
+            Ok((
+              row.get::<_, u32>(0)?,
+              row.get::<_, String>(1)?,
+              row.get::<_, u32>(2)?,
+              row.get::<_, String>(3)?,
+              row.get::<_, String>(4)?,
+              row.get::<_, String>(5)?,
+              row.get(6)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get album clusters");
+        anyhow!("Failed to get album clusters")
+      })??;
+    rows
+      .into_iter()
+      .map(
+        |(id, embedding_key, size, label, primary_genres, descriptors, created_at)| {
+          Ok(AlbumCluster {
+            id,
+            embedding_key,
+            size,
+            label,
+            primary_genres: serde_json::from_str(&primary_genres)?,
+            descriptors: serde_json::from_str(&descriptors)?,
+            created_at,
+          })
+        },
+      )
+      .collect()
+  }
+}
@@ -0,0 +1,219 @@
+use super::album_read_model::AlbumReadModel;
+use crate::helpers::item_with_factor::{desc_sort_by_factor, ItemWithFactor};
+use chrono::NaiveDateTime;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use rayon::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const TOP_ITEM_COUNT: usize = 10;
+const LABEL_GENRE_COUNT: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlbumCluster {
+  pub id: u32,
+  pub embedding_key: String,
+  pub size: u32,
+  /**
+   * The cluster's most common primary genres
+   */
+  pub label: String,
+  pub primary_genres: Vec<ItemWithFactor>,
+  pub descriptors: Vec<ItemWithFactor>,
+  pub created_at: NaiveDateTime,
+}
+
+fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+  a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+pub fn nearest_centroid(centroids: &[Vec<f32>], vector: &[f32]) -> usize {
+  centroids
+    .iter()
+    .map(|centroid| squared_distance(centroid, vector))
+    .enumerate()
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(i, _)| i)
+    .unwrap_or(0)
+}
+
+/**
+ * k-means++ seeding, each centroid after the first is drawn with probability proportional to its
+ * squared distance from the nearest one already chosen. Returns fewer than `k` centroids when
+ * there aren't that many distinct vectors.
+ */
+fn seed_centroids(vectors: &[Vec<f32>], k: usize, rng: &mut impl Rng) -> Vec<Vec<f32>> {
+  let mut centroids = vec![vectors[rng.gen_range(0..vectors.len())].clone()];
+  let mut distances = vectors
+    .par_iter()
+    .map(|vector| squared_distance(&centroids[0], vector))
+    .collect::<Vec<_>>();
+  while centroids.len() < k {
+    let Ok(weights) = WeightedIndex::new(&distances) else {
+      break;
+    };
+    let centroid = vectors[weights.sample(rng)].clone();
+    distances
+      .par_iter_mut()
+      .zip(vectors)
+      .for_each(|(distance, vector)| {
+        *distance = distance.min(squared_distance(&centroid, vector));
+      });
+    centroids.push(centroid);
+  }
+  centroids
+}
+
+/**
+ * Lloyd's algorithm from k-means++ seeds, stopping early once no vector changes cluster. A cluster
+ * that loses all its vectors keeps its previous centroid.
+ */
+pub fn kmeans(
+  vectors: &[Vec<f32>],
+  k: usize,
+  max_iterations: usize,
+  rng: &mut impl Rng,
+) -> Vec<Vec<f32>> {
+  if vectors.is_empty() || k == 0 {
+    return vec![];
+  }
+  let width = vectors[0].len();
+  let mut centroids = seed_centroids(vectors, k, rng);
+  let mut assignments = vec![usize::MAX; vectors.len()];
+  for _ in 0..max_iterations {
+    let next_assignments = vectors
+      .par_iter()
+      .map(|vector| nearest_centroid(&centroids, vector))
+      .collect::<Vec<_>>();
+    if next_assignments == assignments {
+      break;
+    }
+    assignments = next_assignments;
+
+    let mut sums = vec![vec![0.0_f64; width]; centroids.len()];
+    let mut counts = vec![0_usize; centroids.len()];
+    for (vector, cluster) in vectors.iter().zip(&assignments) {
+      counts[*cluster] += 1;
+      sums[*cluster]
+        .iter_mut()
+        .zip(vector)
+        .for_each(|(sum, x)| *sum += *x as f64);
+    }
+    for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+      if count > 0 {
+        *centroid = sum.into_iter().map(|x| (x / count as f64) as f32).collect();
+      }
+    }
+  }
+  centroids
+}
+
+fn top_items(counts: HashMap<String, u32>) -> Vec<ItemWithFactor> {
+  let mut items = counts
+    .into_iter()
+    .map(|(item, factor)| ItemWithFactor { item, factor })
+    .collect::<Vec<_>>();
+  desc_sort_by_factor(&mut items);
+  items.truncate(TOP_ITEM_COUNT);
+  items
+}
+
+/**
+ * Tallies the genres and descriptors of a cluster's albums as they're assigned, so the whole
+ * catalog never has to be held at once
+ */
+#[derive(Default)]
+pub struct AlbumClusterTally {
+  size: u32,
+  primary_genres: HashMap<String, u32>,
+  descriptors: HashMap<String, u32>,
+}
+
+impl AlbumClusterTally {
+  pub fn add(&mut self, album: &AlbumReadModel) {
+    self.size += 1;
+    for genre in &album.primary_genres {
+      *self.primary_genres.entry(genre.clone()).or_default() += 1;
+    }
+    for descriptor in &album.descriptors {
+      *self.descriptors.entry(descriptor.clone()).or_default() += 1;
+    }
+  }
+
+  pub fn into_cluster(
+    self,
+    id: u32,
+    embedding_key: &str,
+    created_at: NaiveDateTime,
+  ) -> AlbumCluster {
+    let primary_genres = top_items(self.primary_genres);
+    let label = primary_genres
+      .iter()
+      .take(LABEL_GENRE_COUNT)
+      .map(|genre| genre.item.clone())
+      .collect::<Vec<_>>()
+      .join(" / ");
+    AlbumCluster {
+      id,
+      embedding_key: embedding_key.to_string(),
+      size: self.size,
+      label,
+      primary_genres,
+      descriptors: top_items(self.descriptors),
+      created_at,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  #[test]
+  fn test_kmeans() {
+    let vectors = vec![
+      vec![0.0, 0.1],
+      vec![0.1, 0.0],
+      vec![0.0, 0.0],
+      vec![10.0, 10.1],
+      vec![10.1, 10.0],
+      vec![10.0, 10.0],
+    ];
+    let centroids = kmeans(&vectors, 2, 50, &mut StdRng::seed_from_u64(7));
+    assert_eq!(centroids.len(), 2);
+    let assignments = vectors
+      .iter()
+      .map(|vector| nearest_centroid(&centroids, vector))
+      .collect::<Vec<_>>();
+    assert!(assignments[..3].iter().all(|c| *c == assignments[0]));
+    assert!(assignments[3..].iter().all(|c| *c == assignments[3]));
+    assert_ne!(assignments[0], assignments[3]);
+
+    let duplicates = vec![vec![1.0, 1.0]; 4];
+    assert_eq!(
+      kmeans(&duplicates, 3, 50, &mut StdRng::seed_from_u64(7)).len(),
+      1
+    );
+    assert!(kmeans(&[], 3, 50, &mut StdRng::seed_from_u64(7)).is_empty());
+  }
+
+  #[test]
+  fn test_album_cluster_tally() {
+    let mut tally = AlbumClusterTally::default();
+    for genres in [
+      vec!["Shoegaze", "Dream Pop"],
+      vec!["Shoegaze"],
+      vec!["Noise Pop"],
+    ] {
+      tally.add(&AlbumReadModel {
+        primary_genres: genres.into_iter().map(String::from).collect(),
+        ..Default::default()
+      });
+    }
+    let cluster = tally.into_cluster(3, "openai-default", NaiveDateTime::default());
+    assert_eq!(cluster.size, 3);
+    assert_eq!(cluster.primary_genres[0].item, "Shoegaze");
+    assert!(cluster.label.starts_with("Shoegaze / "));
+  }
+}
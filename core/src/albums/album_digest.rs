@@ -0,0 +1,144 @@
+use crate::{
+  files::file_metadata::file_name::FileName,
+  proto::{self, operations_service_client::OperationsServiceClient},
+};
+use anyhow::Result;
+use std::collections::HashMap;
+use tracing::info;
+
+pub const DIGEST_PAGE_SIZE: u32 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumDigest {
+  pub file_name: FileName,
+  pub hash: String,
+}
+
+impl From<AlbumDigest> for proto::AlbumDigest {
+  fn from(val: AlbumDigest) -> Self {
+    proto::AlbumDigest {
+      file_name: val.file_name.to_string(),
+      hash: val.hash,
+    }
+  }
+}
+
+impl TryFrom<proto::AlbumDigest> for AlbumDigest {
+  type Error = anyhow::Error;
+
+  fn try_from(val: proto::AlbumDigest) -> Result<Self> {
+    Ok(AlbumDigest {
+      file_name: FileName::try_from(val.file_name)?,
+      hash: val.hash,
+    })
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct AlbumCorpusDiff {
+  pub missing_locally: Vec<FileName>,
+  pub missing_on_peer: Vec<FileName>,
+  pub differing: Vec<FileName>,
+}
+
+impl From<AlbumCorpusDiff> for proto::DiffCorpusReply {
+  fn from(val: AlbumCorpusDiff) -> Self {
+    let to_strings = |file_names: Vec<FileName>| file_names.iter().map(|f| f.to_string()).collect();
+    proto::DiffCorpusReply {
+      missing_locally: to_strings(val.missing_locally),
+      missing_on_peer: to_strings(val.missing_on_peer),
+      differing: to_strings(val.differing),
+      syncs_scheduled: 0,
+    }
+  }
+}
+
+pub fn diff_album_digests(local: Vec<AlbumDigest>, peer: Vec<AlbumDigest>) -> AlbumCorpusDiff {
+  let mut peer = peer
+    .into_iter()
+    .map(|digest| (digest.file_name, digest.hash))
+    .collect::<HashMap<_, _>>();
+  let mut diff = AlbumCorpusDiff::default();
+  for digest in local {
+    match peer.remove(&digest.file_name) {
+      Some(hash) if hash != digest.hash => diff.differing.push(digest.file_name),
+      Some(_) => {}
+      None => diff.missing_on_peer.push(digest.file_name),
+    }
+  }
+  diff.missing_locally = peer.into_keys().collect();
+  for file_names in [
+    &mut diff.missing_locally,
+    &mut diff.missing_on_peer,
+    &mut diff.differing,
+  ] {
+    file_names.sort_by_key(|file_name| file_name.to_string());
+  }
+  diff
+}
+
+/**
+ * Pages through every album digest of another instance over its RPC server
+ */
+pub async fn fetch_peer_album_digests(peer_address: String) -> Result<Vec<AlbumDigest>> {
+  let mut client = OperationsServiceClient::connect(peer_address.clone()).await?;
+  let mut digests: Vec<AlbumDigest> = vec![];
+  loop {
+    let page = client
+      .get_album_digests(proto::GetAlbumDigestsRequest {
+        after: digests.last().map(|digest| digest.file_name.to_string()),
+        limit: Some(DIGEST_PAGE_SIZE),
+      })
+      .await?
+      .into_inner()
+      .digests;
+    let page_size = page.len();
+    for digest in page {
+      digests.push(digest.try_into()?);
+    }
+    if page_size < DIGEST_PAGE_SIZE as usize {
+      break;
+    }
+  }
+  info!(
+    peer_address,
+    count = digests.len(),
+    "Fetched peer album digests"
+  );
+  Ok(digests)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn digest(file_name: &str, hash: &str) -> Result<AlbumDigest> {
+    Ok(AlbumDigest {
+      file_name: FileName::try_from(file_name)?,
+      hash: hash.to_string(),
+    })
+  }
+
+  #[test]
+  fn test_diff_album_digests() -> Result<()> {
+    let local = vec![
+      digest("release/album/bjork/vulnicura", "a")?,
+      digest("release/album/bjork/homogenic", "b")?,
+      digest("release/album/bjork/post", "c")?,
+    ];
+    let peer = vec![
+      digest("release/album/bjork/vulnicura", "a")?,
+      digest("release/album/bjork/homogenic", "changed")?,
+      digest("release/album/bjork/debut", "d")?,
+    ];
+    assert_eq!(
+      diff_album_digests(local, peer),
+      AlbumCorpusDiff {
+        missing_locally: vec![FileName::try_from("release/album/bjork/debut")?],
+        missing_on_peer: vec![FileName::try_from("release/album/bjork/post")?],
+        differing: vec![FileName::try_from("release/album/bjork/homogenic")?],
+      }
+    );
+    Ok(())
+  }
+}
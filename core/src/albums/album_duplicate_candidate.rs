@@ -0,0 +1,224 @@
+use super::album_read_model::AlbumReadModel;
+use crate::files::file_metadata::file_name::FileName;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use strsim::jaro_winkler;
+
+/**
+ * Album names at least this similar, compared in lowercase ascii, make a candidate pair
+ */
+const MIN_NAME_SIMILARITY: f64 = 0.9;
+
+/**
+ * Release dates further apart than this rule a pair out, reissues and remasters usually get their
+ * own release date
+ */
+const MAX_RELEASE_DATE_DISTANCE_DAYS: i64 = 366;
+
+#[derive(
+  Debug, Clone, PartialEq, Serialize, Deserialize, strum_macros::Display, strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AlbumDuplicateCandidateStatus {
+  Pending,
+  Confirmed,
+  Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlbumDuplicateCandidate {
+  /**
+   * The pair's album with more ratings, kept as the original if the pair is confirmed
+   */
+  pub original_file_name: FileName,
+  pub duplicate_file_name: FileName,
+  pub name_similarity: f64,
+  pub status: AlbumDuplicateCandidateStatus,
+  pub detected_at: NaiveDateTime,
+}
+
+/**
+ * Same for either order of the pair, so a pair is only ever reviewed once
+ */
+pub fn album_duplicate_candidate_key(a: &FileName, b: &FileName) -> String {
+  if a < b {
+    format!("{}|{}", a.to_string(), b.to_string())
+  } else {
+    format!("{}|{}", b.to_string(), a.to_string())
+  }
+}
+
+impl AlbumDuplicateCandidate {
+  pub fn key(&self) -> String {
+    album_duplicate_candidate_key(&self.original_file_name, &self.duplicate_file_name)
+  }
+}
+
+/**
+ * The parts of an album duplicate detection looks at, small enough to hold the whole catalog
+ */
+#[derive(Debug, Clone)]
+pub struct DuplicateDetectionAlbum {
+  pub file_name: FileName,
+  pub artist_file_names: Vec<FileName>,
+  pub name: String,
+  pub release_date: Option<NaiveDate>,
+  pub rating_count: u32,
+  pub is_duplicate: bool,
+}
+
+impl From<&AlbumReadModel> for DuplicateDetectionAlbum {
+  fn from(album: &AlbumReadModel) -> Self {
+    Self {
+      file_name: album.file_name.clone(),
+      artist_file_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+      name: album.ascii_name().to_lowercase(),
+      release_date: album.release_date,
+      rating_count: album.rating_count,
+      is_duplicate: album.duplicate_of.is_some(),
+    }
+  }
+}
+
+fn is_release_date_close(a: Option<NaiveDate>, b: Option<NaiveDate>) -> bool {
+  match (a, b) {
+    (Some(a), Some(b)) => (a - b).num_days().abs() <= MAX_RELEASE_DATE_DISTANCE_DAYS,
+    _ => true,
+  }
+}
+
+/**
+ * Pairs of albums sharing an artist with near identical names and close release dates. Albums
+ * already marked as duplicates are left out.
+ */
+pub fn find_album_duplicate_candidates(
+  albums: &[DuplicateDetectionAlbum],
+  detected_at: NaiveDateTime,
+) -> Vec<AlbumDuplicateCandidate> {
+  let mut albums_by_artist: HashMap<&FileName, Vec<&DuplicateDetectionAlbum>> = HashMap::new();
+  for album in albums.iter().filter(|album| !album.is_duplicate) {
+    for artist_file_name in &album.artist_file_names {
+      albums_by_artist
+        .entry(artist_file_name)
+        .or_default()
+        .push(album);
+    }
+  }
+
+  let mut seen_keys = HashSet::new();
+  let mut candidates = vec![];
+  for artist_albums in albums_by_artist.values() {
+    for (i, a) in artist_albums.iter().enumerate() {
+      for b in &artist_albums[i + 1..] {
+        if a.file_name == b.file_name || !is_release_date_close(a.release_date, b.release_date) {
+          continue;
+        }
+        let name_similarity = jaro_winkler(&a.name, &b.name);
+        if name_similarity < MIN_NAME_SIMILARITY {
+          continue;
+        }
+        if !seen_keys.insert(album_duplicate_candidate_key(&a.file_name, &b.file_name)) {
+          continue;
+        }
+        let (original, duplicate) = if a.rating_count >= b.rating_count {
+          (a, b)
+        } else {
+          (b, a)
+        };
+        candidates.push(AlbumDuplicateCandidate {
+          original_file_name: original.file_name.clone(),
+          duplicate_file_name: duplicate.file_name.clone(),
+          name_similarity,
+          status: AlbumDuplicateCandidateStatus::Pending,
+          detected_at,
+        });
+      }
+    }
+  }
+  candidates
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn album(
+    file_name: &str,
+    artist: &str,
+    name: &str,
+    year: i32,
+    rating_count: u32,
+  ) -> DuplicateDetectionAlbum {
+    DuplicateDetectionAlbum {
+      file_name: FileName::try_from(file_name).unwrap(),
+      artist_file_names: vec![FileName::try_from(artist).unwrap()],
+      name: name.to_string(),
+      release_date: NaiveDate::from_ymd_opt(year, 1, 1),
+      rating_count,
+      is_duplicate: false,
+    }
+  }
+
+  #[test]
+  fn test_find_album_duplicate_candidates() {
+    let detected_at = NaiveDate::from_ymd_opt(2024, 1, 1)
+      .unwrap()
+      .and_hms_opt(0, 0, 0)
+      .unwrap();
+    let candidates = find_album_duplicate_candidates(
+      &[
+        album(
+          "release/album/slowdive/souvlaki",
+          "artist/slowdive",
+          "souvlaki",
+          1993,
+          100,
+        ),
+        album(
+          "release/album/slowdive/souvlaki-1",
+          "artist/slowdive",
+          "souvlaki.",
+          1993,
+          500,
+        ),
+        album(
+          "release/album/slowdive/souvlaki-2",
+          "artist/slowdive",
+          "souvlaki",
+          2005,
+          50,
+        ),
+        album(
+          "release/album/slowdive/pygmalion",
+          "artist/slowdive",
+          "pygmalion",
+          1993,
+          80,
+        ),
+        album(
+          "release/album/other/souvlaki",
+          "artist/other",
+          "souvlaki",
+          1993,
+          10,
+        ),
+      ],
+      detected_at,
+    );
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(
+      candidates[0].original_file_name.to_string(),
+      "release/album/slowdive/souvlaki-1"
+    );
+    assert_eq!(
+      candidates[0].duplicate_file_name.to_string(),
+      "release/album/slowdive/souvlaki"
+    );
+  }
+}
@@ -0,0 +1,109 @@
+use super::album_duplicate_candidate::{
+  album_duplicate_candidate_key, AlbumDuplicateCandidate, AlbumDuplicateCandidateStatus,
+};
+use crate::{
+  files::file_metadata::file_name::FileName,
+  helpers::document_store::{DocumentFilter, DocumentStore},
+};
+use anyhow::Result;
+use std::{collections::HashSet, sync::Arc};
+
+const COLLECTION: &str = "album_duplicate_candidate";
+
+pub struct AlbumDuplicateCandidateRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+impl AlbumDuplicateCandidateRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put_many(&self, candidates: Vec<AlbumDuplicateCandidate>) -> Result<()> {
+    self
+      .doc_store
+      .put_many(
+        COLLECTION,
+        candidates
+          .into_iter()
+          .map(|candidate| (candidate.key(), candidate, None))
+          .collect(),
+      )
+      .await
+  }
+
+  pub async fn put(&self, candidate: AlbumDuplicateCandidate) -> Result<()> {
+    self.put_many(vec![candidate]).await
+  }
+
+  pub async fn find(&self, a: &FileName, b: &FileName) -> Result<Option<AlbumDuplicateCandidate>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<AlbumDuplicateCandidate>(COLLECTION, &album_duplicate_candidate_key(a, b))
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Keys of the given ones that have already been detected, whether or not they were reviewed
+   */
+  pub async fn find_existing_keys(&self, keys: Vec<String>) -> Result<HashSet<String>> {
+    Ok(
+      self
+        .doc_store
+        .find_many_by_key::<AlbumDuplicateCandidate>(COLLECTION, keys)
+        .await?
+        .into_keys()
+        .collect(),
+    )
+  }
+
+  pub async fn find_many(
+    &self,
+    status: Option<AlbumDuplicateCandidateStatus>,
+  ) -> Result<Vec<AlbumDuplicateCandidate>> {
+    let mut filter = DocumentFilter::new();
+    if let Some(status) = status {
+      filter.condition("status", "=", status.to_string());
+    }
+    Ok(
+      self
+        .doc_store
+        .find_many::<AlbumDuplicateCandidate>(COLLECTION, filter, None)
+        .await?
+        .documents
+        .into_iter()
+        .map(|doc| doc.document)
+        .collect(),
+    )
+  }
+
+  /**
+   * Albums confirmed as duplicates of the original
+   */
+  pub async fn find_confirmed_duplicates(&self, original: &FileName) -> Result<Vec<FileName>> {
+    Ok(
+      self
+        .doc_store
+        .find_many::<AlbumDuplicateCandidate>(
+          COLLECTION,
+          DocumentFilter::new()
+            .condition("original_file_name", "=", original.to_string())
+            .condition(
+              "status",
+              "=",
+              AlbumDuplicateCandidateStatus::Confirmed.to_string(),
+            )
+            .build(),
+          None,
+        )
+        .await?
+        .documents
+        .into_iter()
+        .map(|doc| doc.document.duplicate_file_name)
+        .collect(),
+    )
+  }
+}
@@ -26,6 +26,11 @@ impl From<&ParsedTrack> for AlbumReadModelTrack {
       duration_seconds: parsed_track.duration_seconds,
       rating: parsed_track.rating,
       position: parsed_track.position.clone(),
+      artists: parsed_track
+        .artists
+        .iter()
+        .map(AlbumReadModelArtist::from)
+        .collect(),
     }
   }
 }
@@ -1,26 +1,62 @@
 use super::{
+  album_cluster_repository::AlbumClusterRepository,
+  album_clustering::{kmeans, nearest_centroid, AlbumCluster, AlbumClusterTally},
+  album_digest::{AlbumDigest, DIGEST_PAGE_SIZE},
+  album_duplicate_candidate::{
+    album_duplicate_candidate_key, find_album_duplicate_candidates, AlbumDuplicateCandidate,
+    AlbumDuplicateCandidateStatus, DuplicateDetectionAlbum,
+  },
+  album_duplicate_candidate_repository::AlbumDuplicateCandidateRepository,
+  album_query_translation::AlbumQueryVocabulary,
   album_read_model::AlbumReadModel,
-  album_repository::{AlbumRepository, GenreAggregate, ItemAndCount},
+  album_repository::{
+    AlbumNotes, AlbumRepository, DescriptorCoOccurrences, GenreAggregate, ItemAndCount,
+  },
+  album_search_boost_profile::AlbumSearchBoostProfile,
+  album_search_boost_profile_repository::AlbumSearchBoostProfileRepository,
+  album_search_cursor::AlbumSearchCursor,
   album_search_index::{
-    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchIndex, AlbumSearchQuery, AlbumSearchResult,
+    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchFacets, AlbumSearchIndex, AlbumSearchQuery,
+    AlbumSearchResult,
   },
+  album_tags::normalize_tags,
 };
 use crate::{
+  artists::artist_alias_repository::ArtistAliasRepository,
   events::{
     event::{Event, EventPayloadBuilder, Topic},
     event_publisher::EventPublisher,
   },
   files::file_metadata::file_name::FileName,
-  helpers::{embedding::EmbeddingDocument, redisearch::SearchPagination},
+  helpers::{
+    document_store::DocumentStore, embedding::EmbeddingDocument, projection::pca,
+    redisearch::SearchPagination,
+  },
+  sqlite::SqliteConnection,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Utc};
 use iter_tools::Itertools;
+use rand::seq::index::sample;
 use std::{
   collections::{HashMap, HashSet},
   sync::Arc,
 };
-use tokio::try_join;
-use tracing::{error, instrument};
+use tokio::{task::spawn_blocking, try_join};
+use tracing::{error, info, instrument};
+
+const SEARCH_PAGE_SIZE: usize = 500;
+const MAX_RANDOM_ALBUMS: usize = 100;
+pub const MAX_PROJECTED_ALBUMS: usize = 5000;
+/**
+ * Centroids are trained on a sample this size, every album is then assigned to its nearest one
+ */
+const CLUSTERING_SAMPLE_SIZE: usize = 20_000;
+const CLUSTERING_BATCH_SIZE: usize = 500;
+/**
+ * Enough facet values to cover every genre and most descriptors in a typical catalog
+ */
+const QUERY_VOCABULARY_SIZE: usize = 5000;
 
 pub struct AlbumMonitor {
   pub album_count: u32,
@@ -36,10 +72,19 @@ pub struct AlbumMonitor {
   pub aggregated_years: Vec<ItemAndCount>,
 }
 
+pub struct AlbumEmbeddingProjection {
+  pub albums: Vec<(AlbumReadModel, Vec<f32>)>,
+  pub explained_variance_ratios: Vec<f32>,
+}
+
 pub struct AlbumInteractor {
   album_repository: Arc<AlbumRepository>,
   album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
   event_publisher: Arc<EventPublisher>,
+  search_boost_profile_repository: AlbumSearchBoostProfileRepository,
+  duplicate_candidate_repository: AlbumDuplicateCandidateRepository,
+  artist_alias_repository: ArtistAliasRepository,
+  cluster_repository: AlbumClusterRepository,
 }
 
 impl AlbumInteractor {
@@ -47,14 +92,41 @@ impl AlbumInteractor {
     album_repository: Arc<AlbumRepository>,
     album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
     event_publisher: Arc<EventPublisher>,
+    doc_store: Arc<DocumentStore>,
+    sqlite_connection: Arc<SqliteConnection>,
   ) -> Self {
     Self {
       album_repository,
       album_search_index,
       event_publisher,
+      search_boost_profile_repository: AlbumSearchBoostProfileRepository::new(Arc::clone(
+        &doc_store,
+      )),
+      duplicate_candidate_repository: AlbumDuplicateCandidateRepository::new(doc_store),
+      artist_alias_repository: ArtistAliasRepository::new(Arc::clone(&sqlite_connection)),
+      cluster_repository: AlbumClusterRepository::new(sqlite_connection),
     }
   }
 
+  pub async fn setup_search_index(&self) -> Result<()> {
+    self.album_search_index.setup_index().await
+  }
+
+  pub async fn count_albums(&self) -> Result<u32> {
+    self.album_repository.count_albums().await
+  }
+
+  pub async fn get_average_rating(&self) -> Result<f32> {
+    self.album_repository.get_average_rating().await
+  }
+
+  /**
+   * Writes albums to the search index only, leaving the repository and events untouched
+   */
+  pub async fn reindex_many(&self, albums: Vec<AlbumReadModel>) -> Result<()> {
+    self.album_search_index.put_many(albums).await
+  }
+
   #[instrument(skip(self))]
   pub async fn get_monitor(&self) -> Result<AlbumMonitor> {
     let (
@@ -134,6 +206,16 @@ impl AlbumInteractor {
       .iter()
       .map(|album| album.file_name.clone())
       .collect::<Vec<FileName>>();
+    // Reviewed duplicates don't share the exact name, they'd be dropped otherwise
+    for confirmed_duplicate in self
+      .duplicate_candidate_repository
+      .find_confirmed_duplicates(&original_album.file_name)
+      .await?
+    {
+      if !duplicates.contains(&confirmed_duplicate) {
+        duplicates.push(confirmed_duplicate);
+      }
+    }
     duplicates.sort();
 
     let original_album_file_name = original_album.file_name.clone();
@@ -146,7 +228,7 @@ impl AlbumInteractor {
       self.album_search_index.put(original_album).await?;
     }
 
-    for mut duplicate_album in duplicate_albums.into_iter() {
+    for duplicate_album in duplicate_albums.into_iter() {
       if duplicate_album
         .duplicate_of
         .as_ref()
@@ -154,24 +236,154 @@ impl AlbumInteractor {
         .unwrap_or(true)
       {
         self
-          .album_repository
-          .set_duplicate_of(&duplicate_album.file_name, &original_album_file_name)
+          .mark_duplicate_of(duplicate_album, &original_album_file_name)
           .await?;
-        duplicate_album.duplicate_of = Some(original_album_file_name.clone());
-        self.album_search_index.put(duplicate_album).await?;
       }
     }
 
     Ok(())
   }
 
+  async fn mark_duplicate_of(
+    &self,
+    mut duplicate_album: AlbumReadModel,
+    original_album_file_name: &FileName,
+  ) -> Result<()> {
+    self
+      .album_repository
+      .set_duplicate_of(&duplicate_album.file_name, original_album_file_name)
+      .await?;
+    duplicate_album.duplicate_of = Some(original_album_file_name.clone());
+    self
+      .event_publisher
+      .publish(
+        Topic::Album,
+        EventPayloadBuilder::default()
+          .key(format!(
+            "duplicate:{}",
+            duplicate_album.file_name.to_string()
+          ))
+          .event(Event::AlbumMarkedDuplicate {
+            file_name: duplicate_album.file_name.clone(),
+            duplicate_of: original_album_file_name.clone(),
+          })
+          .build()?,
+      )
+      .await?;
+    self.album_search_index.put(duplicate_album).await
+  }
+
+  /**
+   * Scans the whole catalog for likely duplicates that don't share an exact name, and stores the
+   * pairs not seen before for review. Returns how many new pairs were found.
+   */
+  #[instrument(skip(self), name = "AlbumInteractor::detect_duplicate_candidates")]
+  pub async fn detect_duplicate_candidates(&self) -> Result<usize> {
+    let mut albums: Vec<DuplicateDetectionAlbum> = vec![];
+    let mut after: Option<FileName> = None;
+    loop {
+      let page = self
+        .find_page(after.clone(), SEARCH_PAGE_SIZE as u32)
+        .await?;
+      let page_size = page.len();
+      after = page.last().map(|album| album.file_name.clone());
+      albums.extend(page.iter().map(DuplicateDetectionAlbum::from));
+      if page_size < SEARCH_PAGE_SIZE {
+        break;
+      }
+    }
+
+    let candidates = find_album_duplicate_candidates(&albums, Utc::now().naive_utc());
+    let existing_keys = self
+      .duplicate_candidate_repository
+      .find_existing_keys(candidates.iter().map(|candidate| candidate.key()).collect())
+      .await?;
+    let new_candidates = candidates
+      .into_iter()
+      .filter(|candidate| !existing_keys.contains(&candidate.key()))
+      .collect::<Vec<_>>();
+    let count = new_candidates.len();
+    if count > 0 {
+      self
+        .duplicate_candidate_repository
+        .put_many(new_candidates)
+        .await?;
+    }
+    Ok(count)
+  }
+
+  pub async fn find_duplicate_candidates(
+    &self,
+    status: Option<AlbumDuplicateCandidateStatus>,
+  ) -> Result<Vec<AlbumDuplicateCandidate>> {
+    self.duplicate_candidate_repository.find_many(status).await
+  }
+
+  /**
+   * Confirming marks the pair's less rated album as a duplicate of the other, rejecting keeps the
+   * pair from being detected again
+   */
+  #[instrument(skip(self), name = "AlbumInteractor::resolve_duplicate_candidate")]
+  pub async fn resolve_duplicate_candidate(
+    &self,
+    a: &FileName,
+    b: &FileName,
+    confirm: bool,
+  ) -> Result<AlbumDuplicateCandidate> {
+    let mut candidate = self
+      .duplicate_candidate_repository
+      .find(a, b)
+      .await?
+      .ok_or_else(|| {
+        anyhow!(
+          "Duplicate candidate not found: {}",
+          album_duplicate_candidate_key(a, b)
+        )
+      })?;
+    if confirm {
+      let mut original_album = self
+        .album_repository
+        .get(&candidate.original_file_name)
+        .await?;
+      let duplicate_album = self
+        .album_repository
+        .get(&candidate.duplicate_file_name)
+        .await?;
+      if !original_album
+        .duplicates
+        .contains(&candidate.duplicate_file_name)
+      {
+        original_album
+          .duplicates
+          .push(candidate.duplicate_file_name.clone());
+        original_album.duplicates.sort();
+        self
+          .album_repository
+          .set_duplicates(&original_album.file_name, original_album.duplicates.clone())
+          .await?;
+        self.album_search_index.put(original_album).await?;
+      }
+      self
+        .mark_duplicate_of(duplicate_album, &candidate.original_file_name)
+        .await?;
+      candidate.status = AlbumDuplicateCandidateStatus::Confirmed;
+    } else {
+      candidate.status = AlbumDuplicateCandidateStatus::Rejected;
+    }
+    self
+      .duplicate_candidate_repository
+      .put(candidate.clone())
+      .await?;
+    Ok(candidate)
+  }
+
   #[instrument(skip_all, name = "AlbumInteractor::put_many", fields(count = albums.len()))]
   pub async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<()> {
     let album_file_names = albums
       .iter()
       .map(|album| album.file_name.clone())
       .collect::<Vec<_>>();
-    self.album_repository.put_many(albums.clone()).await?;
+    let albums = self.album_repository.put_many(albums).await?;
     self.album_search_index.put_many(albums.clone()).await?;
     for album in albums.iter() {
       if let Err(err) = self.process_duplicates(album).await {
@@ -216,6 +428,19 @@ impl AlbumInteractor {
     let album = self.album_repository.get(file_name).await?;
     self.album_repository.delete(file_name).await?;
     self.album_search_index.delete(file_name).await?;
+    // Keyed like the album's saved event, so the deletion supersedes it in the stream
+    self
+      .event_publisher
+      .publish(
+        Topic::Album,
+        EventPayloadBuilder::default()
+          .key(file_name.clone())
+          .event(Event::AlbumDeleted {
+            file_name: file_name.clone(),
+          })
+          .build()?,
+      )
+      .await?;
     // If this album is a duplicate, we need to re-process the original album.
     // If this album has duplicates, we need to re-process them. It is enough to only re-process the first duplicate, as that will cascade to the rest.
     if let Some(duplicate_of) = &album.duplicate_of.as_ref().or(album.duplicates.first()) {
@@ -247,6 +472,17 @@ impl AlbumInteractor {
     self.album_repository.find(file_name).await
   }
 
+  pub async fn find_stale_discogs_releases(
+    &self,
+    refreshed_before: NaiveDateTime,
+    limit: u32,
+  ) -> Result<Vec<FileName>> {
+    self
+      .album_repository
+      .find_stale_discogs_releases(refreshed_before, limit)
+      .await
+  }
+
   pub async fn get(&self, file_name: &FileName) -> Result<AlbumReadModel> {
     self.album_repository.get(file_name).await
   }
@@ -255,12 +491,243 @@ impl AlbumInteractor {
     self.album_repository.get_many(file_names).await
   }
 
+  /**
+   * Replaces the album's tags and reindexes it so tag filters pick the change up. Returns the
+   * normalized tags.
+   */
+  pub async fn set_tags(&self, file_name: &FileName, tags: Vec<String>) -> Result<Vec<String>> {
+    let tags = normalize_tags(tags)?;
+    self.album_repository.get(file_name).await?;
+    self
+      .album_repository
+      .set_tags(file_name, tags.clone())
+      .await?;
+    let album = self.album_repository.get(file_name).await?;
+    self.album_search_index.put(album).await?;
+    Ok(tags)
+  }
+
+  pub async fn get_aggregated_tags(&self, limit: Option<u32>) -> Result<Vec<ItemAndCount>> {
+    self.album_repository.get_aggregated_tags(limit).await
+  }
+
+  pub async fn get_descriptor_co_occurrences(&self) -> Result<DescriptorCoOccurrences> {
+    self.album_repository.get_descriptor_co_occurrences().await
+  }
+
+  pub async fn find_notes(&self, file_name: &FileName) -> Result<Option<AlbumNotes>> {
+    self.album_repository.find_notes(file_name).await
+  }
+
+  /**
+   * Blank notes clear the album's notes
+   */
+  pub async fn set_notes(&self, file_name: &FileName, notes: Option<String>) -> Result<()> {
+    self.album_repository.get(file_name).await?;
+    let notes = notes.filter(|notes| !notes.trim().is_empty());
+    self.album_repository.set_notes(file_name, notes).await
+  }
+
+  /**
+   * Widens artist filters to every alias of the artists, so an artist's albums are found whichever
+   * file name they were filed under
+   */
+  async fn with_artist_aliases(&self, query: &AlbumSearchQuery) -> Result<AlbumSearchQuery> {
+    let mut query = query.clone();
+    if query.include_artists.is_empty() && query.exclude_artists.is_empty() {
+      return Ok(query);
+    }
+    let aliases = self.artist_alias_repository.get_aliases().await?;
+    if !aliases.is_empty() {
+      query.include_artists = aliases.expand(&query.include_artists);
+      query.exclude_artists = aliases.expand(&query.exclude_artists);
+    }
+    Ok(query)
+  }
+
+  pub fn supports_search_boost_profiles(&self) -> bool {
+    self.album_search_index.supports_boost_profiles()
+  }
+
   pub async fn search(
     &self,
     query: &AlbumSearchQuery,
     pagination: Option<&SearchPagination>,
   ) -> Result<AlbumSearchResult> {
-    self.album_search_index.search(query, pagination).await
+    let query = self.with_artist_aliases(query).await?;
+    self.album_search_index.search(&query, pagination).await
+  }
+
+  pub async fn search_from_cursor(
+    &self,
+    query: &AlbumSearchQuery,
+    cursor: Option<&AlbumSearchCursor>,
+    limit: usize,
+  ) -> Result<(AlbumSearchResult, Option<AlbumSearchCursor>)> {
+    let query = self.with_artist_aliases(query).await?;
+    self
+      .album_search_index
+      .search_from_cursor(&query, cursor, limit)
+      .await
+  }
+
+  pub async fn get_search_facets(
+    &self,
+    query: &AlbumSearchQuery,
+    limit: usize,
+  ) -> Result<AlbumSearchFacets> {
+    let query = self.with_artist_aliases(query).await?;
+    self.album_search_index.get_facets(&query, limit).await
+  }
+
+  /**
+   * Every primary genre, language and descriptor in the catalog, for translating free-text queries
+   */
+  pub async fn get_query_vocabulary(&self) -> Result<AlbumQueryVocabulary> {
+    self
+      .album_search_index
+      .get_facets(&AlbumSearchQuery::default(), QUERY_VOCABULARY_SIZE)
+      .await
+      .map(Into::into)
+  }
+
+  /**
+   * Up to `count` albums picked uniformly at random from those matching the query
+   */
+  #[instrument(skip(self))]
+  pub async fn get_random_albums(
+    &self,
+    query: &AlbumSearchQuery,
+    count: usize,
+  ) -> Result<Vec<AlbumReadModel>> {
+    let query = self.with_artist_aliases(query).await?;
+    self
+      .album_search_index
+      .get_random(&query, count.min(MAX_RANDOM_ALBUMS))
+      .await
+  }
+
+  /**
+   * File names of every album matching the query, up to `limit`, fetched a page at a time
+   */
+  pub async fn find_matching_file_names(
+    &self,
+    query: &AlbumSearchQuery,
+    limit: Option<usize>,
+  ) -> Result<Vec<FileName>> {
+    let query = self.with_artist_aliases(query).await?;
+    let mut file_names: Vec<FileName> = vec![];
+    loop {
+      let remaining = limit.map_or(SEARCH_PAGE_SIZE, |limit| {
+        limit.saturating_sub(file_names.len()).min(SEARCH_PAGE_SIZE)
+      });
+      if remaining == 0 {
+        return Ok(file_names);
+      }
+      let result = self
+        .album_search_index
+        .search(
+          &query,
+          Some(&SearchPagination {
+            offset: Some(file_names.len()),
+            limit: Some(remaining),
+          }),
+        )
+        .await?;
+      let page_size = result.albums.len();
+      file_names.extend(result.albums.into_iter().map(|album| album.file_name));
+      if page_size < remaining {
+        return Ok(file_names);
+      }
+    }
+  }
+
+  pub async fn find_search_boost_profile(
+    &self,
+    name: &str,
+  ) -> Result<Option<AlbumSearchBoostProfile>> {
+    self.search_boost_profile_repository.find(name).await
+  }
+
+  pub async fn find_search_boost_profiles(&self) -> Result<Vec<AlbumSearchBoostProfile>> {
+    self.search_boost_profile_repository.find_all().await
+  }
+
+  pub async fn put_search_boost_profile(&self, profile: AlbumSearchBoostProfile) -> Result<()> {
+    profile.validate()?;
+    self.search_boost_profile_repository.put(profile).await
+  }
+
+  pub async fn delete_search_boost_profile(&self, name: &str) -> Result<()> {
+    self.search_boost_profile_repository.delete(name).await
+  }
+
+  /**
+   * A page of albums in file name order, starting after `after`
+   */
+  pub async fn find_page(
+    &self,
+    after: Option<FileName>,
+    limit: u32,
+  ) -> Result<Vec<AlbumReadModel>> {
+    let file_names = self
+      .album_repository
+      .find_file_names_after(after, limit)
+      .await?;
+    let mut albums = self.album_repository.find_many(file_names).await?;
+    albums.sort_by_key(|album| album.file_name.to_string());
+    Ok(albums)
+  }
+
+  /**
+   * Digests of a page of albums in file name order, for comparing corpora across instances
+   */
+  pub async fn find_digests(
+    &self,
+    after: Option<FileName>,
+    limit: u32,
+  ) -> Result<Vec<AlbumDigest>> {
+    let file_names = self
+      .album_repository
+      .find_file_names_after(after, limit)
+      .await?;
+    self
+      .album_repository
+      .find_many(file_names)
+      .await?
+      .into_iter()
+      .map(|album| {
+        Ok(AlbumDigest {
+          hash: album.content_digest()?,
+          file_name: album.file_name,
+        })
+      })
+      .collect::<Result<Vec<_>>>()
+      .map(|mut digests| {
+        digests.sort_by_key(|digest| digest.file_name.to_string());
+        digests
+      })
+  }
+
+  pub async fn find_all_digests(&self) -> Result<Vec<AlbumDigest>> {
+    let mut digests: Vec<AlbumDigest> = vec![];
+    loop {
+      let page = self
+        .find_digests(
+          digests.last().map(|digest| digest.file_name.clone()),
+          DIGEST_PAGE_SIZE,
+        )
+        .await?;
+      let page_size = page.len();
+      digests.extend(page);
+      if page_size < DIGEST_PAGE_SIZE as usize {
+        return Ok(digests);
+      }
+    }
+  }
+
+  pub async fn filter_existing(&self, file_names: Vec<FileName>) -> Result<Vec<FileName>> {
+    self.album_repository.filter_existing(file_names).await
   }
 
   pub async fn find_many_embeddings(
@@ -290,9 +757,15 @@ impl AlbumInteractor {
     &self,
     query: &AlbumEmbeddingSimilarirtySearchQuery,
   ) -> Result<Vec<(AlbumReadModel, f32)>> {
+    let query = AlbumEmbeddingSimilarirtySearchQuery {
+      embedding: query.embedding.clone(),
+      embedding_key: query.embedding_key.clone(),
+      filters: self.with_artist_aliases(&query.filters).await?,
+      limit: query.limit,
+    };
     self
       .album_search_index
-      .embedding_similarity_search(query)
+      .embedding_similarity_search(&query)
       .await
   }
 
@@ -320,6 +793,12 @@ impl AlbumInteractor {
           .iter()
           .map(|credit| credit.artist.file_name.clone())
           .chain(album.artists.iter().map(|artist| artist.file_name.clone()))
+          .chain(
+            album
+              .track_artists()
+              .into_iter()
+              .map(|artist| artist.file_name),
+          )
       })
       .collect::<HashSet<_>>()
       .into_iter()
@@ -327,6 +806,169 @@ impl AlbumInteractor {
     Ok(artist_file_names)
   }
 
+  /**
+   * Principal component projection of the embeddings of up to `limit` albums matching the query,
+   * in search order. Albums without an embedding under the key are left out.
+   */
+  #[instrument(skip(self))]
+  pub async fn project_embeddings(
+    &self,
+    query: &AlbumSearchQuery,
+    embedding_key: &str,
+    dimensions: usize,
+    limit: usize,
+  ) -> Result<AlbumEmbeddingProjection> {
+    let albums = self
+      .search(
+        query,
+        Some(&SearchPagination {
+          offset: Some(0),
+          limit: Some(limit.min(MAX_PROJECTED_ALBUMS)),
+        }),
+      )
+      .await?
+      .albums;
+    let mut embeddings = self
+      .find_many_embeddings(
+        albums.iter().map(|album| album.file_name.clone()).collect(),
+        embedding_key,
+      )
+      .await?
+      .into_iter()
+      .map(|doc| (doc.file_name, doc.embedding))
+      .collect::<HashMap<_, _>>();
+    let (albums, embeddings): (Vec<_>, Vec<_>) = albums
+      .into_iter()
+      .filter_map(|album| {
+        embeddings
+          .remove(&album.file_name)
+          .map(|embedding| (album, embedding))
+      })
+      .unzip();
+    let projection = pca(&embeddings, dimensions);
+    Ok(AlbumEmbeddingProjection {
+      albums: albums.into_iter().zip(projection.points).collect(),
+      explained_variance_ratios: projection.explained_variance_ratios,
+    })
+  }
+
+  pub async fn get_clusters(&self) -> Result<Vec<AlbumCluster>> {
+    self.cluster_repository.get_clusters().await
+  }
+
+  /**
+   * Runs k-means over the album embeddings under the key, replacing the previous run's clusters.
+   * Albums without an embedding are left out of every cluster, and only albums whose cluster
+   * changed are reindexed.
+   */
+  #[instrument(skip(self))]
+  pub async fn cluster_albums(
+    &self,
+    embedding_key: &str,
+    cluster_count: usize,
+    max_iterations: usize,
+  ) -> Result<Vec<AlbumCluster>> {
+    let file_names = self
+      .find_all_digests()
+      .await?
+      .into_iter()
+      .map(|digest| digest.file_name)
+      .collect::<Vec<_>>();
+    let sample_file_names = sample(
+      &mut rand::thread_rng(),
+      file_names.len(),
+      CLUSTERING_SAMPLE_SIZE.min(file_names.len()),
+    )
+    .into_iter()
+    .map(|i| file_names[i].clone())
+    .collect::<Vec<_>>();
+    let mut sample_embeddings = vec![];
+    for chunk in sample_file_names.chunks(CLUSTERING_BATCH_SIZE) {
+      sample_embeddings.extend(
+        self
+          .find_many_embeddings(chunk.to_vec(), embedding_key)
+          .await?
+          .into_iter()
+          .map(|doc| doc.embedding),
+      );
+    }
+    if sample_embeddings.is_empty() {
+      return Err(anyhow!(
+        "No album embeddings found for key {}",
+        embedding_key
+      ));
+    }
+    let centroids = Arc::new(
+      spawn_blocking(move || {
+        kmeans(
+          &sample_embeddings,
+          cluster_count,
+          max_iterations,
+          &mut rand::thread_rng(),
+        )
+      })
+      .await?,
+    );
+
+    let mut tallies = centroids
+      .iter()
+      .map(|_| AlbumClusterTally::default())
+      .collect::<Vec<_>>();
+    let mut assignments = vec![];
+    let mut changed_file_names = vec![];
+    for chunk in file_names.chunks(CLUSTERING_BATCH_SIZE) {
+      let (embeddings, mut albums) = try_join!(
+        self.find_many_embeddings(chunk.to_vec(), embedding_key),
+        self.find_many(chunk.to_vec())
+      )?;
+      let chunk_centroids = Arc::clone(&centroids);
+      let cluster_ids = spawn_blocking(move || {
+        embeddings
+          .into_iter()
+          .map(|doc| {
+            let cluster_id = nearest_centroid(&chunk_centroids, &doc.embedding) as u32;
+            (doc.file_name, cluster_id)
+          })
+          .collect::<HashMap<_, _>>()
+      })
+      .await?;
+      for file_name in chunk {
+        let Some(album) = albums.remove(file_name) else {
+          continue;
+        };
+        let cluster_id = cluster_ids.get(file_name).copied();
+        if let Some(cluster_id) = cluster_id {
+          tallies[cluster_id as usize].add(&album);
+          assignments.push((file_name.clone(), cluster_id));
+        }
+        if album.cluster_id != cluster_id {
+          changed_file_names.push(file_name.clone());
+        }
+      }
+    }
+
+    let created_at = Utc::now().naive_utc();
+    let clusters = tallies
+      .into_iter()
+      .enumerate()
+      .map(|(id, tally)| tally.into_cluster(id as u32, embedding_key, created_at))
+      .collect::<Vec<_>>();
+    self
+      .cluster_repository
+      .put_all(assignments, clusters.clone())
+      .await?;
+    for chunk in changed_file_names.chunks(CLUSTERING_BATCH_SIZE) {
+      let albums = self.album_repository.find_many(chunk.to_vec()).await?;
+      self.album_search_index.put_many(albums).await?;
+    }
+    info!(
+      clusters = clusters.len(),
+      reindexed = changed_file_names.len(),
+      "Clustered albums"
+    );
+    Ok(clusters)
+  }
+
   #[instrument(skip(self))]
   pub async fn find_similar_albums(
     &self,
@@ -0,0 +1,94 @@
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tracing::info;
+
+async fn detect_album_duplicates(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let count = app_context
+    .album_interactor
+    .detect_duplicate_candidates()
+    .await?;
+  info!(count, "Detected album duplicate candidates");
+  Ok(())
+}
+
+async fn cluster_albums(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let settings = &app_context.settings.album_clustering;
+  let Some(embedding_key) = settings.embedding_key.as_ref() else {
+    return Ok(());
+  };
+  app_context
+    .album_interactor
+    .cluster_albums(
+      embedding_key,
+      settings.cluster_count as usize,
+      settings.max_iterations as usize,
+    )
+    .await?;
+  Ok(())
+}
+
+pub async fn setup_album_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::DetectAlbumDuplicates)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(detect_album_duplicates))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::DetectAlbumDuplicates)
+        .interval(TimeDelta::try_weeks(1).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  if app_context
+    .settings
+    .album_clustering
+    .embedding_key
+    .is_some()
+  {
+    app_context
+      .scheduler
+      .register(
+        JobProcessorBuilder::default()
+          .name(JobName::ClusterAlbums)
+          .app_context(Arc::clone(&app_context))
+          .executor(job_executor!(cluster_albums))
+          .build()?,
+      )
+      .await;
+
+    app_context
+      .scheduler
+      .put(
+        JobParametersBuilder::default()
+          .name(JobName::ClusterAlbums)
+          .interval(
+            TimeDelta::try_days(app_context.settings.album_clustering.interval_days as i64)
+              .unwrap(),
+          )
+          .build()?,
+      )
+      .await?;
+  }
+
+  Ok(())
+}
@@ -0,0 +1,520 @@
+use super::{
+  album_repository::ItemAndCount,
+  album_search_index::{AlbumSearchFacets, AlbumSearchQuery},
+};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde_derive::Deserialize;
+use std::cmp::Reverse;
+
+/**
+ * Albums with fewer ratings than this count as obscure, those with more than
+ * `POPULAR_MIN_RATING_COUNT` as popular
+ */
+pub const OBSCURE_MAX_RATING_COUNT: u32 = 1000;
+pub const POPULAR_MIN_RATING_COUNT: u32 = 5000;
+pub const ACCLAIMED_MIN_RATING: f32 = 3.5;
+
+const NEGATIONS: [&str; 8] = [
+  "no", "not", "nothing", "without", "except", "avoid", "dont", "never",
+];
+const OBSCURE_PHRASES: [&str; 7] = [
+  "obscure",
+  "underground",
+  "hidden gem",
+  "hidden gems",
+  "lesser known",
+  "underrated",
+  "deep cuts",
+];
+const POPULAR_PHRASES: [&str; 5] = ["popular", "well known", "mainstream", "famous", "hits"];
+const ACCLAIMED_PHRASES: [&str; 5] = [
+  "acclaimed",
+  "highly rated",
+  "well rated",
+  "classic",
+  "classics",
+];
+/**
+ * Everyday phrasings of terms in the catalog's vocabulary
+ */
+const SYNONYMS: [(&str, &str); 8] = [
+  ("female vocals", "female vocalist"),
+  ("female singer", "female vocalist"),
+  ("male vocals", "male vocalist"),
+  ("male singer", "male vocalist"),
+  ("moody", "melancholic"),
+  ("sad", "melancholic"),
+  ("upbeat", "energetic"),
+  ("chill", "calm"),
+];
+const STOPWORDS: [&str; 40] = [
+  "a",
+  "an",
+  "and",
+  "or",
+  "the",
+  "of",
+  "with",
+  "from",
+  "in",
+  "by",
+  "for",
+  "to",
+  "some",
+  "any",
+  "anything",
+  "something",
+  "too",
+  "very",
+  "really",
+  "that",
+  "is",
+  "are",
+  "i",
+  "me",
+  "want",
+  "give",
+  "show",
+  "find",
+  "like",
+  "music",
+  "album",
+  "albums",
+  "record",
+  "records",
+  "stuff",
+  "songs",
+  "sounding",
+  "era",
+  "vibes",
+  "please",
+];
+
+lazy_static! {
+  static ref SEGMENT_SEPARATOR_RE: Regex = Regex::new(r"[,;]| but ").unwrap();
+  static ref DECADE_RE: Regex =
+    Regex::new(r"\b(?:(early|mid|late) )?(1[0-9]|20)?([0-9])0s\b").unwrap();
+  static ref BEFORE_RE: Regex = Regex::new(r"\b(?:before|pre) ([12][0-9]{3})\b").unwrap();
+  static ref AFTER_RE: Regex = Regex::new(r"\b(after|since) ([12][0-9]{3})\b").unwrap();
+  static ref YEAR_RE: Regex = Regex::new(r"\b(?:in|from) ([12][0-9]{3})\b").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TermKind {
+  PrimaryGenre,
+  Language,
+  Descriptor,
+}
+
+#[derive(Debug, Clone)]
+struct VocabularyTerm {
+  normalized: String,
+  value: String,
+  kind: TermKind,
+}
+
+/**
+ * The genres, languages and descriptors a query can be translated to, longest first so
+ * "post-punk" is matched before "punk"
+ */
+#[derive(Debug, Clone, Default)]
+pub struct AlbumQueryVocabulary {
+  terms: Vec<VocabularyTerm>,
+}
+
+impl AlbumQueryVocabulary {
+  pub fn new(
+    primary_genres: Vec<String>,
+    languages: Vec<String>,
+    descriptors: Vec<String>,
+  ) -> Self {
+    let mut terms = [
+      (TermKind::PrimaryGenre, primary_genres),
+      (TermKind::Language, languages),
+      (TermKind::Descriptor, descriptors),
+    ]
+    .into_iter()
+    .flat_map(|(kind, values)| {
+      values.into_iter().map(move |value| VocabularyTerm {
+        normalized: normalize(&value),
+        value,
+        kind,
+      })
+    })
+    .filter(|term| !term.normalized.is_empty())
+    .collect::<Vec<_>>();
+    terms.sort_by_key(|term| Reverse(term.normalized.split(' ').count()));
+    Self { terms }
+  }
+
+  fn find(&self, value: &str) -> Option<&VocabularyTerm> {
+    let normalized = apply_synonyms(&normalize(value));
+    self.terms.iter().find(|term| term.normalized == normalized)
+  }
+}
+
+impl From<AlbumSearchFacets> for AlbumQueryVocabulary {
+  fn from(facets: AlbumSearchFacets) -> Self {
+    let names =
+      |items: Vec<ItemAndCount>| items.into_iter().map(|item| item.name).collect::<Vec<_>>();
+    Self::new(
+      names(facets.primary_genres),
+      names(facets.languages),
+      names(facets.descriptors),
+    )
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct AlbumQueryTranslation {
+  pub query: AlbumSearchQuery,
+  /**
+   * Words the rules couldn't place, left out of the query
+   */
+  pub unmatched_terms: Vec<String>,
+}
+
+/**
+ * What a language model made of a query, before it's checked against the vocabulary
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AlbumQueryHints {
+  pub include_genres: Vec<String>,
+  pub exclude_genres: Vec<String>,
+  pub include_descriptors: Vec<String>,
+  pub exclude_descriptors: Vec<String>,
+  pub include_languages: Vec<String>,
+  pub exclude_languages: Vec<String>,
+  pub min_release_year: Option<u32>,
+  pub max_release_year: Option<u32>,
+  pub popular: Option<bool>,
+  pub min_rating: Option<f32>,
+}
+
+fn normalize(text: &str) -> String {
+  text
+    .to_lowercase()
+    .replace(['\'', '’'], "")
+    .chars()
+    .map(|c| {
+      if c.is_alphanumeric() || c == '&' {
+        c
+      } else {
+        ' '
+      }
+    })
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn apply_synonyms(text: &str) -> String {
+  let mut padded = format!(" {} ", text);
+  for (phrase, replacement) in SYNONYMS {
+    padded = padded.replace(&format!(" {} ", phrase), &format!(" {} ", replacement));
+  }
+  padded.trim().to_string()
+}
+
+/**
+ * Removes the first whole-word occurrence of `phrase`, returning whether there was one
+ */
+fn take_phrase(text: &mut String, phrase: &str) -> bool {
+  let padded = format!(" {} ", text);
+  match padded.find(&format!(" {} ", phrase)) {
+    Some(start) => {
+      let end = start + phrase.len() + 2;
+      *text = format!("{} {}", &padded[..start], &padded[end..])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+      true
+    }
+    None => false,
+  }
+}
+
+fn take_pattern(text: &mut String, pattern: &Regex, mut on_match: impl FnMut(&Captures)) {
+  for captures in pattern.captures_iter(text) {
+    on_match(&captures);
+  }
+  *text = pattern
+    .replace_all(text, " ")
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ");
+}
+
+/**
+ * Widens the query's release years to cover the range, so "70s or 80s" spans both decades
+ */
+fn include_release_years(query: &mut AlbumSearchQuery, min: Option<u32>, max: Option<u32>) {
+  if let Some(min) = min {
+    query.min_release_year = Some(query.min_release_year.map_or(min, |year| year.min(min)));
+  }
+  if let Some(max) = max {
+    query.max_release_year = Some(query.max_release_year.map_or(max, |year| year.max(max)));
+  }
+}
+
+fn take_release_years(text: &mut String, query: &mut AlbumSearchQuery) {
+  take_pattern(text, &DECADE_RE, |captures| {
+    let digit = captures[3].parse::<u32>().unwrap_or_default();
+    let century = match captures.get(2) {
+      Some(century) => century.as_str().parse::<u32>().unwrap_or_default() * 100,
+      None if digit >= 3 => 1900,
+      None => 2000,
+    };
+    let start = century + digit * 10;
+    let (min, max) = match captures.get(1).map(|part| part.as_str()) {
+      Some("early") => (start, start + 3),
+      Some("mid") => (start + 3, start + 6),
+      Some("late") => (start + 6, start + 9),
+      _ => (start, start + 9),
+    };
+    include_release_years(query, Some(min), Some(max));
+  });
+  take_pattern(text, &BEFORE_RE, |captures| {
+    let year = captures[1].parse::<u32>().unwrap_or_default();
+    include_release_years(query, None, Some(year.saturating_sub(1)));
+  });
+  take_pattern(text, &AFTER_RE, |captures| {
+    let year = captures[2].parse::<u32>().unwrap_or_default();
+    let min = if &captures[1] == "after" {
+      year + 1
+    } else {
+      year
+    };
+    include_release_years(query, Some(min), None);
+  });
+  take_pattern(text, &YEAR_RE, |captures| {
+    let year = captures[1].parse::<u32>().ok();
+    include_release_years(query, year, year);
+  });
+}
+
+fn set_popularity(query: &mut AlbumSearchQuery, popular: bool) {
+  if popular {
+    query.min_rating_count = Some(POPULAR_MIN_RATING_COUNT);
+  } else {
+    query.max_rating_count = Some(OBSCURE_MAX_RATING_COUNT);
+  }
+}
+
+fn take_popularity(text: &mut String, negated: bool, query: &mut AlbumSearchQuery) {
+  let mut take_any = |phrases: &[&str]| {
+    phrases
+      .iter()
+      .filter(|phrase| take_phrase(text, phrase))
+      .count()
+      > 0
+  };
+  let obscure = take_any(&OBSCURE_PHRASES);
+  let popular = take_any(&POPULAR_PHRASES);
+  let acclaimed = take_any(&ACCLAIMED_PHRASES);
+  if obscure {
+    set_popularity(query, negated);
+  }
+  if popular {
+    set_popularity(query, !negated);
+  }
+  if acclaimed && !negated {
+    query.min_rating = Some(ACCLAIMED_MIN_RATING);
+  }
+}
+
+fn add_term(query: &mut AlbumSearchQuery, term: &VocabularyTerm, negated: bool) {
+  let values = match (term.kind, negated) {
+    (TermKind::PrimaryGenre, false) => &mut query.include_primary_genres,
+    (TermKind::PrimaryGenre, true) => &mut query.exclude_primary_genres,
+    (TermKind::Language, false) => &mut query.include_languages,
+    (TermKind::Language, true) => &mut query.exclude_languages,
+    (TermKind::Descriptor, false) => &mut query.include_descriptors,
+    (TermKind::Descriptor, true) => &mut query.exclude_descriptors,
+  };
+  if !values.contains(&term.value) {
+    values.push(term.value.clone());
+  }
+}
+
+/**
+ * Splits a segment at its first negation, everything after it is excluded
+ */
+fn split_at_negation(segment: &str) -> (String, String) {
+  let words = segment.split(' ').collect::<Vec<_>>();
+  match words.iter().position(|word| NEGATIONS.contains(word)) {
+    Some(i) => (words[..i].join(" "), words[i + 1..].join(" ")),
+    None => (segment.to_string(), String::new()),
+  }
+}
+
+/**
+ * Rule-based translation of a free-text request into search filters. Each comma separated part
+ * is read on its own, and whatever follows a negation like "no" or "without" in a part is
+ * excluded rather than included.
+ */
+pub fn translate_query(text: &str, vocabulary: &AlbumQueryVocabulary) -> AlbumQueryTranslation {
+  let mut query = AlbumSearchQuery::default();
+  let mut unmatched_terms: Vec<String> = vec![];
+  for segment in SEGMENT_SEPARATOR_RE.split(&text.to_lowercase()) {
+    let mut segment = apply_synonyms(&normalize(segment));
+    take_release_years(&mut segment, &mut query);
+    let (positive, negative) = split_at_negation(&segment);
+    for (mut part, negated) in [(positive, false), (negative, true)] {
+      take_popularity(&mut part, negated, &mut query);
+      for term in &vocabulary.terms {
+        if take_phrase(&mut part, &term.normalized) {
+          add_term(&mut query, term, negated);
+        }
+      }
+      for word in part.split(' ') {
+        if !word.is_empty()
+          && !STOPWORDS.contains(&word)
+          && !NEGATIONS.contains(&word)
+          && !unmatched_terms.iter().any(|term| term == word)
+        {
+          unmatched_terms.push(word.to_string());
+        }
+      }
+    }
+  }
+  AlbumQueryTranslation {
+    query,
+    unmatched_terms,
+  }
+}
+
+/**
+ * Adds a language model's reading of the query to the rule-based one. Only terms in the
+ * vocabulary are kept, whatever kind the model gave them, and the rules win wherever both set a
+ * value.
+ */
+pub fn apply_hints(
+  translation: &mut AlbumQueryTranslation,
+  hints: AlbumQueryHints,
+  vocabulary: &AlbumQueryVocabulary,
+) {
+  let query = &mut translation.query;
+  let included = [
+    hints.include_genres,
+    hints.include_descriptors,
+    hints.include_languages,
+  ];
+  let excluded = [
+    hints.exclude_genres,
+    hints.exclude_descriptors,
+    hints.exclude_languages,
+  ];
+  for (values, negated) in included
+    .into_iter()
+    .map(|values| (values, false))
+    .chain(excluded.into_iter().map(|values| (values, true)))
+  {
+    for value in values {
+      if let Some(term) = vocabulary.find(&value) {
+        add_term(query, term, negated);
+      }
+    }
+  }
+  if query.min_release_year.is_none() && query.max_release_year.is_none() {
+    query.min_release_year = hints.min_release_year;
+    query.max_release_year = hints.max_release_year;
+  }
+  if query.min_rating_count.is_none() && query.max_rating_count.is_none() {
+    if let Some(popular) = hints.popular {
+      set_popularity(query, popular);
+    }
+  }
+  if query.min_rating.is_none() {
+    query.min_rating = hints
+      .min_rating
+      .filter(|rating| (0.0..=5.0).contains(rating));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn vocabulary() -> AlbumQueryVocabulary {
+    let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+    AlbumQueryVocabulary::new(
+      strings(&["Post-Punk", "Punk Rock", "Gothic Rock", "Synthpop"]),
+      strings(&["English", "French"]),
+      strings(&["melancholic", "female vocalist", "dark", "synths"]),
+    )
+  }
+
+  #[test]
+  fn test_translate_query() {
+    let translation = translate_query(
+      "moody post-punk from the 80s with female vocals, nothing too popular",
+      &vocabulary(),
+    );
+    let query = translation.query;
+    assert_eq!(query.include_primary_genres, vec!["Post-Punk"]);
+    assert_eq!(
+      query.include_descriptors,
+      vec!["female vocalist", "melancholic"]
+    );
+    assert_eq!(query.min_release_year, Some(1980));
+    assert_eq!(query.max_release_year, Some(1989));
+    assert_eq!(query.max_rating_count, Some(OBSCURE_MAX_RATING_COUNT));
+    assert_eq!(query.min_rating_count, None);
+    assert!(translation.unmatched_terms.is_empty());
+  }
+
+  #[test]
+  fn test_translate_query_exclusions_and_years() {
+    let translation = translate_query(
+      "dark gothic rock without synths, late 90s or 2000s, acclaimed but no synthpop, spooky",
+      &vocabulary(),
+    );
+    let query = translation.query;
+    assert_eq!(query.include_primary_genres, vec!["Gothic Rock"]);
+    assert_eq!(query.exclude_primary_genres, vec!["Synthpop"]);
+    assert_eq!(query.include_descriptors, vec!["dark"]);
+    assert_eq!(query.exclude_descriptors, vec!["synths"]);
+    assert_eq!(query.min_release_year, Some(1996));
+    assert_eq!(query.max_release_year, Some(2009));
+    assert_eq!(query.min_rating, Some(ACCLAIMED_MIN_RATING));
+    assert_eq!(translation.unmatched_terms, vec!["spooky"]);
+
+    let query = translate_query("french punk rock since 1977, before 1985", &vocabulary()).query;
+    assert_eq!(query.include_languages, vec!["French"]);
+    assert_eq!(query.include_primary_genres, vec!["Punk Rock"]);
+    assert_eq!(query.min_release_year, Some(1977));
+    assert_eq!(query.max_release_year, Some(1984));
+  }
+
+  #[test]
+  fn test_apply_hints() {
+    let vocabulary = vocabulary();
+    let mut translation = translate_query("spooky 80s records", &vocabulary);
+    apply_hints(
+      &mut translation,
+      AlbumQueryHints {
+        include_genres: vec!["gothic rock".to_string(), "Witch House".to_string()],
+        include_descriptors: vec!["Post-Punk".to_string()],
+        exclude_descriptors: vec!["sad".to_string()],
+        min_release_year: Some(1975),
+        popular: Some(true),
+        min_rating: Some(9.0),
+        ..Default::default()
+      },
+      &vocabulary,
+    );
+    let query = translation.query;
+    assert_eq!(
+      query.include_primary_genres,
+      vec!["Gothic Rock", "Post-Punk"]
+    );
+    assert_eq!(query.exclude_descriptors, vec!["melancholic"]);
+    assert_eq!(query.min_release_year, Some(1980));
+    assert_eq!(query.min_rating_count, Some(POPULAR_MIN_RATING_COUNT));
+    assert_eq!(query.min_rating, None);
+  }
+}
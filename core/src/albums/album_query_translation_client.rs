@@ -0,0 +1,75 @@
+use super::album_query_translation::AlbumQueryHints;
+use crate::settings::NaturalLanguageSearchSettings;
+use anyhow::{anyhow, Result};
+use async_openai::{
+  config::OpenAIConfig,
+  types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs,
+  },
+  Client,
+};
+use tracing::instrument;
+
+const MAX_TOKENS: u16 = 400;
+const SYSTEM_PROMPT: &str = "You turn requests for music into album search filters. Reply with \
+  only a JSON object with these optional keys: include_genres, exclude_genres, \
+  include_descriptors, exclude_descriptors, include_languages, exclude_languages (arrays of \
+  Rate Your Music style names, e.g. \"Post-Punk\", \"melancholic\", \"female vocalist\"), \
+  min_release_year, max_release_year (integers), popular (true for popular albums, false for \
+  obscure ones) and min_rating (between 0 and 5). Leave out anything the request doesn't ask for.";
+
+/**
+ * Reads queries with a language model behind any OpenAI-compatible endpoint
+ */
+pub struct AlbumQueryTranslationClient {
+  client: Client<OpenAIConfig>,
+  model: String,
+}
+
+impl AlbumQueryTranslationClient {
+  pub fn new(settings: &NaturalLanguageSearchSettings) -> Self {
+    let mut config = OpenAIConfig::new().with_api_base(&settings.base_url);
+    if let Some(api_key) = &settings.api_key {
+      config = config.with_api_key(api_key);
+    }
+    Self {
+      client: Client::with_config(config),
+      model: settings.model.clone(),
+    }
+  }
+
+  #[instrument(name = "AlbumQueryTranslationClient::translate", skip(self))]
+  pub async fn translate(&self, text: &str) -> Result<AlbumQueryHints> {
+    let request = CreateChatCompletionRequestArgs::default()
+      .model(&self.model)
+      .max_tokens(MAX_TOKENS)
+      .temperature(0.0)
+      .messages([
+        ChatCompletionRequestSystemMessageArgs::default()
+          .content(SYSTEM_PROMPT)
+          .build()?
+          .into(),
+        ChatCompletionRequestUserMessageArgs::default()
+          .content(text)
+          .build()?
+          .into(),
+      ])
+      .build()?;
+    let response = self.client.chat().create(request).await?;
+    let content = response
+      .choices
+      .into_iter()
+      .next()
+      .and_then(|choice| choice.message.content)
+      .ok_or_else(|| anyhow!("Empty translation returned by {}", self.model))?;
+    // Models that don't support a JSON response format tend to wrap the object in prose
+    let json = content
+      .find('{')
+      .zip(content.rfind('}'))
+      .filter(|(start, end)| start < end)
+      .map(|(start, end)| &content[start..=end])
+      .ok_or_else(|| anyhow!("No JSON object in translation: {}", content))?;
+    Ok(serde_json::from_str(json)?)
+  }
+}
@@ -1,14 +1,16 @@
 use crate::{
+  cover_images::cover_image::{cover_image_thumbnail_url, COVER_IMAGE_THUMBNAIL_SIZES},
   files::file_metadata::file_name::FileName,
   parser::parsed_file_data::{ParsedAlbum, ParsedArtistReference, ParsedCredit, ParsedTrack},
   proto,
 };
 use anyhow::Result;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use data_encoding::BASE64;
 use derive_builder::Builder;
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use unidecode::unidecode;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -29,6 +31,8 @@ pub struct AlbumReadModelTrack {
   pub duration_seconds: Option<u32>,
   pub rating: Option<f32>,
   pub position: Option<String>,
+  #[serde(default)]
+  pub artists: Vec<AlbumReadModelArtist>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -37,6 +41,21 @@ pub struct AlbumReadModelCredit {
   pub roles: Vec<String>,
 }
 
+/**
+ * The album's vinyl release on Discogs and its marketplace state as of `refreshed_at`. The median
+ * is taken over Discogs' suggested prices per media condition, not over actual listings. Prices
+ * are in the configured Discogs currency.
+ */
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct AlbumReadModelDiscogsRelease {
+  pub release_id: u64,
+  #[serde(alias = "median_price")]
+  pub median_suggested_price: Option<f32>,
+  pub lowest_price: Option<f32>,
+  pub num_for_sale: u32,
+  pub refreshed_at: NaiveDateTime,
+}
+
 #[derive(Debug, PartialEq, Builder, Serialize, Deserialize, Clone, Default)]
 #[builder(default)]
 pub struct AlbumReadModel {
@@ -56,8 +75,37 @@ pub struct AlbumReadModel {
   pub duplicates: Vec<FileName>,
   pub cover_image_url: Option<String>,
   pub spotify_id: Option<String>,
+  pub musicbrainz_id: Option<String>,
+  pub discogs_release: Option<AlbumReadModelDiscogsRelease>,
+  /**
+   * Compilations without album artists, their artists are only credited on the tracks
+   */
+  #[serde(default)]
+  pub is_various_artists: bool,
+  /**
+   * The album's Bandcamp page, for linking to where it can be bought
+   */
+  pub bandcamp_url: Option<String>,
+  /**
+   * The `cover_image_url` the locally served thumbnails were generated from, the thumbnails are
+   * stale once the album's cover changes
+   */
+  pub cached_cover_image_url: Option<String>,
+  /**
+   * Tags applied by the user, kept apart from RYM's descriptors
+   */
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /**
+   * Cluster the album's embedding fell into on the last clustering run
+   */
+  #[serde(default)]
+  pub cluster_id: Option<u32>,
 }
 
+pub const EMBEDDING_BODY_VERSION: u32 = 1;
+const VARIOUS_ARTISTS_NAME: &str = "Various Artists";
+
 impl AlbumReadModel {
   pub fn credit_tags(&self) -> Vec<String> {
     self
@@ -80,10 +128,60 @@ impl AlbumReadModel {
     Ok(BASE64.encode(&hash).to_string())
   }
 
+  /**
+   * Hash of the album as crawled, leaving out enrichments that depend on the instance's
+   * integrations and refresh schedule, the user's tags, and its cluster, so instances with the same
+   * crawl agree on it
+   */
+  pub fn content_digest(&self) -> Result<String> {
+    AlbumReadModel {
+      musicbrainz_id: None,
+      discogs_release: None,
+      bandcamp_url: None,
+      cached_cover_image_url: None,
+      tags: vec![],
+      cluster_id: None,
+      ..self.clone()
+    }
+    .to_sha256()
+  }
+
   pub fn ascii_name(&self) -> String {
     unidecode(&self.name)
   }
 
+  /**
+   * Artists credited on the tracks that aren't album artists, in track order
+   */
+  pub fn track_artists(&self) -> Vec<AlbumReadModelArtist> {
+    let mut seen = self
+      .artists
+      .iter()
+      .map(|artist| artist.file_name.clone())
+      .collect::<HashSet<_>>();
+    self
+      .tracks
+      .iter()
+      .flat_map(|track| track.artists.iter())
+      .filter(|artist| seen.insert(artist.file_name.clone()))
+      .cloned()
+      .collect()
+  }
+
+  /**
+   * Names to credit the album to when displaying it
+   */
+  pub fn artist_names(&self) -> Vec<String> {
+    if self.artists.is_empty() && self.is_various_artists {
+      return vec![VARIOUS_ARTISTS_NAME.to_string()];
+    }
+    self
+      .artists
+      .iter()
+      .map(|artist| artist.name.clone())
+      .collect()
+  }
+
   pub fn from_parsed_album(file_name: &FileName, parsed_album: ParsedAlbum) -> Self {
     Self {
       name: parsed_album.name.clone(),
@@ -114,9 +212,32 @@ impl AlbumReadModel {
       duplicate_of: None,
       cover_image_url: parsed_album.cover_image_url,
       spotify_id: parsed_album.spotify_id,
+      musicbrainz_id: None,
+      discogs_release: None,
+      is_various_artists: parsed_album.is_various_artists,
+      bandcamp_url: None,
+      cached_cover_image_url: None,
+      tags: vec![],
+      cluster_id: None,
+    }
+  }
+
+  /**
+   * Locally served thumbnails of the current cover, smallest first
+   */
+  pub fn cover_image_thumbnails(&self) -> Vec<(u32, String)> {
+    if self.cover_image_url.is_none() || self.cached_cover_image_url != self.cover_image_url {
+      return vec![];
     }
+    COVER_IMAGE_THUMBNAIL_SIZES
+      .iter()
+      .map(|size| (*size, cover_image_thumbnail_url(&self.file_name, *size)))
+      .collect()
   }
 
+  /**
+   * Bump `EMBEDDING_BODY_VERSION` whenever the body changes so stored embeddings get regenerated
+   */
   pub fn embedding_body(&self) -> String {
     let mut body = vec![];
     body.push(self.rating.to_string());
@@ -141,6 +262,11 @@ impl From<AlbumReadModelTrack> for proto::Track {
       duration_seconds: val.duration_seconds,
       rating: val.rating,
       position: val.position,
+      artists: val
+        .artists
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
     }
   }
 }
@@ -163,8 +289,25 @@ impl From<AlbumReadModelCredit> for proto::Credit {
   }
 }
 
+impl From<AlbumReadModelDiscogsRelease> for proto::DiscogsRelease {
+  fn from(val: AlbumReadModelDiscogsRelease) -> Self {
+    proto::DiscogsRelease {
+      release_id: val.release_id,
+      median_suggested_price: val.median_suggested_price,
+      lowest_price: val.lowest_price,
+      num_for_sale: val.num_for_sale,
+      refreshed_at: val.refreshed_at.to_string(),
+    }
+  }
+}
+
 impl From<AlbumReadModel> for proto::Album {
   fn from(val: AlbumReadModel) -> Self {
+    let cover_image_thumbnails = val
+      .cover_image_thumbnails()
+      .into_iter()
+      .map(|(size, url)| proto::CoverImageThumbnail { size, url })
+      .collect();
     proto::Album {
       name: val.name,
       file_name: val.file_name.to_string(),
@@ -189,6 +332,13 @@ impl From<AlbumReadModel> for proto::Album {
         .map(|file_name| file_name.to_string())
         .collect(),
       spotify_id: val.spotify_id,
+      musicbrainz_id: val.musicbrainz_id,
+      discogs_release: val.discogs_release.map(|release| release.into()),
+      is_various_artists: val.is_various_artists,
+      bandcamp_url: val.bandcamp_url,
+      cover_image_thumbnails,
+      tags: val.tags,
+      cluster_id: val.cluster_id,
       credits: val
         .credits
         .into_iter()
@@ -223,6 +373,14 @@ impl From<AlbumReadModel> for ParsedAlbum {
           duration_seconds: track.duration_seconds,
           rating: track.rating,
           position: track.position.clone(),
+          artists: track
+            .artists
+            .iter()
+            .map(|artist| ParsedArtistReference {
+              name: artist.name.clone(),
+              file_name: artist.file_name.clone(),
+            })
+            .collect(),
         })
         .collect::<Vec<ParsedTrack>>(),
       release_date: album.release_date,
@@ -240,6 +398,7 @@ impl From<AlbumReadModel> for ParsedAlbum {
         .collect::<Vec<ParsedCredit>>(),
       cover_image_url: album.cover_image_url,
       spotify_id: album.spotify_id,
+      is_various_artists: album.is_various_artists,
     }
   }
 }
@@ -1,9 +1,10 @@
 use super::album_read_model::{
-  AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelTrack,
+  AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelDiscogsRelease,
+  AlbumReadModelTrack,
 };
 use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
 use anyhow::{anyhow, Result};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use rusqlite::{params, types::Value, OptionalExtension};
 use std::{
   collections::{HashMap, HashSet},
@@ -24,6 +25,20 @@ pub struct ItemAndCount {
   pub count: u32,
 }
 
+/**
+ * How often descriptors are applied to the same album, across albums with at least one descriptor
+ */
+pub struct DescriptorCoOccurrences {
+  pub album_count: u32,
+  pub descriptor_counts: Vec<ItemAndCount>,
+  pub pair_counts: Vec<(String, String, u32)>,
+}
+
+pub struct AlbumNotes {
+  pub notes: String,
+  pub updated_at: NaiveDateTime,
+}
+
 pub struct AlbumRepository {
   sqlite_connection: Arc<SqliteConnection>,
 }
@@ -42,6 +57,10 @@ struct AlbumEntity {
   pub release_date: Option<NaiveDate>,
   pub cover_image_url: Option<String>,
   pub spotify_id: Option<String>,
+  pub musicbrainz_id: Option<String>,
+  pub is_various_artists: bool,
+  pub bandcamp_url: Option<String>,
+  pub cached_cover_image_url: Option<String>,
 }
 
 impl AlbumRepository {
@@ -74,7 +93,11 @@ impl AlbumRepository {
             rating_count,
             release_date,
             cover_image_url,
-            spotify_id
+            spotify_id,
+            musicbrainz_id,
+            is_various_artists,
+            bandcamp_url,
+            cached_cover_image_url
           FROM albums
           WHERE file_name IN rarray(?)
           ",
@@ -89,6 +112,10 @@ impl AlbumRepository {
             row.get::<_, Option<String>>(5)?,
             row.get::<_, Option<String>>(6)?,
             row.get::<_, Option<String>>(7)?,
+            row.get::<_, Option<String>>(8)?,
+            row.get::<_, bool>(9)?,
+            row.get::<_, Option<String>>(10)?,
+            row.get::<_, Option<String>>(11)?,
           ))
         })?;
         let mut result = HashMap::<FileName, AlbumEntity>::new();
@@ -102,6 +129,10 @@ impl AlbumRepository {
             release_date,
             cover_image_url,
             spotify_id,
+            musicbrainz_id,
+            is_various_artists,
+            bandcamp_url,
+            cached_cover_image_url,
           ) = row;
           let file_name = FileName::try_from(file_name.clone()).map_err(|e| {
             error!(message = e.to_string(), "Failed to parse album file name");
@@ -119,6 +150,10 @@ impl AlbumRepository {
                 .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").unwrap()),
               cover_image_url,
               spotify_id,
+              musicbrainz_id,
+              is_various_artists,
+              bandcamp_url,
+              cached_cover_image_url,
             },
           );
         }
@@ -334,6 +369,42 @@ impl AlbumRepository {
       .read()
       .await?
       .interact(move |conn| {
+        let album_id_params = Rc::new(album_id_params);
+        let mut stmt = conn.prepare(
+          "
+          SELECT
+            track_artists.track_id,
+            artists.file_name,
+            artists.name
+          FROM track_artists
+          JOIN tracks ON track_artists.track_id = tracks.id
+          JOIN artists ON track_artists.artist_id = artists.id
+          WHERE tracks.album_id IN rarray(?)
+          ORDER BY track_artists.rowid
+          ",
+        )?;
+        let mut rows = stmt.query_map([Rc::clone(&album_id_params)], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+          ))
+        })?;
+        let mut track_artists = HashMap::<i64, Vec<AlbumReadModelArtist>>::new();
+        while let Some(Ok(row)) = rows.next() {
+          let (track_id, artist_file_name, artist_name) = row;
+          track_artists
+            .entry(track_id)
+            .or_default()
+            .push(AlbumReadModelArtist {
+              file_name: FileName::try_from(artist_file_name).map_err(|e| {
+                error!(message = e.to_string(), "Failed to parse artist file name");
+                rusqlite::Error::ExecuteReturnedResults
+              })?,
+              name: artist_name,
+            });
+        }
+
         let mut stmt = conn.prepare(
           "
           SELECT
@@ -341,29 +412,39 @@ impl AlbumRepository {
             tracks.name,
             tracks.duration_seconds,
             tracks.rating,
-            tracks.position
+            tracks.position,
+            tracks.id
           FROM tracks
           WHERE tracks.album_id IN rarray(?)
           ",
         )?;
-        let mut rows = stmt.query_map([Rc::new(album_id_params)], |row| {
+        let mut rows = stmt.query_map([album_id_params], |row| {
           Ok((
             row.get::<_, i64>(0)?,
             row.get::<_, String>(1)?,
             row.get::<_, Option<u32>>(2)?,
             row.get::<_, Option<f32>>(3)?,
             row.get::<_, Option<String>>(4)?,
+            row.get::<_, i64>(5)?,
           ))
         })?;
         let mut result = HashMap::<i64, Vec<AlbumReadModelTrack>>::new();
         while let Some(Ok(row)) = rows.next() {
-          let (album_id, track_name, track_duration_seconds, track_rating, track_position) = row;
+          let (
+            album_id,
+            track_name,
+            track_duration_seconds,
+            track_rating,
+            track_position,
+            track_id,
+          ) = row;
           let album_entry = result.entry(album_id).or_default();
           album_entry.push(AlbumReadModelTrack {
             name: track_name,
             duration_seconds: track_duration_seconds,
             rating: track_rating,
             position: track_position,
+            artists: track_artists.remove(&track_id).unwrap_or_default(),
           });
         }
         Ok(result)
@@ -567,26 +648,37 @@ impl AlbumRepository {
     }
   }
 
+  /**
+   * Upserts the albums and returns them as stored. Parsed albums never carry enrichments, so an
+   * enrichment left unset keeps its stored value, and tags and clusters are read back in the same
+   * transaction.
+   */
   #[instrument(skip_all, fields(count = albums.len()))]
-  pub async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<()> {
+  pub async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<Vec<AlbumReadModel>> {
     self
       .sqlite_connection
       .write()
       .await?
       .interact(move |conn| {
         let tx = conn.transaction()?;
-        for album in albums {
-          tx.execute(
+        let mut stored = Vec::with_capacity(albums.len());
+        for mut album in albums {
+          let (album_id, musicbrainz_id, bandcamp_url, cached_cover_image_url) = tx.query_row(
             "
-            INSERT INTO albums (file_name, name, rating, rating_count, release_date, cover_image_url, spotify_id)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO albums (file_name, name, rating, rating_count, release_date, cover_image_url, spotify_id, musicbrainz_id, is_various_artists, bandcamp_url, cached_cover_image_url)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT (file_name) DO UPDATE SET
               name = excluded.name,
               rating = excluded.rating,
               rating_count = excluded.rating_count,
               release_date = excluded.release_date,
               cover_image_url = excluded.cover_image_url,
-              spotify_id = excluded.spotify_id
+              spotify_id = excluded.spotify_id,
+              musicbrainz_id = COALESCE(excluded.musicbrainz_id, albums.musicbrainz_id),
+              is_various_artists = excluded.is_various_artists,
+              bandcamp_url = COALESCE(excluded.bandcamp_url, albums.bandcamp_url),
+              cached_cover_image_url = COALESCE(excluded.cached_cover_image_url, albums.cached_cover_image_url)
+            RETURNING id, musicbrainz_id, bandcamp_url, cached_cover_image_url
             ",
             params![
               album.file_name.to_string(),
@@ -596,13 +688,56 @@ impl AlbumRepository {
               album.release_date,
               album.cover_image_url,
               album.spotify_id,
+              album.musicbrainz_id,
+              album.is_various_artists,
+              album.bandcamp_url,
+              album.cached_cover_image_url,
             ],
+            |row| {
+              Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+              ))
+            },
           )?;
-          let album_id: i64 = tx.query_row(
-            "SELECT id FROM albums WHERE file_name = ?",
-            params![album.file_name.to_string()],
-            |row| row.get(0),
-          )?;
+          album.musicbrainz_id = musicbrainz_id;
+          album.bandcamp_url = bandcamp_url;
+          album.cached_cover_image_url = cached_cover_image_url;
+          if album.discogs_release.is_none() {
+            album.discogs_release = tx
+              .query_row(
+                "
+                SELECT release_id, median_suggested_price, lowest_price, num_for_sale, refreshed_at
+                FROM album_discogs_releases
+                WHERE album_id = ?
+                ",
+                params![album_id],
+                |row| {
+                  Ok(AlbumReadModelDiscogsRelease {
+                    release_id: row.get::<_, i64>(0)? as u64,
+                    median_suggested_price: row.get(1)?,
+                    lowest_price: row.get(2)?,
+                    num_for_sale: row.get(3)?,
+                    refreshed_at: row.get(4)?,
+                  })
+                },
+              )
+              .optional()?;
+          }
+          album.tags = tx
+            .prepare("SELECT tag FROM album_tags WHERE album_file_name = ? ORDER BY tag")?
+            .query_map(params![album.file_name.to_string()], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+          album.cluster_id = tx
+            .query_row(
+              "SELECT cluster_id FROM album_clusters WHERE album_file_name = ?",
+              params![album.file_name.to_string()],
+              |row| row.get(0),
+            )
+            .optional()?;
+          stored.push(album.clone());
 
           tx.execute(
             "
@@ -734,10 +869,11 @@ impl AlbumRepository {
             params![album_id],
           )?;
           for track in album.tracks {
-            tx.execute(
+            let track_id: i64 = tx.query_row(
               "
               INSERT INTO tracks (album_id, name, duration_seconds, rating, position)
               VALUES (?, ?, ?, ?, ?)
+              RETURNING id
               ",
               params![
                 album_id,
@@ -746,6 +882,49 @@ impl AlbumRepository {
                 track.rating,
                 track.position,
               ],
+              |row| row.get(0),
+            )?;
+            for artist in track.artists {
+              let artist_id: i64 = tx.query_row(
+                "
+                INSERT INTO artists (file_name, name)
+                VALUES (?, ?)
+                ON CONFLICT(file_name) DO UPDATE SET name = excluded.name
+                RETURNING id
+                ",
+                params![artist.file_name.to_string(), artist.name],
+                |row| row.get(0),
+              )?;
+              tx.execute(
+                "
+                INSERT OR IGNORE INTO track_artists (track_id, artist_id)
+                VALUES (?, ?)
+                ",
+                params![track_id, artist_id],
+              )?;
+            }
+          }
+
+          if let Some(release) = album.discogs_release {
+            tx.execute(
+              "
+              INSERT INTO album_discogs_releases (album_id, release_id, median_suggested_price, lowest_price, num_for_sale, refreshed_at)
+              VALUES (?, ?, ?, ?, ?, ?)
+              ON CONFLICT (album_id) DO UPDATE SET
+                release_id = excluded.release_id,
+                median_suggested_price = excluded.median_suggested_price,
+                lowest_price = excluded.lowest_price,
+                num_for_sale = excluded.num_for_sale,
+                refreshed_at = excluded.refreshed_at
+              ",
+              params![
+                album_id,
+                release.release_id as i64,
+                release.median_suggested_price,
+                release.lowest_price,
+                release.num_for_sale,
+                release.refreshed_at,
+              ],
             )?;
           }
 
@@ -842,7 +1021,7 @@ impl AlbumRepository {
           }
         }
         tx.commit()?;
-        Ok(())
+        Ok(stored)
       })
       .await
       .map_err(|e| {
@@ -851,9 +1030,59 @@ impl AlbumRepository {
       })?
   }
 
+  #[instrument(skip_all, fields(count = album_ids.len()))]
+  async fn find_album_discogs_releases(
+    &self,
+    album_ids: Vec<i64>,
+  ) -> Result<HashMap<i64, AlbumReadModelDiscogsRelease>> {
+    let album_id_params = album_ids
+      .into_iter()
+      .map(Value::from)
+      .collect::<Vec<Value>>();
+
+    let releases = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_id, release_id, median_suggested_price, lowest_price, num_for_sale, refreshed_at
+          FROM album_discogs_releases
+          WHERE album_id IN rarray(?)
+          ",
+        )?;
+        let rows = stmt
+          .query_map([Rc::new(album_id_params)], |row| {
+            Ok((
+              row.get::<_, i64>(0)?,
+              AlbumReadModelDiscogsRelease {
+                release_id: row.get::<_, i64>(1)? as u64,
+                median_suggested_price: row.get(2)?,
+                lowest_price: row.get(3)?,
+                num_for_sale: row.get(4)?,
+                refreshed_at: row.get(5)?,
+              },
+            ))
+          })?
+          .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album discogs releases"
+        );
+        anyhow!("Failed to find album discogs releases")
+      })??;
+    Ok(releases)
+  }
+
   #[instrument(skip_all, fields(file_name = album.file_name.to_string()))]
   pub async fn put(&self, album: AlbumReadModel) -> Result<()> {
-    self.put_many(vec![album]).await
+    self.put_many(vec![album]).await?;
+    Ok(())
   }
 
   #[instrument(skip_all, fields(file_name, count = duplicates.len()))]
@@ -987,6 +1216,9 @@ impl AlbumRepository {
       mut album_tracks,
       mut album_credits,
       mut album_duplicates,
+      mut album_discogs_releases,
+      mut album_tags,
+      mut album_cluster_ids,
     ) = try_join!(
       self.find_album_artists(album_ids.clone()),
       self.find_album_genres(album_ids.clone()),
@@ -995,6 +1227,9 @@ impl AlbumRepository {
       self.find_album_tracks(album_ids.clone()),
       self.find_album_credits(album_ids.clone()),
       self.find_album_duplication(album_ids.clone()),
+      self.find_album_discogs_releases(album_ids.clone()),
+      self.find_tags(file_names.clone()),
+      self.find_cluster_ids(file_names.clone()),
     )?;
     let mut result = Vec::<AlbumReadModel>::new();
     for file_name in file_names {
@@ -1015,6 +1250,8 @@ impl AlbumRepository {
           AlbumDuplication::Duplicates(duplicates) => (None, duplicates),
           AlbumDuplication::DuplicateOf(duplicate_of) => (Some(duplicate_of), Vec::new()),
         };
+        let tags = album_tags.remove(&file_name).unwrap_or_else(Vec::new);
+        let cluster_id = album_cluster_ids.remove(&file_name);
         result.push(AlbumReadModel {
           name: album_entity.name,
           file_name: album_entity.file_name,
@@ -1023,6 +1260,11 @@ impl AlbumRepository {
           release_date: album_entity.release_date,
           cover_image_url: album_entity.cover_image_url,
           spotify_id: album_entity.spotify_id,
+          musicbrainz_id: album_entity.musicbrainz_id,
+          is_various_artists: album_entity.is_various_artists,
+          bandcamp_url: album_entity.bandcamp_url,
+          cached_cover_image_url: album_entity.cached_cover_image_url,
+          discogs_release: album_discogs_releases.remove(&album_id),
           duplicate_of,
           duplicates,
           artists,
@@ -1032,12 +1274,344 @@ impl AlbumRepository {
           languages,
           tracks,
           credits,
+          tags,
+          cluster_id,
         });
       }
     }
     Ok(result)
   }
 
+  /**
+   * The subset of `file_names` that are stored, answered from the file name index alone
+   */
+  #[instrument(skip_all, fields(count = file_names.len()))]
+  pub async fn filter_existing(&self, file_names: Vec<FileName>) -> Result<Vec<FileName>> {
+    let file_name_params = file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+    let existing = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare("SELECT file_name FROM albums WHERE file_name IN rarray(?)")?;
+        let rows = stmt
+          .query_map([Rc::new(file_name_params)], |row| row.get::<_, String>(0))?
+          .collect::<Result<HashSet<String>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to filter existing albums");
+        anyhow!("Failed to filter existing albums")
+      })??;
+    Ok(
+      file_names
+        .into_iter()
+        .filter(|file_name| existing.contains(&file_name.to_string()))
+        .collect(),
+    )
+  }
+
+  /**
+   * Tags are keyed by file name rather than album id, so they outlive a recrawl that replaces the
+   * album's rows
+   */
+  #[instrument(skip_all, fields(count = file_names.len()))]
+  pub async fn find_tags(
+    &self,
+    file_names: Vec<FileName>,
+  ) -> Result<HashMap<FileName, Vec<String>>> {
+    let file_name_params = file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_file_name, tag
+          FROM album_tags
+          WHERE album_file_name IN rarray(?)
+          ORDER BY tag
+          ",
+        )?;
+        let rows = stmt
+          .query_map([Rc::new(file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album tags");
+        anyhow!("Failed to find album tags")
+      })??;
+    let mut tags: HashMap<FileName, Vec<String>> = HashMap::new();
+    for (file_name, tag) in rows {
+      tags
+        .entry(FileName::try_from(file_name)?)
+        .or_default()
+        .push(tag);
+    }
+    Ok(tags)
+  }
+
+  #[instrument(skip_all, fields(count = file_names.len()))]
+  pub async fn find_cluster_ids(
+    &self,
+    file_names: Vec<FileName>,
+  ) -> Result<HashMap<FileName, u32>> {
+    let file_name_params = file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_file_name, cluster_id
+          FROM album_clusters
+          WHERE album_file_name IN rarray(?)
+          ",
+        )?;
+        let rows = stmt
+          .query_map([Rc::new(file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album cluster ids");
+        anyhow!("Failed to find album cluster ids")
+      })??;
+    rows
+      .into_iter()
+      .map(|(file_name, cluster_id)| Ok((FileName::try_from(file_name)?, cluster_id)))
+      .collect()
+  }
+
+  #[instrument(skip(self))]
+  pub async fn set_tags(&self, file_name: &FileName, tags: Vec<String>) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM album_tags WHERE album_file_name = ?",
+          params![file_name],
+        )?;
+        for tag in tags {
+          tx.execute(
+            "INSERT INTO album_tags (album_file_name, tag) VALUES (?, ?)",
+            params![file_name, tag],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set album tags");
+        anyhow!("Failed to set album tags")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find_notes(&self, file_name: &FileName) -> Result<Option<AlbumNotes>> {
+    let file_name = file_name.to_string();
+    let notes = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        conn
+          .query_row(
+            "SELECT notes, updated_at FROM album_notes WHERE album_file_name = ?",
+            params![file_name],
+            |row| {
+              Ok(AlbumNotes {
+                notes: row.get(0)?,
+                updated_at: row.get(1)?,
+              })
+            },
+          )
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album notes");
+        anyhow!("Failed to find album notes")
+      })??;
+    Ok(notes)
+  }
+
+  /**
+   * Replaces the album's notes, or clears them when `notes` is None
+   */
+  #[instrument(skip(self, notes))]
+  pub async fn set_notes(&self, file_name: &FileName, notes: Option<String>) -> Result<()> {
+    let file_name = file_name.to_string();
+    let updated_at = Utc::now().naive_utc();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        match notes {
+          Some(notes) => conn.execute(
+            "
+            INSERT INTO album_notes (album_file_name, notes, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(album_file_name) DO UPDATE SET
+              notes = excluded.notes,
+              updated_at = excluded.updated_at
+            ",
+            params![file_name, notes, updated_at],
+          )?,
+          None => conn.execute(
+            "DELETE FROM album_notes WHERE album_file_name = ?",
+            params![file_name],
+          )?,
+        };
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set album notes");
+        anyhow!("Failed to set album notes")
+      })?
+  }
+
+  #[instrument(skip_all)]
+  pub async fn get_aggregated_tags(&self, limit: Option<u32>) -> Result<Vec<ItemAndCount>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT tag, COUNT(*) as count
+          FROM album_tags
+          GROUP BY tag
+          ORDER BY count DESC
+          LIMIT COALESCE(?, -1)
+          ",
+        )?;
+        let tags = stmt
+          .query_map([limit], |row| {
+            Ok(ItemAndCount {
+              name: row.get(0)?,
+              count: row.get(1)?,
+            })
+          })?
+          .filter_map(|r| r.ok())
+          .collect::<Vec<ItemAndCount>>();
+        Ok(tags)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get aggregated tags");
+        anyhow!("Failed to get aggregated tags")
+      })?
+  }
+
+  /**
+   * Albums whose Discogs marketplace state was last refreshed before `refreshed_before`, stalest
+   * first
+   */
+  #[instrument(skip(self))]
+  pub async fn find_stale_discogs_releases(
+    &self,
+    refreshed_before: NaiveDateTime,
+    limit: u32,
+  ) -> Result<Vec<FileName>> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT albums.file_name
+          FROM album_discogs_releases
+          JOIN albums ON albums.id = album_discogs_releases.album_id
+          WHERE album_discogs_releases.refreshed_at < ?
+          ORDER BY album_discogs_releases.refreshed_at ASC
+          LIMIT ?
+          ",
+        )?;
+        let rows = stmt
+          .query_map(params![refreshed_before, limit], |row| {
+            row.get::<_, String>(0)
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find stale discogs releases"
+        );
+        anyhow!("Failed to find stale discogs releases")
+      })??;
+    rows.into_iter().map(FileName::try_from).collect()
+  }
+
+  /**
+   * A page of album file names in file name order, starting after `after`
+   */
+  pub async fn find_file_names_after(
+    &self,
+    after: Option<FileName>,
+    limit: u32,
+  ) -> Result<Vec<FileName>> {
+    let after = after
+      .map(|file_name| file_name.to_string())
+      .unwrap_or_default();
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT file_name
+          FROM albums
+          WHERE file_name > ?
+          ORDER BY file_name ASC
+          LIMIT ?
+          ",
+        )?;
+        let rows = stmt
+          .query_map(params![after, limit], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album file names");
+        anyhow!("Failed to find album file names")
+      })??;
+    rows.into_iter().map(FileName::try_from).collect()
+  }
+
   #[instrument(skip_all, fields(count = artist_file_name.len()))]
   pub async fn find_artist_albums(
     &self,
@@ -1168,6 +1742,64 @@ impl AlbumRepository {
       })?
   }
 
+  #[instrument(skip_all)]
+  pub async fn get_descriptor_co_occurrences(&self) -> Result<DescriptorCoOccurrences> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let album_count = conn.query_row(
+          "SELECT COUNT(DISTINCT album_id) FROM album_descriptors",
+          [],
+          |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+          "
+          SELECT d.name, COUNT(*) as count
+          FROM descriptors d
+          JOIN album_descriptors ad ON d.id = ad.descriptor_id
+          GROUP BY d.name
+          ",
+        )?;
+        let descriptor_counts = stmt
+          .query_map([], |row| {
+            Ok(ItemAndCount {
+              name: row.get(0)?,
+              count: row.get(1)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = conn.prepare(
+          "
+          SELECT d1.name, d2.name, COUNT(*) as count
+          FROM album_descriptors ad1
+          JOIN album_descriptors ad2
+            ON ad1.album_id = ad2.album_id AND ad1.descriptor_id < ad2.descriptor_id
+          JOIN descriptors d1 ON d1.id = ad1.descriptor_id
+          JOIN descriptors d2 ON d2.id = ad2.descriptor_id
+          GROUP BY ad1.descriptor_id, ad2.descriptor_id
+          ",
+        )?;
+        let pair_counts = stmt
+          .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(DescriptorCoOccurrences {
+          album_count,
+          descriptor_counts,
+          pair_counts,
+        })
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get descriptor co-occurrences"
+        );
+        anyhow!("Failed to get descriptor co-occurrences")
+      })?
+  }
+
   #[instrument(skip_all)]
   pub async fn get_aggregated_languages(&self, limit: Option<u32>) -> Result<Vec<ItemAndCount>> {
     self
@@ -1262,6 +1894,27 @@ impl AlbumRepository {
       })?
   }
 
+  #[instrument(skip_all)]
+  pub async fn get_average_rating(&self) -> Result<f32> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare("SELECT COALESCE(AVG(rating), 0) FROM albums")?;
+        stmt.query_row([], |row| row.get::<_, f64>(0)).map_err(|e| {
+          error!(message = e.to_string(), "Failed to get average rating");
+          anyhow!("Failed to get average rating")
+        })
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get average rating");
+        anyhow!("Failed to get average rating")
+      })?
+      .map(|rating| rating as f32)
+  }
+
   #[instrument(skip_all)]
   pub async fn count_artists(&self) -> Result<u32> {
     self
@@ -1391,3 +2044,44 @@ impl AlbumRepository {
       })?
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_put_many_keeps_enrichments() -> Result<()> {
+    let repository = AlbumRepository::new(Arc::new(SqliteConnection::new_for_test().await?));
+    let file_name = FileName::try_from("release/album/bjork/vulnicura")?;
+    let album = AlbumReadModel {
+      name: "Vulnicura".to_string(),
+      file_name: file_name.clone(),
+      ..Default::default()
+    };
+    repository
+      .put(AlbumReadModel {
+        musicbrainz_id: Some("mbid".to_string()),
+        bandcamp_url: Some("https://bjork.bandcamp.com/album/vulnicura".to_string()),
+        ..album.clone()
+      })
+      .await?;
+    repository
+      .set_tags(&file_name, vec!["winter".to_string()])
+      .await?;
+
+    let stored = repository
+      .put_many(vec![AlbumReadModel {
+        bandcamp_url: Some("https://bjork.bandcamp.com/album/vulnicura-live".to_string()),
+        ..album
+      }])
+      .await?;
+    assert_eq!(stored[0].musicbrainz_id.as_deref(), Some("mbid"));
+    assert_eq!(
+      stored[0].bandcamp_url.as_deref(),
+      Some("https://bjork.bandcamp.com/album/vulnicura-live")
+    );
+    assert_eq!(stored[0].tags, vec!["winter".to_string()]);
+    assert_eq!(repository.get(&file_name).await?, stored[0]);
+    Ok(())
+  }
+}
@@ -0,0 +1,122 @@
+use crate::proto;
+use anyhow::{anyhow, Result};
+use serde_derive::{Deserialize, Serialize};
+
+/**
+ * Named relevance tuning for album search, so clients pick a profile instead of each carrying
+ * their own scoring. Only the Elasticsearch backend scores results, the others reject it.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlbumSearchBoostProfile {
+  pub name: String,
+  /**
+   * Weights of the fields matched by the text query
+   */
+  pub name_weight: f32,
+  pub artist_name_weight: f32,
+  /**
+   * Added to the score per point of rating
+   */
+  pub rating_boost: f32,
+  /**
+   * Added to the score of current releases, halving every `recency_scale_years`
+   */
+  pub recency_boost: f32,
+  pub recency_scale_years: u32,
+}
+
+impl Default for AlbumSearchBoostProfile {
+  fn default() -> Self {
+    Self {
+      name: "default".to_string(),
+      name_weight: 1.0,
+      artist_name_weight: 1.0,
+      rating_boost: 0.0,
+      recency_boost: 0.0,
+      recency_scale_years: 10,
+    }
+  }
+}
+
+impl AlbumSearchBoostProfile {
+  pub fn validate(&self) -> Result<()> {
+    if self.name.trim().is_empty() {
+      return Err(anyhow!("Boost profile name is required"));
+    }
+    let weights = [
+      self.name_weight,
+      self.artist_name_weight,
+      self.rating_boost,
+      self.recency_boost,
+    ];
+    if weights
+      .iter()
+      .any(|weight| !weight.is_finite() || *weight < 0.0)
+    {
+      return Err(anyhow!("Boost profile weights must be non-negative"));
+    }
+    if self.recency_boost > 0.0 && self.recency_scale_years == 0 {
+      return Err(anyhow!("Recency boost needs a scale of at least a year"));
+    }
+    Ok(())
+  }
+}
+
+impl From<AlbumSearchBoostProfile> for proto::AlbumSearchBoostProfile {
+  fn from(val: AlbumSearchBoostProfile) -> Self {
+    proto::AlbumSearchBoostProfile {
+      name: val.name,
+      name_weight: val.name_weight,
+      artist_name_weight: val.artist_name_weight,
+      rating_boost: val.rating_boost,
+      recency_boost: val.recency_boost,
+      recency_scale_years: val.recency_scale_years,
+    }
+  }
+}
+
+impl TryFrom<proto::AlbumSearchBoostProfile> for AlbumSearchBoostProfile {
+  type Error = anyhow::Error;
+
+  fn try_from(val: proto::AlbumSearchBoostProfile) -> Result<Self> {
+    let profile = AlbumSearchBoostProfile {
+      name: val.name,
+      name_weight: val.name_weight,
+      artist_name_weight: val.artist_name_weight,
+      rating_boost: val.rating_boost,
+      recency_boost: val.recency_boost,
+      recency_scale_years: val.recency_scale_years,
+    };
+    profile.validate()?;
+    Ok(profile)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate() {
+    assert!(AlbumSearchBoostProfile::default().validate().is_ok());
+    assert!(AlbumSearchBoostProfile {
+      name: " ".to_string(),
+      ..Default::default()
+    }
+    .validate()
+    .is_err());
+    assert!(AlbumSearchBoostProfile {
+      rating_boost: -1.0,
+      ..Default::default()
+    }
+    .validate()
+    .is_err());
+    assert!(AlbumSearchBoostProfile {
+      recency_boost: 2.0,
+      recency_scale_years: 0,
+      ..Default::default()
+    }
+    .validate()
+    .is_err());
+  }
+}
@@ -0,0 +1,50 @@
+use super::album_search_boost_profile::AlbumSearchBoostProfile;
+use crate::helpers::document_store::{DocumentFilter, DocumentStore};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct AlbumSearchBoostProfileRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "album_search_boost_profiles";
+
+impl AlbumSearchBoostProfileRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn find(&self, name: &str) -> Result<Option<AlbumSearchBoostProfile>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<AlbumSearchBoostProfile>(COLLECTION, name)
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  pub async fn find_all(&self) -> Result<Vec<AlbumSearchBoostProfile>> {
+    Ok(
+      self
+        .doc_store
+        .find_many::<AlbumSearchBoostProfile>(COLLECTION, DocumentFilter::new(), None)
+        .await?
+        .documents
+        .into_iter()
+        .map(|doc| doc.document)
+        .collect(),
+    )
+  }
+
+  pub async fn put(&self, profile: AlbumSearchBoostProfile) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &profile.name.clone(), profile, None)
+      .await
+  }
+
+  pub async fn delete(&self, name: &str) -> Result<()> {
+    self.doc_store.delete(COLLECTION, name).await
+  }
+}
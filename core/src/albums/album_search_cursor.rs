@@ -0,0 +1,220 @@
+use super::{
+  album_read_model::AlbumReadModel,
+  album_search_index::{
+    AlbumSearchKeyset, AlbumSearchQuery, AlbumSearchSort, AlbumSearchSortField,
+  },
+};
+use crate::{files::file_metadata::file_name::FileName, helpers::redisearch::SearchPagination};
+use anyhow::{anyhow, Result};
+
+pub const DEFAULT_CURSOR_PAGE_SIZE: usize = 100;
+
+/**
+ * Where the next page of a search starts. Searches ordered by rating or rating count page by
+ * keyset, narrowing the query to albums past the last one seen, so deep pages cost the same as
+ * the first. Text, boosted, name and release date orderings can't be narrowed that way and page
+ * by offset.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlbumSearchCursor {
+  /**
+   * Position of the last album returned
+   */
+  Keyset(AlbumSearchKeyset),
+  Offset(usize),
+}
+
+impl AlbumSearchCursor {
+  pub fn encode(&self) -> String {
+    match self {
+      AlbumSearchCursor::Keyset(keyset) => {
+        format!("k:{}:{}", keyset.value, keyset.file_name.to_string())
+      }
+      AlbumSearchCursor::Offset(offset) => format!("o:{}", offset),
+    }
+  }
+
+  /**
+   * The empty cursor is the first page
+   */
+  pub fn decode(cursor: &str) -> Result<Option<Self>> {
+    if cursor.is_empty() {
+      return Ok(None);
+    }
+    match cursor.splitn(3, ':').collect::<Vec<_>>().as_slice() {
+      ["k", value, file_name] => Ok(Some(AlbumSearchCursor::Keyset(AlbumSearchKeyset {
+        value: value.parse()?,
+        file_name: FileName::try_from(file_name.to_string())?,
+      }))),
+      ["o", offset] => Ok(Some(AlbumSearchCursor::Offset(offset.parse()?))),
+      _ => Err(anyhow!("Invalid cursor: {}", cursor)),
+    }
+  }
+}
+
+/**
+ * Order of a keyset paged search, most rated first when the query has no sort of its own
+ */
+fn keyset_sort(query: &AlbumSearchQuery) -> Option<AlbumSearchSort> {
+  if query.text_search_plan().is_some() || query.boost_profile.is_some() {
+    return None;
+  }
+  match &query.sort {
+    None => Some(AlbumSearchSort {
+      field: AlbumSearchSortField::RatingCount,
+      descending: true,
+    }),
+    Some(sort)
+      if matches!(
+        sort.field,
+        AlbumSearchSortField::Rating | AlbumSearchSortField::RatingCount
+      ) =>
+    {
+      Some(sort.clone())
+    }
+    Some(_) => None,
+  }
+}
+
+fn sort_value(album: &AlbumReadModel, field: AlbumSearchSortField) -> f64 {
+  match field {
+    AlbumSearchSortField::Rating => album.rating as f64,
+    _ => album.rating_count as f64,
+  }
+}
+
+fn offset(cursor: Option<&AlbumSearchCursor>) -> usize {
+  match cursor {
+    Some(AlbumSearchCursor::Offset(offset)) => *offset,
+    _ => 0,
+  }
+}
+
+/**
+ * The query and pagination fetching the page that starts at `cursor`
+ */
+pub fn cursor_page_query(
+  query: &AlbumSearchQuery,
+  cursor: Option<&AlbumSearchCursor>,
+  limit: usize,
+) -> (AlbumSearchQuery, SearchPagination) {
+  let mut page_query = query.clone();
+  let Some(sort) = keyset_sort(query) else {
+    return (
+      page_query,
+      SearchPagination {
+        offset: Some(offset(cursor)),
+        limit: Some(limit),
+      },
+    );
+  };
+  if let Some(AlbumSearchCursor::Keyset(keyset)) = cursor {
+    // The bound keeps totals to the albums from the cursor on, ties with it included
+    match (sort.field, sort.descending) {
+      (AlbumSearchSortField::Rating, true) => page_query.max_rating = Some(keyset.value as f32),
+      (AlbumSearchSortField::Rating, false) => page_query.min_rating = Some(keyset.value as f32),
+      (_, true) => page_query.max_rating_count = Some(keyset.value as u32),
+      (_, false) => page_query.min_rating_count = Some(keyset.value as u32),
+    }
+    page_query.search_after = Some(keyset.clone());
+  }
+  page_query.sort = Some(sort);
+  (
+    page_query,
+    SearchPagination {
+      offset: Some(0),
+      limit: Some(limit),
+    },
+  )
+}
+
+/**
+ * Cursor of the page after the one starting at `cursor`, none once the results run out
+ */
+pub fn next_cursor(
+  query: &AlbumSearchQuery,
+  cursor: Option<&AlbumSearchCursor>,
+  albums: &[AlbumReadModel],
+  limit: usize,
+) -> Option<AlbumSearchCursor> {
+  if albums.is_empty() || albums.len() < limit {
+    return None;
+  }
+  let Some(sort) = keyset_sort(query) else {
+    return Some(AlbumSearchCursor::Offset(offset(cursor) + albums.len()));
+  };
+  let last = albums.last()?;
+  Some(AlbumSearchCursor::Keyset(AlbumSearchKeyset {
+    value: sort_value(last, sort.field),
+    file_name: last.file_name.clone(),
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn album(name: &str, rating_count: u32) -> AlbumReadModel {
+    AlbumReadModel {
+      name: name.to_string(),
+      file_name: FileName::try_from(format!("release/album/artist/{}", name)).unwrap(),
+      rating_count,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_cursor_encoding() -> Result<()> {
+    for cursor in [
+      AlbumSearchCursor::Keyset(AlbumSearchKeyset {
+        value: 3.92_f32 as f64,
+        file_name: FileName::try_from("release/album/slowdive/souvlaki")?,
+      }),
+      AlbumSearchCursor::Offset(40),
+    ] {
+      assert_eq!(AlbumSearchCursor::decode(&cursor.encode())?, Some(cursor));
+    }
+    assert_eq!(AlbumSearchCursor::decode("")?, None);
+    assert!(AlbumSearchCursor::decode("x:1").is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_keyset_cursor() -> Result<()> {
+    let query = AlbumSearchQuery::default();
+    let cursor = next_cursor(
+      &query,
+      None,
+      &[album("a", 50), album("b", 20), album("c", 20)],
+      3,
+    );
+    let keyset = AlbumSearchKeyset {
+      value: 20.0,
+      file_name: FileName::try_from("release/album/artist/c")?,
+    };
+    assert_eq!(cursor, Some(AlbumSearchCursor::Keyset(keyset.clone())));
+
+    let (page_query, pagination) = cursor_page_query(&query, cursor.as_ref(), 3);
+    assert_eq!(page_query.search_after, Some(keyset));
+    assert_eq!(page_query.max_rating_count, Some(20));
+    assert_eq!(pagination.offset, Some(0));
+    assert_eq!(
+      next_cursor(&query, cursor.as_ref(), &[album("d", 20)], 3),
+      None
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_text_search_pages_by_offset() {
+    let query = AlbumSearchQuery {
+      text: Some("souvlaki".to_string()),
+      ..Default::default()
+    };
+    let cursor = next_cursor(&query, None, &[album("a", 50), album("b", 20)], 2);
+    assert_eq!(cursor, Some(AlbumSearchCursor::Offset(2)));
+    let (page_query, pagination) = cursor_page_query(&query, cursor.as_ref(), 2);
+    assert_eq!(page_query.sort, None);
+    assert_eq!(pagination.offset, Some(2));
+  }
+}
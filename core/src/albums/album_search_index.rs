@@ -1,16 +1,94 @@
-use super::album_read_model::AlbumReadModel;
+use super::{
+  album_read_model::AlbumReadModel,
+  album_repository::ItemAndCount,
+  album_search_boost_profile::AlbumSearchBoostProfile,
+  album_search_cursor::{cursor_page_query, next_cursor, AlbumSearchCursor},
+  album_text_search::{AlbumTextMatchMode, AlbumTextSearchPlan},
+};
 use crate::{
   files::file_metadata::file_name::FileName,
   helpers::{embedding::EmbeddingDocument, redisearch::SearchPagination},
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use derive_builder::Builder;
+use futures::future::try_join_all;
+use rand::seq::index::sample;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlbumSearchPredicate {
+  PrimaryGenre(String),
+  SecondaryGenre(String),
+  Descriptor(String),
+  Language(String),
+  Artist(FileName),
+  ReleaseYear { min: Option<u32>, max: Option<u32> },
+}
+
+/**
+ * Boolean expression over album fields, for filters the include/exclude lists can't express
+ * like "(shoegaze OR dream pop) AND NOT live"
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlbumSearchExpression {
+  Predicate(AlbumSearchPredicate),
+  And(Vec<AlbumSearchExpression>),
+  Or(Vec<AlbumSearchExpression>),
+  Not(Box<AlbumSearchExpression>),
+}
+
+impl AlbumSearchExpression {
+  /**
+   * Every backend compiles AND/OR into a group, so empty groups and open year ranges are
+   * rejected up front rather than given backend-specific meanings
+   */
+  pub fn validate(&self) -> Result<()> {
+    match self {
+      AlbumSearchExpression::Predicate(AlbumSearchPredicate::ReleaseYear {
+        min: None,
+        max: None,
+      }) => Err(anyhow!("Release year predicate needs a min or max")),
+      AlbumSearchExpression::Predicate(_) => Ok(()),
+      AlbumSearchExpression::And(children) | AlbumSearchExpression::Or(children) => {
+        if children.is_empty() {
+          return Err(anyhow!("AND/OR expressions need at least one operand"));
+        }
+        children.iter().try_for_each(|child| child.validate())
+      }
+      AlbumSearchExpression::Not(child) => child.validate(),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlbumSearchSortField {
+  Rating,
+  RatingCount,
+  ReleaseDate,
+  Name,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlbumSearchSort {
+  pub field: AlbumSearchSortField,
+  pub descending: bool,
+}
+
+/**
+ * A position in a rating or rating count ordering. Albums tied on the value are ordered by file
+ * name, so no two albums share a position.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlbumSearchKeyset {
+  pub value: f64,
+  pub file_name: FileName,
+}
 
-#[derive(Default, Builder, Debug)]
+#[derive(Default, Builder, Debug, Clone)]
 #[builder(setter(into), default)]
 pub struct AlbumSearchQuery {
   pub text: Option<String>,
+  pub text_match_mode: AlbumTextMatchMode,
   pub exact_name: Option<String>,
   pub include_file_names: Vec<FileName>,
   pub exclude_file_names: Vec<FileName>,
@@ -24,12 +102,49 @@ pub struct AlbumSearchQuery {
   pub exclude_languages: Vec<String>,
   pub include_descriptors: Vec<String>,
   pub exclude_descriptors: Vec<String>,
+  pub include_tags: Vec<String>,
+  pub exclude_tags: Vec<String>,
+  /**
+   * Matches albums in any of the clusters
+   */
+  pub include_cluster_ids: Vec<u32>,
   pub min_primary_genre_count: Option<usize>,
   pub min_secondary_genre_count: Option<usize>,
   pub min_descriptor_count: Option<usize>,
   pub min_release_year: Option<u32>,
   pub max_release_year: Option<u32>,
+  pub min_rating: Option<f32>,
+  pub max_rating: Option<f32>,
+  pub min_rating_count: Option<u32>,
+  pub max_rating_count: Option<u32>,
   pub include_duplicates: Option<bool>,
+  /**
+   * ANDed with the rest of the query
+   */
+  pub expression: Option<AlbumSearchExpression>,
+  /**
+   * Scoring applied on top of the filters, results keep the backend's ranking without it
+   */
+  pub boost_profile: Option<AlbumSearchBoostProfile>,
+  /**
+   * Albums without the sorted field come last. Results keep the backend's ranking without it, and
+   * embedding similarity searches are always ordered by distance.
+   */
+  pub sort: Option<AlbumSearchSort>,
+  /**
+   * Only albums past this position in the sort, which must be on rating or rating count
+   */
+  pub search_after: Option<AlbumSearchKeyset>,
+}
+
+impl AlbumSearchQuery {
+  pub fn text_search_plan(&self) -> Option<AlbumTextSearchPlan> {
+    self
+      .text
+      .as_ref()
+      .filter(|text| !text.trim().is_empty())
+      .map(|text| AlbumTextSearchPlan::new(text, self.text_match_mode))
+  }
 }
 
 #[derive(Debug)]
@@ -38,6 +153,21 @@ pub struct AlbumSearchResult {
   pub total: usize,
 }
 
+/**
+ * Counts of the albums matching a search, for rendering filters alongside the results. Each facet
+ * holds its most common values, most common first.
+ */
+#[derive(Default)]
+pub struct AlbumSearchFacets {
+  pub primary_genres: Vec<ItemAndCount>,
+  pub descriptors: Vec<ItemAndCount>,
+  pub languages: Vec<ItemAndCount>,
+  /**
+   * Named by the decade's first year, e.g. "1990"
+   */
+  pub release_decades: Vec<ItemAndCount>,
+}
+
 #[derive(Debug)]
 pub struct AlbumEmbeddingSimilarirtySearchQuery {
   pub embedding: Vec<f32>,
@@ -48,15 +178,76 @@ pub struct AlbumEmbeddingSimilarirtySearchQuery {
 
 #[async_trait]
 pub trait AlbumSearchIndex {
+  async fn setup_index(&self) -> Result<()>;
   async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<()>;
   async fn put(&self, album: AlbumReadModel) -> Result<()>;
   async fn delete(&self, file_name: &FileName) -> Result<()>;
   async fn find(&self, file_name: &FileName) -> Result<Option<AlbumReadModel>>;
+  /**
+   * Whether searches apply a query's boost profile, queries carrying one are rejected otherwise
+   */
+  fn supports_boost_profiles(&self) -> bool {
+    false
+  }
   async fn search(
     &self,
     query: &AlbumSearchQuery,
     pagination: Option<&SearchPagination>,
   ) -> Result<AlbumSearchResult>;
+  /**
+   * Like `search`, but pages by cursor so deep pages don't have to skip every earlier result.
+   * Totals count the albums from the cursor's sort value on.
+   */
+  async fn search_from_cursor(
+    &self,
+    query: &AlbumSearchQuery,
+    cursor: Option<&AlbumSearchCursor>,
+    limit: usize,
+  ) -> Result<(AlbumSearchResult, Option<AlbumSearchCursor>)> {
+    let (page_query, pagination) = cursor_page_query(query, cursor, limit);
+    let result = self.search(&page_query, Some(&pagination)).await?;
+    let next_cursor = next_cursor(query, cursor, &result.albums, limit);
+    Ok((result, next_cursor))
+  }
+  /**
+   * Up to `count` albums picked uniformly at random from those matching the query. Each pick is
+   * fetched at a random offset by default, which costs as much as skipping the albums before it,
+   * so backends that can order by a random score should.
+   */
+  async fn get_random(
+    &self,
+    query: &AlbumSearchQuery,
+    count: usize,
+  ) -> Result<Vec<AlbumReadModel>> {
+    let total = self
+      .search(
+        query,
+        Some(&SearchPagination {
+          offset: Some(0),
+          limit: Some(0),
+        }),
+      )
+      .await?
+      .total;
+    let offsets = sample(&mut rand::thread_rng(), total, count.min(total)).into_vec();
+    let pages = try_join_all(offsets.into_iter().map(|offset| async move {
+      self
+        .search(
+          query,
+          Some(&SearchPagination {
+            offset: Some(offset),
+            limit: Some(1),
+          }),
+        )
+        .await
+    }))
+    .await?;
+    Ok(pages.into_iter().flat_map(|page| page.albums).collect())
+  }
+  /**
+   * Facets of the albums matching the query, keeping up to `limit` values per facet
+   */
+  async fn get_facets(&self, query: &AlbumSearchQuery, limit: usize) -> Result<AlbumSearchFacets>;
   async fn get_embedding_keys(&self) -> Result<Vec<String>>;
   async fn get_embeddings(&self, file_name: &FileName) -> Result<Vec<EmbeddingDocument>>;
   async fn find_many_embeddings(
@@ -77,3 +268,34 @@ pub trait AlbumSearchIndex {
     query: &AlbumEmbeddingSimilarirtySearchQuery,
   ) -> Result<Vec<(AlbumReadModel, f32)>>;
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_validate_expression() {
+    let genre = |name: &str| {
+      AlbumSearchExpression::Predicate(AlbumSearchPredicate::PrimaryGenre(name.to_string()))
+    };
+    assert!(AlbumSearchExpression::And(vec![
+      AlbumSearchExpression::Or(vec![genre("Shoegaze"), genre("Dream Pop")]),
+      AlbumSearchExpression::Not(Box::new(genre("Noise Pop"))),
+    ])
+    .validate()
+    .is_ok());
+    assert!(
+      AlbumSearchExpression::Not(Box::new(AlbumSearchExpression::Or(vec![])))
+        .validate()
+        .is_err()
+    );
+    assert!(
+      AlbumSearchExpression::Predicate(AlbumSearchPredicate::ReleaseYear {
+        min: None,
+        max: None
+      })
+      .validate()
+      .is_err()
+    );
+  }
+}
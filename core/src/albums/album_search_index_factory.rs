@@ -0,0 +1,84 @@
+use super::{
+  album_search_index::AlbumSearchIndex, es_album_search_index::EsAlbumSearchIndex,
+  qdrant_album_search_index::QdrantAlbumSearchIndex,
+  redis_album_search_index::RedisAlbumSearchIndex,
+  sqlite_album_search_index::SqliteAlbumSearchIndex,
+};
+use crate::{
+  embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
+  settings::{AlbumEmbeddingStore, AlbumSearchIndexBackend, Settings, StorageMode},
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use elasticsearch::Elasticsearch;
+use rustis::{bb8::Pool, client::PooledClientManager};
+use std::sync::Arc;
+use tracing::info;
+
+pub struct AlbumSearchIndexFactory {
+  pub settings: Arc<Settings>,
+  pub sqlite_connection: Arc<SqliteConnection>,
+  pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  pub elasticsearch_client: Arc<Elasticsearch>,
+  pub embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
+}
+
+impl AlbumSearchIndexFactory {
+  /**
+   * With embeddings in Qdrant, album documents are kept out of Redis too, since holding them in
+   * memory is what Qdrant is there to avoid. The SQLite index stands in for the Redis one.
+   */
+  fn backend(&self) -> AlbumSearchIndexBackend {
+    let settings = &self.settings.album_search_index;
+    match (&self.settings.storage.mode, &settings.backend) {
+      (StorageMode::Sqlite, _) => AlbumSearchIndexBackend::Sqlite,
+      (StorageMode::Redis, AlbumSearchIndexBackend::Redis)
+        if settings.embedding_store == AlbumEmbeddingStore::Qdrant =>
+      {
+        AlbumSearchIndexBackend::Sqlite
+      }
+      (StorageMode::Redis, backend) => backend.clone(),
+    }
+  }
+
+  fn build_backend(&self) -> Arc<dyn AlbumSearchIndex + Send + Sync + 'static> {
+    match self.backend() {
+      AlbumSearchIndexBackend::Redis => Arc::new(RedisAlbumSearchIndex::new(
+        Arc::clone(&self.redis_connection_pool),
+        Arc::clone(&self.embedding_provider_interactor),
+      )),
+      AlbumSearchIndexBackend::Elasticsearch => Arc::new(EsAlbumSearchIndex::new(Arc::clone(
+        &self.elasticsearch_client,
+      ))),
+      AlbumSearchIndexBackend::Sqlite => Arc::new(SqliteAlbumSearchIndex::new(Arc::clone(
+        &self.sqlite_connection,
+      ))),
+    }
+  }
+
+  /**
+   * Builds the album search index selected in settings, optionally moving embeddings to a
+   * dedicated vector store.
+   */
+  pub fn build(&self) -> Result<Arc<dyn AlbumSearchIndex + Send + Sync + 'static>> {
+    let settings = &self.settings.album_search_index;
+    info!(
+      backend = format!("{:?}", self.backend()),
+      embedding_store = format!("{:?}", settings.embedding_store),
+      "Building album search index"
+    );
+    let backend = self.build_backend();
+    Ok(match settings.embedding_store {
+      AlbumEmbeddingStore::Backend => backend,
+      AlbumEmbeddingStore::Qdrant => Arc::new(QdrantAlbumSearchIndex::new(
+        self
+          .settings
+          .qdrant
+          .clone()
+          .ok_or_else(|| anyhow!("Missing Qdrant settings"))?,
+        backend,
+        Arc::clone(&self.embedding_provider_interactor),
+      )),
+    })
+  }
+}
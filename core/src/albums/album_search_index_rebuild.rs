@@ -0,0 +1,141 @@
+use crate::{context::ApplicationContext, files::file_metadata::file_name::FileName};
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+const PROGRESS_KEY: &str = "album_search_index_rebuild:progress";
+const ABORT_KEY: &str = "album_search_index_rebuild:abort";
+
+#[derive(Clone, Debug)]
+pub struct AlbumSearchIndexRebuildParameters {
+  pub batch_size: u32,
+  pub throttle: Duration,
+}
+
+impl Default for AlbumSearchIndexRebuildParameters {
+  fn default() -> Self {
+    Self {
+      batch_size: 250,
+      throttle: Duration::from_millis(100),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AlbumSearchIndexRebuildProgress {
+  pub done: u32,
+  pub total: u32,
+  pub running: bool,
+  pub aborted: bool,
+  pub error: Option<String>,
+}
+
+pub async fn get_album_search_index_rebuild_progress(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Option<AlbumSearchIndexRebuildProgress>> {
+  app_context.kv.get(PROGRESS_KEY).await
+}
+
+/**
+ * Marks a album search index rebuild left running by a previous process as interrupted
+ */
+pub async fn recover_album_search_index_rebuild_progress(
+  app_context: Arc<ApplicationContext>,
+) -> Result<()> {
+  if let Some(mut progress) =
+    get_album_search_index_rebuild_progress(Arc::clone(&app_context)).await?
+  {
+    if progress.running {
+      warn!("Album search index rebuild was interrupted by a restart");
+      progress.running = false;
+      progress.error = Some("Interrupted by a restart".to_string());
+      app_context.kv.set(PROGRESS_KEY, progress, None).await?;
+    }
+  }
+  Ok(())
+}
+
+/**
+ * Asks a running rebuild to stop. It's picked up between batches, so albums already written stay
+ * in the index.
+ */
+pub async fn abort_album_search_index_rebuild(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context.kv.set(ABORT_KEY, true, None).await
+}
+
+async fn rebuild_batches(
+  app_context: &Arc<ApplicationContext>,
+  parameters: &AlbumSearchIndexRebuildParameters,
+  progress: &mut AlbumSearchIndexRebuildProgress,
+) -> Result<()> {
+  let album_interactor = &app_context.album_interactor;
+  album_interactor.setup_search_index().await?;
+  let mut after: Option<FileName> = None;
+  loop {
+    if app_context
+      .kv
+      .get::<bool>(ABORT_KEY)
+      .await?
+      .unwrap_or(false)
+    {
+      warn!(done = progress.done, "Album search index rebuild aborted");
+      progress.aborted = true;
+      return Ok(());
+    }
+    let albums = album_interactor
+      .find_page(after.clone(), parameters.batch_size)
+      .await?;
+    let page_size = albums.len() as u32;
+    after = albums.last().map(|album| album.file_name.clone());
+    album_interactor.reindex_many(albums).await?;
+    progress.done += page_size;
+    app_context
+      .kv
+      .set(PROGRESS_KEY, progress.clone(), None)
+      .await?;
+    if page_size < parameters.batch_size {
+      return Ok(());
+    }
+    sleep(parameters.throttle).await;
+  }
+}
+
+/**
+ * Rewrites every album in the repository to the album search index, a page at a time with a pause
+ * between pages so the index stays responsive. Progress is recorded after every page.
+ */
+pub async fn run_album_search_index_rebuild(
+  app_context: Arc<ApplicationContext>,
+  parameters: AlbumSearchIndexRebuildParameters,
+) -> Result<()> {
+  app_context.kv.delete(ABORT_KEY).await?;
+  let mut progress = AlbumSearchIndexRebuildProgress {
+    total: app_context.album_interactor.count_albums().await?,
+    running: true,
+    ..Default::default()
+  };
+  app_context
+    .kv
+    .set(PROGRESS_KEY, progress.clone(), None)
+    .await?;
+  info!(
+    total = progress.total,
+    batch_size = parameters.batch_size,
+    "Rebuilding album search index"
+  );
+  let result = rebuild_batches(&app_context, &parameters, &mut progress).await;
+  if let Err(e) = &result {
+    error!(
+      error = e.to_string(),
+      done = progress.done,
+      "Album search index rebuild failed"
+    );
+    progress.error = Some(e.to_string());
+  }
+  progress.running = false;
+  app_context.kv.set(PROGRESS_KEY, progress, None).await?;
+  app_context.kv.delete(ABORT_KEY).await?;
+  result
+}
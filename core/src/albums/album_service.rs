@@ -1,19 +1,33 @@
 use super::{
+  album_clustering::AlbumCluster,
+  album_duplicate_candidate::{AlbumDuplicateCandidate, AlbumDuplicateCandidateStatus},
   album_interactor::{AlbumInteractor, AlbumMonitor},
+  album_query_translation::{apply_hints, translate_query},
+  album_query_translation_client::AlbumQueryTranslationClient,
   album_repository::{GenreAggregate, ItemAndCount},
-  album_search_index::AlbumSearchQuery,
+  album_search_boost_profile::AlbumSearchBoostProfile,
+  album_search_cursor::{AlbumSearchCursor, DEFAULT_CURSOR_PAGE_SIZE},
+  album_search_index::{
+    AlbumSearchExpression, AlbumSearchFacets, AlbumSearchPredicate, AlbumSearchQuery,
+    AlbumSearchSort, AlbumSearchSortField,
+  },
+  album_text_search::{find_highlights, AlbumTextMatchMode},
 };
 use crate::{
   context::ApplicationContext,
+  crawler::crawler::{Crawler, QueuePushParameters},
+  embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
   files::file_metadata::file_name::FileName,
-  helpers::embedding::EmbeddingDocument,
+  helpers::{embedding::EmbeddingDocument, priority::Priority, redisearch::SearchPagination},
   proto,
+  settings::Settings,
   spotify::spotify_client::{SpotifyAlbum, SpotifyAlbumType, SpotifyClient},
 };
 use anyhow::{Error, Result};
-use std::sync::Arc;
+use chrono::Datelike;
+use std::{str::FromStr, sync::Arc};
 use tonic::{async_trait, Request, Response, Status, Streaming};
-use tracing::error;
+use tracing::{error, warn};
 
 impl From<GenreAggregate> for proto::GenreAggregate {
   fn from(val: GenreAggregate) -> Self {
@@ -25,6 +39,18 @@ impl From<GenreAggregate> for proto::GenreAggregate {
   }
 }
 
+impl From<AlbumDuplicateCandidate> for proto::AlbumDuplicateCandidate {
+  fn from(val: AlbumDuplicateCandidate) -> Self {
+    proto::AlbumDuplicateCandidate {
+      original_file_name: val.original_file_name.to_string(),
+      duplicate_file_name: val.duplicate_file_name.to_string(),
+      name_similarity: val.name_similarity as f32,
+      status: val.status.to_string(),
+      detected_at: val.detected_at.to_string(),
+    }
+  }
+}
+
 impl From<AlbumMonitor> for proto::AlbumMonitor {
   fn from(val: AlbumMonitor) -> Self {
     proto::AlbumMonitor {
@@ -64,6 +90,34 @@ impl From<ItemAndCount> for proto::ItemAndCount {
   }
 }
 
+impl From<AlbumCluster> for proto::AlbumCluster {
+  fn from(val: AlbumCluster) -> Self {
+    proto::AlbumCluster {
+      id: val.id,
+      embedding_key: val.embedding_key,
+      size: val.size,
+      label: val.label,
+      primary_genres: val.primary_genres.into_iter().map(Into::into).collect(),
+      descriptors: val.descriptors.into_iter().map(Into::into).collect(),
+      created_at: val.created_at.to_string(),
+    }
+  }
+}
+
+impl From<AlbumSearchFacets> for proto::AlbumSearchFacets {
+  fn from(val: AlbumSearchFacets) -> Self {
+    let into_proto = |items: Vec<ItemAndCount>| items.into_iter().map(|item| item.into()).collect();
+    proto::AlbumSearchFacets {
+      primary_genres: into_proto(val.primary_genres),
+      descriptors: into_proto(val.descriptors),
+      languages: into_proto(val.languages),
+      release_decades: into_proto(val.release_decades),
+    }
+  }
+}
+
+const DEFAULT_FACET_LIMIT: usize = 20;
+
 fn parse_file_name_list(file_names: Vec<String>) -> Result<Vec<FileName>> {
   file_names
     .into_iter()
@@ -71,11 +125,83 @@ fn parse_file_name_list(file_names: Vec<String>) -> Result<Vec<FileName>> {
     .collect()
 }
 
+impl TryFrom<proto::AlbumSearchPredicate> for AlbumSearchPredicate {
+  type Error = anyhow::Error;
+
+  fn try_from(value: proto::AlbumSearchPredicate) -> Result<Self> {
+    use proto::album_search_predicate::Predicate;
+    Ok(match value.predicate {
+      Some(Predicate::PrimaryGenre(genre)) => AlbumSearchPredicate::PrimaryGenre(genre),
+      Some(Predicate::SecondaryGenre(genre)) => AlbumSearchPredicate::SecondaryGenre(genre),
+      Some(Predicate::Descriptor(descriptor)) => AlbumSearchPredicate::Descriptor(descriptor),
+      Some(Predicate::Language(language)) => AlbumSearchPredicate::Language(language),
+      Some(Predicate::ArtistFileName(file_name)) => {
+        AlbumSearchPredicate::Artist(FileName::try_from(file_name).map_err(anyhow::Error::msg)?)
+      }
+      Some(Predicate::ReleaseYear(range)) => AlbumSearchPredicate::ReleaseYear {
+        min: range.min,
+        max: range.max,
+      },
+      None => return Err(anyhow::anyhow!("Predicate not provided")),
+    })
+  }
+}
+
+impl TryFrom<proto::AlbumSearchExpression> for AlbumSearchExpression {
+  type Error = anyhow::Error;
+
+  fn try_from(value: proto::AlbumSearchExpression) -> Result<Self> {
+    use proto::album_search_expression::Expression;
+    let parse_list = |list: proto::AlbumSearchExpressionList| {
+      list
+        .expressions
+        .into_iter()
+        .map(AlbumSearchExpression::try_from)
+        .collect::<Result<Vec<_>>>()
+    };
+    Ok(match value.expression {
+      Some(Expression::Predicate(predicate)) => {
+        AlbumSearchExpression::Predicate(predicate.try_into()?)
+      }
+      Some(Expression::And(list)) => AlbumSearchExpression::And(parse_list(list)?),
+      Some(Expression::Or(list)) => AlbumSearchExpression::Or(parse_list(list)?),
+      Some(Expression::Not(expression)) => {
+        AlbumSearchExpression::Not(Box::new((*expression).try_into()?))
+      }
+      None => return Err(anyhow::anyhow!("Expression not provided")),
+    })
+  }
+}
+
+impl From<proto::AlbumTextMatchMode> for AlbumTextMatchMode {
+  fn from(val: proto::AlbumTextMatchMode) -> Self {
+    match val {
+      proto::AlbumTextMatchMode::TextMatchLoose => AlbumTextMatchMode::Loose,
+      proto::AlbumTextMatchMode::TextMatchStrict => AlbumTextMatchMode::Strict,
+    }
+  }
+}
+
+impl From<proto::AlbumSearchSort> for AlbumSearchSort {
+  fn from(val: proto::AlbumSearchSort) -> Self {
+    AlbumSearchSort {
+      field: match val.field() {
+        proto::AlbumSearchSortField::AlbumSortRating => AlbumSearchSortField::Rating,
+        proto::AlbumSearchSortField::AlbumSortRatingCount => AlbumSearchSortField::RatingCount,
+        proto::AlbumSearchSortField::AlbumSortReleaseDate => AlbumSearchSortField::ReleaseDate,
+        proto::AlbumSearchSortField::AlbumSortName => AlbumSearchSortField::Name,
+      },
+      descending: val.descending,
+    }
+  }
+}
+
 impl TryFrom<proto::AlbumSearchQuery> for AlbumSearchQuery {
   type Error = anyhow::Error;
 
   fn try_from(value: proto::AlbumSearchQuery) -> Result<Self> {
     Ok(AlbumSearchQuery {
+      text_match_mode: value.text_match_mode().into(),
       text: value.text,
       exact_name: value.exact_name,
       include_file_names: parse_file_name_list(value.include_file_names)?,
@@ -90,16 +216,54 @@ impl TryFrom<proto::AlbumSearchQuery> for AlbumSearchQuery {
       exclude_languages: value.exclude_languages,
       include_descriptors: value.include_descriptors,
       exclude_descriptors: value.exclude_descriptors,
+      include_tags: value.include_tags,
+      exclude_tags: value.exclude_tags,
+      include_cluster_ids: value.include_cluster_ids,
       min_primary_genre_count: value.min_primary_genre_count.map(|i| i as usize),
       min_secondary_genre_count: value.min_secondary_genre_count.map(|i| i as usize),
       min_descriptor_count: value.min_descriptor_count.map(|i| i as usize),
       min_release_year: value.min_release_year,
       max_release_year: value.max_release_year,
+      min_rating: value.min_rating,
+      max_rating: value.max_rating,
+      min_rating_count: value.min_rating_count,
+      max_rating_count: value.max_rating_count,
       include_duplicates: value.include_duplicates,
+      expression: value
+        .expression
+        .map(|expression| {
+          let expression = AlbumSearchExpression::try_from(expression)?;
+          expression.validate()?;
+          Ok::<_, anyhow::Error>(expression)
+        })
+        .transpose()?,
+      boost_profile: None,
+      sort: value.sort.map(Into::into),
+      search_after: None,
     })
   }
 }
 
+/**
+ * Only covers the filters a natural language query can be translated to
+ */
+fn translated_query_into_proto(query: AlbumSearchQuery) -> proto::AlbumSearchQuery {
+  proto::AlbumSearchQuery {
+    include_primary_genres: query.include_primary_genres,
+    exclude_primary_genres: query.exclude_primary_genres,
+    include_languages: query.include_languages,
+    exclude_languages: query.exclude_languages,
+    include_descriptors: query.include_descriptors,
+    exclude_descriptors: query.exclude_descriptors,
+    min_release_year: query.min_release_year,
+    max_release_year: query.max_release_year,
+    min_rating: query.min_rating,
+    min_rating_count: query.min_rating_count,
+    max_rating_count: query.max_rating_count,
+    ..Default::default()
+  }
+}
+
 impl TryFrom<SpotifyAlbum> for proto::SpotifyAlbum {
   type Error = anyhow::Error;
 
@@ -127,18 +291,80 @@ impl TryFrom<SpotifyAlbum> for proto::SpotifyAlbum {
 }
 pub struct AlbumService {
   album_interactor: Arc<AlbumInteractor>,
+  crawler: Arc<Crawler>,
   spotify_client: Arc<SpotifyClient>,
+  embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
+  query_translation_client: Option<AlbumQueryTranslationClient>,
+  settings: Arc<Settings>,
 }
 
 impl AlbumService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       album_interactor: Arc::clone(&app_context.album_interactor),
+      crawler: Arc::clone(&app_context.crawler),
       spotify_client: Arc::clone(&app_context.spotify_client),
+      embedding_provider_interactor: Arc::clone(&app_context.embedding_provider_interactor),
+      query_translation_client: app_context
+        .settings
+        .natural_language_search
+        .as_ref()
+        .map(AlbumQueryTranslationClient::new),
+      settings: Arc::clone(&app_context.settings),
     }
   }
 }
 
+/**
+ * An inline override wins over a named profile, which wins over the configured default. A
+ * missing default is logged rather than failing every search, and so is a default on a backend
+ * that can't apply it.
+ */
+pub async fn resolve_boost_profile(
+  album_interactor: &AlbumInteractor,
+  settings: &Settings,
+  name: Option<String>,
+  profile_override: Option<proto::AlbumSearchBoostProfile>,
+) -> Result<Option<AlbumSearchBoostProfile>, Status> {
+  let requested = name.is_some() || profile_override.is_some();
+  if requested && !album_interactor.supports_search_boost_profiles() {
+    return Err(Status::invalid_argument(
+      "Boost profiles need the Elasticsearch album search index",
+    ));
+  }
+  if let Some(profile) = profile_override {
+    return AlbumSearchBoostProfile::try_from(profile)
+      .map(Some)
+      .map_err(|e| Status::invalid_argument(format!("Invalid boost profile: {}", e)));
+  }
+  if let Some(name) = name {
+    return album_interactor
+      .find_search_boost_profile(&name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .map(Some)
+      .ok_or_else(|| Status::not_found(format!("Boost profile {} not found", name)));
+  }
+  let Some(name) = &settings.album_search_index.default_boost_profile else {
+    return Ok(None);
+  };
+  if !album_interactor.supports_search_boost_profiles() {
+    warn!(
+      name,
+      "Default boost profile ignored by the album search index"
+    );
+    return Ok(None);
+  }
+  let profile = album_interactor
+    .find_search_boost_profile(name)
+    .await
+    .map_err(|e| Status::internal(e.to_string()))?;
+  if profile.is_none() {
+    warn!(name, "Default boost profile not found");
+  }
+  Ok(profile)
+}
+
 #[async_trait]
 impl proto::AlbumService for AlbumService {
   async fn get_monitor(
@@ -190,23 +416,103 @@ impl proto::AlbumService for AlbumService {
     Ok(Response::new(reply))
   }
 
+  async fn filter_existing_albums(
+    &self,
+    request: Request<proto::FilterExistingAlbumsRequest>,
+  ) -> Result<Response<proto::FilterExistingAlbumsReply>, Status> {
+    let file_names = parse_file_name_list(request.into_inner().file_names)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let existing = self
+      .album_interactor
+      .filter_existing(file_names)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::FilterExistingAlbumsReply {
+      file_names: existing.into_iter().map(|f| f.to_string()).collect(),
+    }))
+  }
+
   async fn search_albums(
     &self,
     request: Request<proto::SearchAlbumsRequest>,
   ) -> Result<Response<proto::SearchAlbumsReply>, Status> {
     let request = request.into_inner();
-    let query: AlbumSearchQuery = request
+    let mut query: AlbumSearchQuery = request
       .query
       .map(|q| q.try_into())
       .transpose()
       .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e)))?
       .unwrap_or_default();
-    let pagination = request.pagination.map(|p| p.into());
-    let results = self
-      .album_interactor
-      .search(&query, pagination.as_ref())
-      .await
-      .map_err(|e| Status::internal(e.to_string()))?;
+    query.boost_profile = resolve_boost_profile(
+      &self.album_interactor,
+      &self.settings,
+      request.boost_profile,
+      request.boost_profile_override,
+    )
+    .await?;
+    let pagination: Option<SearchPagination> = request.pagination.map(|p| p.into());
+    let (results, next_cursor) = match request.cursor {
+      Some(cursor) => {
+        if pagination.as_ref().is_some_and(|p| p.offset.is_some()) {
+          return Err(Status::invalid_argument(
+            "Cursor and offset pagination can't be combined",
+          ));
+        }
+        let cursor = AlbumSearchCursor::decode(&cursor)
+          .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let limit = pagination
+          .and_then(|p| p.limit)
+          .unwrap_or(DEFAULT_CURSOR_PAGE_SIZE);
+        self
+          .album_interactor
+          .search_from_cursor(&query, cursor.as_ref(), limit)
+          .await
+          .map_err(|e| Status::internal(e.to_string()))?
+      }
+      None => (
+        self
+          .album_interactor
+          .search(&query, pagination.as_ref())
+          .await
+          .map_err(|e| Status::internal(e.to_string()))?,
+        None,
+      ),
+    };
+    let highlights = query
+      .text_search_plan()
+      .map(|plan| {
+        results
+          .albums
+          .iter()
+          .flat_map(|album| {
+            find_highlights(album, &plan)
+              .into_iter()
+              .map(|highlight| proto::AlbumSearchHighlight {
+                file_name: album.file_name.to_string(),
+                field: highlight.field.to_string(),
+                value: highlight.value,
+              })
+          })
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+    let facets = if request.include_facets {
+      Some(
+        self
+          .album_interactor
+          .get_search_facets(
+            &query,
+            request
+              .facet_limit
+              .map_or(DEFAULT_FACET_LIMIT, |limit| limit as usize),
+          )
+          .await
+          .map_err(|e| Status::internal(e.to_string()))?
+          .into(),
+      )
+    } else {
+      None
+    };
     let reply = proto::SearchAlbumsReply {
       albums: results
         .albums
@@ -214,10 +520,79 @@ impl proto::AlbumService for AlbumService {
         .map(|album| album.into())
         .collect::<Vec<proto::Album>>(),
       total: results.total as u32,
+      highlights,
+      next_cursor: next_cursor.map(|cursor| cursor.encode()),
+      facets,
     };
     Ok(Response::new(reply))
   }
 
+  async fn get_random_albums(
+    &self,
+    request: Request<proto::GetRandomAlbumsRequest>,
+  ) -> Result<Response<proto::GetRandomAlbumsReply>, Status> {
+    let request = request.into_inner();
+    let query: AlbumSearchQuery = request
+      .query
+      .map(|q| q.try_into())
+      .transpose()
+      .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e)))?
+      .unwrap_or_default();
+    let albums = self
+      .album_interactor
+      .get_random_albums(&query, request.count as usize)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let reply = proto::GetRandomAlbumsReply {
+      albums: albums.into_iter().map(|album| album.into()).collect(),
+    };
+    Ok(Response::new(reply))
+  }
+
+  async fn get_search_boost_profiles(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::GetSearchBoostProfilesReply>, Status> {
+    let profiles = self
+      .album_interactor
+      .find_search_boost_profiles()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetSearchBoostProfilesReply {
+      profiles: profiles.into_iter().map(|profile| profile.into()).collect(),
+    }))
+  }
+
+  async fn put_search_boost_profile(
+    &self,
+    request: Request<proto::PutSearchBoostProfileRequest>,
+  ) -> Result<Response<()>, Status> {
+    let profile = request
+      .into_inner()
+      .profile
+      .ok_or_else(|| Status::invalid_argument("Boost profile is required"))?;
+    let profile = AlbumSearchBoostProfile::try_from(profile)
+      .map_err(|e| Status::invalid_argument(format!("Invalid boost profile: {}", e)))?;
+    self
+      .album_interactor
+      .put_search_boost_profile(profile)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  async fn delete_search_boost_profile(
+    &self,
+    request: Request<proto::DeleteSearchBoostProfileRequest>,
+  ) -> Result<Response<()>, Status> {
+    self
+      .album_interactor
+      .delete_search_boost_profile(&request.into_inner().name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
   async fn get_embedding_keys(
     &self,
     _request: Request<()>,
@@ -230,6 +605,7 @@ impl proto::AlbumService for AlbumService {
         .map_err(|e| {
           Status::internal(format!("Failed to get embedding keys: {}", e.to_string()))
         })?,
+      default_key: self.embedding_provider_interactor.default_provider_name(),
     };
     Ok(Response::new(reply))
   }
@@ -241,7 +617,10 @@ impl proto::AlbumService for AlbumService {
     let inner = request.into_inner();
     let file_name =
       FileName::try_from(inner.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
-    let embedding_key = inner.embedding_key;
+    let embedding_key = self
+      .embedding_provider_interactor
+      .resolve_embedding_key(inner.embedding_key)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
     let limit = inner.limit.unwrap_or(10) as usize;
     let filters: Option<AlbumSearchQuery> = inner
       .filters
@@ -259,6 +638,146 @@ impl proto::AlbumService for AlbumService {
     Ok(Response::new(reply))
   }
 
+  async fn get_album_embedding_projection(
+    &self,
+    request: Request<proto::GetAlbumEmbeddingProjectionRequest>,
+  ) -> Result<Response<proto::GetAlbumEmbeddingProjectionReply>, Status> {
+    let request = request.into_inner();
+    let dimensions = request.dimensions.unwrap_or(2);
+    if !(2..=3).contains(&dimensions) {
+      return Err(Status::invalid_argument("Dimensions must be 2 or 3"));
+    }
+    let embedding_key = self
+      .embedding_provider_interactor
+      .resolve_embedding_key(request.embedding_key)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let query: AlbumSearchQuery = request
+      .query
+      .map(|q| q.try_into())
+      .transpose()
+      .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e)))?
+      .unwrap_or_default();
+    let projection = self
+      .album_interactor
+      .project_embeddings(
+        &query,
+        &embedding_key,
+        dimensions as usize,
+        request.limit.unwrap_or(1000) as usize,
+      )
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetAlbumEmbeddingProjectionReply {
+      albums: projection
+        .albums
+        .into_iter()
+        .map(|(album, coordinates)| proto::ProjectedAlbum {
+          file_name: album.file_name.to_string(),
+          artists: album.artist_names(),
+          release_year: album.release_date.map(|date| date.year() as u32),
+          name: album.name,
+          primary_genres: album.primary_genres,
+          coordinates,
+        })
+        .collect(),
+      explained_variance_ratios: projection.explained_variance_ratios,
+      embedding_key,
+    }))
+  }
+
+  async fn get_album_clusters(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::GetAlbumClustersReply>, Status> {
+    let clusters = self
+      .album_interactor
+      .get_clusters()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetAlbumClustersReply {
+      clusters: clusters.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn cluster_albums(
+    &self,
+    request: Request<proto::ClusterAlbumsRequest>,
+  ) -> Result<Response<proto::ClusterAlbumsReply>, Status> {
+    let request = request.into_inner();
+    let settings = &self.settings.album_clustering;
+    let cluster_count = request.cluster_count.unwrap_or(settings.cluster_count);
+    if cluster_count == 0 {
+      return Err(Status::invalid_argument("Cluster count must be positive"));
+    }
+    let embedding_key = if request.embedding_key.is_empty() {
+      settings.embedding_key.clone().unwrap_or_default()
+    } else {
+      request.embedding_key
+    };
+    let embedding_key = self
+      .embedding_provider_interactor
+      .resolve_embedding_key(embedding_key)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let clusters = self
+      .album_interactor
+      .cluster_albums(
+        &embedding_key,
+        cluster_count as usize,
+        settings.max_iterations as usize,
+      )
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ClusterAlbumsReply {
+      clusters: clusters.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn search_albums_by_natural_language(
+    &self,
+    request: Request<proto::SearchAlbumsByNaturalLanguageRequest>,
+  ) -> Result<Response<proto::SearchAlbumsByNaturalLanguageReply>, Status> {
+    let request = request.into_inner();
+    if request.query.trim().is_empty() {
+      return Err(Status::invalid_argument("Query not provided"));
+    }
+    let vocabulary = self
+      .album_interactor
+      .get_query_vocabulary()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let mut translation = translate_query(&request.query, &vocabulary);
+    let mut used_llm = false;
+    if let Some(client) = self
+      .query_translation_client
+      .as_ref()
+      .filter(|_| request.use_llm.unwrap_or(true))
+    {
+      match client.translate(&request.query).await {
+        Ok(hints) => {
+          apply_hints(&mut translation, hints, &vocabulary);
+          used_llm = true;
+        }
+        Err(e) => warn!(
+          error = e.to_string(),
+          "Language model query translation failed, using the rule-based translation"
+        ),
+      }
+    }
+    let pagination: Option<SearchPagination> = request.pagination.map(|p| p.into());
+    let results = self
+      .album_interactor
+      .search(&translation.query, pagination.as_ref())
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::SearchAlbumsByNaturalLanguageReply {
+      query: Some(translated_query_into_proto(translation.query)),
+      albums: results.albums.into_iter().map(Into::into).collect(),
+      total: results.total as u32,
+      unmatched_terms: translation.unmatched_terms,
+      used_llm,
+    }))
+  }
+
   async fn find_spotify_album(
     &self,
     request: Request<proto::FindSpotifyAlbumRequest>,
@@ -316,4 +835,161 @@ impl proto::AlbumService for AlbumService {
       count,
     }))
   }
+
+  async fn recrawl_albums(
+    &self,
+    request: Request<proto::RecrawlAlbumsRequest>,
+  ) -> Result<Response<proto::RecrawlAlbumsReply>, Status> {
+    let request = request.into_inner();
+    let priority = Priority::from(request.priority());
+    let query: AlbumSearchQuery = request
+      .query
+      .ok_or_else(|| Status::invalid_argument("A query is required"))?
+      .try_into()
+      .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e)))?;
+    let file_names = self
+      .album_interactor
+      .find_matching_file_names(&query, request.limit.map(|limit| limit as usize))
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let count = file_names.len() as u32;
+    if request.dry_run {
+      let cost = self
+        .crawler
+        .estimate_cost(&file_names, false)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+      return Ok(Response::new(proto::RecrawlAlbumsReply {
+        count,
+        cost: Some(cost.into()),
+      }));
+    }
+    self
+      .crawler
+      .enqueue_many(
+        file_names
+          .into_iter()
+          .map(|file_name| QueuePushParameters {
+            file_name,
+            priority: Some(priority),
+            correlation_id: request.correlation_id.clone(),
+          })
+          .collect(),
+      )
+      .await
+      .inspect_err(|e| {
+        error!(e = e.to_string(), "Failed to enqueue album recrawls");
+      })
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::RecrawlAlbumsReply {
+      count,
+      cost: None,
+    }))
+  }
+
+  async fn put_album_tags(
+    &self,
+    request: Request<proto::PutAlbumTagsRequest>,
+  ) -> Result<Response<proto::PutAlbumTagsReply>, Status> {
+    let request = request.into_inner();
+    let file_name =
+      FileName::try_from(request.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let tags = self
+      .album_interactor
+      .set_tags(&file_name, request.tags)
+      .await
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    Ok(Response::new(proto::PutAlbumTagsReply { tags }))
+  }
+
+  async fn get_aggregated_tags(
+    &self,
+    request: Request<proto::GetAggregatedTagsRequest>,
+  ) -> Result<Response<proto::GetAggregatedTagsReply>, Status> {
+    let tags = self
+      .album_interactor
+      .get_aggregated_tags(request.into_inner().limit)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetAggregatedTagsReply {
+      tags: tags.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn get_album_notes(
+    &self,
+    request: Request<proto::GetAlbumNotesRequest>,
+  ) -> Result<Response<proto::GetAlbumNotesReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let notes = self
+      .album_interactor
+      .find_notes(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(match notes {
+      Some(notes) => proto::GetAlbumNotesReply {
+        notes: Some(notes.notes),
+        updated_at: Some(notes.updated_at.to_string()),
+      },
+      None => proto::GetAlbumNotesReply {
+        notes: None,
+        updated_at: None,
+      },
+    }))
+  }
+
+  async fn put_album_notes(
+    &self,
+    request: Request<proto::PutAlbumNotesRequest>,
+  ) -> Result<Response<()>, Status> {
+    let request = request.into_inner();
+    let file_name =
+      FileName::try_from(request.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .album_interactor
+      .set_notes(&file_name, request.notes)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  async fn get_album_duplicate_candidates(
+    &self,
+    request: Request<proto::GetAlbumDuplicateCandidatesRequest>,
+  ) -> Result<Response<proto::GetAlbumDuplicateCandidatesReply>, Status> {
+    let status = request
+      .into_inner()
+      .status
+      .map(|status| AlbumDuplicateCandidateStatus::from_str(&status))
+      .transpose()
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let candidates = self
+      .album_interactor
+      .find_duplicate_candidates(status)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetAlbumDuplicateCandidatesReply {
+      candidates: candidates.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn resolve_album_duplicate_candidate(
+    &self,
+    request: Request<proto::ResolveAlbumDuplicateCandidateRequest>,
+  ) -> Result<Response<proto::ResolveAlbumDuplicateCandidateReply>, Status> {
+    let request = request.into_inner();
+    let original_file_name = FileName::try_from(request.original_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let duplicate_file_name = FileName::try_from(request.duplicate_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let candidate = self
+      .album_interactor
+      .resolve_duplicate_candidate(&original_file_name, &duplicate_file_name, request.confirm)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ResolveAlbumDuplicateCandidateReply {
+      candidate: Some(candidate.into()),
+    }))
+  }
 }
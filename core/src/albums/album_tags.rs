@@ -0,0 +1,46 @@
+use anyhow::{bail, Result};
+
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/**
+ * Trims and lowercases tags, dropping duplicates while keeping the order they were given in
+ */
+pub fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>> {
+  let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+  for tag in tags {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+      bail!("Tags cannot be empty");
+    }
+    if tag.len() > MAX_TAG_LENGTH {
+      bail!("Tag is longer than {} characters: {}", MAX_TAG_LENGTH, tag);
+    }
+    if tag.contains(',') {
+      bail!("Tags cannot contain commas: {}", tag);
+    }
+    if !normalized.contains(&tag) {
+      normalized.push(tag);
+    }
+  }
+  Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_normalize_tags() -> Result<()> {
+    assert_eq!(
+      normalize_tags(vec![
+        " Summer ".to_string(),
+        "road trip".to_string(),
+        "summer".to_string(),
+      ])?,
+      vec!["summer", "road trip"]
+    );
+    assert!(normalize_tags(vec!["  ".to_string()]).is_err());
+    assert!(normalize_tags(vec!["a,b".to_string()]).is_err());
+    Ok(())
+  }
+}
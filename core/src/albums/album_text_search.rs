@@ -0,0 +1,218 @@
+use super::album_read_model::AlbumReadModel;
+use unidecode::unidecode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlbumTextMatchMode {
+  /**
+   * Matches the query's own script in either field set, and tolerates typos where the backend
+   * supports it
+   */
+  #[default]
+  Loose,
+  /**
+   * Matches only the fields written in the query's script, without typo tolerance
+   */
+  Strict,
+}
+
+/**
+ * Text fields of an album. The ASCII fields are transliterations of the originals, so a Latin
+ * query can find "Трип" as "Trip".
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum AlbumTextField {
+  Name,
+  AsciiName,
+  ArtistName,
+  ArtistAsciiName,
+}
+
+impl AlbumTextField {
+  pub fn is_transliterated(&self) -> bool {
+    matches!(
+      self,
+      AlbumTextField::AsciiName | AlbumTextField::ArtistAsciiName
+    )
+  }
+
+  pub fn is_artist(&self) -> bool {
+    matches!(
+      self,
+      AlbumTextField::ArtistName | AlbumTextField::ArtistAsciiName
+    )
+  }
+
+  fn values(&self, album: &AlbumReadModel) -> Vec<String> {
+    match self {
+      AlbumTextField::Name => vec![album.name.clone()],
+      AlbumTextField::AsciiName => vec![album.ascii_name()],
+      AlbumTextField::ArtistName => album.artists.iter().map(|a| a.name.clone()).collect(),
+      AlbumTextField::ArtistAsciiName => album.artists.iter().map(|a| a.ascii_name()).collect(),
+    }
+  }
+}
+
+const ORIGINAL_FIELDS: [AlbumTextField; 2] = [AlbumTextField::Name, AlbumTextField::ArtistName];
+const ASCII_FIELDS: [AlbumTextField; 2] =
+  [AlbumTextField::AsciiName, AlbumTextField::ArtistAsciiName];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextScript {
+  Latin,
+  NonLatin,
+}
+
+fn is_latin(c: char) -> bool {
+  // Basic Latin through Latin Extended-B, plus Latin Extended Additional
+  c <= '\u{024F}' || ('\u{1E00}'..='\u{1EFF}').contains(&c)
+}
+
+/**
+ * Non-Latin if any letter is outside the Latin blocks, so mixed queries search the originals
+ */
+pub fn detect_script(text: &str) -> TextScript {
+  if text.chars().filter(|c| c.is_alphabetic()).all(is_latin) {
+    TextScript::Latin
+  } else {
+    TextScript::NonLatin
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumTextSearchClause {
+  pub fields: Vec<AlbumTextField>,
+  pub text: String,
+}
+
+/**
+ * Which fields a text query is matched against, and with what text. Clauses are ORed.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumTextSearchPlan {
+  pub clauses: Vec<AlbumTextSearchClause>,
+  pub fuzzy: bool,
+}
+
+impl AlbumTextSearchPlan {
+  pub fn new(text: &str, mode: AlbumTextMatchMode) -> Self {
+    let original = AlbumTextSearchClause {
+      fields: ORIGINAL_FIELDS.to_vec(),
+      text: text.to_string(),
+    };
+    let transliterated = AlbumTextSearchClause {
+      fields: ASCII_FIELDS.to_vec(),
+      text: unidecode(text),
+    };
+    let clauses = match (mode, detect_script(text)) {
+      (AlbumTextMatchMode::Strict, TextScript::Latin) => vec![transliterated],
+      (AlbumTextMatchMode::Strict, TextScript::NonLatin) => vec![original],
+      (AlbumTextMatchMode::Loose, _) => vec![original, transliterated],
+    };
+    Self {
+      clauses,
+      fuzzy: mode == AlbumTextMatchMode::Loose,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumSearchHighlight {
+  pub field: AlbumTextField,
+  pub value: String,
+}
+
+fn tokens(text: &str) -> Vec<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|token| !token.is_empty())
+    .map(|token| token.to_lowercase())
+    .collect()
+}
+
+/**
+ * Field values of an album matched by a plan, found by prefix-matching the query's terms against
+ * the value's words. Backends rank their own way, so this is a best effort rather than the
+ * backend's reason for the match.
+ */
+pub fn find_highlights(
+  album: &AlbumReadModel,
+  plan: &AlbumTextSearchPlan,
+) -> Vec<AlbumSearchHighlight> {
+  let mut highlights: Vec<AlbumSearchHighlight> = vec![];
+  for clause in plan.clauses.iter() {
+    let terms = tokens(&clause.text);
+    for field in clause.fields.iter() {
+      for value in field.values(album) {
+        let value_tokens = tokens(&value);
+        let matched = terms.iter().any(|term| {
+          value_tokens
+            .iter()
+            .any(|value_token| value_token.starts_with(term.as_str()))
+        });
+        let highlight = AlbumSearchHighlight {
+          field: *field,
+          value,
+        };
+        if matched && !highlights.contains(&highlight) {
+          highlights.push(highlight);
+        }
+      }
+    }
+  }
+  highlights
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_read_model::AlbumReadModelArtist;
+  use anyhow::Result;
+
+  #[test]
+  fn test_plan_selects_fields_by_script() {
+    assert_eq!(detect_script("Björk"), TextScript::Latin);
+    assert_eq!(detect_script("Трип"), TextScript::NonLatin);
+
+    let strict = AlbumTextSearchPlan::new("Трип", AlbumTextMatchMode::Strict);
+    assert_eq!(strict.clauses.len(), 1);
+    assert_eq!(strict.clauses[0].fields, ORIGINAL_FIELDS.to_vec());
+    assert!(!strict.fuzzy);
+
+    let strict = AlbumTextSearchPlan::new("Trip", AlbumTextMatchMode::Strict);
+    assert_eq!(strict.clauses[0].fields, ASCII_FIELDS.to_vec());
+
+    let loose = AlbumTextSearchPlan::new("Трип", AlbumTextMatchMode::Loose);
+    assert_eq!(loose.clauses.len(), 2);
+    assert_eq!(loose.clauses[1].text, "Trip");
+  }
+
+  #[test]
+  fn test_find_highlights() -> Result<()> {
+    let album = AlbumReadModel {
+      name: "Трип".to_string(),
+      artists: vec![AlbumReadModelArtist {
+        name: "Пикник".to_string(),
+        file_name: "artist/piknik".try_into()?,
+      }],
+      ..Default::default()
+    };
+    let highlights = find_highlights(
+      &album,
+      &AlbumTextSearchPlan::new("trip", AlbumTextMatchMode::Loose),
+    );
+    assert_eq!(
+      highlights,
+      vec![AlbumSearchHighlight {
+        field: AlbumTextField::AsciiName,
+        value: "Trip".to_string(),
+      }]
+    );
+    let highlights = find_highlights(
+      &album,
+      &AlbumTextSearchPlan::new("пик", AlbumTextMatchMode::Strict),
+    );
+    assert_eq!(highlights[0].field, AlbumTextField::ArtistName);
+    Ok(())
+  }
+}
@@ -1,10 +1,16 @@
 use super::{
   album_read_model::{
-    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelTrack,
+    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelDiscogsRelease,
+    AlbumReadModelTrack,
   },
+  album_repository::ItemAndCount,
+  album_search_boost_profile::AlbumSearchBoostProfile,
   album_search_index::{
-    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchIndex, AlbumSearchQuery, AlbumSearchResult,
+    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchExpression, AlbumSearchFacets,
+    AlbumSearchIndex, AlbumSearchPredicate, AlbumSearchQuery, AlbumSearchResult,
+    AlbumSearchSortField,
   },
+  album_text_search::AlbumTextField,
 };
 use crate::{
   files::file_metadata::file_name::FileName,
@@ -15,7 +21,7 @@ use crate::{
   },
 };
 use anyhow::Result;
-use chrono::{Datelike, NaiveDate};
+use chrono::{Datelike, NaiveDate, Utc};
 use elasticsearch::Elasticsearch;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -26,10 +32,14 @@ use tracing::error;
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct EsAlbumReadModel {
   pub name: String,
+  #[serde(default)]
+  pub ascii_name: String,
   pub file_name: FileName,
   pub rating: f32,
   pub rating_count: u32,
   pub artists: Vec<AlbumReadModelArtist>,
+  #[serde(default)]
+  pub artist_ascii_names: Vec<String>,
   pub artist_count: u32,
   pub primary_genres: Vec<String>,
   pub primary_genre_count: u32,
@@ -51,15 +61,28 @@ pub struct EsAlbumReadModel {
   pub duplicate_count: u32,
   pub cover_image_url: Option<String>,
   pub spotify_id: Option<String>,
+  pub musicbrainz_id: Option<String>,
+  pub discogs_release: Option<AlbumReadModelDiscogsRelease>,
+  pub is_various_artists: bool,
+  pub bandcamp_url: Option<String>,
+  pub cached_cover_image_url: Option<String>,
+  pub tags: Vec<String>,
+  pub cluster_id: Option<u32>,
 }
 
 impl From<AlbumReadModel> for EsAlbumReadModel {
   fn from(album: AlbumReadModel) -> Self {
     Self {
+      ascii_name: album.ascii_name(),
       name: album.name,
       file_name: album.file_name,
       rating: album.rating,
       rating_count: album.rating_count,
+      artist_ascii_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.ascii_name())
+        .collect(),
       artist_count: album.artists.len() as u32,
       artists: album.artists,
       primary_genre_count: album.primary_genres.len() as u32,
@@ -82,10 +105,129 @@ impl From<AlbumReadModel> for EsAlbumReadModel {
       duplicates: album.duplicates,
       cover_image_url: album.cover_image_url,
       spotify_id: album.spotify_id,
+      musicbrainz_id: album.musicbrainz_id,
+      discogs_release: album.discogs_release,
+      is_various_artists: album.is_various_artists,
+      bandcamp_url: album.bandcamp_url,
+      cached_cover_image_url: album.cached_cover_image_url,
+      tags: album.tags,
+      cluster_id: album.cluster_id,
     }
   }
 }
 
+impl AlbumSearchPredicate {
+  pub fn to_es_query(&self) -> Value {
+    match self {
+      AlbumSearchPredicate::PrimaryGenre(genre) => json!({
+        "term": { "primary_genres.keyword": genre }
+      }),
+      AlbumSearchPredicate::SecondaryGenre(genre) => json!({
+        "term": { "secondary_genres.keyword": genre }
+      }),
+      AlbumSearchPredicate::Descriptor(descriptor) => json!({
+        "term": { "descriptors.keyword": descriptor }
+      }),
+      AlbumSearchPredicate::Language(language) => json!({
+        "term": { "languages.keyword": language }
+      }),
+      AlbumSearchPredicate::Artist(file_name) => json!({
+        "term": { "artists.file_name.keyword": file_name.to_string() }
+      }),
+      AlbumSearchPredicate::ReleaseYear { min, max } => json!({
+        "range": { "release_year": { "gte": min, "lte": max } }
+      }),
+    }
+  }
+}
+
+impl AlbumSearchExpression {
+  pub fn to_es_query(&self) -> Value {
+    match self {
+      AlbumSearchExpression::Predicate(predicate) => predicate.to_es_query(),
+      AlbumSearchExpression::And(children) => json!({
+        "bool": {
+          "must": children.iter().map(|child| child.to_es_query()).collect::<Vec<_>>()
+        }
+      }),
+      AlbumSearchExpression::Or(children) => json!({
+        "bool": {
+          "should": children.iter().map(|child| child.to_es_query()).collect::<Vec<_>>(),
+          "minimum_should_match": 1
+        }
+      }),
+      AlbumSearchExpression::Not(child) => json!({
+        "bool": {
+          "must_not": [child.to_es_query()]
+        }
+      }),
+    }
+  }
+}
+
+impl AlbumTextField {
+  fn es_field(&self) -> &'static str {
+    match self {
+      AlbumTextField::Name => "name",
+      AlbumTextField::AsciiName => "ascii_name",
+      AlbumTextField::ArtistName => "artists.name",
+      AlbumTextField::ArtistAsciiName => "artist_ascii_names",
+    }
+  }
+}
+
+impl AlbumSearchBoostProfile {
+  fn to_es_text_field(&self, field: &AlbumTextField) -> String {
+    let weight = if field.is_artist() {
+      self.artist_name_weight
+    } else {
+      self.name_weight
+    };
+    format!("{}^{}", field.es_field(), weight)
+  }
+
+  /**
+   * Adds the rating and recency boosts to the scores of the query's matches
+   */
+  pub fn to_es_function_score(&self, query: Value, current_year: i32) -> Value {
+    let mut functions = vec![];
+    if self.rating_boost > 0.0 {
+      functions.push(json!({
+        "field_value_factor": {
+          "field": "rating",
+          "factor": self.rating_boost,
+          "missing": 0
+        }
+      }));
+    }
+    if self.recency_boost > 0.0 {
+      functions.push(json!({
+        // Decay functions score missing fields as a perfect match, so undated albums are skipped
+        "filter": { "exists": { "field": "release_year" } },
+        "gauss": {
+          "release_year": {
+            "origin": current_year,
+            "scale": self.recency_scale_years,
+            "decay": 0.5
+          }
+        },
+        "weight": self.recency_boost
+      }));
+    }
+    if functions.is_empty() {
+      return query;
+    }
+    json!({
+      "function_score": {
+        "query": query,
+        "functions": functions,
+        "score_mode": "sum",
+        "boost_mode": "sum"
+      }
+    })
+  }
+}
+
 impl AlbumSearchQuery {
   pub fn to_es_query(&self) -> Value {
     let mut query = json!({
@@ -95,12 +237,32 @@ impl AlbumSearchQuery {
       }
     });
 
-    if let Some(text) = &self.text {
+    if let Some(plan) = self.text_search_plan() {
+      let clauses = plan
+        .clauses
+        .iter()
+        .map(|clause| {
+          let mut multi_match = json!({
+            "query": clause.text,
+            "fields": clause
+              .fields
+              .iter()
+              .map(|field| match &self.boost_profile {
+                Some(profile) => profile.to_es_text_field(field),
+                None => field.es_field().to_string(),
+              })
+              .collect::<Vec<_>>(),
+          });
+          if plan.fuzzy {
+            multi_match["fuzziness"] = json!("AUTO");
+          }
+          json!({ "multi_match": multi_match })
+        })
+        .collect::<Vec<_>>();
       query["bool"]["must"].as_array_mut().unwrap().push(json!({
-        "multi_match": {
-          "query": text,
-          "fields": ["name", "artists.name"],
-          "fuzziness": "AUTO"
+        "bool": {
+          "should": clauses,
+          "minimum_should_match": 1
         }
       }));
     }
@@ -233,6 +395,33 @@ impl AlbumSearchQuery {
         }));
     }
 
+    if !self.include_tags.is_empty() {
+      query["bool"]["must"].as_array_mut().unwrap().push(json!({
+        "terms": {
+          "tags.keyword": self.include_tags
+        }
+      }));
+    }
+
+    if !self.exclude_tags.is_empty() {
+      query["bool"]["must_not"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({
+          "terms": {
+            "tags.keyword": self.exclude_tags
+          }
+        }));
+    }
+
+    if !self.include_cluster_ids.is_empty() {
+      query["bool"]["must"].as_array_mut().unwrap().push(json!({
+        "terms": {
+          "cluster_id": self.include_cluster_ids
+        }
+      }));
+    }
+
     if let Some(min_primary_genre_count) = self.min_primary_genre_count {
       query["bool"]["must"].as_array_mut().unwrap().push(json!({
         "range": {
@@ -283,6 +472,28 @@ impl AlbumSearchQuery {
       }));
     }
 
+    if self.min_rating.is_some() || self.max_rating.is_some() {
+      query["bool"]["must"].as_array_mut().unwrap().push(json!({
+        "range": {
+          "rating": {
+            "gte": self.min_rating,
+            "lte": self.max_rating
+          }
+        }
+      }));
+    }
+
+    if self.min_rating_count.is_some() || self.max_rating_count.is_some() {
+      query["bool"]["must"].as_array_mut().unwrap().push(json!({
+        "range": {
+          "rating_count": {
+            "gte": self.min_rating_count,
+            "lte": self.max_rating_count
+          }
+        }
+      }));
+    }
+
     if !self.include_duplicates.is_some_and(|b| b) {
       query["bool"]["must_not"]
         .as_array_mut()
@@ -294,6 +505,13 @@ impl AlbumSearchQuery {
         }));
     }
 
+    if let Some(expression) = &self.expression {
+      query["bool"]["must"]
+        .as_array_mut()
+        .unwrap()
+        .push(expression.to_es_query());
+    }
+
     if query["bool"]["must"].as_array().unwrap().is_empty()
       && query["bool"]["must_not"].as_array().unwrap().is_empty()
     {
@@ -301,7 +519,42 @@ impl AlbumSearchQuery {
           "match_all": {}
       });
     }
-    query
+    match &self.boost_profile {
+      Some(profile) => profile.to_es_function_score(query, Utc::now().year()),
+      None => query,
+    }
+  }
+
+  pub fn to_es_sort(&self) -> Option<Value> {
+    self.sort.as_ref().map(|sort| {
+      let field = match sort.field {
+        AlbumSearchSortField::Rating => "rating",
+        AlbumSearchSortField::RatingCount => "rating_count",
+        AlbumSearchSortField::ReleaseDate => "release_date",
+        AlbumSearchSortField::Name => "ascii_name.keyword",
+      };
+      json!([
+        {
+          field: {
+            "order": if sort.descending { "desc" } else { "asc" },
+            "missing": "_last"
+          }
+        },
+        { "file_name.keyword": "asc" }
+      ])
+    })
+  }
+
+  /**
+   * Sort values of the keyset, matching the sort's rating or rating count and file name
+   */
+  pub fn to_es_search_after(&self) -> Option<Value> {
+    let keyset = self.search_after.as_ref()?;
+    let value = match self.sort.as_ref()?.field {
+      AlbumSearchSortField::RatingCount => json!(keyset.value as u64),
+      _ => json!(keyset.value),
+    };
+    Some(json!([value, keyset.file_name.to_string()]))
   }
 }
 
@@ -314,6 +567,35 @@ impl From<ElasticsearchResult<AlbumReadModel>> for AlbumSearchResult {
   }
 }
 
+/**
+ * Counts from a terms or histogram aggregation, most common first. Histogram keys are numbers and
+ * come back ordered by key.
+ */
+fn es_buckets_to_counts(aggregation: &Value, limit: usize) -> Vec<ItemAndCount> {
+  let mut counts = aggregation["buckets"]
+    .as_array()
+    .map(|buckets| {
+      buckets
+        .iter()
+        .filter_map(|bucket| {
+          let name = match &bucket["key"] {
+            Value::String(key) => key.clone(),
+            Value::Number(key) => key.as_f64()?.to_string(),
+            _ => return None,
+          };
+          Some(ItemAndCount {
+            name,
+            count: bucket["doc_count"].as_u64()? as u32,
+          })
+        })
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+  counts.sort_by(|a, b| b.count.cmp(&a.count));
+  counts.truncate(limit);
+  counts
+}
+
 pub struct EsAlbumSearchIndex {
   index: ElasticsearchIndex,
 }
@@ -326,15 +608,19 @@ impl EsAlbumSearchIndex {
       index: ElasticsearchIndex::new(elasticsearch_client, INDEX_NAME.to_string()),
     }
   }
+}
 
-  pub async fn setup_index(&self) -> Result<()> {
+#[async_trait]
+impl AlbumSearchIndex for EsAlbumSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
     self.index.setup().await?;
     Ok(())
   }
-}
 
-#[async_trait]
-impl AlbumSearchIndex for EsAlbumSearchIndex {
+  fn supports_boost_profiles(&self) -> bool {
+    true
+  }
+
   async fn get_embedding_keys(&self) -> Result<Vec<String>> {
     let fields = self.index.list_fields().await?;
     Ok(
@@ -376,19 +662,74 @@ impl AlbumSearchIndex for EsAlbumSearchIndex {
     query: &AlbumSearchQuery,
     pagination: Option<&SearchPagination>,
   ) -> Result<AlbumSearchResult> {
+    let mut body = json!({
+      "_source": {
+        "exclude": [ElasticsearchIndex::embedding_field_wildcard()]
+      },
+      "query": query.to_es_query(),
+    });
+    if let Some(sort) = query.to_es_sort() {
+      body["sort"] = sort;
+    }
+    if let Some(search_after) = query.to_es_search_after() {
+      body["search_after"] = search_after;
+    }
+    let result = self.index.search(body, pagination).await?;
+    Ok(result.into())
+  }
+
+  async fn get_random(
+    &self,
+    query: &AlbumSearchQuery,
+    count: usize,
+  ) -> Result<Vec<AlbumReadModel>> {
+    let body = json!({
+      "_source": {
+        "exclude": [ElasticsearchIndex::embedding_field_wildcard()]
+      },
+      "query": {
+        "function_score": {
+          "query": query.to_es_query(),
+          "random_score": {},
+          "boost_mode": "replace"
+        }
+      },
+    });
     let result = self
       .index
       .search(
-        json!({
-          "_source": {
-            "exclude": [ElasticsearchIndex::embedding_field_wildcard()]
-          },
-          "query": query.to_es_query(),
+        body,
+        Some(&SearchPagination {
+          offset: Some(0),
+          limit: Some(count),
         }),
-        pagination,
       )
       .await?;
-    Ok(result.into())
+    Ok(AlbumSearchResult::from(result).albums)
+  }
+
+  async fn get_facets(&self, query: &AlbumSearchQuery, limit: usize) -> Result<AlbumSearchFacets> {
+    let terms = |field: &str| json!({ "terms": { "field": field, "size": limit } });
+    let aggregations = self
+      .index
+      .aggregate(json!({
+        "query": query.to_es_query(),
+        "aggs": {
+          "primary_genres": terms("primary_genres.keyword"),
+          "descriptors": terms("descriptors.keyword"),
+          "languages": terms("languages.keyword"),
+          "release_decades": {
+            "histogram": { "field": "release_year", "interval": 10, "min_doc_count": 1 }
+          }
+        }
+      }))
+      .await?;
+    Ok(AlbumSearchFacets {
+      primary_genres: es_buckets_to_counts(&aggregations["primary_genres"], limit),
+      descriptors: es_buckets_to_counts(&aggregations["descriptors"], limit),
+      languages: es_buckets_to_counts(&aggregations["languages"], limit),
+      release_decades: es_buckets_to_counts(&aggregations["release_decades"], limit),
+    })
   }
 
   async fn embedding_similarity_search(
@@ -542,3 +883,143 @@ impl AlbumSearchIndex for EsAlbumSearchIndex {
       .await
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_search_index::{AlbumSearchKeyset, AlbumSearchSort};
+
+  #[test]
+  fn test_boost_profile_es_query() {
+    let query = AlbumSearchQuery {
+      text: Some("vulnicura".to_string()),
+      boost_profile: Some(AlbumSearchBoostProfile {
+        name_weight: 2.0,
+        rating_boost: 1.5,
+        recency_boost: 3.0,
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+    .to_es_query();
+    let function_score = &query["function_score"];
+    assert_eq!(
+      function_score["query"]["bool"]["must"][0]["bool"]["should"][1]["multi_match"]["fields"],
+      json!(["ascii_name^2", "artist_ascii_names^1"])
+    );
+    assert_eq!(
+      function_score["functions"].as_array().map(Vec::len),
+      Some(2)
+    );
+
+    let unboosted =
+      AlbumSearchBoostProfile::default().to_es_function_score(json!({ "match_all": {} }), 2024);
+    assert_eq!(unboosted, json!({ "match_all": {} }));
+  }
+
+  #[test]
+  fn test_sort_es_query() {
+    let query = AlbumSearchQuery {
+      min_rating: Some(3.5),
+      sort: Some(AlbumSearchSort {
+        field: AlbumSearchSortField::ReleaseDate,
+        descending: true,
+      }),
+      ..Default::default()
+    };
+    assert_eq!(
+      query.to_es_query()["bool"]["must"][0],
+      json!({ "range": { "rating": { "gte": 3.5, "lte": null } } })
+    );
+    assert_eq!(
+      query.to_es_sort(),
+      Some(json!([
+        { "release_date": { "order": "desc", "missing": "_last" } },
+        { "file_name.keyword": "asc" }
+      ]))
+    );
+    assert_eq!(AlbumSearchQuery::default().to_es_sort(), None);
+
+    let query = AlbumSearchQuery {
+      sort: Some(AlbumSearchSort {
+        field: AlbumSearchSortField::RatingCount,
+        descending: true,
+      }),
+      search_after: Some(AlbumSearchKeyset {
+        value: 20.0,
+        file_name: FileName::try_from("release/album/slowdive/souvlaki").unwrap(),
+      }),
+      ..Default::default()
+    };
+    assert_eq!(
+      query.to_es_search_after(),
+      Some(json!([20, "release/album/slowdive/souvlaki"]))
+    );
+  }
+
+  #[test]
+  fn test_expression_es_query() {
+    let query = AlbumSearchQuery {
+      include_duplicates: Some(true),
+      expression: Some(AlbumSearchExpression::And(vec![
+        AlbumSearchExpression::Or(vec![
+          AlbumSearchExpression::Predicate(AlbumSearchPredicate::PrimaryGenre(
+            "Shoegaze".to_string(),
+          )),
+          AlbumSearchExpression::Predicate(AlbumSearchPredicate::Descriptor("dense".to_string())),
+        ]),
+        AlbumSearchExpression::Not(Box::new(AlbumSearchExpression::Predicate(
+          AlbumSearchPredicate::ReleaseYear {
+            min: None,
+            max: Some(1979),
+          },
+        ))),
+      ])),
+      ..Default::default()
+    };
+    assert_eq!(
+      query.to_es_query()["bool"]["must"][0],
+      json!({
+        "bool": {
+          "must": [
+            {
+              "bool": {
+                "should": [
+                  { "term": { "primary_genres.keyword": "Shoegaze" } },
+                  { "term": { "descriptors.keyword": "dense" } },
+                ],
+                "minimum_should_match": 1
+              }
+            },
+            {
+              "bool": {
+                "must_not": [{ "range": { "release_year": { "gte": null, "lte": 1979 } } }]
+              }
+            },
+          ]
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn test_es_buckets_to_counts() {
+    let counts = es_buckets_to_counts(
+      &json!({
+        "buckets": [
+          { "key": 1980.0, "doc_count": 3 },
+          { "key": 1990.0, "doc_count": 12 },
+          { "key": 2000.0, "doc_count": 7 }
+        ]
+      }),
+      2,
+    );
+    assert_eq!(
+      counts
+        .iter()
+        .map(|item| (item.name.as_str(), item.count))
+        .collect::<Vec<_>>(),
+      vec![("1990", 12), ("2000", 7)]
+    );
+  }
+}
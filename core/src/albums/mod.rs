@@ -1,9 +1,26 @@
+pub mod album_cluster_repository;
+pub mod album_clustering;
 pub mod album_collection_summary;
+pub mod album_digest;
+pub mod album_duplicate_candidate;
+pub mod album_duplicate_candidate_repository;
 pub mod album_event_subscribers;
 pub mod album_interactor;
+pub mod album_jobs;
+pub mod album_query_translation;
+pub mod album_query_translation_client;
 pub mod album_read_model;
 pub mod album_repository;
+pub mod album_search_boost_profile;
+pub mod album_search_boost_profile_repository;
+pub mod album_search_cursor;
 pub mod album_search_index;
+pub mod album_search_index_factory;
+pub mod album_search_index_rebuild;
 pub mod album_service;
+pub mod album_tags;
+pub mod album_text_search;
 pub mod es_album_search_index;
+pub mod qdrant_album_search_index;
 pub mod redis_album_search_index;
+pub mod sqlite_album_search_index;
@@ -0,0 +1,798 @@
+use super::{
+  album_read_model::AlbumReadModel,
+  album_search_index::{
+    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchExpression, AlbumSearchFacets,
+    AlbumSearchIndex, AlbumSearchPredicate, AlbumSearchQuery, AlbumSearchResult,
+  },
+};
+use crate::{
+  embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
+  files::file_metadata::file_name::FileName,
+  helpers::{embedding::EmbeddingDocument, redisearch::SearchPagination},
+  settings::QdrantSettings,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Datelike;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::{error, instrument};
+
+const DEFAULT_COLLECTION_PREFIX: &str = "album_embeddings";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QdrantAlbumPayload {
+  #[serde(flatten)]
+  album: AlbumReadModel,
+  artist_file_names: Vec<FileName>,
+  primary_genre_count: u32,
+  secondary_genre_count: u32,
+  descriptor_count: u32,
+  release_year: Option<u32>,
+  is_duplicate: bool,
+}
+
+impl From<AlbumReadModel> for QdrantAlbumPayload {
+  fn from(album: AlbumReadModel) -> Self {
+    Self {
+      artist_file_names: album
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+      primary_genre_count: album.primary_genres.len() as u32,
+      secondary_genre_count: album.secondary_genres.len() as u32,
+      descriptor_count: album.descriptors.len() as u32,
+      release_year: album.release_date.map(|d| d.year() as u32),
+      is_duplicate: album.duplicate_of.is_some(),
+      album,
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantResponse<T> {
+  result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantCollectionDescription {
+  name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantCollections {
+  collections: Vec<QdrantCollectionDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantRecord {
+  vector: Option<Vec<f32>>,
+  payload: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QdrantScoredPoint {
+  score: f32,
+  payload: Option<Value>,
+}
+
+fn match_any<T: ToString>(key: &str, values: &[T]) -> Value {
+  json!({
+    "key": key,
+    "match": {
+      "any": values.iter().map(|v| v.to_string()).collect::<Vec<String>>()
+    }
+  })
+}
+
+fn range(key: &str, gte: Option<u32>, lte: Option<u32>) -> Value {
+  json!({
+    "key": key,
+    "range": {
+      "gte": gte,
+      "lte": lte
+    }
+  })
+}
+
+fn match_value(key: &str, value: String) -> Value {
+  json!({ "key": key, "match": { "value": value } })
+}
+
+impl AlbumSearchPredicate {
+  pub fn to_qdrant_condition(&self) -> Value {
+    match self {
+      AlbumSearchPredicate::PrimaryGenre(genre) => match_value("primary_genres", genre.clone()),
+      AlbumSearchPredicate::SecondaryGenre(genre) => match_value("secondary_genres", genre.clone()),
+      AlbumSearchPredicate::Descriptor(descriptor) => {
+        match_value("descriptors", descriptor.clone())
+      }
+      AlbumSearchPredicate::Language(language) => match_value("languages", language.clone()),
+      AlbumSearchPredicate::Artist(file_name) => {
+        match_value("artist_file_names", file_name.to_string())
+      }
+      AlbumSearchPredicate::ReleaseYear { min, max } => range("release_year", *min, *max),
+    }
+  }
+}
+
+impl AlbumSearchExpression {
+  /**
+   * Qdrant accepts nested filters as conditions, so each group maps onto its own must, should or
+   * must_not clause
+   */
+  pub fn to_qdrant_condition(&self) -> Value {
+    let conditions = |children: &[AlbumSearchExpression]| {
+      children
+        .iter()
+        .map(|child| child.to_qdrant_condition())
+        .collect::<Vec<_>>()
+    };
+    match self {
+      AlbumSearchExpression::Predicate(predicate) => predicate.to_qdrant_condition(),
+      AlbumSearchExpression::And(children) => json!({ "must": conditions(children) }),
+      AlbumSearchExpression::Or(children) => json!({ "should": conditions(children) }),
+      AlbumSearchExpression::Not(child) => json!({ "must_not": [child.to_qdrant_condition()] }),
+    }
+  }
+}
+
+impl AlbumSearchQuery {
+  pub fn to_qdrant_filter(&self) -> Value {
+    let mut must = vec![];
+    let mut must_not = vec![];
+
+    if let Some(exact_name) = &self.exact_name {
+      must.push(json!({ "key": "name", "match": { "value": exact_name } }));
+    }
+    if !self.include_file_names.is_empty() {
+      must.push(match_any("file_name", &self.include_file_names));
+    }
+    if !self.exclude_file_names.is_empty() {
+      must_not.push(match_any("file_name", &self.exclude_file_names));
+    }
+    if !self.include_artists.is_empty() {
+      must.push(match_any("artist_file_names", &self.include_artists));
+    }
+    if !self.exclude_artists.is_empty() {
+      must_not.push(match_any("artist_file_names", &self.exclude_artists));
+    }
+    if !self.include_primary_genres.is_empty() {
+      must.push(match_any("primary_genres", &self.include_primary_genres));
+    }
+    if !self.exclude_primary_genres.is_empty() {
+      must_not.push(match_any("primary_genres", &self.exclude_primary_genres));
+    }
+    if !self.include_secondary_genres.is_empty() {
+      must.push(match_any(
+        "secondary_genres",
+        &self.include_secondary_genres,
+      ));
+    }
+    if !self.exclude_secondary_genres.is_empty() {
+      must_not.push(match_any(
+        "secondary_genres",
+        &self.exclude_secondary_genres,
+      ));
+    }
+    if !self.include_languages.is_empty() {
+      must.push(match_any("languages", &self.include_languages));
+    }
+    if !self.exclude_languages.is_empty() {
+      must_not.push(match_any("languages", &self.exclude_languages));
+    }
+    if !self.include_descriptors.is_empty() {
+      must.push(match_any("descriptors", &self.include_descriptors));
+    }
+    if !self.exclude_descriptors.is_empty() {
+      must_not.push(match_any("descriptors", &self.exclude_descriptors));
+    }
+    if !self.include_tags.is_empty() {
+      must.push(match_any("tags", &self.include_tags));
+    }
+    if !self.exclude_tags.is_empty() {
+      must_not.push(match_any("tags", &self.exclude_tags));
+    }
+    if !self.include_cluster_ids.is_empty() {
+      must.push(json!({
+        "key": "cluster_id",
+        "match": { "any": self.include_cluster_ids }
+      }));
+    }
+    if let Some(min) = self.min_primary_genre_count {
+      must.push(range("primary_genre_count", Some(min as u32), None));
+    }
+    if let Some(min) = self.min_secondary_genre_count {
+      must.push(range("secondary_genre_count", Some(min as u32), None));
+    }
+    if let Some(min) = self.min_descriptor_count {
+      must.push(range("descriptor_count", Some(min as u32), None));
+    }
+    if self.min_release_year.is_some() || self.max_release_year.is_some() {
+      must.push(range(
+        "release_year",
+        self.min_release_year,
+        self.max_release_year,
+      ));
+    }
+    if self.min_rating.is_some() || self.max_rating.is_some() {
+      must.push(json!({
+        "key": "rating",
+        "range": { "gte": self.min_rating, "lte": self.max_rating }
+      }));
+    }
+    if self.min_rating_count.is_some() || self.max_rating_count.is_some() {
+      must.push(range(
+        "rating_count",
+        self.min_rating_count,
+        self.max_rating_count,
+      ));
+    }
+    if !self.include_duplicates.is_some_and(|b| b) {
+      must.push(json!({ "key": "is_duplicate", "match": { "value": false } }));
+    }
+    if let Some(expression) = &self.expression {
+      must.push(expression.to_qdrant_condition());
+    }
+
+    json!({
+      "must": must,
+      "must_not": must_not,
+    })
+  }
+}
+
+/**
+ * Qdrant only accepts unsigned integers or UUIDs as point ids, so file names are hashed into a
+ * stable UUID.
+ */
+fn point_id(file_name: &FileName) -> String {
+  let hash = Sha256::digest(file_name.to_string().as_bytes());
+  let hex = format!("{:x}", hash);
+  format!(
+    "{}-{}-{}-{}-{}",
+    &hex[0..8],
+    &hex[8..12],
+    &hex[12..16],
+    &hex[16..20],
+    &hex[20..32]
+  )
+}
+
+/**
+ * Stores album embeddings in Qdrant, one collection per embedding key, and delegates document
+ * storage and text search to an inner index.
+ */
+pub struct QdrantAlbumSearchIndex {
+  client: reqwest::Client,
+  settings: QdrantSettings,
+  inner: Arc<dyn AlbumSearchIndex + Send + Sync>,
+  embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
+  /**
+   * Collection name by embedding key, listed from Qdrant once and then kept up to date as
+   * collections are created
+   */
+  known_collections: RwLock<Option<HashMap<String, String>>>,
+}
+
+impl QdrantAlbumSearchIndex {
+  pub fn new(
+    settings: QdrantSettings,
+    inner: Arc<dyn AlbumSearchIndex + Send + Sync>,
+    embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
+  ) -> Self {
+    Self {
+      client: reqwest::Client::new(),
+      settings,
+      inner,
+      embedding_provider_interactor,
+      known_collections: RwLock::new(None),
+    }
+  }
+
+  fn collection_prefix(&self) -> &str {
+    self
+      .settings
+      .collection_prefix
+      .as_deref()
+      .unwrap_or(DEFAULT_COLLECTION_PREFIX)
+  }
+
+  fn collection_name(&self, key: &str) -> String {
+    format!("{}_{}", self.collection_prefix(), key)
+  }
+
+  fn url(&self, path: &str) -> String {
+    format!("{}/{}", self.settings.url.trim_end_matches('/'), path)
+  }
+
+  async fn request<T: DeserializeOwned>(
+    &self,
+    method: reqwest::Method,
+    path: &str,
+    body: Option<Value>,
+  ) -> Result<T> {
+    let mut request = self.client.request(method, self.url(path));
+    if let Some(api_key) = &self.settings.api_key {
+      request = request.header("api-key", api_key);
+    }
+    if let Some(body) = body {
+      request = request.json(&body);
+    }
+    let response = request.send().await?;
+    if !response.status().is_success() {
+      let status = response.status();
+      let text = response.text().await.unwrap_or_default();
+      error!(
+        status = status.as_u16(),
+        body = text,
+        "Qdrant request failed"
+      );
+      return Err(anyhow!("Qdrant request failed with status {}", status));
+    }
+    Ok(response.json::<QdrantResponse<T>>().await?.result)
+  }
+
+  async fn list_embedding_collections(&self) -> Result<HashMap<String, String>> {
+    let prefix = format!("{}_", self.collection_prefix());
+    let collections = self
+      .request::<QdrantCollections>(reqwest::Method::GET, "collections", None)
+      .await?;
+    Ok(
+      collections
+        .collections
+        .into_iter()
+        .filter_map(|c| {
+          c.name
+            .strip_prefix(&prefix)
+            .map(|key| (key.to_string(), c.name.clone()))
+        })
+        .collect(),
+    )
+  }
+
+  /**
+   * Embedding keys with a collection, paired with the collection's name
+   */
+  async fn embedding_collections(&self) -> Result<Vec<(String, String)>> {
+    if let Some(known_collections) = self.known_collections.read().await.as_ref() {
+      return Ok(known_collections.clone().into_iter().collect());
+    }
+    let listed = self.list_embedding_collections().await?;
+    Ok(
+      self
+        .known_collections
+        .write()
+        .await
+        .get_or_insert(listed)
+        .clone()
+        .into_iter()
+        .collect(),
+    )
+  }
+
+  #[instrument(skip(self))]
+  async fn ensure_collection(&self, key: &str) -> Result<String> {
+    let name = self.collection_name(key);
+    if self
+      .embedding_collections()
+      .await?
+      .iter()
+      .any(|(_, collection)| *collection == name)
+    {
+      return Ok(name);
+    }
+    // Listed again in case another instance created it since
+    if !self.list_embedding_collections().await?.contains_key(key) {
+      let provider = self
+        .embedding_provider_interactor
+        .get_provider_by_name(key)?;
+      self
+        .request::<Value>(
+          reqwest::Method::PUT,
+          &format!("collections/{}", name),
+          Some(json!({
+            "vectors": {
+              "size": provider.dimensions(),
+              "distance": "Cosine"
+            }
+          })),
+        )
+        .await?;
+      for (field, schema) in [
+        ("file_name", "keyword"),
+        ("name", "keyword"),
+        ("artist_file_names", "keyword"),
+        ("primary_genres", "keyword"),
+        ("secondary_genres", "keyword"),
+        ("languages", "keyword"),
+        ("descriptors", "keyword"),
+        ("tags", "keyword"),
+        ("cluster_id", "integer"),
+        ("primary_genre_count", "integer"),
+        ("secondary_genre_count", "integer"),
+        ("descriptor_count", "integer"),
+        ("release_year", "integer"),
+        ("rating", "float"),
+        ("rating_count", "integer"),
+        ("is_duplicate", "bool"),
+      ] {
+        self
+          .request::<Value>(
+            reqwest::Method::PUT,
+            &format!("collections/{}/index?wait=true", name),
+            Some(json!({ "field_name": field, "field_schema": schema })),
+          )
+          .await?;
+      }
+    }
+    self
+      .known_collections
+      .write()
+      .await
+      .get_or_insert_with(HashMap::new)
+      .insert(key.to_string(), name.clone());
+    Ok(name)
+  }
+
+  async fn find_albums(
+    &self,
+    file_names: Vec<FileName>,
+  ) -> Result<HashMap<FileName, AlbumReadModel>> {
+    let limit = file_names.len();
+    let result = self
+      .inner
+      .search(
+        &AlbumSearchQuery {
+          include_file_names: file_names,
+          include_duplicates: Some(true),
+          ..Default::default()
+        },
+        Some(&SearchPagination {
+          offset: None,
+          limit: Some(limit),
+        }),
+      )
+      .await?;
+    Ok(
+      result
+        .albums
+        .into_iter()
+        .map(|album| (album.file_name.clone(), album))
+        .collect(),
+    )
+  }
+
+  async fn retrieve_points(
+    &self,
+    collection: &str,
+    file_names: &[FileName],
+  ) -> Result<Vec<QdrantRecord>> {
+    self
+      .request::<Vec<QdrantRecord>>(
+        reqwest::Method::POST,
+        &format!("collections/{}/points", collection),
+        Some(json!({
+          "ids": file_names.iter().map(point_id).collect::<Vec<String>>(),
+          "with_vector": true,
+          "with_payload": ["file_name"],
+        })),
+      )
+      .await
+  }
+}
+
+#[async_trait]
+impl AlbumSearchIndex for QdrantAlbumSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
+    self.inner.setup_index().await?;
+    for key in self.embedding_provider_interactor.providers.keys() {
+      self.ensure_collection(key).await?;
+    }
+    Ok(())
+  }
+
+  #[instrument(skip_all, fields(count = albums.len()))]
+  async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<()> {
+    self.inner.put_many(albums.clone()).await?;
+    let operations = albums
+      .into_iter()
+      .map(|album| {
+        let filter = json!({
+          "must": [{ "key": "file_name", "match": { "value": album.file_name.to_string() } }]
+        });
+        Ok(json!({
+          "set_payload": {
+            "payload": serde_json::to_value(QdrantAlbumPayload::from(album))?,
+            "filter": filter,
+          }
+        }))
+      })
+      .collect::<Result<Vec<Value>>>()?;
+    for (_, collection) in self.embedding_collections().await? {
+      self
+        .request::<Value>(
+          reqwest::Method::POST,
+          &format!("collections/{}/points/batch", collection),
+          Some(json!({ "operations": operations })),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn put(&self, album: AlbumReadModel) -> Result<()> {
+    self.put_many(vec![album]).await
+  }
+
+  async fn delete(&self, file_name: &FileName) -> Result<()> {
+    self.inner.delete(file_name).await?;
+    for (_, collection) in self.embedding_collections().await? {
+      self
+        .request::<Value>(
+          reqwest::Method::POST,
+          &format!("collections/{}/points/delete", collection),
+          Some(json!({ "points": [point_id(file_name)] })),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn find(&self, file_name: &FileName) -> Result<Option<AlbumReadModel>> {
+    self.inner.find(file_name).await
+  }
+
+  fn supports_boost_profiles(&self) -> bool {
+    self.inner.supports_boost_profiles()
+  }
+
+  async fn search(
+    &self,
+    query: &AlbumSearchQuery,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<AlbumSearchResult> {
+    self.inner.search(query, pagination).await
+  }
+
+  async fn get_random(
+    &self,
+    query: &AlbumSearchQuery,
+    count: usize,
+  ) -> Result<Vec<AlbumReadModel>> {
+    self.inner.get_random(query, count).await
+  }
+
+  async fn get_facets(&self, query: &AlbumSearchQuery, limit: usize) -> Result<AlbumSearchFacets> {
+    self.inner.get_facets(query, limit).await
+  }
+
+  async fn get_embedding_keys(&self) -> Result<Vec<String>> {
+    Ok(
+      self
+        .embedding_collections()
+        .await?
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect(),
+    )
+  }
+
+  async fn get_embeddings(&self, file_name: &FileName) -> Result<Vec<EmbeddingDocument>> {
+    let mut docs = vec![];
+    for (key, collection) in self.embedding_collections().await? {
+      if let Some(embedding) = self
+        .retrieve_points(&collection, &[file_name.clone()])
+        .await?
+        .into_iter()
+        .find_map(|record| record.vector)
+      {
+        docs.push(EmbeddingDocument {
+          file_name: file_name.clone(),
+          key,
+          embedding,
+        });
+      }
+    }
+    Ok(docs)
+  }
+
+  async fn find_many_embeddings(
+    &self,
+    file_names: Vec<FileName>,
+    key: &str,
+  ) -> Result<Vec<EmbeddingDocument>> {
+    if file_names.is_empty() {
+      return Ok(vec![]);
+    }
+    let collection = self.collection_name(key);
+    if !self
+      .embedding_collections()
+      .await?
+      .iter()
+      .any(|(_, c)| c == &collection)
+    {
+      return Ok(vec![]);
+    }
+    Ok(
+      self
+        .retrieve_points(&collection, &file_names)
+        .await?
+        .into_iter()
+        .filter_map(|record| {
+          let file_name = record
+            .payload
+            .as_ref()
+            .and_then(|p| p.get("file_name"))
+            .and_then(|v| v.as_str())
+            .and_then(|v| FileName::try_from(v.to_string()).ok())?;
+          Some(EmbeddingDocument {
+            file_name,
+            key: key.to_string(),
+            embedding: record.vector?,
+          })
+        })
+        .collect(),
+    )
+  }
+
+  async fn find_embedding(
+    &self,
+    file_name: &FileName,
+    key: &str,
+  ) -> Result<Option<EmbeddingDocument>> {
+    Ok(
+      self
+        .find_many_embeddings(vec![file_name.clone()], key)
+        .await?
+        .into_iter()
+        .next(),
+    )
+  }
+
+  #[instrument(skip_all, fields(count = docs.len()))]
+  async fn put_many_embeddings(&self, docs: Vec<EmbeddingDocument>) -> Result<()> {
+    let mut docs_by_key: HashMap<String, Vec<EmbeddingDocument>> = HashMap::new();
+    for doc in docs {
+      docs_by_key.entry(doc.key.clone()).or_default().push(doc);
+    }
+    for (key, docs) in docs_by_key {
+      let collection = self.ensure_collection(&key).await?;
+      let mut albums = self
+        .find_albums(docs.iter().map(|doc| doc.file_name.clone()).collect())
+        .await?;
+      let points = docs
+        .into_iter()
+        .map(|doc| {
+          let payload = match albums.remove(&doc.file_name) {
+            Some(album) => serde_json::to_value(QdrantAlbumPayload::from(album))?,
+            None => json!({ "file_name": doc.file_name.to_string() }),
+          };
+          Ok(json!({
+            "id": point_id(&doc.file_name),
+            "vector": doc.embedding,
+            "payload": payload,
+          }))
+        })
+        .collect::<Result<Vec<Value>>>()?;
+      self
+        .request::<Value>(
+          reqwest::Method::PUT,
+          &format!("collections/{}/points?wait=true", collection),
+          Some(json!({ "points": points })),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn put_embedding(&self, embedding: EmbeddingDocument) -> Result<()> {
+    self.put_many_embeddings(vec![embedding]).await
+  }
+
+  async fn delete_embedding(&self, file_name: &FileName, key: &str) -> Result<()> {
+    self
+      .request::<Value>(
+        reqwest::Method::POST,
+        &format!("collections/{}/points/delete", self.collection_name(key)),
+        Some(json!({ "points": [point_id(file_name)] })),
+      )
+      .await?;
+    Ok(())
+  }
+
+  /**
+   * Scores are converted from cosine similarity to cosine distance to match the Redis index.
+   */
+  #[instrument(skip_all, fields(key = query.embedding_key, limit = query.limit))]
+  async fn embedding_similarity_search(
+    &self,
+    query: &AlbumEmbeddingSimilarirtySearchQuery,
+  ) -> Result<Vec<(AlbumReadModel, f32)>> {
+    let points = self
+      .request::<Vec<QdrantScoredPoint>>(
+        reqwest::Method::POST,
+        &format!(
+          "collections/{}/points/search",
+          self.collection_name(&query.embedding_key)
+        ),
+        Some(json!({
+          "vector": query.embedding,
+          "limit": query.limit,
+          "filter": query.filters.to_qdrant_filter(),
+          "with_payload": true,
+        })),
+      )
+      .await?;
+    Ok(
+      points
+        .into_iter()
+        .filter_map(|point| {
+          let payload = serde_json::from_value::<QdrantAlbumPayload>(point.payload?)
+            .inspect_err(|e| error!(e = e.to_string(), "Failed to parse Qdrant album payload"))
+            .ok()?;
+          Some((payload.album, 1.0 - point.score))
+        })
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_to_qdrant_filter() -> Result<()> {
+    let query = AlbumSearchQuery {
+      include_primary_genres: vec!["Shoegaze".to_string()],
+      exclude_artists: vec![FileName::try_from("artist/slowdive")?],
+      min_release_year: Some(1990),
+      min_rating: Some(3.5),
+      include_cluster_ids: vec![4],
+      ..Default::default()
+    };
+    assert_eq!(
+      query.to_qdrant_filter(),
+      json!({
+        "must": [
+          { "key": "primary_genres", "match": { "any": ["Shoegaze"] } },
+          { "key": "cluster_id", "match": { "any": [4] } },
+          { "key": "release_year", "range": { "gte": 1990, "lte": null } },
+          { "key": "rating", "range": { "gte": 3.5, "lte": null } },
+          { "key": "is_duplicate", "match": { "value": false } },
+        ],
+        "must_not": [
+          { "key": "artist_file_names", "match": { "any": ["artist/slowdive"] } },
+        ],
+      })
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_expression_to_qdrant_condition() {
+    let expression = AlbumSearchExpression::Or(vec![
+      AlbumSearchExpression::Predicate(AlbumSearchPredicate::Descriptor("dense".to_string())),
+      AlbumSearchExpression::Not(Box::new(AlbumSearchExpression::Predicate(
+        AlbumSearchPredicate::ReleaseYear {
+          min: None,
+          max: Some(1979),
+        },
+      ))),
+    ]);
+    assert_eq!(
+      expression.to_qdrant_condition(),
+      json!({
+        "should": [
+          { "key": "descriptors", "match": { "value": "dense" } },
+          { "must_not": [{ "key": "release_year", "range": { "gte": null, "lte": 1979 } }] },
+        ]
+      })
+    );
+  }
+}
@@ -1,12 +1,15 @@
 use super::{
   album_read_model::{
     AlbumReadModel, AlbumReadModelArtist, AlbumReadModelBuilder, AlbumReadModelCredit,
-    AlbumReadModelTrack,
+    AlbumReadModelDiscogsRelease, AlbumReadModelTrack,
   },
   album_repository::ItemAndCount,
   album_search_index::{
-    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchIndex, AlbumSearchQuery, AlbumSearchResult,
+    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchExpression, AlbumSearchFacets,
+    AlbumSearchIndex, AlbumSearchKeyset, AlbumSearchPredicate, AlbumSearchQuery, AlbumSearchResult,
+    AlbumSearchSort, AlbumSearchSortField,
   },
+  album_text_search::AlbumTextField,
 };
 use crate::{
   embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
@@ -14,24 +17,25 @@ use crate::{
   helpers::{
     embedding::{embedding_to_bytes, EmbeddingDocument},
     redisearch::{
-      escape_search_query_text, get_min_num_query, get_num_range_query, get_tag_query,
-      SearchIndexVersionManager, SearchPagination,
+      escape_search_query_text, escape_search_query_unicode_text, get_min_num_query,
+      get_num_range_query, get_tag_query, SearchIndexVersionManager, SearchPagination,
     },
   },
+  redis_migrations::RedisMigration,
 };
 use anyhow::{anyhow, Error, Result};
 use async_trait::async_trait;
 use chrono::{Datelike, NaiveDate};
-use futures::future::join_all;
+use futures::future::{join_all, try_join_all};
 use futures::{stream, StreamExt, TryStreamExt};
 use rustis::{
   bb8::Pool,
   client::PooledClientManager,
   commands::{
-    FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes, FtIndexDataType,
-    FtSearchOptions, FtSearchReturnAttribute, FtVectorDistanceMetric, FtVectorFieldAlgorithm,
-    FtVectorType, GenericCommands, JsonCommands, JsonGetOptions, SearchCommands, SetCondition,
-    SortOrder,
+    FtAggregateOptions, FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes,
+    FtIndexDataType, FtReducer, FtSearchOptions, FtSearchReturnAttribute, FtSortBy,
+    FtVectorDistanceMetric, FtVectorFieldAlgorithm, FtVectorType, GenericCommands, JsonCommands,
+    JsonGetOptions, SearchCommands, SetCondition, SortOrder,
   },
 };
 use serde_derive::{Deserialize, Serialize};
@@ -107,6 +111,11 @@ pub struct RedisAlbumReadModel {
   pub tracks: Vec<AlbumReadModelTrack>,
   pub release_date: Option<NaiveDate>,
   pub release_year: Option<u32>,
+  /**
+   * Days since the common era, redisearch can only sort release dates as numbers
+   */
+  #[serde(default)]
+  pub release_day: Option<i32>,
   #[serde(default)]
   pub languages: Vec<String>,
   #[serde(default)]
@@ -129,6 +138,20 @@ pub struct RedisAlbumReadModel {
   pub cover_image_url: Option<String>,
   #[serde(default)]
   pub spotify_id: Option<String>,
+  #[serde(default)]
+  pub musicbrainz_id: Option<String>,
+  #[serde(default)]
+  pub discogs_release: Option<AlbumReadModelDiscogsRelease>,
+  #[serde(default)]
+  pub is_various_artists: bool,
+  #[serde(default)]
+  pub bandcamp_url: Option<String>,
+  #[serde(default)]
+  pub cached_cover_image_url: Option<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub cluster_id: Option<u32>,
 }
 
 impl From<RedisAlbumReadModel> for AlbumReadModel {
@@ -150,6 +173,13 @@ impl From<RedisAlbumReadModel> for AlbumReadModel {
       duplicates: val.duplicates,
       cover_image_url: val.cover_image_url,
       spotify_id: val.spotify_id,
+      musicbrainz_id: val.musicbrainz_id,
+      discogs_release: val.discogs_release,
+      is_various_artists: val.is_various_artists,
+      bandcamp_url: val.bandcamp_url,
+      cached_cover_image_url: val.cached_cover_image_url,
+      tags: val.tags,
+      cluster_id: val.cluster_id,
     }
   }
 }
@@ -164,6 +194,7 @@ impl From<AlbumReadModel> for RedisAlbumReadModel {
     let credit_tags = val.credit_tags();
     let credit_tag_count = credit_tags.len() as u32;
     let release_year = val.release_date.map(|d| d.year() as u32);
+    let release_day = val.release_date.map(|d| d.num_days_from_ce());
     let is_duplicate = if val.duplicate_of.is_some() { 1 } else { 0 };
 
     RedisAlbumReadModel {
@@ -184,6 +215,7 @@ impl From<AlbumReadModel> for RedisAlbumReadModel {
       tracks: val.tracks,
       release_date: val.release_date,
       release_year,
+      release_day,
       languages: val.languages,
       language_count,
       credits: val.credits.into_iter().map(|c| c.into()).collect(),
@@ -194,6 +226,13 @@ impl From<AlbumReadModel> for RedisAlbumReadModel {
       is_duplicate,
       cover_image_url: val.cover_image_url,
       spotify_id: val.spotify_id,
+      musicbrainz_id: val.musicbrainz_id,
+      discogs_release: val.discogs_release,
+      is_various_artists: val.is_various_artists,
+      bandcamp_url: val.bandcamp_url,
+      cached_cover_image_url: val.cached_cover_image_url,
+      tags: val.tags,
+      cluster_id: val.cluster_id,
     }
   }
 }
@@ -230,11 +269,92 @@ impl TryFrom<&Vec<(String, String)>> for ItemAndCount {
   }
 }
 
+impl AlbumSearchPredicate {
+  pub fn to_ft_search_query(&self) -> String {
+    let query = match self {
+      AlbumSearchPredicate::PrimaryGenre(genre) => get_tag_query("@primary_genre", &[genre]),
+      AlbumSearchPredicate::SecondaryGenre(genre) => get_tag_query("@secondary_genre", &[genre]),
+      AlbumSearchPredicate::Descriptor(descriptor) => get_tag_query("@descriptor", &[descriptor]),
+      AlbumSearchPredicate::Language(language) => get_tag_query("@language", &[language]),
+      AlbumSearchPredicate::Artist(file_name) => get_tag_query("@artist_file_name", &[file_name]),
+      AlbumSearchPredicate::ReleaseYear { min, max } => {
+        get_num_range_query("@release_year", *min, *max)
+      }
+    };
+    query.trim().to_string()
+  }
+}
+
+impl AlbumSearchExpression {
+  pub fn to_ft_search_query(&self) -> String {
+    match self {
+      AlbumSearchExpression::Predicate(predicate) => predicate.to_ft_search_query(),
+      AlbumSearchExpression::And(children) => format!(
+        "({})",
+        children
+          .iter()
+          .map(|child| child.to_ft_search_query())
+          .collect::<Vec<_>>()
+          .join(" ")
+      ),
+      AlbumSearchExpression::Or(children) => format!(
+        "({})",
+        children
+          .iter()
+          .map(|child| child.to_ft_search_query())
+          .collect::<Vec<_>>()
+          .join(" | ")
+      ),
+      AlbumSearchExpression::Not(child) => format!("-({})", child.to_ft_search_query()),
+    }
+  }
+}
+
+impl AlbumTextField {
+  fn redis_attribute(&self) -> &'static str {
+    match self {
+      AlbumTextField::Name => "name",
+      AlbumTextField::AsciiName => "ascii_name",
+      AlbumTextField::ArtistName => "artist_name",
+      AlbumTextField::ArtistAsciiName => "artist_ascii_name",
+    }
+  }
+}
+
+impl AlbumSearchSortField {
+  fn redis_attribute(&self) -> &'static str {
+    match self {
+      AlbumSearchSortField::Rating => "rating",
+      AlbumSearchSortField::RatingCount => "rating_count",
+      AlbumSearchSortField::ReleaseDate => "release_day",
+      AlbumSearchSortField::Name => "ascii_name",
+    }
+  }
+}
+
 impl AlbumSearchQuery {
   pub fn to_ft_search_query(&self) -> String {
     let mut ft_search_query = String::from("");
-    if let Some(text) = &self.text {
-      ft_search_query.push_str(&format!("({}) ", escape_search_query_text(text)));
+    if let Some(plan) = self.text_search_plan() {
+      let clauses = plan
+        .clauses
+        .iter()
+        .map(|clause| {
+          let fields = clause
+            .fields
+            .iter()
+            .map(|field| field.redis_attribute())
+            .collect::<Vec<_>>()
+            .join("|");
+          let text = if clause.fields.iter().any(|field| field.is_transliterated()) {
+            escape_search_query_text(&clause.text)
+          } else {
+            escape_search_query_unicode_text(&clause.text)
+          };
+          format!("(@{}:({}))", fields, text)
+        })
+        .collect::<Vec<_>>();
+      ft_search_query.push_str(&format!("({}) ", clauses.join(" | ")));
     }
     if let Some(exact_name) = &self.exact_name {
       ft_search_query.push_str(&get_tag_query("@name_tag", &vec![exact_name]));
@@ -259,6 +379,16 @@ impl AlbumSearchQuery {
       self.min_release_year,
       self.max_release_year,
     ));
+    ft_search_query.push_str(&get_num_range_query(
+      "@rating",
+      self.min_rating,
+      self.max_rating,
+    ));
+    ft_search_query.push_str(&get_num_range_query(
+      "@rating_count",
+      self.min_rating_count,
+      self.max_rating_count,
+    ));
     ft_search_query.push_str(&get_tag_query("@file_name", &self.include_file_names));
     ft_search_query.push_str(&get_tag_query("@artist_file_name", &self.include_artists));
     ft_search_query.push_str(&get_tag_query(
@@ -271,6 +401,15 @@ impl AlbumSearchQuery {
     ));
     ft_search_query.push_str(&get_tag_query("@language", &self.include_languages));
     ft_search_query.push_str(&get_tag_query("@descriptor", &self.include_descriptors));
+    ft_search_query.push_str(&get_tag_query("@tag", &self.include_tags));
+    if !self.include_cluster_ids.is_empty() {
+      let ranges = self
+        .include_cluster_ids
+        .iter()
+        .map(|id| format!("@cluster_id:[{} {}]", id, id))
+        .collect::<Vec<_>>();
+      ft_search_query.push_str(&format!("({}) ", ranges.join(" | ")));
+    }
     ft_search_query.push_str(&get_tag_query("-@artist_file_name", &self.exclude_artists));
     ft_search_query.push_str(&get_tag_query("-@file_name", &self.exclude_file_names));
     ft_search_query.push_str(&get_tag_query(
@@ -283,6 +422,10 @@ impl AlbumSearchQuery {
     ));
     ft_search_query.push_str(&get_tag_query("-@language", &self.exclude_languages));
     ft_search_query.push_str(&get_tag_query("-@descriptor", &self.exclude_descriptors));
+    ft_search_query.push_str(&get_tag_query("-@tag", &self.exclude_tags));
+    if let Some(expression) = &self.expression {
+      ft_search_query.push_str(&format!("{} ", expression.to_ft_search_query()));
+    }
     ft_search_query.trim().to_string()
   }
 }
@@ -305,7 +448,7 @@ pub struct RedisAlbumSearchIndex {
 }
 
 const NAMESPACE: &str = "album";
-const INDEX_VERSION: u32 = 8;
+pub const INDEX_VERSION: u32 = 13;
 
 fn redis_key(file_name: &FileName) -> String {
   format!("{}:{}", NAMESPACE, file_name.to_string())
@@ -326,19 +469,29 @@ impl RedisAlbumSearchIndex {
       FtFieldSchema::identifier("$.ascii_name")
         .as_attribute("ascii_name")
         .field_type(FtFieldType::Text)
+        .weight(2.0)
+        .sortable(),
+      FtFieldSchema::identifier("$.name")
+        .as_attribute("name")
+        .field_type(FtFieldType::Text)
         .weight(2.0),
       FtFieldSchema::identifier("$.file_name")
         .as_attribute("file_name")
-        .field_type(FtFieldType::Tag),
+        .field_type(FtFieldType::Tag)
+        .sortable(),
       FtFieldSchema::identifier("$.artists[*].ascii_name")
         .as_attribute("artist_ascii_name")
         .field_type(FtFieldType::Text),
+      FtFieldSchema::identifier("$.artists[*].name")
+        .as_attribute("artist_name")
+        .field_type(FtFieldType::Text),
       FtFieldSchema::identifier("$.artists[*].file_name")
         .as_attribute("artist_file_name")
         .field_type(FtFieldType::Tag),
       FtFieldSchema::identifier("$.rating")
         .as_attribute("rating")
-        .field_type(FtFieldType::Numeric),
+        .field_type(FtFieldType::Numeric)
+        .sortable(),
       FtFieldSchema::identifier("$.rating_count")
         .as_attribute("rating_count")
         .field_type(FtFieldType::Numeric)
@@ -361,9 +514,19 @@ impl RedisAlbumSearchIndex {
       FtFieldSchema::identifier("$.descriptor_count")
         .as_attribute("descriptor_count")
         .field_type(FtFieldType::Numeric),
+      FtFieldSchema::identifier("$.tags.*")
+        .as_attribute("tag")
+        .field_type(FtFieldType::Tag),
+      FtFieldSchema::identifier("$.cluster_id")
+        .as_attribute("cluster_id")
+        .field_type(FtFieldType::Numeric),
       FtFieldSchema::identifier("$.release_year")
         .as_attribute("release_year")
         .field_type(FtFieldType::Numeric),
+      FtFieldSchema::identifier("$.release_day")
+        .as_attribute("release_day")
+        .field_type(FtFieldType::Numeric)
+        .sortable(),
       FtFieldSchema::identifier("$.languages.*")
         .as_attribute("language")
         .field_type(FtFieldType::Tag),
@@ -412,20 +575,119 @@ impl RedisAlbumSearchIndex {
     }
   }
 
-  pub async fn setup_index(&self) -> Result<()> {
-    self
-      .version_manager
-      .setup_index(
-        FtCreateOptions::default()
-          .on(FtIndexDataType::Json)
-          .prefix(format!("{}:", NAMESPACE)),
-        RedisAlbumSearchIndex::get_schema(&self.embedding_provider_interactor),
+  fn index_name(&self) -> String {
+    self.version_manager.latest_index_name()
+  }
+
+  /**
+   * Most common values of `property` across the albums matching `query`. Multi-value tags are
+   * grouped by each of their values.
+   */
+  async fn aggregate_counts(
+    &self,
+    query: &str,
+    options: FtAggregateOptions,
+    property: &str,
+    limit: usize,
+  ) -> Result<Vec<ItemAndCount>> {
+    let result = self
+      .redis_connection_pool
+      .get()
+      .await?
+      .ft_aggregate(
+        self.index_name(),
+        query,
+        options
+          .groupby(property, FtReducer::count().as_name("count"))
+          .sortby(FtSortBy::desc("@count"), None)
+          .limit(0, limit),
       )
-      .await
+      .await?;
+    result
+      .results
+      .iter()
+      .map(ItemAndCount::try_from)
+      .filter(|item| item.as_ref().map_or(true, |item| !item.name.is_empty()))
+      .collect()
   }
 
-  fn index_name(&self) -> String {
-    self.version_manager.latest_index_name()
+  /**
+   * FT.SEARCH sorts on a single field, so pages past a keyset are ordered by an aggregate that
+   * breaks ties by file name and then loaded by key
+   */
+  async fn search_after(
+    &self,
+    query: &AlbumSearchQuery,
+    sort: &AlbumSearchSort,
+    keyset: &AlbumSearchKeyset,
+    limit: usize,
+  ) -> Result<AlbumSearchResult> {
+    let mut bounded_query = query.clone();
+    bounded_query.search_after = None;
+    bounded_query.sort = None;
+    let total = self
+      .search(
+        &bounded_query,
+        Some(&SearchPagination {
+          offset: Some(0),
+          limit: Some(0),
+        }),
+      )
+      .await?
+      .total;
+
+    let attribute = format!("@{}", sort.field.redis_attribute());
+    let value = match sort.field {
+      AlbumSearchSortField::Rating => (keyset.value as f32).to_string(),
+      _ => (keyset.value as u32).to_string(),
+    };
+    let (op, order) = if sort.descending {
+      ("<", FtSortBy::desc(attribute.clone()))
+    } else {
+      (">", FtSortBy::asc(attribute.clone()))
+    };
+    let filter = format!(
+      "{0} {1} {2} || ({0} == {2} && @file_name > '{3}')",
+      attribute,
+      op,
+      value,
+      keyset.file_name.to_string().replace('\'', "\\'")
+    );
+    let ft_query = bounded_query.to_ft_search_query();
+    let result = self
+      .redis_connection_pool
+      .get()
+      .await?
+      .ft_aggregate(
+        self.index_name(),
+        if ft_query.is_empty() {
+          "*".to_string()
+        } else {
+          ft_query
+        },
+        FtAggregateOptions::default()
+          .filter(filter)
+          .sortby([order, FtSortBy::asc("@file_name")], None)
+          .limit(0, limit),
+      )
+      .await?;
+    let file_names = result
+      .results
+      .iter()
+      .map(|values| {
+        values
+          .iter()
+          .find(|(key, _)| key == "file_name")
+          .ok_or(anyhow!("invalid aggregate result: missing file_name"))
+          .and_then(|(_, file_name)| FileName::try_from(file_name.as_str()))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let albums = try_join_all(file_names.iter().map(|file_name| self.find(file_name)))
+      .await?
+      .into_iter()
+      .flatten()
+      .collect();
+    Ok(AlbumSearchResult { albums, total })
   }
 
   pub async fn ensure_album_root(&self, file_name: &FileName) -> Result<()> {
@@ -458,20 +720,120 @@ impl RedisAlbumSearchIndex {
 
     Ok(embeddings)
   }
+}
 
-  async fn delete_legacy_embeddings(&self, file_name: &FileName) -> Result<()> {
-    self
-      .redis_connection_pool
-      .get()
-      .await?
-      .json_del(redis_key(file_name), "$.embeddings")
+/**
+ * Moves embeddings from the legacy `$.embeddings` array to their per-provider paths. An
+ * embedding already at its per-provider path is newer, so it's kept over the legacy one.
+ */
+pub struct LegacyEmbeddingsMigration {
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+}
+
+impl LegacyEmbeddingsMigration {
+  pub fn new(redis_connection_pool: Arc<Pool<PooledClientManager>>) -> Self {
+    Self {
+      redis_connection_pool,
+    }
+  }
+
+  async fn migrate_key(&self, key: String) -> Result<()> {
+    let connection = self.redis_connection_pool.get().await?;
+    let result: Option<String> = connection
+      .json_get(&key, JsonGetOptions::default().path("$.embeddings[*]"))
       .await?;
+    let embeddings = result
+      .map(|r| serde_json::from_str::<Vec<EmbeddingDocument>>(&r))
+      .transpose()?
+      .unwrap_or_default();
+    if embeddings.is_empty() {
+      return Ok(());
+    }
+    for embedding in embeddings {
+      connection
+        .json_set(
+          &key,
+          embedding_json_path(&embedding.key),
+          serde_json::to_string(&embedding.embedding)?,
+          SetCondition::NX,
+        )
+        .await?;
+    }
+    connection.json_del(&key, "$.embeddings").await?;
     Ok(())
   }
 }
 
+#[async_trait]
+impl RedisMigration for LegacyEmbeddingsMigration {
+  fn name(&self) -> &'static str {
+    "album_legacy_embeddings"
+  }
+
+  fn key_pattern(&self) -> String {
+    format!("{}:*", NAMESPACE)
+  }
+
+  async fn migrate(&self, keys: Vec<String>) -> Result<()> {
+    stream::iter(keys)
+      .map(Ok)
+      .try_for_each_concurrent(50, |key| self.migrate_key(key))
+      .await
+  }
+}
+
 #[async_trait]
 impl AlbumSearchIndex for RedisAlbumSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
+    self
+      .version_manager
+      .setup_index(
+        FtCreateOptions::default()
+          .on(FtIndexDataType::Json)
+          .prefix(format!("{}:", NAMESPACE)),
+        RedisAlbumSearchIndex::get_schema(&self.embedding_provider_interactor),
+      )
+      .await
+  }
+
+  #[instrument(skip(self))]
+  async fn get_facets(&self, query: &AlbumSearchQuery, limit: usize) -> Result<AlbumSearchFacets> {
+    let ft_query = query.to_ft_search_query();
+    let decade_query = format!("{} @release_year:[1 +inf]", ft_query);
+    let ft_query = if ft_query.is_empty() {
+      "*".to_string()
+    } else {
+      ft_query
+    };
+    let (primary_genres, descriptors, languages, release_decades) = futures::try_join!(
+      self.aggregate_counts(
+        &ft_query,
+        FtAggregateOptions::default(),
+        "@primary_genre",
+        limit
+      ),
+      self.aggregate_counts(
+        &ft_query,
+        FtAggregateOptions::default(),
+        "@descriptor",
+        limit
+      ),
+      self.aggregate_counts(&ft_query, FtAggregateOptions::default(), "@language", limit),
+      self.aggregate_counts(
+        decade_query.trim(),
+        FtAggregateOptions::default().apply("floor(@release_year / 10) * 10", "release_decade"),
+        "@release_decade",
+        limit
+      ),
+    )?;
+    Ok(AlbumSearchFacets {
+      primary_genres,
+      descriptors,
+      languages,
+      release_decades,
+    })
+  }
+
   async fn get_embedding_keys(&self) -> Result<Vec<String>> {
     Ok(
       self
@@ -539,7 +901,21 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
     pagination: Option<&SearchPagination>,
   ) -> Result<AlbumSearchResult> {
     let limit = pagination.and_then(|p| p.limit).unwrap_or(100000);
+    if let (Some(keyset), Some(sort)) = (&query.search_after, &query.sort) {
+      return self.search_after(query, sort, keyset, limit).await;
+    }
     let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
+    let mut options = FtSearchOptions::default().limit(offset, limit);
+    if let Some(sort) = &query.sort {
+      options = options.sortby(
+        sort.field.redis_attribute(),
+        if sort.descending {
+          SortOrder::Desc
+        } else {
+          SortOrder::Asc
+        },
+      );
+    }
 
     let result = self
       .redis_connection_pool
@@ -548,7 +924,7 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
       .ft_search(
         self.index_name(),
         query.to_ft_search_query(),
-        FtSearchOptions::default().limit(offset, limit)._return([
+        options._return([
           FtSearchReturnAttribute::identifier("$.name"),
           FtSearchReturnAttribute::identifier("$.file_name"),
           FtSearchReturnAttribute::identifier("$.rating"),
@@ -565,6 +941,13 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
           FtSearchReturnAttribute::identifier("$.duplicates"),
           FtSearchReturnAttribute::identifier("$.cover_image_url"),
           FtSearchReturnAttribute::identifier("$.spotify_id"),
+          FtSearchReturnAttribute::identifier("$.musicbrainz_id"),
+          FtSearchReturnAttribute::identifier("$.discogs_release"),
+          FtSearchReturnAttribute::identifier("$.is_various_artists"),
+          FtSearchReturnAttribute::identifier("$.bandcamp_url"),
+          FtSearchReturnAttribute::identifier("$.cached_cover_image_url"),
+          FtSearchReturnAttribute::identifier("$.tags"),
+          FtSearchReturnAttribute::identifier("$.cluster_id"),
         ]),
       )
       .await?;
@@ -635,6 +1018,42 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
               _ => album_builder.spotify_id(Some(value)),
             };
           }
+          "$.musicbrainz_id" => {
+            match value.as_str() {
+              "" => album_builder.musicbrainz_id(None),
+              _ => album_builder.musicbrainz_id(Some(value)),
+            };
+          }
+          "$.discogs_release" => {
+            match value.as_str() {
+              "" => album_builder.discogs_release(None),
+              _ => album_builder.discogs_release(serde_json::from_str(value.as_str())?),
+            };
+          }
+          "$.is_various_artists" => {
+            album_builder.is_various_artists(value.parse()?);
+          }
+          "$.bandcamp_url" => {
+            match value.as_str() {
+              "" => album_builder.bandcamp_url(None),
+              _ => album_builder.bandcamp_url(Some(value)),
+            };
+          }
+          "$.cached_cover_image_url" => {
+            match value.as_str() {
+              "" => album_builder.cached_cover_image_url(None),
+              _ => album_builder.cached_cover_image_url(Some(value)),
+            };
+          }
+          "$.tags" => {
+            album_builder.tags(serde_json::from_str(value.as_str())?);
+          }
+          "$.cluster_id" => {
+            match value.as_str() {
+              "" => album_builder.cluster_id(None),
+              _ => album_builder.cluster_id(serde_json::from_str(value.as_str())?),
+            };
+          }
           _ => {}
         };
       }
@@ -661,12 +1080,6 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
         SetCondition::default(),
       )
       .await?;
-    match self.delete_legacy_embeddings(&embedding.file_name).await {
-      Ok(_) => {}
-      Err(e) => {
-        tracing::warn!("failed to delete legacy embeddings: {:?}", e);
-      }
-    };
     Ok(())
   }
 
@@ -804,3 +1217,42 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
     Ok(albums)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_expression_to_ft_search_query() -> Result<()> {
+    let expression = AlbumSearchExpression::And(vec![
+      AlbumSearchExpression::Or(vec![
+        AlbumSearchExpression::Predicate(AlbumSearchPredicate::PrimaryGenre(
+          "Shoegaze".to_string(),
+        )),
+        AlbumSearchExpression::Predicate(AlbumSearchPredicate::PrimaryGenre(
+          "Dream Pop".to_string(),
+        )),
+      ]),
+      AlbumSearchExpression::Not(Box::new(AlbumSearchExpression::Predicate(
+        AlbumSearchPredicate::Artist(FileName::try_from("artist/slowdive")?),
+      ))),
+      AlbumSearchExpression::Predicate(AlbumSearchPredicate::ReleaseYear {
+        min: Some(1990),
+        max: None,
+      }),
+    ]);
+    assert_eq!(
+      expression.to_ft_search_query(),
+      "((@primary_genre:{Shoegaze} | @primary_genre:{Dream\\ Pop}) \
+       -(@artist_file_name:{artist\\/slowdive}) @release_year:[1990, +inf])"
+    );
+    assert!(AlbumSearchQuery {
+      include_duplicates: Some(true),
+      expression: Some(expression),
+      ..Default::default()
+    }
+    .to_ft_search_query()
+    .ends_with("@release_year:[1990, +inf])"));
+    Ok(())
+  }
+}
@@ -0,0 +1,1090 @@
+use super::{
+  album_read_model::AlbumReadModel,
+  album_repository::ItemAndCount,
+  album_search_index::{
+    AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchExpression, AlbumSearchFacets,
+    AlbumSearchIndex, AlbumSearchKeyset, AlbumSearchPredicate, AlbumSearchQuery, AlbumSearchResult,
+    AlbumSearchSort, AlbumSearchSortField,
+  },
+  album_text_search::{AlbumTextField, AlbumTextSearchPlan},
+};
+use crate::{
+  files::file_metadata::file_name::FileName,
+  helpers::{
+    embedding::{embedding_from_bytes, embedding_to_bytes, EmbeddingDocument},
+    math::cosine_similarity,
+    redisearch::SearchPagination,
+  },
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Datelike;
+use rusqlite::{params, params_from_iter, types::Value, ToSql};
+use std::{rc::Rc, sync::Arc};
+use tracing::{error, instrument};
+
+#[derive(Clone)]
+enum FilterParam {
+  Text(String),
+  Integer(i64),
+  Real(f64),
+  List(Vec<String>),
+}
+
+impl FilterParam {
+  fn into_sql(self) -> Box<dyn ToSql> {
+    match self {
+      FilterParam::Text(value) => Box::new(value),
+      FilterParam::Integer(value) => Box::new(value),
+      FilterParam::Real(value) => Box::new(value),
+      FilterParam::List(values) => Box::new(Rc::new(
+        values.into_iter().map(Value::from).collect::<Vec<Value>>(),
+      )),
+    }
+  }
+}
+
+#[derive(Default)]
+struct SqliteAlbumFilter {
+  joins: Vec<String>,
+  conditions: Vec<String>,
+  params: Vec<FilterParam>,
+  is_text_search: bool,
+}
+
+impl SqliteAlbumFilter {
+  fn list<T: ToString>(&mut self, condition: &str, values: &[T]) {
+    if values.is_empty() {
+      return;
+    }
+    self.conditions.push(condition.to_string());
+    self.params.push(FilterParam::List(
+      values.iter().map(|v| v.to_string()).collect(),
+    ));
+  }
+
+  fn json_array(&mut self, path: &str, include: &[String], exclude: &[String]) {
+    self.list(
+      &format!(
+        "EXISTS (SELECT 1 FROM json_each(d.json, '{}') WHERE value IN rarray(?))",
+        path
+      ),
+      include,
+    );
+    self.list(
+      &format!(
+        "NOT EXISTS (SELECT 1 FROM json_each(d.json, '{}') WHERE value IN rarray(?))",
+        path
+      ),
+      exclude,
+    );
+  }
+
+  fn min(&mut self, column: &str, value: Option<i64>) {
+    if let Some(value) = value {
+      self.conditions.push(format!("d.{} >= ?", column));
+      self.params.push(FilterParam::Integer(value));
+    }
+  }
+
+  fn max(&mut self, column: &str, value: Option<i64>) {
+    if let Some(value) = value {
+      self.conditions.push(format!("d.{} <= ?", column));
+      self.params.push(FilterParam::Integer(value));
+    }
+  }
+
+  /**
+   * Ratings are stored as the shortest decimal form of the f32, so the bound is widened through
+   * the same form to compare equal ratings as equal
+   */
+  fn rating(&mut self, op: &str, value: Option<f32>) {
+    if let Some(value) = value {
+      self
+        .conditions
+        .push(format!("json_extract(d.json, '$.rating') {} ?", op));
+      self.params.push(FilterParam::Real(
+        value.to_string().parse().unwrap_or(value as f64),
+      ));
+    }
+  }
+
+  /**
+   * Albums past the keyset in the sort, ties on the value are ordered by file name
+   */
+  fn search_after(&mut self, keyset: &AlbumSearchKeyset, sort: &AlbumSearchSort) {
+    let (column, value) = match sort.field {
+      AlbumSearchSortField::Rating => (
+        "json_extract(d.json, '$.rating')",
+        FilterParam::Real(
+          (keyset.value as f32)
+            .to_string()
+            .parse()
+            .unwrap_or(keyset.value),
+        ),
+      ),
+      _ => ("d.rating_count", FilterParam::Integer(keyset.value as i64)),
+    };
+    self.conditions.push(format!(
+      "({0} {1} ? OR ({0} = ? AND d.file_name > ?))",
+      column,
+      if sort.descending { "<" } else { ">" }
+    ));
+    self.params.push(value.clone());
+    self.params.push(value);
+    self
+      .params
+      .push(FilterParam::Text(keyset.file_name.to_string()));
+  }
+
+  /**
+   * Compiles an expression into a single condition, pushing its params in the order their
+   * placeholders appear
+   */
+  fn expression_condition(&mut self, expression: &AlbumSearchExpression) -> String {
+    match expression {
+      AlbumSearchExpression::Predicate(predicate) => self.predicate_condition(predicate),
+      AlbumSearchExpression::And(children) => format!(
+        "({})",
+        children
+          .iter()
+          .map(|child| self.expression_condition(child))
+          .collect::<Vec<_>>()
+          .join(" AND ")
+      ),
+      AlbumSearchExpression::Or(children) => format!(
+        "({})",
+        children
+          .iter()
+          .map(|child| self.expression_condition(child))
+          .collect::<Vec<_>>()
+          .join(" OR ")
+      ),
+      AlbumSearchExpression::Not(child) => format!("NOT {}", self.expression_condition(child)),
+    }
+  }
+
+  fn json_array_contains_condition(&mut self, path: &str, value: &str) -> String {
+    self.params.push(FilterParam::Text(value.to_string()));
+    format!(
+      "EXISTS (SELECT 1 FROM json_each(d.json, '{}') WHERE value = ?)",
+      path
+    )
+  }
+
+  fn predicate_condition(&mut self, predicate: &AlbumSearchPredicate) -> String {
+    match predicate {
+      AlbumSearchPredicate::PrimaryGenre(genre) => {
+        self.json_array_contains_condition("$.primary_genres", genre)
+      }
+      AlbumSearchPredicate::SecondaryGenre(genre) => {
+        self.json_array_contains_condition("$.secondary_genres", genre)
+      }
+      AlbumSearchPredicate::Descriptor(descriptor) => {
+        self.json_array_contains_condition("$.descriptors", descriptor)
+      }
+      AlbumSearchPredicate::Language(language) => {
+        self.json_array_contains_condition("$.languages", language)
+      }
+      AlbumSearchPredicate::Artist(file_name) => {
+        self.params.push(FilterParam::Text(file_name.to_string()));
+        "EXISTS (SELECT 1 FROM json_each(d.json, '$.artists') WHERE json_extract(value, '$.file_name') = ?)".to_string()
+      }
+      AlbumSearchPredicate::ReleaseYear { min, max } => {
+        let mut conditions = vec![];
+        if let Some(min) = min {
+          conditions.push("d.release_year >= ?");
+          self.params.push(FilterParam::Integer(*min as i64));
+        }
+        if let Some(max) = max {
+          conditions.push("d.release_year <= ?");
+          self.params.push(FilterParam::Integer(*max as i64));
+        }
+        format!("({})", conditions.join(" AND "))
+      }
+    }
+  }
+
+  fn where_clause(&self) -> String {
+    if self.conditions.is_empty() {
+      "".to_string()
+    } else {
+      format!("WHERE {}", self.conditions.join(" AND "))
+    }
+  }
+}
+
+/**
+ * Quotes every term so user input can't be interpreted as FTS5 syntax, the last term is matched
+ * as a prefix to support search-as-you-type.
+ */
+fn to_fts_terms(text: &str) -> String {
+  let terms = text
+    .split_whitespace()
+    .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+    .collect::<Vec<String>>();
+  match terms.split_last() {
+    Some((last, rest)) if !rest.is_empty() => format!("{} {}*", rest.join(" "), last),
+    Some((last, _)) => format!("{}*", last),
+    None => "\"\"".to_string(),
+  }
+}
+
+impl AlbumTextField {
+  fn fts_column(&self) -> &'static str {
+    match self {
+      AlbumTextField::Name => "name",
+      AlbumTextField::AsciiName => "ascii_name",
+      AlbumTextField::ArtistName => "artist_names",
+      AlbumTextField::ArtistAsciiName => "artist_ascii_names",
+    }
+  }
+}
+
+/**
+ * Each clause is restricted to its columns with a column filter, and the clauses are ORed
+ */
+fn to_fts_query(plan: &AlbumTextSearchPlan) -> String {
+  plan
+    .clauses
+    .iter()
+    .map(|clause| {
+      let columns = clause
+        .fields
+        .iter()
+        .map(|field| field.fts_column())
+        .collect::<Vec<_>>()
+        .join(" ");
+      format!("({{{}}} : ({}))", columns, to_fts_terms(&clause.text))
+    })
+    .collect::<Vec<_>>()
+    .join(" OR ")
+}
+
+impl AlbumSearchQuery {
+  fn to_sqlite_filter(&self) -> SqliteAlbumFilter {
+    let mut filter = SqliteAlbumFilter::default();
+    if let Some(plan) = self.text_search_plan() {
+      filter.joins.push(
+        "JOIN album_search_fts f ON f.file_name = d.file_name AND album_search_fts MATCH ?"
+          .to_string(),
+      );
+      filter.params.push(FilterParam::Text(to_fts_query(&plan)));
+      filter.is_text_search = true;
+    }
+    if let Some(exact_name) = &self.exact_name {
+      filter
+        .conditions
+        .push("json_extract(d.json, '$.name') = ?".to_string());
+      filter.params.push(FilterParam::Text(exact_name.clone()));
+    }
+    filter.list("d.file_name IN rarray(?)", &self.include_file_names);
+    filter.list("d.file_name NOT IN rarray(?)", &self.exclude_file_names);
+    filter.list(
+      "EXISTS (SELECT 1 FROM json_each(d.json, '$.artists') WHERE json_extract(value, '$.file_name') IN rarray(?))",
+      &self.include_artists,
+    );
+    filter.list(
+      "NOT EXISTS (SELECT 1 FROM json_each(d.json, '$.artists') WHERE json_extract(value, '$.file_name') IN rarray(?))",
+      &self.exclude_artists,
+    );
+    filter.json_array(
+      "$.primary_genres",
+      &self.include_primary_genres,
+      &self.exclude_primary_genres,
+    );
+    filter.json_array(
+      "$.secondary_genres",
+      &self.include_secondary_genres,
+      &self.exclude_secondary_genres,
+    );
+    filter.json_array(
+      "$.languages",
+      &self.include_languages,
+      &self.exclude_languages,
+    );
+    filter.json_array(
+      "$.descriptors",
+      &self.include_descriptors,
+      &self.exclude_descriptors,
+    );
+    filter.json_array("$.tags", &self.include_tags, &self.exclude_tags);
+    // List params are bound as text
+    filter.list(
+      "CAST(json_extract(d.json, '$.cluster_id') AS TEXT) IN rarray(?)",
+      &self.include_cluster_ids,
+    );
+    filter.min(
+      "primary_genre_count",
+      self.min_primary_genre_count.map(|v| v as i64),
+    );
+    filter.min(
+      "secondary_genre_count",
+      self.min_secondary_genre_count.map(|v| v as i64),
+    );
+    filter.min(
+      "descriptor_count",
+      self.min_descriptor_count.map(|v| v as i64),
+    );
+    filter.min("release_year", self.min_release_year.map(|v| v as i64));
+    filter.max("release_year", self.max_release_year.map(|v| v as i64));
+    filter.rating(">=", self.min_rating);
+    filter.rating("<=", self.max_rating);
+    filter.min("rating_count", self.min_rating_count.map(|v| v as i64));
+    filter.max("rating_count", self.max_rating_count.map(|v| v as i64));
+    if let (Some(keyset), Some(sort)) = (&self.search_after, &self.sort) {
+      filter.search_after(keyset, sort);
+    }
+    if !self.include_duplicates.is_some_and(|b| b) {
+      filter.conditions.push("d.is_duplicate = 0".to_string());
+    }
+    if let Some(expression) = &self.expression {
+      let condition = filter.expression_condition(expression);
+      filter.conditions.push(condition);
+    }
+    filter
+  }
+
+  fn to_sqlite_order_by(&self, is_text_search: bool) -> String {
+    let Some(sort) = &self.sort else {
+      return if is_text_search {
+        "ORDER BY f.rank, d.rating_count DESC, d.file_name".to_string()
+      } else {
+        "ORDER BY d.rating_count DESC, d.file_name".to_string()
+      };
+    };
+    let column = match sort.field {
+      AlbumSearchSortField::Rating => "json_extract(d.json, '$.rating')",
+      AlbumSearchSortField::RatingCount => "d.rating_count",
+      AlbumSearchSortField::ReleaseDate => "json_extract(d.json, '$.release_date')",
+      AlbumSearchSortField::Name => "json_extract(d.json, '$.name') COLLATE NOCASE",
+    };
+    format!(
+      "ORDER BY {} {} NULLS LAST, d.file_name",
+      column,
+      if sort.descending { "DESC" } else { "ASC" }
+    )
+  }
+}
+
+/**
+ * A basic album search index for deployments without Redis. Text search is backed by FTS5 and
+ * embedding similarity search is a brute-force scan, which is fine for small libraries.
+ */
+pub struct SqliteAlbumSearchIndex {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl SqliteAlbumSearchIndex {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * A page of the albums matching the filter, with the total the filter matches
+   */
+  async fn select(
+    &self,
+    filter: SqliteAlbumFilter,
+    order_by: String,
+    offset: usize,
+    limit: usize,
+  ) -> Result<AlbumSearchResult> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let from = format!(
+          "FROM album_search_documents d {} {}",
+          filter.joins.join(" "),
+          filter.where_clause()
+        );
+        let params = filter
+          .params
+          .into_iter()
+          .map(|p| p.into_sql())
+          .collect::<Vec<_>>();
+        let total = conn.query_row(
+          &format!("SELECT COUNT(*) {}", from),
+          params_from_iter(params.iter()),
+          |row| row.get::<_, usize>(0),
+        )?;
+        let mut stmt = conn.prepare(&format!(
+          "SELECT json(d.json) {} {} LIMIT {} OFFSET {}",
+          from, order_by, limit, offset
+        ))?;
+        let albums = stmt
+          .query_map(params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+          })?
+          .filter_map(|json| {
+            json
+              .ok()
+              .and_then(|json| serde_json::from_str::<AlbumReadModel>(&json).ok())
+          })
+          .collect::<Vec<AlbumReadModel>>();
+        Ok(AlbumSearchResult { albums, total })
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to search albums");
+        anyhow!("Failed to search albums")
+      })?
+  }
+}
+
+#[async_trait]
+impl AlbumSearchIndex for SqliteAlbumSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
+    // Schema is managed by migrations
+    Ok(())
+  }
+
+  #[instrument(skip_all, fields(count = albums.len()))]
+  async fn put_many(&self, albums: Vec<AlbumReadModel>) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        for album in albums {
+          let file_name = album.file_name.to_string();
+          let artist_names = album
+            .artists
+            .iter()
+            .map(|artist| artist.name.clone())
+            .collect::<Vec<String>>()
+            .join(" ");
+          let artist_ascii_names = album
+            .artists
+            .iter()
+            .map(|artist| artist.ascii_name())
+            .collect::<Vec<String>>()
+            .join(" ");
+          tx.execute(
+            "
+            INSERT INTO album_search_documents (
+              file_name,
+              json,
+              rating_count,
+              release_year,
+              is_duplicate,
+              primary_genre_count,
+              secondary_genre_count,
+              descriptor_count
+            )
+            VALUES (?, jsonb(?), ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (file_name) DO UPDATE SET
+              json = excluded.json,
+              rating_count = excluded.rating_count,
+              release_year = excluded.release_year,
+              is_duplicate = excluded.is_duplicate,
+              primary_genre_count = excluded.primary_genre_count,
+              secondary_genre_count = excluded.secondary_genre_count,
+              descriptor_count = excluded.descriptor_count
+            ",
+            params![
+              file_name,
+              serde_json::to_string(&album)?,
+              album.rating_count,
+              album.release_date.map(|d| d.year()),
+              album.duplicate_of.is_some(),
+              album.primary_genres.len(),
+              album.secondary_genres.len(),
+              album.descriptors.len(),
+            ],
+          )?;
+          tx.execute(
+            "DELETE FROM album_search_fts WHERE file_name = ?",
+            params![file_name],
+          )?;
+          tx.execute(
+            "
+            INSERT INTO album_search_fts (
+              file_name,
+              name,
+              ascii_name,
+              artist_names,
+              artist_ascii_names
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+            params![
+              file_name,
+              album.name,
+              album.ascii_name(),
+              artist_names,
+              artist_ascii_names
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put albums in search index"
+        );
+        anyhow!("Failed to put albums in search index")
+      })?
+  }
+
+  async fn put(&self, album: AlbumReadModel) -> Result<()> {
+    self.put_many(vec![album]).await
+  }
+
+  async fn delete(&self, file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM album_search_documents WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.execute(
+          "DELETE FROM album_search_fts WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.execute(
+          "DELETE FROM album_search_embeddings WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete album from search index"
+        );
+        anyhow!("Failed to delete album from search index")
+      })?
+  }
+
+  async fn find(&self, file_name: &FileName) -> Result<Option<AlbumReadModel>> {
+    Ok(
+      self
+        .search(
+          &AlbumSearchQuery {
+            include_file_names: vec![file_name.clone()],
+            include_duplicates: Some(true),
+            ..Default::default()
+          },
+          Some(&SearchPagination {
+            offset: None,
+            limit: Some(1),
+          }),
+        )
+        .await?
+        .albums
+        .into_iter()
+        .next(),
+    )
+  }
+
+  #[instrument(skip(self))]
+  async fn search(
+    &self,
+    query: &AlbumSearchQuery,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<AlbumSearchResult> {
+    let filter = query.to_sqlite_filter();
+    let order_by = query.to_sqlite_order_by(filter.is_text_search);
+    let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
+    let limit = pagination.and_then(|p| p.limit).unwrap_or(10);
+    self.select(filter, order_by, offset, limit).await
+  }
+
+  #[instrument(skip(self))]
+  async fn get_random(
+    &self,
+    query: &AlbumSearchQuery,
+    count: usize,
+  ) -> Result<Vec<AlbumReadModel>> {
+    let result = self
+      .select(
+        query.to_sqlite_filter(),
+        "ORDER BY random()".to_string(),
+        0,
+        count,
+      )
+      .await?;
+    Ok(result.albums)
+  }
+
+  #[instrument(skip(self))]
+  async fn get_facets(&self, query: &AlbumSearchQuery, limit: usize) -> Result<AlbumSearchFacets> {
+    let filter = query.to_sqlite_filter();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let where_clause = filter.where_clause();
+        let joins = filter.joins.join(" ");
+        let params = filter
+          .params
+          .into_iter()
+          .map(|p| p.into_sql())
+          .collect::<Vec<_>>();
+        let count_values = |value: &str, source: &str| {
+          let mut stmt = conn.prepare(&format!(
+            "
+            SELECT {} AS name, COUNT(*) AS count
+            FROM album_search_documents d {} {} {}
+            GROUP BY name
+            HAVING name IS NOT NULL
+            ORDER BY count DESC, name ASC
+            LIMIT {}
+            ",
+            value, joins, source, where_clause, limit
+          ))?;
+          let items = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+              Ok(ItemAndCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+              })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+          Ok::<_, rusqlite::Error>(items)
+        };
+        Ok(AlbumSearchFacets {
+          primary_genres: count_values("v.value", ", json_each(d.json, '$.primary_genres') v")?,
+          descriptors: count_values("v.value", ", json_each(d.json, '$.descriptors') v")?,
+          languages: count_values("v.value", ", json_each(d.json, '$.languages') v")?,
+          release_decades: count_values("CAST(d.release_year / 10 * 10 AS TEXT)", "")?,
+        })
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get album search facets");
+        anyhow!("Failed to get album search facets")
+      })?
+  }
+
+  async fn get_embedding_keys(&self) -> Result<Vec<String>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT key FROM album_search_embeddings")?;
+        let keys = stmt
+          .query_map([], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<String>, _>>()?;
+        Ok(keys)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get embedding keys");
+        anyhow!("Failed to get embedding keys")
+      })?
+  }
+
+  async fn get_embeddings(&self, file_name: &FileName) -> Result<Vec<EmbeddingDocument>> {
+    let file_name = file_name.clone();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt =
+          conn.prepare("SELECT key, embedding FROM album_search_embeddings WHERE file_name = ?")?;
+        let docs = stmt
+          .query_map(params![file_name.to_string()], |row| {
+            Ok(EmbeddingDocument {
+              file_name: file_name.clone(),
+              key: row.get(0)?,
+              embedding: embedding_from_bytes(&row.get::<_, Vec<u8>>(1)?),
+            })
+          })?
+          .collect::<Result<Vec<EmbeddingDocument>, _>>()?;
+        Ok(docs)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get embeddings");
+        anyhow!("Failed to get embeddings")
+      })?
+  }
+
+  async fn find_many_embeddings(
+    &self,
+    file_names: Vec<FileName>,
+    key: &str,
+  ) -> Result<Vec<EmbeddingDocument>> {
+    let key = key.to_string();
+    let file_name_params = file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT file_name, embedding
+          FROM album_search_embeddings
+          WHERE key = ? AND file_name IN rarray(?)
+          ",
+        )?;
+        let docs = stmt
+          .query_map(params![key, Rc::new(file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+          })?
+          .filter_map(|row| {
+            let (file_name, embedding) = row.ok()?;
+            Some(EmbeddingDocument {
+              file_name: FileName::try_from(file_name).ok()?,
+              key: key.clone(),
+              embedding: embedding_from_bytes(&embedding),
+            })
+          })
+          .collect::<Vec<EmbeddingDocument>>();
+        Ok(docs)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find embeddings");
+        anyhow!("Failed to find embeddings")
+      })?
+  }
+
+  async fn find_embedding(
+    &self,
+    file_name: &FileName,
+    key: &str,
+  ) -> Result<Option<EmbeddingDocument>> {
+    Ok(
+      self
+        .find_many_embeddings(vec![file_name.clone()], key)
+        .await?
+        .into_iter()
+        .next(),
+    )
+  }
+
+  #[instrument(skip_all, fields(count = docs.len()))]
+  async fn put_many_embeddings(&self, docs: Vec<EmbeddingDocument>) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        for doc in docs {
+          tx.execute(
+            "
+            INSERT INTO album_search_embeddings (file_name, key, embedding)
+            VALUES (?, ?, ?)
+            ON CONFLICT (file_name, key) DO UPDATE SET embedding = excluded.embedding
+            ",
+            params![
+              doc.file_name.to_string(),
+              doc.key,
+              embedding_to_bytes(&doc.embedding)
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put embeddings");
+        anyhow!("Failed to put embeddings")
+      })?
+  }
+
+  async fn put_embedding(&self, embedding: EmbeddingDocument) -> Result<()> {
+    self.put_many_embeddings(vec![embedding]).await
+  }
+
+  async fn delete_embedding(&self, file_name: &FileName, key: &str) -> Result<()> {
+    let file_name = file_name.to_string();
+    let key = key.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM album_search_embeddings WHERE file_name = ? AND key = ?",
+          params![file_name, key],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete embedding");
+        anyhow!("Failed to delete embedding")
+      })?
+  }
+
+  /**
+   * Returns cosine distance to match the Redis index, lower is more similar.
+   */
+  #[instrument(skip_all, fields(key = query.embedding_key, limit = query.limit))]
+  async fn embedding_similarity_search(
+    &self,
+    query: &AlbumEmbeddingSimilarirtySearchQuery,
+  ) -> Result<Vec<(AlbumReadModel, f32)>> {
+    let mut filter = query.filters.to_sqlite_filter();
+    filter
+      .joins
+      .push("JOIN album_search_embeddings e ON e.file_name = d.file_name".to_string());
+    filter.conditions.push("e.key = ?".to_string());
+    filter
+      .params
+      .push(FilterParam::Text(query.embedding_key.clone()));
+    let embedding = query.embedding.clone();
+    let limit = query.limit;
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+          "SELECT json(d.json), e.embedding FROM album_search_documents d {} {}",
+          filter.joins.join(" "),
+          filter.where_clause()
+        ))?;
+        let params = filter
+          .params
+          .into_iter()
+          .map(|p| p.into_sql())
+          .collect::<Vec<_>>();
+        let mut results = stmt
+          .query_map(params_from_iter(params.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+          })?
+          .filter_map(|row| {
+            let (json, candidate) = row.ok()?;
+            let album = serde_json::from_str::<AlbumReadModel>(&json).ok()?;
+            let distance = 1.0 - cosine_similarity(&embedding, &embedding_from_bytes(&candidate));
+            Some((album, distance))
+          })
+          .collect::<Vec<(AlbumReadModel, f32)>>();
+        results.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        results.truncate(limit);
+        Ok(results)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to run embedding similarity search"
+        );
+        anyhow!("Failed to run embedding similarity search")
+      })?
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_search_cursor::AlbumSearchCursor;
+  use chrono::NaiveDate;
+
+  fn album(name: &str, rating: f32, rating_count: u32, year: i32) -> AlbumReadModel {
+    AlbumReadModel {
+      name: name.to_string(),
+      file_name: FileName::try_from(format!("release/album/artist/{}", name)).unwrap(),
+      rating,
+      rating_count,
+      release_date: NaiveDate::from_ymd_opt(year, 1, 1),
+      primary_genres: vec!["Rock".to_string()],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_to_sqlite_filter() {
+    let query = AlbumSearchQuery {
+      include_primary_genres: vec!["Rock".to_string()],
+      exclude_file_names: vec![FileName::try_from("release/album/artist/a").unwrap()],
+      min_release_year: Some(1990),
+      ..Default::default()
+    };
+    let filter = query.to_sqlite_filter();
+    assert_eq!(
+      filter.where_clause(),
+      "WHERE d.file_name NOT IN rarray(?) \
+       AND EXISTS (SELECT 1 FROM json_each(d.json, '$.primary_genres') WHERE value IN rarray(?)) \
+       AND d.release_year >= ? AND d.is_duplicate = 0"
+    );
+    assert_eq!(filter.params.len(), 3);
+    assert!(!filter.is_text_search);
+  }
+
+  #[test]
+  fn test_to_sqlite_order_by() {
+    let mut query = AlbumSearchQuery::default();
+    assert_eq!(
+      query.to_sqlite_order_by(false),
+      "ORDER BY d.rating_count DESC, d.file_name"
+    );
+    assert_eq!(
+      query.to_sqlite_order_by(true),
+      "ORDER BY f.rank, d.rating_count DESC, d.file_name"
+    );
+    query.sort = Some(AlbumSearchSort {
+      field: AlbumSearchSortField::Rating,
+      descending: true,
+    });
+    assert_eq!(
+      query.to_sqlite_order_by(false),
+      "ORDER BY json_extract(d.json, '$.rating') DESC NULLS LAST, d.file_name"
+    );
+  }
+
+  #[test]
+  fn test_expression_to_sqlite_filter() -> Result<()> {
+    let query = AlbumSearchQuery {
+      include_duplicates: Some(true),
+      expression: Some(AlbumSearchExpression::And(vec![
+        AlbumSearchExpression::Or(vec![
+          AlbumSearchExpression::Predicate(AlbumSearchPredicate::PrimaryGenre(
+            "Shoegaze".to_string(),
+          )),
+          AlbumSearchExpression::Predicate(AlbumSearchPredicate::Artist(FileName::try_from(
+            "artist/slowdive",
+          )?)),
+        ]),
+        AlbumSearchExpression::Not(Box::new(AlbumSearchExpression::Predicate(
+          AlbumSearchPredicate::ReleaseYear {
+            min: Some(1990),
+            max: Some(1999),
+          },
+        ))),
+      ])),
+      ..Default::default()
+    };
+    let filter = query.to_sqlite_filter();
+    assert_eq!(
+      filter.where_clause(),
+      "WHERE ((EXISTS (SELECT 1 FROM json_each(d.json, '$.primary_genres') WHERE value = ?) \
+       OR EXISTS (SELECT 1 FROM json_each(d.json, '$.artists') WHERE json_extract(value, '$.file_name') = ?)) \
+       AND NOT (d.release_year >= ? AND d.release_year <= ?))"
+    );
+    assert_eq!(filter.params.len(), 4);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_search() -> Result<()> {
+    let index = SqliteAlbumSearchIndex::new(Arc::new(SqliteConnection::new_for_test().await?));
+    index
+      .put_many(vec![
+        album("a", 3.5, 100, 1985),
+        album("b", 3.9, 50, 1995),
+        album("c", 3.2, 10, 2005),
+      ])
+      .await?;
+    let result = index
+      .search(
+        &AlbumSearchQuery {
+          min_release_year: Some(1990),
+          sort: Some(AlbumSearchSort {
+            field: AlbumSearchSortField::Rating,
+            descending: false,
+          }),
+          ..Default::default()
+        },
+        None,
+      )
+      .await?;
+    assert_eq!(result.total, 2);
+    assert_eq!(
+      result
+        .albums
+        .iter()
+        .map(|album| album.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["c", "b"]
+    );
+
+    let result = index
+      .search(
+        &AlbumSearchQuery {
+          expression: Some(AlbumSearchExpression::Not(Box::new(
+            AlbumSearchExpression::Predicate(AlbumSearchPredicate::ReleaseYear {
+              min: Some(1990),
+              max: Some(1999),
+            }),
+          ))),
+          ..Default::default()
+        },
+        None,
+      )
+      .await?;
+    assert_eq!(result.total, 2);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_search_from_cursor() -> Result<()> {
+    let index = SqliteAlbumSearchIndex::new(Arc::new(SqliteConnection::new_for_test().await?));
+    index
+      .put_many(vec![
+        album("a", 3.5, 100, 1985),
+        album("c", 3.9, 20, 1995),
+        album("b", 3.2, 20, 2005),
+        album("d", 3.2, 20, 2005),
+        album("e", 3.2, 10, 2005),
+      ])
+      .await?;
+    let query = AlbumSearchQuery::default();
+    let mut cursor = None;
+    let mut names = vec![];
+    loop {
+      let (result, next_cursor) = index.search_from_cursor(&query, cursor.as_ref(), 2).await?;
+      names.extend(result.albums.into_iter().map(|album| album.name));
+      if next_cursor.is_none() {
+        break;
+      }
+      cursor = next_cursor;
+    }
+    assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+    assert!(matches!(cursor, Some(AlbumSearchCursor::Keyset(_))));
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_get_random() -> Result<()> {
+    let index = SqliteAlbumSearchIndex::new(Arc::new(SqliteConnection::new_for_test().await?));
+    index
+      .put_many(vec![
+        album("a", 3.5, 100, 1985),
+        album("b", 3.9, 50, 1995),
+        album("c", 3.2, 10, 2005),
+      ])
+      .await?;
+    let query = AlbumSearchQuery {
+      min_release_year: Some(1990),
+      ..Default::default()
+    };
+    let mut names = index
+      .get_random(&query, 5)
+      .await?
+      .into_iter()
+      .map(|album| album.name)
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["b", "c"]);
+    assert_eq!(index.get_random(&query, 1).await?.len(), 1);
+    Ok(())
+  }
+}
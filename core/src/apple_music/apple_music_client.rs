@@ -0,0 +1,320 @@
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  music_service::music_service_client::{
+    MusicService, MusicServiceAlbum, MusicServiceClient, MusicServiceTrack, MusicServiceTrackQuery,
+  },
+  settings::AppleMusicSettings,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{sync::Arc, time::Duration};
+use tracing::{info, warn};
+
+lazy_static! {
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(5u32)));
+}
+
+const API_HOST: &str = "https://api.music.apple.com";
+const API_URL: &str = "https://api.music.apple.com/v1";
+const DEFAULT_STOREFRONT: &str = "us";
+const USER_TOKEN_KEY: &str = "apple_music:user_token";
+const SEARCH_LIMIT: &str = "10";
+const LIBRARY_PAGE_SIZE: &str = "100";
+const PLAYLIST_ITEMS_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct AppleMusicResource {
+  id: String,
+  #[serde(default)]
+  attributes: Value,
+}
+
+impl AppleMusicResource {
+  fn attribute(&self, name: &str) -> Option<String> {
+    self.attributes.get(name)?.as_str().map(String::from)
+  }
+
+  fn artist_names(&self) -> Vec<String> {
+    self
+      .attribute("artistName")
+      .map(|artist_name| vec![artist_name])
+      .unwrap_or_default()
+  }
+
+  fn to_track(&self) -> Option<MusicServiceTrack> {
+    Some(MusicServiceTrack {
+      id: self.id.clone(),
+      name: self.attribute("name")?,
+      artist_names: self.artist_names(),
+      isrc: self.attribute("isrc"),
+    })
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct AppleMusicResponse {
+  #[serde(default)]
+  data: Vec<AppleMusicResource>,
+  next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppleMusicSearchResults {
+  songs: Option<AppleMusicResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppleMusicSearchResponse {
+  results: AppleMusicSearchResults,
+}
+
+/**
+ * Apple Music credits every artist in a single string, so a candidate whose credit contains one of
+ * the queried artists is compared on its name alone
+ */
+fn is_match(query: &MusicServiceTrackQuery, track: &MusicServiceTrack) -> bool {
+  if query.is_match(track) {
+    return true;
+  }
+  let credit = track.artist_names.join(" ").to_lowercase();
+  query.isrc.is_none()
+    && query
+      .artist_names
+      .iter()
+      .any(|artist_name| credit.contains(&artist_name.to_lowercase()))
+    && query.is_match(&MusicServiceTrack {
+      artist_names: vec![],
+      ..track.clone()
+    })
+}
+
+pub struct AppleMusicClient {
+  client: Client,
+  settings: AppleMusicSettings,
+  kv: Arc<KeyValueStore>,
+}
+
+impl AppleMusicClient {
+  pub fn new(settings: AppleMusicSettings, kv: Arc<KeyValueStore>) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+      kv,
+    }
+  }
+
+  fn storefront(&self) -> &str {
+    self
+      .settings
+      .storefront
+      .as_deref()
+      .unwrap_or(DEFAULT_STOREFRONT)
+  }
+
+  pub async fn is_authorized(&self) -> bool {
+    matches!(self.kv.get::<String>(USER_TOKEN_KEY).await, Ok(Some(_)))
+  }
+
+  /**
+   * Music user tokens are issued to the browser by MusicKit JS, so clients hand them over once
+   * the user has signed in
+   */
+  pub async fn put_user_token(&self, user_token: &str) -> Result<()> {
+    self.kv.set(USER_TOKEN_KEY, user_token, None).await
+  }
+
+  async fn user_token(&self) -> Result<String> {
+    self
+      .kv
+      .get::<String>(USER_TOKEN_KEY)
+      .await?
+      .ok_or_else(|| anyhow!("Apple Music user token not found"))
+  }
+
+  async fn catalog_request(&self, method: Method, url: &str) -> RequestBuilder {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(200)))
+      .await;
+    self
+      .client
+      .request(method, url)
+      .bearer_auth(&self.settings.developer_token)
+  }
+
+  async fn library_request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+    let user_token = self.user_token().await?;
+    Ok(
+      self
+        .catalog_request(method, url)
+        .await
+        .header("music-user-token", user_token),
+    )
+  }
+
+  async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+    Ok(
+      request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<T>()
+        .await?,
+    )
+  }
+
+  async fn search_tracks(&self, query: &MusicServiceTrackQuery) -> Result<Vec<MusicServiceTrack>> {
+    let songs = match &query.isrc {
+      Some(isrc) => {
+        self
+          .send::<AppleMusicResponse>(
+            self
+              .catalog_request(
+                Method::GET,
+                &format!("{}/catalog/{}/songs", API_URL, self.storefront()),
+              )
+              .await
+              .query(&[("filter[isrc]", isrc.as_str())]),
+          )
+          .await?
+          .data
+      }
+      None => self
+        .send::<AppleMusicSearchResponse>(
+          self
+            .catalog_request(
+              Method::GET,
+              &format!("{}/catalog/{}/search", API_URL, self.storefront()),
+            )
+            .await
+            .query(&[
+              ("term", query.search_text().as_str()),
+              ("types", "songs"),
+              ("limit", SEARCH_LIMIT),
+            ]),
+        )
+        .await?
+        .results
+        .songs
+        .map(|songs| songs.data)
+        .unwrap_or_default(),
+    };
+    Ok(songs.iter().filter_map(|song| song.to_track()).collect())
+  }
+
+  async fn add_playlist_tracks(&self, playlist_id: &str, track_ids: &[String]) -> Result<()> {
+    for chunk in track_ids.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+      let response = self
+        .library_request(
+          Method::POST,
+          &format!("{}/me/library/playlists/{}/tracks", API_URL, playlist_id),
+        )
+        .await?
+        .json(&json!({
+          "data": chunk
+            .iter()
+            .map(|id| json!({ "id": id, "type": "songs" }))
+            .collect::<Vec<_>>(),
+        }))
+        .send()
+        .await?;
+      if let Err(e) = response.error_for_status() {
+        warn!(
+          playlist_id,
+          error = e.to_string(),
+          "Failed to add tracks to Apple Music playlist"
+        );
+        return Err(e.into());
+      }
+    }
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl MusicServiceClient for AppleMusicClient {
+  fn service(&self) -> MusicService {
+    MusicService::AppleMusic
+  }
+
+  async fn is_authorized(&self) -> bool {
+    AppleMusicClient::is_authorized(self).await
+  }
+
+  async fn get_saved_albums(&self) -> Result<Vec<MusicServiceAlbum>> {
+    let mut albums = vec![];
+    let mut url = Some(format!(
+      "{}/me/library/albums?limit={}",
+      API_URL, LIBRARY_PAGE_SIZE
+    ));
+    while let Some(next_url) = url {
+      let response: AppleMusicResponse = self
+        .send(self.library_request(Method::GET, &next_url).await?)
+        .await?;
+      albums.extend(response.data.iter().filter_map(|album| {
+        Some(MusicServiceAlbum {
+          id: album.id.clone(),
+          name: album.attribute("name")?,
+          artist_names: album.artist_names(),
+        })
+      }));
+      url = response
+        .next
+        .map(|next| format!("{}{}&limit={}", API_HOST, next, LIBRARY_PAGE_SIZE));
+    }
+    info!(count = albums.len(), "Fetched Apple Music saved albums");
+    Ok(albums)
+  }
+
+  async fn find_track(&self, query: &MusicServiceTrackQuery) -> Result<Option<MusicServiceTrack>> {
+    let track = self
+      .search_tracks(query)
+      .await?
+      .into_iter()
+      .find(|track| is_match(query, track));
+    if track.is_none() && query.isrc.is_some() {
+      return self
+        .find_track(&MusicServiceTrackQuery {
+          isrc: None,
+          ..query.clone()
+        })
+        .await;
+    }
+    Ok(track)
+  }
+
+  async fn create_playlist(
+    &self,
+    name: String,
+    description: Option<String>,
+    track_ids: Vec<String>,
+  ) -> Result<String> {
+    let playlist: AppleMusicResponse = self
+      .send(
+        self
+          .library_request(Method::POST, &format!("{}/me/library/playlists", API_URL))
+          .await?
+          .json(&json!({
+            "attributes": {
+              "name": name,
+              "description": description.unwrap_or_default(),
+            },
+          })),
+      )
+      .await?;
+    let playlist_id = playlist
+      .data
+      .into_iter()
+      .next()
+      .map(|playlist| playlist.id)
+      .ok_or_else(|| anyhow!("Apple Music did not return the created playlist"))?;
+    self.add_playlist_tracks(&playlist_id, &track_ids).await?;
+    Ok(playlist_id)
+  }
+}
@@ -0,0 +1,59 @@
+use super::apple_music_client::AppleMusicClient;
+use crate::{
+  context::ApplicationContext,
+  proto::{self, IsAuthorizedReply, PutAppleMusicUserTokenRequest},
+  tenant::tenant_id::require_default_tenant,
+};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::error;
+
+pub struct AppleMusicService {
+  pub apple_music_client: Option<Arc<AppleMusicClient>>,
+}
+
+impl AppleMusicService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      apple_music_client: app_context.apple_music_client.clone(),
+    }
+  }
+
+  fn client<T>(&self, request: &Request<T>) -> Result<&AppleMusicClient, Status> {
+    require_default_tenant(request, "Apple Music")?;
+    self
+      .apple_music_client
+      .as_deref()
+      .ok_or_else(|| Status::failed_precondition("Apple Music is not configured"))
+  }
+}
+
+#[tonic::async_trait]
+impl proto::AppleMusicService for AppleMusicService {
+  async fn is_authorized(
+    &self,
+    request: Request<()>,
+  ) -> Result<Response<IsAuthorizedReply>, Status> {
+    let reply = IsAuthorizedReply {
+      authorized: self.client(&request)?.is_authorized().await,
+    };
+    Ok(Response::new(reply))
+  }
+
+  async fn put_user_token(
+    &self,
+    request: Request<PutAppleMusicUserTokenRequest>,
+  ) -> Result<Response<()>, Status> {
+    let client = self.client(&request)?;
+    let user_token = request.into_inner().user_token;
+    if user_token.is_empty() {
+      return Err(Status::invalid_argument("User token is required"));
+    }
+    client.put_user_token(&user_token).await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Internal server error")
+    })?;
+
+    Ok(Response::new(()))
+  }
+}
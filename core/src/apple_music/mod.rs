@@ -0,0 +1,2 @@
+pub mod apple_music_client;
+pub mod apple_music_service;
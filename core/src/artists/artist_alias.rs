@@ -0,0 +1,161 @@
+use crate::files::file_metadata::file_name::FileName;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArtistAlias {
+  pub alias_file_name: FileName,
+  pub canonical_file_name: FileName,
+}
+
+/**
+ * Groups of file names RYM keeps for the same artist, e.g. transliterations or "& band" variants.
+ * Aliases always point straight at their canonical artist, which is never an alias itself.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct ArtistAliases {
+  canonical_by_alias: HashMap<FileName, FileName>,
+  aliases_by_canonical: HashMap<FileName, Vec<FileName>>,
+}
+
+impl ArtistAliases {
+  pub fn new(aliases: Vec<ArtistAlias>) -> Self {
+    let mut canonical_by_alias = HashMap::new();
+    let mut aliases_by_canonical: HashMap<FileName, Vec<FileName>> = HashMap::new();
+    for alias in aliases {
+      aliases_by_canonical
+        .entry(alias.canonical_file_name.clone())
+        .or_default()
+        .push(alias.alias_file_name.clone());
+      canonical_by_alias.insert(alias.alias_file_name, alias.canonical_file_name);
+    }
+    Self {
+      canonical_by_alias,
+      aliases_by_canonical,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.canonical_by_alias.is_empty()
+  }
+
+  pub fn aliases(&self) -> Vec<ArtistAlias> {
+    self
+      .canonical_by_alias
+      .iter()
+      .map(|(alias, canonical)| ArtistAlias {
+        alias_file_name: alias.clone(),
+        canonical_file_name: canonical.clone(),
+      })
+      .collect()
+  }
+
+  pub fn resolve(&self, file_name: &FileName) -> FileName {
+    self
+      .canonical_by_alias
+      .get(file_name)
+      .unwrap_or(file_name)
+      .clone()
+  }
+
+  /**
+   * Every file name of the artist, canonical first
+   */
+  pub fn group(&self, file_name: &FileName) -> Vec<FileName> {
+    let canonical = self.resolve(file_name);
+    let mut group = vec![canonical.clone()];
+    if let Some(aliases) = self.aliases_by_canonical.get(&canonical) {
+      group.extend(aliases.iter().cloned());
+    }
+    group
+  }
+
+  /**
+   * The given file names along with every alias of their artists, without repeats
+   */
+  pub fn expand(&self, file_names: &[FileName]) -> Vec<FileName> {
+    let mut seen = HashSet::new();
+    file_names
+      .iter()
+      .flat_map(|file_name| self.group(file_name))
+      .filter(|file_name| seen.insert(file_name.clone()))
+      .collect()
+  }
+
+  /**
+   * Sums the scores of each artist's file names and gives the total to all of them
+   */
+  pub fn merge_scores(&self, scores: HashMap<FileName, f64>) -> HashMap<FileName, f64> {
+    if self.is_empty() {
+      return scores;
+    }
+    let mut canonical_scores: HashMap<FileName, f64> = HashMap::new();
+    for (file_name, score) in scores {
+      *canonical_scores
+        .entry(self.resolve(&file_name))
+        .or_insert(0.0) += score;
+    }
+    canonical_scores
+      .into_iter()
+      .flat_map(|(canonical, score)| {
+        self
+          .group(&canonical)
+          .into_iter()
+          .map(move |file_name| (file_name, score))
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn file_name(value: &str) -> FileName {
+    FileName::try_from(format!("artist/{}", value)).unwrap()
+  }
+
+  #[test]
+  fn test_artist_aliases() {
+    let aliases = ArtistAliases::new(vec![
+      ArtistAlias {
+        alias_file_name: file_name("ryuichi-sakamoto-1"),
+        canonical_file_name: file_name("ryuichi-sakamoto"),
+      },
+      ArtistAlias {
+        alias_file_name: file_name("nick-cave-and-the-bad-seeds"),
+        canonical_file_name: file_name("nick-cave"),
+      },
+    ]);
+    assert_eq!(
+      aliases.resolve(&file_name("ryuichi-sakamoto-1")),
+      file_name("ryuichi-sakamoto")
+    );
+    assert_eq!(
+      aliases.resolve(&file_name("slowdive")),
+      file_name("slowdive")
+    );
+    assert_eq!(
+      aliases.expand(&[
+        file_name("nick-cave-and-the-bad-seeds"),
+        file_name("nick-cave")
+      ]),
+      vec![
+        file_name("nick-cave"),
+        file_name("nick-cave-and-the-bad-seeds")
+      ]
+    );
+
+    let scores = aliases.merge_scores(HashMap::from([
+      (file_name("nick-cave"), 1.0),
+      (file_name("nick-cave-and-the-bad-seeds"), 2.0),
+      (file_name("slowdive"), 0.5),
+    ]));
+    assert_eq!(scores.get(&file_name("nick-cave")), Some(&3.0));
+    assert_eq!(
+      scores.get(&file_name("nick-cave-and-the-bad-seeds")),
+      Some(&3.0)
+    );
+    assert_eq!(scores.get(&file_name("slowdive")), Some(&0.5));
+    assert_eq!(scores.get(&file_name("ryuichi-sakamoto")), None);
+  }
+}
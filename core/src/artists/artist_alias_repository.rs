@@ -0,0 +1,110 @@
+use super::artist_alias::{ArtistAlias, ArtistAliases};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+pub struct ArtistAliasRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl ArtistAliasRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Aliases of the alias are moved over to the canonical artist, so lookups stay one step deep
+   */
+  #[instrument(skip(self))]
+  pub async fn put(
+    &self,
+    alias_file_name: &FileName,
+    canonical_file_name: &FileName,
+  ) -> Result<()> {
+    let alias_file_name = alias_file_name.to_string();
+    let canonical_file_name = canonical_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "UPDATE artist_aliases SET canonical_file_name = ? WHERE canonical_file_name = ?",
+          params![canonical_file_name, alias_file_name],
+        )?;
+        tx.execute(
+          "
+          INSERT INTO artist_aliases (alias_file_name, canonical_file_name)
+          VALUES (?, ?)
+          ON CONFLICT (alias_file_name) DO UPDATE SET canonical_file_name = excluded.canonical_file_name
+          ",
+          params![alias_file_name, canonical_file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put artist alias");
+        anyhow!("Failed to put artist alias")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete(&self, alias_file_name: &FileName) -> Result<()> {
+    let alias_file_name = alias_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM artist_aliases WHERE alias_file_name = ?",
+          params![alias_file_name],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete artist alias");
+        anyhow!("Failed to delete artist alias")
+      })??;
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_aliases(&self) -> Result<ArtistAliases> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement =
+          conn.prepare("SELECT alias_file_name, canonical_file_name FROM artist_aliases")?;
+        let rows = statement
+          .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find artist aliases");
+        anyhow!("Failed to find artist aliases")
+      })??;
+
+    let aliases = rows
+      .into_iter()
+      .map(|(alias_file_name, canonical_file_name)| {
+        Ok(ArtistAlias {
+          alias_file_name: FileName::try_from(alias_file_name)?,
+          canonical_file_name: FileName::try_from(canonical_file_name)?,
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(ArtistAliases::new(aliases))
+  }
+}
@@ -1,5 +1,6 @@
 use crate::{
   context::ApplicationContext,
+  event_handler,
   events::{
     event::{Event, Topic},
     event_subscriber::{
@@ -8,6 +9,7 @@ use crate::{
     },
   },
   group_event_handler,
+  parser::parsed_file_data::ParsedFileData,
 };
 use anyhow::Result;
 use std::sync::Arc;
@@ -44,15 +46,43 @@ pub async fn update_artist_search_records(
   Ok(())
 }
 
+pub async fn update_artist_alternate_names(
+  event_data: EventData,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  if let Event::FileParsed {
+    file_id: _,
+    file_name,
+    data: ParsedFileData::Artist(parsed_artist),
+  } = event_data.payload.event
+  {
+    app_context
+      .artist_interactor
+      .put_alternate_names(file_name, parsed_artist.alternate_names)
+      .await?;
+  }
+  Ok(())
+}
+
 pub fn build_artist_event_subscribers(
   app_context: Arc<ApplicationContext>,
 ) -> Result<Vec<EventSubscriber>> {
-  Ok(vec![EventSubscriberBuilder::default()
-    .id("update_artist_search_records")
-    .topic(Topic::Album)
-    .batch_size(75)
-    .app_context(Arc::clone(&app_context))
-    .grouping_strategy(GroupingStrategy::All)
-    .handler(group_event_handler!(update_artist_search_records))
-    .build()?])
+  Ok(vec![
+    EventSubscriberBuilder::default()
+      .id("update_artist_search_records")
+      .topic(Topic::Album)
+      .batch_size(75)
+      .app_context(Arc::clone(&app_context))
+      .grouping_strategy(GroupingStrategy::All)
+      .handler(group_event_handler!(update_artist_search_records))
+      .build()?,
+    EventSubscriberBuilder::default()
+      .id("update_artist_alternate_names")
+      .topic(Topic::Parser)
+      .batch_size(250)
+      .app_context(Arc::clone(&app_context))
+      .handler(event_handler!(update_artist_alternate_names))
+      .build()?,
+  ])
 }
@@ -1,4 +1,6 @@
 use super::{
+  artist_alias::ArtistAliases,
+  artist_alias_repository::ArtistAliasRepository,
   artist_read_model::{ArtistOverview, ArtistReadModel},
   artist_repository::ArtistRepository,
   artist_search_index::{
@@ -11,7 +13,7 @@ use crate::{
   helpers::{embedding::EmbeddingDocument, redisearch::SearchPagination},
   sqlite::SqliteConnection,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use elasticsearch::Elasticsearch;
 use std::{
   collections::{HashMap, HashSet},
@@ -21,8 +23,14 @@ use tracing::instrument;
 
 pub type ArtistInformation = (ArtistReadModel, ArtistOverview);
 
+pub struct ArtistSearchMatch {
+  pub information: ArtistInformation,
+  pub matched_name: Option<String>,
+}
+
 pub struct ArtistInteractor {
   artist_repository: ArtistRepository,
+  artist_alias_repository: ArtistAliasRepository,
   artist_search_index: ArtistSearchIndex,
   album_interactor: Arc<AlbumInteractor>,
 }
@@ -34,7 +42,8 @@ impl ArtistInteractor {
     album_interactor: Arc<AlbumInteractor>,
   ) -> Self {
     Self {
-      artist_repository: ArtistRepository::new(sqlite_connection),
+      artist_repository: ArtistRepository::new(Arc::clone(&sqlite_connection)),
+      artist_alias_repository: ArtistAliasRepository::new(sqlite_connection),
       artist_search_index: ArtistSearchIndex::new(elasticsearch_client),
       album_interactor,
     }
@@ -109,25 +118,102 @@ impl ArtistInteractor {
 
   #[instrument(skip_all, fields(artists = artist_file_names.len()))]
   pub async fn update_search_records(&self, artist_file_names: Vec<FileName>) -> Result<()> {
-    let overviews = self.get_overviews(artist_file_names).await?;
+    let artists = self.get_artists_information(artist_file_names).await?;
     self
       .artist_search_index
       .put_many(
-        overviews
-          .into_values()
-          .map(|overview| overview.into())
+        artists
+          .into_iter()
+          .map(|(artist, overview)| {
+            ArtistSearchRecord::from(overview).with_name_variants(artist.name_variants())
+          })
           .collect::<Vec<ArtistSearchRecord>>(),
       )
       .await?;
     Ok(())
   }
 
+  #[instrument(skip(self, alternate_names))]
+  pub async fn put_alternate_names(
+    &self,
+    artist_file_name: FileName,
+    alternate_names: Vec<String>,
+  ) -> Result<()> {
+    self
+      .artist_repository
+      .put_alternate_names(&artist_file_name, alternate_names)
+      .await?;
+    self.update_search_records(vec![artist_file_name]).await
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_aliases(&self) -> Result<ArtistAliases> {
+    self.artist_alias_repository.get_aliases().await
+  }
+
+  /**
+   * Aliasing an alias points it at the canonical artist instead
+   */
+  #[instrument(skip(self))]
+  pub async fn put_alias(
+    &self,
+    alias_file_name: FileName,
+    canonical_file_name: FileName,
+  ) -> Result<()> {
+    let canonical_file_name = self.get_aliases().await?.resolve(&canonical_file_name);
+    if canonical_file_name == alias_file_name {
+      bail!(
+        "Artist can't be an alias of itself: {}",
+        alias_file_name.to_string()
+      );
+    }
+    self
+      .artist_alias_repository
+      .put(&alias_file_name, &canonical_file_name)
+      .await
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_alias(&self, alias_file_name: FileName) -> Result<()> {
+    self.artist_alias_repository.delete(&alias_file_name).await
+  }
+
+  /**
+   * Folds the albums of each artist's aliases into the artist
+   */
+  async fn merge_aliases(&self, artists: &mut HashMap<FileName, ArtistReadModel>) -> Result<()> {
+    let aliases = self.get_aliases().await?;
+    if aliases.is_empty() {
+      return Ok(());
+    }
+    let alias_file_names = artists
+      .keys()
+      .flat_map(|file_name| aliases.group(file_name))
+      .filter(|file_name| !artists.contains_key(file_name))
+      .collect::<HashSet<_>>();
+    if alias_file_names.is_empty() {
+      return Ok(());
+    }
+    let mut alias_artists = self
+      .find_many(alias_file_names.into_iter().collect())
+      .await?;
+    for (file_name, artist) in artists.iter_mut() {
+      for alias_file_name in aliases.group(file_name) {
+        if let Some(alias) = alias_artists.remove(&alias_file_name) {
+          artist.merge_alias(alias);
+        }
+      }
+    }
+    Ok(())
+  }
+
   #[instrument(skip_all, fields(count = file_names.len()))]
   pub async fn get_artists_information(
     &self,
     file_names: Vec<FileName>,
   ) -> Result<Vec<ArtistInformation>> {
     let mut artists = self.find_many(file_names.clone()).await?;
+    self.merge_aliases(&mut artists).await?;
     let mut overviews = self.get_overviews_with_artist_map(&artists).await?;
 
     let mut result = Vec::new();
@@ -147,14 +233,42 @@ impl ArtistInteractor {
     &self,
     query: &ArtistSearchQuery,
     pagination: Option<&SearchPagination>,
-  ) -> Result<(Vec<ArtistInformation>, usize)> {
+  ) -> Result<(Vec<ArtistSearchMatch>, usize)> {
     let result = self.artist_search_index.search(query, pagination).await?;
-    let file_names = result
-      .artists
-      .iter()
-      .filter_map(|artist| FileName::try_from(artist.file_name.clone()).ok())
+    let aliases = self.get_aliases().await?;
+    // Aliases are merged into their canonical artist, which takes the place of the first one found
+    let mut matched_names: HashMap<FileName, Option<String>> = HashMap::new();
+    let mut file_names = vec![];
+    for artist in &result.artists {
+      let Ok(file_name) = FileName::try_from(artist.file_name.clone()) else {
+        continue;
+      };
+      let matched_name = query
+        .text
+        .as_ref()
+        .and_then(|text| artist.matched_name(text));
+      let file_name = aliases.resolve(&file_name);
+      match matched_names.get_mut(&file_name) {
+        Some(existing) => {
+          if existing.is_none() {
+            *existing = matched_name;
+          }
+        }
+        None => {
+          matched_names.insert(file_name.clone(), matched_name);
+          file_names.push(file_name);
+        }
+      }
+    }
+    let artists = self
+      .get_artists_information(file_names)
+      .await?
+      .into_iter()
+      .map(|(artist, overview)| ArtistSearchMatch {
+        matched_name: matched_names.get(&artist.file_name).cloned().flatten(),
+        information: (artist, overview),
+      })
       .collect();
-    let artists = self.get_artists_information(file_names).await?;
     Ok((artists, result.total))
   }
 
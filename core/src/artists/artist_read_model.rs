@@ -5,7 +5,7 @@ use crate::{
 use chrono::Datelike;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use unidecode::unidecode;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -29,6 +29,14 @@ pub struct ArtistReadModel {
   pub file_name: FileName,
   pub album_file_names: Vec<FileName>,
   pub credits: Vec<ArtistReadModelCredit>,
+  #[serde(default)]
+  pub alternate_names: Vec<String>,
+  /**
+   * Albums the artist only appears on through track credits, like various artists compilations
+   * and splits. They're kept apart from the artist's own albums.
+   */
+  #[serde(default)]
+  pub appearance_album_file_names: Vec<FileName>,
 }
 
 impl From<ArtistReadModel> for proto::Artist {
@@ -42,6 +50,12 @@ impl From<ArtistReadModel> for proto::Artist {
         .map(|f| f.to_string())
         .collect(),
       credits: artist.credits.into_iter().map(Into::into).collect(),
+      alternate_names: artist.alternate_names,
+      appearance_album_file_names: artist
+        .appearance_album_file_names
+        .iter()
+        .map(|f| f.to_string())
+        .collect(),
     }
   }
 }
@@ -50,6 +64,56 @@ impl ArtistReadModel {
   pub fn ascii_name(&self) -> String {
     unidecode(&self.name)
   }
+
+  /**
+   * Alternate names and romanizations of every name the artist is known by, excluding the
+   * primary name
+   */
+  pub fn name_variants(&self) -> Vec<String> {
+    let mut seen = HashSet::from([self.name.clone()]);
+    std::iter::once(self.ascii_name())
+      .chain(
+        self
+          .alternate_names
+          .iter()
+          .flat_map(|name| [name.clone(), unidecode(name)]),
+      )
+      .map(|name| name.trim().to_string())
+      .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+      .collect()
+  }
+
+  /**
+   * Takes on the albums and credits of another file name of the same artist, keeping its name as
+   * an alternate name
+   */
+  pub fn merge_alias(&mut self, alias: ArtistReadModel) {
+    if alias.name != self.name && !self.alternate_names.contains(&alias.name) {
+      self.alternate_names.push(alias.name);
+    }
+    for name in alias.alternate_names {
+      if name != self.name && !self.alternate_names.contains(&name) {
+        self.alternate_names.push(name);
+      }
+    }
+    for file_name in alias.album_file_names {
+      if !self.album_file_names.contains(&file_name) {
+        self.album_file_names.push(file_name);
+      }
+    }
+    for file_name in alias.appearance_album_file_names {
+      if !self.album_file_names.contains(&file_name)
+        && !self.appearance_album_file_names.contains(&file_name)
+      {
+        self.appearance_album_file_names.push(file_name);
+      }
+    }
+    for credit in alias.credits {
+      if !self.credits.contains(&credit) {
+        self.credits.push(credit);
+      }
+    }
+  }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
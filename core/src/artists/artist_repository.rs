@@ -66,6 +66,64 @@ impl ArtistRepository {
     Ok(album_file_names)
   }
 
+  #[instrument(skip_all, fields(artist_file_names = artist_file_names.len()))]
+  async fn find_appearance_album_file_names(
+    &self,
+    artist_file_names: Vec<FileName>,
+  ) -> Result<HashMap<FileName, Vec<FileName>>> {
+    let artist_file_name_params = artist_file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT DISTINCT
+            albums.file_name,
+            artists.file_name
+          FROM albums
+          JOIN tracks ON albums.id = tracks.album_id
+          JOIN track_artists ON tracks.id = track_artists.track_id
+          JOIN artists ON track_artists.artist_id = artists.id
+          WHERE artists.file_name IN rarray(?)
+          AND NOT EXISTS (
+            SELECT 1
+            FROM album_artists
+            WHERE album_artists.album_id = albums.id
+            AND album_artists.artist_id = artists.id
+          )
+          ",
+        )?;
+        let rows = stmt
+          .query_map([Rc::new(artist_file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<Vec<(String, String)>, _>>();
+        rows.inspect_err(|e| {
+          error!(message = e.to_string(), "Failed to find appearance albums");
+        })
+      })
+      .await
+      .map_err(|e| anyhow!("Failed to find appearance albums {:?}", e))??;
+
+    let mut album_file_names = HashMap::new();
+    for (album_file_name, artist_file_name) in rows {
+      let artist_file_name = FileName::try_from(artist_file_name)?;
+      let album_file_name = FileName::try_from(album_file_name)?;
+      album_file_names
+        .entry(artist_file_name)
+        .or_insert_with(Vec::new)
+        .push(album_file_name);
+    }
+
+    Ok(album_file_names)
+  }
+
   #[instrument(skip_all, fields(artist_file_names = artist_file_names.len()))]
   async fn find_credits(
     &self,
@@ -148,14 +206,97 @@ impl ArtistRepository {
     Ok(credits)
   }
 
+  #[instrument(skip_all, fields(artist_file_names = artist_file_names.len()))]
+  async fn find_alternate_names(
+    &self,
+    artist_file_names: Vec<FileName>,
+  ) -> Result<HashMap<FileName, Vec<String>>> {
+    let artist_file_name_params = artist_file_names
+      .iter()
+      .map(|f| Value::from(f.to_string()))
+      .collect::<Vec<Value>>();
+
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT artist_file_name, name
+          FROM artist_alternate_names
+          WHERE artist_file_name IN rarray(?)
+          ",
+        )?;
+        let rows = stmt
+          .query_map([Rc::new(artist_file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<Vec<(String, String)>, _>>();
+        rows.inspect_err(|e| {
+          error!(message = e.to_string(), "Failed to find alternate names");
+        })
+      })
+      .await
+      .map_err(|e| anyhow!("Failed to find alternate names: {:?}", e))??;
+
+    let mut alternate_names: HashMap<FileName, Vec<String>> = HashMap::new();
+    for (artist_file_name, name) in rows {
+      alternate_names
+        .entry(FileName::try_from(artist_file_name)?)
+        .or_default()
+        .push(name);
+    }
+
+    Ok(alternate_names)
+  }
+
+  #[instrument(skip(self, alternate_names), fields(count = alternate_names.len()))]
+  pub async fn put_alternate_names(
+    &self,
+    artist_file_name: &FileName,
+    alternate_names: Vec<String>,
+  ) -> Result<()> {
+    let artist_file_name = artist_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM artist_alternate_names WHERE artist_file_name = ?",
+          [&artist_file_name],
+        )?;
+        {
+          let mut stmt = tx.prepare(
+            "INSERT OR IGNORE INTO artist_alternate_names (artist_file_name, name) VALUES (?, ?)",
+          )?;
+          for name in alternate_names {
+            stmt.execute([&artist_file_name, &name])?;
+          }
+        }
+        tx.commit()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put alternate names");
+        anyhow!("Failed to put alternate names")
+      })??;
+
+    Ok(())
+  }
+
   #[instrument(skip_all, fields(artist_file_names = artist_file_names.len()))]
   pub async fn find_many(
     &self,
     artist_file_names: Vec<FileName>,
   ) -> Result<HashMap<FileName, ArtistReadModel>> {
-    let (album_file_names, credits) = try_join!(
+    let (album_file_names, appearance_album_file_names, credits, alternate_names) = try_join!(
       self.find_album_file_names(artist_file_names.clone()),
-      self.find_credits(artist_file_names.clone())
+      self.find_appearance_album_file_names(artist_file_names.clone()),
+      self.find_credits(artist_file_names.clone()),
+      self.find_alternate_names(artist_file_names.clone())
     )?;
 
     let artist_file_name_params = artist_file_names
@@ -192,7 +333,12 @@ impl ArtistRepository {
         .get(&file_name)
         .cloned()
         .unwrap_or_default();
+      let appearance_album_file_names = appearance_album_file_names
+        .get(&file_name)
+        .cloned()
+        .unwrap_or_default();
       let credits = credits.get(&file_name).cloned().unwrap_or_default();
+      let alternate_names = alternate_names.get(&file_name).cloned().unwrap_or_default();
       artists.insert(
         file_name.clone(),
         ArtistReadModel {
@@ -200,6 +346,8 @@ impl ArtistRepository {
           file_name,
           album_file_names,
           credits,
+          alternate_names,
+          appearance_album_file_names,
         },
       );
     }
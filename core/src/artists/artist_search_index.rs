@@ -10,8 +10,9 @@ use elasticsearch::Elasticsearch;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use unidecode::unidecode;
 
 #[derive(Debug)]
 pub struct ArtistEmbeddingSimilarirtySearchQuery {
@@ -34,6 +35,8 @@ pub struct ArtistSearchRecord {
   pub creditted_primary_genres: Vec<String>,
   pub secondary_genres: Vec<String>,
   pub creditted_secondary_genres: Vec<String>,
+  #[serde(default)]
+  pub name_variants: Vec<String>,
 }
 
 impl TryFrom<Vec<(String, String)>> for ArtistSearchRecord {
@@ -119,10 +122,52 @@ impl From<ArtistOverview> for ArtistSearchRecord {
         .into_iter()
         .map(|item| item.item)
         .collect(),
+      name_variants: vec![],
     }
   }
 }
 
+fn normalize_name_tokens(text: &str) -> HashSet<String> {
+  unidecode(text)
+    .to_lowercase()
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|token| !token.is_empty())
+    .map(|token| token.to_string())
+    .collect()
+}
+
+impl ArtistSearchRecord {
+  pub fn with_name_variants(mut self, name_variants: Vec<String>) -> Self {
+    self.name_variants = name_variants;
+    self
+  }
+
+  /**
+   * Finds the name, alternate script or romanization that best matches a text query, so clients
+   * can show why an artist matched
+   */
+  pub fn matched_name(&self, text: &str) -> Option<String> {
+    let query_tokens = normalize_name_tokens(text);
+    std::iter::once(&self.name)
+      .chain(self.name_variants.iter())
+      .map(|name| {
+        let overlap = normalize_name_tokens(name)
+          .intersection(&query_tokens)
+          .count();
+        (name, overlap)
+      })
+      .filter(|(_, overlap)| *overlap > 0)
+      .fold(
+        None,
+        |best: Option<(&String, usize)>, (name, overlap)| match best {
+          Some((_, best_overlap)) if best_overlap >= overlap => best,
+          _ => Some((name, overlap)),
+        },
+      )
+      .map(|(name, _)| name.clone())
+  }
+}
+
 #[derive(Default, Builder, Debug)]
 #[builder(setter(into), default)]
 pub struct ArtistSearchQuery {
@@ -171,11 +216,35 @@ impl ArtistSearchQuery {
     });
     if let Some(text) = &self.text {
       query["bool"]["must"].as_array_mut().unwrap().push(json!({
-        "match": {
-          "name": {
-            "query": text,
-            "fuzziness": "AUTO"
-          }
+        "bool": {
+          "should": [
+            {
+              "match": {
+                "name": {
+                  "query": text,
+                  "fuzziness": "AUTO",
+                  "boost": 2
+                }
+              }
+            },
+            {
+              "match": {
+                "name_variants": {
+                  "query": text,
+                  "fuzziness": "AUTO"
+                }
+              }
+            },
+            {
+              "match": {
+                "name_variants": {
+                  "query": unidecode(text),
+                  "fuzziness": "AUTO"
+                }
+              }
+            }
+          ],
+          "minimum_should_match": 1
         }
       }));
     }
@@ -1,17 +1,35 @@
-use super::{artist_interactor::ArtistInteractor, artist_search_index::ArtistSearchQuery};
-use crate::{context::ApplicationContext, files::file_metadata::file_name::FileName, proto};
+use super::{
+  artist_alias::ArtistAlias, artist_interactor::ArtistInteractor,
+  artist_search_index::ArtistSearchQuery,
+};
+use crate::{
+  context::ApplicationContext,
+  embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
+  files::file_metadata::file_name::FileName, proto,
+};
 use async_trait::async_trait;
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 
+impl From<ArtistAlias> for proto::ArtistAlias {
+  fn from(val: ArtistAlias) -> Self {
+    proto::ArtistAlias {
+      alias_file_name: val.alias_file_name.to_string(),
+      canonical_file_name: val.canonical_file_name.to_string(),
+    }
+  }
+}
+
 pub struct ArtistService {
   artist_interactor: Arc<ArtistInteractor>,
+  embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
 }
 
 impl ArtistService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       artist_interactor: Arc::clone(&app_context.artist_interactor),
+      embedding_provider_interactor: Arc::clone(&app_context.embedding_provider_interactor),
     }
   }
 }
@@ -68,9 +86,13 @@ impl proto::ArtistService for ArtistService {
     Ok(Response::new(proto::SearchArtistsReply {
       artists: results
         .into_iter()
-        .map(|(artist, overview)| proto::ArtistSearchResultItem {
-          artist: Some(artist.into()),
-          overview: Some(overview.into()),
+        .map(|result| {
+          let (artist, overview) = result.information;
+          proto::ArtistSearchResultItem {
+            artist: Some(artist.into()),
+            overview: Some(overview.into()),
+            matched_name: result.matched_name,
+          }
         })
         .collect(),
       total: total as u32,
@@ -84,7 +106,10 @@ impl proto::ArtistService for ArtistService {
     let inner = request.into_inner();
     let file_name =
       FileName::try_from(inner.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
-    let embedding_key = inner.embedding_key;
+    let embedding_key = self
+      .embedding_provider_interactor
+      .resolve_embedding_key(inner.embedding_key)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
     let limit = inner.limit.unwrap_or(10) as usize;
     let filters: Option<ArtistSearchQuery> = inner
       .filters
@@ -108,4 +133,49 @@ impl proto::ArtistService for ArtistService {
       .collect::<Vec<_>>();
     Ok(Response::new(proto::FindSimilarArtistsReply { items }))
   }
+
+  async fn get_artist_aliases(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<proto::GetArtistAliasesReply>, Status> {
+    let aliases = self
+      .artist_interactor
+      .get_aliases()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetArtistAliasesReply {
+      aliases: aliases.aliases().into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn put_artist_alias(
+    &self,
+    request: Request<proto::PutArtistAliasRequest>,
+  ) -> Result<Response<()>, Status> {
+    let request = request.into_inner();
+    let alias_file_name = FileName::try_from(request.alias_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let canonical_file_name = FileName::try_from(request.canonical_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .artist_interactor
+      .put_alias(alias_file_name, canonical_file_name)
+      .await
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  async fn delete_artist_alias(
+    &self,
+    request: Request<proto::DeleteArtistAliasRequest>,
+  ) -> Result<Response<()>, Status> {
+    let alias_file_name = FileName::try_from(request.into_inner().alias_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .artist_interactor
+      .delete_alias(alias_file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
 }
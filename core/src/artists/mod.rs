@@ -1,3 +1,5 @@
+pub mod artist_alias;
+pub mod artist_alias_repository;
 pub mod artist_event_subscribers;
 pub mod artist_interactor;
 pub mod artist_read_model;
@@ -0,0 +1,383 @@
+use crate::tenant::tenant_id::TenantId;
+use chrono::{NaiveDateTime, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ulid::Ulid;
+
+const KEY_PREFIX: &str = "lute_";
+const SECRET_LENGTH: usize = 40;
+
+/**
+ * RPCs that only read, which read-only keys may call. Anything not listed needs an admin key, so
+ * a new RPC has to be added here to be readable.
+ */
+const READ_METHODS: [&str; 69] = [
+  "AssessAlbum",
+  "AssessProfileCompatibility",
+  "DefaultQuantileRankAlbumAssessmentSettings",
+  "FilterExistingAlbums",
+  "FindSimilarAlbums",
+  "FindSimilarArtists",
+  "FindSpotifyAlbum",
+  "GetAggregatedAlbumSearchStatuses",
+  "GetAggregatedFailureErrors",
+  "GetAggregatedTags",
+  "GetAlbum",
+  "GetAlbumClusters",
+  "GetAlbumDigests",
+  "GetAlbumDuplicateCandidates",
+  "GetAlbumEmbeddingProjection",
+  "GetAlbumNotes",
+  "GetAlbumSearchIndexRebuildMonitor",
+  "GetAllProfiles",
+  "GetArtist",
+  "GetArtistAliases",
+  "GetArtistIngestion",
+  "GetArtistOverview",
+  "GetCollection",
+  "GetCrawlHistory",
+  "GetEmbeddingCacheStats",
+  "GetEmbeddingKeys",
+  "GetEventKeyMigrationMonitor",
+  "GetEventSubscriberLags",
+  "GetFileContent",
+  "GetFilePageType",
+  "GetFileRetentionStats",
+  "GetGlobalExclusion",
+  "GetInterruptedJobRuns",
+  "GetJobs",
+  "GetKeyValueStoreSize",
+  "GetListLookupProgress",
+  "GetListenCounts",
+  "GetListeningHistory",
+  "GetManyAlbums",
+  "GetMonitor",
+  "GetPendingSpotifyImports",
+  "GetPlaylistTracks",
+  "GetProfile",
+  "GetProfileAnalytics",
+  "GetProfileGoals",
+  "GetProfileSnapshots",
+  "GetProfileSummary",
+  "GetRandomAlbums",
+  "GetReadModelReplayMonitor",
+  "GetRecommendationCuration",
+  "GetSavedTracks",
+  "GetSchedulerMonitor",
+  "GetSchemaUpgradeMonitor",
+  "GetSearchBoostProfiles",
+  "GetSettings",
+  "GetYearInReview",
+  "ListBackups",
+  "ListCollections",
+  "ListRecommendationDigests",
+  "ListYearInReviews",
+  "RecommendAlbums",
+  "RecommendCuratedAlbums",
+  "RecommendTracks",
+  "SearchAlbums",
+  "SearchArtists",
+  "SearchSpotifyTrackIndex",
+  "WatchDashboard",
+  "WatchListLookupResults",
+  "WatchLookup",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ApiKeyScope {
+  ReadOnly,
+  Admin,
+  /**
+   * Read access plus consuming the event streams, for connectors that replicate the corpus
+   */
+  ConnectorReplication,
+}
+
+/**
+ * A key that passed authentication, added to the request's extensions for the layers behind auth
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedKey {
+  pub id: String,
+  pub scope: ApiKeyScope,
+  pub tenant_id: Option<TenantId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RequiredAccess {
+  Public,
+  Read,
+  Replication,
+  Admin,
+}
+
+impl ApiKeyScope {
+  pub fn allows(&self, access: RequiredAccess) -> bool {
+    match self {
+      ApiKeyScope::Admin => true,
+      ApiKeyScope::ConnectorReplication => matches!(
+        access,
+        RequiredAccess::Public | RequiredAccess::Read | RequiredAccess::Replication
+      ),
+      ApiKeyScope::ReadOnly => matches!(access, RequiredAccess::Public | RequiredAccess::Read),
+    }
+  }
+}
+
+fn method_access(method: &str) -> RequiredAccess {
+  if READ_METHODS.contains(&method) {
+    RequiredAccess::Read
+  } else {
+    RequiredAccess::Admin
+  }
+}
+
+fn kebab_to_pascal_case(value: &str) -> String {
+  value
+    .split('-')
+    .map(|part| {
+      let mut chars = part.chars();
+      match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+      }
+    })
+    .collect()
+}
+
+/**
+ * Access needed for a request to the RPC server, from its path. gRPC calls are judged by method
+ * name, and the plain HTTP endpoints by what they expose.
+ */
+pub fn required_access(path: &str) -> RequiredAccess {
+  let mut segments = path.trim_start_matches('/').split('/');
+  let first = segments.next().unwrap_or_default();
+  let second = segments.next().unwrap_or_default();
+  match (first, second) {
+    ("lute.Lute", "HealthCheck")
+    | ("grpc.health.v1.Health", _)
+    | ("healthz", _)
+    | ("covers", _) => RequiredAccess::Public,
+    ("lute.AuthService", _) => RequiredAccess::Admin,
+    ("lute.EventService", "Stream" | "SetCursor" | "DeleteCursor") => RequiredAccess::Replication,
+    ("lute.BootstrapService", _) => RequiredAccess::Replication,
+    ("graphql", _) | ("api", "openapi.json") => RequiredAccess::Read,
+    ("api", _) => method_access(&kebab_to_pascal_case(segments.next().unwrap_or_default())),
+    (service, _) if service.starts_with("grpc.reflection.") => RequiredAccess::Read,
+    (_, method) => method_access(method),
+  }
+}
+
+fn hash_secret(secret: &str) -> String {
+  format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/**
+ * Splits a presented key into its id and secret
+ */
+pub fn parse_api_key(key: &str) -> Option<(&str, &str)> {
+  key.strip_prefix(KEY_PREFIX)?.split_once('_')
+}
+
+/**
+ * A stored key. Only a hash of the secret is kept, the full key is shown once on creation.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+  pub id: String,
+  pub name: String,
+  pub scope: ApiKeyScope,
+  pub secret_hash: String,
+  pub created_at: NaiveDateTime,
+  /**
+   * Tenant the key is bound to, which it can't act for any other than
+   */
+  #[serde(default)]
+  pub tenant_id: Option<TenantId>,
+}
+
+impl ApiKey {
+  /**
+   * A new key along with its full value
+   */
+  pub fn generate(name: String, scope: ApiKeyScope, tenant_id: Option<TenantId>) -> (Self, String) {
+    let id = Ulid::new().to_string().to_lowercase();
+    let secret = rand::thread_rng()
+      .sample_iter(&Alphanumeric)
+      .take(SECRET_LENGTH)
+      .map(char::from)
+      .collect::<String>();
+    let key = format!("{}{}_{}", KEY_PREFIX, id, secret);
+    (
+      Self {
+        id,
+        name,
+        scope,
+        secret_hash: hash_secret(&secret),
+        created_at: Utc::now().naive_utc(),
+        tenant_id,
+      },
+      key,
+    )
+  }
+
+  pub fn verify(&self, secret: &str) -> bool {
+    self.secret_hash == hash_secret(secret)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::BTreeSet;
+
+  #[test]
+  fn test_generated_key_verifies() {
+    let (api_key, key) = ApiKey::generate("connector".to_string(), ApiKeyScope::ReadOnly, None);
+    let (id, secret) = parse_api_key(&key).unwrap();
+    assert_eq!(id, api_key.id);
+    assert!(api_key.verify(secret));
+    assert!(!api_key.verify("wrong"));
+    assert_eq!(parse_api_key("not-a-key"), None);
+  }
+
+  #[test]
+  fn test_required_access() {
+    assert_eq!(
+      required_access("/lute.Lute/HealthCheck"),
+      RequiredAccess::Public
+    );
+    assert_eq!(
+      required_access("/grpc.health.v1.Health/Check"),
+      RequiredAccess::Public
+    );
+    assert_eq!(required_access("/healthz/live"), RequiredAccess::Public);
+    assert_eq!(
+      required_access("/lute.AlbumService/SearchAlbums"),
+      RequiredAccess::Read
+    );
+    assert_eq!(
+      required_access("/lute.CrawlerService/Enqueue"),
+      RequiredAccess::Admin
+    );
+    assert_eq!(
+      required_access("/lute.EventService/Stream"),
+      RequiredAccess::Replication
+    );
+    assert_eq!(
+      required_access("/lute.BootstrapService/Bootstrap"),
+      RequiredAccess::Replication
+    );
+    assert_eq!(
+      required_access("/lute.AuthService/ListApiKeys"),
+      RequiredAccess::Admin
+    );
+    assert_eq!(
+      required_access("/api/albums/get-album"),
+      RequiredAccess::Read
+    );
+    assert_eq!(
+      required_access("/api/lookup/put-list-lookup"),
+      RequiredAccess::Admin
+    );
+    assert_eq!(
+      required_access("/lute.SpotifyService/GetAuthorizationUrl"),
+      RequiredAccess::Admin
+    );
+    assert_eq!(
+      required_access("/lute.RecommendationService/GetRecommendationRationale"),
+      RequiredAccess::Admin
+    );
+    assert_eq!(
+      required_access("/api/albums/search-albums-by-natural-language"),
+      RequiredAccess::Admin
+    );
+    assert!(ApiKeyScope::ConnectorReplication.allows(RequiredAccess::Replication));
+    assert!(!ApiKeyScope::ReadOnly.allows(RequiredAccess::Replication));
+    assert!(!ApiKeyScope::ConnectorReplication.allows(RequiredAccess::Admin));
+  }
+
+  #[test]
+  fn test_read_methods() {
+    let read_methods = include_str!("../../../proto/lute.proto")
+      .lines()
+      .filter_map(|line| line.trim().strip_prefix("rpc ")?.split('(').next())
+      .filter(|method| method_access(method) == RequiredAccess::Read)
+      .collect::<BTreeSet<_>>();
+    assert_eq!(
+      read_methods,
+      BTreeSet::from([
+        "AssessAlbum",
+        "AssessProfileCompatibility",
+        "DefaultQuantileRankAlbumAssessmentSettings",
+        "FilterExistingAlbums",
+        "FindSimilarAlbums",
+        "FindSimilarArtists",
+        "FindSpotifyAlbum",
+        "GetAggregatedAlbumSearchStatuses",
+        "GetAggregatedFailureErrors",
+        "GetAggregatedTags",
+        "GetAlbum",
+        "GetAlbumClusters",
+        "GetAlbumDigests",
+        "GetAlbumDuplicateCandidates",
+        "GetAlbumEmbeddingProjection",
+        "GetAlbumNotes",
+        "GetAlbumSearchIndexRebuildMonitor",
+        "GetAllProfiles",
+        "GetArtist",
+        "GetArtistAliases",
+        "GetArtistIngestion",
+        "GetArtistOverview",
+        "GetCollection",
+        "GetCrawlHistory",
+        "GetEmbeddingCacheStats",
+        "GetEmbeddingKeys",
+        "GetEventKeyMigrationMonitor",
+        "GetEventSubscriberLags",
+        "GetFileContent",
+        "GetFilePageType",
+        "GetFileRetentionStats",
+        "GetGlobalExclusion",
+        "GetInterruptedJobRuns",
+        "GetJobs",
+        "GetKeyValueStoreSize",
+        "GetListLookupProgress",
+        "GetListenCounts",
+        "GetListeningHistory",
+        "GetManyAlbums",
+        "GetMonitor",
+        "GetPendingSpotifyImports",
+        "GetPlaylistTracks",
+        "GetProfile",
+        "GetProfileAnalytics",
+        "GetProfileGoals",
+        "GetProfileSnapshots",
+        "GetProfileSummary",
+        "GetRandomAlbums",
+        "GetReadModelReplayMonitor",
+        "GetRecommendationCuration",
+        "GetSavedTracks",
+        "GetSchedulerMonitor",
+        "GetSchemaUpgradeMonitor",
+        "GetSearchBoostProfiles",
+        "GetSettings",
+        "GetYearInReview",
+        "ListBackups",
+        "ListCollections",
+        "ListRecommendationDigests",
+        "ListYearInReviews",
+        "RecommendAlbums",
+        "RecommendCuratedAlbums",
+        "RecommendTracks",
+        "SearchAlbums",
+        "SearchArtists",
+        "SearchSpotifyTrackIndex",
+        "WatchDashboard",
+        "WatchListLookupResults",
+        "WatchLookup",
+      ])
+    );
+  }
+}
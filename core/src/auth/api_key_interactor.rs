@@ -0,0 +1,111 @@
+use super::api_key::{parse_api_key, ApiKey, ApiKeyScope, AuthenticatedKey};
+use crate::{helpers::key_value_store::KeyValueStore, tenant::tenant_id::TenantId};
+use anyhow::Result;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::info;
+
+const API_KEYS_KEY: &str = "api_keys";
+const ADMIN_KEY_ID: &str = "admin";
+
+pub struct ApiKeyInteractor {
+  kv: Arc<KeyValueStore>,
+  admin_key: Option<String>,
+  /**
+   * Keys are stored together under one entry, so changes to them are made one at a time
+   */
+  write_lock: Mutex<()>,
+}
+
+impl ApiKeyInteractor {
+  pub fn new(kv: Arc<KeyValueStore>, admin_key: Option<String>) -> Self {
+    Self {
+      kv,
+      admin_key,
+      write_lock: Mutex::new(()),
+    }
+  }
+
+  async fn get_keys(&self) -> Result<HashMap<String, ApiKey>> {
+    Ok(
+      self
+        .kv
+        .get::<HashMap<String, ApiKey>>(API_KEYS_KEY)
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  /**
+   * Creates a key, returning it along with its full value, which isn't stored
+   */
+  pub async fn create(
+    &self,
+    name: String,
+    scope: ApiKeyScope,
+    tenant_id: Option<TenantId>,
+  ) -> Result<(ApiKey, String)> {
+    let _lock = self.write_lock.lock().await;
+    let mut keys = self.get_keys().await?;
+    let (api_key, key) = ApiKey::generate(name, scope, tenant_id);
+    keys.insert(api_key.id.clone(), api_key.clone());
+    self.kv.set(API_KEYS_KEY, keys, None).await?;
+    info!(id = api_key.id, name = api_key.name, "API key created");
+    Ok((api_key, key))
+  }
+
+  /**
+   * Revokes a key, returning whether it existed
+   */
+  pub async fn revoke(&self, id: &str) -> Result<bool> {
+    let _lock = self.write_lock.lock().await;
+    let mut keys = self.get_keys().await?;
+    if keys.remove(id).is_none() {
+      return Ok(false);
+    }
+    self.kv.set(API_KEYS_KEY, keys, None).await?;
+    info!(id, "API key revoked");
+    Ok(true)
+  }
+
+  /**
+   * Stored keys, oldest first
+   */
+  pub async fn list(&self) -> Result<Vec<ApiKey>> {
+    let mut keys = self.get_keys().await?.into_values().collect::<Vec<_>>();
+    keys.sort_by_key(|key| key.created_at);
+    Ok(keys)
+  }
+
+  /**
+   * The presented key, if it's valid. The configured admin key is identified as `admin`.
+   */
+  pub async fn authenticate(&self, key: &str) -> Result<Option<AuthenticatedKey>> {
+    if self
+      .admin_key
+      .as_ref()
+      .is_some_and(|admin_key| admin_key == key)
+    {
+      return Ok(Some(AuthenticatedKey {
+        id: ADMIN_KEY_ID.to_string(),
+        scope: ApiKeyScope::Admin,
+        tenant_id: None,
+      }));
+    }
+    let Some((id, secret)) = parse_api_key(key) else {
+      return Ok(None);
+    };
+    Ok(
+      self
+        .get_keys()
+        .await?
+        .remove(id)
+        .filter(|api_key| api_key.verify(secret))
+        .map(|api_key| AuthenticatedKey {
+          id: api_key.id,
+          scope: api_key.scope,
+          tenant_id: api_key.tenant_id,
+        }),
+    )
+  }
+}
@@ -0,0 +1,148 @@
+use super::{
+  api_key::{required_access, AuthenticatedKey, RequiredAccess},
+  api_key_interactor::ApiKeyInteractor,
+};
+use std::sync::Arc;
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, HeaderMap, StatusCode},
+    BoxFuture, Context, Poll, Service,
+  },
+  Status,
+};
+use tower::Layer;
+use tracing::error;
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+pub fn presented_key(headers: &HeaderMap) -> Option<&str> {
+  if let Some(key) = headers
+    .get(API_KEY_HEADER)
+    .and_then(|value| value.to_str().ok())
+  {
+    return Some(key);
+  }
+  headers
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/**
+ * The key the request was authenticated with, none for public paths
+ */
+async fn authorize(
+  api_key_interactor: &ApiKeyInteractor,
+  path: &str,
+  headers: &HeaderMap,
+) -> Result<Option<AuthenticatedKey>, Status> {
+  let access = required_access(path);
+  if access == RequiredAccess::Public {
+    return Ok(None);
+  }
+  let key = presented_key(headers).ok_or_else(|| Status::unauthenticated("API key required"))?;
+  let authenticated_key = api_key_interactor
+    .authenticate(key)
+    .await
+    .map_err(|e| {
+      error!(err = e.to_string(), "Failed to authenticate API key");
+      Status::internal("Failed to authenticate API key")
+    })?
+    .ok_or_else(|| Status::unauthenticated("Invalid API key"))?;
+  if authenticated_key.scope.allows(access) {
+    Ok(Some(authenticated_key))
+  } else {
+    Err(Status::permission_denied(
+      "API key scope doesn't allow this call",
+    ))
+  }
+}
+
+/**
+ * gRPC clients get the status in the usual trailers-only response, other clients a plain HTTP
+ * status
+ */
+fn denied_response(headers: &HeaderMap, status: Status) -> http::Response<BoxBody> {
+  let is_grpc = headers
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|content_type| content_type.starts_with("application/grpc"));
+  if is_grpc {
+    return status.to_http();
+  }
+  let mut response = http::Response::new(tonic::body::empty_body());
+  *response.status_mut() = match status.code() {
+    tonic::Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+    tonic::Code::PermissionDenied => StatusCode::FORBIDDEN,
+    _ => StatusCode::INTERNAL_SERVER_ERROR,
+  };
+  response
+}
+
+/**
+ * Checks the API key of every request to the RPC server against the access its path needs, and
+ * adds the authenticated key to the request's extensions. Does nothing unless auth is enabled.
+ */
+#[derive(Clone)]
+pub struct AuthLayer {
+  api_key_interactor: Option<Arc<ApiKeyInteractor>>,
+}
+
+impl AuthLayer {
+  pub fn new(api_key_interactor: Option<Arc<ApiKeyInteractor>>) -> Self {
+    Self { api_key_interactor }
+  }
+}
+
+impl<S> Layer<S> for AuthLayer {
+  type Service = AuthMiddleware<S>;
+
+  fn layer(&self, inner: S) -> Self::Service {
+    AuthMiddleware {
+      inner,
+      api_key_interactor: self.api_key_interactor.clone(),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct AuthMiddleware<S> {
+  inner: S,
+  api_key_interactor: Option<Arc<ApiKeyInteractor>>,
+}
+
+impl<S, B> Service<http::Request<B>> for AuthMiddleware<S>
+where
+  S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+  S::Future: Send + 'static,
+  B: Send + 'static,
+{
+  type Response = S::Response;
+  type Error = S::Error;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+    // The clone isn't necessarily ready, so the ready service is taken and the clone left behind
+    let clone = self.inner.clone();
+    let mut inner = std::mem::replace(&mut self.inner, clone);
+    let Some(api_key_interactor) = self.api_key_interactor.clone() else {
+      return Box::pin(inner.call(request));
+    };
+    Box::pin(async move {
+      match authorize(&api_key_interactor, request.uri().path(), request.headers()).await {
+        Ok(authenticated_key) => {
+          if let Some(authenticated_key) = authenticated_key {
+            request.extensions_mut().insert(authenticated_key);
+          }
+          inner.call(request).await
+        }
+        Err(status) => Ok(denied_response(request.headers(), status)),
+      }
+    })
+  }
+}
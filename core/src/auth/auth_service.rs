@@ -0,0 +1,106 @@
+use super::api_key::{ApiKey, ApiKeyScope};
+use crate::{context::ApplicationContext, proto, tenant::tenant_id::TenantId};
+use std::sync::Arc;
+use tonic::{async_trait, Request, Response, Status};
+
+impl From<ApiKeyScope> for proto::ApiKeyScope {
+  fn from(val: ApiKeyScope) -> Self {
+    match val {
+      ApiKeyScope::ReadOnly => proto::ApiKeyScope::ApiKeyReadOnly,
+      ApiKeyScope::Admin => proto::ApiKeyScope::ApiKeyAdmin,
+      ApiKeyScope::ConnectorReplication => proto::ApiKeyScope::ApiKeyConnectorReplication,
+    }
+  }
+}
+
+impl From<proto::ApiKeyScope> for ApiKeyScope {
+  fn from(val: proto::ApiKeyScope) -> Self {
+    match val {
+      proto::ApiKeyScope::ApiKeyReadOnly => ApiKeyScope::ReadOnly,
+      proto::ApiKeyScope::ApiKeyAdmin => ApiKeyScope::Admin,
+      proto::ApiKeyScope::ApiKeyConnectorReplication => ApiKeyScope::ConnectorReplication,
+    }
+  }
+}
+
+impl From<ApiKey> for proto::ApiKey {
+  fn from(val: ApiKey) -> Self {
+    proto::ApiKey {
+      id: val.id,
+      name: val.name,
+      scope: proto::ApiKeyScope::from(val.scope) as i32,
+      created_at: val.created_at.to_string(),
+      tenant_id: val.tenant_id.map(|tenant_id| tenant_id.to_string()),
+    }
+  }
+}
+
+pub struct AuthService {
+  app_context: Arc<ApplicationContext>,
+}
+
+impl AuthService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self { app_context }
+  }
+}
+
+#[async_trait]
+impl proto::AuthService for AuthService {
+  async fn create_api_key(
+    &self,
+    request: Request<proto::CreateApiKeyRequest>,
+  ) -> Result<Response<proto::CreateApiKeyReply>, Status> {
+    let request = request.into_inner();
+    if request.name.trim().is_empty() {
+      return Err(Status::invalid_argument("API key name is required"));
+    }
+    let scope = request.scope().into();
+    let tenant_id = request
+      .tenant_id
+      .map(TenantId::try_from)
+      .transpose()
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let (api_key, key) = self
+      .app_context
+      .api_key_interactor
+      .create(request.name, scope, tenant_id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::CreateApiKeyReply {
+      api_key: Some(api_key.into()),
+      key,
+    }))
+  }
+
+  async fn revoke_api_key(
+    &self,
+    request: Request<proto::RevokeApiKeyRequest>,
+  ) -> Result<Response<()>, Status> {
+    let revoked = self
+      .app_context
+      .api_key_interactor
+      .revoke(&request.into_inner().id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    if !revoked {
+      return Err(Status::not_found("API key not found"));
+    }
+    Ok(Response::new(()))
+  }
+
+  async fn list_api_keys(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::ListApiKeysReply>, Status> {
+    let api_keys = self
+      .app_context
+      .api_key_interactor
+      .list()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ListApiKeysReply {
+      api_keys: api_keys.into_iter().map(Into::into).collect(),
+    }))
+  }
+}
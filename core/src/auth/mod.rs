@@ -0,0 +1,4 @@
+pub mod api_key;
+pub mod api_key_interactor;
+pub mod auth_layer;
+pub mod auth_service;
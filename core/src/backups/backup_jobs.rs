@@ -0,0 +1,47 @@
+use super::backup::create_backup;
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use std::sync::Arc;
+
+async fn run_scheduled_backup(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  create_backup(app_context).await?;
+  Ok(())
+}
+
+pub async fn setup_backup_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let Some(interval_hours) = app_context.settings.backup.interval_hours else {
+    return Ok(());
+  };
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CreateBackup)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(run_scheduled_backup))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::CreateBackup)
+        .interval(TimeDelta::try_hours(interval_hours as i64).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,50 @@
+use super::backup::{create_backup, list_backups, BackupArchive};
+use crate::{context::ApplicationContext, proto};
+use std::sync::Arc;
+use tonic::{async_trait, Request, Response, Status};
+
+impl From<BackupArchive> for proto::BackupArchive {
+  fn from(val: BackupArchive) -> Self {
+    proto::BackupArchive {
+      file_name: val.file_name,
+      size_bytes: val.size_bytes,
+      created_at: val.created_at.to_string(),
+    }
+  }
+}
+
+pub struct BackupService {
+  app_context: Arc<ApplicationContext>,
+}
+
+impl BackupService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self { app_context }
+  }
+}
+
+#[async_trait]
+impl proto::BackupService for BackupService {
+  async fn create_backup(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::CreateBackupReply>, Status> {
+    let archive = create_backup(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::CreateBackupReply {
+      archive: Some(archive.into()),
+    }))
+  }
+
+  async fn list_backups(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::ListBackupsReply>, Status> {
+    let archives = list_backups(&self.app_context.settings.backup)
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ListBackupsReply {
+      archives: archives.into_iter().map(Into::into).collect(),
+    }))
+  }
+}
@@ -0,0 +1,3 @@
+pub mod backup;
+pub mod backup_jobs;
+pub mod backup_service;
@@ -0,0 +1,148 @@
+use crate::{files::file_metadata::file_name::FileName, profile::profile::ProfileId};
+use anyhow::{bail, Result};
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+use ulid::Ulid;
+
+/**
+ * A named, ordered list of albums kept by a profile. Unlike RYM lists, collections are local and
+ * never crawled.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+  pub id: String,
+  pub profile_id: ProfileId,
+  pub name: String,
+  pub description: Option<String>,
+  pub album_file_names: Vec<FileName>,
+  pub created_at: NaiveDateTime,
+  pub updated_at: NaiveDateTime,
+}
+
+impl Collection {
+  pub fn new(profile_id: ProfileId, name: String, description: Option<String>) -> Result<Self> {
+    if name.trim().is_empty() {
+      bail!("Collection name must not be empty");
+    }
+    let now = Utc::now().naive_utc();
+    Ok(Self {
+      id: Ulid::new().to_string(),
+      profile_id,
+      name,
+      description,
+      album_file_names: vec![],
+      created_at: now,
+      updated_at: now,
+    })
+  }
+
+  fn touch(&mut self) {
+    self.updated_at = Utc::now().naive_utc();
+  }
+
+  pub fn rename(&mut self, name: String, description: Option<String>) -> Result<()> {
+    if name.trim().is_empty() {
+      bail!("Collection name must not be empty");
+    }
+    self.name = name;
+    self.description = description;
+    self.touch();
+    Ok(())
+  }
+
+  /**
+   * Inserts albums at `position`, or appends them when it's not set. Albums already in the
+   * collection keep their place. Returns the number of albums added.
+   */
+  pub fn add_albums(&mut self, file_names: Vec<FileName>, position: Option<usize>) -> usize {
+    let mut existing = self
+      .album_file_names
+      .iter()
+      .cloned()
+      .collect::<HashSet<_>>();
+    let new_file_names = file_names
+      .into_iter()
+      .filter(|file_name| existing.insert(file_name.clone()))
+      .collect::<Vec<_>>();
+    let count = new_file_names.len();
+    let position = position
+      .unwrap_or(self.album_file_names.len())
+      .min(self.album_file_names.len());
+    self
+      .album_file_names
+      .splice(position..position, new_file_names);
+    self.touch();
+    count
+  }
+
+  pub fn remove_albums(&mut self, file_names: &[FileName]) {
+    self
+      .album_file_names
+      .retain(|file_name| !file_names.contains(file_name));
+    self.touch();
+  }
+
+  /**
+   * Replaces the album order. The new order must contain exactly the albums already in the
+   * collection.
+   */
+  pub fn reorder(&mut self, file_names: Vec<FileName>) -> Result<()> {
+    let current = self.album_file_names.iter().collect::<HashSet<_>>();
+    let next = file_names.iter().collect::<HashSet<_>>();
+    if file_names.len() != self.album_file_names.len() || current != next {
+      bail!("New order must contain exactly the albums in the collection");
+    }
+    self.album_file_names = file_names;
+    self.touch();
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tenant::tenant_id::TenantId;
+
+  fn file_name(name: &str) -> FileName {
+    FileName::try_from(format!("release/album/{}", name)).unwrap()
+  }
+
+  fn collection() -> Collection {
+    let profile_id = ProfileId::scoped(&TenantId::default(), "default".to_string()).unwrap();
+    Collection::new(profile_id, "Favorites".to_string(), None).unwrap()
+  }
+
+  #[test]
+  fn test_add_albums_skips_existing_and_inserts_at_position() {
+    let mut collection = collection();
+    assert_eq!(
+      collection.add_albums(vec![file_name("a/a"), file_name("b/b")], None),
+      2
+    );
+    assert_eq!(
+      collection.add_albums(vec![file_name("b/b"), file_name("c/c")], Some(1)),
+      1
+    );
+    assert_eq!(
+      collection.album_file_names,
+      vec![file_name("a/a"), file_name("c/c"), file_name("b/b")]
+    );
+  }
+
+  #[test]
+  fn test_reorder_requires_same_albums() {
+    let mut collection = collection();
+    collection.add_albums(vec![file_name("a/a"), file_name("b/b")], None);
+    assert!(collection
+      .reorder(vec![file_name("a/a"), file_name("c/c")])
+      .is_err());
+    assert!(collection
+      .reorder(vec![file_name("b/b"), file_name("a/a")])
+      .is_ok());
+    assert_eq!(
+      collection.album_file_names,
+      vec![file_name("b/b"), file_name("a/a")]
+    );
+  }
+}
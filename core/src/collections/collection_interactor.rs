@@ -0,0 +1,218 @@
+use super::{collection::Collection, collection_repository::CollectionRepository};
+use crate::{
+  albums::{album_interactor::AlbumInteractor, album_search_index::AlbumSearchQuery},
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  profile::{profile::ProfileId, profile_interactor::ProfileInteractor},
+  recommendations::spotify_track_search_index::{
+    SpotifyTrackQueryBuilder, SpotifyTrackSearchIndex, SpotifyTrackSearchRecord,
+  },
+  spotify::spotify_client::{SpotifyClient, SpotifyTrackReference},
+  tenant::tenant_id::TenantId,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{collections::HashMap, sync::Arc};
+
+pub struct CollectionInteractor {
+  collection_repository: CollectionRepository,
+  album_interactor: Arc<AlbumInteractor>,
+  profile_interactor: Arc<ProfileInteractor>,
+  spotify_client: Arc<SpotifyClient>,
+  spotify_track_search_index: Arc<dyn SpotifyTrackSearchIndex + Send + Sync>,
+}
+
+impl CollectionInteractor {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      collection_repository: CollectionRepository::new(Arc::clone(&app_context.doc_store)),
+      album_interactor: Arc::clone(&app_context.album_interactor),
+      profile_interactor: Arc::clone(&app_context.profile_interactor),
+      spotify_client: Arc::clone(&app_context.spotify_client),
+      spotify_track_search_index: Arc::clone(&app_context.spotify_track_search_index),
+    }
+  }
+
+  pub async fn create(
+    &self,
+    profile_id: &ProfileId,
+    name: String,
+    description: Option<String>,
+  ) -> Result<Collection> {
+    if self
+      .profile_interactor
+      .find_profile(profile_id)
+      .await?
+      .is_none()
+    {
+      bail!("Profile not found: {}", profile_id.local_id());
+    }
+    let collection = Collection::new(profile_id.clone(), name, description)?;
+    self.collection_repository.put(collection.clone()).await?;
+    Ok(collection)
+  }
+
+  pub async fn find(&self, tenant_id: &TenantId, id: &str) -> Result<Option<Collection>> {
+    Ok(
+      self
+        .collection_repository
+        .find(id)
+        .await?
+        .filter(|collection| collection.profile_id.tenant_id() == *tenant_id),
+    )
+  }
+
+  pub async fn get(&self, tenant_id: &TenantId, id: &str) -> Result<Collection> {
+    self
+      .find(tenant_id, id)
+      .await?
+      .ok_or_else(|| anyhow!("Collection not found: {}", id))
+  }
+
+  pub async fn find_by_profile_id(&self, profile_id: &ProfileId) -> Result<Vec<Collection>> {
+    self
+      .collection_repository
+      .find_by_profile_id(profile_id)
+      .await
+  }
+
+  pub async fn update(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    name: String,
+    description: Option<String>,
+  ) -> Result<Collection> {
+    let mut collection = self.get(tenant_id, id).await?;
+    collection.rename(name, description)?;
+    self.collection_repository.put(collection.clone()).await?;
+    Ok(collection)
+  }
+
+  pub async fn delete(&self, tenant_id: &TenantId, id: &str) -> Result<()> {
+    self.get(tenant_id, id).await?;
+    self.collection_repository.delete(id).await
+  }
+
+  /**
+   * Adds albums that are already in the album repository, so that every album in a collection can
+   * be used as a recommendation seed. Returns the collection and the number of albums added.
+   */
+  pub async fn add_albums(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    file_names: Vec<FileName>,
+    position: Option<usize>,
+  ) -> Result<(Collection, usize)> {
+    let mut collection = self.get(tenant_id, id).await?;
+    self.album_interactor.get_many(file_names.clone()).await?;
+    let added = collection.add_albums(file_names, position);
+    self.collection_repository.put(collection.clone()).await?;
+    Ok((collection, added))
+  }
+
+  /**
+   * Appends every album matching the query, in search order, up to `limit`
+   */
+  pub async fn add_albums_from_search(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    query: &AlbumSearchQuery,
+    limit: Option<usize>,
+  ) -> Result<(Collection, usize)> {
+    let mut collection = self.get(tenant_id, id).await?;
+    let file_names = self
+      .album_interactor
+      .find_matching_file_names(query, limit)
+      .await?;
+    let added = collection.add_albums(file_names, None);
+    self.collection_repository.put(collection.clone()).await?;
+    Ok((collection, added))
+  }
+
+  pub async fn remove_albums(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    file_names: Vec<FileName>,
+  ) -> Result<Collection> {
+    let mut collection = self.get(tenant_id, id).await?;
+    collection.remove_albums(&file_names);
+    self.collection_repository.put(collection.clone()).await?;
+    Ok(collection)
+  }
+
+  pub async fn reorder(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    file_names: Vec<FileName>,
+  ) -> Result<Collection> {
+    let mut collection = self.get(tenant_id, id).await?;
+    collection.reorder(file_names)?;
+    self.collection_repository.put(collection.clone()).await?;
+    Ok(collection)
+  }
+
+  /**
+   * Creates a Spotify playlist of the collection's indexed tracks, album by album in collection
+   * order. Albums without indexed tracks are left out.
+   */
+  pub async fn export_to_spotify(
+    &self,
+    tenant_id: &TenantId,
+    id: &str,
+    name: Option<String>,
+    description: Option<String>,
+    tracks_per_album: Option<usize>,
+  ) -> Result<(String, Vec<SpotifyTrackReference>)> {
+    let collection = self.get(tenant_id, id).await?;
+    if collection.album_file_names.is_empty() {
+      bail!("Collection has no albums to export");
+    }
+    let mut album_tracks: HashMap<FileName, Vec<SpotifyTrackSearchRecord>> = HashMap::new();
+    for track in self
+      .spotify_track_search_index
+      .search(
+        &SpotifyTrackQueryBuilder::default()
+          .include_album_file_names(collection.album_file_names.clone())
+          .build()?,
+        None,
+      )
+      .await?
+      .tracks
+    {
+      album_tracks
+        .entry(track.album_file_name.clone())
+        .or_default()
+        .push(track);
+    }
+    let tracks = collection
+      .album_file_names
+      .iter()
+      .flat_map(|file_name| {
+        let tracks = album_tracks.remove(file_name).unwrap_or_default();
+        let count = tracks_per_album.unwrap_or(tracks.len());
+        tracks.into_iter().take(count)
+      })
+      .map(SpotifyTrackReference::from)
+      .collect::<Vec<_>>();
+    if tracks.is_empty() {
+      bail!("None of the collection's albums have indexed Spotify tracks");
+    }
+    let playlist_id = self
+      .spotify_client
+      .for_tenant(tenant_id)
+      .create_playlist(
+        name.unwrap_or(collection.name),
+        description.or(collection.description),
+        tracks
+          .iter()
+          .map(|track| track.spotify_id.clone())
+          .collect(),
+      )
+      .await?;
+    Ok((playlist_id, tracks))
+  }
+}
@@ -0,0 +1,72 @@
+use super::collection::Collection;
+use crate::{
+  helpers::document_store::{DocumentFilter, DocumentStore},
+  profile::profile::ProfileId,
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct CollectionRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "collection";
+
+impl CollectionRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, collection: Collection) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &collection.id.clone(), collection, None)
+      .await
+  }
+
+  pub async fn find(&self, id: &str) -> Result<Option<Collection>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<Collection>(COLLECTION, id)
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Collections of a profile, oldest first
+   */
+  pub async fn find_by_profile_id(&self, profile_id: &ProfileId) -> Result<Vec<Collection>> {
+    let mut collections = self
+      .doc_store
+      .find_many::<Collection>(
+        COLLECTION,
+        DocumentFilter::new()
+          .condition("profile_id", "=", profile_id.to_string())
+          .build(),
+        None,
+      )
+      .await?
+      .documents
+      .into_iter()
+      .map(|doc| doc.document)
+      .collect::<Vec<_>>();
+    collections.sort_by_key(|collection| collection.created_at);
+    Ok(collections)
+  }
+
+  pub async fn delete(&self, id: &str) -> Result<()> {
+    self.doc_store.delete(COLLECTION, id).await
+  }
+
+  pub async fn delete_by_profile_id(&self, profile_id: &ProfileId) -> Result<()> {
+    let keys = self
+      .find_by_profile_id(profile_id)
+      .await?
+      .into_iter()
+      .map(|collection| collection.id)
+      .collect();
+    self.doc_store.delete_many(COLLECTION, keys).await
+  }
+}
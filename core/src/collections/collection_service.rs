@@ -0,0 +1,243 @@
+use super::{collection::Collection, collection_interactor::CollectionInteractor};
+use crate::{
+  albums::album_search_index::AlbumSearchQuery, context::ApplicationContext,
+  files::file_metadata::file_name::FileName, profile::profile::ProfileId, proto,
+  tenant::tenant_id::request_tenant_id,
+};
+use anyhow::Error;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::error;
+
+impl From<Collection> for proto::Collection {
+  fn from(val: Collection) -> Self {
+    proto::Collection {
+      id: val.id,
+      profile_id: val.profile_id.local_id(),
+      name: val.name,
+      description: val.description,
+      album_file_names: val
+        .album_file_names
+        .into_iter()
+        .map(|file_name| file_name.to_string())
+        .collect(),
+      created_at: val.created_at.to_string(),
+      updated_at: val.updated_at.to_string(),
+    }
+  }
+}
+
+fn parse_file_names(file_names: Vec<String>) -> Result<Vec<FileName>, Status> {
+  file_names
+    .into_iter()
+    .map(FileName::try_from)
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e)))
+}
+
+fn collection_reply(collection: Collection) -> Response<proto::CollectionReply> {
+  Response::new(proto::CollectionReply {
+    collection: Some(collection.into()),
+  })
+}
+
+pub struct CollectionService {
+  collection_interactor: CollectionInteractor,
+}
+
+impl CollectionService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      collection_interactor: CollectionInteractor::new(app_context),
+    }
+  }
+}
+
+#[tonic::async_trait]
+impl proto::CollectionService for CollectionService {
+  async fn create_collection(
+    &self,
+    request: Request<proto::CreateCollectionRequest>,
+  ) -> Result<Response<proto::CollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id)
+      .map_err(|_| Status::invalid_argument("invalid profile id"))?;
+    let collection = self
+      .collection_interactor
+      .create(&profile_id, request.name, request.description)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to create collection");
+        Status::internal(e.to_string())
+      })?;
+    Ok(collection_reply(collection))
+  }
+
+  async fn get_collection(
+    &self,
+    request: Request<proto::GetCollectionRequest>,
+  ) -> Result<Response<proto::CollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let collection = self
+      .collection_interactor
+      .find(&tenant_id, &request.into_inner().id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or_else(|| Status::not_found("collection not found"))?;
+    Ok(collection_reply(collection))
+  }
+
+  async fn list_collections(
+    &self,
+    request: Request<proto::ListCollectionsRequest>,
+  ) -> Result<Response<proto::ListCollectionsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id = ProfileId::scoped(&tenant_id, request.into_inner().profile_id)
+      .map_err(|_| Status::invalid_argument("invalid profile id"))?;
+    let collections = self
+      .collection_interactor
+      .find_by_profile_id(&profile_id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ListCollectionsReply {
+      collections: collections.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn update_collection(
+    &self,
+    request: Request<proto::UpdateCollectionRequest>,
+  ) -> Result<Response<proto::CollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let collection = self
+      .collection_interactor
+      .update(&tenant_id, &request.id, request.name, request.description)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(collection_reply(collection))
+  }
+
+  async fn delete_collection(
+    &self,
+    request: Request<proto::DeleteCollectionRequest>,
+  ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    self
+      .collection_interactor
+      .delete(&tenant_id, &request.into_inner().id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  async fn add_albums_to_collection(
+    &self,
+    request: Request<proto::AddAlbumsToCollectionRequest>,
+  ) -> Result<Response<proto::AddAlbumsToCollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let file_names = parse_file_names(request.file_names)?;
+    let (collection, added) = self
+      .collection_interactor
+      .add_albums(
+        &tenant_id,
+        &request.id,
+        file_names,
+        request.position.map(|position| position as usize),
+      )
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::AddAlbumsToCollectionReply {
+      collection: Some(collection.into()),
+      added: added as u32,
+    }))
+  }
+
+  async fn add_search_results_to_collection(
+    &self,
+    request: Request<proto::AddSearchResultsToCollectionRequest>,
+  ) -> Result<Response<proto::AddAlbumsToCollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let query: AlbumSearchQuery = request
+      .query
+      .ok_or_else(|| Status::invalid_argument("A query is required"))?
+      .try_into()
+      .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e)))?;
+    let (collection, added) = self
+      .collection_interactor
+      .add_albums_from_search(
+        &tenant_id,
+        &request.id,
+        &query,
+        request.limit.map(|limit| limit as usize),
+      )
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::AddAlbumsToCollectionReply {
+      collection: Some(collection.into()),
+      added: added as u32,
+    }))
+  }
+
+  async fn remove_albums_from_collection(
+    &self,
+    request: Request<proto::RemoveAlbumsFromCollectionRequest>,
+  ) -> Result<Response<proto::CollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let file_names = parse_file_names(request.file_names)?;
+    let collection = self
+      .collection_interactor
+      .remove_albums(&tenant_id, &request.id, file_names)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(collection_reply(collection))
+  }
+
+  async fn reorder_collection(
+    &self,
+    request: Request<proto::ReorderCollectionRequest>,
+  ) -> Result<Response<proto::CollectionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let file_names = parse_file_names(request.file_names)?;
+    let collection = self
+      .collection_interactor
+      .reorder(&tenant_id, &request.id, file_names)
+      .await
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    Ok(collection_reply(collection))
+  }
+
+  async fn export_collection_to_spotify(
+    &self,
+    request: Request<proto::ExportCollectionToSpotifyRequest>,
+  ) -> Result<Response<proto::ExportCollectionToSpotifyReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let (playlist_id, tracks) = self
+      .collection_interactor
+      .export_to_spotify(
+        &tenant_id,
+        &request.id,
+        request.name,
+        request.description,
+        request.tracks_per_album.map(|count| count as usize),
+      )
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to export collection to Spotify"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::ExportCollectionToSpotifyReply {
+      playlist_id,
+      tracks: tracks.into_iter().map(Into::into).collect(),
+    }))
+  }
+}
@@ -0,0 +1,4 @@
+pub mod collection;
+pub mod collection_interactor;
+pub mod collection_repository;
+pub mod collection_service;
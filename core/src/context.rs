@@ -1,25 +1,46 @@
 use crate::{
   albums::{
     album_interactor::AlbumInteractor, album_repository::AlbumRepository,
-    album_search_index::AlbumSearchIndex, redis_album_search_index::RedisAlbumSearchIndex,
+    album_search_index_factory::AlbumSearchIndexFactory,
   },
+  apple_music::apple_music_client::AppleMusicClient,
   artists::artist_interactor::ArtistInteractor,
+  auth::api_key_interactor::ApiKeyInteractor,
+  cover_images::cover_image_interactor::CoverImageInteractor,
   crawler::crawler::Crawler,
+  discogs::discogs_interactor::DiscogsInteractor,
   embedding_provider::embedding_provider_interactor::EmbeddingProviderInteractor,
   events::event_publisher::EventPublisher,
   files::file_interactor::FileInteractor,
+  health::subscriber_heartbeats::SubscriberHeartbeats,
   helpers::{document_store::DocumentStore, key_value_store::KeyValueStore},
-  lookup::LookupInteractor,
+  lastfm::{lastfm_client::LastFmClient, lastfm_interactor::LastFmInteractor},
+  listenbrainz::listenbrainz_interactor::ListenBrainzInteractor,
+  lookup::{
+    BandcampLookupInteractor, LookupInteractor, LookupProgressBroadcaster,
+    MusicBrainzLookupInteractor,
+  },
+  music_service::music_service_client::{MusicService, MusicServiceClient},
   profile::profile_interactor::ProfileInteractor,
-  recommendations::spotify_track_search_index::SpotifyTrackSearchIndex,
+  recommendations::{
+    collaborative_filtering::collaborative_filtering_interactor::CollaborativeFilteringInteractor,
+    cross_encoder_reranking::cross_encoder::CrossEncoder,
+    redis_spotify_track_search_index::RedisSpotifyTrackSearchIndex,
+    spotify_track_search_index::SpotifyTrackSearchIndex,
+    sqlite_spotify_track_search_index::SqliteSpotifyTrackSearchIndex,
+  },
   redis::build_redis_connection_pool,
+  runtime_settings::runtime_settings::RuntimeSettings,
   scheduler::scheduler::Scheduler,
-  settings::Settings,
-  spotify::spotify_client::SpotifyClient,
+  settings::{Settings, StorageMode},
+  spotify::{spotify_batch_window::SpotifyBatchWindow, spotify_client::SpotifyClient},
   sqlite::SqliteConnection,
+  tenant::tenant_id::TenantId,
+  tidal::tidal_client::TidalClient,
   tracing::setup_tracing,
+  youtube_music::youtube_music_client::YouTubeMusicClient,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use dotenv::dotenv;
 use elasticsearch::{http::transport::Transport, Elasticsearch};
 use rustis::{bb8::Pool, client::PooledClientManager};
@@ -29,20 +50,36 @@ pub struct ApplicationContext {
   pub settings: Arc<Settings>,
   pub sqlite_connection: Arc<SqliteConnection>,
   pub kv: Arc<KeyValueStore>,
+  pub runtime_settings: Arc<RuntimeSettings>,
+  pub api_key_interactor: Arc<ApiKeyInteractor>,
   pub doc_store: Arc<DocumentStore>,
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
   pub crawler: Arc<Crawler>,
   pub embedding_provider_interactor: Arc<EmbeddingProviderInteractor>,
   pub spotify_client: Arc<SpotifyClient>,
+  pub tidal_client: Option<Arc<TidalClient>>,
+  pub apple_music_client: Option<Arc<AppleMusicClient>>,
+  pub youtube_music_client: Option<Arc<YouTubeMusicClient>>,
   pub artist_interactor: Arc<ArtistInteractor>,
   pub album_interactor: Arc<AlbumInteractor>,
   pub file_interactor: Arc<FileInteractor>,
   pub profile_interactor: Arc<ProfileInteractor>,
+  pub collaborative_filtering_interactor: Arc<CollaborativeFilteringInteractor>,
+  pub lastfm_interactor: Option<Arc<LastFmInteractor>>,
+  pub listenbrainz_interactor: Option<Arc<ListenBrainzInteractor>>,
   pub lookup_interactor: Arc<LookupInteractor>,
+  pub lookup_progress_broadcaster: Arc<LookupProgressBroadcaster>,
+  pub bandcamp_lookup_interactor: Option<Arc<BandcampLookupInteractor>>,
+  pub musicbrainz_lookup_interactor: Option<Arc<MusicBrainzLookupInteractor>>,
+  pub discogs_interactor: Option<Arc<DiscogsInteractor>>,
+  pub cross_encoder: Option<Arc<CrossEncoder>>,
+  pub cover_image_interactor: Option<Arc<CoverImageInteractor>>,
+  pub spotify_batch_window: Option<Arc<SpotifyBatchWindow>>,
   pub event_publisher: Arc<EventPublisher>,
   pub scheduler: Arc<Scheduler>,
-  pub spotify_track_search_index: Arc<SpotifyTrackSearchIndex>,
+  pub spotify_track_search_index: Arc<dyn SpotifyTrackSearchIndex + Send + Sync>,
   pub elasticsearch_client: Arc<Elasticsearch>,
+  pub subscriber_heartbeats: Arc<SubscriberHeartbeats>,
 }
 
 impl ApplicationContext {
@@ -56,15 +93,22 @@ impl ApplicationContext {
     )?));
     let sqlite_connection = Arc::new(SqliteConnection::new(Arc::clone(&settings)).await?);
     let kv = Arc::new(KeyValueStore::new(Arc::clone(&sqlite_connection)));
+    let runtime_settings = Arc::new(RuntimeSettings::new(Arc::clone(&settings), Arc::clone(&kv)));
     let doc_store = Arc::new(DocumentStore::new(Arc::clone(&sqlite_connection)));
-    let redis_connection_pool =
-      Arc::new(build_redis_connection_pool(settings.redis.clone()).await?);
+    let api_key_interactor = Arc::new(ApiKeyInteractor::new(
+      Arc::clone(&kv),
+      settings.auth.admin_key.clone(),
+    ));
+    let redis_connection_pool = Arc::new(
+      build_redis_connection_pool(settings.redis.clone(), settings.storage.mode.clone()).await?,
+    );
     let event_publisher = Arc::new(EventPublisher::new(
       Arc::clone(&settings),
       Arc::clone(&sqlite_connection),
     ));
     let file_interactor = Arc::new(FileInteractor::new(
       Arc::clone(&settings),
+      Arc::clone(&sqlite_connection),
       Arc::clone(&redis_connection_pool),
       Arc::clone(&event_publisher),
     ));
@@ -74,8 +118,10 @@ impl ApplicationContext {
     ));
     let crawler = Arc::new(Crawler::new(
       Arc::clone(&settings),
+      Arc::clone(&runtime_settings),
       Arc::clone(&scheduler),
       Arc::clone(&kv),
+      Arc::clone(&sqlite_connection),
       Arc::clone(&file_interactor),
     )?);
     let album_repository = Arc::new(AlbumRepository::new(Arc::clone(&sqlite_connection)));
@@ -83,21 +129,61 @@ impl ApplicationContext {
       Arc::clone(&settings),
       Arc::clone(&kv),
     ));
-    let album_search_index = Arc::new(RedisAlbumSearchIndex::new(
-      Arc::clone(&redis_connection_pool),
-      Arc::clone(&embedding_provider_interactor),
-    ));
+    let album_search_index = AlbumSearchIndexFactory {
+      settings: Arc::clone(&settings),
+      sqlite_connection: Arc::clone(&sqlite_connection),
+      redis_connection_pool: Arc::clone(&redis_connection_pool),
+      elasticsearch_client: Arc::clone(&elasticsearch_client),
+      embedding_provider_interactor: Arc::clone(&embedding_provider_interactor),
+    }
+    .build()?;
     let spotify_client = Arc::new(SpotifyClient::new(
       &settings.spotify.clone(),
       Arc::clone(&kv),
     ));
-    let spotify_track_search_index = Arc::new(SpotifyTrackSearchIndex::new(Arc::clone(
-      &redis_connection_pool,
-    )));
+    let spotify_batch_window = settings.spotify.batch_window.clone().map(|batch_window| {
+      Arc::new(SpotifyBatchWindow::new(
+        batch_window,
+        Arc::clone(&runtime_settings),
+        Arc::clone(&kv),
+        Arc::clone(&scheduler),
+      ))
+    });
+    let tidal_client = settings
+      .tidal
+      .clone()
+      .map(|tidal_settings| Arc::new(TidalClient::new(tidal_settings, Arc::clone(&kv))));
+    let apple_music_client = settings.apple_music.clone().map(|apple_music_settings| {
+      Arc::new(AppleMusicClient::new(apple_music_settings, Arc::clone(&kv)))
+    });
+    let youtube_music_client = settings
+      .youtube_music
+      .clone()
+      .map(|youtube_music_settings| {
+        Arc::new(YouTubeMusicClient::new(
+          youtube_music_settings,
+          Arc::clone(&kv),
+        ))
+      });
+    let lastfm_client = settings
+      .lastfm
+      .clone()
+      .map(|lastfm_settings| Arc::new(LastFmClient::new(lastfm_settings)));
+    let spotify_track_search_index: Arc<dyn SpotifyTrackSearchIndex + Send + Sync> =
+      match settings.storage.mode {
+        StorageMode::Redis => Arc::new(RedisSpotifyTrackSearchIndex::new(Arc::clone(
+          &redis_connection_pool,
+        ))),
+        StorageMode::Sqlite => Arc::new(SqliteSpotifyTrackSearchIndex::new(Arc::clone(
+          &sqlite_connection,
+        ))),
+      };
     let album_interactor = Arc::new(AlbumInteractor::new(
       Arc::clone(&album_repository),
-      Arc::clone(&album_search_index) as Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
+      Arc::clone(&album_search_index),
       Arc::clone(&event_publisher),
+      Arc::clone(&doc_store),
+      Arc::clone(&sqlite_connection),
     ));
     let artist_interactor = Arc::new(ArtistInteractor::new(
       Arc::clone(&sqlite_connection),
@@ -110,6 +196,8 @@ impl ApplicationContext {
       Arc::clone(&event_publisher),
       Arc::clone(&kv),
       Arc::clone(&crawler),
+      Arc::clone(&file_interactor),
+      Arc::clone(&scheduler),
     ));
     let profile_interactor = Arc::new(ProfileInteractor::new(
       Arc::clone(&redis_connection_pool),
@@ -117,17 +205,77 @@ impl ApplicationContext {
       Arc::clone(&album_interactor),
       Arc::clone(&lookup_interactor),
       Arc::clone(&spotify_client),
+      lastfm_client.clone(),
       Arc::clone(&doc_store),
+      Arc::clone(&sqlite_connection),
+      &settings.storage.mode,
     ));
+    let collaborative_filtering_interactor = Arc::new(CollaborativeFilteringInteractor::new(
+      Arc::clone(&album_interactor),
+      Arc::clone(&profile_interactor),
+    ));
+    let lastfm_interactor = lastfm_client.map(|lastfm_client| {
+      Arc::new(LastFmInteractor::new(
+        lastfm_client,
+        Arc::clone(&kv),
+        Arc::clone(&profile_interactor),
+      ))
+    });
+    let listenbrainz_interactor = settings.listenbrainz.clone().map(|listenbrainz_settings| {
+      Arc::new(ListenBrainzInteractor::new(
+        listenbrainz_settings,
+        Arc::clone(&kv),
+        Arc::clone(&profile_interactor),
+      ))
+    });
+    let musicbrainz_lookup_interactor = settings.musicbrainz.clone().map(|musicbrainz_settings| {
+      Arc::new(MusicBrainzLookupInteractor::new(
+        musicbrainz_settings,
+        Arc::clone(&album_interactor),
+        Arc::clone(&scheduler),
+      ))
+    });
+    let bandcamp_lookup_interactor = settings.lookup.resolve_bandcamp_links.then(|| {
+      Arc::new(BandcampLookupInteractor::new(
+        Arc::clone(&crawler),
+        Arc::clone(&album_interactor),
+      ))
+    });
+    let discogs_interactor = settings.discogs.clone().map(|discogs_settings| {
+      Arc::new(DiscogsInteractor::new(
+        discogs_settings,
+        Arc::clone(&album_interactor),
+        Arc::clone(&scheduler),
+      ))
+    });
+    let cross_encoder = settings
+      .cross_encoder
+      .as_ref()
+      .map(|cross_encoder_settings| CrossEncoder::new(cross_encoder_settings).map(Arc::new))
+      .transpose()?;
+    let cover_image_interactor = if settings.file.cache_cover_images {
+      Some(Arc::new(CoverImageInteractor::new(
+        &settings.file.content_store,
+        Arc::clone(&album_interactor),
+        Arc::clone(&scheduler),
+      )?))
+    } else {
+      None
+    };
 
     Ok(Arc::new(ApplicationContext {
       settings,
       sqlite_connection,
       kv,
+      runtime_settings,
+      api_key_interactor,
       doc_store,
       redis_connection_pool,
       crawler,
       spotify_client,
+      tidal_client,
+      apple_music_client,
+      youtube_music_client,
       embedding_provider_interactor,
       file_interactor,
       event_publisher,
@@ -136,8 +284,56 @@ impl ApplicationContext {
       artist_interactor,
       album_interactor,
       profile_interactor,
+      collaborative_filtering_interactor,
+      lastfm_interactor,
+      listenbrainz_interactor,
       lookup_interactor,
+      lookup_progress_broadcaster: Arc::new(LookupProgressBroadcaster::new()),
+      bandcamp_lookup_interactor,
+      musicbrainz_lookup_interactor,
+      discogs_interactor,
+      cross_encoder,
+      cover_image_interactor,
+      spotify_batch_window,
       elasticsearch_client,
+      subscriber_heartbeats: Arc::new(SubscriberHeartbeats::new()),
     }))
   }
+
+  /**
+   * Spotify is authorized per tenant, the other services are connected once for the instance and
+   * belong to the default tenant
+   */
+  pub fn music_service_client(
+    &self,
+    service: MusicService,
+    tenant_id: &TenantId,
+  ) -> Result<Arc<dyn MusicServiceClient>> {
+    if service != MusicService::Spotify && !tenant_id.is_default() {
+      return Err(anyhow!(
+        "{:?} is only connected for the default tenant",
+        service
+      ));
+    }
+    match service {
+      MusicService::Spotify => {
+        Ok(Arc::new(self.spotify_client.for_tenant(tenant_id)) as Arc<dyn MusicServiceClient>)
+      }
+      MusicService::Tidal => self
+        .tidal_client
+        .as_ref()
+        .map(|client| Arc::clone(client) as Arc<dyn MusicServiceClient>)
+        .ok_or_else(|| anyhow!("Tidal is not configured")),
+      MusicService::AppleMusic => self
+        .apple_music_client
+        .as_ref()
+        .map(|client| Arc::clone(client) as Arc<dyn MusicServiceClient>)
+        .ok_or_else(|| anyhow!("Apple Music is not configured")),
+      MusicService::YouTubeMusic => self
+        .youtube_music_client
+        .as_ref()
+        .map(|client| Arc::clone(client) as Arc<dyn MusicServiceClient>)
+        .ok_or_else(|| anyhow!("YouTube Music is not configured")),
+    }
+  }
 }
@@ -0,0 +1,131 @@
+use crate::files::file_metadata::file_name::FileName;
+use anyhow::Result;
+use image::{DynamicImage, GenericImageView, ImageFormat, ImageReader, Limits};
+use std::io::Cursor;
+
+pub const COVER_IMAGE_THUMBNAIL_SIZES: [u32; 3] = [150, 300, 600];
+const COVER_IMAGE_KEY_PREFIX: &str = "covers/";
+const THUMBNAIL_EXTENSION: &str = ".jpg";
+pub const MAX_COVER_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+const MAX_COVER_IMAGE_DIMENSION: u32 = 8000;
+const MAX_COVER_IMAGE_ALLOC: u64 = 512 * 1024 * 1024;
+
+pub fn cover_image_original_key(file_name: &FileName) -> String {
+  format!(
+    "{}{}/original",
+    COVER_IMAGE_KEY_PREFIX,
+    file_name.to_string()
+  )
+}
+
+pub fn cover_image_thumbnail_key(file_name: &FileName, size: u32) -> String {
+  format!(
+    "{}{}/{}{}",
+    COVER_IMAGE_KEY_PREFIX,
+    file_name.to_string(),
+    size,
+    THUMBNAIL_EXTENSION
+  )
+}
+
+/**
+ * Thumbnails are served from the RPC server at the path of their content store key
+ */
+pub fn cover_image_thumbnail_url(file_name: &FileName, size: u32) -> String {
+  format!("/{}", cover_image_thumbnail_key(file_name, size))
+}
+
+/**
+ * Only keys of known thumbnail sizes are accepted, so requests can't reach anything else in the
+ * content store
+ */
+pub fn parse_cover_image_thumbnail_key(key: &str) -> Option<(FileName, u32)> {
+  let (file_name, size) = key
+    .strip_prefix(COVER_IMAGE_KEY_PREFIX)?
+    .strip_suffix(THUMBNAIL_EXTENSION)?
+    .rsplit_once('/')?;
+  let size = size.parse::<u32>().ok()?;
+  if !COVER_IMAGE_THUMBNAIL_SIZES.contains(&size) {
+    return None;
+  }
+  Some((FileName::try_from(file_name).ok()?, size))
+}
+
+/**
+ * Covers come from remote hosts, so decoding is capped to keep a small file from expanding into
+ * a huge image
+ */
+fn decode_cover_image(content: &[u8]) -> Result<DynamicImage> {
+  let mut limits = Limits::default();
+  limits.max_image_width = Some(MAX_COVER_IMAGE_DIMENSION);
+  limits.max_image_height = Some(MAX_COVER_IMAGE_DIMENSION);
+  limits.max_alloc = Some(MAX_COVER_IMAGE_ALLOC);
+  let mut reader = ImageReader::new(Cursor::new(content)).with_guessed_format()?;
+  reader.limits(limits);
+  Ok(reader.decode()?)
+}
+
+/**
+ * JPEG thumbnails fitting each size, images are never scaled up
+ */
+pub fn generate_thumbnails(content: &[u8]) -> Result<Vec<(u32, Vec<u8>)>> {
+  let image = decode_cover_image(content)?;
+  COVER_IMAGE_THUMBNAIL_SIZES
+    .iter()
+    .map(|size| {
+      let (width, height) = image.dimensions();
+      let thumbnail = if width <= *size && height <= *size {
+        image.to_rgb8()
+      } else {
+        image.thumbnail(*size, *size).to_rgb8()
+      };
+      let mut buffer = Cursor::new(Vec::new());
+      DynamicImage::ImageRgb8(thumbnail).write_to(&mut buffer, ImageFormat::Jpeg)?;
+      Ok((*size, buffer.into_inner()))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::RgbImage;
+
+  #[test]
+  fn test_generate_thumbnails() -> Result<()> {
+    let mut content = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(RgbImage::new(400, 200)).write_to(&mut content, ImageFormat::Png)?;
+    let dimensions = generate_thumbnails(content.get_ref())?
+      .into_iter()
+      .map(|(size, thumbnail)| Ok((size, image::load_from_memory(&thumbnail)?.dimensions())))
+      .collect::<Result<Vec<_>>>()?;
+    assert_eq!(
+      dimensions,
+      vec![(150, (150, 75)), (300, (300, 150)), (600, (400, 200))]
+    );
+
+    let mut content = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(RgbImage::new(MAX_COVER_IMAGE_DIMENSION + 1, 1))
+      .write_to(&mut content, ImageFormat::Png)?;
+    assert!(generate_thumbnails(content.get_ref()).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_cover_image_thumbnail_key() -> Result<()> {
+    let file_name = FileName::try_from("release/album/bjork/vulnicura")?;
+    assert_eq!(
+      parse_cover_image_thumbnail_key(&cover_image_thumbnail_key(&file_name, 300)),
+      Some((file_name.clone(), 300))
+    );
+    assert_eq!(
+      parse_cover_image_thumbnail_key("covers/release/album/bjork/vulnicura/301.jpg"),
+      None
+    );
+    assert_eq!(
+      parse_cover_image_thumbnail_key(&cover_image_original_key(&file_name)),
+      None
+    );
+    Ok(())
+  }
+}
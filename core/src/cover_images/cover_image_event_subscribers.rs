@@ -0,0 +1,64 @@
+use crate::{
+  context::ApplicationContext,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+      GroupingStrategy,
+    },
+  },
+  group_event_handler,
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+async fn enqueue_cover_image_caching(
+  event_data: Vec<EventData>,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  let Some(cover_image_interactor) = app_context.cover_image_interactor.as_ref() else {
+    return Ok(());
+  };
+  let file_names = event_data
+    .into_iter()
+    .filter_map(|event_data| match event_data.payload.event {
+      Event::AlbumSaved { file_name } => Some(file_name),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  if file_names.is_empty() {
+    return Ok(());
+  }
+
+  let uncached = app_context
+    .album_interactor
+    .find_many(file_names)
+    .await?
+    .into_values()
+    .filter(|album| {
+      album.cover_image_url.is_some() && album.cached_cover_image_url != album.cover_image_url
+    })
+    .map(|album| album.file_name)
+    .collect::<Vec<_>>();
+  if !uncached.is_empty() {
+    cover_image_interactor.enqueue_many(uncached).await?;
+  }
+  Ok(())
+}
+
+pub fn build_cover_image_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  if app_context.cover_image_interactor.is_none() {
+    return Ok(vec![]);
+  }
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("enqueue_cover_image_caching")
+    .topic(Topic::Album)
+    .batch_size(250)
+    .app_context(app_context)
+    .grouping_strategy(GroupingStrategy::All)
+    .handler(group_event_handler!(enqueue_cover_image_caching))
+    .build()?])
+}
@@ -0,0 +1,92 @@
+use crate::context::ApplicationContext;
+use std::{convert::Infallible, sync::Arc};
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, Method, StatusCode},
+    Body, BoxFuture, Context, Poll, Service,
+  },
+  server::NamedService,
+  Status,
+};
+use tracing::error;
+
+/**
+ * Serves cover thumbnails over plain HTTP at `/covers/...`. Tonic routes requests by their first
+ * path segment, so this sits alongside the gRPC services on the RPC server.
+ */
+#[derive(Clone)]
+pub struct CoverImageHttpService {
+  app_context: Arc<ApplicationContext>,
+}
+
+impl CoverImageHttpService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self { app_context }
+  }
+}
+
+impl NamedService for CoverImageHttpService {
+  const NAME: &'static str = "covers";
+}
+
+fn response(status: StatusCode, content: Vec<u8>) -> http::Response<BoxBody> {
+  let body = tonic::transport::Body::from(content)
+    .map_err(|e| Status::internal(e.to_string()))
+    .boxed_unsync();
+  let mut response = http::Response::new(body);
+  *response.status_mut() = status;
+  response
+}
+
+async fn get_thumbnail(app_context: Arc<ApplicationContext>, key: &str) -> http::Response<BoxBody> {
+  let Some(cover_image_interactor) = app_context.cover_image_interactor.as_ref() else {
+    return response(StatusCode::NOT_FOUND, vec![]);
+  };
+  match cover_image_interactor.get_thumbnail(key).await {
+    Ok(Some(content)) => {
+      let mut response = response(StatusCode::OK, content);
+      let headers = response.headers_mut();
+      headers.insert(header::CONTENT_TYPE, "image/jpeg".parse().unwrap());
+      // A new cover overwrites the thumbnails under the same keys, so they're only cached for a day
+      headers.insert(
+        header::CACHE_CONTROL,
+        "public, max-age=86400".parse().unwrap(),
+      );
+      response
+    }
+    Ok(None) => response(StatusCode::NOT_FOUND, vec![]),
+    Err(e) => {
+      error!(
+        err = e.to_string(),
+        key, "Failed to get cover image thumbnail"
+      );
+      response(StatusCode::INTERNAL_SERVER_ERROR, vec![])
+    }
+  }
+}
+
+impl<B> Service<http::Request<B>> for CoverImageHttpService
+where
+  B: Body + Send + 'static,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    let app_context = Arc::clone(&self.app_context);
+    let method = request.method().clone();
+    let key = request.uri().path().trim_start_matches('/').to_string();
+    Box::pin(async move {
+      if method != Method::GET {
+        return Ok(response(StatusCode::METHOD_NOT_ALLOWED, vec![]));
+      }
+      Ok(get_thumbnail(app_context, &key).await)
+    })
+  }
+}
@@ -0,0 +1,154 @@
+use super::cover_image::{
+  cover_image_original_key, cover_image_thumbnail_key, generate_thumbnails,
+  parse_cover_image_thumbnail_key, MAX_COVER_IMAGE_BYTES,
+};
+use crate::{
+  albums::album_interactor::AlbumInteractor,
+  files::{file_content_store::FileContentStore, file_metadata::file_name::FileName},
+  helpers::priority::Priority,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobParametersBuilder, Scheduler},
+  },
+  settings::ContentStoreSettings,
+};
+use anyhow::{anyhow, bail, Result};
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{header::CONTENT_TYPE, Client};
+use std::{sync::Arc, time::Duration};
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+lazy_static! {
+  // Cover hosts rate limit hotlinking, so downloads are kept slow
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(1u32)));
+}
+
+pub struct CoverImageInteractor {
+  client: Client,
+  content_store: FileContentStore,
+  album_interactor: Arc<AlbumInteractor>,
+  scheduler: Arc<Scheduler>,
+}
+
+impl CoverImageInteractor {
+  pub fn new(
+    content_store_settings: &ContentStoreSettings,
+    album_interactor: Arc<AlbumInteractor>,
+    scheduler: Arc<Scheduler>,
+  ) -> Result<Self> {
+    Ok(Self {
+      client: Client::new(),
+      content_store: FileContentStore::new(content_store_settings)?,
+      album_interactor,
+      scheduler,
+    })
+  }
+
+  /**
+   * The body is read in chunks so an oversized cover is dropped before it's held in memory
+   */
+  async fn download(&self, url: &str) -> Result<(Vec<u8>, String)> {
+    let mut response = self.client.get(url).send().await?.error_for_status()?;
+    if response
+      .content_length()
+      .is_some_and(|length| length > MAX_COVER_IMAGE_BYTES as u64)
+    {
+      bail!("Cover image is larger than {} bytes", MAX_COVER_IMAGE_BYTES)
+    }
+    let content_type = response
+      .headers()
+      .get(CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .unwrap_or("application/octet-stream")
+      .to_string();
+    let mut content = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+      if content.len() + chunk.len() > MAX_COVER_IMAGE_BYTES {
+        bail!("Cover image is larger than {} bytes", MAX_COVER_IMAGE_BYTES)
+      }
+      content.extend_from_slice(&chunk);
+    }
+    Ok((content, content_type))
+  }
+
+  /**
+   * Downloads the album's cover into the content store along with its thumbnails. Covers that
+   * are already cached are skipped.
+   */
+  #[instrument(skip(self), name = "CoverImageInteractor::cache")]
+  pub async fn cache(&self, file_name: &FileName) -> Result<()> {
+    let mut album = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .ok_or_else(|| anyhow!("Album not found"))?;
+    let Some(cover_image_url) = album.cover_image_url.clone() else {
+      return Ok(());
+    };
+    if album.cached_cover_image_url.as_ref() == Some(&cover_image_url) {
+      return Ok(());
+    }
+
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(500)))
+      .await;
+    let (content, content_type) = self.download(&cover_image_url).await?;
+    self
+      .content_store
+      .put_object(
+        &cover_image_original_key(file_name),
+        &content,
+        &content_type,
+      )
+      .await?;
+
+    let thumbnails = spawn_blocking(move || generate_thumbnails(&content)).await??;
+    for (size, thumbnail) in thumbnails {
+      self
+        .content_store
+        .put_object(
+          &cover_image_thumbnail_key(file_name, size),
+          &thumbnail,
+          "image/jpeg",
+        )
+        .await?;
+    }
+
+    album.cached_cover_image_url = Some(cover_image_url);
+    self.album_interactor.put(album).await
+  }
+
+  pub async fn get_thumbnail(&self, key: &str) -> Result<Option<Vec<u8>>> {
+    if parse_cover_image_thumbnail_key(key).is_none() {
+      return Ok(None);
+    }
+    self.content_store.get_object(key).await
+  }
+
+  pub async fn enqueue_many(&self, file_names: Vec<FileName>) -> Result<()> {
+    self
+      .scheduler
+      .put_many(
+        file_names
+          .into_iter()
+          .map(|file_name| {
+            Ok(
+              JobParametersBuilder::default()
+                .id(format!("cache_cover_image:{}", file_name.to_string()))
+                .name(JobName::CacheCoverImage)
+                .payload(serde_json::to_vec(&file_name)?)
+                .priority(Priority::Low)
+                .overwrite_existing(false)
+                .build()?,
+            )
+          })
+          .collect::<Result<Vec<_>>>()?,
+      )
+      .await?;
+    Ok(())
+  }
+}
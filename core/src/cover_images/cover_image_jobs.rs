@@ -0,0 +1,44 @@
+use crate::{
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn cache_cover_image(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let file_name = job.payload::<FileName>()?;
+  app_context
+    .cover_image_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("Cover image caching is not enabled"))?
+    .cache(&file_name)
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to cache cover image"))
+}
+
+pub async fn setup_cover_image_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if app_context.cover_image_interactor.is_none() {
+    info!("Cover image caching is not enabled, skipping cache jobs");
+    return Ok(());
+  }
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CacheCoverImage)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(cache_cover_image))
+        .build()?,
+    )
+    .await;
+
+  Ok(())
+}
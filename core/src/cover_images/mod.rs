@@ -0,0 +1,5 @@
+pub mod cover_image;
+pub mod cover_image_event_subscribers;
+pub mod cover_image_http_service;
+pub mod cover_image_interactor;
+pub mod cover_image_jobs;
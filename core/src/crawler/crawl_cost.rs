@@ -0,0 +1,79 @@
+use crate::{
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+  proto,
+};
+
+/**
+ * Requests an operation would make, reported instead of enqueueing them on a dry run
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrawlCostReport {
+  pub list_segments: u32,
+  pub albums: u32,
+  pub artists: u32,
+  pub charts: u32,
+  pub searches: u32,
+  /**
+   * Pages that haven't been crawled yet, like list segments whose albums aren't known, will lead
+   * to more requests than counted
+   */
+  pub incomplete: bool,
+  pub remaining_window_requests: u32,
+}
+
+impl CrawlCostReport {
+  pub fn from_file_names<'a>(file_names: impl IntoIterator<Item = &'a FileName>) -> Self {
+    let mut report = Self::default();
+    for file_name in file_names {
+      match file_name.page_type() {
+        PageType::ListSegment => report.list_segments += 1,
+        PageType::Album => report.albums += 1,
+        PageType::Artist => report.artists += 1,
+        PageType::Chart | PageType::GenreTree => report.charts += 1,
+        PageType::AlbumSearchResult | PageType::BandcampSearchResult => report.searches += 1,
+      }
+    }
+    report
+  }
+
+  pub fn total(&self) -> u32 {
+    self.list_segments + self.albums + self.artists + self.charts + self.searches
+  }
+}
+
+impl From<CrawlCostReport> for proto::CrawlCostReport {
+  fn from(val: CrawlCostReport) -> Self {
+    proto::CrawlCostReport {
+      total: val.total(),
+      list_segments: val.list_segments,
+      albums: val.albums,
+      artists: val.artists,
+      charts: val.charts,
+      searches: val.searches,
+      incomplete: val.incomplete,
+      remaining_window_requests: val.remaining_window_requests,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_from_file_names() -> Result<()> {
+    let file_names = vec![
+      FileName::try_from("release/album/bjork/vulnicura")?,
+      FileName::try_from("release/album/fka-twigs/lp1")?,
+      FileName::try_from("list/Seab/theneedledrops-top-200-albums-of-the-2010s/1")?,
+      FileName::try_from("search?searchterm=lp1&searchtype=l")?,
+    ];
+    let report = CrawlCostReport::from_file_names(&file_names);
+    assert_eq!(report.albums, 2);
+    assert_eq!(report.list_segments, 1);
+    assert_eq!(report.searches, 1);
+    assert_eq!(report.total(), 4);
+    Ok(())
+  }
+}
@@ -0,0 +1,117 @@
+use crate::files::file_metadata::file_name::FileName;
+use chrono::{NaiveDateTime, TimeDelta};
+use strum::EnumString;
+
+/**
+ * Failures back off from an hour, doubling with each consecutive failure up to a week
+ */
+const BASE_RECRAWL_BACKOFF_HOURS: i64 = 1;
+const MAX_RECRAWL_BACKOFF_HOURS: i64 = 24 * 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum CrawlOutcome {
+  Succeeded,
+  Failed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlAttempt {
+  pub file_name: FileName,
+  pub crawled_at: NaiveDateTime,
+  pub outcome: CrawlOutcome,
+  /**
+   * Missing when the request never got a response
+   */
+  pub status_code: Option<u16>,
+  pub duration_ms: u32,
+  /**
+   * Correlation id of the crawl job that made the attempt
+   */
+  pub initiator: Option<String>,
+}
+
+/**
+ * Rate limiting, server errors and network failures are worth retrying, other client errors such
+ * as a missing page won't change on retry
+ */
+pub fn is_retryable_status(status_code: Option<u16>) -> bool {
+  match status_code {
+    None => true,
+    Some(status_code) => status_code == 429 || status_code >= 500,
+  }
+}
+
+/**
+ * When the recrawl planner may next enqueue a file, given its attempts newest first. None when
+ * the latest attempt succeeded or there are none.
+ */
+pub fn recrawl_blocked_until(attempts: &[CrawlAttempt]) -> Option<NaiveDateTime> {
+  let latest = attempts.first()?;
+  let consecutive_failures = attempts
+    .iter()
+    .take_while(|attempt| attempt.outcome == CrawlOutcome::Failed)
+    .count() as u32;
+  if consecutive_failures == 0 {
+    return None;
+  }
+  let backoff_hours = if is_retryable_status(latest.status_code) {
+    BASE_RECRAWL_BACKOFF_HOURS
+      .saturating_mul(2i64.saturating_pow(consecutive_failures - 1))
+      .min(MAX_RECRAWL_BACKOFF_HOURS)
+  } else {
+    MAX_RECRAWL_BACKOFF_HOURS
+  };
+  TimeDelta::try_hours(backoff_hours).map(|backoff| latest.crawled_at + backoff)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+  use chrono::NaiveDate;
+
+  fn attempt(hour: u32, outcome: CrawlOutcome, status_code: Option<u16>) -> Result<CrawlAttempt> {
+    Ok(CrawlAttempt {
+      file_name: FileName::try_from("release/album/bjork/vulnicura")?,
+      crawled_at: NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap(),
+      outcome,
+      status_code,
+      duration_ms: 100,
+      initiator: None,
+    })
+  }
+
+  #[test]
+  fn test_recrawl_blocked_until() -> Result<()> {
+    assert_eq!(recrawl_blocked_until(&[]), None);
+    assert_eq!(
+      recrawl_blocked_until(&[
+        attempt(3, CrawlOutcome::Succeeded, Some(200))?,
+        attempt(2, CrawlOutcome::Failed, Some(503))?,
+      ]),
+      None
+    );
+
+    let attempts = vec![
+      attempt(3, CrawlOutcome::Failed, Some(503))?,
+      attempt(2, CrawlOutcome::Failed, None)?,
+      attempt(1, CrawlOutcome::Failed, Some(429))?,
+      attempt(0, CrawlOutcome::Succeeded, Some(200))?,
+    ];
+    assert_eq!(
+      recrawl_blocked_until(&attempts),
+      Some(attempts[0].crawled_at + TimeDelta::try_hours(4).unwrap())
+    );
+
+    let attempts = vec![attempt(3, CrawlOutcome::Failed, Some(404))?];
+    assert_eq!(
+      recrawl_blocked_until(&attempts),
+      Some(attempts[0].crawled_at + TimeDelta::try_hours(MAX_RECRAWL_BACKOFF_HOURS).unwrap())
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,167 @@
+use super::crawl_history::{CrawlAttempt, CrawlOutcome};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use rusqlite::params;
+use std::{str::FromStr, sync::Arc};
+use tracing::{error, instrument};
+
+pub struct CrawlHistoryRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for CrawlAttempt {
+  type Error = rusqlite::Error;
+
+  fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+    Ok(CrawlAttempt {
+      file_name: FileName::try_from(row.get::<_, String>(0)?).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+      })?,
+      crawled_at: row.get(1)?,
+      outcome: CrawlOutcome::from_str(row.get::<_, String>(2)?.as_str()).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+      })?,
+      status_code: row.get(3)?,
+      duration_ms: row.get(4)?,
+      initiator: row.get(5)?,
+    })
+  }
+}
+
+impl CrawlHistoryRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip(self), name = "CrawlHistoryRepository::put")]
+  pub async fn put(&self, attempt: CrawlAttempt) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO crawl_history (
+            file_name,
+            crawled_at,
+            outcome,
+            status_code,
+            duration_ms,
+            initiator
+          )
+          VALUES (?, ?, ?, ?, ?, ?)
+          ",
+          params![
+            attempt.file_name.to_string(),
+            attempt.crawled_at,
+            attempt.outcome.to_string(),
+            attempt.status_code,
+            attempt.duration_ms,
+            attempt.initiator
+          ],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to record crawl attempt");
+        anyhow!("Failed to record crawl attempt")
+      })??;
+
+    Ok(())
+  }
+
+  /**
+   * Most recent attempts first
+   */
+  #[instrument(skip(self), name = "CrawlHistoryRepository::find_by_file_name")]
+  pub async fn find_by_file_name(
+    &self,
+    file_name: &FileName,
+    limit: u32,
+  ) -> Result<Vec<CrawlAttempt>> {
+    let file_name = file_name.to_string();
+    let attempts = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT file_name, crawled_at, outcome, status_code, duration_ms, initiator
+          FROM crawl_history
+          WHERE file_name = ?
+          ORDER BY crawled_at DESC, id DESC
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![file_name, limit], |row| CrawlAttempt::try_from(row))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find crawl history");
+        anyhow!("Failed to find crawl history")
+      })??;
+
+    Ok(attempts)
+  }
+
+  #[instrument(skip(self), name = "CrawlHistoryRepository::delete_before")]
+  pub async fn delete_before(&self, crawled_before: NaiveDateTime) -> Result<usize> {
+    let deleted = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM crawl_history WHERE crawled_at < ?",
+          params![crawled_before],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to prune crawl history");
+        anyhow!("Failed to prune crawl history")
+      })??;
+
+    Ok(deleted)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{TimeDelta, Utc};
+
+  #[tokio::test]
+  async fn test_delete_before() -> Result<()> {
+    let repository = CrawlHistoryRepository::new(Arc::new(SqliteConnection::new_for_test().await?));
+    let file_name = FileName::try_from("release/album/bjork/vulnicura")?;
+    let now = Utc::now().naive_utc();
+    for crawled_at in [now - TimeDelta::try_days(100).unwrap(), now] {
+      repository
+        .put(CrawlAttempt {
+          file_name: file_name.clone(),
+          crawled_at,
+          outcome: CrawlOutcome::Succeeded,
+          status_code: Some(200),
+          duration_ms: 100,
+          initiator: None,
+        })
+        .await?;
+    }
+
+    let deleted = repository
+      .delete_before(now - TimeDelta::try_days(90).unwrap())
+      .await?;
+    assert_eq!(deleted, 1);
+    let attempts = repository.find_by_file_name(&file_name, 10).await?;
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].crawled_at, now);
+    Ok(())
+  }
+}
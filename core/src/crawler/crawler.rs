@@ -1,13 +1,20 @@
-use super::crawler_state_repository::{CrawlerStateRepository, CrawlerStatus};
+use super::{
+  crawl_cost::CrawlCostReport,
+  crawl_history::{recrawl_blocked_until, CrawlAttempt, CrawlOutcome},
+  crawl_history_repository::CrawlHistoryRepository,
+  crawler_state_repository::{CrawlerStateRepository, CrawlerStatus},
+};
 use crate::{
   files::{file_interactor::FileInteractor, file_metadata::file_name::FileName},
   helpers::{key_value_store::KeyValueStore, priority::Priority},
+  runtime_settings::runtime_settings::RuntimeSettings,
   scheduler::{
     job_name::JobName,
     scheduler::{JobParameters, JobParametersBuilder, JobProcessorStatus, Scheduler},
     scheduler_repository::Job,
   },
   settings::Settings,
+  sqlite::SqliteConnection,
 };
 use anyhow::{anyhow, Result};
 use chrono::{NaiveDateTime, TimeDelta, Utc};
@@ -18,8 +25,8 @@ use reqwest_tracing::TracingMiddleware;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::{error, instrument};
+use tokio::{sync::Mutex, time::Instant};
+use tracing::{error, info, instrument, warn};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, Builder)]
 #[builder(default, setter(strip_option, into))]
@@ -103,6 +110,10 @@ impl TryInto<CrawlJob> for Job {
   }
 }
 
+fn crawl_job_id(file_name: &FileName) -> String {
+  format!("crawl:{}", file_name.to_string())
+}
+
 impl TryInto<JobParameters> for QueuePushParameters {
   type Error = anyhow::Error;
 
@@ -114,7 +125,7 @@ impl TryInto<JobParameters> for QueuePushParameters {
 
     Ok(
       JobParametersBuilder::default()
-        .id(format!("crawl:{}", self.file_name.to_string()))
+        .id(crawl_job_id(&self.file_name))
         .name(JobName::Crawl)
         .payload(serde_json::to_vec(&payload)?)
         .priority(self.priority.unwrap_or_default())
@@ -124,11 +135,17 @@ impl TryInto<JobParameters> for QueuePushParameters {
   }
 }
 
+/**
+ * Enough attempts to cover the longest failure backoff
+ */
+const RECRAWL_HISTORY_LIMIT: u32 = 10;
+
 pub struct Crawler {
-  settings: Arc<Settings>,
+  runtime_settings: Arc<RuntimeSettings>,
   client: ClientWithMiddleware,
   file_interactor: Arc<FileInteractor>,
   crawler_state_repository: CrawlerStateRepository,
+  crawl_history_repository: CrawlHistoryRepository,
   throttle_lock: Arc<Mutex<()>>,
   scheduler: Arc<Scheduler>,
 }
@@ -145,8 +162,10 @@ pub struct CrawlerMonitor {
 impl Crawler {
   pub fn new(
     settings: Arc<Settings>,
+    runtime_settings: Arc<RuntimeSettings>,
     scheduler: Arc<Scheduler>,
     kv: Arc<KeyValueStore>,
+    sqlite_connection: Arc<SqliteConnection>,
     file_interactor: Arc<FileInteractor>,
   ) -> Result<Self> {
     let mut base_client_builder = reqwest::ClientBuilder::new().danger_accept_invalid_certs(true);
@@ -168,31 +187,103 @@ impl Crawler {
 
     Ok(Self {
       client,
-      settings,
+      runtime_settings,
       file_interactor,
       crawler_state_repository: CrawlerStateRepository::new(kv),
+      crawl_history_repository: CrawlHistoryRepository::new(sqlite_connection),
       throttle_lock: Arc::new(Mutex::new(())),
       scheduler,
     })
   }
 
   fn get_url(&self, file_name: &FileName) -> String {
-    format!("https://rateyourmusic.com/{}", file_name.to_string())
+    if file_name.page_type().is_bandcamp() {
+      format!(
+        "https://bandcamp.com/{}",
+        file_name.to_string().trim_start_matches("bandcamp/")
+      )
+    } else {
+      format!("https://rateyourmusic.com/{}", file_name.to_string())
+    }
   }
 
+  /**
+   * Fetches the file and records the attempt in its crawl history
+   */
   #[instrument(skip(self))]
-  pub async fn request(&self, file_name: &FileName) -> Result<String> {
+  pub async fn request(&self, file_name: &FileName, initiator: Option<&str>) -> Result<String> {
     self.increment_window_request_count().await?;
 
+    let crawled_at = Utc::now().naive_utc();
+    let started_at = Instant::now();
+    let mut status_code = None;
+    let result = async {
+      let response = self.client.get(&self.get_url(file_name)).send().await?;
+      status_code = Some(response.status().as_u16());
+      Ok::<_, anyhow::Error>(response.error_for_status()?.text().await?)
+    }
+    .await;
+    let attempt = CrawlAttempt {
+      file_name: file_name.clone(),
+      crawled_at,
+      outcome: if result.is_ok() {
+        CrawlOutcome::Succeeded
+      } else {
+        CrawlOutcome::Failed
+      },
+      status_code,
+      duration_ms: started_at.elapsed().as_millis() as u32,
+      initiator: initiator.map(String::from),
+    };
+    if let Err(e) = self.crawl_history_repository.put(attempt).await {
+      warn!(
+        file_name = file_name.to_string(),
+        error = e.to_string(),
+        "Failed to record crawl attempt"
+      );
+    }
+    result
+  }
+
+  pub async fn get_crawl_history(
+    &self,
+    file_name: &FileName,
+    limit: u32,
+  ) -> Result<Vec<CrawlAttempt>> {
     self
-      .client
-      .get(&self.get_url(file_name))
-      .send()
-      .await?
-      .error_for_status()?
-      .text()
+      .crawl_history_repository
+      .find_by_file_name(file_name, limit)
       .await
-      .map_err(|e| e.into())
+  }
+
+  /**
+   * Deletes attempts made more than `max_age_days` ago, returning how many were deleted
+   */
+  pub async fn prune_crawl_history(&self, max_age_days: u32) -> Result<usize> {
+    let crawled_before = Utc::now().naive_utc()
+      - TimeDelta::try_days(max_age_days as i64)
+        .ok_or_else(|| anyhow!("Invalid crawl history retention"))?;
+    self
+      .crawl_history_repository
+      .delete_before(crawled_before)
+      .await
+  }
+
+  /**
+   * None when the file can be crawled now, otherwise when its failure backoff ends
+   */
+  pub async fn get_recrawl_blocked_until(
+    &self,
+    file_name: &FileName,
+  ) -> Result<Option<NaiveDateTime>> {
+    let attempts = self
+      .crawl_history_repository
+      .find_by_file_name(file_name, RECRAWL_HISTORY_LIMIT)
+      .await?;
+    Ok(
+      recrawl_blocked_until(&attempts)
+        .filter(|blocked_until| *blocked_until > Utc::now().naive_utc()),
+    )
   }
 
   pub async fn enqueue(&self, params: QueuePushParameters) -> Result<()> {
@@ -209,15 +300,52 @@ impl Crawler {
     Ok(())
   }
 
+  /**
+   * Moves a queued crawl up to `priority`, for work whose urgency changed after it was enqueued
+   */
+  pub async fn raise_priority(&self, file_name: &FileName, priority: Priority) -> Result<()> {
+    self
+      .scheduler
+      .raise_priority(&crawl_job_id(file_name), priority)
+      .await
+  }
+
+  /**
+   * What enqueueing `file_names` would cost, for dry runs
+   */
+  pub async fn estimate_cost(
+    &self,
+    file_names: &[FileName],
+    incomplete: bool,
+  ) -> Result<CrawlCostReport> {
+    Ok(CrawlCostReport {
+      incomplete,
+      remaining_window_requests: self.remaining_window_requests().await?,
+      ..CrawlCostReport::from_file_names(file_names)
+    })
+  }
+
+  /**
+   * Files that keep failing to crawl are held back until their backoff ends, rather than being
+   * re-enqueued every time they're found stale
+   */
   pub async fn enqueue_if_stale(&self, params: QueuePushParameters) -> Result<()> {
-    if self
+    if !self
       .file_interactor
       .is_file_stale(&params.file_name)
       .await?
     {
-      self.enqueue(params).await?
+      return Ok(());
     }
-    Ok(())
+    if let Some(blocked_until) = self.get_recrawl_blocked_until(&params.file_name).await? {
+      info!(
+        file_name = params.file_name.to_string(),
+        blocked_until = blocked_until.to_string(),
+        "Skipping recrawl of failing file"
+      );
+      return Ok(());
+    }
+    self.enqueue(params).await
   }
 
   pub async fn set_status(&self, status: CrawlerStatus) -> Result<()> {
@@ -278,17 +406,26 @@ impl Crawler {
       .await
   }
 
-  pub async fn remaining_window_requests(&self) -> Result<u32> {
+  /**
+   * Read on every check so a rate limit override applies to the current window
+   */
+  async fn get_max_window_requests(&self) -> Result<u32> {
     Ok(
       self
-        .settings
+        .runtime_settings
+        .get()
+        .await?
         .crawler
         .rate_limit
-        .max_requests
-        .saturating_sub(self.get_window_request_count().await?),
+        .max_requests,
     )
   }
 
+  pub async fn remaining_window_requests(&self) -> Result<u32> {
+    let max_requests = self.get_max_window_requests().await?;
+    Ok(max_requests.saturating_sub(self.get_window_request_count().await?))
+  }
+
   pub async fn reset_window_request_count(&self) -> Result<()> {
     self
       .crawler_state_repository
@@ -309,7 +446,7 @@ impl Crawler {
       return Ok(false);
     }
     let total = self.get_window_request_count().await?;
-    Ok(total >= self.settings.crawler.rate_limit.max_requests)
+    Ok(total >= self.get_max_window_requests().await?)
   }
 
   #[instrument(skip(self))]
@@ -1,6 +1,8 @@
 use crate::{
   context::ApplicationContext,
-  crawler::crawler::CrawlJob,
+  crawler::{
+    crawl_history::is_retryable_status, crawler::CrawlJob, stale_file_refresh::refresh_stale_files,
+  },
   job_executor,
   scheduler::{
     job_name::JobName,
@@ -11,7 +13,7 @@ use crate::{
 use anyhow::{anyhow, bail, Result};
 use chrono::{TimeDelta, Utc};
 use std::sync::Arc;
-use tokio_retry::{strategy::FibonacciBackoff, Retry};
+use tokio_retry::{strategy::FibonacciBackoff, RetryIf};
 use tracing::info;
 
 async fn crawl(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
@@ -22,9 +24,22 @@ async fn crawl(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
     bail!("Crawler is throttled");
   }
 
-  let file_content = Retry::spawn(FibonacciBackoff::from_millis(500).take(5), || async {
-    app_context.crawler.request(&crawl_job.file_name).await
-  })
+  let file_content = RetryIf::spawn(
+    FibonacciBackoff::from_millis(500).take(5),
+    || async {
+      app_context
+        .crawler
+        .request(&crawl_job.file_name, crawl_job.correlation_id.as_deref())
+        .await
+    },
+    |e: &anyhow::Error| {
+      is_retryable_status(
+        e.downcast_ref::<reqwest::Error>()
+          .and_then(|e| e.status())
+          .map(|status| status.as_u16()),
+      )
+    },
+  )
   .await?;
   app_context
     .file_interactor
@@ -38,6 +53,20 @@ async fn reset_crawler_request_window(_: Job, app_context: Arc<ApplicationContex
   app_context.crawler.reset_window_request_count().await
 }
 
+async fn refresh_stale_crawled_files(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  info!("Executing job, refreshing stale files");
+  refresh_stale_files(app_context).await
+}
+
+async fn prune_crawl_history(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let deleted = app_context
+    .crawler
+    .prune_crawl_history(app_context.settings.crawler.history_retention_days)
+    .await?;
+  info!(deleted, "Pruned crawl history");
+  Ok(())
+}
+
 pub async fn setup_crawler_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
   app_context
     .scheduler
@@ -72,8 +101,8 @@ pub async fn setup_crawler_jobs(app_context: Arc<ApplicationContext>) -> Result<
     )
     .await;
 
-  let window =
-    TimeDelta::try_seconds(app_context.settings.crawler.rate_limit.window_seconds as i64).unwrap();
+  let rate_limit = app_context.runtime_settings.get().await?.crawler.rate_limit;
+  let window = TimeDelta::try_seconds(rate_limit.window_seconds as i64).unwrap();
   app_context
     .scheduler
     .put(
@@ -85,5 +114,50 @@ pub async fn setup_crawler_jobs(app_context: Arc<ApplicationContext>) -> Result<
         .build()?,
     )
     .await?;
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::PruneCrawlHistory)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(prune_crawl_history))
+        .build()?,
+    )
+    .await;
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::PruneCrawlHistory)
+        .interval(TimeDelta::try_days(1).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  let refresh_settings = &app_context.settings.crawler.refresh;
+  if !refresh_settings.enabled {
+    info!("Stale file refresh is disabled, skipping job");
+    return Ok(());
+  }
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::RefreshStaleFiles)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(refresh_stale_crawled_files))
+        .build()?,
+    )
+    .await;
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::RefreshStaleFiles)
+        .interval(TimeDelta::try_minutes(refresh_settings.interval_minutes as i64).unwrap())
+        .build()?,
+    )
+    .await?;
   Ok(())
 }
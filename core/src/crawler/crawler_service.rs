@@ -1,4 +1,5 @@
 use super::{
+  crawl_history::{CrawlAttempt, CrawlOutcome},
   crawler::{ClaimedQueueItem, Crawler, CrawlerMonitor, QueueItem, QueuePushParameters},
   crawler_state_repository::CrawlerStatus,
 };
@@ -6,12 +7,17 @@ use crate::{
   context::ApplicationContext,
   files::file_metadata::file_name::FileName,
   helpers::priority::Priority,
-  proto::{self, EnqueueRequest, GetCrawlerMonitorReply, SetCrawlerStatusReply, SetStatusRequest},
+  proto::{
+    self, EnqueueReply, EnqueueRequest, GetCrawlHistoryReply, GetCrawlHistoryRequest,
+    GetCrawlerMonitorReply, SetCrawlerStatusReply, SetStatusRequest,
+  },
 };
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 use tracing::error;
 
+const DEFAULT_CRAWL_HISTORY_LIMIT: u32 = 50;
+
 impl From<CrawlerMonitor> for proto::CrawlerMonitor {
   fn from(val: CrawlerMonitor) -> Self {
     proto::CrawlerMonitor {
@@ -91,6 +97,28 @@ impl From<ClaimedQueueItem> for proto::ClaimedCrawlerQueueItem {
   }
 }
 
+impl From<CrawlOutcome> for proto::CrawlOutcome {
+  fn from(val: CrawlOutcome) -> Self {
+    match val {
+      CrawlOutcome::Succeeded => proto::CrawlOutcome::Succeeded,
+      CrawlOutcome::Failed => proto::CrawlOutcome::Failed,
+    }
+  }
+}
+
+impl From<CrawlAttempt> for proto::CrawlAttempt {
+  fn from(val: CrawlAttempt) -> Self {
+    proto::CrawlAttempt {
+      file_name: val.file_name.to_string(),
+      crawled_at: val.crawled_at.to_string(),
+      outcome: proto::CrawlOutcome::from(val.outcome).into(),
+      status_code: val.status_code.map(u32::from),
+      duration_ms: val.duration_ms,
+      initiator: val.initiator,
+    }
+  }
+}
+
 impl TryFrom<EnqueueRequest> for QueuePushParameters {
   type Error = anyhow::Error;
 
@@ -147,20 +175,35 @@ impl proto::CrawlerService for CrawlerService {
     Ok(Response::new(reply))
   }
 
-  async fn enqueue(&self, request: Request<EnqueueRequest>) -> Result<Response<()>, Status> {
-    self
-      .crawler
-      .enqueue(request.into_inner().try_into().map_err(|e| {
-        error!("Error: {:?}", e);
-        Status::internal("Internal server error")
-      })?)
-      .await
-      .map_err(|e| {
-        error!("Error: {:?}", e);
-        Status::internal("Internal server error")
-      })?;
+  async fn enqueue(
+    &self,
+    request: Request<EnqueueRequest>,
+  ) -> Result<Response<EnqueueReply>, Status> {
+    let request = request.into_inner();
+    let dry_run = request.dry_run;
+    let params: QueuePushParameters = request.try_into().map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Internal server error")
+    })?;
+    if dry_run {
+      let cost = self
+        .crawler
+        .estimate_cost(&[params.file_name], false)
+        .await
+        .map_err(|e| {
+          error!("Error: {:?}", e);
+          Status::internal("Internal server error")
+        })?;
+      return Ok(Response::new(EnqueueReply {
+        cost: Some(cost.into()),
+      }));
+    }
+    self.crawler.enqueue(params).await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Internal server error")
+    })?;
 
-    Ok(Response::new(()))
+    Ok(Response::new(EnqueueReply { cost: None }))
   }
 
   async fn empty(&self, _request: Request<()>) -> Result<Response<()>, Status> {
@@ -193,4 +236,37 @@ impl proto::CrawlerService for CrawlerService {
 
     Ok(Response::new(()))
   }
+
+  async fn get_crawl_history(
+    &self,
+    request: Request<GetCrawlHistoryRequest>,
+  ) -> Result<Response<GetCrawlHistoryReply>, Status> {
+    let request = request.into_inner();
+    let file_name =
+      FileName::try_from(request.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let attempts = self
+      .crawler
+      .get_crawl_history(
+        &file_name,
+        request.limit.unwrap_or(DEFAULT_CRAWL_HISTORY_LIMIT),
+      )
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Internal server error")
+      })?;
+    let recrawl_blocked_until = self
+      .crawler
+      .get_recrawl_blocked_until(&file_name)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Internal server error")
+      })?;
+    let reply = GetCrawlHistoryReply {
+      attempts: attempts.into_iter().map(Into::into).collect(),
+      recrawl_blocked_until: recrawl_blocked_until.map(|blocked_until| blocked_until.to_string()),
+    };
+    Ok(Response::new(reply))
+  }
 }
@@ -1,4 +1,8 @@
+pub mod crawl_cost;
+pub mod crawl_history;
+mod crawl_history_repository;
 pub mod crawler;
 pub mod crawler_jobs;
 pub mod crawler_service;
 mod crawler_state_repository;
+pub mod stale_file_refresh;
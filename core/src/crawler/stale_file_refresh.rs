@@ -0,0 +1,209 @@
+use super::crawler::QueuePushParameters;
+use crate::{
+  context::ApplicationContext,
+  files::file_metadata::{file_metadata::FileMetadata, file_name::FileName, page_type::PageType},
+  helpers::priority::Priority,
+  settings::CrawlerFreshnessPolicySettings,
+};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+
+/**
+ * A page type, the name prefix its files share and how long they stay fresh
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreshnessPolicy {
+  pub page_type: PageType,
+  pub name_prefix: &'static str,
+  pub max_age: TimeDelta,
+}
+
+pub fn freshness_policies(settings: &CrawlerFreshnessPolicySettings) -> Vec<FreshnessPolicy> {
+  [
+    (PageType::Album, "release/", settings.album),
+    (PageType::Artist, "artist/", settings.artist),
+    (PageType::Chart, "charts/", settings.chart),
+    (PageType::ListSegment, "list/", settings.list_segment),
+  ]
+  .into_iter()
+  .filter_map(|(page_type, name_prefix, max_age_days)| {
+    Some(FreshnessPolicy {
+      page_type,
+      name_prefix,
+      max_age: TimeDelta::try_days(max_age_days? as i64)?,
+    })
+  })
+  .collect()
+}
+
+/**
+ * The files most overdue for a refresh across every policy, up to `limit`. A file's due date is
+ * its last save plus its policy's max age.
+ */
+pub fn most_overdue(candidates: Vec<(FileMetadata, TimeDelta)>, limit: usize) -> Vec<FileName> {
+  let mut candidates = candidates
+    .into_iter()
+    .map(|(file_metadata, max_age)| {
+      let last_saved_at: DateTime<Utc> = file_metadata.last_saved_at.into();
+      (last_saved_at + max_age, file_metadata.name)
+    })
+    .collect::<Vec<_>>();
+  candidates.sort_by_key(|(due_at, _)| *due_at);
+  candidates
+    .into_iter()
+    .take(limit)
+    .map(|(_, file_name)| file_name)
+    .collect()
+}
+
+fn budget_key(day: NaiveDate) -> String {
+  format!("crawler_refresh_enqueued:{}", day.format("%Y-%m-%d"))
+}
+
+fn enqueued_key(file_name: &FileName) -> String {
+  format!("crawler_refresh_enqueued_file:{}", file_name.to_string())
+}
+
+/**
+ * Enqueues low priority crawls for pages past their freshness policy, within what's left of the
+ * day's budget. Files are remembered for a day once enqueued, so a backed up queue doesn't spend
+ * the next run's budget on the same pages.
+ */
+pub async fn refresh_stale_files(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let settings = app_context.runtime_settings.get().await?.crawler.refresh;
+  let now = Utc::now();
+  let budget_key = budget_key(now.date_naive());
+  let used = app_context.kv.increment(&budget_key, 0).await?.max(0) as u32;
+  let remaining = settings.daily_budget.saturating_sub(used);
+  if remaining == 0 {
+    info!(used, "Stale file refresh budget spent for today");
+    return Ok(());
+  }
+
+  let mut candidates = vec![];
+  for policy in freshness_policies(&settings.max_age_days) {
+    let stale = app_context
+      .file_interactor
+      .find_saved_before(policy.name_prefix, now - policy.max_age, remaining * 2)
+      .await?;
+    candidates.extend(
+      stale
+        .into_iter()
+        .filter(|file_metadata| file_metadata.name.page_type() == policy.page_type)
+        .map(|file_metadata| (file_metadata, policy.max_age)),
+    );
+  }
+  let already_enqueued = app_context
+    .kv
+    .many_exists(
+      candidates
+        .iter()
+        .map(|(file_metadata, _)| enqueued_key(&file_metadata.name))
+        .collect(),
+    )
+    .await?;
+  candidates.retain(|(file_metadata, _)| {
+    !already_enqueued
+      .get(&enqueued_key(&file_metadata.name))
+      .copied()
+      .unwrap_or(false)
+  });
+
+  let file_names = most_overdue(candidates, remaining as usize);
+  if file_names.is_empty() {
+    return Ok(());
+  }
+  app_context
+    .crawler
+    .enqueue_many(
+      file_names
+        .iter()
+        .map(|file_name| QueuePushParameters {
+          file_name: file_name.clone(),
+          priority: Some(Priority::Low),
+          correlation_id: None,
+        })
+        .collect(),
+    )
+    .await?;
+  app_context
+    .kv
+    .set_many(
+      file_names
+        .iter()
+        .map(|file_name| {
+          (
+            enqueued_key(file_name),
+            true,
+            Some(Duration::from_secs(60 * 60 * 24)),
+          )
+        })
+        .collect(),
+    )
+    .await?;
+  app_context
+    .kv
+    .increment(&budget_key, file_names.len() as i64)
+    .await?;
+  info!(
+    count = file_names.len(),
+    remaining = remaining as usize - file_names.len(),
+    "Enqueued stale files for refresh"
+  );
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::files::file_metadata::file_timestamp::FileTimestamp;
+  use ulid::Ulid;
+
+  fn file_metadata(name: &str, days_ago: i64) -> FileMetadata {
+    FileMetadata {
+      id: Ulid::new(),
+      name: FileName::try_from(name.to_string()).unwrap(),
+      last_saved_at: FileTimestamp(Utc::now() - TimeDelta::try_days(days_ago).unwrap()),
+      redaction_version: None,
+    }
+  }
+
+  #[test]
+  fn test_freshness_policies_skip_unset_page_types() {
+    let policies = freshness_policies(&CrawlerFreshnessPolicySettings {
+      album: Some(180),
+      artist: None,
+      chart: Some(7),
+      list_segment: None,
+    });
+    assert_eq!(
+      policies
+        .iter()
+        .map(|policy| policy.page_type.clone())
+        .collect::<Vec<_>>(),
+      vec![PageType::Album, PageType::Chart]
+    );
+  }
+
+  #[test]
+  fn test_most_overdue() {
+    let days = |days: i64| TimeDelta::try_days(days).unwrap();
+    let file_names = most_overdue(
+      vec![
+        (file_metadata("release/album/a/b", 200), days(180)),
+        (file_metadata("charts/top/album/2024/1", 30), days(7)),
+        (file_metadata("release/album/c/d", 190), days(180)),
+      ],
+      2,
+    );
+    assert_eq!(
+      file_names
+        .iter()
+        .map(|file_name| file_name.to_string())
+        .collect::<Vec<_>>(),
+      vec!["charts/top/album/2024/1", "release/album/a/b"]
+    );
+  }
+}
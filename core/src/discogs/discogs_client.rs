@@ -0,0 +1,191 @@
+use crate::settings::DiscogsSettings;
+use anyhow::{anyhow, Result};
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use std::{collections::HashMap, time::Duration};
+use tracing::warn;
+
+lazy_static! {
+  // Authenticated clients get 60 requests a minute
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_minute(nonzero!(60u32)));
+}
+
+const API_URL: &str = "https://api.discogs.com";
+const USER_AGENT: &str = "lute/0.1 +https://github.com/shedrachokonofua/lute";
+const SEARCH_PAGE_SIZE: u32 = 10;
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscogsSearchResult {
+  pub id: u64,
+  /**
+   * Discogs formats release titles as "Artist - Title"
+   */
+  pub title: String,
+  pub year: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscogsMarketplaceStats {
+  pub lowest_price: Option<f32>,
+  pub num_for_sale: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+  results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+  id: u64,
+  title: String,
+  year: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Price {
+  value: f32,
+  currency: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketplaceStatsResponse {
+  lowest_price: Option<Price>,
+  num_for_sale: Option<u32>,
+}
+
+pub struct DiscogsClient {
+  client: Client,
+  settings: DiscogsSettings,
+}
+
+impl DiscogsClient {
+  pub fn new(settings: DiscogsSettings) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+    }
+  }
+
+  pub fn settings(&self) -> &DiscogsSettings {
+    &self.settings
+  }
+
+  fn currency(&self) -> &str {
+    self
+      .settings
+      .currency
+      .as_deref()
+      .unwrap_or(DEFAULT_CURRENCY)
+  }
+
+  async fn get(&self, path: &str) -> RequestBuilder {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(100)))
+      .await;
+    self
+      .client
+      .get(format!("{}{}", API_URL, path))
+      .header("User-Agent", USER_AGENT)
+      .header(
+        "Authorization",
+        format!("Discogs token={}", self.settings.token),
+      )
+  }
+
+  /**
+   * Vinyl releases matching the artist and title, most relevant first
+   */
+  pub async fn search_vinyl_releases(
+    &self,
+    artist_name: &str,
+    title: &str,
+  ) -> Result<Vec<DiscogsSearchResult>> {
+    let per_page = SEARCH_PAGE_SIZE.to_string();
+    let response = self
+      .get("/database/search")
+      .await
+      .query(&[
+        ("type", "release"),
+        ("format", "Vinyl"),
+        ("artist", artist_name),
+        ("release_title", title),
+        ("per_page", per_page.as_str()),
+      ])
+      .send()
+      .await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+      return Err(anyhow!("Discogs API rate limit exceeded"));
+    }
+    let response = response
+      .error_for_status()?
+      .json::<SearchResponse>()
+      .await?;
+    Ok(
+      response
+        .results
+        .into_iter()
+        .map(|result| DiscogsSearchResult {
+          id: result.id,
+          title: result.title,
+          year: result.year.and_then(|year| year.parse().ok()),
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn get_marketplace_stats(&self, release_id: u64) -> Result<DiscogsMarketplaceStats> {
+    let response = self
+      .get(&format!("/marketplace/stats/{}", release_id))
+      .await
+      .query(&[("curr_abbr", self.currency())])
+      .send()
+      .await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+      return Err(anyhow!("Discogs API rate limit exceeded"));
+    }
+    let response = response
+      .error_for_status()?
+      .json::<MarketplaceStatsResponse>()
+      .await?;
+    Ok(DiscogsMarketplaceStats {
+      lowest_price: response.lowest_price.map(|price| price.value),
+      num_for_sale: response.num_for_sale.unwrap_or(0),
+    })
+  }
+
+  /**
+   * Suggested prices by media condition, which Discogs derives from the release's sales history.
+   * Only available to tokens whose account has seller settings, and priced in the seller
+   * currency, so this is empty when either doesn't line up.
+   */
+  pub async fn get_price_suggestions(&self, release_id: u64) -> Result<Vec<f32>> {
+    let response = self
+      .get(&format!("/marketplace/price_suggestions/{}", release_id))
+      .await
+      .send()
+      .await?;
+    match response.status() {
+      StatusCode::TOO_MANY_REQUESTS => Err(anyhow!("Discogs API rate limit exceeded")),
+      StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+        warn!("Discogs price suggestions need seller settings on the account");
+        Ok(vec![])
+      }
+      _ => Ok(
+        response
+          .error_for_status()?
+          .json::<HashMap<String, Price>>()
+          .await?
+          .into_values()
+          .filter(|price| price.currency.eq_ignore_ascii_case(self.currency()))
+          .map(|price| price.value)
+          .collect(),
+      ),
+    }
+  }
+}
@@ -0,0 +1,287 @@
+use super::discogs_client::{DiscogsClient, DiscogsSearchResult};
+use crate::{
+  albums::{
+    album_interactor::AlbumInteractor,
+    album_read_model::{AlbumReadModel, AlbumReadModelDiscogsRelease},
+  },
+  files::file_metadata::file_name::FileName,
+  helpers::priority::Priority,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobParametersBuilder, Scheduler},
+  },
+  settings::DiscogsSettings,
+};
+use anyhow::{anyhow, Result};
+use chrono::{TimeDelta, Utc};
+use std::sync::Arc;
+use strsim::jaro_winkler;
+use tracing::{error, info, instrument};
+use unidecode::unidecode;
+
+const MIN_TITLE_SIMILARITY: f64 = 0.9;
+const DEFAULT_MAX_PRICE_AGE_DAYS: u32 = 7;
+const REFRESH_BATCH_SIZE: u32 = 100;
+
+fn normalize(value: &str) -> String {
+  unidecode(value).to_lowercase().trim().to_string()
+}
+
+/**
+ * Splits a Discogs "Artist - Title" release title, dropping the "*" name variation marker and
+ * the "(2)" suffix Discogs uses to tell apart artists with the same name
+ */
+fn parse_release_title(title: &str) -> Option<(String, String)> {
+  let (artist, title) = title.split_once(" - ")?;
+  let artist = artist.trim().trim_end_matches('*');
+  let artist = match artist.rsplit_once(" (") {
+    Some((name, suffix))
+      if suffix.ends_with(')') && suffix[..suffix.len() - 1].parse::<u32>().is_ok() =>
+    {
+      name
+    }
+    _ => artist,
+  };
+  Some((normalize(artist), normalize(title)))
+}
+
+/**
+ * The search result by one of the album's artists with the closest title, the most relevant on
+ * ties. Vinyl pressings are often years apart from the original release, so the year isn't
+ * considered.
+ */
+fn find_release_match<'a>(
+  album: &AlbumReadModel,
+  results: &'a [DiscogsSearchResult],
+) -> Option<&'a DiscogsSearchResult> {
+  let album_name = normalize(&album.name);
+  let artist_names = album
+    .artists
+    .iter()
+    .map(|artist| normalize(&artist.name))
+    .collect::<Vec<_>>();
+  results
+    .iter()
+    // max_by keeps the last of equal elements, reversing keeps the most relevant
+    .rev()
+    .filter_map(|result| {
+      let (artist, title) = parse_release_title(&result.title)?;
+      let title_similarity = jaro_winkler(&album_name, &title);
+      (artist_names.contains(&artist) && title_similarity >= MIN_TITLE_SIMILARITY)
+        .then_some((result, title_similarity))
+    })
+    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(result, _)| result)
+}
+
+fn median(mut values: Vec<f32>) -> Option<f32> {
+  if values.is_empty() {
+    return None;
+  }
+  values.sort_by(|a, b| a.total_cmp(b));
+  let mid = values.len() / 2;
+  if values.len() % 2 == 0 {
+    Some((values[mid - 1] + values[mid]) / 2.0)
+  } else {
+    Some(values[mid])
+  }
+}
+
+pub struct DiscogsInteractor {
+  client: DiscogsClient,
+  album_interactor: Arc<AlbumInteractor>,
+  scheduler: Arc<Scheduler>,
+}
+
+impl DiscogsInteractor {
+  pub fn new(
+    settings: DiscogsSettings,
+    album_interactor: Arc<AlbumInteractor>,
+    scheduler: Arc<Scheduler>,
+  ) -> Self {
+    Self {
+      client: DiscogsClient::new(settings),
+      album_interactor,
+      scheduler,
+    }
+  }
+
+  pub fn settings(&self) -> &DiscogsSettings {
+    self.client.settings()
+  }
+
+  async fn get_release(&self, release_id: u64) -> Result<AlbumReadModelDiscogsRelease> {
+    let stats = self.client.get_marketplace_stats(release_id).await?;
+    let price_suggestions = self.client.get_price_suggestions(release_id).await?;
+    Ok(AlbumReadModelDiscogsRelease {
+      release_id,
+      median_suggested_price: median(price_suggestions),
+      lowest_price: stats.lowest_price,
+      num_for_sale: stats.num_for_sale,
+      refreshed_at: Utc::now().naive_utc(),
+    })
+  }
+
+  /**
+   * Resolves the album's vinyl release and its marketplace state. Albums that already have a
+   * release are returned as is, the refresh job keeps their prices current.
+   */
+  #[instrument(skip(self), name = "DiscogsInteractor::lookup")]
+  pub async fn lookup(&self, file_name: &FileName) -> Result<Option<AlbumReadModelDiscogsRelease>> {
+    let mut album = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .ok_or_else(|| anyhow!("Album not found"))?;
+    if album.discogs_release.is_some() {
+      return Ok(album.discogs_release);
+    }
+    let Some(artist) = album.artists.first() else {
+      return Ok(None);
+    };
+    let results = self
+      .client
+      .search_vinyl_releases(&artist.name, &album.name)
+      .await?;
+    let Some(result) = find_release_match(&album, &results) else {
+      info!(
+        file_name = file_name.to_string(),
+        "No matching Discogs vinyl release"
+      );
+      return Ok(None);
+    };
+    let release = self.get_release(result.id).await?;
+    album.discogs_release = Some(release.clone());
+    self.album_interactor.put(album).await?;
+    Ok(Some(release))
+  }
+
+  #[instrument(skip(self), name = "DiscogsInteractor::refresh")]
+  async fn refresh(&self, file_name: &FileName) -> Result<()> {
+    let mut album = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .ok_or_else(|| anyhow!("Album not found"))?;
+    let Some(release_id) = album
+      .discogs_release
+      .as_ref()
+      .map(|release| release.release_id)
+    else {
+      return Ok(());
+    };
+    match self.get_release(release_id).await {
+      Ok(release) => {
+        album.discogs_release = Some(release);
+        self.album_interactor.put(album).await
+      }
+      Err(e) => {
+        // Otherwise a release that keeps failing stays the stalest and is retried in every batch
+        if let Some(release) = album.discogs_release.as_mut() {
+          release.refreshed_at = Utc::now().naive_utc();
+        }
+        self.album_interactor.put(album).await?;
+        Err(e)
+      }
+    }
+  }
+
+  /**
+   * Refreshes a batch of the releases with the oldest prices
+   */
+  pub async fn refresh_stale(&self) -> Result<()> {
+    let max_age_days = self
+      .settings()
+      .max_price_age_days
+      .unwrap_or(DEFAULT_MAX_PRICE_AGE_DAYS);
+    let refreshed_before = Utc::now().naive_utc()
+      - TimeDelta::try_days(max_age_days as i64).ok_or_else(|| anyhow!("Invalid price age"))?;
+    let file_names = self
+      .album_interactor
+      .find_stale_discogs_releases(refreshed_before, REFRESH_BATCH_SIZE)
+      .await?;
+    info!(count = file_names.len(), "Refreshing Discogs prices");
+    for file_name in file_names {
+      if let Err(e) = self.refresh(&file_name).await {
+        error!(
+          file_name = file_name.to_string(),
+          err = e.to_string(),
+          "Failed to refresh Discogs prices"
+        );
+      }
+    }
+    Ok(())
+  }
+
+  pub async fn enqueue_many(&self, file_names: Vec<FileName>) -> Result<()> {
+    self
+      .scheduler
+      .put_many(
+        file_names
+          .into_iter()
+          .map(|file_name| {
+            Ok(
+              JobParametersBuilder::default()
+                .id(format!("lookup_discogs_release:{}", file_name.to_string()))
+                .name(JobName::LookupDiscogsRelease)
+                .payload(serde_json::to_vec(&file_name)?)
+                .priority(Priority::Low)
+                .overwrite_existing(false)
+                .build()?,
+            )
+          })
+          .collect::<Result<Vec<_>>>()?,
+      )
+      .await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_read_model::AlbumReadModelArtist;
+
+  fn result(id: u64, title: &str) -> DiscogsSearchResult {
+    DiscogsSearchResult {
+      id,
+      title: title.to_string(),
+      year: None,
+    }
+  }
+
+  #[test]
+  fn test_find_release_match() -> Result<()> {
+    let album = AlbumReadModel {
+      name: "Vulnicura".to_string(),
+      file_name: FileName::try_from("release/album/bjork/vulnicura")?,
+      artists: vec![AlbumReadModelArtist {
+        name: "Björk".to_string(),
+        file_name: FileName::try_from("artist/bjork")?,
+      }],
+      ..Default::default()
+    };
+    let results = vec![
+      result(1, "Björk Tribute - Vulnicura"),
+      result(2, "Björk - Vulnicura Strings"),
+      result(3, "Bjork* - Vulnicura"),
+      result(4, "Björk - Vulnicura"),
+    ];
+    assert_eq!(
+      find_release_match(&album, &results).map(|result| result.id),
+      Some(3)
+    );
+    assert_eq!(
+      parse_release_title("Nas (2) - Illmatic"),
+      Some(("nas".to_string(), "illmatic".to_string()))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_median() {
+    assert_eq!(median(vec![]), None);
+    assert_eq!(median(vec![30.0, 10.0, 20.0]), Some(20.0));
+    assert_eq!(median(vec![40.0, 10.0, 20.0, 30.0]), Some(25.0));
+  }
+}
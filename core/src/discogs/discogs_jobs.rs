@@ -0,0 +1,81 @@
+use crate::{
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::{anyhow, Result};
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn lookup_discogs_release(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let file_name = job.payload::<FileName>()?;
+  app_context
+    .discogs_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("Discogs is not configured"))?
+    .lookup(&file_name)
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to look up Discogs release"))?;
+  Ok(())
+}
+
+async fn refresh_discogs_prices(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .discogs_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("Discogs is not configured"))?
+    .refresh_stale()
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to refresh Discogs prices"))
+}
+
+pub async fn setup_discogs_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let Some(settings) = app_context.settings.discogs.clone() else {
+    info!("Discogs is not configured, skipping lookup and price refresh jobs");
+    return Ok(());
+  };
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::LookupDiscogsRelease)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(lookup_discogs_release))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::RefreshDiscogsPrices)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(refresh_discogs_prices))
+        // A batch waits on the API rate limit, so give it time to finish before it's reclaimed
+        .claim_duration(std::time::Duration::from_secs(60 * 10))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::RefreshDiscogsPrices)
+        .interval(
+          TimeDelta::try_hours(settings.refresh_interval_hours.unwrap_or(24) as i64).unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,61 @@
+use super::discogs_interactor::DiscogsInteractor;
+use crate::{context::ApplicationContext, files::file_metadata::file_name::FileName, proto};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct DiscogsService {
+  discogs_interactor: Option<Arc<DiscogsInteractor>>,
+}
+
+impl DiscogsService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      discogs_interactor: app_context.discogs_interactor.clone(),
+    }
+  }
+
+  fn interactor(&self) -> Result<&DiscogsInteractor, Status> {
+    self
+      .discogs_interactor
+      .as_deref()
+      .ok_or_else(|| Status::failed_precondition("Discogs is not configured"))
+  }
+}
+
+#[tonic::async_trait]
+impl proto::DiscogsService for DiscogsService {
+  async fn lookup_discogs_release(
+    &self,
+    request: Request<proto::LookupDiscogsReleaseRequest>,
+  ) -> Result<Response<proto::LookupDiscogsReleaseReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    let release = self
+      .interactor()?
+      .lookup(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::LookupDiscogsReleaseReply {
+      release: release.map(|release| release.into()),
+    }))
+  }
+
+  async fn enqueue_discogs_lookups(
+    &self,
+    request: Request<proto::EnqueueDiscogsLookupsRequest>,
+  ) -> Result<Response<()>, Status> {
+    let file_names = request
+      .into_inner()
+      .file_names
+      .into_iter()
+      .map(FileName::try_from)
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    self
+      .interactor()?
+      .enqueue_many(file_names)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+}
@@ -0,0 +1,5 @@
+pub mod discogs_client;
+pub mod discogs_event_subscribers;
+pub mod discogs_interactor;
+pub mod discogs_jobs;
+pub mod discogs_service;
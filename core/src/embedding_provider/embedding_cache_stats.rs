@@ -0,0 +1,131 @@
+use crate::helpers::key_value_store::KeyValueStore;
+use anyhow::Result;
+use std::sync::Arc;
+
+const CHARS_PER_TOKEN: u64 = 4;
+
+const HITS: &str = "hits";
+const MISSES: &str = "misses";
+const PROVIDER_CALLS: &str = "provider_calls";
+const TOKENS_AVOIDED: &str = "tokens_avoided";
+
+/**
+ * Rough token count of an embedding input, at four characters a token
+ */
+pub fn estimate_tokens(content: &str) -> u64 {
+  (content.chars().count() as u64).div_ceil(CHARS_PER_TOKEN)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbeddingCacheStats {
+  pub provider_name: String,
+  pub hits: u64,
+  pub misses: u64,
+  pub provider_calls: u64,
+  pub cached_count: u64,
+  pub bytes_stored: u64,
+  pub tokens_avoided: u64,
+  pub estimated_cost_avoided: f64,
+}
+
+impl EmbeddingCacheStats {
+  pub fn hit_rate(&self) -> f64 {
+    let lookups = self.hits + self.misses;
+    if lookups == 0 {
+      0.0
+    } else {
+      self.hits as f64 / lookups as f64
+    }
+  }
+}
+
+/**
+ * Running counts of embedding cache lookups per provider, kept until the provider's cache is
+ * cleared
+ */
+pub struct EmbeddingCacheStatsRepository {
+  kv: Arc<KeyValueStore>,
+}
+
+impl EmbeddingCacheStatsRepository {
+  pub fn new(kv: Arc<KeyValueStore>) -> Self {
+    Self { kv }
+  }
+
+  fn key(&self, provider_name: &str, counter: &str) -> String {
+    format!("embedding_cache_stats:{}:{}", provider_name, counter)
+  }
+
+  async fn add(&self, provider_name: &str, counter: &str, delta: u64) -> Result<u64> {
+    let value = self
+      .kv
+      .increment(&self.key(provider_name, counter), delta as i64)
+      .await?;
+    Ok(value.max(0) as u64)
+  }
+
+  pub async fn record_lookup(
+    &self,
+    provider_name: &str,
+    hits: u64,
+    misses: u64,
+    tokens_avoided: u64,
+  ) -> Result<()> {
+    self.add(provider_name, HITS, hits).await?;
+    self.add(provider_name, MISSES, misses).await?;
+    self
+      .add(provider_name, TOKENS_AVOIDED, tokens_avoided)
+      .await?;
+    Ok(())
+  }
+
+  pub async fn record_provider_call(&self, provider_name: &str) -> Result<()> {
+    self.add(provider_name, PROVIDER_CALLS, 1).await?;
+    Ok(())
+  }
+
+  /**
+   * Counters of a provider, without the cache size which is read from the cache itself
+   */
+  pub async fn get(
+    &self,
+    provider_name: &str,
+    cost_per_million_tokens: f64,
+  ) -> Result<EmbeddingCacheStats> {
+    let tokens_avoided = self.add(provider_name, TOKENS_AVOIDED, 0).await?;
+    Ok(EmbeddingCacheStats {
+      provider_name: provider_name.to_string(),
+      hits: self.add(provider_name, HITS, 0).await?,
+      misses: self.add(provider_name, MISSES, 0).await?,
+      provider_calls: self.add(provider_name, PROVIDER_CALLS, 0).await?,
+      tokens_avoided,
+      estimated_cost_avoided: tokens_avoided as f64 * cost_per_million_tokens / 1_000_000.0,
+      ..Default::default()
+    })
+  }
+
+  pub async fn reset(&self, provider_name: &str) -> Result<()> {
+    self
+      .kv
+      .delete_matching(&format!("embedding_cache_stats:{}:%", provider_name))
+      .await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_estimates() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("Vulnicura"), 3);
+    let stats = EmbeddingCacheStats {
+      hits: 3,
+      misses: 1,
+      ..Default::default()
+    };
+    assert_eq!(stats.hit_rate(), 0.75);
+    assert_eq!(EmbeddingCacheStats::default().hit_rate(), 0.0);
+  }
+}
@@ -1,7 +1,8 @@
 use super::{
+  embedding_cache_stats::{estimate_tokens, EmbeddingCacheStats, EmbeddingCacheStatsRepository},
   provider::EmbeddingProvider,
   providers::{
-    ollama::OllamaEmbeddingProvider, openai::OpenAIEmbeddingProvider,
+    ollama::OllamaEmbeddingProvider, onnx::OnnxEmbeddingProvider, openai::OpenAIEmbeddingProvider,
     voyageai::VoyageAIEmbeddingProvider,
   },
 };
@@ -16,7 +17,7 @@ use reqwest::Url;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::{info, instrument};
+use tracing::{error, info, instrument, warn};
 
 struct EmbeddingProviderCache {
   kv: Arc<KeyValueStore>,
@@ -35,6 +36,22 @@ impl EmbeddingProviderCache {
     format!("embedding_cache:{}:{}", provider_name, hash)
   }
 
+  fn pattern(&self, provider_name: &str) -> String {
+    format!("embedding_cache:{}:%", provider_name)
+  }
+
+  pub async fn count(&self, provider_name: &str) -> Result<u64> {
+    Ok(self.kv.count_matching(&self.pattern(provider_name)).await? as u64)
+  }
+
+  pub async fn size(&self, provider_name: &str) -> Result<u64> {
+    self.kv.size_matching(&self.pattern(provider_name)).await
+  }
+
+  pub async fn clear(&self, provider_name: &str) -> Result<()> {
+    self.kv.delete_matching(&self.pattern(provider_name)).await
+  }
+
   pub async fn set_many(&self, provider_name: &str, items: Vec<(String, Vec<f32>)>) -> Result<()> {
     self
       .kv
@@ -85,7 +102,9 @@ impl EmbeddingProviderCache {
 
 pub struct EmbeddingProviderInteractor {
   pub providers: HashMap<String, Arc<dyn EmbeddingProvider + Send + Sync>>,
+  default_provider_name: Option<String>,
   cache: EmbeddingProviderCache,
+  cache_stats: EmbeddingCacheStatsRepository,
 }
 
 impl EmbeddingProviderInteractor {
@@ -111,7 +130,7 @@ impl EmbeddingProviderInteractor {
           .as_str(),
       )
       .inspect_err(|e| {
-        tracing::error!(
+        error!(
           "Failed to parse Ollama URL, cannot register Ollama providers: {}",
           e
         )
@@ -126,10 +145,52 @@ impl EmbeddingProviderInteractor {
       }
     }
 
+    if let Some(onnx_settings) = &settings.embedding_provider.onnx {
+      match OnnxEmbeddingProvider::new(onnx_settings, &settings.sqlite.dir) {
+        Ok(provider) => {
+          let provider = Arc::new(provider);
+          providers.insert(provider.name().to_string(), provider);
+        }
+        Err(e) => error!("Failed to register ONNX provider: {}", e),
+      }
+    }
+
+    let default_provider_name = settings
+      .embedding_provider
+      .default
+      .clone()
+      .filter(|name| !name.is_empty());
+    if let Some(name) = &default_provider_name {
+      if !providers.contains_key(name) {
+        warn!(
+          name = name.as_str(),
+          "Default embedding provider is not registered"
+        );
+      }
+    }
+
     Self {
       providers,
-      cache: EmbeddingProviderCache::new(kv),
+      default_provider_name,
+      cache: EmbeddingProviderCache::new(Arc::clone(&kv)),
+      cache_stats: EmbeddingCacheStatsRepository::new(kv),
+    }
+  }
+
+  pub fn default_provider_name(&self) -> Option<String> {
+    self.default_provider_name.clone()
+  }
+
+  /**
+   * Falls back to the default provider when no embedding key is given
+   */
+  pub fn resolve_embedding_key(&self, embedding_key: String) -> Result<String> {
+    if !embedding_key.is_empty() {
+      return Ok(embedding_key);
     }
+    self
+      .default_provider_name()
+      .ok_or_else(|| anyhow!("No embedding key given and no default provider configured"))
   }
 
   pub fn get_provider_by_name(
@@ -157,6 +218,26 @@ impl EmbeddingProviderInteractor {
       .filter(|&key| !embeddings.contains_key(key))
       .cloned()
       .collect::<Vec<_>>();
+    let tokens_avoided = embeddings
+      .keys()
+      .filter_map(|key| input.get(key))
+      .map(|content| estimate_tokens(content))
+      .sum();
+    if let Err(e) = self
+      .cache_stats
+      .record_lookup(
+        provider_name,
+        embeddings.len() as u64,
+        uncached_keys.len() as u64,
+        tokens_avoided,
+      )
+      .await
+    {
+      warn!(
+        err = e.to_string(),
+        "Failed to record embedding cache lookup"
+      );
+    }
 
     if uncached_keys.is_empty() {
       info!(count = embeddings.len(), "All embeddings are cached");
@@ -172,6 +253,12 @@ impl EmbeddingProviderInteractor {
           .collect(),
       )
       .await?;
+    if let Err(e) = self.cache_stats.record_provider_call(provider_name).await {
+      warn!(
+        err = e.to_string(),
+        "Failed to record embedding provider call"
+      );
+    }
     let mut cache_input = HashMap::new();
     for (key, value) in uncached_keys.into_iter().zip(new_embeddings.into_iter()) {
       if let Some(content) = input.remove(&key) {
@@ -186,4 +273,39 @@ impl EmbeddingProviderInteractor {
 
     Ok(embeddings)
   }
+
+  pub async fn get_cache_stats(&self, provider_name: &str) -> Result<EmbeddingCacheStats> {
+    let provider = self.get_provider_by_name(provider_name)?;
+    let mut stats = self
+      .cache_stats
+      .get(provider_name, provider.cost_per_million_tokens())
+      .await?;
+    stats.cached_count = self.cache.count(provider_name).await?;
+    stats.bytes_stored = self.cache.size(provider_name).await?;
+    Ok(stats)
+  }
+
+  /**
+   * Cache stats of every registered provider, by provider name
+   */
+  pub async fn get_all_cache_stats(&self) -> Result<Vec<EmbeddingCacheStats>> {
+    let mut provider_names = self.providers.keys().collect::<Vec<_>>();
+    provider_names.sort();
+    let mut stats = vec![];
+    for provider_name in provider_names {
+      stats.push(self.get_cache_stats(provider_name).await?);
+    }
+    Ok(stats)
+  }
+
+  /**
+   * Drops a provider's cached embeddings and resets its stats, so they're generated again
+   */
+  pub async fn clear_cache(&self, provider_name: &str) -> Result<()> {
+    self.get_provider_by_name(provider_name)?;
+    self.cache.clear(provider_name).await?;
+    self.cache_stats.reset(provider_name).await?;
+    info!(provider_name, "Cleared embedding cache");
+    Ok(())
+  }
 }
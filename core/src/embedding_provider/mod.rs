@@ -1,3 +1,4 @@
+pub mod embedding_cache_stats;
 pub mod embedding_provider_event_subscribers;
 pub mod embedding_provider_interactor;
 pub mod embedding_provider_jobs;
@@ -11,5 +11,11 @@ pub trait EmbeddingProvider {
   fn concurrency(&self) -> usize;
   fn batch_size(&self) -> usize;
   fn job_name(&self) -> JobName;
+  /**
+   * List price in USD, used to estimate what cache hits saved. Local providers cost nothing.
+   */
+  fn cost_per_million_tokens(&self) -> f64 {
+    0.0
+  }
   async fn generate(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>>;
 }
@@ -1,3 +1,4 @@
 pub mod ollama;
+pub mod onnx;
 pub mod openai;
 pub mod voyageai;
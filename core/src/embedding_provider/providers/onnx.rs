@@ -0,0 +1,160 @@
+use super::super::provider::EmbeddingProvider;
+use crate::{scheduler::job_name::JobName, settings::OnnxSettings};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use std::{
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
+use tokio::{sync::OnceCell, task::spawn_blocking};
+use tracing::info;
+
+const DEFAULT_MODEL: &str = "all-minilm-l6-v2";
+
+fn resolve_model(name: &str) -> Result<(EmbeddingModel, usize)> {
+  match name {
+    "all-minilm-l6-v2" => Ok((EmbeddingModel::AllMiniLML6V2, 384)),
+    "bge-small-en-v1.5" => Ok((EmbeddingModel::BGESmallENV15, 384)),
+    "bge-base-en-v1.5" => Ok((EmbeddingModel::BGEBaseENV15, 768)),
+    "nomic-embed-text-v1.5" => Ok((EmbeddingModel::NomicEmbedTextV15, 768)),
+    _ => Err(anyhow!("Unsupported ONNX embedding model: {}", name)),
+  }
+}
+
+/**
+ * Runs a sentence-transformers model locally through ONNX runtime. The model is downloaded to the
+ * cache directory on first use, after which embeddings are generated fully offline.
+ */
+pub struct OnnxEmbeddingProvider {
+  model_name: String,
+  model: EmbeddingModel,
+  dimensions: usize,
+  cache_dir: PathBuf,
+  embedder: OnceCell<Arc<TextEmbedding>>,
+}
+
+impl OnnxEmbeddingProvider {
+  /**
+   * Models are cached under `data_dir` unless a cache directory is configured
+   */
+  pub fn new(settings: &OnnxSettings, data_dir: &str) -> Result<Self> {
+    let model_name = settings
+      .model
+      .clone()
+      .filter(|model| !model.is_empty())
+      .unwrap_or(DEFAULT_MODEL.to_string());
+    let (model, dimensions) = resolve_model(&model_name)?;
+    Ok(Self {
+      model_name,
+      model,
+      dimensions,
+      cache_dir: settings
+        .cache_dir
+        .clone()
+        .filter(|cache_dir| !cache_dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(data_dir).join("onnx_models")),
+      embedder: OnceCell::new(),
+    })
+  }
+
+  async fn embedder(&self) -> Result<Arc<TextEmbedding>> {
+    let embedder = self
+      .embedder
+      .get_or_try_init(|| async {
+        info!(
+          model = self.model_name.as_str(),
+          "Loading ONNX embedding model"
+        );
+        let options = InitOptions::new(self.model.clone())
+          .with_cache_dir(self.cache_dir.clone())
+          .with_show_download_progress(false);
+        let embedder = spawn_blocking(move || TextEmbedding::try_new(options)).await??;
+        Ok::<_, anyhow::Error>(Arc::new(embedder))
+      })
+      .await?;
+    Ok(Arc::clone(embedder))
+  }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OnnxEmbeddingProvider {
+  fn name(&self) -> String {
+    format!("onnx_{}", self.model_name)
+  }
+
+  fn dimensions(&self) -> usize {
+    self.dimensions
+  }
+
+  fn batch_size(&self) -> usize {
+    32
+  }
+
+  fn concurrency(&self) -> usize {
+    1
+  }
+
+  fn interval(&self) -> Duration {
+    Duration::from_secs(1)
+  }
+
+  fn job_name(&self) -> JobName {
+    JobName::GenerateOnnxEmbeddings
+  }
+
+  #[tracing::instrument(name = "OnnxEmbeddingProvider::generate", skip_all, fields(count = payloads.len()))]
+  async fn generate(&self, payloads: Vec<String>) -> Result<Vec<Vec<f32>>> {
+    let embedder = self.embedder().await?;
+    let batch_size = self.batch_size();
+    spawn_blocking(move || embedder.embed(payloads, Some(batch_size))).await?
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_new_defaults() -> Result<()> {
+    let provider = OnnxEmbeddingProvider::new(
+      &OnnxSettings {
+        model: Some("".to_string()),
+        cache_dir: None,
+      },
+      "/var/lib/lute",
+    )?;
+    assert_eq!(provider.name(), "onnx_all-minilm-l6-v2");
+    assert_eq!(provider.dimensions(), 384);
+    assert_eq!(
+      provider.cache_dir,
+      PathBuf::from("/var/lib/lute/onnx_models")
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_new_with_settings() -> Result<()> {
+    let provider = OnnxEmbeddingProvider::new(
+      &OnnxSettings {
+        model: Some("bge-base-en-v1.5".to_string()),
+        cache_dir: Some("/models".to_string()),
+      },
+      "/var/lib/lute",
+    )?;
+    assert_eq!(provider.name(), "onnx_bge-base-en-v1.5");
+    assert_eq!(provider.dimensions(), 768);
+    assert_eq!(provider.cache_dir, PathBuf::from("/models"));
+    assert!(OnnxEmbeddingProvider::new(
+      &OnnxSettings {
+        model: Some("text-embedding-3-small".to_string()),
+        cache_dir: None,
+      },
+      "/var/lib/lute",
+    )
+    .is_err());
+    Ok(())
+  }
+}
@@ -66,6 +66,10 @@ impl EmbeddingProvider for OpenAIEmbeddingProvider {
     JobName::GenerateOpenAIEmbeddings
   }
 
+  fn cost_per_million_tokens(&self) -> f64 {
+    0.13
+  }
+
   #[tracing::instrument(name = "OpenAIEmbeddingProvider::generate", skip_all, fields(count = payloads.len()))]
   async fn generate(&self, payloads: Vec<String>) -> Result<Vec<Vec<f32>>> {
     RATE_LIMITER
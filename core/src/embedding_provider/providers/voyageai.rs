@@ -99,6 +99,10 @@ impl EmbeddingProvider for VoyageAIEmbeddingProvider {
     JobName::GenerateVoyageAIEmbeddings
   }
 
+  fn cost_per_million_tokens(&self) -> f64 {
+    0.12
+  }
+
   #[tracing::instrument(name = "VoyageAIEmbeddingProvider::generate", skip_all, fields(count = payloads.len()))]
   async fn generate(&self, payloads: Vec<String>) -> Result<Vec<Vec<f32>>> {
     RATE_LIMITER
@@ -0,0 +1,75 @@
+use super::{event_repository::EventRepository, event_service::parse_entry_id};
+use crate::{albums::album_interactor::AlbumInteractor, context::ApplicationContext, proto};
+use futures::Stream;
+use std::{pin::Pin, sync::Arc};
+use tonic::{Request, Response, Status};
+
+const DEFAULT_BATCH_SIZE: u32 = 500;
+
+pub struct BootstrapService {
+  album_interactor: Arc<AlbumInteractor>,
+  event_repository: EventRepository,
+}
+
+impl BootstrapService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      album_interactor: Arc::clone(&app_context.album_interactor),
+      event_repository: EventRepository::new(Arc::clone(&app_context.sqlite_connection)),
+    }
+  }
+}
+
+#[tonic::async_trait]
+impl proto::BootstrapService for BootstrapService {
+  type BootstrapStream =
+    Pin<Box<dyn Stream<Item = Result<proto::BootstrapReply, Status>> + Send + 'static>>;
+
+  /**
+   * Streams every album read model in file name order, then the cursor to stream events after.
+   * The cursor is taken before the snapshot is read, so changes made while it streams are
+   * replayed afterwards rather than missed. Replayed events may repeat what the snapshot already
+   * has, so connectors should apply them as upserts.
+   */
+  async fn bootstrap(
+    &self,
+    request: Request<proto::BootstrapRequest>,
+  ) -> Result<Response<Self::BootstrapStream>, Status> {
+    let batch_size = request
+      .into_inner()
+      .batch_size
+      .unwrap_or(DEFAULT_BATCH_SIZE)
+      .max(1);
+    let cursor = self
+      .event_repository
+      .get_tail_entry_id()
+      .await
+      .map_err(|err| Status::internal(err.to_string()))?;
+    parse_entry_id(&cursor)?;
+    let album_interactor = Arc::clone(&self.album_interactor);
+    let output_stream = async_stream::try_stream! {
+      let mut after = None;
+      loop {
+        let albums = album_interactor
+          .find_page(after.clone(), batch_size)
+          .await
+          .map_err(|err| Status::internal(err.to_string()))?;
+        let Some(last) = albums.last() else {
+          break;
+        };
+        after = Some(last.file_name.clone());
+        yield proto::BootstrapReply {
+          albums: albums.into_iter().map(|album| album.into()).collect(),
+          cursor: None,
+        };
+      }
+      yield proto::BootstrapReply {
+        albums: vec![],
+        cursor: Some(cursor),
+      };
+    };
+    Ok(Response::new(
+      Box::pin(output_stream) as Self::BootstrapStream
+    ))
+  }
+}
@@ -1,8 +1,9 @@
 use crate::files::file_metadata::file_name::{FileName, ListRootFileName};
 use crate::lookup::{AlbumSearchLookup, ListLookupStatus};
 use crate::parser::parsed_file_data::ParsedFileData;
-use crate::profile::profile::ProfileId;
+use crate::profile::{profile::ProfileId, profile_goal::ProfileGoalKind};
 use crate::proto;
+use chrono::NaiveDateTime;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,6 +12,23 @@ use strum_macros;
 use ulid::serde::ulid_as_u128;
 use ulid::Ulid;
 
+/**
+ * Version of the event schema this build publishes. Bump it when adding an event variant, and
+ * return the new version from `Event::since_version` for the variant, so subscribers built
+ * against an older schema aren't sent events they can't decode.
+ */
+pub const EVENT_SCHEMA_VERSION: u32 = 4;
+
+/**
+ * The schema of events stored before envelopes were versioned, and of subscribers that don't
+ * declare a version
+ */
+pub const BASE_EVENT_SCHEMA_VERSION: u32 = 1;
+
+fn base_event_schema_version() -> u32 {
+  BASE_EVENT_SCHEMA_VERSION
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", content = "data")]
 pub enum Event {
@@ -61,6 +79,91 @@ pub enum Event {
     root_file_name: ListRootFileName,
     status: ListLookupStatus,
   },
+  DocumentStoreQuotaExceeded {
+    collection: String,
+    row_count: u64,
+    size_bytes: u64,
+    max_rows: Option<u64>,
+    max_bytes: Option<u64>,
+    sample_percent: Option<u32>,
+  },
+  ProfileGoalMilestoneReached {
+    profile_id: ProfileId,
+    goal_id: String,
+    kind: ProfileGoalKind,
+    target: u32,
+    progress: u32,
+    milestone: u32,
+  },
+  AlbumDeleted {
+    file_name: FileName,
+  },
+  AlbumMarkedDuplicate {
+    file_name: FileName,
+    duplicate_of: FileName,
+  },
+  EventSubscriberLagExceeded {
+    subscriber_id: String,
+    events_behind: u64,
+    oldest_unprocessed_at: Option<NaiveDateTime>,
+    max_lag_events: Option<u64>,
+    max_lag_minutes: Option<u64>,
+  },
+}
+
+impl Event {
+  /**
+   * Matches the field name of the event in the proto `Event` oneof
+   */
+  pub fn event_type(&self) -> &'static str {
+    match self {
+      Event::FileSaved { .. } => "file_saved",
+      Event::FileDeleted { .. } => "file_deleted",
+      Event::FileParsed { .. } => "file_parsed",
+      Event::FileParseFailed { .. } => "file_parse_failed",
+      Event::ProfileAlbumAdded { .. } => "profile_album_added",
+      Event::LookupAlbumSearchUpdated { .. } => "lookup_album_search_updated",
+      Event::AlbumSaved { .. } => "album_saved",
+      Event::CrawlEnqueued { .. } => "crawl_enqueued",
+      Event::CrawlFailed { .. } => "crawl_failed",
+      Event::ListSegmentSaved { .. } => "list_segment_saved",
+      Event::ListLookupStatusUpdated { .. } => "list_lookup_status_updated",
+      Event::DocumentStoreQuotaExceeded { .. } => "document_store_quota_exceeded",
+      Event::ProfileGoalMilestoneReached { .. } => "profile_goal_milestone_reached",
+      Event::AlbumDeleted { .. } => "album_deleted",
+      Event::AlbumMarkedDuplicate { .. } => "album_marked_duplicate",
+      Event::EventSubscriberLagExceeded { .. } => "event_subscriber_lag_exceeded",
+    }
+  }
+
+  /**
+   * Schema version that introduced the variant
+   */
+  pub fn since_version(&self) -> u32 {
+    match self {
+      Event::DocumentStoreQuotaExceeded { .. } | Event::ProfileGoalMilestoneReached { .. } => 2,
+      Event::AlbumDeleted { .. } | Event::AlbumMarkedDuplicate { .. } => 3,
+      Event::EventSubscriberLagExceeded { .. } => 4,
+      _ => BASE_EVENT_SCHEMA_VERSION,
+    }
+  }
+
+  pub fn file_name(&self) -> Option<&FileName> {
+    match self {
+      Event::FileSaved { file_name, .. }
+      | Event::FileDeleted { file_name, .. }
+      | Event::FileParsed { file_name, .. }
+      | Event::FileParseFailed { file_name, .. }
+      | Event::ProfileAlbumAdded { file_name, .. }
+      | Event::AlbumSaved { file_name }
+      | Event::CrawlEnqueued { file_name }
+      | Event::CrawlFailed { file_name, .. }
+      | Event::ListSegmentSaved { file_name }
+      | Event::AlbumDeleted { file_name }
+      | Event::AlbumMarkedDuplicate { file_name, .. } => Some(file_name),
+      _ => None,
+    }
+  }
 }
 
 impl From<Event> for proto::Event {
@@ -139,6 +242,67 @@ impl From<Event> for proto::Event {
           root_file_name: root_file_name.to_string(),
           status: status as i32,
         }),
+        Event::DocumentStoreQuotaExceeded {
+          collection,
+          row_count,
+          size_bytes,
+          max_rows,
+          max_bytes,
+          sample_percent,
+        } => {
+          proto::event::Event::DocumentStoreQuotaExceeded(proto::DocumentStoreQuotaExceededEvent {
+            collection,
+            row_count,
+            size_bytes,
+            max_rows,
+            max_bytes,
+            sample_percent,
+          })
+        }
+        Event::ProfileGoalMilestoneReached {
+          profile_id,
+          goal_id,
+          kind,
+          target,
+          progress,
+          milestone,
+        } => proto::event::Event::ProfileGoalMilestoneReached(
+          proto::ProfileGoalMilestoneReachedEvent {
+            profile_id: profile_id.to_string(),
+            goal_id,
+            kind: proto::ProfileGoalKind::from(kind) as i32,
+            target,
+            progress,
+            milestone,
+          },
+        ),
+        Event::AlbumDeleted { file_name } => {
+          proto::event::Event::AlbumDeleted(proto::AlbumDeletedEvent {
+            file_name: file_name.to_string(),
+          })
+        }
+        Event::AlbumMarkedDuplicate {
+          file_name,
+          duplicate_of,
+        } => proto::event::Event::AlbumMarkedDuplicate(proto::AlbumMarkedDuplicateEvent {
+          file_name: file_name.to_string(),
+          duplicate_of: duplicate_of.to_string(),
+        }),
+        Event::EventSubscriberLagExceeded {
+          subscriber_id,
+          events_behind,
+          oldest_unprocessed_at,
+          max_lag_events,
+          max_lag_minutes,
+        } => {
+          proto::event::Event::EventSubscriberLagExceeded(proto::EventSubscriberLagExceededEvent {
+            subscriber_id,
+            events_behind,
+            oldest_unprocessed_at: oldest_unprocessed_at.map(|d| d.to_string()),
+            max_lag_events,
+            max_lag_minutes,
+          })
+        }
       }),
     }
   }
@@ -158,6 +322,12 @@ pub struct EventPayload {
   pub causation_id: Option<String>,
   #[builder(setter(into), default)]
   pub metadata: Option<HashMap<String, String>>,
+  /**
+   * Schema version of the publisher that wrote the event
+   */
+  #[builder(default = "EVENT_SCHEMA_VERSION")]
+  #[serde(default = "base_event_schema_version")]
+  pub version: u32,
 }
 
 impl From<EventPayload> for proto::EventPayload {
@@ -166,6 +336,45 @@ impl From<EventPayload> for proto::EventPayload {
       event: Some(val.event.into()),
       correlation_id: val.correlation_id,
       metadata: val.metadata.unwrap_or_default(),
+      version: val.version,
+    }
+  }
+}
+
+/**
+ * The fields most connectors need, pulled out of the event so they don't have to unwrap it
+ */
+impl From<&EventPayload> for proto::FlatEvent {
+  fn from(val: &EventPayload) -> Self {
+    proto::FlatEvent {
+      event_type: val.event.event_type().to_string(),
+      file_name: val.event.file_name().map(|file_name| file_name.to_string()),
+      correlation_id: val.correlation_id.clone(),
+      album: match &val.event {
+        Event::FileParsed {
+          data: ParsedFileData::Album(album),
+          ..
+        } => Some(proto::FlatParsedAlbum {
+          name: album.name.clone(),
+          rating: album.rating,
+          rating_count: album.rating_count,
+          artist_file_names: album
+            .artists
+            .iter()
+            .map(|artist| artist.file_name.to_string())
+            .collect(),
+          primary_genres: album.primary_genres.clone(),
+          secondary_genres: album.secondary_genres.clone(),
+          descriptors: album.descriptors.clone(),
+          languages: album.languages.clone(),
+          release_date: album.release_date.map(|val| val.to_string()),
+          is_various_artists: album.is_various_artists,
+          artists: album.artists.iter().cloned().map(Into::into).collect(),
+          tracks: album.tracks.iter().cloned().map(Into::into).collect(),
+          credits: album.credits.iter().cloned().map(Into::into).collect(),
+        }),
+        _ => None,
+      },
     }
   }
 }
@@ -178,5 +387,70 @@ pub enum Topic {
   Profile,
   Lookup,
   Album,
+  Ops,
   All,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::parsed_file_data::{ParsedAlbum, ParsedArtistReference};
+  use anyhow::Result;
+
+  #[test]
+  fn test_flat_event() -> Result<()> {
+    let file_name = FileName::try_from("release/album/bjork/vulnicura")?;
+    let payload = EventPayloadBuilder::default()
+      .event(Event::FileParsed {
+        file_id: Ulid::new(),
+        file_name: file_name.clone(),
+        data: ParsedFileData::Album(ParsedAlbum {
+          name: "Vulnicura".to_string(),
+          rating: 3.8,
+          rating_count: 20000,
+          artists: vec![ParsedArtistReference {
+            name: "Björk".to_string(),
+            file_name: FileName::try_from("artist/bjork")?,
+          }],
+          primary_genres: vec!["Art Pop".to_string()],
+          secondary_genres: vec![],
+          descriptors: vec![],
+          tracks: vec![],
+          release_date: None,
+          languages: vec![],
+          credits: vec![],
+          cover_image_url: None,
+          spotify_id: None,
+          is_various_artists: false,
+        }),
+      })
+      .key(file_name.to_string())
+      .correlation_id("crawl".to_string())
+      .build()?;
+    let flat = proto::FlatEvent::from(&payload);
+    assert_eq!(flat.event_type, "file_parsed");
+    assert_eq!(flat.file_name, Some(file_name.to_string()));
+    assert_eq!(flat.correlation_id, Some("crawl".to_string()));
+    let album = flat.album.expect("Album summary missing");
+    assert_eq!(album.name, "Vulnicura");
+    assert_eq!(album.artist_file_names, vec!["artist/bjork".to_string()]);
+    assert_eq!(album.artists[0].name, "Björk");
+
+    let payload = EventPayloadBuilder::default()
+      .event(Event::DocumentStoreQuotaExceeded {
+        collection: "lookups".to_string(),
+        row_count: 1,
+        size_bytes: 1,
+        max_rows: None,
+        max_bytes: None,
+        sample_percent: None,
+      })
+      .key("lookups")
+      .build()?;
+    let flat = proto::FlatEvent::from(&payload);
+    assert_eq!(flat.event_type, "document_store_quota_exceeded");
+    assert_eq!(flat.file_name, None);
+    assert!(flat.album.is_none());
+    Ok(())
+  }
+}
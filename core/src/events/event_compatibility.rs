@@ -0,0 +1,84 @@
+use super::event::{EventPayload, BASE_EVENT_SCHEMA_VERSION};
+use std::collections::HashSet;
+
+/**
+ * What a subscriber declared it can decode when it opened a stream
+ */
+#[derive(Debug, Clone)]
+pub struct SubscriberCapabilities {
+  schema_version: u32,
+  event_types: HashSet<String>,
+}
+
+impl SubscriberCapabilities {
+  /**
+   * Subscribers that predate the handshake don't send a version, and are treated as built
+   * against the base schema. No event types means any type the version covers.
+   */
+  pub fn new(schema_version: Option<u32>, event_types: Vec<String>) -> Self {
+    Self {
+      schema_version: schema_version.unwrap_or(BASE_EVENT_SCHEMA_VERSION),
+      event_types: event_types.into_iter().collect(),
+    }
+  }
+
+  pub fn can_decode(&self, payload: &EventPayload) -> bool {
+    payload.event.since_version() <= self.schema_version
+      && (self.event_types.is_empty() || self.event_types.contains(payload.event.event_type()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    events::event::{Event, EventPayloadBuilder, EVENT_SCHEMA_VERSION},
+    files::file_metadata::file_name::FileName,
+  };
+  use anyhow::Result;
+
+  #[test]
+  fn test_can_decode() -> Result<()> {
+    let album_saved = EventPayloadBuilder::default()
+      .key("album")
+      .event(Event::AlbumSaved {
+        file_name: FileName::try_from("release/album/bjork/vulnicura")?,
+      })
+      .build()?;
+    let quota_exceeded = EventPayloadBuilder::default()
+      .key("quota")
+      .event(Event::DocumentStoreQuotaExceeded {
+        collection: "crawl_history".to_string(),
+        row_count: 10,
+        size_bytes: 1024,
+        max_rows: Some(5),
+        max_bytes: None,
+        sample_percent: None,
+      })
+      .build()?;
+
+    let legacy = SubscriberCapabilities::new(None, vec![]);
+    assert!(legacy.can_decode(&album_saved));
+    assert!(!legacy.can_decode(&quota_exceeded));
+
+    let album_deleted = EventPayloadBuilder::default()
+      .key("album")
+      .event(Event::AlbumDeleted {
+        file_name: FileName::try_from("release/album/bjork/vulnicura")?,
+      })
+      .build()?;
+    let v2 = SubscriberCapabilities::new(Some(2), vec![]);
+    assert!(v2.can_decode(&quota_exceeded));
+    assert!(!v2.can_decode(&album_deleted));
+
+    let current = SubscriberCapabilities::new(Some(EVENT_SCHEMA_VERSION), vec![]);
+    assert!(current.can_decode(&quota_exceeded));
+    assert!(current.can_decode(&album_deleted));
+
+    let albums_only =
+      SubscriberCapabilities::new(Some(EVENT_SCHEMA_VERSION), vec!["album_saved".to_string()]);
+    assert!(albums_only.can_decode(&album_saved));
+    assert!(!albums_only.can_decode(&quota_exceeded));
+    Ok(())
+  }
+}
@@ -0,0 +1,119 @@
+use super::event::Event;
+use anyhow::{anyhow, Result};
+use rusqlite::types::Value;
+use strum::EnumString;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum EventCompression {
+  Zstd,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventCompressionSettings {
+  /**
+   * Serialized events at least this large are compressed. 0 disables compression.
+   */
+  pub threshold_bytes: usize,
+  pub level: i32,
+}
+
+impl Default for EventCompressionSettings {
+  fn default() -> Self {
+    Self {
+      threshold_bytes: 16 * 1024,
+      level: 3,
+    }
+  }
+}
+
+pub struct EncodedEvent {
+  pub body: Value,
+  pub compression: Option<EventCompression>,
+  pub uncompressed_size: usize,
+  pub stored_size: usize,
+}
+
+impl EncodedEvent {
+  pub fn compression_ratio(&self) -> f64 {
+    self.uncompressed_size as f64 / self.stored_size.max(1) as f64
+  }
+}
+
+/**
+ * Serializes an event, compressing it when it crosses the size threshold. Compressed events are
+ * stored as blobs, everything else as plain JSON text.
+ */
+pub fn encode_event(event: &Event, settings: &EventCompressionSettings) -> Result<EncodedEvent> {
+  let json = serde_json::to_string(event)?;
+  let uncompressed_size = json.len();
+  if settings.threshold_bytes == 0 || uncompressed_size < settings.threshold_bytes {
+    return Ok(EncodedEvent {
+      body: Value::Text(json),
+      compression: None,
+      uncompressed_size,
+      stored_size: uncompressed_size,
+    });
+  }
+  let compressed = zstd::encode_all(json.as_bytes(), settings.level)?;
+  Ok(EncodedEvent {
+    stored_size: compressed.len(),
+    body: Value::Blob(compressed),
+    compression: Some(EventCompression::Zstd),
+    uncompressed_size,
+  })
+}
+
+pub fn decode_event(body: Value, compression: Option<EventCompression>) -> Result<Event> {
+  match (compression, body) {
+    (None, Value::Text(json)) => Ok(serde_json::from_str(&json)?),
+    (Some(EventCompression::Zstd), Value::Blob(bytes)) => Ok(serde_json::from_slice(
+      &zstd::decode_all(bytes.as_slice())?,
+    )?),
+    (compression, body) => Err(anyhow!(
+      "Unexpected {:?} event body for compression {:?}",
+      body.data_type(),
+      compression
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::files::file_metadata::file_name::FileName;
+
+  fn event() -> Result<Event> {
+    Ok(Event::CrawlFailed {
+      file_name: FileName::try_from("release/album/billy-woods/aethiopes")?,
+      error: "timeout ".repeat(100),
+    })
+  }
+
+  #[test]
+  fn test_small_events_are_not_compressed() -> Result<()> {
+    let encoded = encode_event(&event()?, &EventCompressionSettings::default())?;
+    assert_eq!(encoded.compression, None);
+    assert!(matches!(
+      decode_event(encoded.body, encoded.compression)?,
+      Event::CrawlFailed { .. }
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_large_events_round_trip_through_compression() -> Result<()> {
+    let settings = EventCompressionSettings {
+      threshold_bytes: 100,
+      level: 3,
+    };
+    let encoded = encode_event(&event()?, &settings)?;
+    assert_eq!(encoded.compression, Some(EventCompression::Zstd));
+    assert!(encoded.compression_ratio() > 1.0);
+    match decode_event(encoded.body, encoded.compression)? {
+      Event::CrawlFailed { error, .. } => assert_eq!(error, "timeout ".repeat(100)),
+      _ => panic!("unexpected event"),
+    }
+    Ok(())
+  }
+}
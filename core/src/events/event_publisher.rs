@@ -1,5 +1,6 @@
 use super::{
-  event::{EventPayload, Topic},
+  event::{EventPayload, Topic, EVENT_SCHEMA_VERSION},
+  event_compression::EventCompressionSettings,
   event_repository::EventRepository,
 };
 use crate::{settings::Settings, sqlite::SqliteConnection};
@@ -14,9 +15,14 @@ pub struct EventPublisher {
 
 impl EventPublisher {
   pub fn new(settings: Arc<Settings>, sqlite_connection: Arc<SqliteConnection>) -> Self {
+    let compression_settings = EventCompressionSettings {
+      threshold_bytes: settings.events.compression_threshold_bytes,
+      level: settings.events.compression_level,
+    };
     Self {
       settings,
-      event_repository: EventRepository::new(sqlite_connection),
+      event_repository: EventRepository::new(sqlite_connection)
+        .with_compression_settings(compression_settings),
     }
   }
 
@@ -30,7 +36,15 @@ impl EventPublisher {
       .put_many(
         payloads
           .into_iter()
-          .map(|payload| (stream.clone(), payload))
+          .map(|payload| {
+            (
+              stream.clone(),
+              EventPayload {
+                version: EVENT_SCHEMA_VERSION,
+                ..payload
+              },
+            )
+          })
           .collect(),
       )
       .await
@@ -1,15 +1,20 @@
-use super::event::{Event, EventPayload, EventPayloadBuilder, Topic};
+use super::{
+  event::{EventPayload, EventPayloadBuilder, Topic},
+  event_compression::{decode_event, encode_event, EventCompression, EventCompressionSettings},
+};
 use crate::sqlite::SqliteConnection;
 use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
 use rusqlite::{params, types::Value, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, rc::Rc, sync::Arc};
 use strum::EnumString;
-use tracing::{error, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 #[derive(Debug, Clone)]
 pub struct EventRepository {
   sqlite_connection: Arc<SqliteConnection>,
+  compression_settings: EventCompressionSettings,
 }
 
 #[derive(
@@ -39,6 +44,20 @@ pub struct EventSubscriberRow {
   pub status: EventSubscriberStatus,
 }
 
+#[derive(Debug, Clone)]
+pub struct EventSubscriberLag {
+  pub subscriber_id: String,
+  pub status: EventSubscriberStatus,
+  pub cursor: String,
+  /**
+   * Streams the subscriber last read. Lag of subscribers that haven't recorded theirs is counted
+   * across every stream.
+   */
+  pub streams: Option<Vec<Topic>>,
+  pub events_behind: u64,
+  pub oldest_unprocessed_at: Option<NaiveDateTime>,
+}
+
 #[derive(Debug, Clone)]
 pub struct EventRow {
   pub id: String,
@@ -46,6 +65,22 @@ pub struct EventRow {
   pub payload: EventPayload,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct EventCompressionStats {
+  pub compressed_event_count: usize,
+  pub uncompressed_bytes: usize,
+  pub compressed_bytes: usize,
+}
+
+impl EventCompressionStats {
+  pub fn compression_ratio(&self) -> f64 {
+    if self.compressed_bytes == 0 {
+      return 0.0;
+    }
+    self.uncompressed_bytes as f64 / self.compressed_bytes as f64
+  }
+}
+
 pub struct EventList {
   pub rows: Vec<EventRow>,
 }
@@ -63,7 +98,21 @@ fn map_event_row(row: &rusqlite::Row<'_>) -> Result<EventRow, rusqlite::Error> {
       .correlation_id(row.get::<_, Option<String>>(1)?)
       .causation_id(row.get::<_, Option<String>>(2)?)
       .event(
-        serde_json::from_str::<Event>(&row.get::<_, String>(3)?).map_err(|err| {
+        decode_event(
+          row.get::<_, Value>(3)?,
+          row
+            .get::<_, Option<String>>(7)?
+            .map(|compression| EventCompression::try_from(compression.as_str()))
+            .transpose()
+            .map_err(|err| {
+              error!(
+                message = err.to_string(),
+                "Failed to parse event compression"
+              );
+              rusqlite::Error::ExecuteReturnedResults
+            })?,
+        )
+        .map_err(|err| {
           error!(message = err.to_string(), "Failed to deserialize event");
           rusqlite::Error::ExecuteReturnedResults
         })?,
@@ -74,6 +123,7 @@ fn map_event_row(row: &rusqlite::Row<'_>) -> Result<EventRow, rusqlite::Error> {
           .map(|metadata: String| serde_json::from_str(&metadata).unwrap_or(HashMap::new())),
       )
       .key(row.get::<_, Option<String>>(6)?.unwrap_or("".to_string()))
+      .version(row.get::<_, u32>(8)?)
       .build()
       .map_err(|err| {
         error!(message = err.to_string(), "Failed to build event payload");
@@ -88,21 +138,49 @@ fn map_event_row(row: &rusqlite::Row<'_>) -> Result<EventRow, rusqlite::Error> {
 
 impl EventRepository {
   pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
-    Self { sqlite_connection }
+    Self {
+      sqlite_connection,
+      compression_settings: EventCompressionSettings::default(),
+    }
+  }
+
+  pub fn with_compression_settings(mut self, settings: EventCompressionSettings) -> Self {
+    self.compression_settings = settings;
+    self
   }
 
   pub async fn put_many(&self, events: Vec<(Topic, EventPayload)>) -> Result<()> {
+    let events = events
+      .into_iter()
+      .map(|(stream, payload)| {
+        let encoded = encode_event(&payload.event, &self.compression_settings)?;
+        if let Some(compression) = encoded.compression {
+          debug!(
+            key = payload.key.as_str(),
+            compression = compression.to_string(),
+            uncompressed_size = encoded.uncompressed_size,
+            stored_size = encoded.stored_size,
+            ratio = encoded.compression_ratio(),
+            "Compressed event payload"
+          );
+        }
+        Ok((stream, payload, encoded))
+      })
+      .collect::<Result<Vec<_>>>()?;
     self
       .sqlite_connection
       .write()
       .await?
       .interact(move |conn| {
         let transaction = conn.transaction()?;
-        for (stream, payload) in events {
+        for (stream, payload, encoded) in events {
           let mut statement = transaction.prepare(
             "
-            INSERT INTO events (correlation_id, causation_id, event, metadata, stream, key) 
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            INSERT INTO events (
+              correlation_id, causation_id, event, metadata, stream, key, compression,
+              uncompressed_size, version
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             ON CONFLICT (stream, key) DO UPDATE SET
               id = excluded.id,
               correlation_id = excluded.correlation_id,
@@ -111,18 +189,25 @@ impl EventRepository {
               metadata = excluded.metadata,
               stream = excluded.stream,
               key = excluded.key,
+              compression = excluded.compression,
+              uncompressed_size = excluded.uncompressed_size,
+              version = excluded.version,
               created_at = excluded.created_at
             ",
           )?;
           statement.execute((
             &payload.correlation_id,
             &payload.causation_id,
-            serde_json::to_string(&payload.event)
-              .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+            encoded.body,
             serde_json::to_string(&payload.metadata)
               .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
             &stream.to_string(),
             &payload.key,
+            encoded
+              .compression
+              .map(|compression| compression.to_string()),
+            encoded.uncompressed_size as i64,
+            payload.version,
           ))?;
         }
         transaction.commit()?;
@@ -149,7 +234,8 @@ impl EventRepository {
         let row = conn
           .query_row(
             "
-            SELECT id, correlation_id, causation_id, event, metadata, stream, key
+            SELECT
+              id, correlation_id, causation_id, event, metadata, stream, key, compression, version
             FROM events
             WHERE id = ?1
             ",
@@ -219,6 +305,45 @@ impl EventRepository {
       })?
   }
 
+  pub async fn get_compression_stats(&self) -> Result<EventCompressionStats> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        conn.query_row(
+          "
+          SELECT COUNT(*), COALESCE(SUM(uncompressed_size), 0), COALESCE(SUM(LENGTH(event)), 0)
+          FROM events
+          WHERE compression IS NOT NULL
+          ",
+          [],
+          |row| {
+            Ok(EventCompressionStats {
+              compressed_event_count: row.get::<_, i64>(0)? as usize,
+              uncompressed_bytes: row.get::<_, i64>(1)? as usize,
+              compressed_bytes: row.get::<_, i64>(2)? as usize,
+            })
+          },
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get event compression stats"
+        );
+        anyhow!("Failed to get event compression stats")
+      })?
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get event compression stats"
+        );
+        anyhow!("Failed to get event compression stats")
+      })
+  }
+
   pub async fn count_events_without_key(&self) -> Result<usize> {
     self
       .sqlite_connection
@@ -297,6 +422,109 @@ impl EventRepository {
       })?
   }
 
+  /**
+   * Records the streams a subscriber reads, so its lag can be measured against them
+   */
+  #[instrument(skip(self))]
+  pub async fn set_subscriber_streams(&self, subscriber_id: &str, streams: &[Topic]) -> Result<()> {
+    let subscriber_id = subscriber_id.to_string();
+    let streams = streams
+      .iter()
+      .map(|s| s.to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "UPDATE event_subscribers SET streams = ?2 WHERE id = ?1",
+          params![subscriber_id, streams],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set subscriber streams");
+        anyhow!("Failed to set subscriber streams")
+      })?
+  }
+
+  /**
+   * How far each subscriber is behind the streams it reads
+   */
+  pub async fn get_subscriber_lags(&self) -> Result<Vec<EventSubscriberLag>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement =
+          conn.prepare("SELECT id, cursor, status, streams FROM event_subscribers ORDER BY id")?;
+        let subscribers = statement
+          .query_map([], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, u32>(1)?,
+              row.get::<_, u32>(2)?,
+              row.get::<_, Option<String>>(3)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+
+        let mut lags = vec![];
+        for (subscriber_id, cursor, status, streams) in subscribers {
+          let streams = streams
+            .map(|streams| {
+              streams
+                .split(',')
+                .map(Topic::try_from)
+                .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+          let (events_behind, oldest_unprocessed_at) = match &streams {
+            Some(streams) if !streams.contains(&Topic::All) => conn.query_row(
+              "
+              SELECT COUNT(*), MIN(created_at)
+              FROM events
+              WHERE stream IN rarray(?1) AND id > ?2
+              ",
+              params![
+                Rc::new(
+                  streams
+                    .iter()
+                    .map(|s| Value::from(s.to_string()))
+                    .collect::<Vec<_>>()
+                ),
+                cursor
+              ],
+              |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+            )?,
+            _ => conn.query_row(
+              "SELECT COUNT(*), MIN(created_at) FROM events WHERE id > ?1",
+              params![cursor],
+              |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+            )?,
+          };
+          lags.push(EventSubscriberLag {
+            subscriber_id,
+            status: EventSubscriberStatus::try_from(status)?,
+            cursor: cursor.to_string(),
+            streams,
+            events_behind,
+            oldest_unprocessed_at,
+          });
+        }
+        Ok::<_, anyhow::Error>(lags)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get subscriber lags");
+        anyhow!("Failed to get subscriber lags")
+      })?
+  }
+
   pub async fn get_stream_tails(&self) -> Result<Vec<(Topic, String)>> {
     self
       .sqlite_connection
@@ -409,8 +637,21 @@ impl EventRepository {
     subscriber_id: &str,
     count: usize,
   ) -> Result<EventList> {
-    let subscriber_id = subscriber_id.to_string();
-    let cursor = self.get_cursor(&subscriber_id).await?;
+    let cursor = self.get_cursor(subscriber_id).await?;
+    self.get_events_after_entry(streams, &cursor, count).await
+  }
+
+  /**
+   * Events after an entry id, read without a subscriber so no cursor moves
+   */
+  #[instrument(skip(self))]
+  pub async fn get_events_after_entry(
+    &self,
+    streams: &Vec<Topic>,
+    entry_id: &str,
+    count: usize,
+  ) -> Result<EventList> {
+    let cursor = entry_id.to_string();
     let is_global = streams.iter().any(|s| s == &Topic::All);
     let stream_tags = streams
       .iter()
@@ -424,7 +665,8 @@ impl EventRepository {
         if is_global {
           let mut statement = conn.prepare(
             "
-            SELECT id, correlation_id, causation_id, event, metadata, stream, key
+            SELECT
+              id, correlation_id, causation_id, event, metadata, stream, key, compression, version
             FROM events
             WHERE id > ?1
             ORDER BY id ASC
@@ -438,7 +680,8 @@ impl EventRepository {
         } else {
           let mut statement = conn.prepare(
             "
-            SELECT id, correlation_id, causation_id, event, metadata, stream, key
+            SELECT
+              id, correlation_id, causation_id, event, metadata, stream, key, compression, version
             FROM events
             WHERE stream IN rarray(?1) AND id > ?2
             ORDER BY id ASC
@@ -461,6 +704,64 @@ impl EventRepository {
       })?
   }
 
+  /**
+   * Id of the last entry, the tail across all streams
+   */
+  pub async fn get_tail_entry_id(&self) -> Result<String> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare("SELECT COALESCE(MAX(id), 0) FROM events")?;
+        Ok(
+          statement
+            .query_row([], |row| row.get::<_, i64>(0))?
+            .to_string(),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get tail entry id");
+        anyhow!("Failed to get tail entry id")
+      })?
+  }
+
+  /**
+   * Cursor that reads from the first entry created at or after the timestamp. Entries written
+   * later are past the tail, so a timestamp after the last entry gives the tail.
+   */
+  pub async fn get_cursor_at(&self, timestamp: NaiveDateTime) -> Result<String> {
+    let timestamp = timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+    let first_id = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT id
+          FROM events
+          WHERE created_at >= ?1
+          ORDER BY created_at ASC, id ASC
+          LIMIT 1
+          ",
+        )?;
+        statement
+          .query_row([timestamp], |row| row.get::<_, i64>(0))
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get cursor at timestamp");
+        anyhow!("Failed to get cursor at timestamp")
+      })??;
+    match first_id {
+      Some(id) => Ok((id - 1).to_string()),
+      None => self.get_tail_entry_id().await,
+    }
+  }
+
   #[instrument(skip(self))]
   pub async fn set_subscriber_status(
     &self,
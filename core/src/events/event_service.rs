@@ -1,5 +1,10 @@
-use super::event_repository::{EventRepository, EventSubscriberRow, EventSubscriberStatus};
+use super::{
+  event::{Topic, EVENT_SCHEMA_VERSION},
+  event_compatibility::SubscriberCapabilities,
+  event_repository::{EventRepository, EventRow, EventSubscriberRow, EventSubscriberStatus},
+};
 use crate::{context::ApplicationContext, proto};
+use chrono::DateTime;
 use futures::{try_join, Stream};
 use std::{pin::Pin, sync::Arc, time::Duration};
 use tokio::time::sleep;
@@ -33,6 +38,29 @@ impl From<EventSubscriberRow> for proto::EventSubscriberSnapshot {
   }
 }
 
+fn to_stream_item(row: EventRow, stream_id: &Topic, flatten: bool) -> proto::EventStreamItem {
+  proto::EventStreamItem {
+    entry_id: row.id.clone(),
+    flat: flatten.then(|| (&row.payload).into()),
+    payload: Some(row.payload.into()),
+    stream_id: stream_id.to_string(),
+    timestamp: row
+      .id
+      .clone()
+      .split('-')
+      .next()
+      .expect("Invalid event stream item ID")
+      .parse::<u64>()
+      .expect("Invalid event stream item ID"),
+  }
+}
+
+pub fn parse_entry_id(entry_id: &str) -> Result<i64, Status> {
+  entry_id
+    .parse::<i64>()
+    .map_err(|_| Status::invalid_argument(format!("Invalid entry id: {}", entry_id)))
+}
+
 pub struct EventService {
   event_repository: EventRepository,
 }
@@ -49,6 +77,8 @@ impl EventService {
 impl proto::EventService for EventService {
   type StreamStream =
     Pin<Box<dyn Stream<Item = Result<proto::EventStreamReply, Status>> + Send + 'static>>;
+  type ReplayStream =
+    Pin<Box<dyn Stream<Item = Result<proto::EventStreamReply, Status>> + Send + 'static>>;
 
   async fn set_cursor(
     &self,
@@ -80,10 +110,11 @@ impl proto::EventService for EventService {
     &self,
     _: Request<()>,
   ) -> Result<Response<proto::GetEventsMonitorReply>, Status> {
-    let (event_count, subscribers, stream_tails) = try_join!(
+    let (event_count, subscribers, stream_tails, compression_stats) = try_join!(
       self.event_repository.count_events(),
       self.event_repository.get_subscribers(),
       self.event_repository.get_stream_tails(),
+      self.event_repository.get_compression_stats(),
     )
     .map_err(|err| Status::internal(err.to_string()))?;
     let monitor = proto::EventsMonitor {
@@ -99,6 +130,12 @@ impl proto::EventService for EventService {
           tail,
         })
         .collect(),
+      compression: Some(proto::EventCompressionStats {
+        compressed_event_count: compression_stats.compressed_event_count as u32,
+        uncompressed_bytes: compression_stats.uncompressed_bytes as u64,
+        compressed_bytes: compression_stats.compressed_bytes as u64,
+        compression_ratio: compression_stats.compression_ratio(),
+      }),
     };
 
     let reply = proto::GetEventsMonitorReply {
@@ -136,6 +173,7 @@ impl proto::EventService for EventService {
     let mut input_stream: Streaming<proto::EventStreamRequest> = request.into_inner();
     let event_repository = self.event_repository.clone();
     let output_stream = async_stream::try_stream! {
+      let mut recorded_stream_id = None;
       while let Ok(Some(event_stream_request)) = input_stream.message().await {
         loop {
           let stream_id = super::event::Topic::try_from(event_stream_request.stream_id.as_str())
@@ -147,6 +185,15 @@ impl proto::EventService for EventService {
             )
             .await
             .map_err(|err| Status::internal(err.to_string()))?;
+            if recorded_stream_id.as_ref() != Some(&stream_id) {
+              event_repository.set_subscriber_streams(
+                &event_stream_request.subscriber_id,
+                &[stream_id.clone()],
+              )
+              .await
+              .map_err(|err| Status::internal(err.to_string()))?;
+              recorded_stream_id = Some(stream_id.clone());
+            }
           }
           let event_list = event_repository.get_events_after_cursor(
             &vec![stream_id.clone()],
@@ -156,21 +203,21 @@ impl proto::EventService for EventService {
           .await
           .map_err(|err| Status::internal(err.to_string()))?;
 
+          // Events the subscriber can't decode are skipped, but still move the cursor along
+          let capabilities = SubscriberCapabilities::new(
+            event_stream_request.schema_version,
+            event_stream_request.supported_event_types.clone(),
+          );
           let tail_cursor = event_list.tail_cursor().clone();
           if let Some(tail_cursor) = tail_cursor {
             yield proto::EventStreamReply {
-              items: event_list.rows.into_iter().map(|row| {
-                proto::EventStreamItem {
-                  entry_id: row.id.clone(),
-                  payload: Some(row.payload.into()),
-                  stream_id: stream_id.to_string(),
-                  timestamp: row.id.clone().split('-').next()
-                    .expect("Invalid event stream item ID")
-                    .parse::<u64>()
-                    .expect("Invalid event stream item ID")
-                }
-              }).collect(),
+              items: event_list.rows.into_iter()
+                .filter(|row| capabilities.can_decode(&row.payload))
+                .map(|row| {
+                  to_stream_item(row, &stream_id, event_stream_request.flatten)
+                }).collect(),
               cursor: tail_cursor.clone(),
+              schema_version: EVENT_SCHEMA_VERSION,
             };
             break;
           }
@@ -180,4 +227,73 @@ impl proto::EventService for EventService {
     };
     Ok(Response::new(Box::pin(output_stream) as Self::StreamStream))
   }
+
+  /**
+   * Replays a stream from an entry or a point in time up to its tail when the replay started.
+   * The replay reads with its own cursor, so subscriber cursors don't move. Events filtered out
+   * by type or schema version aren't sent, and batches left empty by the filter are skipped.
+   */
+  async fn replay(
+    &self,
+    request: Request<proto::ReplayEventsRequest>,
+  ) -> Result<Response<Self::ReplayStream>, Status> {
+    let request = request.into_inner();
+    let stream_id = Topic::try_from(request.stream_id.as_str())
+      .map_err(|err| Status::invalid_argument(err.to_string()))?;
+    let mut cursor = match request.start {
+      Some(proto::replay_events_request::Start::AfterEntryId(entry_id)) => {
+        parse_entry_id(&entry_id)?;
+        entry_id
+      }
+      Some(proto::replay_events_request::Start::SinceTimestamp(timestamp)) => {
+        let timestamp = DateTime::from_timestamp(timestamp as i64, 0)
+          .ok_or_else(|| Status::invalid_argument("Invalid timestamp"))?
+          .naive_utc();
+        self
+          .event_repository
+          .get_cursor_at(timestamp)
+          .await
+          .map_err(|err| Status::internal(err.to_string()))?
+      }
+      None => "0".to_string(),
+    };
+    let tail = parse_entry_id(
+      &self
+        .event_repository
+        .get_tail_entry_id()
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?,
+    )?;
+    let capabilities = SubscriberCapabilities::new(request.schema_version, request.event_types);
+    let max_batch_size = request.max_batch_size.unwrap_or(10) as usize;
+    let flatten = request.flatten;
+    let event_repository = self.event_repository.clone();
+    let output_stream = async_stream::try_stream! {
+      while parse_entry_id(&cursor)? < tail {
+        let event_list = event_repository
+          .get_events_after_entry(&vec![stream_id.clone()], &cursor, max_batch_size)
+          .await
+          .map_err(|err| Status::internal(err.to_string()))?;
+        let Some(tail_cursor) = event_list.tail_cursor() else {
+          break;
+        };
+        cursor = parse_entry_id(&tail_cursor)?.min(tail).to_string();
+        let items = event_list
+          .rows
+          .into_iter()
+          .filter(|row| row.id.parse::<i64>().is_ok_and(|id| id <= tail))
+          .filter(|row| capabilities.can_decode(&row.payload))
+          .map(|row| to_stream_item(row, &stream_id, flatten))
+          .collect::<Vec<_>>();
+        if !items.is_empty() {
+          yield proto::EventStreamReply {
+            items,
+            cursor: cursor.clone(),
+            schema_version: EVENT_SCHEMA_VERSION,
+          };
+        }
+      }
+    };
+    Ok(Response::new(Box::pin(output_stream) as Self::ReplayStream))
+  }
 }
@@ -52,6 +52,13 @@ impl EventSubscriberInteractor {
       .await
   }
 
+  pub async fn set_streams(&self, topics: &[Topic]) -> Result<()> {
+    self
+      .event_repository
+      .set_subscriber_streams(&self.subscriber_id, topics)
+      .await
+  }
+
   pub async fn delete_cursor(&self) -> Result<()> {
     self
       .event_repository
@@ -355,7 +362,10 @@ impl EventSubscriber {
   }
 
   pub async fn run(&self) -> Result<()> {
+    // The subscriber's row only exists once it has a cursor, so its streams are recorded then
+    let mut streams_recorded = false;
     loop {
+      self.app_context.subscriber_heartbeats.beat(&self.id);
       if self
         .interactor
         .get_status()
@@ -371,6 +381,10 @@ impl EventSubscriber {
           );
         })? {
           self.interactor.set_cursor(&tail_cursor).await?;
+          if !streams_recorded {
+            self.interactor.set_streams(&self.topics).await?;
+            streams_recorded = true;
+          }
         }
       }
       self.sleep().await;
@@ -1,17 +1,22 @@
-use super::event_repository::{EventRepository, EventSubscriberStatus};
+use super::{
+  event::{Event, EventPayloadBuilder, Topic},
+  event_repository::{EventRepository, EventSubscriberLag, EventSubscriberStatus},
+};
 use crate::{
   context::ApplicationContext,
   job_executor,
   scheduler::{
     job_name::JobName,
-    scheduler::{JobExecutorFn, JobProcessorBuilder},
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
     scheduler_repository::Job,
   },
+  settings::EventSettings,
 };
 use anyhow::Result;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ChangeEventSubscriberStatusJobParameters {
@@ -33,6 +38,60 @@ async fn change_subscriber_status(job: Job, app_context: Arc<ApplicationContext>
   Ok(())
 }
 
+pub fn is_lag_exceeded(
+  lag: &EventSubscriberLag,
+  settings: &EventSettings,
+  now: NaiveDateTime,
+) -> bool {
+  settings
+    .max_lag_events
+    .is_some_and(|max_lag_events| lag.events_behind > max_lag_events)
+    || settings
+      .max_lag_minutes
+      .zip(lag.oldest_unprocessed_at)
+      .is_some_and(|(max_lag_minutes, oldest_unprocessed_at)| {
+        now - oldest_unprocessed_at > TimeDelta::try_minutes(max_lag_minutes as i64).unwrap()
+      })
+}
+
+/**
+ * Alerts on every running subscriber over a lag threshold. Paused subscribers are expected to
+ * fall behind, so they're left out.
+ */
+async fn check_subscriber_lag(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let settings = &app_context.settings.events;
+  let event_repository = EventRepository::new(Arc::clone(&app_context.sqlite_connection));
+  let now = Utc::now().naive_utc();
+  for lag in event_repository.get_subscriber_lags().await? {
+    if lag.status != EventSubscriberStatus::Running || !is_lag_exceeded(&lag, settings, now) {
+      continue;
+    }
+    warn!(
+      subscriber_id = lag.subscriber_id.as_str(),
+      events_behind = lag.events_behind,
+      oldest_unprocessed_at = ?lag.oldest_unprocessed_at,
+      "Event subscriber lag exceeded its threshold"
+    );
+    app_context
+      .event_publisher
+      .publish(
+        Topic::Ops,
+        EventPayloadBuilder::default()
+          .key(format!("subscriber_lag:{}", lag.subscriber_id))
+          .event(Event::EventSubscriberLagExceeded {
+            subscriber_id: lag.subscriber_id,
+            events_behind: lag.events_behind,
+            oldest_unprocessed_at: lag.oldest_unprocessed_at,
+            max_lag_events: settings.max_lag_events,
+            max_lag_minutes: settings.max_lag_minutes,
+          })
+          .build()?,
+      )
+      .await?;
+  }
+  Ok(())
+}
+
 pub async fn setup_event_subscriber_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
   app_context
     .scheduler
@@ -44,5 +103,63 @@ pub async fn setup_event_subscriber_jobs(app_context: Arc<ApplicationContext>) -
         .build()?,
     )
     .await;
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CheckEventSubscriberLag)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(check_subscriber_lag))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::CheckEventSubscriberLag)
+        .interval(
+          TimeDelta::try_minutes(app_context.settings.events.lag_check_interval_minutes as i64)
+            .unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_lag_exceeded() {
+    let now = Utc::now().naive_utc();
+    let lag = |events_behind, oldest_minutes_ago: Option<i64>| EventSubscriberLag {
+      subscriber_id: "album".to_string(),
+      status: EventSubscriberStatus::Running,
+      cursor: "10".to_string(),
+      streams: Some(vec![Topic::Album]),
+      events_behind,
+      oldest_unprocessed_at: oldest_minutes_ago
+        .map(|minutes| now - TimeDelta::try_minutes(minutes).unwrap()),
+    };
+    let settings = EventSettings {
+      max_lag_events: Some(100),
+      max_lag_minutes: Some(60),
+      ..Default::default()
+    };
+    assert!(!is_lag_exceeded(&lag(0, None), &settings, now));
+    assert!(!is_lag_exceeded(&lag(50, Some(30)), &settings, now));
+    assert!(is_lag_exceeded(&lag(150, Some(30)), &settings, now));
+    assert!(is_lag_exceeded(&lag(50, Some(90)), &settings, now));
+    assert!(!is_lag_exceeded(
+      &lag(150, Some(90)),
+      &EventSettings::default(),
+      now
+    ));
+  }
+}
@@ -1,6 +1,10 @@
+pub mod bootstrap_service;
 pub mod event;
+pub mod event_compatibility;
+pub mod event_compression;
 pub mod event_publisher;
 pub mod event_repository;
 pub mod event_service;
 pub mod event_subscriber;
 pub mod event_subscriber_jobs;
+pub mod read_model_replay;
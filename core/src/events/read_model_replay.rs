@@ -0,0 +1,235 @@
+use super::{
+  event::{Event, Topic},
+  event_repository::{EventRepository, EventRow},
+};
+use crate::{
+  albums::album_read_model::AlbumReadModel, context::ApplicationContext,
+  parser::parsed_file_data::ParsedFileData,
+};
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+const PROGRESS_KEY: &str = "read_model_replay:progress";
+const ABORT_KEY: &str = "read_model_replay:abort";
+
+#[derive(Clone, Debug)]
+pub struct ReadModelReplayParameters {
+  pub batch_size: u32,
+  pub throttle: Duration,
+  /**
+   * Picks up after the last checkpoint of a previous replay instead of the start of the streams
+   */
+  pub resume: bool,
+}
+
+impl Default for ReadModelReplayParameters {
+  fn default() -> Self {
+    Self {
+      batch_size: 500,
+      throttle: Duration::from_millis(100),
+      resume: false,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReadModelReplayProgress {
+  /**
+   * Id of the last replayed event, the checkpoint a resumed replay starts after
+   */
+  pub cursor: String,
+  pub done: u32,
+  pub total: u32,
+  pub running: bool,
+  pub aborted: bool,
+  pub error: Option<String>,
+}
+
+fn replay_streams() -> Vec<Topic> {
+  vec![Topic::Parser, Topic::File]
+}
+
+pub async fn get_read_model_replay_progress(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Option<ReadModelReplayProgress>> {
+  app_context.kv.get(PROGRESS_KEY).await
+}
+
+/**
+ * Marks a read model replay left running by a previous process as interrupted
+ */
+pub async fn recover_read_model_replay_progress(
+  app_context: Arc<ApplicationContext>,
+) -> Result<()> {
+  if let Some(mut progress) = get_read_model_replay_progress(Arc::clone(&app_context)).await? {
+    if progress.running {
+      warn!("Read model replay was interrupted by a restart");
+      progress.running = false;
+      progress.error = Some("Interrupted by a restart".to_string());
+      app_context.kv.set(PROGRESS_KEY, progress, None).await?;
+    }
+  }
+  Ok(())
+}
+
+/**
+ * Asks a running replay to stop after its current batch, which stays checkpointed so the replay
+ * can be resumed
+ */
+pub async fn abort_read_model_replay(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context.kv.set(ABORT_KEY, true, None).await
+}
+
+/**
+ * Applies a batch in stream order. Consecutive parsed albums are written together, deletions
+ * flush them first so an album deleted and parsed again ends up saved.
+ */
+async fn replay_batch(app_context: &Arc<ApplicationContext>, rows: Vec<EventRow>) -> Result<()> {
+  let mut albums: Vec<AlbumReadModel> = vec![];
+  for row in rows {
+    match row.payload.event {
+      Event::FileParsed {
+        file_name,
+        data: ParsedFileData::Album(parsed_album),
+        ..
+      } => {
+        albums.push(AlbumReadModel::from_parsed_album(&file_name, parsed_album));
+      }
+      Event::FileParsed {
+        file_name,
+        data: ParsedFileData::Artist(parsed_artist),
+        ..
+      } => {
+        app_context
+          .artist_interactor
+          .put_alternate_names(file_name, parsed_artist.alternate_names)
+          .await?;
+      }
+      Event::FileDeleted { file_name, .. } => {
+        if !albums.is_empty() {
+          app_context
+            .album_interactor
+            .put_many(std::mem::take(&mut albums))
+            .await?;
+        }
+        if app_context
+          .album_interactor
+          .find(&file_name)
+          .await?
+          .is_some()
+        {
+          app_context.album_interactor.delete(&file_name).await?;
+        }
+      }
+      _ => {}
+    }
+  }
+  if !albums.is_empty() {
+    app_context.album_interactor.put_many(albums).await?;
+  }
+  Ok(())
+}
+
+async fn replay_batches(
+  app_context: &Arc<ApplicationContext>,
+  event_repository: &EventRepository,
+  parameters: &ReadModelReplayParameters,
+  progress: &mut ReadModelReplayProgress,
+) -> Result<()> {
+  app_context.album_interactor.setup_search_index().await?;
+  let streams = replay_streams();
+  loop {
+    if app_context
+      .kv
+      .get::<bool>(ABORT_KEY)
+      .await?
+      .unwrap_or(false)
+    {
+      warn!(
+        done = progress.done,
+        cursor = progress.cursor,
+        "Read model replay aborted"
+      );
+      progress.aborted = true;
+      return Ok(());
+    }
+    let events = event_repository
+      .get_events_after_entry(&streams, &progress.cursor, parameters.batch_size as usize)
+      .await?;
+    let Some(cursor) = events.tail_cursor() else {
+      return Ok(());
+    };
+    let batch_size = events.rows.len() as u32;
+    replay_batch(app_context, events.rows).await?;
+    progress.cursor = cursor;
+    progress.done += batch_size;
+    app_context
+      .kv
+      .set(PROGRESS_KEY, progress.clone(), None)
+      .await?;
+    if batch_size < parameters.batch_size {
+      return Ok(());
+    }
+    sleep(parameters.throttle).await;
+  }
+}
+
+/**
+ * Rebuilds the album read models, artist alternate names and album search index by replaying the
+ * parser and file streams through the same interactors their subscribers use, so a lost database
+ * can be restored without crawling again. Existing read models are upserted rather than cleared.
+ * The checkpoint is recorded after every batch.
+ */
+pub async fn run_read_model_replay(
+  app_context: Arc<ApplicationContext>,
+  parameters: ReadModelReplayParameters,
+) -> Result<()> {
+  app_context.kv.delete(ABORT_KEY).await?;
+  let event_repository = EventRepository::new(Arc::clone(&app_context.sqlite_connection));
+  let previous = if parameters.resume {
+    get_read_model_replay_progress(Arc::clone(&app_context)).await?
+  } else {
+    None
+  };
+  let counts = event_repository.count_events_each_topic().await?;
+  let mut progress = ReadModelReplayProgress {
+    cursor: previous
+      .as_ref()
+      .map(|previous| previous.cursor.clone())
+      .unwrap_or_else(|| "0".to_string()),
+    done: previous.map(|previous| previous.done).unwrap_or(0),
+    total: replay_streams()
+      .iter()
+      .map(|stream| counts.get(stream).copied().unwrap_or(0) as u32)
+      .sum(),
+    running: true,
+    ..Default::default()
+  };
+  app_context
+    .kv
+    .set(PROGRESS_KEY, progress.clone(), None)
+    .await?;
+  info!(
+    total = progress.total,
+    cursor = progress.cursor,
+    batch_size = parameters.batch_size,
+    "Replaying read models"
+  );
+  let result = replay_batches(&app_context, &event_repository, &parameters, &mut progress).await;
+  if let Err(e) = &result {
+    error!(
+      error = e.to_string(),
+      done = progress.done,
+      cursor = progress.cursor,
+      "Read model replay failed"
+    );
+    progress.error = Some(e.to_string());
+  }
+  progress.running = false;
+  app_context.kv.set(PROGRESS_KEY, progress, None).await?;
+  app_context.kv.delete(ABORT_KEY).await?;
+  result
+}
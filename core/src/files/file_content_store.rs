@@ -1,9 +1,17 @@
 use super::file_metadata::file_name::FileName;
 use crate::settings::ContentStoreSettings;
 use anyhow::Result;
-use s3::{creds::Credentials, Bucket};
+use chrono::{DateTime, Utc};
+use s3::{creds::Credentials, error::S3Error, Bucket};
 use tracing::{error, info, instrument, warn};
 
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+  pub file_name: FileName,
+  pub size_bytes: u64,
+  pub last_modified: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FileContentStore {
   bucket: Bucket,
@@ -69,6 +77,43 @@ impl FileContentStore {
     })
   }
 
+  /**
+   * Stores non-page content, like images, under a key outside the crawled file names
+   */
+  #[instrument(skip(self, content))]
+  pub async fn put_object(&self, key: &str, content: &[u8], content_type: &str) -> Result<()> {
+    self
+      .bucket
+      .put_object_with_content_type(key, content, content_type)
+      .await
+      .map_err(|e| {
+        error!("Failed to save object to content store: {:?}", e);
+        e
+      })?;
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+    match self.bucket.get_object(key).await {
+      Ok(response) => Ok(Some(response.bytes().to_vec())),
+      Err(S3Error::HttpFailWithBody(404, _)) => Ok(None),
+      Err(e) => {
+        error!("Failed to read object from content store: {:?}", e);
+        Err(e.into())
+      }
+    }
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_object(&self, key: &str) -> Result<()> {
+    self.bucket.delete_object(key).await.map_err(|e| {
+      error!("Failed to delete object from content store: {:?}", e);
+      e
+    })?;
+    Ok(())
+  }
+
   #[instrument(skip(self))]
   pub async fn delete(&self, file_name: &FileName) -> Result<()> {
     self.bucket.delete_object(file_name.to_string()).await?;
@@ -79,8 +124,11 @@ impl FileContentStore {
     Ok(())
   }
 
+  /**
+   * Current content of every crawled file, archived versions live outside the listed prefixes
+   */
   #[instrument(skip(self))]
-  pub async fn list_files(&self) -> Result<Vec<FileName>> {
+  pub async fn list_stored_files(&self) -> Result<Vec<StoredFile>> {
     let mut objects = self.bucket.list("release/".to_string(), None).await?;
     objects.append(&mut self.bucket.list("charts/".to_string(), None).await?);
     objects.append(&mut self.bucket.list("artist/".to_string(), None).await?);
@@ -88,15 +136,15 @@ impl FileContentStore {
     Ok(
       objects
         .into_iter()
-        .flat_map(|page| {
-          page
-            .contents
-            .into_iter()
-            .map(|object| FileName::try_from(object.key))
-            .collect::<Vec<Result<FileName>>>()
-        })
-        .filter_map(|o| match o {
-          Ok(file_name) => Some(file_name),
+        .flat_map(|page| page.contents)
+        .filter_map(|object| match FileName::try_from(object.key) {
+          Ok(file_name) => Some(StoredFile {
+            file_name,
+            size_bytes: object.size,
+            last_modified: DateTime::parse_from_rfc3339(&object.last_modified)
+              .map(|last_modified| last_modified.with_timezone(&Utc))
+              .unwrap_or_else(|_| Utc::now()),
+          }),
           Err(e) => {
             warn!("Invalid file name: {:?}", e);
             None
@@ -105,4 +153,15 @@ impl FileContentStore {
         .collect(),
     )
   }
+
+  pub async fn list_files(&self) -> Result<Vec<FileName>> {
+    Ok(
+      self
+        .list_stored_files()
+        .await?
+        .into_iter()
+        .map(|stored_file| stored_file.file_name)
+        .collect(),
+    )
+  }
 }
@@ -0,0 +1,400 @@
+use super::{file_content_store::StoredFile, file_metadata::file_name::FileName};
+use crate::sqlite::SqliteConnection;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+/**
+ * Content of a file held in the content store. The current version is stored under the file name,
+ * archived versions under their own keys.
+ */
+#[derive(Clone, Debug)]
+pub struct FileContentVersion {
+  pub key: String,
+  pub file_name: FileName,
+  pub saved_at: DateTime<Utc>,
+  pub size_bytes: u64,
+  pub parsed_at: Option<DateTime<Utc>>,
+}
+
+impl FileContentVersion {
+  pub fn is_current(&self) -> bool {
+    self.key == self.file_name.to_string()
+  }
+}
+
+pub struct FileContentTotals {
+  pub versions: u64,
+  pub size_bytes: u64,
+}
+
+type VersionRow = (String, String, DateTime<Utc>, u64, Option<DateTime<Utc>>);
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<VersionRow> {
+  Ok((
+    row.get::<_, String>(0)?,
+    row.get::<_, String>(1)?,
+    row.get::<_, DateTime<Utc>>(2)?,
+    row.get::<_, u64>(3)?,
+    row.get::<_, Option<DateTime<Utc>>>(4)?,
+  ))
+}
+
+fn to_versions(rows: Vec<VersionRow>) -> Result<Vec<FileContentVersion>> {
+  rows
+    .into_iter()
+    .map(|(key, file_name, saved_at, size_bytes, parsed_at)| {
+      Ok(FileContentVersion {
+        key,
+        file_name: FileName::try_from(file_name)?,
+        saved_at,
+        size_bytes,
+        parsed_at,
+      })
+    })
+    .collect()
+}
+
+#[derive(Debug)]
+pub struct FileContentVersionRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl FileContentVersionRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find_current(&self, file_name: &FileName) -> Result<Option<FileContentVersion>> {
+    let file_name = file_name.to_string();
+    let row = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        conn
+          .query_row(
+            "
+            SELECT key, file_name, saved_at, size_bytes, parsed_at
+            FROM file_content_versions
+            WHERE key = ?
+            ",
+            params![file_name],
+            map_row,
+          )
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find file content version"
+        );
+        anyhow!("Failed to find file content version")
+      })??;
+    Ok(to_versions(row.into_iter().collect())?.pop())
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find_by_file_name(&self, file_name: &FileName) -> Result<Vec<FileContentVersion>> {
+    let file_name = file_name.to_string();
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT key, file_name, saved_at, size_bytes, parsed_at
+          FROM file_content_versions
+          WHERE file_name = ?
+          ORDER BY saved_at DESC
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![file_name], map_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find file content versions"
+        );
+        anyhow!("Failed to find file content versions")
+      })??;
+    to_versions(rows)
+  }
+
+  /**
+   * Records newly stored content as the current version of the file
+   */
+  #[instrument(skip(self))]
+  pub async fn put_current(&self, file_name: &FileName, size_bytes: u64) -> Result<()> {
+    let file_name = file_name.to_string();
+    let saved_at = Utc::now();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO file_content_versions (key, file_name, saved_at, size_bytes, parsed_at)
+          VALUES (?1, ?1, ?2, ?3, NULL)
+          ON CONFLICT (key) DO UPDATE SET
+            saved_at = excluded.saved_at,
+            size_bytes = excluded.size_bytes,
+            parsed_at = NULL
+          ",
+          params![file_name, saved_at, size_bytes],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put file content version"
+        );
+        anyhow!("Failed to put file content version")
+      })??;
+    Ok(())
+  }
+
+  /**
+   * Records content stored before versions were tracked as the current version of its file,
+   * leaving files that already have one alone. Whether that content parsed isn't known, so it
+   * isn't marked parsed. Returns how many were recorded.
+   */
+  #[instrument(skip_all, fields(count = stored_files.len()))]
+  pub async fn backfill_current(&self, stored_files: Vec<StoredFile>) -> Result<usize> {
+    let recorded = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let mut recorded = 0;
+        {
+          let mut statement = tx.prepare(
+            "
+            INSERT OR IGNORE INTO file_content_versions
+              (key, file_name, saved_at, size_bytes, parsed_at)
+            VALUES (?1, ?1, ?2, ?3, NULL)
+            ",
+          )?;
+          for stored_file in stored_files {
+            recorded += statement.execute(params![
+              stored_file.file_name.to_string(),
+              stored_file.last_modified,
+              stored_file.size_bytes
+            ])?;
+          }
+        }
+        tx.commit()?;
+        Ok::<_, rusqlite::Error>(recorded)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to backfill file content versions"
+        );
+        anyhow!("Failed to backfill file content versions")
+      })??;
+    Ok(recorded)
+  }
+
+  /**
+   * Moves the current version of the file to the key it was archived under
+   */
+  #[instrument(skip(self))]
+  pub async fn archive_current(&self, file_name: &FileName, key: String) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "UPDATE file_content_versions SET key = ? WHERE key = ?",
+          params![key, file_name],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to archive file content version"
+        );
+        anyhow!("Failed to archive file content version")
+      })??;
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  pub async fn mark_parsed(&self, file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    let parsed_at = Utc::now();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "UPDATE file_content_versions SET parsed_at = ? WHERE key = ?",
+          params![parsed_at, file_name],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to mark file content parsed"
+        );
+        anyhow!("Failed to mark file content parsed")
+      })??;
+    Ok(())
+  }
+
+  /**
+   * Archived versions past the newest `keep` of each file
+   */
+  #[instrument(skip(self))]
+  pub async fn find_excess_archived(
+    &self,
+    keep: u32,
+    limit: u32,
+  ) -> Result<Vec<FileContentVersion>> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT key, file_name, saved_at, size_bytes, parsed_at
+          FROM (
+            SELECT
+              *,
+              ROW_NUMBER() OVER (PARTITION BY file_name ORDER BY saved_at DESC) AS version_rank
+            FROM file_content_versions
+            WHERE key != file_name
+          )
+          WHERE version_rank > ?
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![keep, limit], map_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find excess file content versions"
+        );
+        anyhow!("Failed to find excess file content versions")
+      })??;
+    to_versions(rows)
+  }
+
+  /**
+   * Versions that parsed successfully and were saved before `saved_before`
+   */
+  #[instrument(skip(self))]
+  pub async fn find_parsed_saved_before(
+    &self,
+    saved_before: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Vec<FileContentVersion>> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT key, file_name, saved_at, size_bytes, parsed_at
+          FROM file_content_versions
+          WHERE parsed_at IS NOT NULL AND saved_at < ?
+          ORDER BY saved_at ASC
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![saved_before, limit], map_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find expired file content versions"
+        );
+        anyhow!("Failed to find expired file content versions")
+      })??;
+    to_versions(rows)
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_many(&self, keys: Vec<String>) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        {
+          let mut statement = tx.prepare("DELETE FROM file_content_versions WHERE key = ?")?;
+          for key in keys {
+            statement.execute(params![key])?;
+          }
+        }
+        tx.commit()
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete file content versions"
+        );
+        anyhow!("Failed to delete file content versions")
+      })??;
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_totals(&self) -> Result<FileContentTotals> {
+    let (versions, size_bytes) = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        conn.query_row(
+          "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM file_content_versions",
+          [],
+          |row| Ok((row.get::<_, u64>(0)?, row.get::<_, u64>(1)?)),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to count file content versions"
+        );
+        anyhow!("Failed to count file content versions")
+      })??;
+    Ok(FileContentTotals {
+      versions,
+      size_bytes,
+    })
+  }
+}
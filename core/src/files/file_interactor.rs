@@ -1,59 +1,86 @@
 use super::{
   file_content_store::FileContentStore,
+  file_content_version_repository::{
+    FileContentTotals, FileContentVersion, FileContentVersionRepository,
+  },
   file_metadata::{
-    file_metadata::FileMetadata, file_metadata_repository::FileMetadataRepository,
-    file_name::FileName, file_timestamp::FileTimestamp, page_type::PageType,
+    file_metadata::FileMetadata,
+    file_metadata_repository::{FileMetadataRepository, RedisFileMetadataRepository},
+    file_name::FileName,
+    file_timestamp::FileTimestamp,
+    page_type::PageType,
+    sqlite_file_metadata_repository::SqliteFileMetadataRepository,
   },
+  file_redaction::redact_html,
 };
 use crate::{
   events::{
     event::{Event, EventPayloadBuilder, Topic},
     event_publisher::EventPublisher,
   },
-  settings::Settings,
+  settings::{Settings, StorageMode},
+  sqlite::SqliteConnection,
 };
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Duration, Utc};
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tracing::info;
 
 #[derive(Debug, Clone)]
 pub struct FileInteractor {
   settings: Arc<Settings>,
   file_content_store: FileContentStore,
-  file_metadata_repository: FileMetadataRepository,
+  file_metadata_repository: Arc<dyn FileMetadataRepository + Send + Sync>,
+  file_content_version_repository: Arc<FileContentVersionRepository>,
   event_publisher: Arc<EventPublisher>,
 }
 
+fn archived_content_key(version: &FileContentVersion) -> String {
+  format!(
+    "versions/{}/{}",
+    version.file_name.to_string(),
+    version.saved_at.timestamp_millis()
+  )
+}
+
 impl FileInteractor {
   pub fn new(
     settings: Arc<Settings>,
+    sqlite_connection: Arc<SqliteConnection>,
     redis_connection_pool: Arc<Pool<PooledClientManager>>,
     event_publisher: Arc<EventPublisher>,
   ) -> Self {
+    let file_metadata_repository: Arc<dyn FileMetadataRepository + Send + Sync> =
+      match settings.storage.mode {
+        StorageMode::Redis => Arc::new(RedisFileMetadataRepository {
+          redis_connection_pool: Arc::clone(&redis_connection_pool),
+        }),
+        StorageMode::Sqlite => Arc::new(SqliteFileMetadataRepository {
+          sqlite_connection: Arc::clone(&sqlite_connection),
+        }),
+      };
     Self {
       settings: Arc::clone(&settings),
       file_content_store: FileContentStore::new(&settings.file.content_store).unwrap(),
-      file_metadata_repository: FileMetadataRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      file_metadata_repository,
+      file_content_version_repository: Arc::new(FileContentVersionRepository::new(Arc::clone(
+        &sqlite_connection,
+      ))),
       event_publisher,
     }
   }
 
-  pub async fn is_file_stale(&self, file_name: &FileName) -> Result<bool> {
-    let file_metadata = self
-      .file_metadata_repository
-      .find_by_name(file_name)
-      .await?;
-
+  fn is_stale(&self, file_name: &FileName, file_metadata: Option<&FileMetadata>) -> Result<bool> {
     let ttl_days = match file_name.page_type() {
       PageType::Artist => self.settings.file.ttl_days.artist,
       PageType::Album => self.settings.file.ttl_days.album,
       PageType::Chart => self.settings.file.ttl_days.chart,
-      PageType::AlbumSearchResult => self.settings.file.ttl_days.search,
+      PageType::AlbumSearchResult | PageType::BandcampSearchResult => {
+        self.settings.file.ttl_days.search
+      }
       PageType::ListSegment => self.settings.file.ttl_days.list_segment,
+      PageType::GenreTree => self.settings.file.ttl_days.genre_tree,
     };
 
     let ttl_days = Duration::try_days(ttl_days.into()).ok_or(anyhow!(
@@ -66,7 +93,7 @@ impl FileInteractor {
       file_metadata
         .map(|file_metadata| {
           let now: DateTime<Utc> = FileTimestamp::now().into();
-          let last_saved_at: DateTime<Utc> = file_metadata.last_saved_at.into();
+          let last_saved_at: DateTime<Utc> = file_metadata.last_saved_at.clone().into();
           let stale_at = last_saved_at + ttl_days;
           now > stale_at
         })
@@ -74,12 +101,44 @@ impl FileInteractor {
     )
   }
 
-  pub async fn put_file_metadata(
+  pub async fn is_file_stale(&self, file_name: &FileName) -> Result<bool> {
+    let file_metadata = self
+      .file_metadata_repository
+      .find_by_name(file_name)
+      .await?;
+    self.is_stale(file_name, file_metadata.as_ref())
+  }
+
+  /**
+   * The stale files among `file_names`, in their given order
+   */
+  pub async fn find_stale(&self, file_names: Vec<FileName>) -> Result<Vec<FileName>> {
+    let file_metadata = self
+      .file_metadata_repository
+      .find_many_by_names(file_names.clone())
+      .await?
+      .into_iter()
+      .map(|file_metadata| (file_metadata.name.clone(), file_metadata))
+      .collect::<HashMap<_, _>>();
+    let mut stale = Vec::new();
+    for file_name in file_names {
+      if self.is_stale(&file_name, file_metadata.get(&file_name))? {
+        stale.push(file_name);
+      }
+    }
+    Ok(stale)
+  }
+
+  async fn save_file_metadata(
     &self,
     file_name: &FileName,
+    redaction_version: Option<u32>,
     correlation_id: Option<String>,
   ) -> Result<FileMetadata> {
-    let file_metadata = self.file_metadata_repository.upsert(file_name).await?;
+    let file_metadata = self
+      .file_metadata_repository
+      .upsert(file_name, redaction_version)
+      .await?;
     info!(file_name = file_name.to_string(), "File metadata saved");
     self
       .event_publisher
@@ -98,6 +157,25 @@ impl FileInteractor {
     Ok(file_metadata)
   }
 
+  /**
+   * Saves metadata for content already in the store, which keeps whatever redaction it was stored
+   * with
+   */
+  pub async fn put_file_metadata(
+    &self,
+    file_name: &FileName,
+    correlation_id: Option<String>,
+  ) -> Result<FileMetadata> {
+    let redaction_version = self
+      .file_metadata_repository
+      .find_by_name(file_name)
+      .await?
+      .and_then(|file_metadata| file_metadata.redaction_version);
+    self
+      .save_file_metadata(file_name, redaction_version, correlation_id)
+      .await
+  }
+
   pub async fn put_file(
     &self,
     file_name: &FileName,
@@ -108,14 +186,76 @@ impl FileInteractor {
       file_name = file_name.to_string(),
       "Saving file content and metadata"
     );
+    let (content, redaction_version) = match &self.settings.file.redaction {
+      Some(redaction) => (
+        redact_html(&content, &redaction.selectors)?,
+        Some(redaction.version),
+      ),
+      None => (content, None),
+    };
+    if self.settings.file.retention.versions > 1 {
+      self.archive_file_content(file_name).await?;
+    }
+    let size_bytes = content.len() as u64;
     self.file_content_store.put(file_name, content).await?;
-    self.put_file_metadata(file_name, correlation_id).await
+    self
+      .file_content_version_repository
+      .put_current(file_name, size_bytes)
+      .await?;
+    self
+      .save_file_metadata(file_name, redaction_version, correlation_id)
+      .await
+  }
+
+  /**
+   * Copies the current content of the file to its own key, so it is kept as an earlier version
+   * when the file is saved again
+   */
+  async fn archive_file_content(&self, file_name: &FileName) -> Result<()> {
+    let Some(current) = self
+      .file_content_version_repository
+      .find_current(file_name)
+      .await?
+    else {
+      return Ok(());
+    };
+    let key = archived_content_key(&current);
+    let content = self.file_content_store.get(file_name).await?;
+    self
+      .file_content_store
+      .put_object(&key, content.as_bytes(), "text/html")
+      .await?;
+    self
+      .file_content_version_repository
+      .archive_current(file_name, key)
+      .await
+  }
+
+  /**
+   * Records that the current content of the file parsed, which starts its retention period
+   */
+  pub async fn mark_file_parsed(&self, file_name: &FileName) -> Result<()> {
+    self
+      .file_content_version_repository
+      .mark_parsed(file_name)
+      .await
   }
 
   pub async fn list_files(&self) -> Result<Vec<FileName>> {
     self.file_content_store.list_files().await
   }
 
+  /**
+   * Records versions for content stored before they were tracked, returning how many were missing
+   */
+  pub async fn backfill_content_versions(&self) -> Result<usize> {
+    let stored_files = self.file_content_store.list_stored_files().await?;
+    self
+      .file_content_version_repository
+      .backfill_current(stored_files)
+      .await
+  }
+
   pub async fn get_file_metadata(&self, file_name: &FileName) -> Result<FileMetadata> {
     self
       .file_metadata_repository
@@ -129,10 +269,29 @@ impl FileInteractor {
       })
   }
 
+  pub async fn find_saved_before(
+    &self,
+    name_prefix: &str,
+    saved_before: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Vec<FileMetadata>> {
+    self
+      .file_metadata_repository
+      .find_saved_before(name_prefix, saved_before, limit)
+      .await
+  }
+
   pub async fn delete_file(&self, file_name: &FileName) -> Result<()> {
     let file_metadata = self.get_file_metadata(file_name).await?;
     self.file_metadata_repository.delete(file_name).await?;
-    self.file_content_store.delete(file_name).await?;
+    let versions = self
+      .file_content_version_repository
+      .find_by_file_name(file_name)
+      .await?;
+    if !versions.iter().any(|version| version.is_current()) {
+      self.file_content_store.delete(file_name).await?;
+    }
+    self.delete_content_versions(&versions).await?;
     self
       .event_publisher
       .publish(
@@ -152,4 +311,50 @@ impl FileInteractor {
   pub async fn get_file_content(&self, file_name: &FileName) -> Result<String> {
     self.file_content_store.get(file_name).await
   }
+
+  /**
+   * Archived versions past the newest `keep` of each file
+   */
+  pub async fn find_excess_content_versions(
+    &self,
+    keep: u32,
+    limit: u32,
+  ) -> Result<Vec<FileContentVersion>> {
+    self
+      .file_content_version_repository
+      .find_excess_archived(keep, limit)
+      .await
+  }
+
+  pub async fn find_parsed_content_versions_saved_before(
+    &self,
+    saved_before: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Vec<FileContentVersion>> {
+    self
+      .file_content_version_repository
+      .find_parsed_saved_before(saved_before, limit)
+      .await
+  }
+
+  /**
+   * Deletes the content of the versions from the content store. Metadata of the files is kept.
+   */
+  pub async fn delete_content_versions(&self, versions: &[FileContentVersion]) -> Result<()> {
+    for version in versions {
+      if version.is_current() {
+        self.file_content_store.delete(&version.file_name).await?;
+      } else {
+        self.file_content_store.delete_object(&version.key).await?;
+      }
+    }
+    self
+      .file_content_version_repository
+      .delete_many(versions.iter().map(|version| version.key.clone()).collect())
+      .await
+  }
+
+  pub async fn get_content_totals(&self) -> Result<FileContentTotals> {
+    self.file_content_version_repository.get_totals().await
+  }
 }
@@ -0,0 +1,45 @@
+use super::file_retention::enforce_file_retention;
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use std::sync::Arc;
+
+async fn run_file_retention(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  enforce_file_retention(app_context).await?;
+  Ok(())
+}
+
+pub async fn setup_file_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::EnforceFileRetention)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(run_file_retention))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::EnforceFileRetention)
+        .interval(
+          TimeDelta::try_hours(app_context.settings.file.retention.interval_hours as i64).unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
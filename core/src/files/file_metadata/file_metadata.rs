@@ -8,6 +8,10 @@ pub struct FileMetadata {
   pub id: Ulid,
   pub name: FileName,
   pub last_saved_at: FileTimestamp,
+  /**
+   * Redaction pass the stored content went through, none when it was stored as crawled
+   */
+  pub redaction_version: Option<u32>,
 }
 
 impl FileMetadata {
@@ -27,6 +31,7 @@ impl From<FileMetadata> for proto::FileMetadata {
       name: val.name.to_string(),
       first_saved_at: val.first_saved_at().to_string(),
       last_saved_at: val.last_saved_at.to_string(),
+      redaction_version: val.redaction_version,
     }
   }
 }
@@ -1,11 +1,14 @@
 use super::{file_metadata::FileMetadata, file_name::FileName, file_timestamp::FileTimestamp};
 use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
 use rustis::{
   bb8::Pool,
   client::{BatchPreparedCommand, PooledClientManager},
   commands::{GenericCommands, HashCommands, StringCommands},
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
 use ulid::Ulid;
 
 fn get_key(id: String) -> String {
@@ -33,43 +36,108 @@ impl From<HashMap<String, String>> for FileMetadata {
       .parse()
       .expect("invalid last_saved_at");
 
+    let redaction_version = values
+      .get("redaction_version")
+      .map(|version| version.parse().expect("invalid redaction_version"));
+
     Self {
       id,
       name,
       last_saved_at,
+      redaction_version,
     }
   }
 }
 
 impl From<FileMetadata> for HashMap<String, String> {
   fn from(val: FileMetadata) -> Self {
-    vec![
-      ("id".to_string(), val.id.to_string()),
-      ("name".to_string(), val.name.to_string()),
-      ("last_saved_at".to_string(), val.last_saved_at.to_string()),
-    ]
-    .into_iter()
-    .collect()
+    Vec::<(String, String)>::from(val).into_iter().collect()
   }
 }
 
 impl From<FileMetadata> for Vec<(String, String)> {
   fn from(val: FileMetadata) -> Self {
-    vec![
+    let mut values = vec![
       ("id".to_string(), val.id.to_string()),
       ("name".to_string(), val.name.to_string()),
       ("last_saved_at".to_string(), val.last_saved_at.to_string()),
-    ]
+    ];
+    if let Some(redaction_version) = val.redaction_version {
+      values.push((
+        "redaction_version".to_string(),
+        redaction_version.to_string(),
+      ));
+    }
+    values
   }
 }
 
+#[async_trait]
+pub trait FileMetadataRepository: Debug {
+  async fn find_by_id(&self, id: &str) -> Result<Option<FileMetadata>>;
+  async fn find_by_name(&self, name: &FileName) -> Result<Option<FileMetadata>>;
+  /**
+   * Metadata of the files among `names` that exist
+   */
+  async fn find_many_by_names(&self, names: Vec<FileName>) -> Result<Vec<FileMetadata>>;
+  async fn upsert(&self, name: &FileName, redaction_version: Option<u32>) -> Result<FileMetadata>;
+  async fn delete(&self, name: &FileName) -> Result<()>;
+  /**
+   * Files whose names start with `name_prefix` that were last saved before `saved_before`, least
+   * recently saved first
+   */
+  async fn find_saved_before(
+    &self,
+    name_prefix: &str,
+    saved_before: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Vec<FileMetadata>>;
+}
+
 #[derive(Debug, Clone)]
-pub struct FileMetadataRepository {
+pub struct RedisFileMetadataRepository {
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
 }
 
-impl FileMetadataRepository {
-  pub async fn find_by_id(&self, id: &str) -> Result<Option<FileMetadata>> {
+impl RedisFileMetadataRepository {
+  pub async fn insert(
+    &self,
+    name: &FileName,
+    redaction_version: Option<u32>,
+  ) -> Result<FileMetadata> {
+    if self.find_by_name(name).await?.is_some() {
+      bail!("File already exists");
+    }
+
+    let file_metadata = FileMetadata {
+      id: Ulid::new(),
+      name: FileName::try_from(name.to_string())?,
+      last_saved_at: FileTimestamp::now(),
+      redaction_version,
+    };
+
+    let hset_items: HashMap<String, String> = file_metadata.clone().into();
+    let connection = self.redis_connection_pool.get().await?;
+
+    let mut transaction = connection.create_transaction();
+    transaction
+      .hset(get_key(file_metadata.id.into()), hset_items)
+      .forget();
+    transaction
+      .set(
+        get_name_index_key(file_metadata.name.clone().into()),
+        file_metadata.id.to_string(),
+      )
+      .queue();
+    transaction.execute().await?;
+
+    Ok(file_metadata)
+  }
+}
+
+#[async_trait]
+impl FileMetadataRepository for RedisFileMetadataRepository {
+  async fn find_by_id(&self, id: &str) -> Result<Option<FileMetadata>> {
     let res: HashMap<String, String> = self
       .redis_connection_pool
       .get()
@@ -84,7 +152,7 @@ impl FileMetadataRepository {
     }
   }
 
-  pub async fn find_by_name(&self, name: &FileName) -> Result<Option<FileMetadata>> {
+  async fn find_by_name(&self, name: &FileName) -> Result<Option<FileMetadata>> {
     let id: Option<String> = self
       .redis_connection_pool
       .get()
@@ -111,59 +179,52 @@ impl FileMetadataRepository {
     }
   }
 
-  pub async fn insert(&self, name: &FileName) -> Result<FileMetadata> {
-    if self.find_by_name(name).await?.is_some() {
-      bail!("File already exists");
-    }
-
-    let file_metadata = FileMetadata {
-      id: Ulid::new(),
-      name: FileName::try_from(name.to_string())?,
-      last_saved_at: FileTimestamp::now(),
-    };
-
-    let hset_items: HashMap<String, String> = file_metadata.clone().into();
-    let connection = self.redis_connection_pool.get().await?;
-
-    let mut transaction = connection.create_transaction();
-    transaction
-      .hset(get_key(file_metadata.id.into()), hset_items)
-      .forget();
-    transaction
-      .set(
-        get_name_index_key(file_metadata.name.clone().into()),
-        file_metadata.id.to_string(),
-      )
-      .queue();
-    transaction.execute().await?;
-
-    Ok(file_metadata)
+  async fn find_many_by_names(&self, names: Vec<FileName>) -> Result<Vec<FileMetadata>> {
+    Ok(
+      try_join_all(names.iter().map(|name| self.find_by_name(name)))
+        .await?
+        .into_iter()
+        .flatten()
+        .collect(),
+    )
   }
 
-  pub async fn upsert(&self, name: &FileName) -> Result<FileMetadata> {
+  async fn upsert(&self, name: &FileName, redaction_version: Option<u32>) -> Result<FileMetadata> {
     let connection = self.redis_connection_pool.get().await?;
 
     match self.find_by_name(name).await? {
       Some(file_metadata) => {
         let last_saved_at = FileTimestamp::now();
+        let key = get_key(file_metadata.id.into());
         connection
-          .hset(
-            get_key(file_metadata.id.into()),
-            ("last_saved_at", last_saved_at.to_string()),
-          )
+          .hset(key.as_str(), ("last_saved_at", last_saved_at.to_string()))
           .await?;
+        match redaction_version {
+          Some(redaction_version) => {
+            connection
+              .hset(
+                key.as_str(),
+                ("redaction_version", redaction_version.to_string()),
+              )
+              .await?;
+          }
+          None => {
+            connection.hdel(key.as_str(), "redaction_version").await?;
+          }
+        }
 
         Ok(FileMetadata {
           id: file_metadata.id,
           name: file_metadata.name,
           last_saved_at,
+          redaction_version,
         })
       }
-      None => self.insert(name).await,
+      None => self.insert(name, redaction_version).await,
     }
   }
 
-  pub async fn delete(&self, name: &FileName) -> Result<()> {
+  async fn delete(&self, name: &FileName) -> Result<()> {
     let connection = self.redis_connection_pool.get().await?;
     connection.del(get_name_index_key(name.to_string())).await?;
     let id: Option<String> = connection.get(get_name_index_key(name.to_string())).await?;
@@ -173,4 +234,13 @@ impl FileMetadataRepository {
 
     Ok(())
   }
+
+  async fn find_saved_before(
+    &self,
+    _name_prefix: &str,
+    _saved_before: DateTime<Utc>,
+    _limit: u32,
+  ) -> Result<Vec<FileMetadata>> {
+    bail!("Finding files by save time is not supported in redis storage mode")
+  }
 }
@@ -3,3 +3,4 @@ pub mod file_metadata_repository;
 pub mod file_name;
 pub mod file_timestamp;
 pub mod page_type;
+pub mod sqlite_file_metadata_repository;
@@ -12,8 +12,15 @@ pub enum PageType {
   Chart,
   AlbumSearchResult,
   ListSegment,
+  BandcampSearchResult,
+  GenreTree,
 }
 
+/**
+ * RYM's genre index, listing every genre nested under its parents
+ */
+pub const GENRE_TREE_FILE_NAME: &str = "genres";
+
 const SUPPORTED_RELEASE_TYPES: [&str; 4] = ["album", "mixtape", "ep", "comp"];
 
 fn is_album_page(file_name: &str) -> bool {
@@ -27,6 +34,8 @@ lazy_static! {
   static ref ALBUM_SEARCH_RESULT_PAGE_RE: Regex =
     Regex::new(r"^search\?searchterm=[^&]+&searchtype=l$").unwrap();
   static ref LIST_SEGMENT_PAGE_RE: Regex = Regex::new(r"^list/(\w+)/([\w-]+)/?(\d*)/?$").unwrap();
+  static ref BANDCAMP_SEARCH_RESULT_PAGE_RE: Regex =
+    Regex::new(r"^bandcamp/search\?q=[^&]+&item_type=a$").unwrap();
 }
 
 fn is_chart_page(file_name: &str) -> bool {
@@ -41,6 +50,10 @@ fn is_album_search_result_page(file_name: &str) -> bool {
   (*ALBUM_SEARCH_RESULT_PAGE_RE).is_match(file_name)
 }
 
+fn is_bandcamp_search_result_page(file_name: &str) -> bool {
+  (*BANDCAMP_SEARCH_RESULT_PAGE_RE).is_match(file_name)
+}
+
 impl TryFrom<&str> for PageType {
   type Error = ();
 
@@ -51,6 +64,8 @@ impl TryFrom<&str> for PageType {
       file_name if is_album_search_result_page(file_name) => Ok(PageType::AlbumSearchResult),
       file_name if file_name.starts_with("artist") => Ok(PageType::Artist),
       file_name if is_list_segment_page(file_name) => Ok(PageType::ListSegment),
+      file_name if is_bandcamp_search_result_page(file_name) => Ok(PageType::BandcampSearchResult),
+      GENRE_TREE_FILE_NAME => Ok(PageType::GenreTree),
       _ => Err(()),
     }
   }
@@ -72,6 +87,13 @@ impl PageType {
   pub fn is_artist(&self) -> bool {
     matches!(self, PageType::Artist)
   }
+
+  /**
+   * Pages crawled from Bandcamp rather than RYM
+   */
+  pub fn is_bandcamp(&self) -> bool {
+    matches!(self, PageType::BandcampSearchResult)
+  }
 }
 
 impl From<PageType> for proto::PageType {
@@ -82,6 +104,8 @@ impl From<PageType> for proto::PageType {
       PageType::Chart => proto::PageType::ChartPage,
       PageType::AlbumSearchResult => proto::PageType::AlbumSearchResultPage,
       PageType::ListSegment => proto::PageType::ListSegmentPage,
+      PageType::BandcampSearchResult => proto::PageType::BandcampSearchResultPage,
+      PageType::GenreTree => proto::PageType::GenreTreePage,
     }
   }
 }
@@ -120,6 +144,11 @@ mod tests {
       PageType::try_from("list/sunohara227/ethereal-sounds-of-the-internet/1/"),
       Ok(PageType::ListSegment)
     );
+    assert_eq!(
+      PageType::try_from("bandcamp/search?q=bjork+vulnicura&item_type=a"),
+      Ok(PageType::BandcampSearchResult)
+    );
+    assert_eq!(PageType::try_from("genres"), Ok(PageType::GenreTree));
     assert_eq!(PageType::try_from("invalid"), Err(()));
   }
 }
@@ -0,0 +1,319 @@
+use super::{
+  file_metadata::FileMetadata, file_metadata_repository::FileMetadataRepository,
+  file_name::FileName, file_timestamp::FileTimestamp,
+};
+use crate::sqlite::SqliteConnection;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, types::Value, OptionalExtension};
+use std::{rc::Rc, sync::Arc};
+use tracing::error;
+use ulid::Ulid;
+
+#[derive(Debug, Clone)]
+pub struct SqliteFileMetadataRepository {
+  pub sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl SqliteFileMetadataRepository {
+  async fn find_by_column(
+    &self,
+    column: &'static str,
+    value: String,
+  ) -> Result<Option<FileMetadata>> {
+    let row = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        conn
+          .query_row(
+            &format!(
+              "SELECT id, name, last_saved_at, redaction_version FROM file_metadata WHERE {} = ?",
+              column
+            ),
+            params![value],
+            |row| {
+              Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, DateTime<Utc>>(2)?,
+                row.get::<_, Option<u32>>(3)?,
+              ))
+            },
+          )
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find file metadata");
+        anyhow!("Failed to find file metadata")
+      })??;
+
+    row
+      .map(|(id, name, last_saved_at, redaction_version)| {
+        Ok(FileMetadata {
+          id: id.parse::<Ulid>()?,
+          name: FileName::try_from(name)?,
+          last_saved_at: last_saved_at.into(),
+          redaction_version,
+        })
+      })
+      .transpose()
+  }
+}
+
+#[async_trait]
+impl FileMetadataRepository for SqliteFileMetadataRepository {
+  async fn find_by_id(&self, id: &str) -> Result<Option<FileMetadata>> {
+    self.find_by_column("id", id.to_string()).await
+  }
+
+  async fn find_by_name(&self, name: &FileName) -> Result<Option<FileMetadata>> {
+    self.find_by_column("name", name.to_string()).await
+  }
+
+  async fn find_many_by_names(&self, names: Vec<FileName>) -> Result<Vec<FileMetadata>> {
+    if names.is_empty() {
+      return Ok(vec![]);
+    }
+    let names = names
+      .into_iter()
+      .map(|name| Value::from(name.to_string()))
+      .collect::<Vec<_>>();
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT id, name, last_saved_at, redaction_version
+          FROM file_metadata
+          WHERE name IN rarray(?)
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![Rc::new(names)], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, String>(1)?,
+              row.get::<_, DateTime<Utc>>(2)?,
+              row.get::<_, Option<u32>>(3)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find file metadata");
+        anyhow!("Failed to find file metadata")
+      })??;
+
+    rows
+      .into_iter()
+      .map(|(id, name, last_saved_at, redaction_version)| {
+        Ok(FileMetadata {
+          id: id.parse::<Ulid>()?,
+          name: FileName::try_from(name)?,
+          last_saved_at: last_saved_at.into(),
+          redaction_version,
+        })
+      })
+      .collect()
+  }
+
+  async fn upsert(&self, name: &FileName, redaction_version: Option<u32>) -> Result<FileMetadata> {
+    let candidate_id = Ulid::new();
+    let last_saved_at = FileTimestamp::now();
+    let saved_at: DateTime<Utc> = last_saved_at.clone().into();
+    let file_name = name.to_string();
+    let id = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.query_row(
+          "
+          INSERT INTO file_metadata (id, name, last_saved_at, redaction_version)
+          VALUES (?, ?, ?, ?)
+          ON CONFLICT (name) DO UPDATE SET
+            last_saved_at = excluded.last_saved_at,
+            redaction_version = excluded.redaction_version
+          RETURNING id
+          ",
+          params![
+            candidate_id.to_string(),
+            file_name,
+            saved_at,
+            redaction_version
+          ],
+          |row| row.get::<_, String>(0),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to upsert file metadata");
+        anyhow!("Failed to upsert file metadata")
+      })??;
+
+    Ok(FileMetadata {
+      id: id.parse::<Ulid>()?,
+      name: name.clone(),
+      last_saved_at,
+      redaction_version,
+    })
+  }
+
+  async fn delete(&self, name: &FileName) -> Result<()> {
+    let file_name = name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM file_metadata WHERE name = ?",
+          params![file_name],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete file metadata");
+        anyhow!("Failed to delete file metadata")
+      })??;
+    Ok(())
+  }
+
+  async fn find_saved_before(
+    &self,
+    name_prefix: &str,
+    saved_before: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Vec<FileMetadata>> {
+    let name_pattern = format!("{}%", name_prefix);
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT id, name, last_saved_at, redaction_version
+          FROM file_metadata
+          WHERE name LIKE ? AND last_saved_at < ?
+          ORDER BY last_saved_at ASC
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![name_pattern, saved_before, limit], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, String>(1)?,
+              row.get::<_, DateTime<Utc>>(2)?,
+              row.get::<_, Option<u32>>(3)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find stale file metadata"
+        );
+        anyhow!("Failed to find stale file metadata")
+      })??;
+
+    rows
+      .into_iter()
+      .map(|(id, name, last_saved_at, redaction_version)| {
+        Ok(FileMetadata {
+          id: id.parse::<Ulid>()?,
+          name: FileName::try_from(name)?,
+          last_saved_at: last_saved_at.into(),
+          redaction_version,
+        })
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_upsert_keeps_id() -> Result<()> {
+    let repository = SqliteFileMetadataRepository {
+      sqlite_connection: Arc::new(SqliteConnection::new_for_test().await?),
+    };
+    let name = FileName::try_from("release/album/artist/a")?;
+    let inserted = repository.upsert(&name, None).await?;
+    let updated = repository.upsert(&name, Some(2)).await?;
+    assert_eq!(updated.id, inserted.id);
+    let found = repository
+      .find_by_id(&inserted.id.to_string())
+      .await?
+      .unwrap();
+    assert_eq!(found.name, name);
+    assert_eq!(found.redaction_version, Some(2));
+    repository.delete(&name).await?;
+    assert!(repository.find_by_name(&name).await?.is_none());
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_find_saved_before() -> Result<()> {
+    let repository = SqliteFileMetadataRepository {
+      sqlite_connection: Arc::new(SqliteConnection::new_for_test().await?),
+    };
+    let album = FileName::try_from("release/album/artist/a")?;
+    repository.upsert(&album, None).await?;
+    repository
+      .upsert(&FileName::try_from("artist/foo")?, None)
+      .await?;
+    let cutoff = Utc::now() + chrono::Duration::seconds(1);
+    let stale = repository.find_saved_before("release/", cutoff, 10).await?;
+    assert_eq!(
+      stale
+        .iter()
+        .map(|file| file.name.clone())
+        .collect::<Vec<_>>(),
+      vec![album]
+    );
+    assert!(repository
+      .find_saved_before("release/", Utc::now() - chrono::Duration::days(1), 10)
+      .await?
+      .is_empty());
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_find_many_by_names() -> Result<()> {
+    let repository = SqliteFileMetadataRepository {
+      sqlite_connection: Arc::new(SqliteConnection::new_for_test().await?),
+    };
+    let album = FileName::try_from("release/album/artist/a")?;
+    repository.upsert(&album, None).await?;
+    let found = repository
+      .find_many_by_names(vec![
+        album.clone(),
+        FileName::try_from("release/album/artist/b")?,
+      ])
+      .await?;
+    assert_eq!(
+      found
+        .iter()
+        .map(|file| file.name.clone())
+        .collect::<Vec<_>>(),
+      vec![album]
+    );
+    assert!(repository.find_many_by_names(vec![]).await?.is_empty());
+    Ok(())
+  }
+}
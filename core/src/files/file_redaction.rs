@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+/**
+ * Strips every element matching the selectors, along with its contents, leaving the rest of the
+ * page byte for byte as crawled
+ */
+pub fn redact_html(html: &str, selectors: &[String]) -> Result<String> {
+  let dom = tl::parse(html, tl::ParserOptions::default())?;
+  let parser = dom.parser();
+  let mut ranges = selectors
+    .iter()
+    .filter_map(|selector| dom.query_selector(selector))
+    .flatten()
+    .filter_map(|node| node.get(parser).and_then(|node| node.as_tag()))
+    .filter_map(|tag| {
+      // Tags borrow their raw source from the input, so the slice locates the element in the page
+      let raw = tag.raw().as_bytes();
+      let start = (raw.as_ptr() as usize).checked_sub(html.as_ptr() as usize)?;
+      let end = start + raw.len();
+      (end <= html.len()).then_some((start, end))
+    })
+    .collect::<Vec<_>>();
+  ranges.sort_unstable();
+
+  let mut redacted = String::with_capacity(html.len());
+  let mut cursor = 0;
+  for (start, end) in ranges {
+    // Nested matches are already covered by their ancestor
+    if end <= cursor {
+      continue;
+    }
+    redacted.push_str(&html[cursor..start.max(cursor)]);
+    cursor = end;
+  }
+  redacted.push_str(&html[cursor..]);
+  Ok(redacted)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_redact_html() -> Result<()> {
+    let html = concat!(
+      r#"<div class="album"><h1>Vulnicura</h1>"#,
+      r#"<div class="review"><a class="user">someone</a><p>Great album</p></div>"#,
+      r#"<span class="user">someone else</span></div>"#
+    );
+    let redacted = redact_html(html, &[".review".to_string(), ".user".to_string()])?;
+    assert_eq!(redacted, r#"<div class="album"><h1>Vulnicura</h1></div>"#);
+    assert_eq!(redact_html(html, &[])?, html);
+    Ok(())
+  }
+}
@@ -0,0 +1,176 @@
+use super::file_content_version_repository::FileContentVersion;
+use crate::{context::ApplicationContext, proto};
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+const STATS_KEY: &str = "file_retention:stats";
+const BACKFILLED_KEY: &str = "file_retention:backfilled";
+const BATCH_SIZE: u32 = 500;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileRetentionRun {
+  pub deleted_versions: u64,
+  pub reclaimed_bytes: u64,
+}
+
+impl FileRetentionRun {
+  fn add(&mut self, versions: &[FileContentVersion]) {
+    self.deleted_versions += versions.len() as u64;
+    self.reclaimed_bytes += versions
+      .iter()
+      .map(|version| version.size_bytes)
+      .sum::<u64>();
+  }
+}
+
+/**
+ * Totals of every retention run, along with the latest one
+ */
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FileRetentionStats {
+  pub last_run_at: Option<NaiveDateTime>,
+  pub last_run: FileRetentionRun,
+  pub deleted_versions: u64,
+  pub reclaimed_bytes: u64,
+}
+
+impl FileRetentionStats {
+  fn record(&mut self, run: FileRetentionRun) {
+    self.last_run_at = Some(Utc::now().naive_utc());
+    self.deleted_versions += run.deleted_versions;
+    self.reclaimed_bytes += run.reclaimed_bytes;
+    self.last_run = run;
+  }
+}
+
+pub async fn get_file_retention_stats(
+  app_context: Arc<ApplicationContext>,
+) -> Result<FileRetentionStats> {
+  Ok(app_context.kv.get(STATS_KEY).await?.unwrap_or_default())
+}
+
+/**
+ * Content stored before versions were tracked is recorded once, from the content store's listing
+ */
+async fn backfill_content_versions(app_context: &Arc<ApplicationContext>) -> Result<()> {
+  if app_context.kv.exists(BACKFILLED_KEY.to_string()).await? {
+    return Ok(());
+  }
+  let backfilled = app_context
+    .file_interactor
+    .backfill_content_versions()
+    .await?;
+  app_context.kv.set(BACKFILLED_KEY, true, None).await?;
+  info!(backfilled, "File content versions backfilled");
+  Ok(())
+}
+
+/**
+ * Deletes archived versions beyond the configured count, then content that parsed successfully
+ * and has outlived the configured age
+ */
+pub async fn enforce_file_retention(
+  app_context: Arc<ApplicationContext>,
+) -> Result<FileRetentionRun> {
+  backfill_content_versions(&app_context).await?;
+  let settings = &app_context.settings.file.retention;
+  let mut run = FileRetentionRun::default();
+
+  let keep_archived = settings.versions.saturating_sub(1);
+  loop {
+    let versions = app_context
+      .file_interactor
+      .find_excess_content_versions(keep_archived, BATCH_SIZE)
+      .await?;
+    if versions.is_empty() {
+      break;
+    }
+    app_context
+      .file_interactor
+      .delete_content_versions(&versions)
+      .await?;
+    run.add(&versions);
+  }
+
+  if let Some(max_age_days) = settings.max_age_days {
+    let max_age = Duration::try_days(max_age_days.into())
+      .ok_or(anyhow!("Invalid max_age_days: {}", max_age_days))?;
+    let saved_before = Utc::now() - max_age;
+    loop {
+      let versions = app_context
+        .file_interactor
+        .find_parsed_content_versions_saved_before(saved_before, BATCH_SIZE)
+        .await?;
+      if versions.is_empty() {
+        break;
+      }
+      app_context
+        .file_interactor
+        .delete_content_versions(&versions)
+        .await?;
+      run.add(&versions);
+    }
+  }
+
+  let mut stats = get_file_retention_stats(Arc::clone(&app_context)).await?;
+  stats.record(run.clone());
+  app_context.kv.set(STATS_KEY, stats, None).await?;
+  info!(
+    deleted_versions = run.deleted_versions,
+    reclaimed_bytes = run.reclaimed_bytes,
+    "File retention enforced"
+  );
+  Ok(run)
+}
+
+impl From<FileRetentionRun> for proto::FileRetentionRun {
+  fn from(val: FileRetentionRun) -> Self {
+    proto::FileRetentionRun {
+      deleted_versions: val.deleted_versions,
+      reclaimed_bytes: val.reclaimed_bytes,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::files::file_metadata::file_name::FileName;
+
+  fn version(key: &str, size_bytes: u64) -> FileContentVersion {
+    FileContentVersion {
+      key: key.to_string(),
+      file_name: FileName::try_from("release/album/slowdive/souvlaki".to_string()).unwrap(),
+      saved_at: Utc::now(),
+      size_bytes,
+      parsed_at: None,
+    }
+  }
+
+  #[test]
+  fn test_file_retention_stats() {
+    let mut run = FileRetentionRun::default();
+    run.add(&[version("versions/a/1", 100), version("versions/a/2", 50)]);
+    assert_eq!(
+      run,
+      FileRetentionRun {
+        deleted_versions: 2,
+        reclaimed_bytes: 150,
+      }
+    );
+
+    let mut stats = FileRetentionStats::default();
+    stats.record(run);
+    stats.record(FileRetentionRun {
+      deleted_versions: 1,
+      reclaimed_bytes: 10,
+    });
+    assert_eq!(stats.deleted_versions, 3);
+    assert_eq!(stats.reclaimed_bytes, 160);
+    assert_eq!(stats.last_run.reclaimed_bytes, 10);
+    assert!(stats.last_run_at.is_some());
+  }
+}
@@ -1,9 +1,14 @@
-use super::{file_interactor::FileInteractor, file_metadata::file_name::FileName};
+use super::{
+  file_interactor::FileInteractor,
+  file_metadata::file_name::FileName,
+  file_retention::{enforce_file_retention, get_file_retention_stats},
+};
 use crate::{
   context::ApplicationContext,
   proto::{
-    self, GetFileContentReply, GetFilePageTypeReply, GetFilePageTypeRequest, IsFileStaleReply,
-    IsFileStaleRequest, PutFileReply, PutFileRequest,
+    self, EnforceFileRetentionReply, GetFileContentReply, GetFilePageTypeReply,
+    GetFilePageTypeRequest, GetFileRetentionStatsReply, IsFileStaleReply, IsFileStaleRequest,
+    PutFileReply, PutFileRequest,
   },
 };
 use anyhow::Result;
@@ -13,12 +18,14 @@ use tracing::error;
 
 pub struct FileService {
   pub file_interactor: Arc<FileInteractor>,
+  app_context: Arc<ApplicationContext>,
 }
 
 impl FileService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       file_interactor: Arc::clone(&app_context.file_interactor),
+      app_context,
     }
   }
 }
@@ -114,4 +121,49 @@ impl proto::FileService for FileService {
 
     Ok(Response::new(GetFileContentReply { content }))
   }
+
+  async fn get_file_retention_stats(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetFileRetentionStatsReply>, Status> {
+    let stats = get_file_retention_stats(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get file retention stats")
+      })?;
+    let totals = self
+      .file_interactor
+      .get_content_totals()
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get file retention stats")
+      })?;
+
+    Ok(Response::new(GetFileRetentionStatsReply {
+      last_run_at: stats.last_run_at.map(|last_run_at| last_run_at.to_string()),
+      last_run: Some(stats.last_run.into()),
+      deleted_versions: stats.deleted_versions,
+      reclaimed_bytes: stats.reclaimed_bytes,
+      stored_versions: totals.versions,
+      stored_bytes: totals.size_bytes,
+    }))
+  }
+
+  async fn enforce_file_retention(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<EnforceFileRetentionReply>, Status> {
+    let run = enforce_file_retention(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to enforce file retention")
+      })?;
+
+    Ok(Response::new(EnforceFileRetentionReply {
+      run: Some(run.into()),
+    }))
+  }
 }
@@ -1,4 +1,8 @@
 pub mod file_content_store;
+pub mod file_content_version_repository;
 pub mod file_interactor;
+pub mod file_jobs;
 pub mod file_metadata;
+pub mod file_redaction;
+pub mod file_retention;
 pub mod file_service;
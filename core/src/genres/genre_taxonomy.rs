@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GenreEdge {
+  pub parent_genre: String,
+  pub child_genre: String,
+}
+
+/**
+ * RYM's genre hierarchy. A genre can sit under more than one parent.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct GenreTaxonomy {
+  parents: HashMap<String, Vec<String>>,
+  children: HashMap<String, Vec<String>>,
+}
+
+impl GenreTaxonomy {
+  pub fn new(edges: Vec<GenreEdge>) -> Self {
+    let mut taxonomy = Self::default();
+    for edge in edges {
+      taxonomy
+        .parents
+        .entry(edge.child_genre.clone())
+        .or_default()
+        .push(edge.parent_genre.clone());
+      taxonomy
+        .children
+        .entry(edge.parent_genre)
+        .or_default()
+        .push(edge.child_genre);
+    }
+    taxonomy
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.parents.is_empty()
+  }
+
+  fn walk(
+    edges: &HashMap<String, Vec<String>>,
+    genre: &str,
+    max_distance: u32,
+    related: &mut Vec<(String, u32)>,
+  ) {
+    let mut visited = HashSet::from([genre.to_string()]);
+    let mut queue = VecDeque::from([(genre.to_string(), 0)]);
+    while let Some((current, distance)) = queue.pop_front() {
+      if distance == max_distance {
+        continue;
+      }
+      for next in edges.get(&current).into_iter().flatten() {
+        if visited.insert(next.clone()) {
+          related.push((next.clone(), distance + 1));
+          queue.push_back((next.clone(), distance + 1));
+        }
+      }
+    }
+  }
+
+  /**
+   * Ancestors and descendants of the genre within `max_distance` levels of it, along with how many
+   * levels away they are. Siblings aren't related, they only share a parent.
+   */
+  pub fn related_genres(&self, genre: &str, max_distance: u32) -> Vec<(String, u32)> {
+    let mut related = vec![];
+    Self::walk(&self.parents, genre, max_distance, &mut related);
+    Self::walk(&self.children, genre, max_distance, &mut related);
+    related
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn edge(parent_genre: &str, child_genre: &str) -> GenreEdge {
+    GenreEdge {
+      parent_genre: parent_genre.to_string(),
+      child_genre: child_genre.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_related_genres() {
+    let taxonomy = GenreTaxonomy::new(vec![
+      edge("Rock", "Psychedelic Rock"),
+      edge("Rock", "Post-Rock"),
+      edge("Psychedelic Rock", "Space Rock"),
+      edge("Space Rock", "Stoner Rock"),
+    ]);
+    let mut related = taxonomy.related_genres("Psychedelic Rock", 1);
+    related.sort();
+    assert_eq!(
+      related,
+      vec![("Rock".to_string(), 1), ("Space Rock".to_string(), 1)]
+    );
+    let mut related = taxonomy.related_genres("Stoner Rock", 2);
+    related.sort();
+    assert_eq!(
+      related,
+      vec![
+        ("Psychedelic Rock".to_string(), 2),
+        ("Space Rock".to_string(), 1)
+      ]
+    );
+    assert!(taxonomy.related_genres("Ambient", 2).is_empty());
+  }
+}
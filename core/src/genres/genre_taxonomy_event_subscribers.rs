@@ -0,0 +1,56 @@
+use super::{genre_taxonomy::GenreEdge, genre_taxonomy_repository::GenreTaxonomyRepository};
+use crate::{
+  context::ApplicationContext,
+  event_handler,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+    },
+  },
+  parser::parsed_file_data::ParsedFileData,
+};
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::info;
+
+async fn update_genre_taxonomy(
+  event_data: EventData,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  if let Event::FileParsed {
+    file_id: _,
+    file_name: _,
+    data: ParsedFileData::GenreTree(genre_tree),
+  } = event_data.payload.event
+  {
+    let edges = genre_tree
+      .genres
+      .into_iter()
+      .filter_map(|genre| {
+        genre.parent.map(|parent_genre| GenreEdge {
+          parent_genre,
+          child_genre: genre.name,
+        })
+      })
+      .collect::<Vec<_>>();
+    info!(count = edges.len(), "Updating genre taxonomy");
+    GenreTaxonomyRepository::new(Arc::clone(&app_context.sqlite_connection))
+      .put_all(edges)
+      .await?;
+  }
+  Ok(())
+}
+
+pub fn build_genre_taxonomy_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("update_genre_taxonomy")
+    .topic(Topic::Parser)
+    .batch_size(250)
+    .app_context(Arc::clone(&app_context))
+    .handler(event_handler!(update_genre_taxonomy))
+    .build()?])
+}
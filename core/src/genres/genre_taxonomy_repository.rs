@@ -0,0 +1,71 @@
+use super::genre_taxonomy::{GenreEdge, GenreTaxonomy};
+use crate::sqlite::SqliteConnection;
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+pub struct GenreTaxonomyRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl GenreTaxonomyRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Replaces every stored edge, the genre tree is always parsed whole
+   */
+  #[instrument(skip(self, edges), fields(count = edges.len()))]
+  pub async fn put_all(&self, edges: Vec<GenreEdge>) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM genre_edges", [])?;
+        for edge in edges {
+          tx.execute(
+            "INSERT OR IGNORE INTO genre_edges (parent_genre, child_genre) VALUES (?, ?)",
+            params![edge.parent_genre, edge.child_genre],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put genre edges");
+        anyhow!("Failed to put genre edges")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_taxonomy(&self) -> Result<GenreTaxonomy> {
+    let edges = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare("SELECT parent_genre, child_genre FROM genre_edges")?;
+        let rows = statement
+          .query_map([], |row| {
+            Ok(GenreEdge {
+              parent_genre: row.get(0)?,
+              child_genre: row.get(1)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find genre edges");
+        anyhow!("Failed to find genre edges")
+      })??;
+
+    Ok(GenreTaxonomy::new(edges))
+  }
+}
@@ -0,0 +1,3 @@
+pub mod genre_taxonomy;
+pub mod genre_taxonomy_event_subscribers;
+pub mod genre_taxonomy_repository;
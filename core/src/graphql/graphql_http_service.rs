@@ -0,0 +1,177 @@
+use super::graphql_schema::{build_schema, LuteSchema};
+use crate::{
+  context::ApplicationContext,
+  rest_gateway::rest_gateway_service::status_code,
+  tenant::tenant_id::{http_request_tenant_id, TenantId},
+};
+use async_graphql::http::GraphiQLSource;
+use std::{convert::Infallible, fmt::Display, sync::Arc};
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, Method, StatusCode},
+    Body, BoxFuture, Bytes, Context, Poll, Service,
+  },
+  server::NamedService,
+  Status,
+};
+use tracing::error;
+
+/**
+ * Serves the GraphQL API over plain HTTP at `/graphql/`, with GraphiQL on GET. Like the cover
+ * thumbnails, it's routed by path prefix alongside the gRPC services on the RPC server.
+ */
+#[derive(Clone)]
+pub struct GraphQlHttpService {
+  schema: LuteSchema,
+}
+
+impl GraphQlHttpService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      schema: build_schema(app_context),
+    }
+  }
+}
+
+impl NamedService for GraphQlHttpService {
+  const NAME: &'static str = "graphql";
+}
+
+fn response(status: StatusCode, content_type: &str, content: Vec<u8>) -> http::Response<BoxBody> {
+  let body = tonic::transport::Body::from(content)
+    .map_err(|e| Status::internal(e.to_string()))
+    .boxed_unsync();
+  let mut response = http::Response::new(body);
+  *response.status_mut() = status;
+  response
+    .headers_mut()
+    .insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+  response
+}
+
+async fn read_body<B>(body: B) -> Result<Vec<u8>, B::Error>
+where
+  B: Body<Data = Bytes>,
+{
+  let mut body = Box::pin(body);
+  let mut content = Vec::new();
+  while let Some(chunk) = body.data().await {
+    content.extend_from_slice(&chunk?);
+  }
+  Ok(content)
+}
+
+async fn execute<B>(schema: LuteSchema, tenant_id: TenantId, body: B) -> http::Response<BoxBody>
+where
+  B: Body<Data = Bytes>,
+  B::Error: Display,
+{
+  let content = match read_body(body).await {
+    Ok(content) => content,
+    Err(e) => {
+      error!(err = e.to_string(), "Failed to read GraphQL request");
+      return response(StatusCode::BAD_REQUEST, "text/plain", vec![]);
+    }
+  };
+  let request = match serde_json::from_slice::<async_graphql::Request>(&content) {
+    Ok(request) => request,
+    Err(e) => {
+      return response(
+        StatusCode::BAD_REQUEST,
+        "text/plain",
+        e.to_string().into_bytes(),
+      )
+    }
+  };
+  // Resolver errors are part of the GraphQL response, so the status stays 200
+  match serde_json::to_vec(&schema.execute(request.data(tenant_id)).await) {
+    Ok(content) => response(StatusCode::OK, "application/json", content),
+    Err(e) => {
+      error!(err = e.to_string(), "Failed to serialize GraphQL response");
+      response(StatusCode::INTERNAL_SERVER_ERROR, "text/plain", vec![])
+    }
+  }
+}
+
+impl<B> Service<http::Request<B>> for GraphQlHttpService
+where
+  B: Body<Data = Bytes> + Send + 'static,
+  B::Error: Display,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    let schema = self.schema.clone();
+    let method = request.method().clone();
+    Box::pin(async move {
+      match method {
+        Method::GET => Ok(response(
+          StatusCode::OK,
+          "text/html",
+          GraphiQLSource::build()
+            .endpoint("/graphql/")
+            .finish()
+            .into_bytes(),
+        )),
+        Method::POST => match http_request_tenant_id(&request) {
+          Ok(tenant_id) => Ok(execute(schema, tenant_id, request.into_body()).await),
+          Err(status) => Ok(response(
+            status_code(status.code()),
+            "text/plain",
+            status.message().as_bytes().to_vec(),
+          )),
+        },
+        _ => Ok(response(
+          StatusCode::METHOD_NOT_ALLOWED,
+          "text/plain",
+          vec![],
+        )),
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    auth::api_key::{ApiKeyScope, AuthenticatedKey},
+    profile::profile::ProfileId,
+    tenant::tenant_id::TENANT_METADATA_KEY,
+  };
+  use anyhow::Result;
+  use tonic::Code;
+
+  #[test]
+  fn test_tenant_bound_key_reads_own_profiles() -> Result<()> {
+    let alice = TenantId::try_from("alice".to_string())?;
+    let mut request = http::Request::post("/graphql/").body(())?;
+    request.extensions_mut().insert(AuthenticatedKey {
+      id: "key".to_string(),
+      scope: ApiKeyScope::ReadOnly,
+      tenant_id: Some(alice.clone()),
+    });
+    let tenant_id = http_request_tenant_id(&request)?;
+    assert_eq!(tenant_id, alice);
+    assert_ne!(
+      ProfileId::scoped(&tenant_id, "main".to_string())?,
+      ProfileId::scoped(&TenantId::default(), "main".to_string())?
+    );
+
+    request
+      .headers_mut()
+      .insert(TENANT_METADATA_KEY, "default".parse()?);
+    assert_eq!(
+      http_request_tenant_id(&request).unwrap_err().code(),
+      Code::PermissionDenied
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,62 @@
+use crate::{
+  albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  artists::{artist_interactor::ArtistInteractor, artist_read_model::ArtistReadModel},
+  files::file_metadata::file_name::FileName,
+};
+use async_graphql::dataloader::Loader;
+use std::{collections::HashMap, sync::Arc};
+
+/**
+ * Batches the album lookups of every parent in a query into one `find_many`
+ */
+pub struct AlbumLoader {
+  album_interactor: Arc<AlbumInteractor>,
+}
+
+impl AlbumLoader {
+  pub fn new(album_interactor: Arc<AlbumInteractor>) -> Self {
+    Self { album_interactor }
+  }
+}
+
+impl Loader<FileName> for AlbumLoader {
+  type Value = AlbumReadModel;
+  type Error = Arc<anyhow::Error>;
+
+  async fn load(
+    &self,
+    keys: &[FileName],
+  ) -> Result<HashMap<FileName, AlbumReadModel>, Self::Error> {
+    self
+      .album_interactor
+      .find_many(keys.to_vec())
+      .await
+      .map_err(Arc::new)
+  }
+}
+
+pub struct ArtistLoader {
+  artist_interactor: Arc<ArtistInteractor>,
+}
+
+impl ArtistLoader {
+  pub fn new(artist_interactor: Arc<ArtistInteractor>) -> Self {
+    Self { artist_interactor }
+  }
+}
+
+impl Loader<FileName> for ArtistLoader {
+  type Value = ArtistReadModel;
+  type Error = Arc<anyhow::Error>;
+
+  async fn load(
+    &self,
+    keys: &[FileName],
+  ) -> Result<HashMap<FileName, ArtistReadModel>, Self::Error> {
+    self
+      .artist_interactor
+      .find_many(keys.to_vec())
+      .await
+      .map_err(Arc::new)
+  }
+}
@@ -0,0 +1,525 @@
+use crate::{
+  albums::{
+    album_read_model::AlbumReadModel,
+    album_search_index::{AlbumSearchQuery, AlbumSearchSort, AlbumSearchSortField},
+    album_service::resolve_boost_profile,
+    album_text_search::AlbumTextMatchMode,
+  },
+  artists::artist_read_model::ArtistReadModel,
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  graphql::graphql_loaders::{AlbumLoader, ArtistLoader},
+  helpers::redisearch::SearchPagination,
+  profile::profile::{self, ProfileId},
+  proto,
+  recommendations::{
+    quantile_ranking::quantile_rank_interactor::QuantileRankAlbumAssessmentSettings,
+    recommendation_interactor::{AlbumAssessmentSettings, RecommendationInteractor},
+    seed::AlbumRecommendationSeed,
+    types::AlbumRecommendationSettings,
+  },
+  tenant::tenant_id::TenantId,
+};
+use async_graphql::{
+  dataloader::{DataLoader, Loader},
+  Context, EmptyMutation, EmptySubscription, Enum, InputObject, Object, Result, Schema,
+  SimpleObject,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::sync::Arc;
+use tokio::spawn;
+
+pub type LuteSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(app_context: Arc<ApplicationContext>) -> LuteSchema {
+  Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+    .limit_depth(app_context.settings.graphql.max_depth)
+    .limit_complexity(app_context.settings.graphql.max_complexity)
+    .data(DataLoader::new(
+      AlbumLoader::new(Arc::clone(&app_context.album_interactor)),
+      spawn,
+    ))
+    .data(DataLoader::new(
+      ArtistLoader::new(Arc::clone(&app_context.artist_interactor)),
+      spawn,
+    ))
+    .data(RecommendationInteractor::new(Arc::clone(&app_context)))
+    .data(app_context)
+    .finish()
+}
+
+fn app_context<'a>(ctx: &Context<'a>) -> Result<&'a Arc<ApplicationContext>> {
+  ctx.data::<Arc<ApplicationContext>>()
+}
+
+/**
+ * Resolved from the request's key and tenant header before the query runs
+ */
+fn tenant_id<'a>(ctx: &Context<'a>) -> Result<&'a TenantId> {
+  ctx.data::<TenantId>()
+}
+
+fn parse_file_names(file_names: Vec<String>) -> Result<Vec<FileName>> {
+  Ok(
+    file_names
+      .into_iter()
+      .map(FileName::try_from)
+      .collect::<anyhow::Result<Vec<_>>>()?,
+  )
+}
+
+/**
+ * Loads through the query's batching loader, in the order asked for and skipping what isn't stored
+ */
+async fn load_ordered<T>(ctx: &Context<'_>, file_names: Vec<FileName>) -> Result<Vec<T::Value>>
+where
+  T: Loader<FileName, Error = Arc<anyhow::Error>>,
+{
+  let mut values = ctx
+    .data::<DataLoader<T>>()?
+    .load_many(file_names.iter().cloned())
+    .await?;
+  Ok(
+    file_names
+      .iter()
+      .filter_map(|file_name| values.remove(file_name))
+      .collect(),
+  )
+}
+
+async fn find_albums(ctx: &Context<'_>, file_names: Vec<FileName>) -> Result<Vec<Album>> {
+  Ok(
+    load_ordered::<AlbumLoader>(ctx, file_names)
+      .await?
+      .into_iter()
+      .map(Album)
+      .collect(),
+  )
+}
+
+async fn find_artists(ctx: &Context<'_>, file_names: Vec<FileName>) -> Result<Vec<Artist>> {
+  Ok(
+    load_ordered::<ArtistLoader>(ctx, file_names)
+      .await?
+      .into_iter()
+      .map(Artist)
+      .collect(),
+  )
+}
+
+pub struct Album(AlbumReadModel);
+
+#[Object]
+impl Album {
+  async fn name(&self) -> &str {
+    &self.0.name
+  }
+
+  async fn file_name(&self) -> String {
+    self.0.file_name.to_string()
+  }
+
+  async fn rating(&self) -> f32 {
+    self.0.rating
+  }
+
+  async fn rating_count(&self) -> u32 {
+    self.0.rating_count
+  }
+
+  async fn primary_genres(&self) -> &[String] {
+    &self.0.primary_genres
+  }
+
+  async fn secondary_genres(&self) -> &[String] {
+    &self.0.secondary_genres
+  }
+
+  async fn descriptors(&self) -> &[String] {
+    &self.0.descriptors
+  }
+
+  async fn tags(&self) -> &[String] {
+    &self.0.tags
+  }
+
+  async fn cluster_id(&self) -> Option<u32> {
+    self.0.cluster_id
+  }
+
+  async fn languages(&self) -> &[String] {
+    &self.0.languages
+  }
+
+  async fn release_date(&self) -> Option<NaiveDate> {
+    self.0.release_date
+  }
+
+  async fn cover_image_url(&self) -> Option<&str> {
+    self.0.cover_image_url.as_deref()
+  }
+
+  async fn spotify_id(&self) -> Option<&str> {
+    self.0.spotify_id.as_deref()
+  }
+
+  async fn bandcamp_url(&self) -> Option<&str> {
+    self.0.bandcamp_url.as_deref()
+  }
+
+  async fn is_various_artists(&self) -> bool {
+    self.0.is_various_artists
+  }
+
+  async fn artists(&self, ctx: &Context<'_>) -> Result<Vec<Artist>> {
+    find_artists(
+      ctx,
+      self
+        .0
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.clone())
+        .collect(),
+    )
+    .await
+  }
+}
+
+pub struct Artist(ArtistReadModel);
+
+#[Object]
+impl Artist {
+  async fn name(&self) -> &str {
+    &self.0.name
+  }
+
+  async fn file_name(&self) -> String {
+    self.0.file_name.to_string()
+  }
+
+  async fn alternate_names(&self) -> &[String] {
+    &self.0.alternate_names
+  }
+
+  async fn albums(&self, ctx: &Context<'_>) -> Result<Vec<Album>> {
+    find_albums(ctx, self.0.album_file_names.clone()).await
+  }
+
+  /**
+   * Albums the artist is only credited on through tracks, like compilations
+   */
+  async fn appearances(&self, ctx: &Context<'_>) -> Result<Vec<Album>> {
+    find_albums(ctx, self.0.appearance_album_file_names.clone()).await
+  }
+}
+
+#[derive(SimpleObject)]
+pub struct ProfileAlbum {
+  factor: u32,
+  album: Album,
+}
+
+pub struct Profile(profile::Profile);
+
+#[Object]
+impl Profile {
+  async fn id(&self) -> String {
+    self.0.id.local_id()
+  }
+
+  async fn name(&self) -> &str {
+    &self.0.name
+  }
+
+  async fn last_updated_at(&self) -> NaiveDateTime {
+    self.0.last_updated_at
+  }
+
+  async fn album_count(&self) -> u32 {
+    self.0.albums.len() as u32
+  }
+
+  async fn albums(&self, ctx: &Context<'_>) -> Result<Vec<ProfileAlbum>> {
+    let albums = find_albums(ctx, self.0.album_file_names()).await?;
+    Ok(
+      albums
+        .into_iter()
+        .map(|album| ProfileAlbum {
+          factor: self.0.albums.get(&album.0.file_name).copied().unwrap_or(0),
+          album,
+        })
+        .collect(),
+    )
+  }
+}
+
+#[derive(SimpleObject)]
+pub struct AlbumSearchResult {
+  albums: Vec<Album>,
+  total: u32,
+}
+
+#[derive(SimpleObject)]
+pub struct AlbumRecommendation {
+  album: Album,
+  score: f32,
+  exploratory: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct AlbumRecommendations {
+  /**
+   * Below 1 when the time budget ran out before every candidate was assessed
+   */
+  completeness: f32,
+  recommendations: Vec<AlbumRecommendation>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum AlbumSortField {
+  Rating,
+  RatingCount,
+  ReleaseDate,
+  Name,
+}
+
+impl From<AlbumSortField> for AlbumSearchSortField {
+  fn from(val: AlbumSortField) -> Self {
+    match val {
+      AlbumSortField::Rating => AlbumSearchSortField::Rating,
+      AlbumSortField::RatingCount => AlbumSearchSortField::RatingCount,
+      AlbumSortField::ReleaseDate => AlbumSearchSortField::ReleaseDate,
+      AlbumSortField::Name => AlbumSearchSortField::Name,
+    }
+  }
+}
+
+#[derive(InputObject)]
+pub struct AlbumSearchBoostProfileInput {
+  name: String,
+  name_weight: f32,
+  artist_name_weight: f32,
+  rating_boost: f32,
+  recency_boost: f32,
+  recency_scale_years: u32,
+}
+
+impl From<AlbumSearchBoostProfileInput> for proto::AlbumSearchBoostProfile {
+  fn from(val: AlbumSearchBoostProfileInput) -> Self {
+    proto::AlbumSearchBoostProfile {
+      name: val.name,
+      name_weight: val.name_weight,
+      artist_name_weight: val.artist_name_weight,
+      rating_boost: val.rating_boost,
+      recency_boost: val.recency_boost,
+      recency_scale_years: val.recency_scale_years,
+    }
+  }
+}
+
+#[derive(InputObject, Default)]
+pub struct AlbumSearchInput {
+  text: Option<String>,
+  /**
+   * Match text only against fields in its own script, without typo tolerance
+   */
+  #[graphql(default)]
+  strict_text_match: bool,
+  exact_name: Option<String>,
+  #[graphql(default)]
+  include_artists: Vec<String>,
+  #[graphql(default)]
+  exclude_artists: Vec<String>,
+  #[graphql(default)]
+  include_primary_genres: Vec<String>,
+  #[graphql(default)]
+  exclude_primary_genres: Vec<String>,
+  #[graphql(default)]
+  include_secondary_genres: Vec<String>,
+  #[graphql(default)]
+  exclude_secondary_genres: Vec<String>,
+  #[graphql(default)]
+  include_languages: Vec<String>,
+  #[graphql(default)]
+  exclude_languages: Vec<String>,
+  #[graphql(default)]
+  include_descriptors: Vec<String>,
+  #[graphql(default)]
+  exclude_descriptors: Vec<String>,
+  #[graphql(default)]
+  include_tags: Vec<String>,
+  #[graphql(default)]
+  exclude_tags: Vec<String>,
+  #[graphql(default)]
+  include_cluster_ids: Vec<u32>,
+  min_release_year: Option<u32>,
+  max_release_year: Option<u32>,
+  min_rating: Option<f32>,
+  min_rating_count: Option<u32>,
+  include_duplicates: Option<bool>,
+  sort_by: Option<AlbumSortField>,
+  #[graphql(default)]
+  sort_descending: bool,
+  /**
+   * Name of a stored boost profile, the configured default applies when unset
+   */
+  boost_profile: Option<String>,
+  /**
+   * Used instead of a stored profile
+   */
+  boost_profile_override: Option<AlbumSearchBoostProfileInput>,
+}
+
+/**
+ * Profiles and recommendations are read as the request's tenant's
+ */
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+  async fn album(&self, ctx: &Context<'_>, file_name: String) -> Result<Option<Album>> {
+    let file_name = FileName::try_from(file_name)?;
+    Ok(
+      app_context(ctx)?
+        .album_interactor
+        .find(&file_name)
+        .await?
+        .map(Album),
+    )
+  }
+
+  async fn albums(&self, ctx: &Context<'_>, file_names: Vec<String>) -> Result<Vec<Album>> {
+    find_albums(ctx, parse_file_names(file_names)?).await
+  }
+
+  async fn search_albums(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default)] query: AlbumSearchInput,
+    offset: Option<u32>,
+    limit: Option<u32>,
+  ) -> Result<AlbumSearchResult> {
+    let app_context = app_context(ctx)?;
+    let boost_profile = resolve_boost_profile(
+      &app_context.album_interactor,
+      &app_context.settings,
+      query.boost_profile,
+      query.boost_profile_override.map(Into::into),
+    )
+    .await
+    .map_err(|e| e.message().to_string())?;
+    let search_query = AlbumSearchQuery {
+      text: query.text,
+      text_match_mode: if query.strict_text_match {
+        AlbumTextMatchMode::Strict
+      } else {
+        AlbumTextMatchMode::Loose
+      },
+      exact_name: query.exact_name,
+      include_artists: parse_file_names(query.include_artists)?,
+      exclude_artists: parse_file_names(query.exclude_artists)?,
+      include_primary_genres: query.include_primary_genres,
+      exclude_primary_genres: query.exclude_primary_genres,
+      include_secondary_genres: query.include_secondary_genres,
+      exclude_secondary_genres: query.exclude_secondary_genres,
+      include_languages: query.include_languages,
+      exclude_languages: query.exclude_languages,
+      include_descriptors: query.include_descriptors,
+      exclude_descriptors: query.exclude_descriptors,
+      include_tags: query.include_tags,
+      exclude_tags: query.exclude_tags,
+      include_cluster_ids: query.include_cluster_ids,
+      min_release_year: query.min_release_year,
+      max_release_year: query.max_release_year,
+      min_rating: query.min_rating,
+      min_rating_count: query.min_rating_count,
+      include_duplicates: query.include_duplicates,
+      boost_profile,
+      sort: query.sort_by.map(|field| AlbumSearchSort {
+        field: field.into(),
+        descending: query.sort_descending,
+      }),
+      ..Default::default()
+    };
+    let results = app_context
+      .album_interactor
+      .search(
+        &search_query,
+        Some(&SearchPagination {
+          offset: offset.map(|offset| offset as usize),
+          limit: limit.map(|limit| limit as usize),
+        }),
+      )
+      .await?;
+    Ok(AlbumSearchResult {
+      albums: results.albums.into_iter().map(Album).collect(),
+      total: results.total as u32,
+    })
+  }
+
+  async fn artist(&self, ctx: &Context<'_>, file_name: String) -> Result<Option<Artist>> {
+    let file_name = FileName::try_from(file_name)?;
+    Ok(
+      app_context(ctx)?
+        .artist_interactor
+        .find(file_name)
+        .await?
+        .map(Artist),
+    )
+  }
+
+  async fn profile(&self, ctx: &Context<'_>, id: String) -> Result<Option<Profile>> {
+    let id = ProfileId::scoped(tenant_id(ctx)?, id)?;
+    Ok(
+      app_context(ctx)?
+        .profile_interactor
+        .find_profile(&id)
+        .await?
+        .map(Profile),
+    )
+  }
+
+  async fn profiles(&self, ctx: &Context<'_>) -> Result<Vec<Profile>> {
+    let profiles = app_context(ctx)?
+      .profile_interactor
+      .get_tenant_profiles(tenant_id(ctx)?)
+      .await?;
+    Ok(profiles.into_iter().map(Profile).collect())
+  }
+
+  /**
+   * Quantile rank recommendations for a profile, with the default assessment settings
+   */
+  async fn recommend_albums(
+    &self,
+    ctx: &Context<'_>,
+    profile_id: String,
+    #[graphql(default = 20)] count: u32,
+  ) -> Result<AlbumRecommendations> {
+    let tenant_id = tenant_id(ctx)?;
+    let recommendations = ctx
+      .data::<RecommendationInteractor>()?
+      .recommend_albums(
+        tenant_id,
+        AlbumRecommendationSeed::Profile(ProfileId::scoped(tenant_id, profile_id)?),
+        AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+        AlbumRecommendationSettings {
+          count,
+          ..Default::default()
+        },
+      )
+      .await?;
+    Ok(AlbumRecommendations {
+      completeness: recommendations.completeness,
+      recommendations: recommendations
+        .recommendations
+        .into_iter()
+        .map(|recommendation| AlbumRecommendation {
+          album: Album(recommendation.album),
+          score: recommendation.assessment.score,
+          exploratory: recommendation.exploratory,
+        })
+        .collect(),
+    })
+  }
+}
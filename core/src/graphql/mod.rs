@@ -0,0 +1,3 @@
+pub mod graphql_http_service;
+pub mod graphql_loaders;
+pub mod graphql_schema;
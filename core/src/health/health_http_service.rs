@@ -0,0 +1,91 @@
+use super::health_interactor::{HealthInteractor, HealthReport};
+use crate::context::ApplicationContext;
+use std::{convert::Infallible, sync::Arc};
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, Method, StatusCode},
+    Body, BoxFuture, Context, Poll, Service,
+  },
+  server::NamedService,
+  Status,
+};
+
+/**
+ * Serves health checks over plain HTTP for load balancers and Kubernetes probes. `/healthz/` and
+ * `/healthz/ready` check every dependency, `/healthz/live` only that the event subscribers are
+ * running. Unhealthy reports are served with a 503.
+ */
+#[derive(Clone)]
+pub struct HealthHttpService {
+  health_interactor: Arc<HealthInteractor>,
+}
+
+impl HealthHttpService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      health_interactor: Arc::new(HealthInteractor::new(app_context)),
+    }
+  }
+}
+
+impl NamedService for HealthHttpService {
+  const NAME: &'static str = "healthz";
+}
+
+fn response(status: StatusCode, content: Vec<u8>) -> http::Response<BoxBody> {
+  let body = tonic::transport::Body::from(content)
+    .map_err(|e| Status::internal(e.to_string()))
+    .boxed_unsync();
+  let mut response = http::Response::new(body);
+  *response.status_mut() = status;
+  response
+}
+
+fn report_response(report: HealthReport) -> http::Response<BoxBody> {
+  let status = if report.ok {
+    StatusCode::OK
+  } else {
+    StatusCode::SERVICE_UNAVAILABLE
+  };
+  let mut response = response(status, serde_json::to_vec(&report).unwrap_or_default());
+  response
+    .headers_mut()
+    .insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+  response
+}
+
+impl<B> Service<http::Request<B>> for HealthHttpService
+where
+  B: Body + Send + 'static,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    let health_interactor = Arc::clone(&self.health_interactor);
+    let method = request.method().clone();
+    let probe = request
+      .uri()
+      .path()
+      .trim_start_matches('/')
+      .trim_start_matches(Self::NAME)
+      .trim_matches('/')
+      .to_string();
+    Box::pin(async move {
+      if method != Method::GET {
+        return Ok(response(StatusCode::METHOD_NOT_ALLOWED, vec![]));
+      }
+      Ok(match probe.as_str() {
+        "" | "ready" => report_response(health_interactor.readiness().await),
+        "live" => report_response(health_interactor.liveness().await),
+        _ => response(StatusCode::NOT_FOUND, vec![]),
+      })
+    })
+  }
+}
@@ -0,0 +1,135 @@
+use super::subscriber_heartbeats::SubscriberHeartbeats;
+use crate::{
+  context::ApplicationContext,
+  settings::{Settings, StorageMode},
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use elasticsearch::Elasticsearch;
+use rustis::{
+  bb8::Pool,
+  client::PooledClientManager,
+  commands::{ConnectionCommands, PingOptions},
+};
+use serde::Serialize;
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{join, time::timeout};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthCheck {
+  pub name: &'static str,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HealthReport {
+  pub ok: bool,
+  pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+  fn new(checks: Vec<HealthCheck>) -> Self {
+    Self {
+      ok: checks.iter().all(|check| check.ok),
+      checks,
+    }
+  }
+}
+
+pub struct HealthInteractor {
+  settings: Arc<Settings>,
+  sqlite_connection: Arc<SqliteConnection>,
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  elasticsearch_client: Arc<Elasticsearch>,
+  subscriber_heartbeats: Arc<SubscriberHeartbeats>,
+}
+
+impl HealthInteractor {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      settings: Arc::clone(&app_context.settings),
+      sqlite_connection: Arc::clone(&app_context.sqlite_connection),
+      redis_connection_pool: Arc::clone(&app_context.redis_connection_pool),
+      elasticsearch_client: Arc::clone(&app_context.elasticsearch_client),
+      subscriber_heartbeats: Arc::clone(&app_context.subscriber_heartbeats),
+    }
+  }
+
+  async fn run_check(
+    &self,
+    name: &'static str,
+    check: impl Future<Output = Result<()>>,
+  ) -> HealthCheck {
+    let result = timeout(
+      Duration::from_secs(self.settings.health.check_timeout_seconds),
+      check,
+    )
+    .await
+    .unwrap_or_else(|_| Err(anyhow!("Timed out")));
+    HealthCheck {
+      name,
+      ok: result.is_ok(),
+      error: result.err().map(|e| e.to_string()),
+    }
+  }
+
+  async fn check_redis(&self) -> Result<()> {
+    let connection = self.redis_connection_pool.get().await?;
+    connection.ping::<String>(PingOptions::default()).await?;
+    Ok(())
+  }
+
+  async fn check_elasticsearch(&self) -> Result<()> {
+    self
+      .elasticsearch_client
+      .ping()
+      .send()
+      .await?
+      .error_for_status_code()?;
+    Ok(())
+  }
+
+  async fn check_subscribers(&self) -> Result<()> {
+    let stalled = self.subscriber_heartbeats.stalled(Duration::from_secs(
+      self.settings.health.subscriber_stall_seconds,
+    ));
+    if !stalled.is_empty() {
+      return Err(anyhow!("Stalled subscribers: {}", stalled.join(", ")));
+    }
+    Ok(())
+  }
+
+  /**
+   * Whether the event subscribers are still running. Only a restart brings a dead one back.
+   */
+  pub async fn liveness(&self) -> HealthReport {
+    HealthReport::new(vec![
+      self
+        .run_check("event_subscribers", self.check_subscribers())
+        .await,
+    ])
+  }
+
+  /**
+   * Whether every dependency is reachable, for deciding if traffic should be sent here. Redis is
+   * skipped in sqlite storage mode, where it's only needed by features that aren't in use.
+   */
+  pub async fn readiness(&self) -> HealthReport {
+    let (sqlite, elasticsearch, subscribers, redis) = join!(
+      self.run_check("sqlite", self.sqlite_connection.check_writable()),
+      self.run_check("elasticsearch", self.check_elasticsearch()),
+      self.run_check("event_subscribers", self.check_subscribers()),
+      async {
+        match self.settings.storage.mode {
+          StorageMode::Redis => Some(self.run_check("redis", self.check_redis()).await),
+          StorageMode::Sqlite => None,
+        }
+      }
+    );
+    let mut checks = vec![sqlite, elasticsearch, subscribers];
+    checks.extend(redis);
+    HealthReport::new(checks)
+  }
+}
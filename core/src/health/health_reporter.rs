@@ -0,0 +1,35 @@
+use super::health_interactor::{HealthInteractor, HealthReport};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tonic_health::{server::HealthReporter, ServingStatus};
+
+fn serving_status(report: &HealthReport) -> ServingStatus {
+  if report.ok {
+    ServingStatus::Serving
+  } else {
+    ServingStatus::NotServing
+  }
+}
+
+/**
+ * Keeps the statuses of the gRPC health checking protocol current. The overall status and the
+ * `readiness` service follow the readiness checks, the `liveness` service the liveness ones.
+ */
+pub async fn run_health_reporter(
+  health_interactor: Arc<HealthInteractor>,
+  mut health_reporter: HealthReporter,
+  interval: Duration,
+) {
+  loop {
+    let readiness = serving_status(&health_interactor.readiness().await);
+    let liveness = serving_status(&health_interactor.liveness().await);
+    health_reporter.set_service_status("", readiness).await;
+    health_reporter
+      .set_service_status("readiness", readiness)
+      .await;
+    health_reporter
+      .set_service_status("liveness", liveness)
+      .await;
+    sleep(interval).await;
+  }
+}
@@ -0,0 +1,4 @@
+pub mod health_http_service;
+pub mod health_interactor;
+pub mod health_reporter;
+pub mod subscriber_heartbeats;
@@ -0,0 +1,66 @@
+use std::{
+  collections::HashMap,
+  sync::RwLock,
+  time::{Duration, Instant},
+};
+
+/**
+ * When each event subscriber last started a poll, kept in memory for the health checks. A
+ * subscriber whose task has died stops beating.
+ */
+#[derive(Default)]
+pub struct SubscriberHeartbeats {
+  heartbeats: RwLock<HashMap<String, Instant>>,
+}
+
+impl SubscriberHeartbeats {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn beat(&self, subscriber_id: &str) {
+    if let Ok(mut heartbeats) = self.heartbeats.write() {
+      heartbeats.insert(subscriber_id.to_string(), Instant::now());
+    }
+  }
+
+  fn stalled_at(&self, now: Instant, threshold: Duration) -> Vec<String> {
+    let Ok(heartbeats) = self.heartbeats.read() else {
+      return vec![];
+    };
+    let mut stalled = heartbeats
+      .iter()
+      .filter(|(_, beat)| now.saturating_duration_since(**beat) > threshold)
+      .map(|(subscriber_id, _)| subscriber_id.clone())
+      .collect::<Vec<_>>();
+    stalled.sort();
+    stalled
+  }
+
+  /**
+   * Ids of the subscribers that haven't beaten within the threshold
+   */
+  pub fn stalled(&self, threshold: Duration) -> Vec<String> {
+    self.stalled_at(Instant::now(), threshold)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stalled() {
+    let heartbeats = SubscriberHeartbeats::new();
+    heartbeats.beat("parser");
+    heartbeats.beat("album");
+    let later = Instant::now() + Duration::from_secs(60);
+    assert!(heartbeats
+      .stalled_at(later, Duration::from_secs(120))
+      .is_empty());
+    assert_eq!(
+      heartbeats.stalled_at(later, Duration::from_secs(30)),
+      vec!["album".to_string(), "parser".to_string()]
+    );
+  }
+}
@@ -0,0 +1,50 @@
+/**
+ * Splits CSV content into records of fields, paired with the 1-based line each record starts on.
+ * Quoted fields may contain commas, newlines and doubled quotes. Blank records are dropped.
+ */
+pub fn parse_csv_records(content: &str) -> Vec<(usize, Vec<String>)> {
+  let mut records = Vec::new();
+  let mut record = Vec::new();
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut line = 1;
+  let mut record_line = 1;
+  let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if in_quotes => {
+        if chars.peek() == Some(&'"') {
+          field.push('"');
+          chars.next();
+        } else {
+          in_quotes = false;
+        }
+      }
+      '"' if field.is_empty() => in_quotes = true,
+      ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+      '\r' if !in_quotes => {}
+      '\n' if !in_quotes => {
+        record.push(std::mem::take(&mut field));
+        records.push((record_line, std::mem::take(&mut record)));
+        line += 1;
+        record_line = line;
+      }
+      c => {
+        if c == '\n' {
+          line += 1;
+        }
+        field.push(c)
+      }
+    }
+  }
+  if !field.is_empty() || !record.is_empty() {
+    record.push(field);
+    records.push((record_line, record));
+  }
+
+  records
+    .into_iter()
+    .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()))
+    .collect()
+}
@@ -4,7 +4,13 @@ use anyhow::{anyhow, Result};
 use chrono::{Duration, NaiveDateTime};
 use rusqlite::{params, types::Value, ToSql};
 use serde::{de::DeserializeOwned, Serialize};
-use std::{borrow::BorrowMut, collections::HashMap, rc::Rc, sync::Arc};
+use std::{
+  borrow::BorrowMut,
+  collections::{hash_map::DefaultHasher, HashMap},
+  hash::{Hash, Hasher},
+  rc::Rc,
+  sync::{Arc, RwLock},
+};
 use tracing::{error, instrument};
 
 #[derive(Debug)]
@@ -44,6 +50,23 @@ pub struct DocumentFindResult<T> {
   pub range_size: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentCollectionStats {
+  pub collection: String,
+  pub row_count: u64,
+  pub size_bytes: u64,
+}
+
+/**
+ * Keeps roughly `percent` of new keys. Hashing the key rather than rolling a die keeps the same
+ * keys out across retries.
+ */
+fn is_sampled_in(key: &str, percent: u32) -> bool {
+  let mut hasher = DefaultHasher::new();
+  key.hash(&mut hasher);
+  hasher.finish() % 100 < percent as u64
+}
+
 /**
  * DocumentStore is a lightweight helper for interacting with jsonb documents in the sqlite database
  * as if it were a document store. This is for simple use cases where a rigid relational schema is
@@ -53,11 +76,78 @@ pub struct DocumentFindResult<T> {
 #[derive(Debug, Clone)]
 pub struct DocumentStore {
   sqlite_connection: Arc<SqliteConnection>,
+  /**
+   * Collections in sampling mode, mapped to the percent of new documents kept. This is only held in
+   * memory, so after a restart collections are written in full until the next quota check samples
+   * them again.
+   */
+  sample_percents: Arc<RwLock<HashMap<String, u32>>>,
 }
 
 impl DocumentStore {
   pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
-    Self { sqlite_connection }
+    Self {
+      sqlite_connection,
+      sample_percents: Arc::new(RwLock::new(HashMap::new())),
+    }
+  }
+
+  pub fn set_sample_percent(&self, collection: &str, percent: Option<u32>) -> Result<()> {
+    let mut sample_percents = self
+      .sample_percents
+      .write()
+      .map_err(|_| anyhow!("Document store sampling lock poisoned"))?;
+    match percent {
+      Some(percent) => sample_percents.insert(collection.to_string(), percent.min(100)),
+      None => sample_percents.remove(collection),
+    };
+    Ok(())
+  }
+
+  pub fn sample_percent(&self, collection: &str) -> Result<Option<u32>> {
+    Ok(
+      self
+        .sample_percents
+        .read()
+        .map_err(|_| anyhow!("Document store sampling lock poisoned"))?
+        .get(collection)
+        .copied(),
+    )
+  }
+
+  #[instrument(skip(self), name = "DocumentStore::collection_stats")]
+  pub async fn collection_stats(&self) -> Result<Vec<DocumentCollectionStats>> {
+    let stats = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT collection, COUNT(*), COALESCE(SUM(LENGTH(json)), 0)
+          FROM document_store
+          GROUP BY collection;
+          ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+          Ok(DocumentCollectionStats {
+            collection: row.get::<_, String>(0)?,
+            row_count: row.get::<_, i64>(1)? as u64,
+            size_bytes: row.get::<_, i64>(2)? as u64,
+          })
+        })?;
+        let rows = rows.collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get collection stats from sqlite database"
+        );
+        anyhow!("Failed to get collection stats from sqlite database")
+      })??;
+    Ok(stats)
   }
 
   #[instrument(skip(self), name = "DocumentStore::setup_indexes")]
@@ -207,20 +297,95 @@ impl DocumentStore {
     Ok(result)
   }
 
+  /**
+   * Matching documents, most recently created first. Every match is returned without a limit.
+   */
+  #[instrument(skip(self), name = "DocumentStore::find_latest")]
+  pub async fn find_latest<T: DeserializeOwned + Send + Sync>(
+    &self,
+    collection: &str,
+    filter: DocumentFilter,
+    limit: Option<usize>,
+  ) -> Result<Vec<Document<T>>> {
+    let mut filter = filter;
+    // A negative limit is no limit in sqlite
+    let limit = limit.map_or(-1, |limit| limit as i64);
+    let (sql, params) = filter.borrow_mut().to_sql(collection.to_string())?;
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut params = params
+          .iter()
+          .map(|(k, v)| (k.as_ref(), v as &dyn ToSql))
+          .collect::<Vec<_>>();
+        params.push((":limit", &limit as &dyn ToSql));
+        // Keys break ties between documents created in the same second
+        let mut stmt = conn.prepare(&format!(
+          "{} ORDER BY created_at DESC, key DESC LIMIT :limit",
+          sql
+        ))?;
+        let rows = stmt.query_map(params.as_slice(), |row| {
+          Ok((
+            row.get::<_, u64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, NaiveDateTime>(4)?,
+            row.get::<_, NaiveDateTime>(5)?,
+            row.get::<_, Option<NaiveDateTime>>(6)?,
+          ))
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find latest from sqlite database"
+        );
+        anyhow!("Failed to find latest from sqlite database")
+      })??;
+    Ok(
+      rows
+        .into_iter()
+        .filter_map(
+          |(id, collection, key, json, created_at, updated_at, expires_at)| {
+            serde_json::from_str::<T>(&json)
+              .inspect_err(|e| error!(err = e.to_string(), "Failed to deserialize document"))
+              .ok()
+              .map(|document| Document {
+                id,
+                collection,
+                key,
+                document,
+                created_at,
+                updated_at,
+                expires_at,
+              })
+          },
+        )
+        .collect(),
+    )
+  }
+
   #[instrument(skip(self, entries), name = "DocumentStore::put_many")]
   pub async fn put_many<T: Serialize + Send + Sync>(
     &self,
     collection: &str,
     entries: Vec<(String, T, Option<Duration>)>,
   ) -> Result<()> {
+    let sample_percent = self.sample_percent(collection)?;
     let entries = entries
       .into_iter()
       .map(|(key, document, ttl)| {
         let expires_at = ttl.map(|ttl| chrono::Utc::now().naive_utc() + ttl);
         let json = serde_json::to_string(&document)?;
-        Ok((key.to_string(), json, expires_at))
+        let sampled_in = sample_percent.map_or(true, |percent| is_sampled_in(&key, percent));
+        Ok((key, json, expires_at, sampled_in))
       })
-      .collect::<Result<Vec<(String, String, Option<NaiveDateTime>)>>>()?;
+      .collect::<Result<Vec<(String, String, Option<NaiveDateTime>, bool)>>>()?;
     let collection = collection.to_string();
     self
       .sqlite_connection
@@ -228,7 +393,19 @@ impl DocumentStore {
       .await?
       .interact(move |conn| {
         let tx = conn.transaction()?;
-        for (key, json, expires_at) in entries.into_iter() {
+        for (key, json, expires_at, sampled_in) in entries.into_iter() {
+          if !sampled_in {
+            // Sampled-out keys still receive updates, they just can't be inserted
+            tx.execute(
+              "
+              UPDATE document_store
+              SET json = jsonb(?), expires_at = ?, updated_at = CURRENT_TIMESTAMP
+              WHERE collection = ? AND key = ?
+              ",
+              params![json, expires_at, collection, key],
+            )?;
+            continue;
+          }
           tx.execute(
             "
             INSERT INTO document_store (collection, key, json, expires_at)
@@ -512,3 +689,76 @@ impl DocumentStore {
     self.delete_many(collection, vec![key.to_string()]).await
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::{json, Value as JsonValue};
+
+  #[tokio::test]
+  async fn test_find_latest() -> Result<()> {
+    let doc_store = DocumentStore::new(Arc::new(SqliteConnection::new_for_test().await?));
+    for (key, group) in [("a", "x"), ("b", "x"), ("c", "y"), ("d", "x")] {
+      doc_store
+        .put("test", key, json!({ "group": group }), None)
+        .await?;
+    }
+    let keys = doc_store
+      .find_latest::<JsonValue>(
+        "test",
+        DocumentFilter::new().condition("group", "=", "x").build(),
+        Some(2),
+      )
+      .await?
+      .into_iter()
+      .map(|doc| doc.key)
+      .collect::<Vec<_>>();
+    assert_eq!(keys, vec!["d", "b"]);
+    assert_eq!(
+      doc_store
+        .find_latest::<JsonValue>("test", DocumentFilter::new().build(), None)
+        .await?
+        .len(),
+      4
+    );
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn test_put_many_sampling() -> Result<()> {
+    let doc_store = DocumentStore::new(Arc::new(SqliteConnection::new_for_test().await?));
+    let keys = (0..20).map(|i| i.to_string()).collect::<Vec<_>>();
+    let sampled_out_key = keys
+      .iter()
+      .find(|key| !is_sampled_in(key, 50))
+      .unwrap()
+      .clone();
+    doc_store
+      .put("test", &sampled_out_key, json!({ "version": 1 }), None)
+      .await?;
+
+    doc_store.set_sample_percent("test", Some(50))?;
+    doc_store
+      .put_many(
+        "test",
+        keys
+          .iter()
+          .map(|key| (key.clone(), json!({ "version": 2 }), None))
+          .collect(),
+      )
+      .await?;
+    let documents = doc_store
+      .find_many_by_key::<JsonValue>("test", keys.clone())
+      .await?;
+    assert!(documents.values().all(|doc| doc.document["version"] == 2));
+    assert!(documents.contains_key(&sampled_out_key));
+    assert_eq!(
+      documents.len(),
+      keys
+        .iter()
+        .filter(|key| is_sampled_in(key, 50) || **key == sampled_out_key)
+        .count()
+    );
+    Ok(())
+  }
+}
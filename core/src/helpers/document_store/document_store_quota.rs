@@ -0,0 +1,149 @@
+use super::document_store::DocumentCollectionStats;
+use crate::{
+  context::ApplicationContext,
+  events::event::{Event, EventPayloadBuilder, Topic},
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+  settings::DocumentStoreQuotaSettings,
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{info, warn};
+
+pub fn is_quota_exceeded(
+  stats: &DocumentCollectionStats,
+  quota: &DocumentStoreQuotaSettings,
+) -> bool {
+  quota
+    .max_rows
+    .is_some_and(|max_rows| stats.row_count > max_rows)
+    || quota
+      .max_bytes
+      .is_some_and(|max_bytes| stats.size_bytes > max_bytes)
+}
+
+/**
+ * Alerts on every collection over its soft quota and flips the ones that allow it into sampling
+ * mode. Sampling is lifted once the collection is back under quota.
+ */
+async fn check_doc_store_quotas(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let quotas = &app_context.settings.doc_store.quotas;
+  if quotas.is_empty() {
+    return Ok(());
+  }
+  let mut stats = app_context
+    .doc_store
+    .collection_stats()
+    .await?
+    .into_iter()
+    .map(|stats| (stats.collection.clone(), stats))
+    .collect::<HashMap<_, _>>();
+  for (collection, quota) in quotas {
+    let stats = stats
+      .remove(collection)
+      .unwrap_or_else(|| DocumentCollectionStats {
+        collection: collection.clone(),
+        row_count: 0,
+        size_bytes: 0,
+      });
+    if !is_quota_exceeded(&stats, quota) {
+      if app_context.doc_store.sample_percent(collection)?.is_some() {
+        info!(
+          collection = collection.as_str(),
+          "Collection back under quota, disabling sampling"
+        );
+        app_context.doc_store.set_sample_percent(collection, None)?;
+      }
+      continue;
+    }
+    warn!(
+      collection = collection.as_str(),
+      row_count = stats.row_count,
+      size_bytes = stats.size_bytes,
+      max_rows = quota.max_rows,
+      max_bytes = quota.max_bytes,
+      "Document store collection exceeded its quota"
+    );
+    if quota.sample_percent.is_some() {
+      app_context
+        .doc_store
+        .set_sample_percent(collection, quota.sample_percent)?;
+    }
+    app_context
+      .event_publisher
+      .publish(
+        Topic::Ops,
+        EventPayloadBuilder::default()
+          .key(format!("doc_store_quota:{}", collection))
+          .event(Event::DocumentStoreQuotaExceeded {
+            collection: collection.clone(),
+            row_count: stats.row_count,
+            size_bytes: stats.size_bytes,
+            max_rows: quota.max_rows,
+            max_bytes: quota.max_bytes,
+            sample_percent: quota.sample_percent,
+          })
+          .build()?,
+      )
+      .await?;
+  }
+  Ok(())
+}
+
+pub async fn setup_doc_store_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CheckDocumentStoreQuotas)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(check_doc_store_quotas))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::CheckDocumentStoreQuotas)
+        .interval(
+          TimeDelta::try_minutes(
+            app_context.settings.doc_store.quota_check_interval_minutes as i64,
+          )
+          .unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_quota_exceeded() {
+    let stats = DocumentCollectionStats {
+      collection: "parser_failure".to_string(),
+      row_count: 150,
+      size_bytes: 2048,
+    };
+    let quota = |max_rows, max_bytes| DocumentStoreQuotaSettings {
+      max_rows,
+      max_bytes,
+      sample_percent: None,
+    };
+    assert!(is_quota_exceeded(&stats, &quota(Some(100), None)));
+    assert!(is_quota_exceeded(&stats, &quota(None, Some(1024))));
+    assert!(!is_quota_exceeded(&stats, &quota(Some(200), Some(4096))));
+    assert!(!is_quota_exceeded(&stats, &quota(None, None)));
+  }
+}
@@ -1,5 +1,6 @@
 pub mod document_filter;
 pub mod document_store;
+pub mod document_store_quota;
 
 pub use document_filter::*;
 pub use document_store::*;
@@ -181,6 +181,25 @@ impl ElasticsearchIndex {
     })
   }
 
+  /**
+   * Runs the aggregations in `body` without fetching any hits, returning the response's
+   * aggregations object
+   */
+  #[instrument(skip_all)]
+  pub async fn aggregate(&self, body: Value) -> Result<Value> {
+    let res = self
+      .client
+      .search(SearchParts::Index(&[self.index_name.as_str()]))
+      .size(0)
+      .body(body)
+      .send()
+      .await?;
+    let mut response_body = res.json::<Value>().await?;
+    let took = response_body["took"].as_i64().unwrap_or_default();
+    info!("ElasticSearch aggregated in {}ms", took);
+    Ok(response_body["aggregations"].take())
+  }
+
   #[instrument(skip_all)]
   pub async fn delete(&self, id: String) -> Result<()> {
     let res = self
@@ -32,6 +32,13 @@ pub fn embedding_to_bytes(embedding: &Vec<f32>) -> Vec<u8> {
     .collect()
 }
 
+pub fn embedding_from_bytes(bytes: &[u8]) -> Vec<f32> {
+  bytes
+    .chunks_exact(4)
+    .map(|chunk| f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+    .collect()
+}
+
 impl EmbeddingDocument {
   pub fn embedding_bytes(&self) -> Vec<u8> {
     embedding_to_bytes(&self.embedding)
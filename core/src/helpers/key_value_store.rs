@@ -322,6 +322,46 @@ impl KeyValueStore {
     self.set_many(vec![(key.to_string(), value, ttl)]).await
   }
 
+  /**
+   * Sets the key only if it's missing or expired, returning whether it was set
+   */
+  #[instrument(name = "KeyValueStore::set_if_absent", skip(self, value))]
+  pub async fn set_if_absent<T: Serialize + Send + Sync>(
+    &self,
+    key: &str,
+    value: T,
+    ttl: Option<Duration>,
+  ) -> Result<bool> {
+    let key = key.to_string();
+    let value = serde_json::to_vec(&value)?;
+    let now = Utc::now().naive_utc();
+    let expires_at = ttl.map(|ttl| now + ttl);
+    let changes = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO key_value_store (key, value, expires_at, updated_at)
+          VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)
+          ON CONFLICT(key) DO UPDATE SET
+            value = excluded.value,
+            expires_at = excluded.expires_at,
+            updated_at = excluded.updated_at
+          WHERE key_value_store.expires_at < ?4
+          ",
+          params![key, value, expires_at, now],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set key value");
+        anyhow!("Failed to set key value")
+      })??;
+    Ok(changes > 0)
+  }
+
   #[instrument(name = "KeyValueStore::delete_many", skip(self))]
   pub async fn delete_many(&self, keys: Vec<String>) -> Result<()> {
     let key_params = keys
@@ -439,6 +479,36 @@ impl KeyValueStore {
       })??;
     Ok(count)
   }
+
+  /**
+   * Total size in bytes of the values under keys matching a pattern
+   */
+  #[instrument(name = "KeyValueStore::size_matching", skip(self))]
+  pub async fn size_matching(&self, pattern: &str) -> Result<u64> {
+    let pattern = pattern.to_string();
+    let size: u64 = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        conn
+          .query_row(
+            "SELECT COALESCE(SUM(LENGTH(CAST(value AS BLOB))), 0) FROM key_value_store WHERE key LIKE ?1",
+            [pattern],
+            |row| row.get::<_, u64>(0),
+          )
+          .map_err(|e| {
+            error!(message = e.to_string(), "Failed to size key value");
+            rusqlite::Error::ExecuteReturnedResults
+          })
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to size key value");
+        anyhow!("Failed to size key value")
+      })??;
+    Ok(size)
+  }
 }
 
 async fn delete_expired_keys(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
@@ -32,3 +32,13 @@ pub fn default_if_zero<T: Num + FloatCore>(value: T, default: T) -> T {
     value
   }
 }
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+  let dot = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f32>();
+  let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+  let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+  if norm_a == 0.0 || norm_b == 0.0 {
+    return 0.0;
+  }
+  dot / (norm_a * norm_b)
+}
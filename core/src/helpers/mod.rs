@@ -1,11 +1,14 @@
 pub mod async_utils;
 pub mod batch_loader;
+pub mod csv;
 pub mod document_store;
 pub mod elasticsearch_index;
 pub mod embedding;
 pub mod item_with_factor;
 pub mod key_value_store;
 pub mod math;
+pub mod operation_lease;
 pub mod priority;
+pub mod projection;
 pub mod redisearch;
 pub mod test;
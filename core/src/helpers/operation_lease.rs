@@ -0,0 +1,99 @@
+use super::key_value_store::KeyValueStore;
+use anyhow::Result;
+use std::{sync::Arc, time::Duration};
+use tokio::{spawn, task::JoinHandle, time::sleep};
+use tracing::error;
+
+const LEASE_DURATION: Duration = Duration::from_secs(60);
+
+fn lease_key(operation: &str) -> String {
+  format!("operation_lease:{}", operation)
+}
+
+/**
+ * An exclusive claim on a long-running operation. The claim expires unless it's renewed, and it's
+ * renewed in the background for as long as the lease is held, so a crashed holder's claim lapses on
+ * its own.
+ */
+pub struct OperationLease {
+  kv: Arc<KeyValueStore>,
+  key: String,
+  renewal: JoinHandle<()>,
+}
+
+impl OperationLease {
+  /**
+   * None when another holder has the operation claimed
+   */
+  pub async fn acquire(kv: Arc<KeyValueStore>, operation: &str) -> Result<Option<Self>> {
+    let key = lease_key(operation);
+    if !kv.set_if_absent(&key, true, Some(LEASE_DURATION)).await? {
+      return Ok(None);
+    }
+    let renewal = {
+      let kv = Arc::clone(&kv);
+      let key = key.clone();
+      spawn(async move {
+        loop {
+          sleep(LEASE_DURATION / 3).await;
+          if let Err(e) = kv.set(&key, true, Some(LEASE_DURATION)).await {
+            error!(
+              key = key.as_str(),
+              error = e.to_string(),
+              "Failed to renew lease"
+            );
+          }
+        }
+      })
+    };
+    Ok(Some(Self { kv, key, renewal }))
+  }
+
+  pub async fn release(self) -> Result<()> {
+    self.renewal.abort();
+    self.kv.delete(&self.key).await
+  }
+
+  /**
+   * Leases left by a previous process are stale, must run before anything acquires one
+   */
+  pub async fn clear_all(kv: &KeyValueStore) -> Result<()> {
+    kv.delete_matching(&lease_key("%")).await
+  }
+}
+
+impl Drop for OperationLease {
+  fn drop(&mut self) {
+    self.renewal.abort();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sqlite::SqliteConnection;
+
+  #[tokio::test]
+  async fn test_acquire() -> Result<()> {
+    let kv = Arc::new(KeyValueStore::new(Arc::new(
+      SqliteConnection::new_for_test().await?,
+    )));
+    let lease = OperationLease::acquire(Arc::clone(&kv), "test").await?;
+    assert!(lease.is_some());
+    assert!(OperationLease::acquire(Arc::clone(&kv), "test")
+      .await?
+      .is_none());
+    assert!(OperationLease::acquire(Arc::clone(&kv), "other")
+      .await?
+      .is_some());
+
+    lease.unwrap().release().await?;
+    assert!(OperationLease::acquire(Arc::clone(&kv), "test")
+      .await?
+      .is_some());
+
+    OperationLease::clear_all(&kv).await?;
+    assert!(OperationLease::acquire(kv, "test").await?.is_some());
+    Ok(())
+  }
+}
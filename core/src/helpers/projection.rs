@@ -0,0 +1,187 @@
+use rayon::prelude::*;
+
+const MAX_ITERATIONS: usize = 200;
+const CONVERGENCE_TOLERANCE: f64 = 1e-9;
+/**
+ * Components explaining less than this share of the variance are rounding error
+ */
+const NEGLIGIBLE_VARIANCE_RATIO: f64 = 1e-12;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Projection {
+  /**
+   * One point per input vector, in input order
+   */
+  pub points: Vec<Vec<f32>>,
+  /**
+   * Share of the total variance each axis captures
+   */
+  pub explained_variance_ratios: Vec<f32>,
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+  a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(v: &mut [f64]) -> f64 {
+  let norm = dot(v, v).sqrt();
+  if norm > 0.0 {
+    v.iter_mut().for_each(|x| *x /= norm);
+  }
+  norm
+}
+
+/**
+ * Covariance matrix times `v`, without building the matrix
+ */
+fn covariance_product(centered: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+  let n = centered.len() as f64;
+  centered
+    .par_iter()
+    .fold(
+      || vec![0.0; v.len()],
+      |mut acc, row| {
+        let weight = dot(row, v);
+        acc.iter_mut().zip(row).for_each(|(a, x)| *a += weight * x);
+        acc
+      },
+    )
+    .reduce(
+      || vec![0.0; v.len()],
+      |mut a, b| {
+        a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+        a
+      },
+    )
+    .into_iter()
+    .map(|x| x / n)
+    .collect()
+}
+
+fn remove_components(v: &mut [f64], components: &[Vec<f64>]) {
+  for component in components {
+    let overlap = dot(v, component);
+    v.iter_mut()
+      .zip(component)
+      .for_each(|(x, c)| *x -= overlap * c);
+  }
+}
+
+/**
+ * Projects the vectors onto their top principal components, found one at a time by power
+ * iteration so only the vectors and a few components are held in memory. Each axis is signed so
+ * the first point with a non-zero coordinate on it is positive, keeping layouts stable between
+ * calls.
+ */
+pub fn pca(vectors: &[Vec<f32>], dimensions: usize) -> Projection {
+  let Some(first) = vectors.first() else {
+    return Projection::default();
+  };
+  let width = first.len();
+  let n = vectors.len() as f64;
+  let mut mean = vec![0.0; width];
+  for vector in vectors {
+    mean
+      .iter_mut()
+      .zip(vector)
+      .for_each(|(m, x)| *m += *x as f64 / n);
+  }
+  let centered = vectors
+    .par_iter()
+    .map(|vector| {
+      vector
+        .iter()
+        .zip(&mean)
+        .map(|(x, m)| *x as f64 - m)
+        .collect::<Vec<_>>()
+    })
+    .collect::<Vec<_>>();
+  let total_variance = centered.iter().map(|row| dot(row, row)).sum::<f64>() / n;
+  let negligible_variance = total_variance * NEGLIGIBLE_VARIANCE_RATIO;
+
+  let mut components: Vec<Vec<f64>> = vec![];
+  let mut variances = vec![];
+  for _ in 0..dimensions.min(width) {
+    let mut v = (0..width)
+      .map(|i| 1.0 + (i % 7) as f64 / 7.0)
+      .collect::<Vec<_>>();
+    remove_components(&mut v, &components);
+    normalize(&mut v);
+    let mut variance = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+      let mut next = covariance_product(&centered, &v);
+      remove_components(&mut next, &components);
+      variance = normalize(&mut next);
+      if variance <= negligible_variance {
+        break;
+      }
+      let converged = 1.0 - dot(&next, &v).abs() < CONVERGENCE_TOLERANCE;
+      v = next;
+      if converged {
+        break;
+      }
+    }
+    if variance <= negligible_variance {
+      break;
+    }
+    if let Some(sign) = centered
+      .iter()
+      .map(|row| dot(row, &v))
+      .find(|coordinate| coordinate.abs() > f64::EPSILON)
+      .map(f64::signum)
+    {
+      v.iter_mut().for_each(|x| *x *= sign);
+    }
+    components.push(v);
+    variances.push(variance);
+  }
+
+  Projection {
+    points: centered
+      .par_iter()
+      .map(|row| {
+        components
+          .iter()
+          .map(|component| dot(row, component) as f32)
+          .collect()
+      })
+      .collect(),
+    explained_variance_ratios: variances
+      .into_iter()
+      .map(|variance| {
+        if total_variance > 0.0 {
+          (variance / total_variance) as f32
+        } else {
+          0.0
+        }
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pca() {
+    let projection = pca(
+      &[
+        vec![0.0, 0.0, 1.0],
+        vec![1.0, 1.0, 1.0],
+        vec![2.0, 2.0, 1.0],
+        vec![3.0, 3.0, 1.0],
+      ],
+      2,
+    );
+    assert_eq!(projection.points.len(), 4);
+    assert_eq!(projection.explained_variance_ratios.len(), 1);
+    assert!((projection.explained_variance_ratios[0] - 1.0).abs() < 1e-5);
+    let step = 2.0_f32.sqrt();
+    for (i, point) in projection.points.iter().enumerate() {
+      assert!((point[0] - (i as f32 - 1.5) * -step).abs() < 1e-4);
+    }
+
+    assert_eq!(pca(&[], 2), Projection::default());
+  }
+}
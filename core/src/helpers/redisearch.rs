@@ -5,7 +5,7 @@ use rustis::{
   client::PooledClientManager,
   commands::{FtCreateOptions, FtFieldSchema, SearchCommands},
 };
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 use tracing::warn;
 use unidecode::unidecode;
 
@@ -50,6 +50,18 @@ pub fn escape_search_query_text(input: &str) -> String {
     .collect()
 }
 
+/**
+ * Like `escape_search_query_text`, but keeps non-ASCII letters for matching fields in their
+ * original script
+ */
+pub fn escape_search_query_unicode_text(input: &str) -> String {
+  input
+    .trim()
+    .chars()
+    .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+    .collect()
+}
+
 pub fn escape_tag_value(input: &str) -> String {
   input
     .chars()
@@ -157,7 +169,7 @@ pub fn get_max_num_query(tag: &str, min: Option<usize>) -> String {
   }
 }
 
-pub fn get_num_range_query(tag: &str, min: Option<u32>, max: Option<u32>) -> String {
+pub fn get_num_range_query<T: Display>(tag: &str, min: Option<T>, max: Option<T>) -> String {
   match (min, max) {
     (Some(min), Some(max)) => format!("{}:[{}, {}] ", tag, min, max),
     (Some(min), None) => format!("{}:[{}, +inf] ", tag, min),
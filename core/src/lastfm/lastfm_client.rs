@@ -0,0 +1,249 @@
+use crate::settings::LastFmSettings;
+use anyhow::{anyhow, Result};
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::time::Duration;
+use tracing::info;
+
+lazy_static! {
+  static ref RATE_LIMITER: DefaultDirectRateLimiter = RateLimiter::direct(Quota::per_second(nonzero!(4u32))); // API limit is 5/s
+}
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+const PAGE_SIZE: u32 = 200;
+const DEFAULT_PERIOD: &str = "overall";
+const DEFAULT_LIMIT: u32 = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastFmTopAlbum {
+  pub artist_name: String,
+  pub album_name: String,
+  pub playcount: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmTopAlbumsResponse {
+  topalbums: LastFmTopAlbums,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmTopAlbums {
+  album: Vec<LastFmAlbum>,
+  #[serde(rename = "@attr")]
+  attr: LastFmPageAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmAlbum {
+  name: String,
+  playcount: String,
+  artist: LastFmArtist,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmArtist {
+  name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastFmPageAttributes {
+  total_pages: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastFmScrobble {
+  pub artist_name: String,
+  pub album_name: Option<String>,
+  pub scrobbled_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmRecentTracksResponse {
+  recenttracks: LastFmRecentTracks,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmRecentTracks {
+  track: Vec<LastFmTrack>,
+  #[serde(rename = "@attr")]
+  attr: LastFmPageAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmTrack {
+  artist: LastFmText,
+  album: LastFmText,
+  /**
+   * Missing on the track that's playing now
+   */
+  date: Option<LastFmDate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmText {
+  #[serde(rename = "#text")]
+  text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmDate {
+  uts: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastFmErrorResponse {
+  error: u32,
+  message: String,
+}
+
+pub struct LastFmClient {
+  client: Client,
+  settings: LastFmSettings,
+}
+
+impl LastFmClient {
+  pub fn new(settings: LastFmSettings) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+    }
+  }
+
+  pub fn settings(&self) -> &LastFmSettings {
+    &self.settings
+  }
+
+  async fn get<T: DeserializeOwned>(&self, method: &str, params: &[(&str, String)]) -> Result<T> {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(100)))
+      .await;
+    let response = self
+      .client
+      .get(API_URL)
+      .query(&[
+        ("method", method),
+        ("format", "json"),
+        ("user", self.settings.username.as_str()),
+        ("api_key", self.settings.api_key.as_str()),
+      ])
+      .query(params)
+      .send()
+      .await?;
+    let body = response.text().await?;
+    if let Ok(error) = serde_json::from_str::<LastFmErrorResponse>(&body) {
+      return Err(anyhow!(
+        "Last.fm API error {}: {}",
+        error.error,
+        error.message
+      ));
+    }
+    Ok(serde_json::from_str::<T>(&body)?)
+  }
+
+  async fn get_top_albums_page(&self, page: u32) -> Result<LastFmTopAlbums> {
+    let response = self
+      .get::<LastFmTopAlbumsResponse>(
+        "user.gettopalbums",
+        &[
+          (
+            "period",
+            self
+              .settings
+              .period
+              .clone()
+              .unwrap_or(DEFAULT_PERIOD.to_string()),
+          ),
+          ("limit", PAGE_SIZE.to_string()),
+          ("page", page.to_string()),
+        ],
+      )
+      .await?;
+    Ok(response.topalbums)
+  }
+
+  async fn get_recent_tracks_page(
+    &self,
+    from: Option<i64>,
+    page: u32,
+  ) -> Result<LastFmRecentTracks> {
+    let mut params = vec![("limit", PAGE_SIZE.to_string()), ("page", page.to_string())];
+    if let Some(from) = from {
+      params.push(("from", from.to_string()));
+    }
+    let response = self
+      .get::<LastFmRecentTracksResponse>("user.getrecenttracks", &params)
+      .await?;
+    Ok(response.recenttracks)
+  }
+
+  /**
+   * Fetches the user's scrobbles from `from` onwards, newest first, up to `limit`. The track
+   * that's playing now isn't a scrobble yet, so it's left out.
+   */
+  pub async fn get_scrobbles_since(
+    &self,
+    from: Option<i64>,
+    limit: usize,
+  ) -> Result<Vec<LastFmScrobble>> {
+    let mut scrobbles = Vec::new();
+    let mut page = 1;
+    loop {
+      let recent_tracks = self.get_recent_tracks_page(from, page).await?;
+      let total_pages = recent_tracks.attr.total_pages.parse::<u32>().unwrap_or(0);
+      let is_empty = recent_tracks.track.is_empty();
+      scrobbles.extend(recent_tracks.track.into_iter().filter_map(|track| {
+        let scrobbled_at = track.date?.uts.parse::<i64>().ok()?;
+        Some(LastFmScrobble {
+          artist_name: track.artist.text,
+          album_name: Some(track.album.text).filter(|name| !name.is_empty()),
+          scrobbled_at,
+        })
+      }));
+      if is_empty || scrobbles.len() >= limit || page >= total_pages {
+        break;
+      }
+      page += 1;
+    }
+    scrobbles.truncate(limit);
+    info!(
+      username = self.settings.username.as_str(),
+      count = scrobbles.len(),
+      "Fetched Last.fm scrobbles"
+    );
+    Ok(scrobbles)
+  }
+
+  /**
+   * Fetches the user's most played albums for the configured period, up to the configured limit
+   */
+  pub async fn get_top_albums(&self) -> Result<Vec<LastFmTopAlbum>> {
+    let limit = self.settings.limit.unwrap_or(DEFAULT_LIMIT) as usize;
+    let mut albums = Vec::new();
+    let mut page = 1;
+    loop {
+      let top_albums = self.get_top_albums_page(page).await?;
+      let total_pages = top_albums.attr.total_pages.parse::<u32>().unwrap_or(0);
+      let is_empty = top_albums.album.is_empty();
+      albums.extend(top_albums.album.into_iter().map(|album| LastFmTopAlbum {
+        artist_name: album.artist.name,
+        album_name: album.name,
+        playcount: album.playcount.parse::<u32>().unwrap_or(0),
+      }));
+      if is_empty || albums.len() >= limit || page >= total_pages {
+        break;
+      }
+      page += 1;
+    }
+    albums.truncate(limit);
+    info!(
+      username = self.settings.username.as_str(),
+      count = albums.len(),
+      "Fetched Last.fm top albums"
+    );
+    Ok(albums)
+  }
+}
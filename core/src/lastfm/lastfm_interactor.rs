@@ -0,0 +1,226 @@
+use super::lastfm_client::{LastFmClient, LastFmScrobble};
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  profile::{
+    lastfm_import_lookup_subscription::LastFmAlbumScrobbles, profile::ProfileId,
+    profile_interactor::ProfileInteractor,
+  },
+};
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{cmp::Reverse, collections::HashMap, sync::Arc};
+use tracing::{info, instrument};
+
+const DEFAULT_MAX_INITIAL_SCROBBLES: u32 = 10000;
+const MAX_TRACKED_ALBUMS: usize = 5000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LastFmSyncState {
+  last_scrobbled_at: Option<i64>,
+  /**
+   * Cumulative scrobbles keyed by encoded album search lookup query
+   */
+  album_scrobbles: HashMap<String, LastFmAlbumScrobbles>,
+  album_last_scrobbled_at: HashMap<String, i64>,
+}
+
+impl LastFmSyncState {
+  /**
+   * Counts the scrobbles towards their albums and returns the keys of the albums that gained
+   * scrobbles. Scrobbles without an album are skipped.
+   */
+  fn add_scrobbles(&mut self, scrobbles: &[LastFmScrobble]) -> Vec<String> {
+    let mut updated_keys = Vec::new();
+    for scrobble in scrobbles {
+      let Some(album_name) = &scrobble.album_name else {
+        continue;
+      };
+      let album = LastFmAlbumScrobbles {
+        artist_name: scrobble.artist_name.clone(),
+        album_name: album_name.clone(),
+        scrobbles: 0,
+      };
+      let key = album.album_search_lookup_query().to_encoded_string();
+      self
+        .album_scrobbles
+        .entry(key.clone())
+        .or_insert(album)
+        .scrobbles += 1;
+      let last_scrobbled_at = self.album_last_scrobbled_at.entry(key.clone()).or_default();
+      *last_scrobbled_at = (*last_scrobbled_at).max(scrobble.scrobbled_at);
+      updated_keys.push(key);
+    }
+    if let Some(scrobbled_at) = scrobbles.iter().map(|s| s.scrobbled_at).max() {
+      self.last_scrobbled_at = Some(
+        self
+          .last_scrobbled_at
+          .map_or(scrobbled_at, |last| last.max(scrobbled_at)),
+      );
+    }
+    updated_keys.sort();
+    updated_keys.dedup();
+    updated_keys
+  }
+
+  /**
+   * Drops the least recently scrobbled albums past `max_albums`. A dropped album's count starts
+   * over if it's scrobbled again.
+   */
+  fn prune_album_scrobbles(&mut self, max_albums: usize) {
+    if self.album_scrobbles.len() <= max_albums {
+      return;
+    }
+    let mut keys = self.album_scrobbles.keys().cloned().collect::<Vec<_>>();
+    keys.sort_by_key(|key| {
+      Reverse(
+        self
+          .album_last_scrobbled_at
+          .get(key)
+          .copied()
+          .unwrap_or_default(),
+      )
+    });
+    for key in keys.into_iter().skip(max_albums) {
+      self.album_scrobbles.remove(&key);
+      self.album_last_scrobbled_at.remove(&key);
+    }
+  }
+}
+
+pub struct LastFmInteractor {
+  client: Arc<LastFmClient>,
+  kv: Arc<KeyValueStore>,
+  profile_interactor: Arc<ProfileInteractor>,
+}
+
+impl LastFmInteractor {
+  pub fn new(
+    client: Arc<LastFmClient>,
+    kv: Arc<KeyValueStore>,
+    profile_interactor: Arc<ProfileInteractor>,
+  ) -> Self {
+    Self {
+      client,
+      kv,
+      profile_interactor,
+    }
+  }
+
+  fn sync_state_key(&self) -> String {
+    format!(
+      "lastfm_sync_state:{}:{}",
+      self.client.settings().username,
+      self.client.settings().profile_id
+    )
+  }
+
+  async fn get_sync_state(&self) -> Result<LastFmSyncState> {
+    Ok(
+      self
+        .kv
+        .get::<LastFmSyncState>(&self.sync_state_key())
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  /**
+   * Fetches scrobbles made since the last sync and re-imports every album that gained scrobbles.
+   * Timestamps only have second precision, so scrobbles submitted late for a second that was
+   * already synced are missed.
+   */
+  #[instrument(skip(self))]
+  pub async fn sync_scrobbles(&self) -> Result<()> {
+    let settings = self.client.settings();
+    let profile_id = ProfileId::try_from(settings.profile_id.clone())?;
+    if self
+      .profile_interactor
+      .find_profile(&profile_id)
+      .await?
+      .is_none()
+    {
+      self
+        .profile_interactor
+        .create_profile(
+          profile_id.clone(),
+          format!("Last.fm: {}", settings.username),
+        )
+        .await?;
+    }
+
+    let mut state = self.get_sync_state().await?;
+    let limit = match state.last_scrobbled_at {
+      Some(_) => usize::MAX,
+      None => settings
+        .max_initial_scrobbles
+        .unwrap_or(DEFAULT_MAX_INITIAL_SCROBBLES) as usize,
+    };
+    let scrobbles = self
+      .client
+      .get_scrobbles_since(state.last_scrobbled_at.map(|last| last + 1), limit)
+      .await?;
+    if scrobbles.is_empty() {
+      info!("No new Last.fm scrobbles");
+      return Ok(());
+    }
+
+    let updated_keys = state.add_scrobbles(&scrobbles);
+    info!(
+      scrobbles = scrobbles.len(),
+      albums = updated_keys.len(),
+      "Syncing Last.fm scrobbles"
+    );
+    self
+      .profile_interactor
+      .import_lastfm_album_scrobbles(
+        &profile_id,
+        updated_keys
+          .iter()
+          .filter_map(|key| state.album_scrobbles.get(key).cloned())
+          .collect(),
+      )
+      .await?;
+
+    state.prune_album_scrobbles(MAX_TRACKED_ALBUMS);
+    self.kv.set(&self.sync_state_key(), state, None).await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn scrobble(album_name: Option<&str>, scrobbled_at: i64) -> LastFmScrobble {
+    LastFmScrobble {
+      artist_name: "Slowdive".to_string(),
+      album_name: album_name.map(|name| name.to_string()),
+      scrobbled_at,
+    }
+  }
+
+  #[test]
+  fn test_add_scrobbles() {
+    let mut state = LastFmSyncState::default();
+    let updated_keys = state.add_scrobbles(&[
+      scrobble(Some("Souvlaki"), 30),
+      scrobble(None, 40),
+      scrobble(Some("Souvlaki"), 20),
+      scrobble(Some("Pygmalion"), 10),
+    ]);
+    assert_eq!(updated_keys.len(), 2);
+    assert_eq!(state.last_scrobbled_at, Some(40));
+    let souvlaki = state
+      .album_scrobbles
+      .values()
+      .find(|album| album.album_name == "Souvlaki")
+      .unwrap();
+    assert_eq!(souvlaki.scrobbles, 2);
+
+    state.add_scrobbles(&[scrobble(Some("Souvlaki"), 50)]);
+    state.prune_album_scrobbles(1);
+    assert_eq!(state.last_scrobbled_at, Some(50));
+    assert_eq!(state.album_scrobbles.len(), 1);
+    assert_eq!(state.album_scrobbles.values().next().unwrap().scrobbles, 3);
+  }
+}
@@ -0,0 +1,81 @@
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+  settings::LastFmImportSource,
+};
+use anyhow::{anyhow, Result};
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn import_lastfm_top_albums(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .profile_interactor
+    .import_lastfm_top_albums()
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to import Last.fm top albums"))
+}
+
+async fn sync_lastfm_scrobbles(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .lastfm_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("Last.fm is not configured"))?
+    .sync_scrobbles()
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to sync Last.fm scrobbles"))
+}
+
+pub async fn setup_lastfm_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let Some(settings) = app_context.settings.lastfm.clone() else {
+    info!("Last.fm is not configured, skipping Last.fm import job");
+    return Ok(());
+  };
+
+  let (job_name, executor, replaced_job_name): (_, JobExecutorFn, _) =
+    match settings.source.unwrap_or_default() {
+      LastFmImportSource::TopAlbums => (
+        JobName::ImportLastFmTopAlbums,
+        job_executor!(import_lastfm_top_albums),
+        JobName::SyncLastFmScrobbles,
+      ),
+      LastFmImportSource::Scrobbles => (
+        JobName::SyncLastFmScrobbles,
+        job_executor!(sync_lastfm_scrobbles),
+        JobName::ImportLastFmTopAlbums,
+      ),
+    };
+  // Drops the other source's job, left over if the source was switched
+  app_context
+    .scheduler
+    .delete_jobs_by_name(replaced_job_name)
+    .await?;
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(job_name.clone())
+        .app_context(Arc::clone(&app_context))
+        .executor(executor)
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(job_name)
+        .interval(TimeDelta::try_hours(settings.sync_interval_hours.unwrap_or(24) as i64).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
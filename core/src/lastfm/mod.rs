@@ -0,0 +1,3 @@
+pub mod lastfm_client;
+pub mod lastfm_interactor;
+pub mod lastfm_jobs;
@@ -1,21 +1,42 @@
 pub mod albums;
+pub mod apple_music;
 pub mod artists;
+pub mod auth;
+pub mod backups;
+pub mod collections;
 pub mod context;
+pub mod cover_images;
 pub mod crawler;
+pub mod discogs;
 pub mod embedding_provider;
 pub mod events;
 pub mod files;
+pub mod genres;
+pub mod graphql;
+pub mod health;
 pub mod helpers;
+pub mod lastfm;
+pub mod listenbrainz;
+pub mod listening;
 pub mod lookup;
+pub mod music_service;
 pub mod ops;
 pub mod parser;
 pub mod profile;
 pub mod proto;
+pub mod rate_limit;
 pub mod recommendations;
 pub mod redis;
+pub mod redis_migrations;
+pub mod rest_gateway;
 pub mod rpc;
+pub mod runtime_settings;
 pub mod scheduler;
+pub mod schema_manifest;
 pub mod settings;
 pub mod spotify;
 pub mod sqlite;
+pub mod tenant;
+pub mod tidal;
 pub mod tracing;
+pub mod youtube_music;
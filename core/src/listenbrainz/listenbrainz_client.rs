@@ -0,0 +1,226 @@
+use crate::settings::ListenBrainzSettings;
+use anyhow::{anyhow, Result};
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::{collections::HashSet, time::Duration};
+
+lazy_static! {
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(2u32)));
+}
+
+const API_URL: &str = "https://api.listenbrainz.org/1";
+const PAGE_SIZE: u32 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenBrainzListen {
+  /**
+   * Only unique among listens with the same timestamp
+   */
+  pub id: String,
+  pub listened_at: i64,
+  pub artist_name: String,
+  pub release_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListensResponse {
+  payload: ListensPayload,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListensPayload {
+  listens: Vec<Listen>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Listen {
+  listened_at: i64,
+  recording_msid: Option<String>,
+  track_metadata: TrackMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackMetadata {
+  artist_name: String,
+  track_name: Option<String>,
+  release_name: Option<String>,
+}
+
+/**
+ * Position of the newest synced listens. Timestamps only have second precision, so the listens
+ * seen at that second are kept to tell them apart from ones submitted later for the same second.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListenBrainzCursor {
+  pub listened_at: i64,
+  pub listen_ids: HashSet<String>,
+}
+
+impl ListenBrainzCursor {
+  pub fn is_before(&self, listen: &ListenBrainzListen) -> bool {
+    listen.listened_at > self.listened_at
+      || (listen.listened_at == self.listened_at && !self.listen_ids.contains(&listen.id))
+  }
+
+  /**
+   * Moves the cursor past the given listens
+   */
+  pub fn advance(&mut self, listens: &[ListenBrainzListen]) {
+    for listen in listens {
+      if listen.listened_at > self.listened_at {
+        self.listened_at = listen.listened_at;
+        self.listen_ids.clear();
+      }
+      if listen.listened_at == self.listened_at {
+        self.listen_ids.insert(listen.id.clone());
+      }
+    }
+  }
+}
+
+pub struct ListenBrainzClient {
+  client: Client,
+  settings: ListenBrainzSettings,
+}
+
+impl ListenBrainzClient {
+  pub fn new(settings: ListenBrainzSettings) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+    }
+  }
+
+  pub fn settings(&self) -> &ListenBrainzSettings {
+    &self.settings
+  }
+
+  /**
+   * Returns up to a page of listens strictly older than `max_ts`, newest first
+   */
+  async fn get_listens_page(&self, max_ts: Option<i64>) -> Result<Vec<ListenBrainzListen>> {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(100)))
+      .await;
+    let mut query = vec![("count", PAGE_SIZE.to_string())];
+    if let Some(max_ts) = max_ts {
+      query.push(("max_ts", max_ts.to_string()));
+    }
+    let response = self
+      .client
+      .get(format!(
+        "{}/user/{}/listens",
+        API_URL, self.settings.username
+      ))
+      .header(
+        "Authorization",
+        format!("Token {}", self.settings.user_token),
+      )
+      .query(&query)
+      .send()
+      .await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+      return Err(anyhow!("ListenBrainz API rate limit exceeded"));
+    }
+    let response = response
+      .error_for_status()?
+      .json::<ListensResponse>()
+      .await?;
+    Ok(
+      response
+        .payload
+        .listens
+        .into_iter()
+        .map(|listen| ListenBrainzListen {
+          id: listen.recording_msid.unwrap_or_else(|| {
+            format!(
+              "{}:{}:{}",
+              listen.track_metadata.artist_name,
+              listen.track_metadata.release_name.as_deref().unwrap_or(""),
+              listen.track_metadata.track_name.as_deref().unwrap_or("")
+            )
+          }),
+          listened_at: listen.listened_at,
+          artist_name: listen.track_metadata.artist_name,
+          release_name: listen.track_metadata.release_name,
+        })
+        .collect(),
+    )
+  }
+
+  /**
+   * Pages backwards from the latest listen until passing the cursor or reaching `limit` listens,
+   * whichever comes first. Each page after the first overlaps the previous one by a second so
+   * listens sharing a timestamp across the page boundary aren't skipped.
+   */
+  pub async fn get_listens_since(
+    &self,
+    cursor: Option<&ListenBrainzCursor>,
+    limit: usize,
+  ) -> Result<Vec<ListenBrainzListen>> {
+    let mut listens = Vec::new();
+    let mut seen = HashSet::new();
+    let mut max_ts = None;
+    loop {
+      let page = self.get_listens_page(max_ts).await?;
+      let Some(oldest) = page.iter().map(|listen| listen.listened_at).min() else {
+        break;
+      };
+      let mut found_new = false;
+      for listen in page {
+        if seen.insert((listen.listened_at, listen.id.clone())) {
+          found_new = true;
+          if cursor.map_or(true, |cursor| cursor.is_before(&listen)) {
+            listens.push(listen);
+          }
+        }
+      }
+      let passed_cursor = cursor.is_some_and(|cursor| oldest < cursor.listened_at);
+      if passed_cursor || listens.len() >= limit {
+        break;
+      }
+      // A page taken up by one second's listens has nothing new once overlapped, so step past it
+      max_ts = Some(if found_new { oldest + 1 } else { oldest });
+    }
+    listens.truncate(limit);
+    Ok(listens)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn listen(id: &str, listened_at: i64) -> ListenBrainzListen {
+    ListenBrainzListen {
+      id: id.to_string(),
+      listened_at,
+      artist_name: "Slowdive".to_string(),
+      release_name: Some("Souvlaki".to_string()),
+    }
+  }
+
+  #[test]
+  fn test_cursor() {
+    let mut cursor = ListenBrainzCursor::default();
+    cursor.advance(&[listen("a", 10), listen("b", 20), listen("c", 20)]);
+    assert_eq!(cursor.listened_at, 20);
+    assert_eq!(
+      cursor.listen_ids,
+      HashSet::from(["b".to_string(), "c".to_string()])
+    );
+    assert!(!cursor.is_before(&listen("a", 10)));
+    assert!(!cursor.is_before(&listen("b", 20)));
+    assert!(cursor.is_before(&listen("d", 20)));
+    assert!(cursor.is_before(&listen("e", 21)));
+
+    cursor.advance(&[listen("d", 20)]);
+    assert_eq!(cursor.listen_ids.len(), 3);
+    cursor.advance(&[listen("e", 21)]);
+    assert_eq!(cursor.listen_ids, HashSet::from(["e".to_string()]));
+  }
+}
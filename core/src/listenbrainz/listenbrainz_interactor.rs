@@ -0,0 +1,241 @@
+use super::listenbrainz_client::{ListenBrainzClient, ListenBrainzCursor, ListenBrainzListen};
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  profile::{
+    listenbrainz_import_lookup_subscription::ListenBrainzAlbumListens, profile::ProfileId,
+    profile_interactor::ProfileInteractor,
+  },
+  settings::ListenBrainzSettings,
+};
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+  cmp::Reverse,
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+use tracing::{info, instrument};
+
+const DEFAULT_MAX_INITIAL_LISTENS: u32 = 10000;
+const DEFAULT_MAX_TRACKED_ALBUMS: u32 = 5000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ListenBrainzSyncState {
+  last_listened_at: Option<i64>,
+  /**
+   * Listens already synced at `last_listened_at`
+   */
+  #[serde(default)]
+  last_listen_ids: HashSet<String>,
+  /**
+   * Cumulative listens keyed by encoded album search lookup query
+   */
+  album_listens: HashMap<String, ListenBrainzAlbumListens>,
+  #[serde(default)]
+  album_last_listened_at: HashMap<String, i64>,
+}
+
+impl ListenBrainzSyncState {
+  fn cursor(&self) -> Option<ListenBrainzCursor> {
+    self.last_listened_at.map(|listened_at| ListenBrainzCursor {
+      listened_at,
+      listen_ids: self.last_listen_ids.clone(),
+    })
+  }
+
+  fn set_cursor(&mut self, cursor: ListenBrainzCursor) {
+    self.last_listened_at = Some(cursor.listened_at);
+    self.last_listen_ids = cursor.listen_ids;
+  }
+
+  /**
+   * Drops the least recently played albums past `max_albums`. A dropped album's count starts over
+   * if it's played again.
+   */
+  fn prune_album_listens(&mut self, max_albums: usize) {
+    if self.album_listens.len() <= max_albums {
+      return;
+    }
+    let mut keys = self.album_listens.keys().cloned().collect::<Vec<_>>();
+    keys.sort_by_key(|key| {
+      Reverse(
+        self
+          .album_last_listened_at
+          .get(key)
+          .copied()
+          .unwrap_or_default(),
+      )
+    });
+    for key in keys.into_iter().skip(max_albums) {
+      self.album_listens.remove(&key);
+      self.album_last_listened_at.remove(&key);
+    }
+  }
+}
+
+pub struct ListenBrainzInteractor {
+  client: ListenBrainzClient,
+  kv: Arc<KeyValueStore>,
+  profile_interactor: Arc<ProfileInteractor>,
+}
+
+impl ListenBrainzInteractor {
+  pub fn new(
+    settings: ListenBrainzSettings,
+    kv: Arc<KeyValueStore>,
+    profile_interactor: Arc<ProfileInteractor>,
+  ) -> Self {
+    Self {
+      client: ListenBrainzClient::new(settings),
+      kv,
+      profile_interactor,
+    }
+  }
+
+  fn sync_state_key(&self) -> String {
+    format!(
+      "listenbrainz_sync_state:{}:{}",
+      self.client.settings().username,
+      self.client.settings().profile_id
+    )
+  }
+
+  async fn get_sync_state(&self) -> Result<ListenBrainzSyncState> {
+    Ok(
+      self
+        .kv
+        .get::<ListenBrainzSyncState>(&self.sync_state_key())
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  pub async fn reset_sync_state(&self) -> Result<()> {
+    self.kv.delete(&self.sync_state_key()).await
+  }
+
+  /**
+   * Fetches listens made since the last sync and re-imports every album that gained listens
+   */
+  #[instrument(skip(self))]
+  pub async fn sync(&self) -> Result<()> {
+    let settings = self.client.settings();
+    let profile_id = ProfileId::try_from(settings.profile_id.clone())?;
+    if self
+      .profile_interactor
+      .find_profile(&profile_id)
+      .await?
+      .is_none()
+    {
+      self
+        .profile_interactor
+        .create_profile(
+          profile_id.clone(),
+          format!("ListenBrainz: {}", settings.username),
+        )
+        .await?;
+    }
+
+    let mut state = self.get_sync_state().await?;
+    let cursor = state.cursor();
+    let limit = match cursor {
+      Some(_) => usize::MAX,
+      None => settings
+        .max_initial_listens
+        .unwrap_or(DEFAULT_MAX_INITIAL_LISTENS) as usize,
+    };
+    let listens = self
+      .client
+      .get_listens_since(cursor.as_ref(), limit)
+      .await?;
+    if listens.is_empty() {
+      info!("No new ListenBrainz listens");
+      return Ok(());
+    }
+
+    let mut updated_keys = Vec::new();
+    for listen in &listens {
+      let ListenBrainzListen {
+        artist_name,
+        release_name: Some(release_name),
+        listened_at,
+        ..
+      } = listen
+      else {
+        continue;
+      };
+      let album = ListenBrainzAlbumListens {
+        artist_name: artist_name.clone(),
+        album_name: release_name.clone(),
+        listens: 0,
+      };
+      let key = album.album_search_lookup_query().to_encoded_string();
+      state
+        .album_listens
+        .entry(key.clone())
+        .or_insert(album)
+        .listens += 1;
+      let last_listened_at = state.album_last_listened_at.entry(key.clone()).or_default();
+      *last_listened_at = (*last_listened_at).max(*listened_at);
+      updated_keys.push(key);
+    }
+    updated_keys.sort();
+    updated_keys.dedup();
+    info!(
+      listens = listens.len(),
+      albums = updated_keys.len(),
+      "Syncing ListenBrainz listens"
+    );
+
+    self
+      .profile_interactor
+      .import_listenbrainz_album_listens(
+        &profile_id,
+        updated_keys
+          .iter()
+          .filter_map(|key| state.album_listens.get(key).cloned())
+          .collect(),
+      )
+      .await?;
+
+    let mut cursor = cursor.unwrap_or_default();
+    cursor.advance(&listens);
+    state.set_cursor(cursor);
+    state.prune_album_listens(
+      settings
+        .max_tracked_albums
+        .unwrap_or(DEFAULT_MAX_TRACKED_ALBUMS) as usize,
+    );
+    self.kv.set(&self.sync_state_key(), state, None).await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_prune_album_listens() {
+    let mut state = ListenBrainzSyncState::default();
+    for (album_name, listened_at) in [("Souvlaki", 30), ("Pygmalion", 10), ("Just for a Day", 20)] {
+      let album = ListenBrainzAlbumListens {
+        artist_name: "Slowdive".to_string(),
+        album_name: album_name.to_string(),
+        listens: 1,
+      };
+      let key = album.album_search_lookup_query().to_encoded_string();
+      state.album_listens.insert(key.clone(), album);
+      state.album_last_listened_at.insert(key, listened_at);
+    }
+    state.prune_album_listens(2);
+    let mut album_names = state
+      .album_listens
+      .values()
+      .map(|album| album.album_name.as_str())
+      .collect::<Vec<_>>();
+    album_names.sort();
+    assert_eq!(album_names, vec!["Just for a Day", "Souvlaki"]);
+    assert_eq!(state.album_last_listened_at.len(), 2);
+  }
+}
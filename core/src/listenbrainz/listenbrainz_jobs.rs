@@ -0,0 +1,55 @@
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::{anyhow, Result};
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn sync_listenbrainz_listens(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .listenbrainz_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("ListenBrainz is not configured"))?
+    .sync()
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to sync ListenBrainz listens"))
+}
+
+pub async fn setup_listenbrainz_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let Some(settings) = app_context.settings.listenbrainz.clone() else {
+    info!("ListenBrainz is not configured, skipping listen sync job");
+    return Ok(());
+  };
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::SyncListenBrainzListens)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(sync_listenbrainz_listens))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::SyncListenBrainzListens)
+        .interval(
+          TimeDelta::try_minutes(settings.sync_interval_minutes.unwrap_or(60) as i64).unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
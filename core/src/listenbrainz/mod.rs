@@ -0,0 +1,3 @@
+pub mod listenbrainz_client;
+pub mod listenbrainz_interactor;
+pub mod listenbrainz_jobs;
@@ -0,0 +1,56 @@
+use crate::{files::file_metadata::file_name::FileName, profile::profile::ProfileId};
+use chrono::NaiveDateTime;
+
+/**
+ * A listen's weight halves every this many days, so recent listening outweighs old favourites
+ */
+pub const LISTEN_RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListeningEvent {
+  pub profile_id: ProfileId,
+  pub album_file_name: FileName,
+  pub listened_at: NaiveDateTime,
+  /**
+   * Where the listen was logged from, e.g. a scrobbler's name or "manual"
+   */
+  pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumListenCount {
+  pub album_file_name: FileName,
+  pub count: u32,
+  pub last_listened_at: NaiveDateTime,
+}
+
+impl AlbumListenCount {
+  /**
+   * Listen frequency discounted by how long ago the album was last listened to
+   */
+  pub fn score(&self, now: NaiveDateTime) -> f64 {
+    let days_since = (now - self.last_listened_at).num_seconds().max(0) as f64 / 86400.0;
+    self.count as f64 * 0.5f64.powf(days_since / LISTEN_RECENCY_HALF_LIFE_DAYS)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+  use chrono::TimeDelta;
+
+  #[test]
+  fn test_score_decays_with_recency() -> Result<()> {
+    let now = NaiveDateTime::parse_from_str("2024-06-01T00:00:00", "%Y-%m-%dT%H:%M:%S")?;
+    let count = |count: u32, days_ago: i64| AlbumListenCount {
+      album_file_name: FileName::try_from("release/album/bjork/vulnicura").unwrap(),
+      count,
+      last_listened_at: now - TimeDelta::try_days(days_ago).unwrap(),
+    };
+    assert_eq!(count(4, 0).score(now), 4.0);
+    assert_eq!(count(4, 30).score(now), 2.0);
+    assert!(count(2, 0).score(now) > count(8, 90).score(now));
+    Ok(())
+  }
+}
@@ -0,0 +1,117 @@
+use super::{
+  listening_event::{AlbumListenCount, ListeningEvent},
+  listening_event_repository::ListeningEventRepository,
+};
+use crate::{
+  albums::album_interactor::AlbumInteractor,
+  artists::artist_alias_repository::ArtistAliasRepository, context::ApplicationContext,
+  files::file_metadata::file_name::FileName, profile::profile::ProfileId,
+};
+use anyhow::{bail, Result};
+use chrono::{NaiveDateTime, Utc};
+use std::{collections::HashMap, sync::Arc};
+
+pub const DEFAULT_LISTENING_HISTORY_LIMIT: u32 = 100;
+
+pub struct ListeningEventInteractor {
+  listening_event_repository: ListeningEventRepository,
+  artist_alias_repository: ArtistAliasRepository,
+  album_interactor: Arc<AlbumInteractor>,
+}
+
+impl ListeningEventInteractor {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      listening_event_repository: ListeningEventRepository::new(Arc::clone(
+        &app_context.sqlite_connection,
+      )),
+      artist_alias_repository: ArtistAliasRepository::new(Arc::clone(
+        &app_context.sqlite_connection,
+      )),
+      album_interactor: Arc::clone(&app_context.album_interactor),
+    }
+  }
+
+  /**
+   * Returns the number of listens that weren't already recorded
+   */
+  pub async fn record(&self, events: Vec<ListeningEvent>) -> Result<u32> {
+    let now = Utc::now().naive_utc();
+    if let Some(event) = events.iter().find(|event| event.listened_at > now) {
+      bail!(
+        "Listen of {} is in the future: {}",
+        event.album_file_name.to_string(),
+        event.listened_at
+      );
+    }
+    self.listening_event_repository.put_many(events).await
+  }
+
+  pub async fn find_history(
+    &self,
+    profile_id: &ProfileId,
+    before: Option<NaiveDateTime>,
+    limit: Option<u32>,
+  ) -> Result<Vec<ListeningEvent>> {
+    self
+      .listening_event_repository
+      .find_by_profile_id(
+        profile_id,
+        before,
+        limit.unwrap_or(DEFAULT_LISTENING_HISTORY_LIMIT),
+      )
+      .await
+  }
+
+  pub async fn find_listen_counts(
+    &self,
+    profile_id: &ProfileId,
+    since: Option<NaiveDateTime>,
+    limit: Option<u32>,
+  ) -> Result<Vec<AlbumListenCount>> {
+    self
+      .listening_event_repository
+      .find_listen_counts(profile_id, since, limit)
+      .await
+  }
+
+  /**
+   * Recency weighted listen scores of the profile's albums summed per artist. Listens of albums
+   * that haven't been crawled can't be attributed to an artist and are left out.
+   * Aliases of an artist share the artist's total.
+   */
+  pub async fn find_artist_listen_scores(
+    &self,
+    profile_id: &ProfileId,
+  ) -> Result<HashMap<FileName, f64>> {
+    let counts = self
+      .listening_event_repository
+      .find_listen_counts(profile_id, None, None)
+      .await?;
+    if counts.is_empty() {
+      return Ok(HashMap::new());
+    }
+    let albums = self
+      .album_interactor
+      .find_many(
+        counts
+          .iter()
+          .map(|count| count.album_file_name.clone())
+          .collect(),
+      )
+      .await?;
+    let now = Utc::now().naive_utc();
+    let mut scores = HashMap::new();
+    for count in counts {
+      let Some(album) = albums.get(&count.album_file_name) else {
+        continue;
+      };
+      let score = count.score(now);
+      for artist in &album.artists {
+        *scores.entry(artist.file_name.clone()).or_insert(0.0) += score;
+      }
+    }
+    let aliases = self.artist_alias_repository.get_aliases().await?;
+    Ok(aliases.merge_scores(scores))
+  }
+}
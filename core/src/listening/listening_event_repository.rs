@@ -0,0 +1,174 @@
+use super::listening_event::{AlbumListenCount, ListeningEvent};
+use crate::{
+  files::file_metadata::file_name::FileName, profile::profile::ProfileId, sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+pub struct ListeningEventRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+fn parse_file_name(index: usize, value: String) -> Result<FileName, rusqlite::Error> {
+  FileName::try_from(value).map_err(|e| {
+    rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into())
+  })
+}
+
+impl ListeningEventRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Listens already recorded for the same album at the same time are skipped, so scrobblers can
+   * resend overlapping batches. Returns the number of new listens.
+   */
+  #[instrument(skip_all, fields(count = events.len()))]
+  pub async fn put_many(&self, events: Vec<ListeningEvent>) -> Result<u32> {
+    let inserted = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        for event in events {
+          inserted += tx.execute(
+            "
+            INSERT OR IGNORE INTO listening_events (profile_id, album_file_name, listened_at, source)
+            VALUES (?, ?, ?, ?)
+            ",
+            params![
+              event.profile_id.to_string(),
+              event.album_file_name.to_string(),
+              event.listened_at,
+              event.source
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok::<_, rusqlite::Error>(inserted as u32)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to record listening events");
+        anyhow!("Failed to record listening events")
+      })??;
+    Ok(inserted)
+  }
+
+  /**
+   * Most recent listens first
+   */
+  #[instrument(skip(self))]
+  pub async fn find_by_profile_id(
+    &self,
+    profile_id: &ProfileId,
+    before: Option<NaiveDateTime>,
+    limit: u32,
+  ) -> Result<Vec<ListeningEvent>> {
+    let profile_id = profile_id.clone();
+    let events = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT album_file_name, listened_at, source
+          FROM listening_events
+          WHERE profile_id = ? AND (?2 IS NULL OR listened_at < ?2)
+          ORDER BY listened_at DESC, id DESC
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![profile_id.to_string(), before, limit], |row| {
+            Ok(ListeningEvent {
+              profile_id: profile_id.clone(),
+              album_file_name: parse_file_name(0, row.get(0)?)?,
+              listened_at: row.get(1)?,
+              source: row.get(2)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find listening history");
+        anyhow!("Failed to find listening history")
+      })??;
+    Ok(events)
+  }
+
+  /**
+   * Listens per album since `since`, most listened first
+   */
+  #[instrument(skip(self))]
+  pub async fn find_listen_counts(
+    &self,
+    profile_id: &ProfileId,
+    since: Option<NaiveDateTime>,
+    limit: Option<u32>,
+  ) -> Result<Vec<AlbumListenCount>> {
+    let profile_id = profile_id.to_string();
+    let counts = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT album_file_name, COUNT(*) as count, MAX(listened_at)
+          FROM listening_events
+          WHERE profile_id = ? AND (?2 IS NULL OR listened_at >= ?2)
+          GROUP BY album_file_name
+          ORDER BY count DESC
+          LIMIT COALESCE(?, -1)
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![profile_id, since, limit], |row| {
+            Ok(AlbumListenCount {
+              album_file_name: parse_file_name(0, row.get(0)?)?,
+              count: row.get(1)?,
+              last_listened_at: row.get(2)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find listen counts");
+        anyhow!("Failed to find listen counts")
+      })??;
+    Ok(counts)
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_by_profile_id(&self, profile_id: &ProfileId) -> Result<()> {
+    let profile_id = profile_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM listening_events WHERE profile_id = ?",
+          params![profile_id],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete listening events");
+        anyhow!("Failed to delete listening events")
+      })??;
+    Ok(())
+  }
+}
@@ -0,0 +1,150 @@
+use super::{
+  listening_event::{AlbumListenCount, ListeningEvent},
+  listening_event_interactor::ListeningEventInteractor,
+};
+use crate::{
+  context::ApplicationContext, files::file_metadata::file_name::FileName,
+  profile::profile::ProfileId, proto, tenant::tenant_id::request_tenant_id,
+};
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::error;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn parse_timestamp(value: &str, field: &str) -> Result<NaiveDateTime, Status> {
+  NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT)
+    .map_err(|err| Status::invalid_argument(format!("invalid {}: {}", field, err)))
+}
+
+impl From<ListeningEvent> for proto::ListeningEvent {
+  fn from(val: ListeningEvent) -> Self {
+    proto::ListeningEvent {
+      album_file_name: val.album_file_name.to_string(),
+      listened_at: val.listened_at.format(TIMESTAMP_FORMAT).to_string(),
+      source: val.source,
+    }
+  }
+}
+
+impl From<AlbumListenCount> for proto::AlbumListenCount {
+  fn from(val: AlbumListenCount) -> Self {
+    proto::AlbumListenCount {
+      album_file_name: val.album_file_name.to_string(),
+      count: val.count,
+      last_listened_at: val.last_listened_at.format(TIMESTAMP_FORMAT).to_string(),
+    }
+  }
+}
+
+pub struct ListeningEventService {
+  listening_event_interactor: ListeningEventInteractor,
+  app_context: Arc<ApplicationContext>,
+}
+
+impl ListeningEventService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      listening_event_interactor: ListeningEventInteractor::new(Arc::clone(&app_context)),
+      app_context,
+    }
+  }
+
+  async fn get_profile_id<T>(
+    &self,
+    request: &Request<T>,
+    profile_id: String,
+  ) -> Result<ProfileId, Status> {
+    let tenant_id = request_tenant_id(request)?;
+    let profile_id = ProfileId::scoped(&tenant_id, profile_id)
+      .map_err(|_| Status::invalid_argument("invalid profile id"))?;
+    self
+      .app_context
+      .profile_interactor
+      .find_profile(&profile_id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or_else(|| Status::not_found("profile not found"))?;
+    Ok(profile_id)
+  }
+}
+
+#[tonic::async_trait]
+impl proto::ListeningEventService for ListeningEventService {
+  async fn put_listening_events(
+    &self,
+    request: Request<proto::PutListeningEventsRequest>,
+  ) -> Result<Response<proto::PutListeningEventsReply>, Status> {
+    let profile_id = self
+      .get_profile_id(&request, request.get_ref().profile_id.clone())
+      .await?;
+    let events = request
+      .into_inner()
+      .events
+      .into_iter()
+      .map(|event| {
+        Ok(ListeningEvent {
+          profile_id: profile_id.clone(),
+          album_file_name: FileName::try_from(event.album_file_name)
+            .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e)))?,
+          listened_at: parse_timestamp(&event.listened_at, "listened_at")?,
+          source: event.source,
+        })
+      })
+      .collect::<Result<Vec<_>, Status>>()?;
+    let recorded = self
+      .listening_event_interactor
+      .record(events)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to record listening events");
+        Status::invalid_argument(e.to_string())
+      })?;
+    Ok(Response::new(proto::PutListeningEventsReply { recorded }))
+  }
+
+  async fn get_listening_history(
+    &self,
+    request: Request<proto::GetListeningHistoryRequest>,
+  ) -> Result<Response<proto::GetListeningHistoryReply>, Status> {
+    let profile_id = self
+      .get_profile_id(&request, request.get_ref().profile_id.clone())
+      .await?;
+    let request = request.into_inner();
+    let before = request
+      .before
+      .map(|before| parse_timestamp(&before, "before"))
+      .transpose()?;
+    let events = self
+      .listening_event_interactor
+      .find_history(&profile_id, before, request.limit)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetListeningHistoryReply {
+      events: events.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn get_listen_counts(
+    &self,
+    request: Request<proto::GetListenCountsRequest>,
+  ) -> Result<Response<proto::GetListenCountsReply>, Status> {
+    let profile_id = self
+      .get_profile_id(&request, request.get_ref().profile_id.clone())
+      .await?;
+    let request = request.into_inner();
+    let since = request
+      .since
+      .map(|since| parse_timestamp(&since, "since"))
+      .transpose()?;
+    let counts = self
+      .listening_event_interactor
+      .find_listen_counts(&profile_id, since, request.limit)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetListenCountsReply {
+      counts: counts.into_iter().map(Into::into).collect(),
+    }))
+  }
+}
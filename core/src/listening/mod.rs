@@ -0,0 +1,4 @@
+pub mod listening_event;
+pub mod listening_event_interactor;
+pub mod listening_event_repository;
+pub mod listening_event_service;
@@ -1,12 +1,17 @@
 use crate::{
   files::file_metadata::file_name::FileName,
+  helpers::priority::Priority,
+  lookup::LookupLane,
   parser::parsed_file_data::{ParsedAlbum, ParsedAlbumSearchResult},
 };
 use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
 use data_encoding::BASE64;
 use serde_derive::{Deserialize, Serialize};
-use std::cmp::Ordering;
+use std::{
+  cmp::Ordering,
+  hash::{Hash, Hasher},
+};
 use strum::{EnumDiscriminants, EnumString, VariantArray, VariantNames};
 
 pub fn is_album_search_correlation_id(correlation_id: &str) -> bool {
@@ -27,10 +32,41 @@ pub fn get_query_from_album_search_correlation_id(
   AlbumSearchLookupQuery::from_encoded_string(&encoded)
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AlbumSearchLookupQuery {
   album_name: String,
   artist_name: String,
+  /**
+   * The lane, priority and deadline are not part of the query's identity, two queries for the same
+   * album are equal regardless of how they were started.
+   */
+  #[serde(default)]
+  lane: LookupLane,
+  /**
+   * Crawl priority for the lookup's pages, the lane's when unset
+   */
+  #[serde(default)]
+  priority: Option<Priority>,
+  /**
+   * The lookup expires if it hasn't finished by then
+   */
+  #[serde(default)]
+  deadline: Option<NaiveDateTime>,
+}
+
+impl PartialEq for AlbumSearchLookupQuery {
+  fn eq(&self, other: &Self) -> bool {
+    self.album_name == other.album_name && self.artist_name == other.artist_name
+  }
+}
+
+impl Eq for AlbumSearchLookupQuery {}
+
+impl Hash for AlbumSearchLookupQuery {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.album_name.hash(state);
+    self.artist_name.hash(state);
+  }
 }
 
 impl AlbumSearchLookupQuery {
@@ -38,9 +74,43 @@ impl AlbumSearchLookupQuery {
     AlbumSearchLookupQuery {
       album_name: album_name.to_lowercase(),
       artist_name: artist_name.to_lowercase(),
+      lane: LookupLane::default(),
+      priority: None,
+      deadline: None,
     }
   }
 
+  pub fn with_lane(mut self, lane: LookupLane) -> Self {
+    self.lane = lane;
+    self
+  }
+
+  pub fn lane(&self) -> LookupLane {
+    self.lane
+  }
+
+  pub fn with_priority(mut self, priority: Option<Priority>) -> Self {
+    self.priority = priority;
+    self
+  }
+
+  pub fn priority(&self) -> Option<Priority> {
+    self.priority
+  }
+
+  pub fn crawler_priority(&self) -> Priority {
+    self.priority.unwrap_or(self.lane.crawler_priority())
+  }
+
+  pub fn with_deadline(mut self, deadline: Option<NaiveDateTime>) -> Self {
+    self.deadline = deadline;
+    self
+  }
+
+  pub fn deadline(&self) -> Option<NaiveDateTime> {
+    self.deadline
+  }
+
   pub fn album_name(&self) -> &str {
     &self.album_name
   }
@@ -75,6 +145,9 @@ impl AlbumSearchLookupQuery {
     Ok(AlbumSearchLookupQuery {
       album_name: album_name.to_string(),
       artist_name: artist_name.to_string(),
+      lane: LookupLane::default(),
+      priority: None,
+      deadline: None,
     })
   }
 }
@@ -95,6 +168,7 @@ pub enum AlbumSearchLookupStep {
   AlbumParsing = 7,
   AlbumParseFailed = 8,
   AlbumParsed = 9,
+  Expired = 10,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, EnumDiscriminants, VariantNames)]
@@ -160,6 +234,15 @@ pub enum AlbumSearchLookup {
     parsed_album: ParsedAlbum,
     file_processing_correlation_id: String,
   },
+  /**
+   * The deadline passed before the lookup finished. Crawls already enqueued still run, but the
+   * lookup no longer follows them.
+   */
+  Expired {
+    query: AlbumSearchLookupQuery,
+    last_updated_at: NaiveDateTime,
+    file_processing_correlation_id: String,
+  },
 }
 
 impl AlbumSearchLookup {
@@ -186,6 +269,74 @@ impl AlbumSearchLookup {
       AlbumSearchLookup::AlbumParsing { query, .. } => query,
       AlbumSearchLookup::AlbumParseFailed { query, .. } => query,
       AlbumSearchLookup::AlbumParsed { query, .. } => query,
+      AlbumSearchLookup::Expired { query, .. } => query,
+    }
+  }
+
+  fn query_mut(&mut self) -> &mut AlbumSearchLookupQuery {
+    match self {
+      AlbumSearchLookup::Started { query } => query,
+      AlbumSearchLookup::SearchCrawling { query, .. } => query,
+      AlbumSearchLookup::SearchParsing { query, .. } => query,
+      AlbumSearchLookup::SearchParseFailed { query, .. } => query,
+      AlbumSearchLookup::SearchParsed { query, .. } => query,
+      AlbumSearchLookup::AlbumCrawling { query, .. } => query,
+      AlbumSearchLookup::AlbumParsing { query, .. } => query,
+      AlbumSearchLookup::AlbumParseFailed { query, .. } => query,
+      AlbumSearchLookup::AlbumParsed { query, .. } => query,
+      AlbumSearchLookup::Expired { query, .. } => query,
+    }
+  }
+
+  pub fn lane(&self) -> LookupLane {
+    self.query().lane()
+  }
+
+  pub fn with_lane(mut self, lane: LookupLane) -> Self {
+    self.query_mut().lane = lane;
+    self
+  }
+
+  /**
+   * Takes the priority and deadline of a later request for the same album. A request without a
+   * deadline clears it, since it's waiting for the lookup to finish however long that takes.
+   */
+  pub fn with_request_options(mut self, query: &AlbumSearchLookupQuery) -> Self {
+    let current = self.query_mut();
+    current.priority = query.priority.or(current.priority);
+    current.deadline = match (current.deadline, query.deadline) {
+      (Some(current_deadline), Some(deadline)) => Some(current_deadline.max(deadline)),
+      _ => None,
+    };
+    self
+  }
+
+  pub fn is_terminal(&self) -> bool {
+    matches!(
+      self,
+      AlbumSearchLookup::AlbumParsed { .. }
+        | AlbumSearchLookup::AlbumParseFailed { .. }
+        | AlbumSearchLookup::SearchParseFailed { .. }
+        | AlbumSearchLookup::Expired { .. }
+    )
+  }
+
+  /**
+   * Whether the lookup is unfinished with its deadline passed
+   */
+  pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+    !self.is_terminal()
+      && self
+        .query()
+        .deadline()
+        .is_some_and(|deadline| deadline <= now)
+  }
+
+  pub fn expire(&self, now: NaiveDateTime) -> AlbumSearchLookup {
+    AlbumSearchLookup::Expired {
+      query: self.query().clone(),
+      last_updated_at: now,
+      file_processing_correlation_id: self.file_processing_correlation_id(),
     }
   }
 
@@ -202,6 +353,7 @@ impl AlbumSearchLookup {
       AlbumSearchLookup::AlbumParsing { .. } => AlbumSearchLookupStep::AlbumParsing as u32,
       AlbumSearchLookup::AlbumParseFailed { .. } => AlbumSearchLookupStep::AlbumParseFailed as u32,
       AlbumSearchLookup::AlbumParsed { .. } => AlbumSearchLookupStep::AlbumParsed as u32,
+      AlbumSearchLookup::Expired { .. } => AlbumSearchLookupStep::Expired as u32,
     }
   }
 
@@ -240,6 +392,10 @@ impl AlbumSearchLookup {
         file_processing_correlation_id,
         ..
       } => file_processing_correlation_id.to_string(),
+      AlbumSearchLookup::Expired {
+        file_processing_correlation_id,
+        ..
+      } => file_processing_correlation_id.to_string(),
     }
   }
 
@@ -298,6 +454,9 @@ impl AlbumSearchLookup {
       AlbumSearchLookup::AlbumParsed {
         last_updated_at, ..
       } => Some(*last_updated_at),
+      AlbumSearchLookup::Expired {
+        last_updated_at, ..
+      } => Some(*last_updated_at),
       _ => None,
     }
   }
@@ -340,6 +499,23 @@ impl AlbumSearchLookup {
     }
   }
 
+  /**
+   * The file the lookup is waiting on the crawler for
+   */
+  pub fn crawling_file_name(&self) -> Option<FileName> {
+    match self {
+      AlbumSearchLookup::SearchCrawling {
+        album_search_file_name,
+        ..
+      } => Some(album_search_file_name.clone()),
+      AlbumSearchLookup::AlbumCrawling {
+        parsed_album_search_result,
+        ..
+      } => Some(parsed_album_search_result.file_name.clone()),
+      _ => None,
+    }
+  }
+
   pub fn album_file_parse_error(&self) -> Option<String> {
     match self {
       AlbumSearchLookup::AlbumParseFailed {
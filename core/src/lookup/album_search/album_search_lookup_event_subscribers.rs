@@ -18,7 +18,9 @@ use crate::{
   },
   files::file_metadata::{file_name::FileName, page_type::PageType},
   helpers::priority::Priority,
+  lookup::LookupLane,
   parser::parsed_file_data::ParsedFileData,
+  settings::Settings,
 };
 use anyhow::Result;
 use chrono::Utc;
@@ -130,20 +132,44 @@ impl AlbumSearchLookup {
   }
 }
 
+/**
+ * Each lane gets its own subscriber and cursor, so a backlog of background lookups never holds up
+ * interactive ones. The interactive lane keeps the original subscriber id to resume its cursor.
+ */
+fn lane_subscriber_id(lane: LookupLane) -> &'static str {
+  match lane {
+    LookupLane::Interactive => "album_search_lookup",
+    LookupLane::Background => "album_search_lookup_background",
+  }
+}
+
+/**
+ * Lookups of a lane advance concurrently up to the lane's concurrency, one per correlation id in a
+ * batch
+ */
+fn lane_batch_size(settings: &Settings, lane: LookupLane) -> usize {
+  match lane {
+    LookupLane::Interactive => settings.lookup.lanes.interactive.concurrency as usize,
+    LookupLane::Background => settings.lookup.lanes.background.concurrency as usize,
+  }
+}
+
 struct AlbumSearchLookupOrchestrator {
   crawler: Arc<Crawler>,
   lookup_interactor: Arc<LookupInteractor>,
   event_publisher: Arc<EventPublisher>,
   album_interactor: Arc<AlbumInteractor>,
+  lane: LookupLane,
 }
 
 impl AlbumSearchLookupOrchestrator {
-  fn new(app_context: Arc<ApplicationContext>) -> Self {
+  fn new(app_context: Arc<ApplicationContext>, lane: LookupLane) -> Self {
     Self {
       crawler: Arc::clone(&app_context.crawler),
       lookup_interactor: Arc::clone(&app_context.lookup_interactor),
       event_publisher: Arc::clone(&app_context.event_publisher),
       album_interactor: Arc::clone(&app_context.album_interactor),
+      lane,
     }
   }
 
@@ -158,12 +184,17 @@ impl AlbumSearchLookupOrchestrator {
   }
 
   #[instrument(skip(self))]
-  async fn enqueue_to_crawler(&self, file_name: &FileName, correlation_id: String) -> Result<()> {
+  async fn enqueue_to_crawler(
+    &self,
+    file_name: &FileName,
+    correlation_id: String,
+    priority: Priority,
+  ) -> Result<()> {
     self
       .crawler
       .enqueue(QueuePushParameters {
         file_name: file_name.clone(),
-        priority: Some(Priority::High),
+        priority: Some(priority),
         correlation_id: Some(correlation_id),
       })
       .await?;
@@ -172,12 +203,40 @@ impl AlbumSearchLookupOrchestrator {
 
   #[instrument(skip(self))]
   async fn handle_lookup_event(&self, event: Event, correlation_id: String) -> Result<()> {
-    if let Event::LookupAlbumSearchUpdated { lookup } = event {
+    if let Event::LookupAlbumSearchUpdated { mut lookup } = event {
+      if lookup.lane() != self.lane {
+        return Ok(());
+      }
+      if !matches!(
+        lookup,
+        AlbumSearchLookup::Started { .. } | AlbumSearchLookup::Expired { .. }
+      ) {
+        match self
+          .lookup_interactor
+          .find_album_search_lookup(lookup.query())
+          .await?
+        {
+          Some(AlbumSearchLookup::Expired { .. }) => {
+            // Progress that was underway when the lookup expired
+            info!("Ignoring update to expired album search lookup");
+            return Ok(());
+          }
+          // Moved to another lane since the update was published
+          Some(stored) if stored.lane() != lookup.lane() => {
+            lookup = lookup.with_lane(stored.lane());
+          }
+          _ => (),
+        }
+      }
       self.save_lookup(&lookup).await?;
 
       if let AlbumSearchLookup::Started { query, .. } = lookup {
         self
-          .enqueue_to_crawler(&query.file_name(), correlation_id.clone())
+          .enqueue_to_crawler(
+            &query.file_name(),
+            correlation_id.clone(),
+            query.crawler_priority(),
+          )
           .await?;
         self
           .save_lookup(&AlbumSearchLookup::SearchCrawling {
@@ -227,6 +286,7 @@ impl AlbumSearchLookupOrchestrator {
               .enqueue_to_crawler(
                 &parsed_album_search_result.file_name,
                 correlation_id.clone(),
+                query.crawler_priority(),
               )
               .await?;
             self
@@ -299,6 +359,9 @@ impl AlbumSearchLookupOrchestrator {
       return Ok(());
     }
     let lookup = lookup.unwrap();
+    if lookup.lane() != self.lane {
+      return Ok(());
+    }
 
     if let Some(next_lookup) = lookup.apply_file_processing_event(event, correlation_id.clone()) {
       info!(
@@ -349,7 +412,8 @@ impl AlbumSearchLookupOrchestrator {
         }
         _ => (),
       }
-    } else {
+    } else if self.lane == LookupLane::Interactive {
+      // Not tied to a lane, handled once
       self
         .handle_non_related_event(event_data.payload.event)
         .await?;
@@ -362,16 +426,52 @@ impl AlbumSearchLookupOrchestrator {
 pub fn build_album_search_lookup_event_subscribers(
   app_context: Arc<ApplicationContext>,
 ) -> Result<Vec<EventSubscriber>> {
-  let orchestrator = Arc::new(AlbumSearchLookupOrchestrator::new(Arc::clone(&app_context)));
-  Ok(vec![EventSubscriberBuilder::default()
-    .id("album_search_lookup")
-    .topics(vec![Topic::File, Topic::Parser, Topic::Lookup])
-    .batch_size(250)
-    .app_context(Arc::clone(&app_context))
-    .grouping_strategy(GroupingStrategy::GroupByCorrelationId)
-    .handler(EventHandler::Single(Arc::new(move |(event_data, _, _)| {
-      let orchestrator = Arc::clone(&orchestrator);
-      Box::pin(async move { orchestrator.handle_event(event_data).await })
-    })))
-    .build()?])
+  [LookupLane::Interactive, LookupLane::Background]
+    .into_iter()
+    .map(|lane| {
+      let orchestrator = Arc::new(AlbumSearchLookupOrchestrator::new(
+        Arc::clone(&app_context),
+        lane,
+      ));
+      Ok(
+        EventSubscriberBuilder::default()
+          .id(lane_subscriber_id(lane))
+          .topics(vec![Topic::File, Topic::Parser, Topic::Lookup])
+          .batch_size(lane_batch_size(&app_context.settings, lane))
+          .app_context(Arc::clone(&app_context))
+          .grouping_strategy(GroupingStrategy::GroupByCorrelationId)
+          .handler(EventHandler::Single(Arc::new(move |(event_data, _, _)| {
+            let orchestrator = Arc::clone(&orchestrator);
+            Box::pin(async move { orchestrator.handle_event(event_data).await })
+          })))
+          .build()?,
+      )
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_lanes_have_separate_subscribers() {
+    assert_eq!(
+      lane_subscriber_id(LookupLane::Interactive),
+      "album_search_lookup"
+    );
+    assert_ne!(
+      lane_subscriber_id(LookupLane::Interactive),
+      lane_subscriber_id(LookupLane::Background)
+    );
+  }
+
+  #[test]
+  fn test_lane_batch_size() {
+    let mut settings = Settings::default();
+    settings.lookup.lanes.interactive.concurrency = 100;
+    settings.lookup.lanes.background.concurrency = 10;
+    assert_eq!(lane_batch_size(&settings, LookupLane::Interactive), 100);
+    assert_eq!(lane_batch_size(&settings, LookupLane::Background), 10);
+  }
 }
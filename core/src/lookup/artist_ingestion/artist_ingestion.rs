@@ -0,0 +1,413 @@
+use super::super::file_processing_status::FileProcessingStatus;
+use crate::{
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+  proto,
+};
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use ulid::Ulid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtistIngestionStatus {
+  Started,
+  InProgress,
+  Completed,
+  Failed,
+}
+
+impl From<ArtistIngestionStatus> for proto::ArtistIngestionStatus {
+  fn from(val: ArtistIngestionStatus) -> Self {
+    match val {
+      ArtistIngestionStatus::Started => proto::ArtistIngestionStatus::ArtistIngestionStarted,
+      ArtistIngestionStatus::InProgress => proto::ArtistIngestionStatus::ArtistIngestionInProgress,
+      ArtistIngestionStatus::Completed => proto::ArtistIngestionStatus::ArtistIngestionCompleted,
+      ArtistIngestionStatus::Failed => proto::ArtistIngestionStatus::ArtistIngestionFailed,
+    }
+  }
+}
+
+/**
+ * Artists still waiting to be fanned out this long after the ingestion started are marked failed,
+ * since a crawl failure never reaches the parser
+ */
+const FAN_OUT_TIMEOUT_HOURS: i64 = 24;
+
+/**
+ * Accepts either a file name (artist/billy-woods) or a full artist page URL
+ */
+pub fn parse_artist_file_name(value: &str) -> Result<FileName> {
+  let path = value
+    .trim()
+    .trim_start_matches("https://")
+    .trim_start_matches("http://")
+    .trim_start_matches("www.")
+    .trim_start_matches("rateyourmusic.com");
+  let file_name = FileName::try_from(path)?;
+  if file_name.page_type() != PageType::Artist {
+    return Err(anyhow!("Not an artist page: {}", value));
+  }
+  Ok(file_name)
+}
+
+/**
+ * A bulk artist ingestion: crawls each artist page, then fans out crawls for every album in the
+ * parsed discographies until the album crawl budget runs out.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtistIngestion {
+  pub id: String,
+  pub artist_file_names: Vec<FileName>,
+  /**
+   * Maximum number of album crawls across the whole ingestion
+   */
+  pub max_album_crawls: Option<u32>,
+  /**
+   * File name prefixes that are never crawled, e.g. "release/comp/" or "artist/some-artist"
+   */
+  pub blocklist: Vec<String>,
+  /**
+   * Discography albums included in the ingestion, by artist. Artists are added once fanned out.
+   */
+  pub artist_albums: HashMap<FileName, Vec<FileName>>,
+  /**
+   * Albums crawled by this ingestion, as opposed to ones that were already fresh
+   */
+  pub crawled_album_file_names: Vec<FileName>,
+  pub skipped_file_names: Vec<FileName>,
+  /**
+   * Artists whose page failed to parse or that weren't fanned out before the timeout
+   */
+  #[serde(default)]
+  pub failed_artist_file_names: Vec<FileName>,
+  /**
+   * Whether any artist is still waiting to be parsed and fanned out
+   */
+  pub active: bool,
+  pub created_at: NaiveDateTime,
+  pub updated_at: NaiveDateTime,
+}
+
+impl ArtistIngestion {
+  pub fn new(
+    artist_file_names: Vec<FileName>,
+    max_album_crawls: Option<u32>,
+    blocklist: Vec<String>,
+  ) -> Self {
+    let now = Utc::now().naive_utc();
+    let mut ingestion = Self {
+      id: Ulid::new().to_string(),
+      artist_file_names: vec![],
+      max_album_crawls,
+      blocklist,
+      artist_albums: HashMap::new(),
+      crawled_album_file_names: vec![],
+      skipped_file_names: vec![],
+      failed_artist_file_names: vec![],
+      active: true,
+      created_at: now,
+      updated_at: now,
+    };
+    let mut seen = HashSet::new();
+    for file_name in artist_file_names {
+      if !seen.insert(file_name.clone()) {
+        continue;
+      }
+      if ingestion.is_blocked(&file_name) {
+        ingestion.skipped_file_names.push(file_name);
+      } else {
+        ingestion.artist_file_names.push(file_name);
+      }
+    }
+    ingestion.active = !ingestion.artist_file_names.is_empty();
+    ingestion
+  }
+
+  pub fn correlation_id(&self) -> String {
+    format!("artist_ingestion:{}", self.id)
+  }
+
+  pub fn is_blocked(&self, file_name: &FileName) -> bool {
+    let file_name = file_name.to_string();
+    self
+      .blocklist
+      .iter()
+      .any(|prefix| file_name.starts_with(prefix.as_str()))
+  }
+
+  pub fn is_pending_fan_out(&self, artist_file_name: &FileName) -> bool {
+    self.artist_file_names.contains(artist_file_name)
+      && !self.artist_albums.contains_key(artist_file_name)
+      && !self.failed_artist_file_names.contains(artist_file_name)
+  }
+
+  pub fn pending_artist_file_names(&self) -> Vec<FileName> {
+    self
+      .artist_file_names
+      .iter()
+      .filter(|file_name| self.is_pending_fan_out(file_name))
+      .cloned()
+      .collect()
+  }
+
+  fn update_active(&mut self) {
+    self.active = !self.pending_artist_file_names().is_empty();
+    self.updated_at = Utc::now().naive_utc();
+  }
+
+  pub fn deadline(&self) -> NaiveDateTime {
+    self.created_at + TimeDelta::try_hours(FAN_OUT_TIMEOUT_HOURS).unwrap()
+  }
+
+  /**
+   * Marks pending artists failed, returning whether any were pending
+   */
+  pub fn fail_artists(&mut self, artist_file_names: &[FileName]) -> bool {
+    let failed = artist_file_names
+      .iter()
+      .filter(|file_name| self.is_pending_fan_out(file_name))
+      .cloned()
+      .collect::<Vec<_>>();
+    if failed.is_empty() {
+      return false;
+    }
+    self.failed_artist_file_names.extend(failed);
+    self.update_active();
+    true
+  }
+
+  /**
+   * Fails every artist that hasn't been fanned out yet, ending the ingestion
+   */
+  pub fn expire(&mut self) -> bool {
+    let pending = self.pending_artist_file_names();
+    self.fail_artists(&pending)
+  }
+
+  pub fn remaining_album_crawls(&self) -> Option<u32> {
+    self
+      .max_album_crawls
+      .map(|max| max.saturating_sub(self.crawled_album_file_names.len() as u32))
+  }
+
+  fn album_file_names(&self) -> HashSet<&FileName> {
+    self.artist_albums.values().flatten().collect()
+  }
+
+  /**
+   * Records an artist's discography. Blocked albums are skipped, albums already included through
+   * another artist are ignored, and the rest are returned for the caller to crawl or mark fresh.
+   */
+  pub fn fan_out(
+    &mut self,
+    artist_file_name: &FileName,
+    discography: Vec<FileName>,
+  ) -> Vec<FileName> {
+    let included = self
+      .album_file_names()
+      .into_iter()
+      .cloned()
+      .collect::<HashSet<_>>();
+    let mut albums = Vec::new();
+    for file_name in discography {
+      if included.contains(&file_name) || albums.contains(&file_name) {
+        continue;
+      }
+      if self.is_blocked(&file_name) {
+        if !self.skipped_file_names.contains(&file_name) {
+          self.skipped_file_names.push(file_name);
+        }
+        continue;
+      }
+      albums.push(file_name);
+    }
+    self
+      .artist_albums
+      .insert(artist_file_name.clone(), albums.clone());
+    self.update_active();
+    albums
+  }
+
+  /**
+   * Takes stale albums from a fan-out up to the remaining crawl budget. Albums over budget are
+   * dropped from their artist and recorded as skipped.
+   */
+  pub fn claim_album_crawls(
+    &mut self,
+    artist_file_name: &FileName,
+    stale_albums: Vec<FileName>,
+  ) -> Vec<FileName> {
+    let budget = self
+      .remaining_album_crawls()
+      .map_or(stale_albums.len(), |remaining| remaining as usize);
+    let (claimed, over_budget) = if stale_albums.len() > budget {
+      let mut claimed = stale_albums;
+      let over_budget = claimed.split_off(budget);
+      (claimed, over_budget)
+    } else {
+      (stale_albums, vec![])
+    };
+    if let Some(albums) = self.artist_albums.get_mut(artist_file_name) {
+      albums.retain(|file_name| !over_budget.contains(file_name));
+    }
+    self.skipped_file_names.extend(over_budget);
+    self.crawled_album_file_names.extend(claimed.clone());
+    claimed
+  }
+
+  pub fn progress(
+    &self,
+    mut statuses: HashMap<FileName, FileProcessingStatus>,
+  ) -> ArtistIngestionProgress {
+    let crawled = self.crawled_album_file_names.iter().collect::<HashSet<_>>();
+    let mut completed = 0;
+    let mut failed = 0;
+    let mut artist_statuses = HashMap::new();
+    for file_name in &self.artist_file_names {
+      let status = statuses.remove(file_name);
+      if status.is_some_and(|status| status.is_error())
+        || self.failed_artist_file_names.contains(file_name)
+      {
+        failed += 1;
+      } else if self.artist_albums.contains_key(file_name) {
+        completed += 1;
+      }
+      artist_statuses.insert(file_name.clone(), status);
+    }
+    let mut album_statuses = HashMap::new();
+    for file_name in self.album_file_names() {
+      let status = statuses.remove(file_name);
+      if !crawled.contains(file_name) {
+        completed += 1;
+      } else if status.is_some_and(|status| status.is_error()) {
+        failed += 1;
+      } else if status == Some(FileProcessingStatus::ReadModelUpdated) {
+        completed += 1;
+      }
+      album_statuses.insert(file_name.clone(), status);
+    }
+    let total = artist_statuses.len() + album_statuses.len();
+    let status = if total == 0 {
+      ArtistIngestionStatus::Completed
+    } else if completed == 0 && failed == 0 {
+      ArtistIngestionStatus::Started
+    } else if completed + failed < total {
+      ArtistIngestionStatus::InProgress
+    } else if completed == 0 {
+      ArtistIngestionStatus::Failed
+    } else {
+      ArtistIngestionStatus::Completed
+    };
+    ArtistIngestionProgress {
+      status,
+      total: total as u32,
+      completed,
+      failed,
+      artist_statuses,
+      album_statuses,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtistIngestionProgress {
+  pub status: ArtistIngestionStatus,
+  pub total: u32,
+  pub completed: u32,
+  pub failed: u32,
+  /**
+   * Latest processing status of each component, None if it hasn't been enqueued yet
+   */
+  pub artist_statuses: HashMap<FileName, Option<FileProcessingStatus>>,
+  pub album_statuses: HashMap<FileName, Option<FileProcessingStatus>>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_artist_file_name() -> Result<()> {
+    assert_eq!(
+      parse_artist_file_name("https://rateyourmusic.com/artist/billy-woods/")?,
+      FileName::try_from("artist/billy-woods")?
+    );
+    assert_eq!(
+      parse_artist_file_name("artist/billy-woods")?,
+      FileName::try_from("artist/billy-woods")?
+    );
+    assert!(parse_artist_file_name("release/album/billy-woods/aethiopes").is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_fan_out_respects_blocklist_and_budget() -> Result<()> {
+    let artist = FileName::try_from("artist/billy-woods")?;
+    let aethiopes = FileName::try_from("release/album/billy-woods/aethiopes")?;
+    let hiding_places = FileName::try_from("release/album/billy-woods-kenny-segal/hiding-places")?;
+    let maps = FileName::try_from("release/album/billy-woods-kenny-segal/maps")?;
+    let comp = FileName::try_from("release/comp/billy-woods/known-unknowns")?;
+    let mut ingestion = ArtistIngestion::new(
+      vec![artist.clone(), artist.clone()],
+      Some(1),
+      vec!["release/comp/".to_string()],
+    );
+    assert_eq!(ingestion.artist_file_names, vec![artist.clone()]);
+
+    let albums = ingestion.fan_out(
+      &artist,
+      vec![
+        aethiopes.clone(),
+        comp.clone(),
+        hiding_places.clone(),
+        maps.clone(),
+      ],
+    );
+    assert_eq!(
+      albums,
+      vec![aethiopes.clone(), hiding_places.clone(), maps.clone()]
+    );
+    assert!(!ingestion.active);
+
+    // aethiopes is already fresh, so only the remaining two compete for the budget of one
+    let claimed = ingestion.claim_album_crawls(&artist, vec![hiding_places.clone(), maps.clone()]);
+    assert_eq!(claimed, vec![hiding_places.clone()]);
+    assert_eq!(ingestion.skipped_file_names, vec![comp, maps]);
+
+    let progress = ingestion.progress(HashMap::from([(
+      hiding_places,
+      FileProcessingStatus::CrawlEnqueued,
+    )]));
+    assert_eq!(progress.total, 3);
+    assert_eq!(progress.completed, 2);
+    assert_eq!(progress.status, ArtistIngestionStatus::InProgress);
+    Ok(())
+  }
+
+  #[test]
+  fn test_failed_artists_end_ingestion() -> Result<()> {
+    let billy_woods = FileName::try_from("artist/billy-woods")?;
+    let armand_hammer = FileName::try_from("artist/armand-hammer")?;
+    let mut ingestion = ArtistIngestion::new(
+      vec![billy_woods.clone(), armand_hammer.clone()],
+      None,
+      vec![],
+    );
+    assert!(ingestion.fail_artists(&[billy_woods.clone()]));
+    assert!(!ingestion.fail_artists(&[billy_woods.clone()]));
+    assert!(ingestion.active);
+    assert_eq!(
+      ingestion.pending_artist_file_names(),
+      vec![armand_hammer.clone()]
+    );
+
+    assert!(ingestion.expire());
+    assert!(!ingestion.active);
+    assert!(!ingestion.is_pending_fan_out(&armand_hammer));
+    let progress = ingestion.progress(HashMap::new());
+    assert_eq!(progress.failed, 2);
+    assert_eq!(progress.status, ArtistIngestionStatus::Failed);
+    Ok(())
+  }
+}
@@ -0,0 +1,75 @@
+use crate::{
+  context::ApplicationContext,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+      GroupingStrategy,
+    },
+  },
+  files::file_metadata::page_type::PageType,
+  group_event_handler,
+  parser::parsed_file_data::ParsedFileData,
+};
+use anyhow::Result;
+use std::{collections::HashMap, sync::Arc};
+
+async fn fan_out_artist_ingestions(
+  event_data: Vec<EventData>,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  let mut discographies = HashMap::new();
+  let mut failed_artist_file_names = Vec::new();
+  for event_data in event_data {
+    match event_data.payload.event {
+      Event::FileParsed {
+        file_name,
+        data: ParsedFileData::Artist(artist),
+        ..
+      } => {
+        discographies.insert(
+          file_name,
+          artist
+            .albums
+            .into_iter()
+            .map(|album| album.file_name)
+            .collect::<Vec<_>>(),
+        );
+      }
+      Event::FileParseFailed { file_name, .. } if file_name.page_type() == PageType::Artist => {
+        failed_artist_file_names.push(file_name);
+      }
+      _ => {}
+    }
+  }
+  // A successful parse in the same batch wins
+  failed_artist_file_names.retain(|file_name| !discographies.contains_key(file_name));
+
+  if !discographies.is_empty() {
+    app_context
+      .lookup_interactor
+      .fan_out_artist_ingestions(discographies)
+      .await?;
+  }
+  if !failed_artist_file_names.is_empty() {
+    app_context
+      .lookup_interactor
+      .fail_artist_ingestion_artists(failed_artist_file_names)
+      .await?;
+  }
+  Ok(())
+}
+
+pub fn build_artist_ingestion_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("fan_out_artist_ingestions")
+    .topic(Topic::Parser)
+    .batch_size(50)
+    .app_context(Arc::clone(&app_context))
+    .grouping_strategy(GroupingStrategy::All)
+    .handler(group_event_handler!(fan_out_artist_ingestions))
+    .build()?])
+}
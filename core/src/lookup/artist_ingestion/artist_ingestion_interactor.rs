@@ -0,0 +1,204 @@
+use super::{
+  super::file_processing_status::{FileProcessingStatus, FileProcessingStatusRepository},
+  artist_ingestion::{ArtistIngestion, ArtistIngestionProgress},
+  artist_ingestion_repository::ArtistIngestionRepository,
+};
+use crate::{
+  crawler::crawler::{Crawler, QueuePushParametersBuilder},
+  files::{file_interactor::FileInteractor, file_metadata::file_name::FileName},
+  helpers::{document_store::DocumentStore, priority::Priority},
+};
+use anyhow::Result;
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+use tracing::info;
+
+pub struct ArtistIngestionInteractor {
+  artist_ingestion_repository: ArtistIngestionRepository,
+  file_processing_status_repository: Arc<FileProcessingStatusRepository>,
+  file_interactor: Arc<FileInteractor>,
+  crawler: Arc<Crawler>,
+}
+
+impl ArtistIngestionInteractor {
+  pub fn new(
+    doc_store: Arc<DocumentStore>,
+    file_processing_status_repository: Arc<FileProcessingStatusRepository>,
+    file_interactor: Arc<FileInteractor>,
+    crawler: Arc<Crawler>,
+  ) -> Self {
+    Self {
+      artist_ingestion_repository: ArtistIngestionRepository::new(doc_store),
+      file_processing_status_repository,
+      file_interactor,
+      crawler,
+    }
+  }
+
+  async fn enqueue(
+    &self,
+    ingestion: &ArtistIngestion,
+    file_names: Vec<FileName>,
+    priority: Priority,
+  ) -> Result<()> {
+    if file_names.is_empty() {
+      return Ok(());
+    }
+    self
+      .crawler
+      .enqueue_many(
+        file_names
+          .iter()
+          .map(|file_name| {
+            QueuePushParametersBuilder::default()
+              .file_name(file_name.clone())
+              .priority(priority)
+              .correlation_id(ingestion.correlation_id())
+              .build()
+          })
+          .collect::<Result<Vec<_>, _>>()?,
+      )
+      .await?;
+    self
+      .file_processing_status_repository
+      .put_many(
+        file_names
+          .into_iter()
+          .map(|file_name| (file_name, FileProcessingStatus::CrawlEnqueued))
+          .collect(),
+      )
+      .await?;
+    Ok(())
+  }
+
+  async fn get_progress(&self, ingestion: &ArtistIngestion) -> Result<ArtistIngestionProgress> {
+    let mut file_names = ingestion.artist_file_names.clone();
+    file_names.extend(ingestion.artist_albums.values().flatten().cloned());
+    let statuses = self
+      .file_processing_status_repository
+      .get_many(file_names)
+      .await?;
+    Ok(ingestion.progress(statuses))
+  }
+
+  pub async fn start(
+    &self,
+    artist_file_names: Vec<FileName>,
+    max_album_crawls: Option<u32>,
+    blocklist: Vec<String>,
+  ) -> Result<(ArtistIngestion, ArtistIngestionProgress)> {
+    let ingestion = ArtistIngestion::new(artist_file_names, max_album_crawls, blocklist);
+    self
+      .artist_ingestion_repository
+      .put(ingestion.clone())
+      .await?;
+    self
+      .enqueue(
+        &ingestion,
+        ingestion.artist_file_names.clone(),
+        Priority::High,
+      )
+      .await?;
+    info!(
+      id = ingestion.id,
+      artists = ingestion.artist_file_names.len(),
+      "Started artist ingestion"
+    );
+    let progress = self.get_progress(&ingestion).await?;
+    Ok((ingestion, progress))
+  }
+
+  pub async fn find(&self, id: &str) -> Result<Option<(ArtistIngestion, ArtistIngestionProgress)>> {
+    match self.artist_ingestion_repository.find(id).await? {
+      Some(ingestion) => {
+        let progress = self.get_progress(&ingestion).await?;
+        Ok(Some((ingestion, progress)))
+      }
+      None => Ok(None),
+    }
+  }
+
+  /**
+   * Fans out album crawls for parsed artists belonging to active ingestions. Albums with a fresh
+   * file aren't crawled again and don't count towards the crawl budget.
+   */
+  pub async fn fan_out(&self, discographies: HashMap<FileName, Vec<FileName>>) -> Result<()> {
+    let artist_file_names = discographies.keys().cloned().collect::<Vec<_>>();
+    for mut ingestion in self
+      .artist_ingestion_repository
+      .find_pending_fan_out(&artist_file_names)
+      .await?
+    {
+      let mut fanned_out = Vec::new();
+      for (artist_file_name, discography) in &discographies {
+        if ingestion.is_pending_fan_out(artist_file_name) {
+          let albums = ingestion.fan_out(artist_file_name, discography.clone());
+          fanned_out.push((artist_file_name, albums));
+        }
+      }
+      if fanned_out.is_empty() {
+        continue;
+      }
+      let stale = self
+        .file_interactor
+        .find_stale(
+          fanned_out
+            .iter()
+            .flat_map(|(_, albums)| albums.iter().cloned())
+            .collect(),
+        )
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+      let mut crawls = Vec::new();
+      for (artist_file_name, albums) in fanned_out {
+        let stale_albums = albums
+          .into_iter()
+          .filter(|file_name| stale.contains(file_name))
+          .collect();
+        crawls.extend(ingestion.claim_album_crawls(artist_file_name, stale_albums));
+      }
+      info!(
+        id = ingestion.id,
+        crawls = crawls.len(),
+        "Fanning out artist ingestion"
+      );
+      self.enqueue(&ingestion, crawls, Priority::Standard).await?;
+      self.artist_ingestion_repository.put(ingestion).await?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Marks artists whose page failed to parse as failed in the ingestions waiting on them
+   */
+  pub async fn fail_artists(&self, artist_file_names: Vec<FileName>) -> Result<()> {
+    for mut ingestion in self
+      .artist_ingestion_repository
+      .find_pending_fan_out(&artist_file_names)
+      .await?
+    {
+      if ingestion.fail_artists(&artist_file_names) {
+        info!(id = ingestion.id, "Artist ingestion artists failed");
+        self.artist_ingestion_repository.put(ingestion).await?;
+      }
+    }
+    Ok(())
+  }
+
+  /**
+   * Ends the ingestion if artists are still waiting to be fanned out past its deadline
+   */
+  pub async fn expire(&self, id: &str) -> Result<()> {
+    let Some(mut ingestion) = self.artist_ingestion_repository.find(id).await? else {
+      return Ok(());
+    };
+    if ingestion.expire() {
+      info!(id = ingestion.id, "Artist ingestion passed its deadline");
+      self.artist_ingestion_repository.put(ingestion).await?;
+    }
+    Ok(())
+  }
+}
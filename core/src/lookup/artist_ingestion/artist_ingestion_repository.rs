@@ -0,0 +1,124 @@
+use super::artist_ingestion::ArtistIngestion;
+use crate::{
+  files::file_metadata::file_name::FileName,
+  helpers::document_store::{DocumentFilter, DocumentStore},
+};
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+
+pub struct ArtistIngestionRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "artist_ingestion";
+const PENDING_ARTIST_COLLECTION: &str = "artist_ingestion_pending_artist";
+
+/**
+ * Indexes ingestions by the artists they're waiting to fan out, so a parsed artist page only
+ * loads the ingestions that include it
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingArtist {
+  ingestion_id: String,
+  artist_file_name: FileName,
+}
+
+fn pending_artist_key(ingestion_id: &str, artist_file_name: &FileName) -> String {
+  format!("{}:{}", ingestion_id, artist_file_name.to_string())
+}
+
+impl ArtistIngestionRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, ingestion: ArtistIngestion) -> Result<()> {
+    let pending = ingestion.pending_artist_file_names();
+    let resolved_keys = ingestion
+      .artist_file_names
+      .iter()
+      .filter(|file_name| !pending.contains(file_name))
+      .map(|file_name| pending_artist_key(&ingestion.id, file_name))
+      .collect::<Vec<_>>();
+    let pending_artists = pending
+      .into_iter()
+      .map(|artist_file_name| {
+        (
+          pending_artist_key(&ingestion.id, &artist_file_name),
+          PendingArtist {
+            ingestion_id: ingestion.id.clone(),
+            artist_file_name,
+          },
+          None,
+        )
+      })
+      .collect::<Vec<_>>();
+    self
+      .doc_store
+      .put(COLLECTION, &ingestion.id.clone(), ingestion, None)
+      .await?;
+    if !pending_artists.is_empty() {
+      self
+        .doc_store
+        .put_many(PENDING_ARTIST_COLLECTION, pending_artists)
+        .await?;
+    }
+    if !resolved_keys.is_empty() {
+      self
+        .doc_store
+        .delete_many(PENDING_ARTIST_COLLECTION, resolved_keys)
+        .await?;
+    }
+    Ok(())
+  }
+
+  pub async fn find(&self, id: &str) -> Result<Option<ArtistIngestion>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<ArtistIngestion>(COLLECTION, id)
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Ingestions waiting to fan out any of the artists
+   */
+  pub async fn find_pending_fan_out(
+    &self,
+    artist_file_names: &[FileName],
+  ) -> Result<Vec<ArtistIngestion>> {
+    if artist_file_names.is_empty() {
+      return Ok(vec![]);
+    }
+    let mut filter = DocumentFilter::new();
+    for (i, artist_file_name) in artist_file_names.iter().enumerate() {
+      if i > 0 {
+        filter.or();
+      }
+      filter.condition("artist_file_name", "=", artist_file_name.to_string());
+    }
+    let ingestion_ids = self
+      .doc_store
+      .find_many::<PendingArtist>(PENDING_ARTIST_COLLECTION, filter.build(), None)
+      .await?
+      .documents
+      .into_iter()
+      .map(|doc| doc.document.ingestion_id)
+      .collect::<HashSet<_>>();
+    if ingestion_ids.is_empty() {
+      return Ok(vec![]);
+    }
+    Ok(
+      self
+        .doc_store
+        .find_many_by_key::<ArtistIngestion>(COLLECTION, ingestion_ids.into_iter().collect())
+        .await?
+        .into_values()
+        .map(|doc| doc.document)
+        .collect(),
+    )
+  }
+}
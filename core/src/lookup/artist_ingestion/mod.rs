@@ -0,0 +1,4 @@
+pub mod artist_ingestion;
+pub mod artist_ingestion_event_subscribers;
+pub mod artist_ingestion_interactor;
+pub mod artist_ingestion_repository;
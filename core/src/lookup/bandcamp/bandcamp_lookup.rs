@@ -0,0 +1,150 @@
+use crate::{
+  albums::album_read_model::AlbumReadModel, files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::ParsedBandcampAlbum,
+};
+use anyhow::Result;
+use strsim::jaro_winkler;
+use unidecode::unidecode;
+
+const MIN_TITLE_SIMILARITY: f64 = 0.9;
+const CORRELATION_ID_PREFIX: &str = "bandcamp_lookup:";
+const VARIOUS_ARTISTS: &str = "various artists";
+
+fn normalize(value: &str) -> String {
+  unidecode(value).to_lowercase().trim().to_string()
+}
+
+/**
+ * Bandcamp credits collaborations in a single artist string, e.g. "Artist A & Artist B"
+ */
+fn split_artist_names(artist_name: &str) -> Vec<String> {
+  normalize(artist_name)
+    .split([',', '&', '+', '/'])
+    .flat_map(|name| name.split(" and "))
+    .map(|name| name.trim().to_string())
+    .filter(|name| !name.is_empty())
+    .collect()
+}
+
+pub fn bandcamp_search_file_name(album: &AlbumReadModel) -> Result<FileName> {
+  let query = album
+    .artists
+    .first()
+    .map(|artist| format!("{} {}", artist.name, album.name))
+    .unwrap_or_else(|| album.name.clone());
+  let query_string = serde_urlencoded::to_string([("q", query.as_str()), ("item_type", "a")])?;
+  FileName::try_from(format!("bandcamp/search?{}", query_string))
+}
+
+pub fn bandcamp_lookup_correlation_id(album_file_name: &FileName) -> String {
+  format!("{}{}", CORRELATION_ID_PREFIX, album_file_name.to_string())
+}
+
+pub fn get_album_file_name_from_bandcamp_lookup_correlation_id(
+  correlation_id: &str,
+) -> Option<FileName> {
+  correlation_id
+    .strip_prefix(CORRELATION_ID_PREFIX)
+    .and_then(|file_name| FileName::try_from(file_name).ok())
+}
+
+/**
+ * The search result by one of the album's artists with the closest title, the first on ties.
+ * Compilations match results credited to various artists.
+ */
+pub fn find_bandcamp_album_match<'a>(
+  album: &AlbumReadModel,
+  results: &'a [ParsedBandcampAlbum],
+) -> Option<&'a ParsedBandcampAlbum> {
+  let album_name = normalize(&album.name);
+  let mut artist_names = album
+    .artists
+    .iter()
+    .map(|artist| normalize(&artist.name))
+    .collect::<Vec<_>>();
+  if album.is_various_artists {
+    artist_names.push(VARIOUS_ARTISTS.to_string());
+  }
+  results
+    .iter()
+    // max_by keeps the last of equal elements, reversing keeps the first
+    .rev()
+    .filter_map(|result| {
+      let title_similarity = jaro_winkler(&album_name, &normalize(&result.name));
+      let shares_artist = split_artist_names(&result.artist_name)
+        .iter()
+        .any(|artist_name| artist_names.contains(artist_name));
+      (shares_artist && title_similarity >= MIN_TITLE_SIMILARITY)
+        .then_some((result, title_similarity))
+    })
+    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(result, _)| result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_read_model::AlbumReadModelArtist;
+
+  fn result(name: &str, artist_name: &str, url: &str) -> ParsedBandcampAlbum {
+    ParsedBandcampAlbum {
+      name: name.to_string(),
+      artist_name: artist_name.to_string(),
+      url: url.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_find_bandcamp_album_match() -> Result<()> {
+    let album = AlbumReadModel {
+      name: "Hypnotic Brass Ensemble".to_string(),
+      file_name: FileName::try_from(
+        "release/album/hypnotic-brass-ensemble/hypnotic-brass-ensemble",
+      )?,
+      artists: vec![AlbumReadModelArtist {
+        name: "Hypnotic Brass Ensemble".to_string(),
+        file_name: FileName::try_from("artist/hypnotic-brass-ensemble")?,
+      }],
+      ..Default::default()
+    };
+    let results = vec![
+      result(
+        "Hypnotic Brass Ensemble",
+        "Someone Else",
+        "https://a.bandcamp.com/album/x",
+      ),
+      result(
+        "Hypnotic Brass Ensemble (Remixes)",
+        "Hypnotic Brass Ensemble",
+        "https://b.bandcamp.com/album/remixes",
+      ),
+      result(
+        "Hypnotic Brass Ensemble",
+        "Hypnotic Brass Ensemble & Friends",
+        "https://b.bandcamp.com/album/hbe",
+      ),
+    ];
+    assert_eq!(
+      find_bandcamp_album_match(&album, &results).map(|result| result.url.as_str()),
+      Some("https://b.bandcamp.com/album/hbe")
+    );
+    assert_eq!(find_bandcamp_album_match(&album, &results[..1]), None);
+    Ok(())
+  }
+
+  #[test]
+  fn test_bandcamp_lookup_correlation_id() -> Result<()> {
+    let file_name = FileName::try_from("release/album/sprain/as-lost-through-collision")?;
+    assert_eq!(
+      get_album_file_name_from_bandcamp_lookup_correlation_id(&bandcamp_lookup_correlation_id(
+        &file_name
+      )),
+      Some(file_name)
+    );
+    assert_eq!(
+      get_album_file_name_from_bandcamp_lookup_correlation_id("crawl_chart_albums:foo"),
+      None
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,69 @@
+use super::bandcamp_lookup::get_album_file_name_from_bandcamp_lookup_correlation_id;
+use crate::{
+  context::ApplicationContext,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+      GroupingStrategy,
+    },
+  },
+  group_event_handler,
+  parser::parsed_file_data::ParsedFileData,
+};
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::error;
+
+async fn resolve_bandcamp_links(
+  event_data: Vec<EventData>,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  let Some(bandcamp_lookup_interactor) = app_context.bandcamp_lookup_interactor.as_ref() else {
+    return Ok(());
+  };
+  for event_data in event_data {
+    let Some(album_file_name) = event_data
+      .payload
+      .correlation_id
+      .as_deref()
+      .and_then(get_album_file_name_from_bandcamp_lookup_correlation_id)
+    else {
+      continue;
+    };
+    if let Event::FileParsed {
+      data: ParsedFileData::BandcampSearchResult(search_result),
+      ..
+    } = event_data.payload.event
+    {
+      if let Err(e) = bandcamp_lookup_interactor
+        .resolve(&album_file_name, &search_result)
+        .await
+      {
+        error!(
+          file_name = album_file_name.to_string(),
+          err = e.to_string(),
+          "Failed to resolve Bandcamp link"
+        );
+      }
+    }
+  }
+  Ok(())
+}
+
+pub fn build_bandcamp_lookup_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  if app_context.bandcamp_lookup_interactor.is_none() {
+    return Ok(vec![]);
+  }
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("resolve_bandcamp_links")
+    .topic(Topic::Parser)
+    .batch_size(50)
+    .app_context(app_context)
+    .grouping_strategy(GroupingStrategy::All)
+    .handler(group_event_handler!(resolve_bandcamp_links))
+    .build()?])
+}
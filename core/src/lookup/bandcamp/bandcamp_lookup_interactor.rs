@@ -0,0 +1,73 @@
+use super::bandcamp_lookup::{
+  bandcamp_lookup_correlation_id, bandcamp_search_file_name, find_bandcamp_album_match,
+};
+use crate::{
+  albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  crawler::crawler::{Crawler, QueuePushParameters},
+  files::file_metadata::file_name::FileName,
+  helpers::priority::Priority,
+  parser::parsed_file_data::ParsedBandcampSearchResult,
+};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+pub struct BandcampLookupInteractor {
+  crawler: Arc<Crawler>,
+  album_interactor: Arc<AlbumInteractor>,
+}
+
+impl BandcampLookupInteractor {
+  pub fn new(crawler: Arc<Crawler>, album_interactor: Arc<AlbumInteractor>) -> Self {
+    Self {
+      crawler,
+      album_interactor,
+    }
+  }
+
+  /**
+   * Crawls the Bandcamp search of each album without a link, the search result is matched once
+   * it's parsed
+   */
+  pub async fn enqueue_many(&self, albums: Vec<&AlbumReadModel>) -> Result<()> {
+    for album in albums
+      .into_iter()
+      .filter(|album| album.bandcamp_url.is_none())
+    {
+      self
+        .crawler
+        .enqueue_if_stale(QueuePushParameters {
+          file_name: bandcamp_search_file_name(album)?,
+          priority: Some(Priority::Low),
+          correlation_id: Some(bandcamp_lookup_correlation_id(&album.file_name)),
+        })
+        .await?;
+    }
+    Ok(())
+  }
+
+  #[instrument(skip(self, search_result), name = "BandcampLookupInteractor::resolve")]
+  pub async fn resolve(
+    &self,
+    file_name: &FileName,
+    search_result: &ParsedBandcampSearchResult,
+  ) -> Result<()> {
+    let mut album = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .ok_or_else(|| anyhow!("Album not found"))?;
+    let Some(result) = find_bandcamp_album_match(&album, &search_result.albums) else {
+      info!(
+        file_name = file_name.to_string(),
+        "No matching Bandcamp album"
+      );
+      return Ok(());
+    };
+    if album.bandcamp_url.as_ref() == Some(&result.url) {
+      return Ok(());
+    }
+    album.bandcamp_url = Some(result.url.clone());
+    self.album_interactor.put(album).await
+  }
+}
@@ -0,0 +1,3 @@
+pub mod bandcamp_lookup;
+pub mod bandcamp_lookup_event_subscribers;
+pub mod bandcamp_lookup_interactor;
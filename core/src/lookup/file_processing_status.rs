@@ -32,7 +32,7 @@ impl From<FileProcessingStatus> for proto::FileProcessingStatus {
 }
 
 impl FileProcessingStatus {
-  fn is_error(&self) -> bool {
+  pub fn is_error(&self) -> bool {
     matches!(
       self,
       FileProcessingStatus::CrawlFailed | FileProcessingStatus::FileParseFailed
@@ -1,9 +1,10 @@
 use super::super::file_processing_status::FileProcessingStatus;
 use crate::{
   files::file_metadata::file_name::{FileName, ListRootFileName},
+  helpers::priority::Priority,
   proto,
 };
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::collections::{HashMap, HashSet};
 
@@ -15,6 +16,7 @@ pub enum ListLookupStatus {
   Completed = 2,
   Failed = 3,
   Invalid = 4,
+  Expired = 5,
 }
 
 impl From<ListLookupStatus> for proto::ListLookupStatus {
@@ -25,10 +27,37 @@ impl From<ListLookupStatus> for proto::ListLookupStatus {
       ListLookupStatus::Completed => proto::ListLookupStatus::Completed,
       ListLookupStatus::Failed => proto::ListLookupStatus::Failed,
       ListLookupStatus::Invalid => proto::ListLookupStatus::Invalid,
+      ListLookupStatus::Expired => proto::ListLookupStatus::Expired,
     }
   }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListSegmentProgress {
+  pub file_name: FileName,
+  pub status: Option<FileProcessingStatus>,
+  pub album_count: u32,
+  pub resolved_album_count: u32,
+  pub failed_album_count: u32,
+}
+
+/**
+ * Where a lookup is at, segment by segment. An album is resolved once its read model is saved, and
+ * pending until it's resolved or has failed.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListLookupProgress {
+  pub status: ListLookupStatus,
+  pub segments_discovered: u32,
+  pub segments_crawled: u32,
+  pub segments_parsed: u32,
+  pub albums_discovered: u32,
+  pub albums_resolved: u32,
+  pub albums_failed: u32,
+  pub albums_pending: u32,
+  pub segments: Vec<ListSegmentProgress>,
+}
+
 pub struct ListLookup {
   pub root_file_name: ListRootFileName,
   pub segment_file_names: Vec<FileName>,
@@ -36,6 +65,11 @@ pub struct ListLookup {
   pub component_processing_statuses: HashMap<FileName, FileProcessingStatus>,
   pub last_run: Option<NaiveDateTime>,
   pub last_run_status: Option<ListLookupStatus>,
+  /**
+   * Crawl priority for the lookup's pages, segments are crawled first when unset
+   */
+  pub priority: Option<Priority>,
+  pub deadline: Option<NaiveDateTime>,
 }
 
 impl ListLookup {
@@ -47,10 +81,32 @@ impl ListLookup {
       component_processing_statuses: HashMap::new(),
       last_run: None,
       last_run_status: None,
+      priority: None,
+      deadline: None,
     }
   }
 
+  /**
+   * A lookup still underway once its deadline passes is expired
+   */
   pub fn status(&self) -> ListLookupStatus {
+    let status = self.progress_status();
+    let overdue = self
+      .deadline
+      .is_some_and(|deadline| deadline <= Utc::now().naive_utc());
+    if overdue
+      && matches!(
+        status,
+        ListLookupStatus::Started | ListLookupStatus::InProgress
+      )
+    {
+      ListLookupStatus::Expired
+    } else {
+      status
+    }
+  }
+
+  fn progress_status(&self) -> ListLookupStatus {
     if self.segment_file_names.is_empty() {
       return ListLookupStatus::Invalid;
     }
@@ -100,6 +156,10 @@ impl ListLookup {
     matches!(self.status(), ListLookupStatus::Completed)
   }
 
+  pub fn is_expired(&self) -> bool {
+    matches!(self.status(), ListLookupStatus::Expired)
+  }
+
   pub fn components(&self) -> Vec<FileName> {
     let mut components = HashSet::new();
     components.extend(self.segment_file_names.clone());
@@ -130,6 +190,146 @@ impl ListLookup {
       })
       .collect()
   }
+
+  fn component_status(&self, file_name: &FileName) -> Option<FileProcessingStatus> {
+    self.component_processing_statuses.get(file_name).copied()
+  }
+
+  fn segment_file_names_by_page(&self) -> Vec<FileName> {
+    let mut segment_file_names = self.segment_file_names.clone();
+    segment_file_names.sort_by_key(|file_name| {
+      file_name
+        .to_string()
+        .rsplit('/')
+        .next()
+        .and_then(|page_number| page_number.parse::<u32>().ok())
+    });
+    segment_file_names
+  }
+
+  /**
+   * Albums with a saved read model, in page order
+   */
+  pub fn resolved_albums(&self) -> Vec<FileName> {
+    let mut seen = HashSet::new();
+    self
+      .segment_file_names_by_page()
+      .iter()
+      .flat_map(|segment| {
+        self
+          .segment_albums
+          .get(segment)
+          .cloned()
+          .unwrap_or_default()
+      })
+      .filter(|album| {
+        self.component_status(album) == Some(FileProcessingStatus::ReadModelUpdated)
+          && seen.insert(album.clone())
+      })
+      .collect()
+  }
+
+  pub fn progress(&self) -> ListLookupProgress {
+    let segments = self
+      .segment_file_names_by_page()
+      .iter()
+      .map(|file_name| {
+        let albums = self
+          .segment_albums
+          .get(file_name)
+          .cloned()
+          .unwrap_or_default();
+        let album_statuses = albums
+          .iter()
+          .map(|album| self.component_status(album))
+          .collect::<Vec<_>>();
+        ListSegmentProgress {
+          status: self.component_status(file_name),
+          album_count: albums.len() as u32,
+          resolved_album_count: album_statuses
+            .iter()
+            .filter(|status| **status == Some(FileProcessingStatus::ReadModelUpdated))
+            .count() as u32,
+          failed_album_count: album_statuses
+            .iter()
+            .filter(|status| status.is_some_and(|status| status.is_error()))
+            .count() as u32,
+          file_name: file_name.clone(),
+        }
+      })
+      .collect::<Vec<_>>();
+    let albums = self
+      .segment_albums
+      .values()
+      .flatten()
+      .collect::<HashSet<_>>();
+    let albums_resolved = albums
+      .iter()
+      .filter(|album| self.component_status(album) == Some(FileProcessingStatus::ReadModelUpdated))
+      .count() as u32;
+    let albums_failed = albums
+      .iter()
+      .filter(|album| {
+        self
+          .component_status(album)
+          .is_some_and(|status| status.is_error())
+      })
+      .count() as u32;
+    ListLookupProgress {
+      status: self.status(),
+      segments_discovered: segments.len() as u32,
+      segments_crawled: segments
+        .iter()
+        .filter(|segment| {
+          segment.status.is_some_and(|status| {
+            !matches!(
+              status,
+              FileProcessingStatus::CrawlEnqueued | FileProcessingStatus::CrawlFailed
+            )
+          })
+        })
+        .count() as u32,
+      segments_parsed: segments
+        .iter()
+        .filter(|segment| self.segment_albums.contains_key(&segment.file_name))
+        .count() as u32,
+      albums_discovered: albums.len() as u32,
+      albums_resolved,
+      albums_failed,
+      albums_pending: albums.len() as u32 - albums_resolved - albums_failed,
+      segments,
+    }
+  }
+}
+
+impl From<ListSegmentProgress> for proto::ListLookupSegmentProgress {
+  fn from(val: ListSegmentProgress) -> Self {
+    Self {
+      file_name: val.file_name.to_string(),
+      status: val
+        .status
+        .map(|status| Into::<proto::FileProcessingStatus>::into(status) as i32),
+      album_count: val.album_count,
+      resolved_album_count: val.resolved_album_count,
+      failed_album_count: val.failed_album_count,
+    }
+  }
+}
+
+impl From<ListLookupProgress> for proto::ListLookupProgress {
+  fn from(val: ListLookupProgress) -> Self {
+    Self {
+      status: Into::<proto::ListLookupStatus>::into(val.status) as i32,
+      segments_discovered: val.segments_discovered,
+      segments_crawled: val.segments_crawled,
+      segments_parsed: val.segments_parsed,
+      albums_discovered: val.albums_discovered,
+      albums_resolved: val.albums_resolved,
+      albums_failed: val.albums_failed,
+      albums_pending: val.albums_pending,
+      segments: val.segments.into_iter().map(Into::into).collect(),
+    }
+  }
 }
 
 impl From<ListLookup> for proto::ListLookup {
@@ -161,6 +361,64 @@ impl From<ListLookup> for proto::ListLookup {
         })
         .collect(),
       last_run_at: val.last_run.map(|d| d.to_string()),
+      priority: val
+        .priority
+        .map(|priority| proto::Priority::from(priority).into()),
+      deadline: val.deadline.map(|d| d.to_string()),
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_progress() -> Result<()> {
+    let root_file_name =
+      ListRootFileName::try_from("list/Seab/theneedledrops-top-200-albums-of-the-2010s")?;
+    let first_segment = root_file_name.segment_file_name(1);
+    let second_segment = root_file_name.segment_file_name(2);
+    let resolved = FileName::try_from("release/album/bjork/vulnicura")?;
+    let failed = FileName::try_from("release/album/fka-twigs/lp1")?;
+    let pending = FileName::try_from("release/album/radiohead/a-moon-shaped-pool")?;
+    let lookup = ListLookup {
+      root_file_name,
+      segment_file_names: vec![first_segment.clone(), second_segment.clone()],
+      segment_albums: HashMap::from([(
+        first_segment.clone(),
+        vec![resolved.clone(), failed.clone(), pending.clone()],
+      )]),
+      component_processing_statuses: HashMap::from([
+        (first_segment, FileProcessingStatus::ReadModelUpdated),
+        (second_segment, FileProcessingStatus::CrawlEnqueued),
+        (resolved.clone(), FileProcessingStatus::ReadModelUpdated),
+        (failed, FileProcessingStatus::FileParseFailed),
+        (pending, FileProcessingStatus::FileSaved),
+      ]),
+      last_run: None,
+      last_run_status: None,
+      priority: None,
+      deadline: None,
+    };
+    let progress = lookup.progress();
+    assert_eq!(progress.segments_discovered, 2);
+    assert_eq!(progress.segments_crawled, 1);
+    assert_eq!(progress.segments_parsed, 1);
+    assert_eq!(progress.albums_discovered, 3);
+    assert_eq!(progress.albums_resolved, 1);
+    assert_eq!(progress.albums_failed, 1);
+    assert_eq!(progress.albums_pending, 1);
+    assert_eq!(progress.segments[0].resolved_album_count, 1);
+    assert_eq!(lookup.resolved_albums(), vec![resolved]);
+    assert_eq!(lookup.status(), ListLookupStatus::InProgress);
+
+    let expired = ListLookup {
+      deadline: Some(Utc::now().naive_utc() - chrono::TimeDelta::try_minutes(1).unwrap()),
+      ..lookup
+    };
+    assert_eq!(expired.status(), ListLookupStatus::Expired);
+    Ok(())
+  }
+}
@@ -1,10 +1,13 @@
 use super::{
   super::file_processing_status::FileProcessingStatusRepository,
-  list_lookup::ListLookup,
+  list_lookup::{ListLookup, ListLookupStatus},
   list_lookup_repository::{ListLookupRecord, ListLookupRepository, ListSegmentReadModel},
 };
 use crate::{
-  crawler::crawler::{Crawler, QueuePushParametersBuilder},
+  crawler::{
+    crawl_cost::CrawlCostReport,
+    crawler::{Crawler, QueuePushParametersBuilder},
+  },
   events::{
     event::{Event, EventPayloadBuilder, Topic},
     event_publisher::EventPublisher,
@@ -18,7 +21,7 @@ use crate::{
   sqlite::SqliteConnection,
 };
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use std::{
   collections::{HashMap, HashSet},
   sync::Arc,
@@ -148,6 +151,8 @@ impl ListLookupInteractor {
             .collect(),
           last_run: lookup_record.latest_run,
           last_run_status: Some(lookup_record.latest_status),
+          priority: lookup_record.priority,
+          deadline: lookup_record.deadline,
         },
       );
     }
@@ -165,7 +170,7 @@ impl ListLookupInteractor {
     let mut dormant_lookup_roots = HashSet::new();
 
     for lookup in lookups {
-      if lookup.is_complete() {
+      if lookup.is_complete() || lookup.is_expired() {
         outputs.insert(lookup.root_file_name.clone(), lookup);
         continue;
       }
@@ -189,14 +194,19 @@ impl ListLookupInteractor {
           dormant_components
             .iter()
             .flat_map(|(root_file_name, dormant_components)| {
+              let lookup_priority = dormant_lookups
+                .get(root_file_name)
+                .and_then(|lookup: &ListLookup| lookup.priority);
               dormant_components
                 .iter()
-                .map(|file_name| {
-                  let priority = if matches!(file_name.page_type(), PageType::ListSegment) {
-                    Priority::Express
-                  } else {
-                    Priority::High
-                  };
+                .map(move |file_name| {
+                  let priority = lookup_priority.unwrap_or(
+                    if matches!(file_name.page_type(), PageType::ListSegment) {
+                      Priority::Express
+                    } else {
+                      Priority::High
+                    },
+                  );
                   QueuePushParametersBuilder::default()
                     .file_name(file_name.clone())
                     .priority(priority)
@@ -320,10 +330,15 @@ impl ListLookupInteractor {
     )
   }
 
-  pub async fn put_lookup(&self, root_file_name: ListRootFileName) -> Result<ListLookup> {
+  pub async fn put_lookup(
+    &self,
+    root_file_name: ListRootFileName,
+    priority: Option<Priority>,
+    deadline: Option<NaiveDateTime>,
+  ) -> Result<ListLookup> {
     let record = self
       .list_lookup_repository
-      .put_lookup_record(root_file_name.clone())
+      .put_lookup_record(root_file_name.clone(), priority, deadline)
       .await?;
 
     let lookup = self
@@ -335,6 +350,72 @@ impl ListLookupInteractor {
     Ok(lookup)
   }
 
+  pub async fn find_lookup(&self, root_file_name: ListRootFileName) -> Result<Option<ListLookup>> {
+    let Some(record) = self
+      .list_lookup_repository
+      .find_lookup_record(root_file_name.clone())
+      .await?
+    else {
+      return Ok(None);
+    };
+    Ok(
+      self
+        .draft_many_lookups(vec![record])
+        .await?
+        .remove(&root_file_name),
+    )
+  }
+
+  /**
+   * The lookup as it stands and the crawls running it would enqueue, without persisting or
+   * enqueueing anything. Only segments already crawled are known, so the cost of a list that
+   * hasn't been crawled yet is a lower bound.
+   */
+  pub async fn estimate_lookup(
+    &self,
+    root_file_name: ListRootFileName,
+  ) -> Result<(ListLookup, CrawlCostReport)> {
+    let mut lookup = self
+      .draft_many_lookups(vec![ListLookupRecord {
+        root_file_name: root_file_name.clone(),
+        latest_status: ListLookupStatus::Started,
+        latest_run: None,
+        priority: None,
+        deadline: None,
+      }])
+      .await?
+      .remove(&root_file_name)
+      .ok_or(anyhow!("Unexpected error: Failed to draft lookup"))?;
+    if lookup.segment_file_names.is_empty() {
+      lookup = ListLookup::initialize(root_file_name);
+    }
+    let incomplete = lookup.segment_file_names.len() > lookup.segment_albums.len();
+    let dormant_components = if lookup.is_complete() {
+      vec![]
+    } else {
+      lookup.dormant_components()
+    };
+    let cost = self
+      .crawler
+      .estimate_cost(&dormant_components, incomplete)
+      .await?;
+    Ok((lookup, cost))
+  }
+
+  /**
+   * Runs the lookup again so a deadline that passed is recorded and published
+   */
+  pub async fn expire_lookup(&self, root_file_name: ListRootFileName) -> Result<()> {
+    if let Some(record) = self
+      .list_lookup_repository
+      .find_lookup_record(root_file_name)
+      .await?
+    {
+      self.run_lookups_records(vec![record]).await?;
+    }
+    Ok(())
+  }
+
   pub async fn delete_lookup(&self, root_file_name: ListRootFileName) -> Result<()> {
     self
       .list_lookup_repository
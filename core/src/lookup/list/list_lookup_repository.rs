@@ -3,13 +3,14 @@ use crate::{
     file_name::{FileName, ListRootFileName},
     page_type::PageType,
   },
+  helpers::priority::Priority,
   lookup::ListLookupStatus,
   parser::parsed_file_data::ParsedListSegment,
   sqlite::SqliteConnection,
 };
 use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
-use rusqlite::{params, types::Value};
+use rusqlite::{params, types::Value, OptionalExtension};
 use serde_derive::{Deserialize, Serialize};
 use std::{collections::HashMap, rc::Rc, sync::Arc};
 use tokio::try_join;
@@ -42,6 +43,8 @@ pub struct ListLookupRecord {
   pub root_file_name: ListRootFileName,
   pub latest_status: ListLookupStatus,
   pub latest_run: Option<NaiveDateTime>,
+  pub priority: Option<Priority>,
+  pub deadline: Option<NaiveDateTime>,
 }
 
 impl ListSegmentReadModel {
@@ -335,7 +338,7 @@ impl ListLookupRepository {
       .interact(move |conn| {
         let mut stmt = conn.prepare(
           "
-          SELECT root_file_name, latest_status, latest_run
+          SELECT root_file_name, latest_status, latest_run, priority, deadline
           FROM list_lookups
           WHERE root_file_name IN (
             SELECT DISTINCT l.root_file_name
@@ -352,6 +355,8 @@ impl ListLookupRepository {
               row.get::<_, String>(0)?,
               row.get::<_, u32>(1)?,
               row.get::<_, Option<NaiveDateTime>>(2)?,
+              row.get::<_, Option<u32>>(3)?,
+              row.get::<_, Option<NaiveDateTime>>(4)?,
             ))
           })?
           .filter_map(|r| r.ok())
@@ -364,13 +369,17 @@ impl ListLookupRepository {
       })
       .map_err(|e| anyhow!("Failed to find records {}", e))??
       .into_iter()
-      .map(|(root_file_name, latest_status, latest_run)| {
-        Ok(ListLookupRecord {
-          root_file_name: ListRootFileName::try_from(root_file_name)?,
-          latest_status: serde_json::from_str(&latest_status.to_string())?,
-          latest_run,
-        })
-      })
+      .map(
+        |(root_file_name, latest_status, latest_run, priority, deadline)| {
+          Ok(ListLookupRecord {
+            root_file_name: ListRootFileName::try_from(root_file_name)?,
+            latest_status: serde_json::from_str(&latest_status.to_string())?,
+            latest_run,
+            priority: priority.map(Priority::try_from).transpose()?,
+            deadline,
+          })
+        },
+      )
       .collect::<Result<Vec<ListLookupRecord>>>()?;
     Ok(result)
   }
@@ -390,7 +399,7 @@ impl ListLookupRepository {
       .interact(move |conn| {
         let mut stmt = conn.prepare(
           "
-          SELECT root_file_name, latest_status, latest_run
+          SELECT root_file_name, latest_status, latest_run, priority, deadline
           FROM list_lookups
           WHERE root_file_name IN (
             SELECT DISTINCT l.root_file_name
@@ -407,6 +416,8 @@ impl ListLookupRepository {
               row.get::<_, String>(0)?,
               row.get::<_, u32>(1)?,
               row.get::<_, Option<NaiveDateTime>>(2)?,
+              row.get::<_, Option<u32>>(3)?,
+              row.get::<_, Option<NaiveDateTime>>(4)?,
             ))
           })?
           .filter_map(|r| r.ok())
@@ -419,13 +430,17 @@ impl ListLookupRepository {
       })
       .map_err(|e| anyhow!("Failed to find records {}", e))??
       .into_iter()
-      .map(|(root_file_name, latest_status, latest_run)| {
-        Ok(ListLookupRecord {
-          root_file_name: ListRootFileName::try_from(root_file_name)?,
-          latest_status: serde_json::from_str(&latest_status.to_string())?,
-          latest_run,
-        })
-      })
+      .map(
+        |(root_file_name, latest_status, latest_run, priority, deadline)| {
+          Ok(ListLookupRecord {
+            root_file_name: ListRootFileName::try_from(root_file_name)?,
+            latest_status: serde_json::from_str(&latest_status.to_string())?,
+            latest_run,
+            priority: priority.map(Priority::try_from).transpose()?,
+            deadline,
+          })
+        },
+      )
       .collect::<Result<Vec<ListLookupRecord>>>()?;
     Ok(result)
   }
@@ -461,9 +476,60 @@ impl ListLookupRepository {
     Ok(results.into_iter().map(|(_, v)| v).collect())
   }
 
+  pub async fn find_lookup_record(
+    &self,
+    root_file_name: ListRootFileName,
+  ) -> Result<Option<ListLookupRecord>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let row = conn
+          .query_row(
+            "
+            SELECT latest_status, latest_run, priority, deadline
+            FROM list_lookups
+            WHERE root_file_name = ?
+            ",
+            params![root_file_name.to_string()],
+            |row| {
+              Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, Option<NaiveDateTime>>(1)?,
+                row.get::<_, Option<u32>>(2)?,
+                row.get::<_, Option<NaiveDateTime>>(3)?,
+              ))
+            },
+          )
+          .optional()?;
+        row
+          .map(|(latest_status, latest_run, priority, deadline)| {
+            Ok(ListLookupRecord {
+              latest_status: serde_json::from_str(&latest_status.to_string())?,
+              root_file_name,
+              latest_run,
+              priority: priority.map(Priority::try_from).transpose()?,
+              deadline,
+            })
+          })
+          .transpose()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find lookup");
+        anyhow!("Failed to find lookup")
+      })?
+  }
+
+  /**
+   * Creates the lookup, or replaces the priority and deadline of an existing one
+   */
   pub async fn put_lookup_record(
     &self,
     root_file_name: ListRootFileName,
+    priority: Option<Priority>,
+    deadline: Option<NaiveDateTime>,
   ) -> Result<ListLookupRecord> {
     self
       .sqlite_connection
@@ -472,12 +538,18 @@ impl ListLookupRepository {
       .interact(move |conn| {
         let (latest_status, latest_run) = conn.query_row(
           "
-          INSERT INTO list_lookups (root_file_name)
-          VALUES (?)
-          ON CONFLICT (root_file_name) DO UPDATE SET root_file_name = excluded.root_file_name
+          INSERT INTO list_lookups (root_file_name, priority, deadline)
+          VALUES (?, ?, ?)
+          ON CONFLICT (root_file_name) DO UPDATE SET
+            priority = excluded.priority,
+            deadline = excluded.deadline
           RETURNING latest_status, latest_run
           ",
-          params![root_file_name.to_string()],
+          params![
+            root_file_name.to_string(),
+            priority.map(|priority| priority as u32),
+            deadline
+          ],
           |row| {
             Ok((
               row.get::<_, u32>(0)?,
@@ -489,6 +561,8 @@ impl ListLookupRepository {
           latest_status: serde_json::from_str(&latest_status.to_string())?,
           root_file_name,
           latest_run,
+          priority,
+          deadline,
         })
       })
       .await
@@ -1,7 +1,11 @@
 use super::{
   album_search::album_search_lookup_event_subscribers::build_album_search_lookup_event_subscribers,
+  artist_ingestion::artist_ingestion_event_subscribers::build_artist_ingestion_event_subscribers,
+  bandcamp::bandcamp_lookup_event_subscribers::build_bandcamp_lookup_event_subscribers,
   file_processing_status::FileProcessingStatus,
   list::list_lookup_event_subscribers::build_list_lookup_event_subscribers,
+  lookup_progress::LookupProgressUpdate,
+  musicbrainz::musicbrainz_lookup_event_subscribers::build_musicbrainz_lookup_event_subscribers,
 };
 use crate::{
   context::ApplicationContext,
@@ -130,20 +134,52 @@ async fn update_file_processing_status(
   Ok(())
 }
 
+async fn broadcast_lookup_progress(
+  event_data: Vec<EventData>,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  for data in event_data {
+    if let Some(update) = LookupProgressUpdate::from_payload(&data.payload) {
+      app_context.lookup_progress_broadcaster.publish(update);
+    }
+  }
+  Ok(())
+}
+
 pub fn build_lookup_event_subscribers(
   app_context: Arc<ApplicationContext>,
 ) -> Result<Vec<EventSubscriber>> {
-  let mut subscribers = vec![EventSubscriberBuilder::default()
-    .id("update_file_processing_status")
-    .topic(Topic::All)
-    .batch_size(500)
-    .app_context(Arc::clone(&app_context))
-    .grouping_strategy(GroupingStrategy::All)
-    .handler(group_event_handler!(update_file_processing_status))
-    .build()?];
+  let mut subscribers = vec![
+    EventSubscriberBuilder::default()
+      .id("update_file_processing_status")
+      .topic(Topic::All)
+      .batch_size(500)
+      .app_context(Arc::clone(&app_context))
+      .grouping_strategy(GroupingStrategy::All)
+      .handler(group_event_handler!(update_file_processing_status))
+      .build()?,
+    EventSubscriberBuilder::default()
+      .id("broadcast_lookup_progress")
+      .topic(Topic::Lookup)
+      .batch_size(500)
+      .app_context(Arc::clone(&app_context))
+      .grouping_strategy(GroupingStrategy::All)
+      .handler(group_event_handler!(broadcast_lookup_progress))
+      .build()?,
+  ];
   subscribers.extend(build_album_search_lookup_event_subscribers(Arc::clone(
     &app_context,
   ))?);
-  subscribers.extend(build_list_lookup_event_subscribers(app_context)?);
+  subscribers.extend(build_list_lookup_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
+  subscribers.extend(build_artist_ingestion_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
+  subscribers.extend(build_bandcamp_lookup_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
+  subscribers.extend(build_musicbrainz_lookup_event_subscribers(app_context)?);
   Ok(subscribers)
 }
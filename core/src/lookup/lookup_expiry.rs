@@ -0,0 +1,95 @@
+use super::AlbumSearchLookupQuery;
+use crate::{
+  context::ApplicationContext,
+  files::file_metadata::file_name::ListRootFileName,
+  helpers::priority::Priority,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParameters, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum LookupExpiryTarget {
+  AlbumSearch(AlbumSearchLookupQuery),
+  List(ListRootFileName),
+  ArtistIngestion(String),
+}
+
+impl LookupExpiryTarget {
+  fn job_id(&self) -> String {
+    match self {
+      LookupExpiryTarget::AlbumSearch(query) => {
+        format!("expire_lookup:album_search:{}", query.to_encoded_string())
+      }
+      LookupExpiryTarget::List(root_file_name) => {
+        format!("expire_lookup:list:{}", root_file_name.to_string())
+      }
+      LookupExpiryTarget::ArtistIngestion(id) => {
+        format!("expire_lookup:artist_ingestion:{}", id)
+      }
+    }
+  }
+
+  /**
+   * A single run at the deadline. Rescheduling replaces the run, and a lookup that finished or had
+   * its deadline moved in the meantime is left alone when it comes around.
+   */
+  pub fn job_parameters(&self, deadline: NaiveDateTime) -> Result<JobParameters> {
+    Ok(
+      JobParametersBuilder::default()
+        .id(self.job_id())
+        .name(JobName::ExpireLookup)
+        .next_execution(deadline)
+        .payload(serde_json::to_vec(self)?)
+        .priority(Priority::High)
+        .build()?,
+    )
+  }
+}
+
+async fn expire_lookup(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let result = match job.payload::<LookupExpiryTarget>()? {
+    LookupExpiryTarget::AlbumSearch(query) => {
+      app_context
+        .lookup_interactor
+        .expire_album_search_lookup(&query)
+        .await
+    }
+    LookupExpiryTarget::List(root_file_name) => {
+      app_context
+        .lookup_interactor
+        .expire_list_lookup(root_file_name)
+        .await
+    }
+    LookupExpiryTarget::ArtistIngestion(id) => {
+      app_context
+        .lookup_interactor
+        .expire_artist_ingestion(&id)
+        .await
+    }
+  };
+  result.inspect_err(|e| error!(err = e.to_string(), "Failed to expire lookup"))
+}
+
+pub async fn setup_lookup_expiry_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::ExpireLookup)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(expire_lookup))
+        .build()?,
+    )
+    .await;
+
+  Ok(())
+}
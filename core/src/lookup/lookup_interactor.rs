@@ -5,30 +5,53 @@ use super::{
     },
     album_search_lookup_repository::{AggregatedStatus, AlbumSearchLookupRepository},
   },
+  artist_ingestion::{
+    artist_ingestion::{ArtistIngestion, ArtistIngestionProgress},
+    artist_ingestion_interactor::ArtistIngestionInteractor,
+  },
   file_processing_status::{FileProcessingStatus, FileProcessingStatusRepository},
   list::{
     list_lookup_interactor::ListLookupInteractor, list_lookup_repository::ListSegmentReadModel,
   },
-  ListLookup,
+  ListLookup, LookupExpiryTarget, LookupLane,
 };
 use crate::{
-  crawler::crawler::Crawler,
+  crawler::{crawl_cost::CrawlCostReport, crawler::Crawler},
   events::{
     event::{Event, EventPayloadBuilder, Topic},
     event_publisher::EventPublisher,
   },
-  files::file_metadata::file_name::{FileName, ListRootFileName},
-  helpers::{document_store::DocumentStore, key_value_store::KeyValueStore},
+  files::{
+    file_interactor::FileInteractor,
+    file_metadata::file_name::{FileName, ListRootFileName},
+  },
+  helpers::{document_store::DocumentStore, key_value_store::KeyValueStore, priority::Priority},
+  scheduler::scheduler::Scheduler,
   sqlite::SqliteConnection,
 };
 use anyhow::Result;
-use std::{collections::HashMap, sync::Arc};
+use chrono::{NaiveDateTime, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+use tracing::info;
+
+/**
+ * Album search lookups started at once by a batch, enough to keep the event subscriber busy
+ * without holding many sqlite writers
+ */
+const SEARCH_ALBUMS_CONCURRENCY: usize = 25;
 
 pub struct LookupInteractor {
   file_processing_status_repository: Arc<FileProcessingStatusRepository>,
   album_search_lookup_repository: AlbumSearchLookupRepository,
   event_publisher: Arc<EventPublisher>,
   list_lookup_interactor: ListLookupInteractor,
+  artist_ingestion_interactor: ArtistIngestionInteractor,
+  scheduler: Arc<Scheduler>,
+  crawler: Arc<Crawler>,
 }
 
 impl LookupInteractor {
@@ -38,18 +61,28 @@ impl LookupInteractor {
     event_publisher: Arc<EventPublisher>,
     kv: Arc<KeyValueStore>,
     crawler: Arc<Crawler>,
+    file_interactor: Arc<FileInteractor>,
+    scheduler: Arc<Scheduler>,
   ) -> Self {
     let file_processing_status_repository = Arc::new(FileProcessingStatusRepository::new(kv));
     Self {
-      album_search_lookup_repository: AlbumSearchLookupRepository::new(doc_store),
+      album_search_lookup_repository: AlbumSearchLookupRepository::new(Arc::clone(&doc_store)),
+      artist_ingestion_interactor: ArtistIngestionInteractor::new(
+        doc_store,
+        Arc::clone(&file_processing_status_repository),
+        file_interactor,
+        Arc::clone(&crawler),
+      ),
       file_processing_status_repository: Arc::clone(&file_processing_status_repository),
       event_publisher: Arc::clone(&event_publisher),
       list_lookup_interactor: ListLookupInteractor::new(
         file_processing_status_repository,
         sqlite_connection,
-        crawler,
+        Arc::clone(&crawler),
         event_publisher,
       ),
+      scheduler,
+      crawler,
     }
   }
 
@@ -71,15 +104,26 @@ impl LookupInteractor {
     self.album_search_lookup_repository.get(query).await
   }
 
-  pub async fn search_album(
+  async fn schedule_expiry(
     &self,
-    artist_name: String,
-    album_name: String,
-  ) -> Result<AlbumSearchLookup> {
-    let query = AlbumSearchLookupQuery::new(album_name, artist_name);
+    target: LookupExpiryTarget,
+    deadline: Option<NaiveDateTime>,
+  ) -> Result<()> {
+    if let Some(deadline) = deadline {
+      self.scheduler.put(target.job_parameters(deadline)?).await?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Starts a lookup for the query, or returns the one underway. A lookup underway takes the lane,
+   * priority and deadline of the request, and an expired one starts over.
+   */
+  pub async fn search_album(&self, query: AlbumSearchLookupQuery) -> Result<AlbumSearchLookup> {
+    let lane = query.lane();
     let lookup = self.album_search_lookup_repository.find(&query).await?;
     match lookup {
-      Some(AlbumSearchLookup::Started { .. }) | None => {
+      Some(AlbumSearchLookup::Started { .. }) | Some(AlbumSearchLookup::Expired { .. }) | None => {
         let lookup = AlbumSearchLookup::new(query);
         self.put_album_search_lookup(&lookup).await?;
         self
@@ -95,12 +139,113 @@ impl LookupInteractor {
               .build()?,
           )
           .await?;
+        self
+          .schedule_expiry(
+            LookupExpiryTarget::AlbumSearch(lookup.query().clone()),
+            lookup.query().deadline(),
+          )
+          .await?;
         Ok(lookup)
       }
+      Some(lookup) if !lookup.is_terminal() => {
+        let mut updated = lookup.clone().with_request_options(&query);
+        if lookup.lane() == LookupLane::Background && lane == LookupLane::Interactive {
+          updated = updated.with_lane(lane);
+        }
+        let current = lookup.query();
+        let next = updated.query();
+        if current.lane() == next.lane()
+          && current.priority() == next.priority()
+          && current.deadline() == next.deadline()
+        {
+          return Ok(lookup);
+        }
+        self.put_album_search_lookup(&updated).await?;
+        // Lower values run first
+        if (next.crawler_priority() as u32) < (current.crawler_priority() as u32) {
+          if let Some(file_name) = updated.crawling_file_name() {
+            self
+              .crawler
+              .raise_priority(&file_name, next.crawler_priority())
+              .await?;
+          }
+        }
+        if next.deadline() != current.deadline() {
+          self
+            .schedule_expiry(
+              LookupExpiryTarget::AlbumSearch(next.clone()),
+              next.deadline(),
+            )
+            .await?;
+        }
+        Ok(updated)
+      }
       Some(lookup) => Ok(lookup),
     }
   }
 
+  /**
+   * Starts or finds a lookup for each query, returned in query order. Queries for the same album
+   * share a lookup, taking the options of the first.
+   */
+  pub async fn search_albums(
+    &self,
+    queries: Vec<AlbumSearchLookupQuery>,
+  ) -> Result<Vec<AlbumSearchLookup>> {
+    let mut seen = HashSet::new();
+    let unique_queries = queries
+      .iter()
+      .filter(|query| seen.insert(*query))
+      .cloned()
+      .collect::<Vec<_>>();
+    let lookups = stream::iter(unique_queries)
+      .map(|query| async move {
+        let lookup = self.search_album(query.clone()).await?;
+        Ok::<_, anyhow::Error>((query, lookup))
+      })
+      .buffered(SEARCH_ALBUMS_CONCURRENCY)
+      .try_collect::<HashMap<_, _>>()
+      .await?;
+    Ok(
+      queries
+        .iter()
+        .filter_map(|query| lookups.get(query).cloned())
+        .collect(),
+    )
+  }
+
+  /**
+   * Marks the lookup expired if it's still underway past its deadline, which ends watches on it
+   */
+  pub async fn expire_album_search_lookup(&self, query: &AlbumSearchLookupQuery) -> Result<()> {
+    let Some(lookup) = self.album_search_lookup_repository.find(query).await? else {
+      return Ok(());
+    };
+    let now = Utc::now().naive_utc();
+    if !lookup.is_overdue(now) {
+      return Ok(());
+    }
+    info!(
+      status = lookup.status_string(),
+      "Album search lookup passed its deadline"
+    );
+    let correlation_id = get_album_search_correlation_id(lookup.query());
+    self
+      .event_publisher
+      .publish(
+        Topic::Lookup,
+        EventPayloadBuilder::default()
+          .key(correlation_id.clone())
+          .event(Event::LookupAlbumSearchUpdated {
+            lookup: lookup.expire(now),
+          })
+          .correlation_id(correlation_id)
+          .build()?,
+      )
+      .await?;
+    Ok(())
+  }
+
   pub async fn aggregate_statuses(&self) -> Result<Vec<AggregatedStatus>> {
     self
       .album_search_lookup_repository
@@ -151,8 +296,47 @@ impl LookupInteractor {
     Ok(())
   }
 
-  pub async fn put_list_lookup(&self, root_file_name: ListRootFileName) -> Result<ListLookup> {
-    self.list_lookup_interactor.put_lookup(root_file_name).await
+  pub async fn put_list_lookup(
+    &self,
+    root_file_name: ListRootFileName,
+    priority: Option<Priority>,
+    deadline: Option<NaiveDateTime>,
+  ) -> Result<ListLookup> {
+    let lookup = self
+      .list_lookup_interactor
+      .put_lookup(root_file_name.clone(), priority, deadline)
+      .await?;
+    self
+      .schedule_expiry(LookupExpiryTarget::List(root_file_name), deadline)
+      .await?;
+    Ok(lookup)
+  }
+
+  pub async fn expire_list_lookup(&self, root_file_name: ListRootFileName) -> Result<()> {
+    self
+      .list_lookup_interactor
+      .expire_lookup(root_file_name)
+      .await
+  }
+
+  pub async fn find_list_lookup(
+    &self,
+    root_file_name: ListRootFileName,
+  ) -> Result<Option<ListLookup>> {
+    self
+      .list_lookup_interactor
+      .find_lookup(root_file_name)
+      .await
+  }
+
+  pub async fn estimate_list_lookup(
+    &self,
+    root_file_name: ListRootFileName,
+  ) -> Result<(ListLookup, CrawlCostReport)> {
+    self
+      .list_lookup_interactor
+      .estimate_lookup(root_file_name)
+      .await
   }
 
   pub async fn delete_list_lookup(&self, root_file_name: ListRootFileName) -> Result<()> {
@@ -171,4 +355,56 @@ impl LookupInteractor {
       .run_lookups_containing_components(components)
       .await
   }
+
+  pub async fn start_artist_ingestion(
+    &self,
+    artist_file_names: Vec<FileName>,
+    max_album_crawls: Option<u32>,
+    blocklist: Vec<String>,
+  ) -> Result<(ArtistIngestion, ArtistIngestionProgress)> {
+    let (ingestion, progress) = self
+      .artist_ingestion_interactor
+      .start(artist_file_names, max_album_crawls, blocklist)
+      .await?;
+    if ingestion.active {
+      self
+        .schedule_expiry(
+          LookupExpiryTarget::ArtistIngestion(ingestion.id.clone()),
+          Some(ingestion.deadline()),
+        )
+        .await?;
+    }
+    Ok((ingestion, progress))
+  }
+
+  pub async fn find_artist_ingestion(
+    &self,
+    id: &str,
+  ) -> Result<Option<(ArtistIngestion, ArtistIngestionProgress)>> {
+    self.artist_ingestion_interactor.find(id).await
+  }
+
+  pub async fn fan_out_artist_ingestions(
+    &self,
+    discographies: HashMap<FileName, Vec<FileName>>,
+  ) -> Result<()> {
+    self
+      .artist_ingestion_interactor
+      .fan_out(discographies)
+      .await
+  }
+
+  pub async fn fail_artist_ingestion_artists(
+    &self,
+    artist_file_names: Vec<FileName>,
+  ) -> Result<()> {
+    self
+      .artist_ingestion_interactor
+      .fail_artists(artist_file_names)
+      .await
+  }
+
+  pub async fn expire_artist_ingestion(&self, id: &str) -> Result<()> {
+    self.artist_ingestion_interactor.expire(id).await
+  }
 }
@@ -0,0 +1,34 @@
+use crate::helpers::priority::Priority;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupLane {
+  /**
+   * Lookups started directly by a user, these should never wait behind bulk work
+   */
+  #[default]
+  Interactive,
+  /**
+   * Bulk lookups started by imports and other background processes
+   */
+  Background,
+}
+
+impl LookupLane {
+  pub fn crawler_priority(&self) -> Priority {
+    match self {
+      LookupLane::Interactive => Priority::High,
+      LookupLane::Background => Priority::Low,
+    }
+  }
+}
+
+impl ToString for LookupLane {
+  fn to_string(&self) -> String {
+    match self {
+      LookupLane::Interactive => "interactive".to_string(),
+      LookupLane::Background => "background".to_string(),
+    }
+  }
+}
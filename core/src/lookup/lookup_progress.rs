@@ -0,0 +1,167 @@
+use super::{AlbumSearchLookup, AlbumSearchLookupQuery, ListLookupStatus};
+use crate::{
+  events::event::{Event, EventPayload},
+  files::file_metadata::file_name::ListRootFileName,
+};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/**
+ * Updates are dropped for watchers that fall this far behind, rather than holding up the
+ * subscriber
+ */
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub enum LookupWatchTarget {
+  AlbumSearch(AlbumSearchLookupQuery),
+  List(ListRootFileName),
+  CorrelationId(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum LookupProgressUpdate {
+  AlbumSearch {
+    correlation_id: Option<String>,
+    lookup: AlbumSearchLookup,
+  },
+  List {
+    correlation_id: Option<String>,
+    root_file_name: ListRootFileName,
+    status: ListLookupStatus,
+  },
+}
+
+impl LookupProgressUpdate {
+  pub fn from_payload(payload: &EventPayload) -> Option<Self> {
+    match &payload.event {
+      Event::LookupAlbumSearchUpdated { lookup } => Some(LookupProgressUpdate::AlbumSearch {
+        correlation_id: payload.correlation_id.clone(),
+        lookup: lookup.clone(),
+      }),
+      Event::ListLookupStatusUpdated {
+        root_file_name,
+        status,
+      } => Some(LookupProgressUpdate::List {
+        correlation_id: payload.correlation_id.clone(),
+        root_file_name: root_file_name.clone(),
+        status: status.clone(),
+      }),
+      _ => None,
+    }
+  }
+
+  pub fn correlation_id(&self) -> Option<&String> {
+    match self {
+      LookupProgressUpdate::AlbumSearch { correlation_id, .. }
+      | LookupProgressUpdate::List { correlation_id, .. } => correlation_id.as_ref(),
+    }
+  }
+
+  pub fn status_string(&self) -> String {
+    match self {
+      LookupProgressUpdate::AlbumSearch { lookup, .. } => lookup.status_string(),
+      LookupProgressUpdate::List { status, .. } => format!("{:?}", status),
+    }
+  }
+
+  /**
+   * Whether the lookup is done, so a watch on it can end
+   */
+  pub fn is_terminal(&self) -> bool {
+    match self {
+      LookupProgressUpdate::AlbumSearch { lookup, .. } => lookup.is_terminal(),
+      LookupProgressUpdate::List { status, .. } => matches!(
+        status,
+        ListLookupStatus::Completed
+          | ListLookupStatus::Failed
+          | ListLookupStatus::Invalid
+          | ListLookupStatus::Expired
+      ),
+    }
+  }
+
+  pub fn matches(&self, target: &LookupWatchTarget) -> bool {
+    match (target, self) {
+      (LookupWatchTarget::AlbumSearch(query), LookupProgressUpdate::AlbumSearch { lookup, .. }) => {
+        query == lookup.query()
+      }
+      (
+        LookupWatchTarget::List(target_root_file_name),
+        LookupProgressUpdate::List { root_file_name, .. },
+      ) => target_root_file_name == root_file_name,
+      (LookupWatchTarget::CorrelationId(correlation_id), update) => {
+        update.correlation_id() == Some(correlation_id)
+      }
+      _ => false,
+    }
+  }
+}
+
+/**
+ * Fans lookup status transitions out to the RPC streams watching them. It's fed by an event
+ * subscriber, so only updates published while the server is running are seen.
+ */
+pub struct LookupProgressBroadcaster {
+  sender: Sender<LookupProgressUpdate>,
+}
+
+impl Default for LookupProgressBroadcaster {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl LookupProgressBroadcaster {
+  pub fn new() -> Self {
+    let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+    Self { sender }
+  }
+
+  pub fn publish(&self, update: LookupProgressUpdate) {
+    // Fails only when nobody is watching
+    let _ = self.sender.send(update);
+  }
+
+  pub fn subscribe(&self) -> Receiver<LookupProgressUpdate> {
+    self.sender.subscribe()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{events::event::EventPayloadBuilder, lookup::get_album_search_correlation_id};
+  use anyhow::Result;
+
+  #[test]
+  fn test_matches_watch_targets() -> Result<()> {
+    let query = AlbumSearchLookupQuery::new("Vulnicura".to_string(), "Björk".to_string());
+    let correlation_id = get_album_search_correlation_id(&query);
+    let payload = EventPayloadBuilder::default()
+      .key(correlation_id.clone())
+      .event(Event::LookupAlbumSearchUpdated {
+        lookup: AlbumSearchLookup::new(query.clone()),
+      })
+      .correlation_id(correlation_id.clone())
+      .build()?;
+    let update = LookupProgressUpdate::from_payload(&payload).unwrap();
+    assert!(update.matches(&LookupWatchTarget::AlbumSearch(query)));
+    assert!(update.matches(&LookupWatchTarget::CorrelationId(correlation_id)));
+    assert!(!update.matches(&LookupWatchTarget::AlbumSearch(
+      AlbumSearchLookupQuery::new("Homogenic".to_string(), "Björk".to_string())
+    )));
+    assert!(!update.is_terminal());
+
+    let root_file_name =
+      ListRootFileName::try_from("list/sunohara227/ethereal-sounds-of-the-internet".to_string())?;
+    let update = LookupProgressUpdate::List {
+      correlation_id: None,
+      root_file_name: root_file_name.clone(),
+      status: ListLookupStatus::Completed,
+    };
+    assert!(update.matches(&LookupWatchTarget::List(root_file_name)));
+    assert!(!update.matches(&LookupWatchTarget::CorrelationId("x".to_string())));
+    assert!(update.is_terminal());
+    Ok(())
+  }
+}
@@ -1,12 +1,64 @@
-use super::{AlbumSearchLookup, LookupInteractor};
+use super::{
+  get_album_search_correlation_id, list::list_lookup::ListLookupStatus, parse_artist_file_name,
+  AlbumSearchLookup, AlbumSearchLookupQuery, ArtistIngestion, ArtistIngestionProgress,
+  LookupInteractor, LookupLane, LookupProgressBroadcaster, LookupProgressUpdate, LookupWatchTarget,
+  MusicBrainzLookupInteractor,
+};
 use crate::{
-  albums::album_read_model::{AlbumReadModel, AlbumReadModelArtist},
+  albums::{
+    album_interactor::AlbumInteractor,
+    album_read_model::{AlbumReadModel, AlbumReadModelArtist},
+  },
   context::ApplicationContext,
-  files::file_metadata::file_name::ListRootFileName,
+  files::file_metadata::file_name::{FileName, ListRootFileName},
+  helpers::priority::Priority,
   proto,
 };
-use std::sync::Arc;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use futures::Stream;
+use std::{
+  collections::{HashMap, HashSet},
+  pin::Pin,
+  sync::Arc,
+  time::Duration,
+};
+use tokio::{sync::broadcast::error::RecvError, time::sleep};
 use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+use ulid::Ulid;
+
+const LIST_LOOKUP_RESULTS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_LOOKUP_ALBUMS_BATCH_SIZE: usize = 1000;
+
+fn deadline_from_ttl(ttl_seconds: Option<u32>) -> Result<Option<NaiveDateTime>, Status> {
+  ttl_seconds
+    .map(|ttl_seconds| {
+      if ttl_seconds == 0 {
+        return Err(Status::invalid_argument("ttl_seconds must be positive"));
+      }
+      Ok(Utc::now().naive_utc() + TimeDelta::try_seconds(ttl_seconds as i64).unwrap())
+    })
+    .transpose()
+}
+
+impl From<LookupLane> for proto::LookupLane {
+  fn from(val: LookupLane) -> Self {
+    match val {
+      LookupLane::Interactive => proto::LookupLane::Interactive,
+      LookupLane::Background => proto::LookupLane::Background,
+    }
+  }
+}
+
+impl From<proto::LookupLane> for LookupLane {
+  fn from(val: proto::LookupLane) -> Self {
+    match val {
+      proto::LookupLane::Interactive => LookupLane::Interactive,
+      proto::LookupLane::Background => LookupLane::Background,
+    }
+  }
+}
 
 impl From<AlbumSearchLookup> for proto::AlbumSearchLookup {
   fn from(val: AlbumSearchLookup) -> Self {
@@ -41,35 +93,156 @@ impl From<AlbumSearchLookup> for proto::AlbumSearchLookup {
         .into()
       }),
       status: val.status_string(),
+      lane: proto::LookupLane::from(val.lane()).into(),
+      priority: val
+        .query()
+        .priority()
+        .map(|priority| proto::Priority::from(priority).into()),
+      deadline: val.query().deadline().map(|date| date.to_string()),
     }
   }
 }
 
+impl From<LookupProgressUpdate> for proto::LookupProgressUpdate {
+  fn from(val: LookupProgressUpdate) -> Self {
+    let terminal = val.is_terminal();
+    let status = val.status_string();
+    match val {
+      LookupProgressUpdate::AlbumSearch {
+        correlation_id,
+        lookup,
+      } => proto::LookupProgressUpdate {
+        correlation_id,
+        status,
+        terminal,
+        lookup: Some(proto::lookup_progress_update::Lookup::AlbumSearch(
+          lookup.into(),
+        )),
+      },
+      LookupProgressUpdate::List {
+        correlation_id,
+        root_file_name,
+        status: list_status,
+      } => proto::LookupProgressUpdate {
+        correlation_id,
+        status,
+        terminal,
+        lookup: Some(proto::lookup_progress_update::Lookup::List(
+          proto::ListLookupStatusUpdatedEvent {
+            root_file_name: root_file_name.to_string(),
+            status: list_status as i32,
+          },
+        )),
+      },
+    }
+  }
+}
+
+fn artist_ingestion_to_proto(
+  ingestion: ArtistIngestion,
+  progress: ArtistIngestionProgress,
+) -> proto::ArtistIngestion {
+  let mut artist_statuses = progress.artist_statuses;
+  proto::ArtistIngestion {
+    id: ingestion.id,
+    status: proto::ArtistIngestionStatus::from(progress.status).into(),
+    artists: ingestion
+      .artist_file_names
+      .into_iter()
+      .map(|file_name| {
+        let album_file_names = ingestion.artist_albums.get(&file_name);
+        proto::ArtistIngestionArtist {
+          status: artist_statuses
+            .remove(&file_name)
+            .flatten()
+            .map(|status| proto::FileProcessingStatus::from(status).into()),
+          fanned_out: album_file_names.is_some(),
+          album_file_names: album_file_names
+            .map(|albums| albums.iter().map(|f| f.to_string()).collect())
+            .unwrap_or_default(),
+          file_name: file_name.to_string(),
+        }
+      })
+      .collect(),
+    album_processing_statuses: progress
+      .album_statuses
+      .into_iter()
+      .filter_map(|(file_name, status)| {
+        status.map(|status| {
+          (
+            file_name.to_string(),
+            proto::FileProcessingStatus::from(status).into(),
+          )
+        })
+      })
+      .collect(),
+    skipped_file_names: ingestion
+      .skipped_file_names
+      .into_iter()
+      .map(|f| f.to_string())
+      .collect(),
+    max_album_crawls: ingestion.max_album_crawls,
+    album_crawl_count: ingestion.crawled_album_file_names.len() as u32,
+    total: progress.total,
+    completed: progress.completed,
+    failed: progress.failed,
+    created_at: ingestion.created_at.to_string(),
+    updated_at: ingestion.updated_at.to_string(),
+  }
+}
+
 pub struct LookupService {
   lookup_interactor: Arc<LookupInteractor>,
+  album_interactor: Arc<AlbumInteractor>,
+  musicbrainz_lookup_interactor: Option<Arc<MusicBrainzLookupInteractor>>,
+  lookup_progress_broadcaster: Arc<LookupProgressBroadcaster>,
 }
 
 impl LookupService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       lookup_interactor: Arc::clone(&app_context.lookup_interactor),
+      album_interactor: Arc::clone(&app_context.album_interactor),
+      musicbrainz_lookup_interactor: app_context.musicbrainz_lookup_interactor.clone(),
+      lookup_progress_broadcaster: Arc::clone(&app_context.lookup_progress_broadcaster),
     }
   }
+
+  fn musicbrainz_lookup_interactor(&self) -> Result<&MusicBrainzLookupInteractor, Status> {
+    self
+      .musicbrainz_lookup_interactor
+      .as_deref()
+      .ok_or_else(|| Status::failed_precondition("MusicBrainz is not configured"))
+  }
 }
 
 #[tonic::async_trait]
 impl proto::LookupService for LookupService {
+  type WatchLookupStream =
+    Pin<Box<dyn Stream<Item = Result<proto::LookupProgressUpdate, Status>> + Send + 'static>>;
+  type WatchListLookupResultsStream =
+    Pin<Box<dyn Stream<Item = Result<proto::ListLookupPartialResult, Status>> + Send + 'static>>;
+  type LookupAlbumsStream =
+    Pin<Box<dyn Stream<Item = Result<proto::LookupAlbumsUpdate, Status>> + Send + 'static>>;
+
   async fn lookup_album(
     &self,
     request: Request<proto::LookupAlbumRequest>,
   ) -> Result<Response<proto::LookupAlbumReply>, Status> {
+    let request = request.into_inner();
+    let lane = LookupLane::from(request.lane());
+    let priority = request.priority.map(|_| Priority::from(request.priority()));
+    let deadline = deadline_from_ttl(request.ttl_seconds)?;
     let query = request
-      .into_inner()
       .query
       .ok_or(Status::invalid_argument("query is required"))?;
+    let query = AlbumSearchLookupQuery::new(query.album_name, query.artist_name)
+      .with_lane(lane)
+      .with_priority(priority)
+      .with_deadline(deadline);
     let lookup = self
       .lookup_interactor
-      .search_album(query.artist_name, query.album_name)
+      .search_album(query)
       .await
       .map_err(|e| Status::internal(e.to_string()))?;
     let reply = proto::LookupAlbumReply {
@@ -78,6 +251,110 @@ impl proto::LookupService for LookupService {
     Ok(Response::new(reply))
   }
 
+  async fn lookup_albums(
+    &self,
+    request: Request<proto::LookupAlbumsRequest>,
+  ) -> Result<Response<Self::LookupAlbumsStream>, Status> {
+    let request = request.into_inner();
+    if request.queries.is_empty() {
+      return Err(Status::invalid_argument("at least one query is required"));
+    }
+    if request.queries.len() > MAX_LOOKUP_ALBUMS_BATCH_SIZE {
+      return Err(Status::invalid_argument(format!(
+        "at most {} queries are allowed",
+        MAX_LOOKUP_ALBUMS_BATCH_SIZE
+      )));
+    }
+    let lane = LookupLane::from(request.lane());
+    let priority = request.priority.map(|_| Priority::from(request.priority()));
+    let deadline = deadline_from_ttl(request.ttl_seconds)?;
+    let queries = request
+      .queries
+      .into_iter()
+      .map(|query| {
+        AlbumSearchLookupQuery::new(query.album_name, query.artist_name)
+          .with_lane(lane)
+          .with_priority(priority)
+          .with_deadline(deadline)
+      })
+      .collect::<Vec<_>>();
+    let group_id = format!("lookup_group:{}", Ulid::new().to_string());
+    info!(
+      group_id,
+      count = queries.len(),
+      "Starting album search lookup batch"
+    );
+
+    // Subscribed before the lookups start, so no transition in between is missed
+    let mut receiver = self.lookup_progress_broadcaster.subscribe();
+    let lookups = self
+      .lookup_interactor
+      .search_albums(queries.clone())
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let mut indices = HashMap::<AlbumSearchLookupQuery, Vec<u32>>::new();
+    for (index, query) in queries.iter().enumerate() {
+      indices.entry(query.clone()).or_default().push(index as u32);
+    }
+    let total = queries.len() as u32;
+
+    let output_stream = async_stream::stream! {
+      let mut completed = 0;
+      let mut pending = HashSet::new();
+      let mut initial = HashSet::new();
+      for lookup in lookups {
+        if !initial.insert(lookup.query().clone()) {
+          continue;
+        }
+        if lookup.is_terminal() {
+          completed += indices[lookup.query()].len() as u32;
+        } else {
+          pending.insert(lookup.query().clone());
+        }
+        for index in &indices[lookup.query()] {
+          yield Ok::<_, Status>(proto::LookupAlbumsUpdate {
+            group_id: group_id.clone(),
+            index: *index,
+            terminal: lookup.is_terminal(),
+            completed,
+            total,
+            lookup: Some(lookup.clone().into()),
+          });
+        }
+      }
+      while !pending.is_empty() {
+        match receiver.recv().await {
+          Ok(LookupProgressUpdate::AlbumSearch { lookup, .. })
+            if pending.contains(lookup.query()) =>
+          {
+            if lookup.is_terminal() {
+              pending.remove(lookup.query());
+              completed += indices[lookup.query()].len() as u32;
+            }
+            for index in &indices[lookup.query()] {
+              yield Ok::<_, Status>(proto::LookupAlbumsUpdate {
+                group_id: group_id.clone(),
+                index: *index,
+                terminal: lookup.is_terminal(),
+                completed,
+                total,
+                lookup: Some(lookup.clone().into()),
+              });
+            }
+          }
+          Ok(_) => {}
+          Err(RecvError::Lagged(skipped)) => {
+            warn!(skipped, "Lookup batch watch fell behind, skipping updates");
+          }
+          Err(RecvError::Closed) => break,
+        }
+      }
+    };
+    Ok(Response::new(
+      Box::pin(output_stream) as Self::LookupAlbumsStream
+    ))
+  }
+
   async fn get_aggregated_album_search_statuses(
     &self,
     _request: Request<()>,
@@ -103,15 +380,30 @@ impl proto::LookupService for LookupService {
     &self,
     request: Request<proto::PutListLookupRequest>,
   ) -> Result<Response<proto::PutListLookupReply>, Status> {
-    let root_file_name = ListRootFileName::try_from(request.into_inner().file_name)
+    let request = request.into_inner();
+    let root_file_name = ListRootFileName::try_from(request.file_name)
       .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    if request.dry_run {
+      let (lookup, cost) = self
+        .lookup_interactor
+        .estimate_list_lookup(root_file_name)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+      return Ok(Response::new(proto::PutListLookupReply {
+        lookup: Some(lookup.into()),
+        cost: Some(cost.into()),
+      }));
+    }
+    let priority = request.priority.map(|_| Priority::from(request.priority()));
+    let deadline = deadline_from_ttl(request.ttl_seconds)?;
     let lookup = self
       .lookup_interactor
-      .put_list_lookup(root_file_name)
+      .put_list_lookup(root_file_name, priority, deadline)
       .await
       .map_err(|e| Status::internal(e.to_string()))?;
     let reply = proto::PutListLookupReply {
       lookup: Some(lookup.into()),
+      cost: None,
     };
     Ok(Response::new(reply))
   }
@@ -129,4 +421,244 @@ impl proto::LookupService for LookupService {
       .map_err(|e| Status::internal(e.to_string()))?;
     Ok(Response::new(()))
   }
+
+  async fn get_list_lookup_progress(
+    &self,
+    request: Request<proto::GetListLookupProgressRequest>,
+  ) -> Result<Response<proto::GetListLookupProgressReply>, Status> {
+    let root_file_name = ListRootFileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    let lookup = self
+      .lookup_interactor
+      .find_list_lookup(root_file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or_else(|| Status::not_found("list lookup not found"))?;
+    Ok(Response::new(proto::GetListLookupProgressReply {
+      progress: Some(lookup.progress().into()),
+    }))
+  }
+
+  /**
+   * Polls a list lookup and streams its progress along with albums resolved since the last result.
+   * Results are only sent when something changed, and the stream ends once the lookup is done.
+   */
+  async fn watch_list_lookup_results(
+    &self,
+    request: Request<proto::WatchListLookupResultsRequest>,
+  ) -> Result<Response<Self::WatchListLookupResultsStream>, Status> {
+    let root_file_name = ListRootFileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    let lookup_interactor = Arc::clone(&self.lookup_interactor);
+    let album_interactor = Arc::clone(&self.album_interactor);
+    if lookup_interactor
+      .find_list_lookup(root_file_name.clone())
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .is_none()
+    {
+      return Err(Status::not_found("list lookup not found"));
+    }
+    let output_stream = async_stream::stream! {
+      let mut sent_albums = HashSet::new();
+      let mut last_progress = None;
+      loop {
+        let lookup = match lookup_interactor.find_list_lookup(root_file_name.clone()).await {
+          Ok(Some(lookup)) => lookup,
+          Ok(None) => {
+            yield Err(Status::not_found("list lookup not found"));
+            break;
+          }
+          Err(e) => {
+            yield Err(Status::internal(e.to_string()));
+            break;
+          }
+        };
+        let progress = lookup.progress();
+        let new_albums = lookup
+          .resolved_albums()
+          .into_iter()
+          .filter(|file_name| !sent_albums.contains(file_name))
+          .collect::<Vec<_>>();
+        if !new_albums.is_empty() || last_progress.as_ref() != Some(&progress) {
+          let mut found = match album_interactor.find_many(new_albums.clone()).await {
+            Ok(found) => found,
+            Err(e) => {
+              yield Err(Status::internal(e.to_string()));
+              break;
+            }
+          };
+          // Albums whose read model can't be found yet are picked up on a later poll
+          let albums = new_albums
+            .into_iter()
+            .filter_map(|file_name| found.remove(&file_name))
+            .collect::<Vec<_>>();
+          sent_albums.extend(albums.iter().map(|album| album.file_name.clone()));
+          yield Ok(proto::ListLookupPartialResult {
+            progress: Some(progress.clone().into()),
+            albums: albums.into_iter().map(Into::into).collect(),
+          });
+        }
+        let done = matches!(
+          progress.status,
+          ListLookupStatus::Completed
+            | ListLookupStatus::Failed
+            | ListLookupStatus::Invalid
+            | ListLookupStatus::Expired
+        );
+        last_progress = Some(progress);
+        if done {
+          break;
+        }
+        sleep(LIST_LOOKUP_RESULTS_POLL_INTERVAL).await;
+      }
+    };
+    Ok(Response::new(
+      Box::pin(output_stream) as Self::WatchListLookupResultsStream
+    ))
+  }
+
+  async fn start_artist_ingestion(
+    &self,
+    request: Request<proto::StartArtistIngestionRequest>,
+  ) -> Result<Response<proto::ArtistIngestionReply>, Status> {
+    let request = request.into_inner();
+    let artist_file_names = request
+      .artists
+      .iter()
+      .map(|artist| parse_artist_file_name(artist))
+      .collect::<anyhow::Result<Vec<_>>>()
+      .map_err(|e| Status::invalid_argument(format!("invalid artist: {}", e)))?;
+    if artist_file_names.is_empty() {
+      return Err(Status::invalid_argument("at least one artist is required"));
+    }
+    let (ingestion, progress) = self
+      .lookup_interactor
+      .start_artist_ingestion(
+        artist_file_names,
+        request.max_album_crawls,
+        request.blocklist,
+      )
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ArtistIngestionReply {
+      ingestion: Some(artist_ingestion_to_proto(ingestion, progress)),
+    }))
+  }
+
+  async fn get_artist_ingestion(
+    &self,
+    request: Request<proto::GetArtistIngestionRequest>,
+  ) -> Result<Response<proto::ArtistIngestionReply>, Status> {
+    let (ingestion, progress) = self
+      .lookup_interactor
+      .find_artist_ingestion(&request.into_inner().id)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or_else(|| Status::not_found("artist ingestion not found"))?;
+    Ok(Response::new(proto::ArtistIngestionReply {
+      ingestion: Some(artist_ingestion_to_proto(ingestion, progress)),
+    }))
+  }
+
+  async fn lookup_music_brainz_id(
+    &self,
+    request: Request<proto::LookupMusicBrainzIdRequest>,
+  ) -> Result<Response<proto::LookupMusicBrainzIdReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    let musicbrainz_id = self
+      .musicbrainz_lookup_interactor()?
+      .lookup(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::LookupMusicBrainzIdReply {
+      musicbrainz_id,
+    }))
+  }
+
+  async fn enqueue_music_brainz_lookups(
+    &self,
+    request: Request<proto::EnqueueMusicBrainzLookupsRequest>,
+  ) -> Result<Response<()>, Status> {
+    let file_names = request
+      .into_inner()
+      .file_names
+      .into_iter()
+      .map(FileName::try_from)
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| Status::invalid_argument(format!("invalid file name: {}", e.to_string())))?;
+    self
+      .musicbrainz_lookup_interactor()?
+      .enqueue_many(file_names)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  /**
+   * Streams status transitions of a lookup as they happen. Album search watches start with the
+   * lookup's current state, and album search and list watches end once the lookup is done.
+   */
+  async fn watch_lookup(
+    &self,
+    request: Request<proto::WatchLookupRequest>,
+  ) -> Result<Response<Self::WatchLookupStream>, Status> {
+    let target =
+      match request.into_inner().target {
+        Some(proto::watch_lookup_request::Target::AlbumSearch(query)) => {
+          LookupWatchTarget::AlbumSearch(AlbumSearchLookupQuery::new(
+            query.album_name,
+            query.artist_name,
+          ))
+        }
+        Some(proto::watch_lookup_request::Target::ListRootFileName(file_name)) => {
+          LookupWatchTarget::List(ListRootFileName::try_from(file_name).map_err(|e| {
+            Status::invalid_argument(format!("invalid file name: {}", e.to_string()))
+          })?)
+        }
+        Some(proto::watch_lookup_request::Target::CorrelationId(correlation_id)) => {
+          LookupWatchTarget::CorrelationId(correlation_id)
+        }
+        None => return Err(Status::invalid_argument("target is required")),
+      };
+    // Subscribed before reading the current state, so no transition in between is missed
+    let mut receiver = self.lookup_progress_broadcaster.subscribe();
+    let current = match &target {
+      LookupWatchTarget::AlbumSearch(query) => self
+        .lookup_interactor
+        .find_album_search_lookup(query)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map(|lookup| LookupProgressUpdate::AlbumSearch {
+          correlation_id: Some(get_album_search_correlation_id(query)),
+          lookup,
+        }),
+      _ => None,
+    };
+    let ends_when_done = !matches!(target, LookupWatchTarget::CorrelationId(_));
+    let output_stream = async_stream::stream! {
+      let mut done = false;
+      if let Some(update) = current {
+        done = ends_when_done && update.is_terminal();
+        yield Ok::<_, Status>(proto::LookupProgressUpdate::from(update));
+      }
+      while !done {
+        match receiver.recv().await {
+          Ok(update) if update.matches(&target) => {
+            done = ends_when_done && update.is_terminal();
+            yield Ok::<_, Status>(proto::LookupProgressUpdate::from(update));
+          }
+          Ok(_) => {}
+          Err(RecvError::Lagged(skipped)) => {
+            warn!(skipped, "Lookup watch fell behind, skipping updates");
+          }
+          Err(RecvError::Closed) => break,
+        }
+      }
+    };
+    Ok(Response::new(
+      Box::pin(output_stream) as Self::WatchLookupStream
+    ))
+  }
 }
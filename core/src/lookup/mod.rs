@@ -1,12 +1,25 @@
 mod album_search;
+mod artist_ingestion;
+mod bandcamp;
 mod file_processing_status;
 mod list;
 mod lookup_event_subscribers;
+mod lookup_expiry;
 mod lookup_interactor;
+mod lookup_lane;
+mod lookup_progress;
 mod lookup_service;
+mod musicbrainz;
 
 pub use album_search::album_search_lookup::*;
+pub use artist_ingestion::artist_ingestion::*;
+pub use bandcamp::bandcamp_lookup_interactor::*;
 pub use list::list_lookup::*;
 pub use lookup_event_subscribers::*;
+pub use lookup_expiry::*;
 pub use lookup_interactor::*;
+pub use lookup_lane::*;
+pub use lookup_progress::*;
 pub use lookup_service::*;
+pub use musicbrainz::musicbrainz_lookup_interactor::*;
+pub use musicbrainz::musicbrainz_lookup_jobs::*;
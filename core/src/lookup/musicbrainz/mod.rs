@@ -0,0 +1,5 @@
+pub mod musicbrainz_client;
+pub mod musicbrainz_lookup;
+pub mod musicbrainz_lookup_event_subscribers;
+pub mod musicbrainz_lookup_interactor;
+pub mod musicbrainz_lookup_jobs;
@@ -0,0 +1,141 @@
+use crate::settings::MusicBrainzSettings;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+lazy_static! {
+  // MusicBrainz allows one request per second per client
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(1u32)));
+}
+
+const API_URL: &str = "https://musicbrainz.org/ws/2";
+const SEARCH_LIMIT: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MusicBrainzReleaseGroup {
+  pub id: String,
+  pub title: String,
+  pub artist_names: Vec<String>,
+  pub first_release_date: Option<NaiveDate>,
+  pub primary_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseGroupSearchResponse {
+  #[serde(rename = "release-groups")]
+  release_groups: Vec<ReleaseGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseGroup {
+  id: String,
+  title: String,
+  #[serde(rename = "first-release-date")]
+  first_release_date: Option<String>,
+  #[serde(rename = "primary-type")]
+  primary_type: Option<String>,
+  #[serde(rename = "artist-credit", default)]
+  artist_credit: Vec<ArtistCredit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtistCredit {
+  artist: ArtistCreditArtist,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtistCreditArtist {
+  name: String,
+}
+
+/**
+ * Release dates come as YYYY, YYYY-MM or YYYY-MM-DD, partial dates are pinned to the first
+ */
+fn parse_release_date(value: &str) -> Option<NaiveDate> {
+  let mut parts = value.split('-');
+  let year = parts.next()?.parse().ok()?;
+  let month = parts.next().map_or(Some(1), |month| month.parse().ok())?;
+  let day = parts.next().map_or(Some(1), |day| day.parse().ok())?;
+  NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn escape_query_value(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub struct MusicBrainzClient {
+  client: Client,
+  settings: MusicBrainzSettings,
+}
+
+impl MusicBrainzClient {
+  pub fn new(settings: MusicBrainzSettings) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+    }
+  }
+
+  pub fn settings(&self) -> &MusicBrainzSettings {
+    &self.settings
+  }
+
+  pub async fn search_release_groups(
+    &self,
+    title: &str,
+    artist_name: &str,
+  ) -> Result<Vec<MusicBrainzReleaseGroup>> {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(100)))
+      .await;
+    let query = format!(
+      "releasegroup:\"{}\" AND artist:\"{}\"",
+      escape_query_value(title),
+      escape_query_value(artist_name)
+    );
+    let response = self
+      .client
+      .get(format!("{}/release-group", API_URL))
+      .header("User-Agent", &self.settings.user_agent)
+      .query(&[
+        ("query", query),
+        ("limit", SEARCH_LIMIT.to_string()),
+        ("fmt", "json".to_string()),
+      ])
+      .send()
+      .await?;
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+      return Err(anyhow!("MusicBrainz API rate limit exceeded"));
+    }
+    let response = response
+      .error_for_status()?
+      .json::<ReleaseGroupSearchResponse>()
+      .await?;
+    Ok(
+      response
+        .release_groups
+        .into_iter()
+        .map(|release_group| MusicBrainzReleaseGroup {
+          id: release_group.id,
+          title: release_group.title,
+          artist_names: release_group
+            .artist_credit
+            .into_iter()
+            .map(|credit| credit.artist.name)
+            .collect(),
+          first_release_date: release_group
+            .first_release_date
+            .as_deref()
+            .and_then(parse_release_date),
+          primary_type: release_group.primary_type,
+        })
+        .collect(),
+    )
+  }
+}
@@ -0,0 +1,113 @@
+use super::musicbrainz_client::MusicBrainzReleaseGroup;
+use crate::albums::album_read_model::AlbumReadModel;
+use chrono::Datelike;
+use strsim::jaro_winkler;
+use unidecode::unidecode;
+
+const MIN_TITLE_SIMILARITY: f64 = 0.9;
+/**
+ * Reissues and regional releases routinely shift the first release date by a year
+ */
+const MAX_YEAR_DIFFERENCE: i32 = 1;
+
+fn normalize(value: &str) -> String {
+  unidecode(value).to_lowercase().trim().to_string()
+}
+
+fn year_difference(album: &AlbumReadModel, candidate: &MusicBrainzReleaseGroup) -> Option<i32> {
+  match (album.release_date, candidate.first_release_date) {
+    (Some(release_date), Some(first_release_date)) => {
+      Some((release_date.year() - first_release_date.year()).abs())
+    }
+    _ => None,
+  }
+}
+
+/**
+ * The release group that is the same album: a close title, a shared artist, and a release year
+ * within a year when both sides know it. The closest title wins, then the closest year.
+ */
+pub fn find_release_group_match<'a>(
+  album: &AlbumReadModel,
+  candidates: &'a [MusicBrainzReleaseGroup],
+) -> Option<&'a MusicBrainzReleaseGroup> {
+  let album_name = normalize(&album.name);
+  let artist_names = album
+    .artists
+    .iter()
+    .map(|artist| normalize(&artist.name))
+    .collect::<Vec<_>>();
+  candidates
+    .iter()
+    .filter_map(|candidate| {
+      let title_similarity = jaro_winkler(&album_name, &normalize(&candidate.title));
+      let shares_artist = candidate
+        .artist_names
+        .iter()
+        .any(|artist_name| artist_names.contains(&normalize(artist_name)));
+      let years_apart = year_difference(album, candidate);
+      let year_matches = years_apart.map_or(true, |diff| diff <= MAX_YEAR_DIFFERENCE);
+      (title_similarity >= MIN_TITLE_SIMILARITY && shares_artist && year_matches).then_some((
+        candidate,
+        title_similarity,
+        years_apart.unwrap_or(MAX_YEAR_DIFFERENCE),
+      ))
+    })
+    .max_by(
+      |(_, a_similarity, a_year_diff), (_, b_similarity, b_year_diff)| {
+        a_similarity
+          .total_cmp(b_similarity)
+          .then_with(|| b_year_diff.cmp(a_year_diff))
+      },
+    )
+    .map(|(candidate, _, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    albums::album_read_model::AlbumReadModelArtist, files::file_metadata::file_name::FileName,
+  };
+  use anyhow::Result;
+  use chrono::NaiveDate;
+
+  fn release_group(id: &str, title: &str, artist: &str, year: i32) -> MusicBrainzReleaseGroup {
+    MusicBrainzReleaseGroup {
+      id: id.to_string(),
+      title: title.to_string(),
+      artist_names: vec![artist.to_string()],
+      first_release_date: NaiveDate::from_ymd_opt(year, 1, 1),
+      primary_type: Some("Album".to_string()),
+    }
+  }
+
+  #[test]
+  fn test_find_release_group_match() -> Result<()> {
+    let album = AlbumReadModel {
+      name: "Vulnicura".to_string(),
+      file_name: FileName::try_from("release/album/bjork/vulnicura")?,
+      artists: vec![AlbumReadModelArtist {
+        name: "Björk".to_string(),
+        file_name: FileName::try_from("artist/bjork")?,
+      }],
+      release_date: NaiveDate::from_ymd_opt(2015, 1, 20),
+      ..Default::default()
+    };
+
+    let candidates = vec![
+      release_group("live", "Vulnicura Live", "Björk", 2017),
+      release_group("other-artist", "Vulnicura", "Bjork Tribute Band", 2015),
+      release_group("reissue", "Vulnicura", "Bjork", 2016),
+      release_group("original", "Vulnicura", "Björk", 2015),
+    ];
+    assert_eq!(
+      find_release_group_match(&album, &candidates).map(|candidate| candidate.id.as_str()),
+      Some("original")
+    );
+
+    let candidates = vec![release_group("too-late", "Vulnicura", "Björk", 2019)];
+    assert_eq!(find_release_group_match(&album, &candidates), None);
+    Ok(())
+  }
+}
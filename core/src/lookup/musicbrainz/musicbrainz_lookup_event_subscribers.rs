@@ -0,0 +1,69 @@
+use crate::{
+  context::ApplicationContext,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+      GroupingStrategy,
+    },
+  },
+  group_event_handler,
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+async fn enqueue_musicbrainz_lookups(
+  event_data: Vec<EventData>,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  let Some(musicbrainz_lookup_interactor) = app_context.musicbrainz_lookup_interactor.as_ref()
+  else {
+    return Ok(());
+  };
+  let file_names = event_data
+    .into_iter()
+    .filter_map(|event_data| match event_data.payload.event {
+      Event::AlbumSaved { file_name } => Some(file_name),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  if file_names.is_empty() {
+    return Ok(());
+  }
+
+  let unresolved = app_context
+    .album_interactor
+    .find_many(file_names)
+    .await?
+    .into_values()
+    .filter(|album| album.musicbrainz_id.is_none())
+    .map(|album| album.file_name)
+    .collect::<Vec<_>>();
+  if !unresolved.is_empty() {
+    musicbrainz_lookup_interactor
+      .enqueue_many(unresolved)
+      .await?;
+  }
+  Ok(())
+}
+
+pub fn build_musicbrainz_lookup_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  let auto_lookup = app_context
+    .musicbrainz_lookup_interactor
+    .as_ref()
+    .is_some_and(|interactor| interactor.auto_lookup());
+  if !auto_lookup {
+    return Ok(vec![]);
+  }
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("enqueue_musicbrainz_lookups")
+    .topic(Topic::Album)
+    .batch_size(250)
+    .app_context(app_context)
+    .grouping_strategy(GroupingStrategy::All)
+    .handler(group_event_handler!(enqueue_musicbrainz_lookups))
+    .build()?])
+}
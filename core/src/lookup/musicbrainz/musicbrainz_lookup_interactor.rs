@@ -0,0 +1,94 @@
+use super::{musicbrainz_client::MusicBrainzClient, musicbrainz_lookup::find_release_group_match};
+use crate::{
+  albums::album_interactor::AlbumInteractor,
+  files::file_metadata::file_name::FileName,
+  helpers::priority::Priority,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobParametersBuilder, Scheduler},
+  },
+  settings::MusicBrainzSettings,
+};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+pub struct MusicBrainzLookupInteractor {
+  client: MusicBrainzClient,
+  album_interactor: Arc<AlbumInteractor>,
+  scheduler: Arc<Scheduler>,
+}
+
+impl MusicBrainzLookupInteractor {
+  pub fn new(
+    settings: MusicBrainzSettings,
+    album_interactor: Arc<AlbumInteractor>,
+    scheduler: Arc<Scheduler>,
+  ) -> Self {
+    Self {
+      client: MusicBrainzClient::new(settings),
+      album_interactor,
+      scheduler,
+    }
+  }
+
+  pub fn auto_lookup(&self) -> bool {
+    self.client.settings().auto_lookup
+  }
+
+  /**
+   * Resolves the album's release group MBID and saves it on the album. Albums that already have
+   * one are returned as is.
+   */
+  #[instrument(skip(self), name = "MusicBrainzLookupInteractor::lookup")]
+  pub async fn lookup(&self, file_name: &FileName) -> Result<Option<String>> {
+    let mut album = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .ok_or_else(|| anyhow!("Album not found"))?;
+    if album.musicbrainz_id.is_some() {
+      return Ok(album.musicbrainz_id);
+    }
+    let Some(artist) = album.artists.first() else {
+      return Ok(None);
+    };
+    let candidates = self
+      .client
+      .search_release_groups(&album.name, &artist.name)
+      .await?;
+    let Some(release_group) = find_release_group_match(&album, &candidates) else {
+      info!(
+        file_name = file_name.to_string(),
+        "No matching MusicBrainz release group"
+      );
+      return Ok(None);
+    };
+    album.musicbrainz_id = Some(release_group.id.clone());
+    self.album_interactor.put(album).await?;
+    Ok(Some(release_group.id.clone()))
+  }
+
+  pub async fn enqueue_many(&self, file_names: Vec<FileName>) -> Result<()> {
+    self
+      .scheduler
+      .put_many(
+        file_names
+          .into_iter()
+          .map(|file_name| {
+            Ok(
+              JobParametersBuilder::default()
+                .id(format!("lookup_musicbrainz_id:{}", file_name.to_string()))
+                .name(JobName::LookupMusicBrainzId)
+                .payload(serde_json::to_vec(&file_name)?)
+                .priority(Priority::Low)
+                .overwrite_existing(false)
+                .build()?,
+            )
+          })
+          .collect::<Result<Vec<_>>>()?,
+      )
+      .await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,45 @@
+use crate::{
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn lookup_musicbrainz_id(job: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let file_name = job.payload::<FileName>()?;
+  app_context
+    .musicbrainz_lookup_interactor
+    .as_ref()
+    .ok_or_else(|| anyhow!("MusicBrainz is not configured"))?
+    .lookup(&file_name)
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to look up MusicBrainz id"))?;
+  Ok(())
+}
+
+pub async fn setup_musicbrainz_lookup_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if app_context.musicbrainz_lookup_interactor.is_none() {
+    info!("MusicBrainz is not configured, skipping lookup job");
+    return Ok(());
+  }
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::LookupMusicBrainzId)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(lookup_musicbrainz_id))
+        .build()?,
+    )
+    .await;
+
+  Ok(())
+}
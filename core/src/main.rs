@@ -1,30 +1,67 @@
 use anyhow::Result;
 use lute::{
-  albums::album_event_subscribers::build_album_event_subscribers,
+  albums::{
+    album_event_subscribers::build_album_event_subscribers, album_jobs::setup_album_jobs,
+    album_search_index_rebuild::recover_album_search_index_rebuild_progress,
+  },
   artists::artist_event_subscribers::build_artist_event_subscribers,
+  backups::{
+    backup::{restore_backup, restore_path_from_args},
+    backup_jobs::setup_backup_jobs,
+  },
   context::ApplicationContext,
+  cover_images::{
+    cover_image_event_subscribers::build_cover_image_event_subscribers,
+    cover_image_jobs::setup_cover_image_jobs,
+  },
   crawler::crawler_jobs::setup_crawler_jobs,
+  discogs::{
+    discogs_event_subscribers::build_discogs_event_subscribers, discogs_jobs::setup_discogs_jobs,
+  },
   embedding_provider::{
     embedding_provider_event_subscribers::build_embedding_provider_event_subscribers,
     embedding_provider_jobs::setup_embedding_provider_jobs,
   },
-  events::{event_subscriber::EventSubscriber, event_subscriber_jobs::setup_event_subscriber_jobs},
-  helpers::key_value_store::setup_kv_jobs,
-  lookup::build_lookup_event_subscribers,
+  events::{
+    event_subscriber::EventSubscriber, event_subscriber_jobs::setup_event_subscriber_jobs,
+    read_model_replay::recover_read_model_replay_progress,
+  },
+  files::file_jobs::setup_file_jobs,
+  genres::genre_taxonomy_event_subscribers::build_genre_taxonomy_event_subscribers,
+  helpers::{
+    document_store::document_store_quota::setup_doc_store_jobs, key_value_store::setup_kv_jobs,
+    operation_lease::OperationLease,
+  },
+  lastfm::lastfm_jobs::setup_lastfm_jobs,
+  listenbrainz::listenbrainz_jobs::setup_listenbrainz_jobs,
+  lookup::{
+    build_lookup_event_subscribers, setup_lookup_expiry_jobs, setup_musicbrainz_lookup_jobs,
+  },
   parser::{
     parser_event_subscribers::build_parser_event_subscribers, parser_jobs::setup_parser_jobs,
   },
-  profile::profile_event_subscribers::build_profile_event_subscribers,
+  profile::{
+    profile_event_subscribers::build_profile_event_subscribers, profile_jobs::setup_profile_jobs,
+  },
   recommendations::{
+    recommendation_digest_jobs::setup_recommendation_digest_jobs,
     recommendation_event_subscribers::build_recommendation_event_subscribers,
     recommendation_jobs::setup_recommendation_jobs,
+    year_in_review_jobs::setup_year_in_review_jobs,
   },
   redis::setup_redis_indexes,
+  redis_migrations::run_redis_migrations,
   rpc::RpcServer,
+  schema_manifest::{
+    check_schema_versions, recover_schema_upgrade_progress, run_schema_upgrade, SchemaStatus,
+  },
+  settings::Settings,
 };
+use dotenv::dotenv;
 use mimalloc::MiMalloc;
 use std::{collections::HashMap, sync::Arc};
 use tokio::spawn;
+use tracing::info;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -33,9 +70,16 @@ fn start_event_subscribers(app_context: Arc<ApplicationContext>) -> Result<()> {
   let mut event_subscribers: Vec<EventSubscriber> = Vec::new();
   event_subscribers.extend(build_album_event_subscribers(Arc::clone(&app_context))?);
   event_subscribers.extend(build_artist_event_subscribers(Arc::clone(&app_context))?);
+  event_subscribers.extend(build_cover_image_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
+  event_subscribers.extend(build_discogs_event_subscribers(Arc::clone(&app_context))?);
   event_subscribers.extend(build_embedding_provider_event_subscribers(Arc::clone(
     &app_context,
   ))?);
+  event_subscribers.extend(build_genre_taxonomy_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
   event_subscribers.extend(build_lookup_event_subscribers(Arc::clone(&app_context))?);
   event_subscribers.extend(build_parser_event_subscribers(Arc::clone(&app_context))?);
   event_subscribers.extend(build_profile_event_subscribers(Arc::clone(&app_context))?);
@@ -49,17 +93,43 @@ fn start_event_subscribers(app_context: Arc<ApplicationContext>) -> Result<()> {
 }
 
 async fn setup_jobs(context: Arc<ApplicationContext>) -> Result<()> {
+  setup_album_jobs(Arc::clone(&context)).await?;
+  setup_backup_jobs(Arc::clone(&context)).await?;
+  setup_cover_image_jobs(Arc::clone(&context)).await?;
   setup_crawler_jobs(Arc::clone(&context)).await?;
+  setup_discogs_jobs(Arc::clone(&context)).await?;
+  setup_doc_store_jobs(Arc::clone(&context)).await?;
   setup_embedding_provider_jobs(Arc::clone(&context)).await?;
   setup_event_subscriber_jobs(Arc::clone(&context)).await?;
+  setup_file_jobs(Arc::clone(&context)).await?;
   setup_kv_jobs(Arc::clone(&context)).await?;
+  setup_lastfm_jobs(Arc::clone(&context)).await?;
+  setup_listenbrainz_jobs(Arc::clone(&context)).await?;
+  setup_lookup_expiry_jobs(Arc::clone(&context)).await?;
+  setup_musicbrainz_lookup_jobs(Arc::clone(&context)).await?;
   setup_parser_jobs(Arc::clone(&context)).await?;
-  setup_recommendation_jobs(context).await?;
+  setup_profile_jobs(Arc::clone(&context)).await?;
+  setup_recommendation_digest_jobs(Arc::clone(&context)).await?;
+  setup_recommendation_jobs(Arc::clone(&context)).await?;
+  setup_year_in_review_jobs(context).await?;
   Ok(())
 }
 
-async fn setup_elasticsearch_indexes(context: Arc<ApplicationContext>) -> Result<()> {
+async fn setup_search_indexes(context: Arc<ApplicationContext>) -> Result<()> {
   context.artist_interactor.setup_search_index().await?;
+  context.album_interactor.setup_search_index().await?;
+  setup_redis_indexes(Arc::clone(&context)).await?;
+  Ok(())
+}
+
+/**
+ * Long-running operations don't survive a restart, so their leases and progress are stale
+ */
+async fn recover_interrupted_operations(context: Arc<ApplicationContext>) -> Result<()> {
+  OperationLease::clear_all(&context.kv).await?;
+  recover_schema_upgrade_progress(Arc::clone(&context)).await?;
+  recover_album_search_index_rebuild_progress(Arc::clone(&context)).await?;
+  recover_read_model_replay_progress(context).await?;
   Ok(())
 }
 
@@ -83,18 +153,53 @@ async fn setup_doc_store_indexes(context: Arc<ApplicationContext>) -> Result<()>
         vec![vec!["page_type", "error"], vec!["error"]],
       ),
       ("list_lookup", vec![vec!["root_file_name"]]),
+      (
+        "artist_ingestion_pending_artist",
+        vec![vec!["artist_file_name"]],
+      ),
+      ("profile_snapshot", vec![vec!["profile_id"]]),
+      ("profile_goal", vec![vec!["profile_id"]]),
+      ("recommendation_digest", vec![vec!["profile_id"]]),
+      ("collection", vec![vec!["profile_id"]]),
+      ("year_in_review", vec![vec!["profile_id"]]),
+      (
+        "album_duplicate_candidate",
+        vec![vec!["status"], vec!["original_file_name", "status"]],
+      ),
     ]))
     .await
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+  // Restoring replaces the database files, so it has to finish before anything opens them
+  let restored = match restore_path_from_args(std::env::args()) {
+    Some(archive_path) => {
+      dotenv().ok();
+      Some(restore_backup(&Settings::new()?, &archive_path).await?)
+    }
+    None => None,
+  };
   let context = ApplicationContext::init().await?;
+  if let Some(manifest) = restored {
+    info!(
+      created_at = manifest.created_at.to_string(),
+      redis_key_count = manifest.redis_key_count,
+      "Restored backup"
+    );
+  }
+  recover_interrupted_operations(Arc::clone(&context)).await?;
+  // Nothing may run against stores that are behind this build, so a pending upgrade goes first
+  if let SchemaStatus::UpgradePending { .. } = check_schema_versions(Arc::clone(&context)).await? {
+    run_schema_upgrade(Arc::clone(&context)).await?;
+  }
   setup_doc_store_indexes(Arc::clone(&context)).await?;
-  setup_elasticsearch_indexes(Arc::clone(&context)).await?;
-  setup_redis_indexes(Arc::clone(&context)).await?;
+  setup_search_indexes(Arc::clone(&context)).await?;
+  let migration_context = Arc::clone(&context);
+  spawn(async move { run_redis_migrations(migration_context).await });
   start_event_subscribers(Arc::clone(&context))?;
   setup_jobs(Arc::clone(&context)).await?;
+  context.scheduler.recover_interrupted_runs().await?;
   context.scheduler.run().await?;
   RpcServer::new(context).run().await?;
   Ok(())
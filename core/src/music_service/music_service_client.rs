@@ -0,0 +1,150 @@
+use crate::proto;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strsim::jaro_winkler;
+use unidecode::unidecode;
+
+const MIN_TRACK_NAME_SIMILARITY: f64 = 0.85;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MusicService {
+  Spotify,
+  Tidal,
+  AppleMusic,
+  YouTubeMusic,
+}
+
+impl From<proto::MusicService> for MusicService {
+  fn from(value: proto::MusicService) -> Self {
+    match value {
+      proto::MusicService::Spotify => MusicService::Spotify,
+      proto::MusicService::Tidal => MusicService::Tidal,
+      proto::MusicService::AppleMusic => MusicService::AppleMusic,
+      proto::MusicService::YoutubeMusic => MusicService::YouTubeMusic,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MusicServiceAlbum {
+  pub id: String,
+  pub name: String,
+  pub artist_names: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MusicServiceTrack {
+  pub id: String,
+  pub name: String,
+  pub artist_names: Vec<String>,
+  pub isrc: Option<String>,
+}
+
+impl From<MusicServiceTrack> for proto::MusicServiceTrack {
+  fn from(val: MusicServiceTrack) -> Self {
+    proto::MusicServiceTrack {
+      id: val.id,
+      name: val.name,
+      artist_names: val.artist_names,
+      isrc: val.isrc,
+    }
+  }
+}
+
+/**
+ * A track to look up on a service. The ISRC is used when known, since names rarely match exactly
+ * across catalogs.
+ */
+#[derive(Clone, Debug)]
+pub struct MusicServiceTrackQuery {
+  pub name: String,
+  pub artist_names: Vec<String>,
+  pub isrc: Option<String>,
+}
+
+fn normalize(value: &str) -> String {
+  unidecode(value).to_ascii_lowercase()
+}
+
+impl MusicServiceTrackQuery {
+  pub fn search_text(&self) -> String {
+    match self.artist_names.first() {
+      Some(artist_name) => format!("{} {}", artist_name, self.name),
+      None => self.name.clone(),
+    }
+  }
+
+  /**
+   * Whether a search result is the same recording. An ISRC match is conclusive, otherwise the
+   * names must be close and the candidate must share an artist when it lists any.
+   */
+  pub fn is_match(&self, candidate: &MusicServiceTrack) -> bool {
+    if let (Some(isrc), Some(candidate_isrc)) = (&self.isrc, &candidate.isrc) {
+      return isrc.eq_ignore_ascii_case(candidate_isrc);
+    }
+    let name_similarity = jaro_winkler(&normalize(&self.name), &normalize(&candidate.name));
+    let shares_artist = candidate.artist_names.is_empty()
+      || candidate.artist_names.iter().any(|candidate_artist| {
+        self
+          .artist_names
+          .iter()
+          .any(|artist| normalize(artist) == normalize(candidate_artist))
+      });
+    name_similarity >= MIN_TRACK_NAME_SIMILARITY && shares_artist
+  }
+}
+
+/**
+ * The operations recommendations need from a streaming service: importing a library and
+ * exporting playlists
+ */
+#[async_trait]
+pub trait MusicServiceClient: Send + Sync {
+  fn service(&self) -> MusicService;
+  async fn is_authorized(&self) -> bool;
+  async fn get_saved_albums(&self) -> Result<Vec<MusicServiceAlbum>>;
+  async fn find_track(&self, query: &MusicServiceTrackQuery) -> Result<Option<MusicServiceTrack>>;
+  /**
+   * Returns the id of the new playlist
+   */
+  async fn create_playlist(
+    &self,
+    name: String,
+    description: Option<String>,
+    track_ids: Vec<String>,
+  ) -> Result<String>;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn track(name: &str, artist_names: Vec<&str>, isrc: Option<&str>) -> MusicServiceTrack {
+    MusicServiceTrack {
+      id: "1".to_string(),
+      name: name.to_string(),
+      artist_names: artist_names.into_iter().map(String::from).collect(),
+      isrc: isrc.map(String::from),
+    }
+  }
+
+  #[test]
+  fn test_is_match() {
+    let query = MusicServiceTrackQuery {
+      name: "Jógvan".to_string(),
+      artist_names: vec!["Björk".to_string()],
+      isrc: None,
+    };
+    assert!(query.is_match(&track("jogvan", vec!["Bjork"], None)));
+    assert!(!query.is_match(&track("Jógvan", vec!["Someone Else"], None)));
+    assert!(!query.is_match(&track("Hunter", vec!["Björk"], None)));
+
+    let query = MusicServiceTrackQuery {
+      isrc: Some("GBAAA9700001".to_string()),
+      ..query
+    };
+    assert!(query.is_match(&track("Jóga (Remastered)", vec![], Some("gbaaa9700001"))));
+    assert!(!query.is_match(&track("Jógvan", vec!["Björk"], Some("GBAAA9700002"))));
+  }
+}
@@ -1,29 +1,59 @@
 use crate::{
+  albums::{
+    album_digest::{diff_album_digests, fetch_peer_album_digests, DIGEST_PAGE_SIZE},
+    album_search_index_rebuild::{
+      abort_album_search_index_rebuild, get_album_search_index_rebuild_progress,
+      run_album_search_index_rebuild, AlbumSearchIndexRebuildParameters,
+      AlbumSearchIndexRebuildProgress,
+    },
+  },
   context::ApplicationContext,
   crawler::crawler::{Crawler, QueuePushParametersBuilder},
-  events::event_repository::EventRepository,
-  files::file_interactor::FileInteractor,
-  helpers::{key_value_store::KeyValueStore, priority::Priority},
+  embedding_provider::embedding_cache_stats::EmbeddingCacheStats,
+  events::{
+    event_repository::EventRepository,
+    event_subscriber_jobs::is_lag_exceeded,
+    read_model_replay::{
+      abort_read_model_replay, get_read_model_replay_progress, run_read_model_replay,
+      ReadModelReplayParameters, ReadModelReplayProgress,
+    },
+  },
+  files::{file_interactor::FileInteractor, file_metadata::file_name::FileName},
+  helpers::{key_value_store::KeyValueStore, operation_lease::OperationLease, priority::Priority},
   parser::parser_failure_repository::ParserFailureRepository,
   proto::{
-    self, CrawlParseFailedFilesReply, CrawlParseFailedFilesRequest,
-    GetEventKeyMigrationMonitorReply, KeyCountReply, MigrateSqliteRequest,
-    ParseFileContentStoreReply,
+    self, ClearEmbeddingCacheRequest, CrawlParseFailedFilesReply, CrawlParseFailedFilesRequest,
+    DiffCorpusReply, DiffCorpusRequest, GetAlbumDigestsReply, GetAlbumDigestsRequest,
+    GetAlbumSearchIndexRebuildMonitorReply, GetEmbeddingCacheStatsReply,
+    GetEventKeyMigrationMonitorReply, GetEventSubscriberLagsReply, GetReadModelReplayMonitorReply,
+    GetSchemaUpgradeMonitorReply, KeyCountReply, MigrateSqliteRequest, ParseFileContentStoreReply,
+    RebuildAlbumSearchIndexRequest, ReplayReadModelsRequest, WatchDashboardRequest,
+  },
+  schema_manifest::{
+    compiled_schema_versions, get_applied_schema_versions, get_schema_upgrade_progress,
+    run_schema_upgrade, SchemaUpgradeProgress, SchemaVersions,
   },
+  settings::StorageMode,
   sqlite::SqliteConnection,
 };
-use futures::future::join_all;
+use chrono::Utc;
+use futures::{future::join_all, try_join, Stream};
 use rustis::{
   bb8::Pool,
   client::PooledClientManager,
   commands::{FlushingMode, ServerCommands},
 };
-use std::{collections::HashMap, sync::Arc};
-use tokio::spawn;
+use std::{collections::HashMap, pin::Pin, sync::Arc, time::Duration};
+use tokio::{spawn, time::sleep};
 use tonic::{Request, Response, Status};
 use tracing::error;
 
+const SCHEMA_UPGRADE_OPERATION: &str = "schema_upgrade";
+const ALBUM_SEARCH_INDEX_REBUILD_OPERATION: &str = "album_search_index_rebuild";
+const READ_MODEL_REPLAY_OPERATION: &str = "read_model_replay";
+
 pub struct OperationsService {
+  app_context: Arc<ApplicationContext>,
   sqlite_connection: Arc<SqliteConnection>,
   redis_connection_pool: Arc<Pool<PooledClientManager>>,
   crawler: Arc<Crawler>,
@@ -43,12 +73,160 @@ impl OperationsService {
       file_interactor: Arc::clone(&app_context.file_interactor),
       parser_failure_repository: ParserFailureRepository::new(Arc::clone(&app_context.doc_store)),
       event_repository: EventRepository::new(Arc::clone(&app_context.sqlite_connection)),
+      app_context,
     }
   }
+
+  /**
+   * Claims a long-running operation, or fails if it's already running
+   */
+  async fn acquire_lease(
+    &self,
+    operation: &str,
+    running_message: &str,
+  ) -> Result<OperationLease, Status> {
+    OperationLease::acquire(Arc::clone(&self.kv), operation)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to claim operation")
+      })?
+      .ok_or_else(|| Status::failed_precondition(running_message))
+  }
+}
+
+impl From<SchemaVersions> for proto::SchemaVersions {
+  fn from(val: SchemaVersions) -> Self {
+    proto::SchemaVersions {
+      sqlite: val.sqlite,
+      album_index: val.album_index,
+      spotify_track_index: val.spotify_track_index,
+      album_embedding_body: val.album_embedding_body,
+    }
+  }
+}
+
+impl From<SchemaUpgradeProgress> for proto::SchemaUpgradeProgress {
+  fn from(val: SchemaUpgradeProgress) -> Self {
+    proto::SchemaUpgradeProgress {
+      steps: val.steps,
+      completed_steps: val.completed_steps as u32,
+      running: val.running,
+      error: val.error,
+    }
+  }
+}
+
+impl From<AlbumSearchIndexRebuildProgress> for proto::AlbumSearchIndexRebuildProgress {
+  fn from(val: AlbumSearchIndexRebuildProgress) -> Self {
+    proto::AlbumSearchIndexRebuildProgress {
+      done: val.done,
+      total: val.total,
+      running: val.running,
+      aborted: val.aborted,
+      error: val.error,
+    }
+  }
+}
+
+impl From<ReadModelReplayProgress> for proto::ReadModelReplayProgress {
+  fn from(val: ReadModelReplayProgress) -> Self {
+    proto::ReadModelReplayProgress {
+      cursor: val.cursor,
+      done: val.done,
+      total: val.total,
+      running: val.running,
+      aborted: val.aborted,
+      error: val.error,
+    }
+  }
+}
+
+impl From<EmbeddingCacheStats> for proto::EmbeddingCacheStats {
+  fn from(val: EmbeddingCacheStats) -> Self {
+    proto::EmbeddingCacheStats {
+      hit_rate: val.hit_rate() as f32,
+      embedding_key: val.provider_name,
+      hits: val.hits,
+      misses: val.misses,
+      provider_calls: val.provider_calls,
+      cached_count: val.cached_count,
+      bytes_stored: val.bytes_stored,
+      tokens_avoided: val.tokens_avoided,
+      estimated_cost_avoided: val.estimated_cost_avoided,
+    }
+  }
+}
+
+async fn get_event_subscriber_lags(
+  app_context: &ApplicationContext,
+) -> anyhow::Result<Vec<proto::EventSubscriberLag>> {
+  let lags = EventRepository::new(Arc::clone(&app_context.sqlite_connection))
+    .get_subscriber_lags()
+    .await?;
+  let now = Utc::now().naive_utc();
+  Ok(
+    lags
+      .into_iter()
+      .map(|lag| proto::EventSubscriberLag {
+        exceeds_threshold: is_lag_exceeded(&lag, &app_context.settings.events, now),
+        subscriber_id: lag.subscriber_id,
+        status: proto::EventSubscriberStatus::from(lag.status) as i32,
+        cursor: lag.cursor,
+        streams: lag
+          .streams
+          .unwrap_or_default()
+          .into_iter()
+          .map(|stream| stream.to_string())
+          .collect(),
+        events_behind: lag.events_behind,
+        oldest_unprocessed_at: lag.oldest_unprocessed_at.map(|d| d.to_string()),
+      })
+      .collect(),
+  )
+}
+
+async fn get_operations_dashboard(
+  app_context: &ApplicationContext,
+  parser_failure_limit: usize,
+) -> anyhow::Result<proto::OperationsDashboard> {
+  let parser_failure_repository = ParserFailureRepository::new(Arc::clone(&app_context.doc_store));
+  let (crawler, subscriber_lags, recent_parser_failures, jobs, job_counts) = try_join!(
+    app_context.crawler.get_monitor(),
+    get_event_subscriber_lags(app_context),
+    parser_failure_repository.find_recent(parser_failure_limit),
+    app_context.scheduler.get_jobs(),
+    app_context.scheduler.count_jobs_by_each_name(),
+  )?;
+  Ok(proto::OperationsDashboard {
+    generated_at: Utc::now().naive_utc().to_string(),
+    crawler: Some(crawler.into()),
+    subscriber_lags,
+    recent_parser_failures: recent_parser_failures
+      .into_iter()
+      .map(|failure| proto::RecentParserFailure {
+        file_name: failure.file_name.to_string(),
+        error: failure.error,
+        last_attempted_at: failure.last_attempted_at.to_string(),
+      })
+      .collect(),
+    recurring_jobs: jobs
+      .into_iter()
+      .filter(|job| job.interval_seconds.is_some())
+      .map(Into::into)
+      .collect(),
+    job_counts: job_counts
+      .into_iter()
+      .map(|(name, count)| (name.to_string(), count as u32))
+      .collect(),
+  })
 }
 
 #[tonic::async_trait]
 impl proto::OperationsService for OperationsService {
+  type WatchDashboardStream =
+    Pin<Box<dyn Stream<Item = Result<proto::OperationsDashboard, Status>> + Send + 'static>>;
+
   async fn get_key_value_store_size(
     &self,
     _: Request<()>,
@@ -130,6 +308,11 @@ impl proto::OperationsService for OperationsService {
   }
 
   async fn flush_redis(&self, _: Request<()>) -> Result<Response<()>, Status> {
+    if self.app_context.settings.storage.mode == StorageMode::Sqlite {
+      return Err(Status::failed_precondition(
+        "Redis is not used in sqlite storage mode",
+      ));
+    }
     let connection = self.redis_connection_pool.get().await.map_err(|e| {
       error!("Error: {:?}", e);
       Status::internal("Failed to get redis connection")
@@ -242,4 +425,328 @@ impl proto::OperationsService for OperationsService {
     }
     Ok(Response::new(CrawlParseFailedFilesReply { count }))
   }
+
+  async fn upgrade_schema(&self, _: Request<()>) -> Result<Response<()>, Status> {
+    let lease = self
+      .acquire_lease(
+        SCHEMA_UPGRADE_OPERATION,
+        "A schema upgrade is already running",
+      )
+      .await?;
+    let app_context = Arc::clone(&self.app_context);
+    spawn(async move {
+      if let Err(e) = run_schema_upgrade(app_context).await {
+        error!("Failed to upgrade schema: {:?}", e);
+      }
+      if let Err(e) = lease.release().await {
+        error!("Failed to release schema upgrade lease: {:?}", e);
+      }
+    });
+    Ok(Response::new(()))
+  }
+
+  async fn get_schema_upgrade_monitor(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetSchemaUpgradeMonitorReply>, Status> {
+    let applied = get_applied_schema_versions(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get applied schema versions")
+      })?;
+    let progress = get_schema_upgrade_progress(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get schema upgrade progress")
+      })?;
+    Ok(Response::new(GetSchemaUpgradeMonitorReply {
+      applied: applied.map(Into::into),
+      compiled: Some(compiled_schema_versions().into()),
+      progress: progress.map(Into::into),
+    }))
+  }
+
+  async fn get_album_digests(
+    &self,
+    request: Request<GetAlbumDigestsRequest>,
+  ) -> Result<Response<GetAlbumDigestsReply>, Status> {
+    let request = request.into_inner();
+    let after = request
+      .after
+      .map(FileName::try_from)
+      .transpose()
+      .map_err(|e| Status::invalid_argument(format!("Invalid file name: {}", e)))?;
+    let digests = self
+      .app_context
+      .album_interactor
+      .find_digests(
+        after,
+        request
+          .limit
+          .unwrap_or(DIGEST_PAGE_SIZE)
+          .min(DIGEST_PAGE_SIZE),
+      )
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get album digests")
+      })?;
+    Ok(Response::new(GetAlbumDigestsReply {
+      digests: digests.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  /**
+   * Compares this instance's albums against a peer's. With `schedule_syncs`, albums the peer has
+   * that are missing or differ here are recrawled; the peer is never written to.
+   */
+  async fn diff_corpus(
+    &self,
+    request: Request<DiffCorpusRequest>,
+  ) -> Result<Response<DiffCorpusReply>, Status> {
+    let request = request.into_inner();
+    let peer_digests = fetch_peer_album_digests(request.peer_address)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::unavailable(format!("Failed to get peer album digests: {}", e))
+      })?;
+    let local_digests = self
+      .app_context
+      .album_interactor
+      .find_all_digests()
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get album digests")
+      })?;
+    let diff = diff_album_digests(local_digests, peer_digests);
+    let mut syncs_scheduled = 0;
+    if request.schedule_syncs {
+      for file_name in diff.missing_locally.iter().chain(diff.differing.iter()) {
+        self
+          .crawler
+          .enqueue(
+            QueuePushParametersBuilder::default()
+              .file_name(file_name.clone())
+              .priority(Priority::Low)
+              .correlation_id("rpc:diff_corpus")
+              .build()
+              .map_err(|e| {
+                error!("Error: {:?}", e);
+                Status::internal(format!("Failed to build queue push parameters: {}", e))
+              })?,
+          )
+          .await
+          .map_err(|e| {
+            error!("Error: {:?}", e);
+            Status::internal("Failed to enqueue file")
+          })?;
+        syncs_scheduled += 1;
+      }
+    }
+    Ok(Response::new(DiffCorpusReply {
+      syncs_scheduled,
+      ..diff.into()
+    }))
+  }
+
+  async fn get_embedding_cache_stats(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetEmbeddingCacheStatsReply>, Status> {
+    let stats = self
+      .app_context
+      .embedding_provider_interactor
+      .get_all_cache_stats()
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get embedding cache stats")
+      })?;
+    Ok(Response::new(GetEmbeddingCacheStatsReply {
+      stats: stats.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn clear_embedding_cache(
+    &self,
+    request: Request<ClearEmbeddingCacheRequest>,
+  ) -> Result<Response<()>, Status> {
+    let embedding_key = request.into_inner().embedding_key;
+    if !self
+      .app_context
+      .embedding_provider_interactor
+      .providers
+      .contains_key(&embedding_key)
+    {
+      return Err(Status::not_found("Embedding provider not found"));
+    }
+    self
+      .app_context
+      .embedding_provider_interactor
+      .clear_cache(&embedding_key)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to clear embedding cache")
+      })?;
+    Ok(Response::new(()))
+  }
+
+  async fn get_event_subscriber_lags(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetEventSubscriberLagsReply>, Status> {
+    let lags = get_event_subscriber_lags(&self.app_context)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get event subscriber lags")
+      })?;
+    Ok(Response::new(GetEventSubscriberLagsReply { lags }))
+  }
+  async fn rebuild_album_search_index(
+    &self,
+    request: Request<RebuildAlbumSearchIndexRequest>,
+  ) -> Result<Response<()>, Status> {
+    let lease = self
+      .acquire_lease(
+        ALBUM_SEARCH_INDEX_REBUILD_OPERATION,
+        "An album search index rebuild is already running",
+      )
+      .await?;
+    let request = request.into_inner();
+    let defaults = AlbumSearchIndexRebuildParameters::default();
+    let parameters = AlbumSearchIndexRebuildParameters {
+      batch_size: request.batch_size.unwrap_or(defaults.batch_size).max(1),
+      throttle: request
+        .throttle_millis
+        .map(|millis| Duration::from_millis(millis as u64))
+        .unwrap_or(defaults.throttle),
+    };
+    let app_context = Arc::clone(&self.app_context);
+    spawn(async move {
+      if let Err(e) = run_album_search_index_rebuild(app_context, parameters).await {
+        error!("Failed to rebuild album search index: {:?}", e);
+      }
+      if let Err(e) = lease.release().await {
+        error!(
+          "Failed to release album search index rebuild lease: {:?}",
+          e
+        );
+      }
+    });
+    Ok(Response::new(()))
+  }
+
+  async fn get_album_search_index_rebuild_monitor(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetAlbumSearchIndexRebuildMonitorReply>, Status> {
+    let progress = get_album_search_index_rebuild_progress(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get album search index rebuild progress")
+      })?;
+    Ok(Response::new(GetAlbumSearchIndexRebuildMonitorReply {
+      progress: progress.map(Into::into),
+    }))
+  }
+
+  async fn abort_album_search_index_rebuild(&self, _: Request<()>) -> Result<Response<()>, Status> {
+    abort_album_search_index_rebuild(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to abort album search index rebuild")
+      })?;
+    Ok(Response::new(()))
+  }
+
+  async fn replay_read_models(
+    &self,
+    request: Request<ReplayReadModelsRequest>,
+  ) -> Result<Response<()>, Status> {
+    let lease = self
+      .acquire_lease(
+        READ_MODEL_REPLAY_OPERATION,
+        "A read model replay is already running",
+      )
+      .await?;
+    let request = request.into_inner();
+    let defaults = ReadModelReplayParameters::default();
+    let parameters = ReadModelReplayParameters {
+      batch_size: request.batch_size.unwrap_or(defaults.batch_size).max(1),
+      throttle: request
+        .throttle_millis
+        .map(|millis| Duration::from_millis(millis as u64))
+        .unwrap_or(defaults.throttle),
+      resume: request.resume,
+    };
+    let app_context = Arc::clone(&self.app_context);
+    spawn(async move {
+      if let Err(e) = run_read_model_replay(app_context, parameters).await {
+        error!("Failed to replay read models: {:?}", e);
+      }
+      if let Err(e) = lease.release().await {
+        error!("Failed to release read model replay lease: {:?}", e);
+      }
+    });
+    Ok(Response::new(()))
+  }
+
+  async fn get_read_model_replay_monitor(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<GetReadModelReplayMonitorReply>, Status> {
+    let progress = get_read_model_replay_progress(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to get read model replay progress")
+      })?;
+    Ok(Response::new(GetReadModelReplayMonitorReply {
+      progress: progress.map(Into::into),
+    }))
+  }
+
+  async fn abort_read_model_replay(&self, _: Request<()>) -> Result<Response<()>, Status> {
+    abort_read_model_replay(Arc::clone(&self.app_context))
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to abort read model replay")
+      })?;
+    Ok(Response::new(()))
+  }
+
+  /**
+   * Sends a snapshot of the instance's status on an interval until the client disconnects
+   */
+  async fn watch_dashboard(
+    &self,
+    request: Request<WatchDashboardRequest>,
+  ) -> Result<Response<Self::WatchDashboardStream>, Status> {
+    let request = request.into_inner();
+    let interval = Duration::from_secs(request.interval_seconds.unwrap_or(2).max(1) as u64);
+    let parser_failure_limit = request.parser_failure_limit.unwrap_or(20) as usize;
+    let app_context = Arc::clone(&self.app_context);
+    let output_stream = async_stream::try_stream! {
+      loop {
+        let dashboard = get_operations_dashboard(&app_context, parser_failure_limit)
+          .await
+          .map_err(|e| {
+            error!("Error: {:?}", e);
+            Status::internal("Failed to get operations dashboard")
+          })?;
+        yield dashboard;
+        sleep(interval).await;
+      }
+    };
+    Ok(Response::new(Box::pin(output_stream)))
+  }
 }
@@ -1,6 +1,9 @@
 use super::{
   parsed_file_data::{ParsedAlbum, ParsedArtistReference, ParsedCredit, ParsedTrack},
-  util::{clean_album_name, clean_artist_name, parse_release_date},
+  util::{
+    clean_album_name, clean_artist_name, parse_release_date, strip_track_artists,
+    VARIOUS_ARTISTS_FILE_NAME,
+  },
 };
 use crate::{files::file_metadata::file_name::FileName, parser::dom::HtmlParser};
 use anyhow::Result;
@@ -74,6 +77,13 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
       })
     })
     .collect::<Result<Vec<_>>>()?;
+  let is_various_artists = artists
+    .iter()
+    .any(|artist| artist.file_name.to_string() == VARIOUS_ARTISTS_FILE_NAME);
+  let artists = artists
+    .into_iter()
+    .filter(|artist| artist.file_name.to_string() != VARIOUS_ARTISTS_FILE_NAME)
+    .collect::<Vec<_>>();
 
   let primary_genres = parser
     .query_by_selector(&[".release_pri_genres", ".genre"], Some(info_container))
@@ -125,6 +135,27 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
         .into_iter()
         .map(|tag| {
           let name = parser.get_text(&[".rendered_text"], Some(tag))?;
+          let artists = parser
+            .query_by_selector(&[".rendered_text", "a"], Some(tag))
+            .into_iter()
+            .filter_map(|tag| {
+              let href = parser.find_tag_href(tag)?;
+              if !href.starts_with("/artist/") {
+                return None;
+              }
+              Some(ParsedArtistReference {
+                name: clean_artist_name(&parser.find_tag_text(tag)?).to_string(),
+                file_name: FileName::try_from(href).ok()?,
+              })
+            })
+            .collect::<Vec<_>>();
+          let name = strip_track_artists(
+            &name,
+            &artists
+              .iter()
+              .map(|artist| artist.name.clone())
+              .collect::<Vec<_>>(),
+          );
           let rating = parser
             .find_text(&[".track_rating_avg"], Some(tag))
             .and_then(|rating| {
@@ -152,6 +183,7 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
             rating,
             position,
             duration_seconds,
+            artists,
           })
         })
         .collect::<Result<Vec<_>>>()
@@ -211,6 +243,7 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
     credits,
     cover_image_url,
     spotify_id,
+    is_various_artists,
   })
 }
 
@@ -307,7 +340,37 @@ mod tests {
     );
     assert_eq!(album.credits[5].roles, ["tenor saxophone"]);
     assert!(album.cover_image_url.is_some());
+    assert!(!album.is_various_artists);
+    assert!(album.tracks.iter().all(|track| track.artists.is_empty()));
     assert_eq!(album.cover_image_url.unwrap(), "https://e.snmc.io/i/600/w/5f531a5819eda8ce114ffdb1e2359148/1346423/fela-ransome-kuti-and-the-afrika-70-gentleman-Cover-Art.jpg");
     Ok(())
   }
+
+  #[test]
+  fn test_various_artists_album_parser() -> Result<(), String> {
+    let file_content = include_str!(test_resource!("album_various_artists.html"));
+    let album = parse_album(file_content).map_err(|err| err.to_string())?;
+    assert_eq!(album.name, "Artificial Intelligence");
+    assert!(album.is_various_artists);
+    assert!(album.artists.is_empty());
+    assert_eq!(album.primary_genres, ["IDM"]);
+    assert_eq!(album.release_date, NaiveDate::from_ymd_opt(1992, 7, 6));
+    assert_eq!(album.tracks.len(), 3);
+    assert_eq!(album.tracks[0].name, "Polygon Window");
+    assert_eq!(album.tracks[0].artists.len(), 1);
+    assert_eq!(album.tracks[0].artists[0].name, "The Dice Man");
+    assert_eq!(album.tracks[1].name, "The Egg");
+    assert_eq!(
+      album.tracks[1]
+        .artists
+        .iter()
+        .map(|artist| artist.file_name.to_string())
+        .collect::<Vec<_>>(),
+      ["artist/autechre", "artist/richie-hawtin"]
+    );
+    assert_eq!(album.tracks[1].duration_seconds, Some(405));
+    assert_eq!(album.tracks[2].name, "Untitled - Part 2");
+    assert!(album.tracks[2].artists.is_empty());
+    Ok(())
+  }
 }
@@ -4,6 +4,7 @@ use super::{
 };
 use crate::files::file_metadata::file_name::FileName;
 use anyhow::Result;
+use std::collections::HashSet;
 use tracing::instrument;
 
 fn parse_artist_albums(parser: &HtmlParser, id: &str) -> Result<Vec<ParsedArtistAlbum>> {
@@ -21,11 +22,44 @@ fn parse_artist_albums(parser: &HtmlParser, id: &str) -> Result<Vec<ParsedArtist
     .collect::<Result<Vec<_>>>()
 }
 
+fn parse_alternate_names(parser: &HtmlParser, name: &str) -> Vec<String> {
+  let mut alternate_names = parser
+    .query_by_selector(&[".artist_name_hdr", ".additional_names"], None)
+    .into_iter()
+    .filter_map(|tag| parser.find_tag_text(tag))
+    .map(|text| {
+      text
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim()
+        .to_string()
+    })
+    .collect::<Vec<_>>();
+
+  let headers = parser.query_by_selector(&[".artist_info_main", ".info_hdr"], None);
+  let contents = parser.query_by_selector(&[".artist_info_main", ".info_content"], None);
+  for (header, content) in headers.into_iter().zip(contents) {
+    if parser.find_tag_text(header).as_deref() == Some("Also Known As") {
+      if let Some(text) = parser.find_tag_text(content) {
+        alternate_names.extend(text.split(", ").map(|name| name.trim().to_string()));
+      }
+    }
+  }
+
+  let mut seen = HashSet::new();
+  alternate_names
+    .into_iter()
+    .filter(|alternate_name| !alternate_name.is_empty() && alternate_name != name)
+    .filter(|alternate_name| seen.insert(alternate_name.clone()))
+    .collect()
+}
+
 #[instrument(skip_all)]
 pub fn parse_artist(file_content: &str) -> Result<ParsedArtist> {
   let parser = HtmlParser::try_from(file_content)?;
 
   let name = parser.get_meta_item_prop("name")?;
+  let alternate_names = parse_alternate_names(&parser, &name);
   let albums = parse_artist_albums(&parser, "disco_type_s").unwrap_or_default();
   let mixtapes = parse_artist_albums(&parser, "disco_type_m").unwrap_or_default();
   let eps = parse_artist_albums(&parser, "disco_type_e").unwrap_or_default();
@@ -37,7 +71,11 @@ pub fn parse_artist(file_content: &str) -> Result<ParsedArtist> {
     .chain(compilations.into_iter())
     .collect();
 
-  Ok(ParsedArtist { name, albums })
+  Ok(ParsedArtist {
+    name,
+    albums,
+    alternate_names,
+  })
 }
 
 #[cfg(test)]
@@ -50,6 +88,7 @@ mod tests {
     let file_content = include_str!(test_resource!("artist.html"));
     let artist = parse_artist(file_content).map_err(|err| err.to_string())?;
     assert_eq!(artist.name, "billy woods");
+    assert_eq!(artist.alternate_names, vec!["F. Porter"]);
     assert_eq!(artist.albums.len(), 13);
     assert_eq!(artist.albums[0].name, "Camouflage");
     assert_eq!(
@@ -0,0 +1,70 @@
+use super::{
+  dom::HtmlParser,
+  parsed_file_data::{ParsedBandcampAlbum, ParsedBandcampSearchResult},
+};
+use anyhow::Result;
+use tracing::instrument;
+
+#[instrument(skip(file_content))]
+pub fn parse_bandcamp_search_result(file_content: &str) -> Result<ParsedBandcampSearchResult> {
+  let parser = HtmlParser::try_from(file_content)?;
+
+  let albums = parser
+    .query_by_selector(&[".searchresult"], None)
+    .into_iter()
+    .filter_map(|tag| {
+      let item_type = parser.find_text(&[".itemtype"], Some(tag))?;
+      if !item_type.eq_ignore_ascii_case("album") {
+        return None;
+      }
+      let name = parser.find_text(&[".heading", "a"], Some(tag))?;
+      let artist_name = parser
+        .find_text(&[".subhead"], Some(tag))?
+        .trim_start_matches("by ")
+        .trim()
+        .to_string();
+      // The heading links carry search tracking parameters, the item url is the canonical one
+      let url = parser.find_text(&[".itemurl", "a"], Some(tag))?;
+      Some(ParsedBandcampAlbum {
+        name,
+        artist_name,
+        url,
+      })
+    })
+    .collect::<Vec<_>>();
+
+  Ok(ParsedBandcampSearchResult { albums })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_bandcamp_search_result_parser() -> Result<()> {
+    let file_content = concat!(
+      r#"<ul class="result-items">"#,
+      r#"<li class="searchresult data-search"><div class="result-info">"#,
+      r#"<div class="itemtype">ARTIST</div>"#,
+      r#"<div class="heading"><a href="https://sprain.bandcamp.com?from=search">Sprain</a></div>"#,
+      r#"<div class="itemurl"><a href="https://sprain.bandcamp.com?from=search">https://sprain.bandcamp.com</a></div>"#,
+      r#"</div></li>"#,
+      r#"<li class="searchresult data-search"><div class="result-info">"#,
+      r#"<div class="itemtype"> ALBUM </div>"#,
+      r#"<div class="heading"><a href="https://sprain.bandcamp.com/album/as-lost-through-collision?from=search"> As Lost Through Collision </a></div>"#,
+      r#"<div class="subhead"> by Sprain </div>"#,
+      r#"<div class="itemurl"><a href="https://sprain.bandcamp.com/album/as-lost-through-collision?from=search">https://sprain.bandcamp.com/album/as-lost-through-collision</a></div>"#,
+      r#"</div></li>"#,
+      r#"</ul>"#
+    );
+    let result = parse_bandcamp_search_result(file_content)?;
+    assert_eq!(result.albums.len(), 1);
+    assert_eq!(result.albums[0].name, "As Lost Through Collision");
+    assert_eq!(result.albums[0].artist_name, "Sprain");
+    assert_eq!(
+      result.albums[0].url,
+      "https://sprain.bandcamp.com/album/as-lost-through-collision"
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,95 @@
+use super::{
+  dom::HtmlParser,
+  parsed_file_data::{ParsedGenre, ParsedGenreTree},
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use tracing::instrument;
+
+#[instrument(skip(file_content))]
+pub fn parse_genre_tree(file_content: &str) -> Result<ParsedGenreTree> {
+  let parser = HtmlParser::try_from(file_content)?;
+
+  // Each list item holds its genre's link followed by the nested lists of its subgenres, so the
+  // first link is the item's own genre and the rest are its descendants
+  let items = parser
+    .query_by_selector(&[".hierarchy_list_item"], None)
+    .into_iter()
+    .filter_map(|item| {
+      let names = parser
+        .query_by_selector(&["a.genre"], Some(item))
+        .into_iter()
+        .filter_map(|tag| parser.find_tag_text(tag))
+        .collect::<Vec<_>>();
+      let (name, descendants) = names.split_first()?;
+      Some((name.clone(), descendants.to_vec()))
+    })
+    .collect::<Vec<_>>();
+
+  if items.is_empty() {
+    return Err(anyhow!("No genres found"));
+  }
+
+  // A genre's parent is the closest item containing it, the one with the fewest descendants
+  let mut parents: HashMap<&str, (&str, usize)> = HashMap::new();
+  for (name, descendants) in &items {
+    for descendant in descendants {
+      let parent = parents
+        .entry(descendant.as_str())
+        .or_insert((name.as_str(), descendants.len()));
+      if descendants.len() < parent.1 {
+        *parent = (name.as_str(), descendants.len());
+      }
+    }
+  }
+
+  let genres = items
+    .iter()
+    .map(|(name, _)| ParsedGenre {
+      name: name.clone(),
+      parent: parents
+        .get(name.as_str())
+        .map(|(parent, _)| parent.to_string()),
+    })
+    .collect::<Vec<_>>();
+
+  Ok(ParsedGenreTree { genres })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_genre_tree_parser() -> Result<()> {
+    let file_content = concat!(
+      r#"<ul class="hierarchy_list">"#,
+      r#"<li class="hierarchy_list_item"><a class="genre" href="/genre/rock/">Rock</a>"#,
+      r#"<ul class="hierarchy_list">"#,
+      r#"<li class="hierarchy_list_item"><a class="genre" href="/genre/post-rock/">Post-Rock</a></li>"#,
+      r#"<li class="hierarchy_list_item"><a class="genre" href="/genre/psychedelic-rock/">Psychedelic Rock</a>"#,
+      r#"<ul class="hierarchy_list">"#,
+      r#"<li class="hierarchy_list_item"><a class="genre" href="/genre/space-rock/">Space Rock</a></li>"#,
+      r#"</ul></li>"#,
+      r#"</ul></li>"#,
+      r#"<li class="hierarchy_list_item"><a class="genre" href="/genre/ambient/">Ambient</a></li>"#,
+      r#"</ul>"#
+    );
+    let result = parse_genre_tree(file_content)?;
+    let genre = |name: &str, parent: Option<&str>| ParsedGenre {
+      name: name.to_string(),
+      parent: parent.map(|parent| parent.to_string()),
+    };
+    assert_eq!(
+      result.genres,
+      vec![
+        genre("Rock", None),
+        genre("Post-Rock", Some("Rock")),
+        genre("Psychedelic Rock", Some("Rock")),
+        genre("Space Rock", Some("Psychedelic Rock")),
+        genre("Ambient", None),
+      ]
+    );
+    Ok(())
+  }
+}
@@ -1,13 +1,16 @@
 mod album;
 mod album_search_result;
 mod artist;
+mod bandcamp_search_result;
 mod chart;
 mod dom;
+mod genre_tree;
 mod list_segment;
 pub mod parse;
 pub mod parsed_file_data;
 pub mod parser_event_subscribers;
 pub mod parser_failure_repository;
+pub mod parser_fixtures;
 pub mod parser_jobs;
 pub mod parser_service;
 mod util;
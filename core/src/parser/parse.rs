@@ -5,14 +5,31 @@ use crate::{
   files::file_metadata::{file_name::FileName, page_type::PageType},
   parser::{
     album::parse_album, album_search_result::parse_album_search_result, artist::parse_artist,
-    chart::parse_chart,
+    bandcamp_search_result::parse_bandcamp_search_result, chart::parse_chart,
+    genre_tree::parse_genre_tree,
   },
 };
 use anyhow::Result;
 use std::sync::Arc;
-use tracing::{info, instrument, warn};
+use tracing::{error, info, instrument, warn};
 use ulid::Ulid;
 
+pub fn parse_content(page_type: PageType, file_content: &str) -> Result<ParsedFileData> {
+  match page_type {
+    PageType::Chart => parse_chart(file_content).map(ParsedFileData::Chart),
+    PageType::Album => parse_album(file_content).map(ParsedFileData::Album),
+    PageType::Artist => parse_artist(file_content).map(ParsedFileData::Artist),
+    PageType::AlbumSearchResult => {
+      parse_album_search_result(file_content).map(ParsedFileData::AlbumSearchResult)
+    }
+    PageType::ListSegment => parse_list_segment(file_content).map(ParsedFileData::ListSegment),
+    PageType::BandcampSearchResult => {
+      parse_bandcamp_search_result(file_content).map(ParsedFileData::BandcampSearchResult)
+    }
+    PageType::GenreTree => parse_genre_tree(file_content).map(ParsedFileData::GenreTree),
+  }
+}
+
 #[instrument(skip(app_context))]
 pub async fn parse_file_on_store(
   app_context: Arc<ApplicationContext>,
@@ -25,18 +42,22 @@ pub async fn parse_file_on_store(
     .get_file_content(&file_name)
     .await?;
 
-  let parse_result = match file_name.page_type() {
-    PageType::Chart => parse_chart(&file_content).map(ParsedFileData::Chart),
-    PageType::Album => parse_album(&file_content).map(ParsedFileData::Album),
-    PageType::Artist => parse_artist(&file_content).map(ParsedFileData::Artist),
-    PageType::AlbumSearchResult => {
-      parse_album_search_result(&file_content).map(ParsedFileData::AlbumSearchResult)
-    }
-    PageType::ListSegment => parse_list_segment(&file_content).map(ParsedFileData::ListSegment),
-  };
+  let parse_result = parse_content(file_name.page_type(), &file_content);
 
   let event = match &parse_result {
     Ok(file_data) => {
+      // Only delays the content's retention, so it mustn't hold back the parsed event
+      if let Err(e) = app_context
+        .file_interactor
+        .mark_file_parsed(&file_name)
+        .await
+      {
+        error!(
+          file_name = file_name.to_string(),
+          error = e.to_string(),
+          "Failed to mark file content parsed"
+        );
+      }
       info!(
         file_id = file_id.to_string(),
         file_name = file_name.to_string(),
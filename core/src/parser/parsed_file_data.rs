@@ -15,6 +15,11 @@ pub struct ParsedTrack {
   pub duration_seconds: Option<u32>,
   pub rating: Option<f32>,
   pub position: Option<String>,
+  /**
+   * Set on compilations and splits, where tracks are credited to their own artists
+   */
+  #[serde(default)]
+  pub artists: Vec<ParsedArtistReference>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -42,6 +47,12 @@ pub struct ParsedAlbum {
   pub cover_image_url: Option<String>,
   #[serde(default)]
   pub spotify_id: Option<String>,
+  /**
+   * Compilations credited to "Various Artists". The placeholder artist is left out of `artists`,
+   * the tracks carry the actual artists instead.
+   */
+  #[serde(default)]
+  pub is_various_artists: bool,
 }
 
 impl ParsedAlbum {
@@ -71,6 +82,11 @@ pub struct ParsedArtistAlbum {
 pub struct ParsedArtist {
   pub name: String,
   pub albums: Vec<ParsedArtistAlbum>,
+  /**
+   * Native script names and aliases listed on the artist page
+   */
+  #[serde(default)]
+  pub alternate_names: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -87,6 +103,32 @@ pub struct ParsedListSegment {
   pub albums: Vec<FileName>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedBandcampAlbum {
+  pub name: String,
+  pub artist_name: String,
+  pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedBandcampSearchResult {
+  pub albums: Vec<ParsedBandcampAlbum>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ParsedGenre {
+  pub name: String,
+  /**
+   * Unset for top level genres
+   */
+  pub parent: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedGenreTree {
+  pub genres: Vec<ParsedGenre>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", content = "data")]
 pub enum ParsedFileData {
@@ -95,4 +137,6 @@ pub enum ParsedFileData {
   Artist(ParsedArtist),
   AlbumSearchResult(ParsedAlbumSearchResult),
   ListSegment(ParsedListSegment),
+  BandcampSearchResult(ParsedBandcampSearchResult),
+  GenreTree(ParsedGenreTree),
 }
@@ -101,6 +101,16 @@ impl ParserFailureRepository {
     Ok(docs)
   }
 
+  /**
+   * Failures with the latest attempts first
+   */
+  pub async fn find_recent(&self, limit: usize) -> Result<Vec<ParserFailure>> {
+    let mut failures = self.find_many(None).await?;
+    failures.sort_by(|a, b| b.last_attempted_at.cmp(&a.last_attempted_at));
+    failures.truncate(limit);
+    Ok(failures)
+  }
+
   pub async fn aggregate_errors(
     &self,
     page_type: Option<PageType>,
@@ -0,0 +1,298 @@
+use super::parse::parse_content;
+use crate::files::{file_metadata::file_name::FileName, file_redaction::redact_html};
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  fmt, fs,
+  path::{Path, PathBuf},
+};
+
+pub const DEFAULT_FIXTURE_DIR: &str =
+  concat!(env!("CARGO_MANIFEST_DIR"), "/resources/test/fixtures");
+
+/**
+ * Stripped from every captured page on top of the configured redaction selectors. No parser reads
+ * them, and they're where session and tracking details end up.
+ */
+const ANONYMIZED_SELECTORS: [&str; 4] = ["script", "style", "noscript", "iframe"];
+
+lazy_static! {
+  static ref COMMENT_RE: Regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
+  static ref SLUG_RE: Regex = Regex::new(r"[^A-Za-z0-9_-]+").unwrap();
+}
+
+/**
+ * A recorded page's parse as of its capture, stored as JSON next to the page's HTML
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserFixture {
+  pub file_name: FileName,
+  pub recorded_at: NaiveDateTime,
+  pub expected: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserFixtureFieldDiff {
+  /**
+   * e.g. "data.tracks[2].name"
+   */
+  pub path: String,
+  /**
+   * Null when the field is missing on that side
+   */
+  pub expected: Value,
+  pub actual: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParserFixtureReport {
+  pub file_name: FileName,
+  /**
+   * Set when the page no longer parses at all
+   */
+  pub error: Option<String>,
+  pub diffs: Vec<ParserFixtureFieldDiff>,
+}
+
+impl ParserFixtureReport {
+  pub fn is_ok(&self) -> bool {
+    self.error.is_none() && self.diffs.is_empty()
+  }
+}
+
+impl fmt::Display for ParserFixtureReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.file_name)?;
+    if let Some(error) = &self.error {
+      write!(f, ": {}", error)?;
+    }
+    for diff in &self.diffs {
+      write!(
+        f,
+        "\n  {}: expected {}, got {}",
+        diff.path, diff.expected, diff.actual
+      )?;
+    }
+    Ok(())
+  }
+}
+
+pub fn fixture_slug(file_name: &FileName) -> String {
+  SLUG_RE
+    .replace_all(&file_name.to_string(), "_")
+    .trim_matches('_')
+    .to_string()
+}
+
+fn fixture_paths(dir: &Path, file_name: &FileName) -> (PathBuf, PathBuf) {
+  let slug = fixture_slug(file_name);
+  (
+    dir.join(format!("{}.html", slug)),
+    dir.join(format!("{}.json", slug)),
+  )
+}
+
+pub fn anonymize_html(html: &str, redaction_selectors: &[String]) -> Result<String> {
+  let selectors = ANONYMIZED_SELECTORS
+    .iter()
+    .map(|selector| selector.to_string())
+    .chain(redaction_selectors.iter().cloned())
+    .collect::<Vec<_>>();
+  let redacted = redact_html(html, &selectors)?;
+  Ok(COMMENT_RE.replace_all(&redacted, "").to_string())
+}
+
+/**
+ * Every field that differs between two parses, down to the leaf values
+ */
+pub fn diff_json(expected: &Value, actual: &Value) -> Vec<ParserFixtureFieldDiff> {
+  let mut diffs = vec![];
+  collect_diffs("", expected, actual, &mut diffs);
+  diffs
+}
+
+fn collect_diffs(
+  path: &str,
+  expected: &Value,
+  actual: &Value,
+  diffs: &mut Vec<ParserFixtureFieldDiff>,
+) {
+  match (expected, actual) {
+    (Value::Object(expected), Value::Object(actual)) => {
+      let mut keys = expected.keys().chain(actual.keys()).collect::<Vec<_>>();
+      keys.sort();
+      keys.dedup();
+      for key in keys {
+        let child_path = if path.is_empty() {
+          key.to_string()
+        } else {
+          format!("{}.{}", path, key)
+        };
+        collect_diffs(
+          &child_path,
+          expected.get(key).unwrap_or(&Value::Null),
+          actual.get(key).unwrap_or(&Value::Null),
+          diffs,
+        );
+      }
+    }
+    (Value::Array(expected), Value::Array(actual)) => {
+      for i in 0..expected.len().max(actual.len()) {
+        collect_diffs(
+          &format!("{}[{}]", path, i),
+          expected.get(i).unwrap_or(&Value::Null),
+          actual.get(i).unwrap_or(&Value::Null),
+          diffs,
+        );
+      }
+    }
+    _ if expected != actual => diffs.push(ParserFixtureFieldDiff {
+      path: path.to_string(),
+      expected: expected.clone(),
+      actual: actual.clone(),
+    }),
+    _ => {}
+  }
+}
+
+/**
+ * Anonymizes the page and records it with its current parse, replacing any earlier fixture for
+ * the same file
+ */
+pub fn capture_fixture(
+  dir: &Path,
+  file_name: &FileName,
+  html: &str,
+  redaction_selectors: &[String],
+) -> Result<ParserFixture> {
+  let html = anonymize_html(html, redaction_selectors)?;
+  let parsed = parse_content(file_name.page_type(), &html)?;
+  let fixture = ParserFixture {
+    file_name: file_name.clone(),
+    recorded_at: Utc::now().naive_utc(),
+    expected: serde_json::to_value(parsed)?,
+  };
+  let (html_path, json_path) = fixture_paths(dir, file_name);
+  fs::create_dir_all(dir)?;
+  fs::write(html_path, html)?;
+  fs::write(json_path, serde_json::to_string_pretty(&fixture)?)?;
+  Ok(fixture)
+}
+
+pub fn validate_fixture(dir: &Path, fixture: &ParserFixture) -> ParserFixtureReport {
+  let (html_path, _) = fixture_paths(dir, &fixture.file_name);
+  let actual = fs::read_to_string(&html_path)
+    .map_err(|e| anyhow!("Failed to read {}: {}", html_path.display(), e))
+    .and_then(|html| parse_content(fixture.file_name.page_type(), &html))
+    .and_then(|parsed| Ok(serde_json::to_value(parsed)?));
+  let (error, diffs) = match actual {
+    Ok(actual) => (None, diff_json(&fixture.expected, &actual)),
+    Err(e) => (Some(e.to_string()), vec![]),
+  };
+  ParserFixtureReport {
+    file_name: fixture.file_name.clone(),
+    error,
+    diffs,
+  }
+}
+
+/**
+ * Parses every recorded page again and compares the result with its fixture. A missing directory
+ * has no fixtures.
+ */
+pub fn validate_fixtures(dir: &Path) -> Result<Vec<ParserFixtureReport>> {
+  if !dir.exists() {
+    return Ok(vec![]);
+  }
+  let mut json_paths = fs::read_dir(dir)?
+    .map(|entry| entry.map(|entry| entry.path()))
+    .collect::<Result<Vec<_>, _>>()?
+    .into_iter()
+    .filter(|path| {
+      path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    })
+    .collect::<Vec<_>>();
+  json_paths.sort();
+  json_paths
+    .into_iter()
+    .map(|path| {
+      let fixture = serde_json::from_str::<ParserFixture>(&fs::read_to_string(&path)?)
+        .map_err(|e| anyhow!("Invalid fixture {}: {}", path.display(), e))?;
+      Ok(validate_fixture(dir, &fixture))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_resource;
+  use serde_json::json;
+  use ulid::Ulid;
+
+  #[test]
+  fn test_diff_json() {
+    let expected = json!({
+      "type": "Album",
+      "data": { "name": "Gentleman", "tracks": [{ "name": "A" }, { "name": "B" }] }
+    });
+    let actual = json!({
+      "type": "Album",
+      "data": { "name": "Gentleman", "tracks": [{ "name": "A" }], "rating": 3.91 }
+    });
+    assert_eq!(
+      diff_json(&expected, &actual),
+      vec![
+        ParserFixtureFieldDiff {
+          path: "data.rating".to_string(),
+          expected: Value::Null,
+          actual: json!(3.91),
+        },
+        ParserFixtureFieldDiff {
+          path: "data.tracks[1]".to_string(),
+          expected: json!({ "name": "B" }),
+          actual: Value::Null,
+        },
+      ]
+    );
+    assert!(diff_json(&expected, &expected).is_empty());
+  }
+
+  #[test]
+  fn test_anonymize_html() {
+    let html =
+      "<div><script>track()</script><!-- user 42 --><span class=\"user\">me</span>ok</div>";
+    assert_eq!(
+      anonymize_html(html, &[".user".to_string()]).unwrap(),
+      "<div>ok</div>"
+    );
+  }
+
+  #[test]
+  fn test_capture_and_validate_fixture() {
+    let dir = std::env::temp_dir().join(format!("lute-parser-fixtures-{}", Ulid::new()));
+    let file_name = FileName::try_from("release/album/fela-kuti/gentleman".to_string()).unwrap();
+    let mut fixture = capture_fixture(
+      &dir,
+      &file_name,
+      include_str!(test_resource!("album.html")),
+      &[],
+    )
+    .unwrap();
+    let reports = validate_fixtures(&dir).unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].is_ok());
+
+    fixture.expected["data"]["name"] = json!("Lady");
+    let report = validate_fixture(&dir, &fixture);
+    assert_eq!(report.diffs.len(), 1);
+    assert_eq!(report.diffs[0].path, "data.name");
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
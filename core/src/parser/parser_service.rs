@@ -2,22 +2,26 @@ use super::{
   parse::parse_file_on_store,
   parsed_file_data::{
     ParsedAlbum, ParsedAlbumSearchResult, ParsedArtist, ParsedArtistAlbum, ParsedArtistReference,
-    ParsedChartAlbum, ParsedCredit, ParsedFileData, ParsedListSegment, ParsedTrack,
+    ParsedBandcampAlbum, ParsedBandcampSearchResult, ParsedChartAlbum, ParsedCredit,
+    ParsedFileData, ParsedGenre, ParsedGenreTree, ParsedListSegment, ParsedTrack,
   },
   parser_failure_repository::{AggregatedError, ParserFailureRepository},
+  parser_fixtures::{
+    capture_fixture, fixture_slug, validate_fixtures, ParserFixtureReport, DEFAULT_FIXTURE_DIR,
+  },
 };
 use crate::{
   context::ApplicationContext,
   files::file_metadata::{file_name::FileName, page_type::PageType},
   proto::{
-    self, EnqueueRetriesRequest, GetAggregatedFailureErrorsReply,
-    GetAggregatedFailureErrorsRequest, ParseFileOnContentStoreReply,
-    ParseFileOnContentStoreRequest,
+    self, CaptureParserFixtureReply, CaptureParserFixtureRequest, EnqueueRetriesRequest,
+    GetAggregatedFailureErrorsReply, GetAggregatedFailureErrorsRequest,
+    ParseFileOnContentStoreReply, ParseFileOnContentStoreRequest, ValidateParserFixturesReply,
   },
   scheduler::{job_name::JobName, scheduler::JobParametersBuilder},
 };
 use anyhow::Result;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 use tonic::{Request, Response, Status};
 use tracing::error;
 use ulid::Ulid;
@@ -36,6 +40,7 @@ impl TryFrom<i32> for PageType {
       1 => Ok(Self::Artist),
       2 => Ok(Self::Chart),
       3 => Ok(Self::AlbumSearchResult),
+      5 => Ok(Self::BandcampSearchResult),
       _ => Err(()),
     }
   }
@@ -76,6 +81,11 @@ impl From<ParsedTrack> for proto::ParsedTrack {
       duration_seconds: val.duration_seconds,
       rating: val.rating,
       position: val.position,
+      artists: val
+        .artists
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
     }
   }
 }
@@ -118,6 +128,7 @@ impl From<ParsedAlbum> for proto::ParsedAlbum {
       credits,
       cover_image_url: val.cover_image_url,
       spotify_id: val.spotify_id,
+      is_various_artists: val.is_various_artists,
     }
   }
 }
@@ -139,6 +150,7 @@ impl From<ParsedArtist> for proto::ParsedArtist {
     proto::ParsedArtist {
       name: val.name,
       albums,
+      alternate_names: val.alternate_names,
     }
   }
 }
@@ -173,6 +185,41 @@ impl From<ParsedListSegment> for proto::ParsedListSegment {
   }
 }
 
+impl From<ParsedBandcampAlbum> for proto::ParsedBandcampAlbum {
+  fn from(val: ParsedBandcampAlbum) -> Self {
+    proto::ParsedBandcampAlbum {
+      name: val.name,
+      artist_name: val.artist_name,
+      url: val.url,
+    }
+  }
+}
+
+impl From<ParsedBandcampSearchResult> for proto::ParsedBandcampSearchResult {
+  fn from(val: ParsedBandcampSearchResult) -> Self {
+    proto::ParsedBandcampSearchResult {
+      albums: val.albums.into_iter().map(|album| album.into()).collect(),
+    }
+  }
+}
+
+impl From<ParsedGenre> for proto::ParsedGenre {
+  fn from(val: ParsedGenre) -> Self {
+    proto::ParsedGenre {
+      name: val.name,
+      parent: val.parent,
+    }
+  }
+}
+
+impl From<ParsedGenreTree> for proto::ParsedGenreTree {
+  fn from(val: ParsedGenreTree) -> Self {
+    proto::ParsedGenreTree {
+      genres: val.genres.into_iter().map(|genre| genre.into()).collect(),
+    }
+  }
+}
+
 impl From<ParsedFileData> for proto::ParsedFileData {
   fn from(val: ParsedFileData) -> Self {
     match val {
@@ -199,6 +246,32 @@ impl From<ParsedFileData> for proto::ParsedFileData {
       ParsedFileData::ListSegment(data) => proto::ParsedFileData {
         data: Some(proto::parsed_file_data::Data::ListSegment(data.into())),
       },
+      ParsedFileData::BandcampSearchResult(data) => proto::ParsedFileData {
+        data: Some(proto::parsed_file_data::Data::BandcampSearchResult(
+          data.into(),
+        )),
+      },
+      ParsedFileData::GenreTree(data) => proto::ParsedFileData {
+        data: Some(proto::parsed_file_data::Data::GenreTree(data.into())),
+      },
+    }
+  }
+}
+
+impl From<ParserFixtureReport> for proto::ParserFixtureReport {
+  fn from(val: ParserFixtureReport) -> Self {
+    Self {
+      file_name: val.file_name.to_string(),
+      error: val.error,
+      diffs: val
+        .diffs
+        .into_iter()
+        .map(|diff| proto::ParserFixtureFieldDiff {
+          path: diff.path,
+          expected: diff.expected.to_string(),
+          actual: diff.actual.to_string(),
+        })
+        .collect(),
     }
   }
 }
@@ -210,6 +283,18 @@ impl ParserService {
       app_context,
     }
   }
+
+  fn fixture_dir(&self) -> PathBuf {
+    PathBuf::from(
+      self
+        .app_context
+        .settings
+        .parser
+        .fixture_dir
+        .as_deref()
+        .unwrap_or(DEFAULT_FIXTURE_DIR),
+    )
+  }
 }
 
 #[tonic::async_trait]
@@ -321,4 +406,67 @@ impl proto::ParserService for ParserService {
     }
     Ok(Response::new(()))
   }
+
+  async fn capture_parser_fixture(
+    &self,
+    request: Request<CaptureParserFixtureRequest>,
+  ) -> Result<Response<CaptureParserFixtureReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let content = self
+      .app_context
+      .file_interactor
+      .get_file_content(&file_name)
+      .await
+      .map_err(|e| {
+        error!(err = e.to_string(), "Failed to get file content");
+        Status::not_found("Failed to get file content")
+      })?;
+    let redaction_selectors = self
+      .app_context
+      .settings
+      .file
+      .redaction
+      .as_ref()
+      .map(|redaction| redaction.selectors.clone())
+      .unwrap_or_default();
+    let dir = self.fixture_dir();
+    let fixture_path = dir.join(format!("{}.json", fixture_slug(&file_name)));
+    let fixture = tokio::task::spawn_blocking(move || {
+      capture_fixture(&dir, &file_name, &content, &redaction_selectors)
+    })
+    .await
+    .map_err(|e| Status::internal(e.to_string()))?
+    .map_err(|e| {
+      error!(err = e.to_string(), "Failed to capture parser fixture");
+      Status::internal(format!("Failed to capture parser fixture: {}", e))
+    })?;
+    let data = serde_json::from_value::<ParsedFileData>(fixture.expected).map_err(|e| {
+      error!(err = e.to_string(), "Failed to read captured parse");
+      Status::internal("Failed to read captured parse")
+    })?;
+    Ok(Response::new(CaptureParserFixtureReply {
+      fixture_path: fixture_path.display().to_string(),
+      data: Some(data.into()),
+    }))
+  }
+
+  async fn validate_parser_fixtures(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<ValidateParserFixturesReply>, Status> {
+    let dir = self.fixture_dir();
+    let reports = tokio::task::spawn_blocking(move || validate_fixtures(&dir))
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .map_err(|e| {
+        error!(err = e.to_string(), "Failed to validate parser fixtures");
+        Status::internal(format!("Failed to validate parser fixtures: {}", e))
+      })?;
+    let failed = reports.iter().filter(|report| !report.is_ok()).count() as u32;
+    Ok(Response::new(ValidateParserFixturesReply {
+      reports: reports.into_iter().map(Into::into).collect(),
+      failed,
+    }))
+  }
 }
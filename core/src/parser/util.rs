@@ -1,6 +1,8 @@
 use anyhow::Result;
 use chrono::{Month, NaiveDate};
 
+pub const VARIOUS_ARTISTS_FILE_NAME: &str = "artist/various-artists";
+
 pub fn parse_release_date(date_string: String) -> Result<NaiveDate> {
   let date_string = date_string.trim();
   if date_string.is_empty() {
@@ -38,3 +40,42 @@ pub fn clean_artist_name(artist_name: &str) -> &str {
 pub fn clean_album_name(album_name: String) -> String {
   album_name.replace('’', "'")
 }
+
+/**
+ * Tracks credited to their own artists are rendered as "Artist - Title", this drops the artist
+ * part when it leads with one of the linked artists
+ */
+pub fn strip_track_artists(track_name: &str, artist_names: &[String]) -> String {
+  match track_name.split_once(" - ") {
+    Some((artists, title))
+      if artist_names
+        .iter()
+        .any(|artist_name| artists.starts_with(artist_name.as_str())) =>
+    {
+      title.trim().to_string()
+    }
+    _ => track_name.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_strip_track_artists() {
+    let artist_names = vec!["Aphex Twin".to_string(), "Squarepusher".to_string()];
+    assert_eq!(
+      strip_track_artists(
+        "Aphex Twin & Squarepusher - Freeman Hardy & Willis Acid",
+        &artist_names
+      ),
+      "Freeman Hardy & Willis Acid"
+    );
+    assert_eq!(
+      strip_track_artists("Untitled - Part 2", &artist_names),
+      "Untitled - Part 2"
+    );
+    assert_eq!(strip_track_artists("Xtal", &[]), "Xtal");
+  }
+}
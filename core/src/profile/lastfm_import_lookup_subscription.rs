@@ -0,0 +1,91 @@
+use super::{
+  listenbrainz_import_lookup_subscription::cumulative_listen_count_factor, profile::ProfileId,
+  spotify_import_lookup_subscription::SpotifyImportLookupSubscription,
+};
+use crate::{lastfm::lastfm_client::LastFmTopAlbum, lookup::AlbumSearchLookupQuery};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastFmAlbumScrobbles {
+  pub artist_name: String,
+  pub album_name: String,
+  pub scrobbles: u32,
+}
+
+impl LastFmAlbumScrobbles {
+  pub fn album_search_lookup_query(&self) -> AlbumSearchLookupQuery {
+    AlbumSearchLookupQuery::new(self.album_name.clone(), self.artist_name.clone())
+  }
+}
+
+/**
+ * Maps a listen count to a factor from 1 to 10 on a log scale relative to the most played album,
+ * so a handful of heavily played albums don't flatten everything else to 1.
+ */
+pub fn listen_count_factor(playcount: u32, max_playcount: u32) -> u32 {
+  if playcount == 0 || max_playcount == 0 {
+    return 1;
+  }
+  let ratio = (playcount as f64).ln_1p() / (max_playcount as f64).ln_1p();
+  (1.0 + ratio * 9.0).round().clamp(1.0, 10.0) as u32
+}
+
+pub fn build_lastfm_import_lookup_subscriptions(
+  profile_id: &ProfileId,
+  albums: Vec<LastFmTopAlbum>,
+) -> Vec<SpotifyImportLookupSubscription> {
+  let max_playcount = albums
+    .iter()
+    .map(|album| album.playcount)
+    .max()
+    .unwrap_or(0);
+  let mut subscriptions: HashMap<AlbumSearchLookupQuery, SpotifyImportLookupSubscription> =
+    HashMap::new();
+  for album in albums {
+    let factor = listen_count_factor(album.playcount, max_playcount);
+    let query = AlbumSearchLookupQuery::new(album.album_name, album.artist_name);
+    let subscription =
+      subscriptions
+        .entry(query.clone())
+        .or_insert_with(|| SpotifyImportLookupSubscription {
+          album_search_lookup_encoded_query: query.to_encoded_string(),
+          album_search_lookup_query: query,
+          profile_id: profile_id.clone(),
+          factor,
+        });
+    subscription.factor = subscription.factor.max(factor);
+  }
+  subscriptions.into_values().collect()
+}
+
+pub fn build_lastfm_scrobble_import_lookup_subscriptions(
+  profile_id: &ProfileId,
+  albums: Vec<LastFmAlbumScrobbles>,
+) -> Vec<SpotifyImportLookupSubscription> {
+  albums
+    .into_iter()
+    .map(|album| {
+      let query = album.album_search_lookup_query();
+      SpotifyImportLookupSubscription {
+        album_search_lookup_encoded_query: query.to_encoded_string(),
+        album_search_lookup_query: query,
+        profile_id: profile_id.clone(),
+        factor: cumulative_listen_count_factor(album.scrobbles),
+      }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_listen_count_factor() {
+    assert_eq!(listen_count_factor(500, 500), 10);
+    assert_eq!(listen_count_factor(1, 500), 2);
+    assert_eq!(listen_count_factor(0, 500), 1);
+    assert_eq!(listen_count_factor(50, 500), 7);
+  }
+}
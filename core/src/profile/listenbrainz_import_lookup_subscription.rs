@@ -0,0 +1,61 @@
+use super::{
+  profile::ProfileId, spotify_import_lookup_subscription::SpotifyImportLookupSubscription,
+};
+use crate::lookup::AlbumSearchLookupQuery;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListenBrainzAlbumListens {
+  pub artist_name: String,
+  pub album_name: String,
+  pub listens: u32,
+}
+
+impl ListenBrainzAlbumListens {
+  pub fn album_search_lookup_query(&self) -> AlbumSearchLookupQuery {
+    AlbumSearchLookupQuery::new(self.album_name.clone(), self.artist_name.clone())
+  }
+}
+
+/**
+ * Maps a cumulative track listen count to a factor from 1 to 10, gaining a point each time the
+ * count doubles. Unlike the Last.fm factor this doesn't depend on the most played album, so a sync
+ * only needs to touch albums with new listens.
+ */
+pub fn cumulative_listen_count_factor(listens: u32) -> u32 {
+  if listens == 0 {
+    return 1;
+  }
+  (listens.ilog2() + 1).clamp(1, 10)
+}
+
+pub fn build_listenbrainz_import_lookup_subscriptions(
+  profile_id: &ProfileId,
+  albums: Vec<ListenBrainzAlbumListens>,
+) -> Vec<SpotifyImportLookupSubscription> {
+  albums
+    .into_iter()
+    .map(|album| {
+      let query = album.album_search_lookup_query();
+      SpotifyImportLookupSubscription {
+        album_search_lookup_encoded_query: query.to_encoded_string(),
+        album_search_lookup_query: query,
+        profile_id: profile_id.clone(),
+        factor: cumulative_listen_count_factor(album.listens),
+      }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cumulative_listen_count_factor() {
+    assert_eq!(cumulative_listen_count_factor(0), 1);
+    assert_eq!(cumulative_listen_count_factor(1), 1);
+    assert_eq!(cumulative_listen_count_factor(12), 4);
+    assert_eq!(cumulative_listen_count_factor(100_000), 10);
+  }
+}
@@ -1,9 +1,20 @@
+pub mod lastfm_import_lookup_subscription;
+pub mod listenbrainz_import_lookup_subscription;
 pub mod profile;
+pub mod profile_analytics;
 pub mod profile_event_subscribers;
+pub mod profile_file_import;
+pub mod profile_goal;
+mod profile_goal_event_subscribers;
+pub mod profile_goal_repository;
 pub mod profile_interactor;
+pub mod profile_jobs;
 pub mod profile_repository;
 pub mod profile_service;
+pub mod profile_snapshot;
+pub mod profile_snapshot_repository;
 pub mod profile_summary;
 mod spotify_import_event_subscribers;
 pub mod spotify_import_lookup_subscription;
 pub mod spotify_import_repository;
+pub mod sqlite_profile_repository;
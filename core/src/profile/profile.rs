@@ -1,4 +1,4 @@
-use crate::files::file_metadata::file_name::FileName;
+use crate::{files::file_metadata::file_name::FileName, tenant::tenant_id::TenantId};
 use anyhow::{bail, Result};
 use chrono::NaiveDateTime;
 use lazy_static::lazy_static;
@@ -17,12 +17,38 @@ impl TryFrom<String> for ProfileId {
   type Error = anyhow::Error;
 
   fn try_from(value: String) -> Result<Self> {
-    if PROFILE_ID_RE.is_match(&value) {
-      Ok(Self(value))
+    let (tenant_id, local_id) = TenantId::unscope_key(&value);
+    Self::scoped(&tenant_id, local_id.to_string())
+  }
+}
+
+impl ProfileId {
+  /**
+   * Id of a tenant's profile. Tenants name their profiles independently, so the stored id is
+   * scoped by the tenant.
+   */
+  pub fn scoped(tenant_id: &TenantId, local_id: String) -> Result<Self> {
+    if PROFILE_ID_RE.is_match(&local_id) {
+      Ok(Self(tenant_id.scope_key(&local_id)))
     } else {
-      bail!("Invalid profile name: {}", value)
+      bail!("Invalid profile name: {}", local_id)
     }
   }
+
+  pub fn tenant_id(&self) -> TenantId {
+    TenantId::unscope_key(&self.0).0
+  }
+
+  /**
+   * The id as the profile's tenant knows it
+   */
+  pub fn local_id(&self) -> String {
+    TenantId::unscope_key(&self.0).1.to_string()
+  }
+
+  pub fn with_tenant(&self, tenant_id: &TenantId) -> Self {
+    Self(tenant_id.scope_key(&self.local_id()))
+  }
 }
 
 impl ToString for ProfileId {
@@ -37,10 +63,87 @@ pub struct Profile {
   pub name: String,
   pub albums: HashMap<FileName, u32>,
   pub last_updated_at: NaiveDateTime,
+  /**
+   * When each album was first put on the profile. Albums added before this was tracked have no
+   * entry.
+   */
+  #[serde(default)]
+  pub album_added_at: HashMap<FileName, NaiveDateTime>,
+  /**
+   * Album factors halve every this many days since the album was added when building
+   * recommendation seeds, unset to weigh every album by its factor alone
+   */
+  #[serde(default)]
+  pub time_decay_half_life_days: Option<u32>,
 }
 
 impl Profile {
   pub fn album_file_names(&self) -> Vec<FileName> {
     self.albums.keys().cloned().collect()
   }
+
+  /**
+   * Album factors with the profile's time decay applied, rescaled so they sum to the same total as
+   * the undecayed factors. This keeps the profile's weight in blended seeds and leaves room for
+   * recent albums to rise above old ones with the same factor. Albums without an addition date
+   * are treated as added with the oldest dated album.
+   */
+  pub fn decayed_albums(&self, now: NaiveDateTime) -> HashMap<FileName, u32> {
+    let Some(half_life_days) = self.time_decay_half_life_days.filter(|days| *days > 0) else {
+      return self.albums.clone();
+    };
+    let Some(oldest_added_at) = self.album_added_at.values().min() else {
+      return self.albums.clone();
+    };
+    let decayed = self
+      .albums
+      .iter()
+      .map(|(file_name, factor)| {
+        let added_at = self
+          .album_added_at
+          .get(file_name)
+          .unwrap_or(oldest_added_at);
+        let age_days = (now - *added_at).num_seconds().max(0) as f64 / 86400.0;
+        let decay = 0.5f64.powf(age_days / half_life_days as f64);
+        (file_name.clone(), *factor as f64 * decay)
+      })
+      .collect::<Vec<_>>();
+    let decayed_total = decayed.iter().map(|(_, factor)| factor).sum::<f64>();
+    if decayed_total <= 0.0 {
+      return self.albums.clone();
+    }
+    let scale = self.albums.values().sum::<u32>() as f64 / decayed_total;
+    decayed
+      .into_iter()
+      .map(|(file_name, factor)| (file_name, ((factor * scale).round() as u32).max(1)))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeDelta;
+
+  #[test]
+  fn test_decayed_albums() -> Result<()> {
+    let now = NaiveDateTime::parse_from_str("2024-06-01T00:00:00", "%Y-%m-%dT%H:%M:%S")?;
+    let recent = FileName::try_from("release/album/bjork/vulnicura")?;
+    let old = FileName::try_from("release/album/fka-twigs/lp1")?;
+    let mut profile = Profile {
+      albums: HashMap::from([(recent.clone(), 5), (old.clone(), 5)]),
+      album_added_at: HashMap::from([
+        (recent.clone(), now - TimeDelta::try_days(1).unwrap()),
+        (old.clone(), now - TimeDelta::try_days(3650).unwrap()),
+      ]),
+      ..Default::default()
+    };
+    assert_eq!(profile.decayed_albums(now), profile.albums);
+
+    profile.time_decay_half_life_days = Some(365);
+    let decayed = profile.decayed_albums(now);
+    assert_eq!(decayed.get(&recent), Some(&10));
+    assert_eq!(decayed.get(&old), Some(&1));
+    Ok(())
+  }
 }
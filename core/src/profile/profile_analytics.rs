@@ -0,0 +1,199 @@
+use super::profile::{Profile, ProfileId};
+use crate::{
+  albums::{album_collection_summary::AlbumCollectionSummary, album_read_model::AlbumReadModel},
+  files::file_metadata::file_name::FileName,
+  helpers::item_with_factor::{desc_sort_by_factor, ItemWithFactor},
+};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+
+const COLLABORATOR_LIMIT: usize = 25;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DecadeShare {
+  pub decade: u32,
+  pub factor: u32,
+  /**
+   * Fraction of the profile's dated albums, by factor, released in the decade
+   */
+  pub share: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProfileAnalytics {
+  pub id: ProfileId,
+  pub indexed_album_count: u32,
+  pub decades: Vec<DecadeShare>,
+  /**
+   * Shannon entropy, in bits, of the factor weighted primary genre distribution
+   */
+  pub genre_entropy: f32,
+  /**
+   * Genre entropy over the most it could be for as many genres, 0 when the profile sticks to one
+   * genre and 1 when it spreads evenly across all of them
+   */
+  pub genre_diversity: f32,
+  pub average_rating: f32,
+  /**
+   * Average rating of every album known to lute
+   */
+  pub catalog_average_rating: f32,
+  pub average_rating_delta: f32,
+  /**
+   * Credited artists other than the album's own, weighed by the factors of the albums they appear on
+   */
+  pub collaborators: Vec<ItemWithFactor>,
+}
+
+fn decade_shares(decades: &[ItemWithFactor]) -> Vec<DecadeShare> {
+  let total = decades.iter().map(|d| d.factor).sum::<u32>();
+  let mut shares = decades
+    .iter()
+    .filter_map(|d| {
+      d.item.parse::<u32>().ok().map(|decade| DecadeShare {
+        decade,
+        factor: d.factor,
+        share: d.factor as f32 / total as f32,
+      })
+    })
+    .collect::<Vec<_>>();
+  shares.sort_by_key(|share| share.decade);
+  shares
+}
+
+fn genre_entropy(genres: &[ItemWithFactor]) -> (f32, f32) {
+  let total = genres.iter().map(|g| g.factor).sum::<u32>();
+  if total == 0 {
+    return (0.0, 0.0);
+  }
+  let entropy = -genres
+    .iter()
+    .filter(|g| g.factor > 0)
+    .map(|g| {
+      let p = g.factor as f32 / total as f32;
+      p * p.log2()
+    })
+    .sum::<f32>();
+  let max_entropy = (genres.len() as f32).log2();
+  let diversity = if max_entropy > 0.0 {
+    entropy / max_entropy
+  } else {
+    0.0
+  };
+  (entropy, diversity)
+}
+
+fn collaborators(
+  albums: &[AlbumReadModel],
+  factor_map: &HashMap<FileName, u32>,
+) -> Vec<ItemWithFactor> {
+  let mut collaborators_map: HashMap<String, u32> = HashMap::new();
+  for album in albums {
+    let Some(factor) = factor_map.get(&album.file_name) else {
+      continue;
+    };
+    let album_artists = album
+      .artists
+      .iter()
+      .map(|artist| &artist.file_name)
+      .collect::<HashSet<_>>();
+    let credited = album
+      .credits
+      .iter()
+      .filter(|credit| !album_artists.contains(&credit.artist.file_name))
+      .map(|credit| credit.artist.name.clone())
+      .collect::<HashSet<_>>();
+    for name in credited {
+      collaborators_map
+        .entry(name)
+        .and_modify(|c| *c += factor)
+        .or_insert(*factor);
+    }
+  }
+  let mut collaborators = collaborators_map
+    .into_iter()
+    .map(|(item, factor)| ItemWithFactor { item, factor })
+    .collect::<Vec<_>>();
+  desc_sort_by_factor(&mut collaborators);
+  collaborators.truncate(COLLABORATOR_LIMIT);
+  collaborators
+}
+
+impl Profile {
+  #[instrument(skip_all, fields(id = %self.id.to_string(), len = album_read_models.len()))]
+  pub fn analyze(
+    &self,
+    album_read_models: &[AlbumReadModel],
+    catalog_average_rating: f32,
+  ) -> ProfileAnalytics {
+    let collection_summary = AlbumCollectionSummary::new(album_read_models, &self.albums);
+    let (genre_entropy, genre_diversity) = genre_entropy(&collection_summary.primary_genres);
+    let average_rating = if collection_summary.average_rating.is_nan() {
+      0.0
+    } else {
+      collection_summary.average_rating
+    };
+
+    ProfileAnalytics {
+      id: self.id.clone(),
+      indexed_album_count: album_read_models.len() as u32,
+      decades: decade_shares(&collection_summary.decades),
+      genre_entropy,
+      genre_diversity,
+      average_rating,
+      catalog_average_rating,
+      average_rating_delta: average_rating - catalog_average_rating,
+      collaborators: collaborators(album_read_models, &self.albums),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn items(factors: &[(&str, u32)]) -> Vec<ItemWithFactor> {
+    factors
+      .iter()
+      .map(|(item, factor)| ItemWithFactor {
+        item: item.to_string(),
+        factor: *factor,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_genre_entropy() {
+    assert_eq!(genre_entropy(&[]), (0.0, 0.0));
+    assert_eq!(genre_entropy(&items(&[("Jazz", 4)])), (0.0, 0.0));
+
+    let (entropy, diversity) = genre_entropy(&items(&[("Jazz", 2), ("Rock", 2)]));
+    assert!((entropy - 1.0).abs() < 1e-6);
+    assert!((diversity - 1.0).abs() < 1e-6);
+
+    let (entropy, diversity) = genre_entropy(&items(&[("Jazz", 3), ("Rock", 1)]));
+    assert!((entropy - 0.811_278).abs() < 1e-5);
+    assert!(diversity < 1.0);
+  }
+
+  #[test]
+  fn test_decade_shares() {
+    let shares = decade_shares(&items(&[("1990", 3), ("1970", 1)]));
+    assert_eq!(
+      shares,
+      vec![
+        DecadeShare {
+          decade: 1970,
+          factor: 1,
+          share: 0.25
+        },
+        DecadeShare {
+          decade: 1990,
+          factor: 3,
+          share: 0.75
+        },
+      ]
+    );
+  }
+}
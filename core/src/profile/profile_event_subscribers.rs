@@ -1,4 +1,7 @@
-use super::spotify_import_event_subscribers::build_spotify_import_event_subscribers;
+use super::{
+  profile_goal_event_subscribers::build_profile_goal_event_subscribers,
+  spotify_import_event_subscribers::build_spotify_import_event_subscribers,
+};
 use crate::{context::ApplicationContext, events::event_subscriber::EventSubscriber};
 use anyhow::Result;
 use std::sync::Arc;
@@ -7,6 +10,9 @@ pub fn build_profile_event_subscribers(
   app_context: Arc<ApplicationContext>,
 ) -> Result<Vec<EventSubscriber>> {
   let mut subscribers = vec![];
-  subscribers.extend(build_spotify_import_event_subscribers(app_context)?);
+  subscribers.extend(build_spotify_import_event_subscribers(Arc::clone(
+    &app_context,
+  ))?);
+  subscribers.extend(build_profile_goal_event_subscribers(app_context)?);
   Ok(subscribers)
 }
@@ -0,0 +1,220 @@
+use crate::{helpers::csv::parse_csv_records, lookup::AlbumSearchLookupQuery};
+use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+  static ref DISCOGS_ARTIST_SUFFIX: Regex = Regex::new(r"\s*\(\d+\)$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileImportFormat {
+  /**
+   * Columns: artist, album, rating, date. A header row is optional.
+   */
+  Csv,
+  /**
+   * A Discogs collection CSV export
+   */
+  Discogs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileImportRow {
+  pub artist_name: String,
+  pub album_name: String,
+  pub rating: Option<f32>,
+  pub date: Option<String>,
+}
+
+impl ProfileImportRow {
+  /**
+   * Ratings on a 5 point scale are doubled so both 5 and 10 point scales map to a factor from 1
+   * to 10. Unrated rows get a factor of 1.
+   */
+  pub fn factor(&self) -> u32 {
+    match self.rating {
+      Some(rating) if rating > 0.0 => {
+        let rating = if rating <= 5.0 { rating * 2.0 } else { rating };
+        (rating.round() as u32).clamp(1, 10)
+      }
+      _ => 1,
+    }
+  }
+
+  pub fn album_search_lookup_query(&self) -> AlbumSearchLookupQuery {
+    AlbumSearchLookupQuery::new(self.album_name.clone(), self.artist_name.clone())
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedProfileImportRow {
+  /**
+   * 1-based line number of the row in the uploaded file
+   */
+  pub line: usize,
+  pub row: Result<ProfileImportRow, String>,
+}
+
+struct ColumnIndexes {
+  artist: usize,
+  album: usize,
+  rating: Option<usize>,
+  date: Option<usize>,
+}
+
+fn find_column(headers: &[String], names: &[&str]) -> Option<usize> {
+  headers
+    .iter()
+    .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+}
+
+fn find_header_columns(headers: &[String], format: ProfileImportFormat) -> Option<ColumnIndexes> {
+  let (artist_names, album_names, rating_names, date_names): (&[&str], &[&str], &[&str], &[&str]) =
+    match format {
+      ProfileImportFormat::Csv => (
+        &["artist", "artist name", "artist_name"],
+        &["album", "album name", "album_name", "title"],
+        &["rating"],
+        &["date", "date added", "date_added"],
+      ),
+      ProfileImportFormat::Discogs => (&["artist"], &["title"], &["rating"], &["date added"]),
+    };
+  Some(ColumnIndexes {
+    artist: find_column(headers, artist_names)?,
+    album: find_column(headers, album_names)?,
+    rating: find_column(headers, rating_names),
+    date: find_column(headers, date_names),
+  })
+}
+
+fn clean_discogs_artist_name(artist_name: &str) -> String {
+  DISCOGS_ARTIST_SUFFIX
+    .replace(artist_name.trim().trim_end_matches('*'), "")
+    .to_string()
+}
+
+fn parse_row(
+  record: &[String],
+  columns: &ColumnIndexes,
+  format: ProfileImportFormat,
+) -> Result<ProfileImportRow, String> {
+  let get = |index: usize| {
+    record
+      .get(index)
+      .map(|value| value.trim().to_string())
+      .filter(|value| !value.is_empty())
+  };
+  let artist_name = get(columns.artist).ok_or("Missing artist name")?;
+  let artist_name = match format {
+    ProfileImportFormat::Csv => artist_name,
+    ProfileImportFormat::Discogs => clean_discogs_artist_name(&artist_name),
+  };
+  let album_name = get(columns.album).ok_or("Missing album name")?;
+  let rating = columns
+    .rating
+    .and_then(get)
+    .map(|rating| {
+      rating
+        .parse::<f32>()
+        .map_err(|_| format!("Invalid rating: {}", rating))
+    })
+    .transpose()?;
+  let date = columns.date.and_then(get);
+
+  Ok(ProfileImportRow {
+    artist_name,
+    album_name,
+    rating,
+    date,
+  })
+}
+
+pub fn parse_profile_import_rows(
+  format: ProfileImportFormat,
+  content: &str,
+) -> Result<Vec<ParsedProfileImportRow>> {
+  let mut records = parse_csv_records(content).into_iter().peekable();
+  let (_, first_record) = records.peek().ok_or(anyhow!("Import file is empty"))?;
+  let columns = match find_header_columns(first_record, format) {
+    Some(columns) => {
+      records.next();
+      columns
+    }
+    None if format == ProfileImportFormat::Csv => ColumnIndexes {
+      artist: 0,
+      album: 1,
+      rating: Some(2),
+      date: Some(3),
+    },
+    None => bail!("Missing Artist or Title column in Discogs export"),
+  };
+
+  Ok(
+    records
+      .map(|(line, record)| ParsedProfileImportRow {
+        line,
+        row: parse_row(&record, &columns, format),
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_csv_without_header() -> Result<()> {
+    let rows = parse_profile_import_rows(
+      ProfileImportFormat::Csv,
+      "billy woods,Aethiopes,4.5,2022-04-08\n\"Sakamoto, Ryuichi\",\"async\",,\n",
+    )?;
+    assert_eq!(rows.len(), 2);
+    let first = rows[0].row.clone().unwrap();
+    assert_eq!(first.artist_name, "billy woods");
+    assert_eq!(first.album_name, "Aethiopes");
+    assert_eq!(first.factor(), 9);
+    assert_eq!(first.date, Some("2022-04-08".to_string()));
+    let second = rows[1].row.clone().unwrap();
+    assert_eq!(second.artist_name, "Sakamoto, Ryuichi");
+    assert_eq!(second.rating, None);
+    assert_eq!(second.factor(), 1);
+    assert_eq!(rows[1].line, 2);
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_csv_with_header_and_invalid_rows() -> Result<()> {
+    let rows = parse_profile_import_rows(
+      ProfileImportFormat::Csv,
+      "Album,Artist,Rating\r\nMaps,billy woods,ten\r\n,billy woods,8\r\n",
+    )?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].line, 2);
+    assert_eq!(rows[0].row, Err("Invalid rating: ten".to_string()));
+    assert_eq!(rows[1].row, Err("Missing album name".to_string()));
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_discogs_export() -> Result<()> {
+    let rows = parse_profile_import_rows(
+      ProfileImportFormat::Discogs,
+      "Catalog#,Artist,Title,Label,Format,Rating,Released,release_id,CollectionFolder,Date Added\n\
+       BKR001,Armand Hammer (2)*,Haram,Backwoodz Studioz,\"LP, Album\",5,2021,17385163,Uncategorized,2023-01-02 10:00:00\n",
+    )?;
+    assert_eq!(rows.len(), 1);
+    let row = rows[0].row.clone().unwrap();
+    assert_eq!(row.artist_name, "Armand Hammer");
+    assert_eq!(row.album_name, "Haram");
+    assert_eq!(row.factor(), 10);
+    assert_eq!(row.date, Some("2023-01-02 10:00:00".to_string()));
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_discogs_export_requires_header() {
+    assert!(parse_profile_import_rows(ProfileImportFormat::Discogs, "a,b,c\n").is_err());
+  }
+}
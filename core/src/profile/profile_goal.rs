@@ -0,0 +1,185 @@
+use super::profile::ProfileId;
+use crate::files::file_metadata::file_name::FileName;
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/**
+ * Percentages of a goal's target that are announced as they're reached
+ */
+pub const GOAL_MILESTONES: [u32; 4] = [25, 50, 75, 100];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ProfileGoalKind {
+  /**
+   * Albums added to the profile that weren't on it before
+   */
+  NewAlbums,
+  /**
+   * Primary genres of added albums that the profile had no albums in when the goal was set
+   */
+  NewGenres,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileGoalStatus {
+  InProgress,
+  Completed,
+  Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileGoal {
+  pub id: String,
+  pub profile_id: ProfileId,
+  pub kind: ProfileGoalKind,
+  pub target: u32,
+  pub starts_at: NaiveDateTime,
+  pub ends_at: Option<NaiveDateTime>,
+  /**
+   * Genres already on the profile when the goal was set, which don't count as new
+   */
+  pub known_genres: Vec<String>,
+  /**
+   * Album file names or genres counted towards the target so far
+   */
+  pub progress_items: Vec<String>,
+  pub milestones_reached: Vec<u32>,
+}
+
+impl ProfileGoal {
+  pub fn new(
+    profile_id: ProfileId,
+    kind: ProfileGoalKind,
+    target: u32,
+    ends_at: Option<NaiveDateTime>,
+    known_genres: Vec<String>,
+  ) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      profile_id,
+      kind,
+      target,
+      starts_at: Utc::now().naive_utc(),
+      ends_at,
+      known_genres,
+      progress_items: vec![],
+      milestones_reached: vec![],
+    }
+  }
+
+  pub fn progress(&self) -> u32 {
+    self.progress_items.len() as u32
+  }
+
+  pub fn status(&self, now: NaiveDateTime) -> ProfileGoalStatus {
+    if self.progress() >= self.target {
+      ProfileGoalStatus::Completed
+    } else if self.ends_at.is_some_and(|ends_at| now >= ends_at) {
+      ProfileGoalStatus::Expired
+    } else {
+      ProfileGoalStatus::InProgress
+    }
+  }
+
+  /**
+   * Counts an album added to the profile at `added_at`, returning the milestones it newly reaches
+   */
+  pub fn record_album_added(
+    &mut self,
+    file_name: &FileName,
+    primary_genres: &[String],
+    added_at: NaiveDateTime,
+  ) -> Vec<u32> {
+    if added_at < self.starts_at || self.status(added_at) != ProfileGoalStatus::InProgress {
+      return vec![];
+    }
+    let items = match self.kind {
+      ProfileGoalKind::NewAlbums => vec![file_name.to_string()],
+      ProfileGoalKind::NewGenres => primary_genres
+        .iter()
+        .filter(|genre| !self.known_genres.contains(genre))
+        .cloned()
+        .collect(),
+    };
+    for item in items {
+      if !self.progress_items.contains(&item) {
+        self.progress_items.push(item);
+      }
+    }
+    let percent = (self.progress() * 100 / self.target.max(1)).min(100);
+    let reached = GOAL_MILESTONES
+      .into_iter()
+      .filter(|milestone| *milestone <= percent && !self.milestones_reached.contains(milestone))
+      .collect::<Vec<_>>();
+    self.milestones_reached.extend(reached.iter());
+    reached
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+  use chrono::TimeDelta;
+
+  #[test]
+  fn test_new_genres_goal_progress() -> Result<()> {
+    let mut goal = ProfileGoal::new(
+      ProfileId::try_from("default".to_string())?,
+      ProfileGoalKind::NewGenres,
+      2,
+      None,
+      vec!["Art Pop".to_string()],
+    );
+    let now = Utc::now().naive_utc();
+    let vulnicura = FileName::try_from("release/album/bjork/vulnicura")?;
+    let aethiopes = FileName::try_from("release/album/billy-woods/aethiopes")?;
+
+    assert_eq!(
+      goal.record_album_added(&vulnicura, &["Art Pop".to_string()], now),
+      Vec::<u32>::new()
+    );
+    assert_eq!(
+      goal.record_album_added(&aethiopes, &["Abstract Hip Hop".to_string()], now),
+      vec![25, 50]
+    );
+    assert_eq!(
+      goal.record_album_added(&aethiopes, &["Abstract Hip Hop".to_string()], now),
+      Vec::<u32>::new()
+    );
+    assert_eq!(goal.status(now), ProfileGoalStatus::InProgress);
+    assert_eq!(
+      goal.record_album_added(
+        &aethiopes,
+        &["Abstract Hip Hop".to_string(), "Jazz Rap".to_string()],
+        now
+      ),
+      vec![75, 100]
+    );
+    assert_eq!(goal.status(now), ProfileGoalStatus::Completed);
+    Ok(())
+  }
+
+  #[test]
+  fn test_expired_goal_ignores_additions() -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let mut goal = ProfileGoal::new(
+      ProfileId::try_from("default".to_string())?,
+      ProfileGoalKind::NewAlbums,
+      52,
+      Some(now + TimeDelta::try_days(1).unwrap()),
+      vec![],
+    );
+    let later = now + TimeDelta::try_days(2).unwrap();
+    assert_eq!(goal.status(later), ProfileGoalStatus::Expired);
+    goal.record_album_added(
+      &FileName::try_from("release/album/bjork/vulnicura")?,
+      &[],
+      later,
+    );
+    assert_eq!(goal.progress(), 0);
+    Ok(())
+  }
+}
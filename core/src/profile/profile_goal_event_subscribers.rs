@@ -0,0 +1,51 @@
+use crate::{
+  context::ApplicationContext,
+  event_handler,
+  events::{
+    event::{Event, Topic},
+    event_subscriber::{
+      EventData, EventHandler, EventSubscriber, EventSubscriberBuilder, EventSubscriberInteractor,
+      GroupingStrategy,
+    },
+  },
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub async fn track_goal_progress(
+  event_data: EventData,
+  app_context: Arc<ApplicationContext>,
+  _: Arc<EventSubscriberInteractor>,
+) -> Result<()> {
+  if let Event::ProfileAlbumAdded {
+    profile_id,
+    file_name,
+    ..
+  } = event_data.payload.event
+  {
+    app_context
+      .profile_interactor
+      .record_goal_progress(&profile_id, &file_name)
+      .await?;
+  }
+  Ok(())
+}
+
+pub fn build_profile_goal_event_subscribers(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Vec<EventSubscriber>> {
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("profile_goal_progress")
+    .topic(Topic::Profile)
+    .batch_size(250)
+    .app_context(Arc::clone(&app_context))
+    // A profile's additions are counted one at a time, so its goals aren't updated concurrently
+    .grouping_strategy(GroupingStrategy::GroupByKey(Arc::new(|row| {
+      match &row.payload.event {
+        Event::ProfileAlbumAdded { profile_id, .. } => profile_id.to_string(),
+        _ => "".to_string(),
+      }
+    })))
+    .handler(event_handler!(track_goal_progress))
+    .build()?])
+}
@@ -0,0 +1,69 @@
+use super::{profile::ProfileId, profile_goal::ProfileGoal};
+use crate::helpers::document_store::{DocumentFilter, DocumentStore};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct ProfileGoalRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "profile_goal";
+
+impl ProfileGoalRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, goal: ProfileGoal) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &goal.id.clone(), goal, None)
+      .await
+  }
+
+  pub async fn find(&self, id: &str) -> Result<Option<ProfileGoal>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<ProfileGoal>(COLLECTION, id)
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Goals of a profile, oldest first
+   */
+  pub async fn find_by_profile_id(&self, profile_id: &ProfileId) -> Result<Vec<ProfileGoal>> {
+    let mut goals = self
+      .doc_store
+      .find_many::<ProfileGoal>(
+        COLLECTION,
+        DocumentFilter::new()
+          .condition("profile_id", "=", profile_id.to_string())
+          .build(),
+        None,
+      )
+      .await?
+      .documents
+      .into_iter()
+      .map(|doc| doc.document)
+      .collect::<Vec<_>>();
+    goals.sort_by_key(|goal| goal.starts_at);
+    Ok(goals)
+  }
+
+  pub async fn delete(&self, id: &str) -> Result<()> {
+    self.doc_store.delete(COLLECTION, id).await
+  }
+
+  pub async fn delete_by_profile_id(&self, profile_id: &ProfileId) -> Result<()> {
+    let keys = self
+      .find_by_profile_id(profile_id)
+      .await?
+      .into_iter()
+      .map(|goal| goal.id)
+      .collect();
+    self.doc_store.delete_many(COLLECTION, keys).await
+  }
+}
@@ -1,30 +1,59 @@
 use super::{
+  lastfm_import_lookup_subscription::{
+    build_lastfm_import_lookup_subscriptions, build_lastfm_scrobble_import_lookup_subscriptions,
+    LastFmAlbumScrobbles,
+  },
+  listenbrainz_import_lookup_subscription::{
+    build_listenbrainz_import_lookup_subscriptions, ListenBrainzAlbumListens,
+  },
   profile::{Profile, ProfileId},
-  profile_repository::ProfileRepository,
+  profile_analytics::ProfileAnalytics,
+  profile_file_import::{parse_profile_import_rows, ProfileImportFormat},
+  profile_goal::{ProfileGoal, ProfileGoalKind},
+  profile_goal_repository::ProfileGoalRepository,
+  profile_repository::{ProfileRepository, RedisProfileRepository},
+  profile_snapshot::{ProfileDiff, ProfileSnapshot},
+  profile_snapshot_repository::ProfileSnapshotRepository,
   profile_summary::ProfileSummary,
   spotify_import_lookup_subscription::{
     build_spotify_import_lookup_subscriptions, SpotifyImportLookupSubscription,
   },
   spotify_import_repository::SpotifyImportRepository,
+  sqlite_profile_repository::SqliteProfileRepository,
 };
 use crate::{
   albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  collections::collection_repository::CollectionRepository,
   events::{
     event::{Event, EventPayloadBuilder, Topic},
     event_publisher::EventPublisher,
   },
   files::file_metadata::file_name::FileName,
   helpers::document_store::DocumentStore,
+  lastfm::lastfm_client::LastFmClient,
+  listening::listening_event_repository::ListeningEventRepository,
   lookup::{
     AlbumSearchLookup, AlbumSearchLookupDiscriminants, AlbumSearchLookupQuery, LookupInteractor,
+    LookupLane,
   },
+  music_service::music_service_client::MusicServiceClient,
+  settings::StorageMode,
   spotify::spotify_client::{SpotifyClient, SpotifyTrack},
+  sqlite::SqliteConnection,
+  tenant::tenant_id::TenantId,
 };
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use chrono::{NaiveDateTime, Utc};
 use futures::future::join_all;
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::{collections::HashMap, sync::Arc};
-use tracing::{instrument, warn};
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+};
+use tracing::{error, info, instrument, warn};
 
 pub struct PendingSpotifyImport {
   pub profile_id: ProfileId,
@@ -32,13 +61,41 @@ pub struct PendingSpotifyImport {
   pub album_search_lookup: AlbumSearchLookup,
 }
 
+pub struct ProfileImportRowResult {
+  pub line: usize,
+  pub artist_name: Option<String>,
+  pub album_name: Option<String>,
+  pub factor: u32,
+  pub album_search_lookup: Option<AlbumSearchLookup>,
+  pub error: Option<String>,
+}
+
+impl ProfileImportRowResult {
+  pub fn status_string(&self) -> String {
+    match &self.album_search_lookup {
+      Some(lookup) => lookup.status_string(),
+      None => "invalid".to_string(),
+    }
+  }
+}
+
 pub struct ProfileInteractor {
-  profile_repository: ProfileRepository,
+  profile_repository: Arc<dyn ProfileRepository + Send + Sync>,
   album_interactor: Arc<AlbumInteractor>,
   event_publisher: Arc<EventPublisher>,
   spotify_client: Arc<SpotifyClient>,
+  lastfm_client: Option<Arc<LastFmClient>>,
   lookup_interactor: Arc<LookupInteractor>,
   spotify_import_repository: SpotifyImportRepository,
+  profile_snapshot_repository: ProfileSnapshotRepository,
+  profile_goal_repository: ProfileGoalRepository,
+  collection_repository: CollectionRepository,
+  listening_event_repository: ListeningEventRepository,
+  /**
+   * Bumped whenever an album is put on or removed from any profile, so data derived from every
+   * profile knows when to rebuild
+   */
+  albums_version: AtomicU64,
 }
 
 impl ProfileInteractor {
@@ -48,20 +105,43 @@ impl ProfileInteractor {
     album_interactor: Arc<AlbumInteractor>,
     lookup_interactor: Arc<LookupInteractor>,
     spotify_client: Arc<SpotifyClient>,
+    lastfm_client: Option<Arc<LastFmClient>>,
     doc_store: Arc<DocumentStore>,
+    sqlite_connection: Arc<SqliteConnection>,
+    storage_mode: &StorageMode,
   ) -> Self {
-    Self {
-      profile_repository: ProfileRepository {
+    let profile_repository: Arc<dyn ProfileRepository + Send + Sync> = match storage_mode {
+      StorageMode::Redis => Arc::new(RedisProfileRepository {
         redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      }),
+      StorageMode::Sqlite => Arc::new(SqliteProfileRepository {
+        sqlite_connection: Arc::clone(&sqlite_connection),
+      }),
+    };
+    Self {
+      profile_repository,
       album_interactor,
       event_publisher,
       spotify_client,
+      lastfm_client,
       lookup_interactor,
       spotify_import_repository: SpotifyImportRepository::new(Arc::clone(&doc_store)),
+      profile_snapshot_repository: ProfileSnapshotRepository::new(Arc::clone(&doc_store)),
+      profile_goal_repository: ProfileGoalRepository::new(Arc::clone(&doc_store)),
+      collection_repository: CollectionRepository::new(Arc::clone(&doc_store)),
+      listening_event_repository: ListeningEventRepository::new(sqlite_connection),
+      albums_version: AtomicU64::new(0),
     }
   }
 
+  pub fn albums_version(&self) -> u64 {
+    self.albums_version.load(Ordering::SeqCst)
+  }
+
+  fn bump_albums_version(&self) {
+    self.albums_version.fetch_add(1, Ordering::SeqCst);
+  }
+
   pub async fn create_profile(&self, id: ProfileId, name: String) -> Result<Profile> {
     let profile = self.profile_repository.insert(id, name).await?;
     Ok(profile)
@@ -79,6 +159,10 @@ impl ProfileInteractor {
     self.profile_repository.get_all().await
   }
 
+  pub async fn get_tenant_profiles(&self, tenant_id: &TenantId) -> Result<Vec<Profile>> {
+    self.profile_repository.get_all_for_tenant(tenant_id).await
+  }
+
   async fn put_album_on_profile_with_model(
     &self,
     id: &ProfileId,
@@ -97,6 +181,7 @@ impl ProfileInteractor {
           e
         )
       })?;
+    self.bump_albums_version();
 
     if new_addition {
       self
@@ -162,6 +247,23 @@ impl ProfileInteractor {
     self.get_profile(id).await
   }
 
+  /**
+   * Sets the half-life of the profile's time decay, or turns it off
+   */
+  pub async fn set_time_decay(
+    &self,
+    id: &ProfileId,
+    half_life_days: Option<u32>,
+  ) -> Result<Profile> {
+    if half_life_days == Some(0) {
+      bail!("Time decay half-life must be at least a day");
+    }
+    self
+      .profile_repository
+      .set_time_decay(id, half_life_days)
+      .await
+  }
+
   pub async fn remove_album_from_profile(
     &self,
     id: &ProfileId,
@@ -170,7 +272,9 @@ impl ProfileInteractor {
     self
       .profile_repository
       .remove_album_from_profile(id, file_name)
-      .await
+      .await?;
+    self.bump_albums_version();
+    Ok(())
   }
 
   pub async fn get_profile_summary_and_albums(
@@ -198,12 +302,32 @@ impl ProfileInteractor {
     Ok(profile_summary)
   }
 
-  async fn import_spotify_tracks(
+  #[instrument(skip(self))]
+  pub async fn get_profile_analytics(&self, id: &ProfileId) -> Result<ProfileAnalytics> {
+    let profile = self.profile_repository.get(id).await?;
+    let albums = if !profile.albums.is_empty() {
+      self
+        .album_interactor
+        .find_many(profile.albums.keys().cloned().collect())
+        .await?
+        .into_values()
+        .collect()
+    } else {
+      vec![]
+    };
+    let catalog_average_rating = self.album_interactor.get_average_rating().await?;
+    Ok(profile.analyze(&albums, catalog_average_rating))
+  }
+
+  /**
+   * Looks up every subscription's album, adding resolved albums to the profile immediately. The
+   * rest are added by the lookup subscribers once their lookups complete.
+   */
+  async fn import_lookup_subscriptions(
     &self,
     id: &ProfileId,
-    spotify_tracks: Vec<SpotifyTrack>,
-  ) -> Result<()> {
-    let subscriptions = build_spotify_import_lookup_subscriptions(id, spotify_tracks);
+    subscriptions: Vec<SpotifyImportLookupSubscription>,
+  ) -> Result<Vec<AlbumSearchLookup>> {
     join_all(subscriptions.iter().map(|subscription| async move {
       self
         .spotify_import_repository
@@ -215,26 +339,26 @@ impl ProfileInteractor {
         .await
     }))
     .await;
-    let pairs = join_all(subscriptions.iter().map(|subscription| async move {
-      let lookup = self
-        .lookup_interactor
-        .search_album(
-          subscription
-            .album_search_lookup_query
-            .artist_name()
-            .to_string(),
-          subscription
-            .album_search_lookup_query
-            .album_name()
-            .to_string(),
-        )
-        .await
-        .expect("failed to search album");
-      (lookup, subscription)
-    }))
-    .await;
-    let complete_pairs = pairs
+    let lookups = self
+      .lookup_interactor
+      .search_albums(
+        subscriptions
+          .iter()
+          .map(|subscription| {
+            subscription
+              .album_search_lookup_query
+              .clone()
+              .with_lane(LookupLane::Background)
+          })
+          .collect(),
+      )
+      .await?;
+    let pairs = lookups
       .into_iter()
+      .zip(subscriptions.iter())
+      .collect::<Vec<_>>();
+    let complete_pairs = pairs
+      .iter()
       .filter(|(lookup, _)| lookup.status() == AlbumSearchLookupDiscriminants::AlbumParsed)
       .collect::<Vec<_>>();
     self
@@ -258,23 +382,182 @@ impl ProfileInteractor {
     }))
     .await;
 
+    Ok(pairs.into_iter().map(|(lookup, _)| lookup).collect())
+  }
+
+  async fn import_spotify_tracks(
+    &self,
+    id: &ProfileId,
+    spotify_tracks: Vec<SpotifyTrack>,
+  ) -> Result<()> {
+    let subscriptions = build_spotify_import_lookup_subscriptions(id, spotify_tracks);
+    self.import_lookup_subscriptions(id, subscriptions).await?;
     Ok(())
   }
 
+  #[instrument(skip(self, content))]
+  pub async fn import_albums_from_file(
+    &self,
+    id: &ProfileId,
+    format: ProfileImportFormat,
+    content: &str,
+  ) -> Result<Vec<ProfileImportRowResult>> {
+    self.profile_repository.get(id).await?;
+    let rows = parse_profile_import_rows(format, content)?;
+
+    let mut subscriptions: HashMap<AlbumSearchLookupQuery, SpotifyImportLookupSubscription> =
+      HashMap::new();
+    for row in rows.iter().filter_map(|row| row.row.as_ref().ok()) {
+      let query = row.album_search_lookup_query();
+      let subscription =
+        subscriptions
+          .entry(query.clone())
+          .or_insert_with(|| SpotifyImportLookupSubscription {
+            album_search_lookup_encoded_query: query.to_encoded_string(),
+            album_search_lookup_query: query,
+            profile_id: id.clone(),
+            factor: row.factor(),
+          });
+      subscription.factor = subscription.factor.max(row.factor());
+    }
+    let lookups = self
+      .import_lookup_subscriptions(id, subscriptions.into_values().collect())
+      .await?
+      .into_iter()
+      .map(|lookup| (lookup.query().clone(), lookup))
+      .collect::<HashMap<_, _>>();
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|parsed| match parsed.row {
+          Ok(row) => ProfileImportRowResult {
+            line: parsed.line,
+            factor: row.factor(),
+            album_search_lookup: lookups.get(&row.album_search_lookup_query()).cloned(),
+            artist_name: Some(row.artist_name),
+            album_name: Some(row.album_name),
+            error: None,
+          },
+          Err(error) => ProfileImportRowResult {
+            line: parsed.line,
+            artist_name: None,
+            album_name: None,
+            factor: 0,
+            album_search_lookup: None,
+            error: Some(error),
+          },
+        })
+        .collect(),
+    )
+  }
+
   pub async fn import_saved_spotify_tracks(&self, id: &ProfileId) -> Result<()> {
-    let spotify_tracks = self.spotify_client.get_saved_tracks().await?;
+    let spotify_tracks = self
+      .spotify_client
+      .for_tenant(&id.tenant_id())
+      .get_saved_tracks()
+      .await?;
     self.import_spotify_tracks(id, spotify_tracks).await
   }
 
+  /**
+   * Puts the albums saved in a streaming service's library on the profile, each with a factor of 1
+   */
+  pub async fn import_music_service_saved_albums(
+    &self,
+    id: &ProfileId,
+    client: &dyn MusicServiceClient,
+  ) -> Result<()> {
+    self.profile_repository.get(id).await?;
+    let mut subscriptions: HashMap<AlbumSearchLookupQuery, SpotifyImportLookupSubscription> =
+      HashMap::new();
+    for album in client.get_saved_albums().await? {
+      let query = AlbumSearchLookupQuery::new(
+        album.name,
+        album.artist_names.first().cloned().unwrap_or_default(),
+      );
+      subscriptions
+        .entry(query.clone())
+        .or_insert_with(|| SpotifyImportLookupSubscription {
+          album_search_lookup_encoded_query: query.to_encoded_string(),
+          album_search_lookup_query: query,
+          profile_id: id.clone(),
+          factor: 1,
+        });
+    }
+    self
+      .import_lookup_subscriptions(id, subscriptions.into_values().collect())
+      .await?;
+    Ok(())
+  }
+
   pub async fn import_spotify_playlist_tracks(
     &self,
     id: &ProfileId,
     playlist_id: &str,
   ) -> Result<()> {
-    let spotify_tracks = self.spotify_client.get_playlist_tracks(playlist_id).await?;
+    let spotify_tracks = self
+      .spotify_client
+      .for_tenant(&id.tenant_id())
+      .get_playlist_tracks(playlist_id)
+      .await?;
     self.import_spotify_tracks(id, spotify_tracks).await
   }
 
+  /**
+   * Imports the configured Last.fm user's top albums into the configured profile, weighting each
+   * album by its listen count
+   */
+  pub async fn import_lastfm_top_albums(&self) -> Result<()> {
+    let lastfm_client = self
+      .lastfm_client
+      .as_ref()
+      .ok_or_else(|| anyhow!("Last.fm is not configured"))?;
+    let settings = lastfm_client.settings();
+    let id = ProfileId::try_from(settings.profile_id.clone())?;
+    if self.profile_repository.find(&id).await?.is_none() {
+      self
+        .create_profile(id.clone(), format!("Last.fm: {}", settings.username))
+        .await?;
+    }
+    let albums = lastfm_client.get_top_albums().await?;
+    let subscriptions = build_lastfm_import_lookup_subscriptions(&id, albums);
+    info!(
+      profile_id = id.to_string(),
+      count = subscriptions.len(),
+      "Importing Last.fm top albums"
+    );
+    self.import_lookup_subscriptions(&id, subscriptions).await?;
+    Ok(())
+  }
+
+  /**
+   * Puts albums on the profile weighted by their cumulative Last.fm scrobble counts
+   */
+  pub async fn import_lastfm_album_scrobbles(
+    &self,
+    id: &ProfileId,
+    albums: Vec<LastFmAlbumScrobbles>,
+  ) -> Result<()> {
+    let subscriptions = build_lastfm_scrobble_import_lookup_subscriptions(id, albums);
+    self.import_lookup_subscriptions(id, subscriptions).await?;
+    Ok(())
+  }
+
+  /**
+   * Puts albums on the profile weighted by their cumulative ListenBrainz listen counts
+   */
+  pub async fn import_listenbrainz_album_listens(
+    &self,
+    id: &ProfileId,
+    albums: Vec<ListenBrainzAlbumListens>,
+  ) -> Result<()> {
+    let subscriptions = build_listenbrainz_import_lookup_subscriptions(id, albums);
+    self.import_lookup_subscriptions(id, subscriptions).await?;
+    Ok(())
+  }
+
   pub async fn find_spotify_import_subscriptions_by_query(
     &self,
     album_search_lookup_query: &AlbumSearchLookupQuery,
@@ -338,8 +621,197 @@ impl ProfileInteractor {
     Ok(pending_imports)
   }
 
+  /**
+   * Saves the profile's current album/factor map, unless it's unchanged since the latest snapshot
+   */
+  pub async fn snapshot_profile(&self, id: &ProfileId) -> Result<Option<ProfileSnapshot>> {
+    let profile = self.profile_repository.get(id).await?;
+    let latest = self
+      .profile_snapshot_repository
+      .find_latest_by_profile_id(id)
+      .await?;
+    if latest.is_some_and(|latest| latest.albums == profile.albums) {
+      return Ok(None);
+    }
+    let snapshot = ProfileSnapshot::new(&profile);
+    self
+      .profile_snapshot_repository
+      .put(snapshot.clone())
+      .await?;
+    Ok(Some(snapshot))
+  }
+
+  /**
+   * Snapshots every profile, returning how many changed. A profile that fails to snapshot is
+   * logged and skipped.
+   */
+  pub async fn snapshot_all_profiles(&self) -> Result<usize> {
+    let mut count = 0;
+    for profile in self.profile_repository.get_all().await? {
+      match self.snapshot_profile(&profile.id).await {
+        Ok(Some(_)) => count += 1,
+        Ok(None) => {}
+        Err(e) => error!(
+          profile_id = profile.id.to_string(),
+          err = e.to_string(),
+          "Failed to snapshot profile"
+        ),
+      }
+    }
+    Ok(count)
+  }
+
+  pub async fn get_profile_snapshots(&self, id: &ProfileId) -> Result<Vec<ProfileSnapshot>> {
+    self
+      .profile_snapshot_repository
+      .find_by_profile_id(id)
+      .await
+  }
+
+  /**
+   * Diffs a snapshot against a later snapshot of the same profile, or against the profile's
+   * current state when no later snapshot is given. None when either snapshot doesn't exist.
+   */
+  pub async fn diff_profile_snapshots(
+    &self,
+    tenant_id: &TenantId,
+    from_snapshot_id: &str,
+    to_snapshot_id: Option<&str>,
+  ) -> Result<Option<ProfileDiff>> {
+    let Some(from) = self
+      .profile_snapshot_repository
+      .find(from_snapshot_id)
+      .await?
+      .filter(|snapshot| snapshot.profile_id.tenant_id() == *tenant_id)
+    else {
+      return Ok(None);
+    };
+    let to_albums = match to_snapshot_id {
+      Some(to_snapshot_id) => {
+        let Some(to) = self
+          .profile_snapshot_repository
+          .find(to_snapshot_id)
+          .await?
+        else {
+          return Ok(None);
+        };
+        if to.profile_id != from.profile_id {
+          return Err(anyhow!("Snapshots belong to different profiles"));
+        }
+        to.albums
+      }
+      None => self.profile_repository.get(&from.profile_id).await?.albums,
+    };
+    Ok(Some(from.diff(&to_albums)))
+  }
+
   pub async fn delete_profile(&self, id: &ProfileId) -> Result<()> {
-    self.profile_repository.delete(id).await
+    self.profile_repository.delete(id).await?;
+    self.bump_albums_version();
+    self
+      .profile_snapshot_repository
+      .delete_by_profile_id(id)
+      .await?;
+    self
+      .profile_goal_repository
+      .delete_by_profile_id(id)
+      .await?;
+    self.collection_repository.delete_by_profile_id(id).await?;
+    self
+      .listening_event_repository
+      .delete_by_profile_id(id)
+      .await
+  }
+
+  pub async fn create_goal(
+    &self,
+    id: &ProfileId,
+    kind: ProfileGoalKind,
+    target: u32,
+    ends_at: Option<NaiveDateTime>,
+  ) -> Result<ProfileGoal> {
+    if target == 0 {
+      bail!("Goal target must be greater than zero");
+    }
+    let known_genres = match kind {
+      ProfileGoalKind::NewAlbums => vec![],
+      ProfileGoalKind::NewGenres => self
+        .get_profile_summary(id)
+        .await?
+        .primary_genres
+        .into_iter()
+        .map(|genre| genre.item)
+        .collect(),
+    };
+    let goal = ProfileGoal::new(id.clone(), kind, target, ends_at, known_genres);
+    self.profile_goal_repository.put(goal.clone()).await?;
+    Ok(goal)
+  }
+
+  pub async fn get_goals(&self, id: &ProfileId) -> Result<Vec<ProfileGoal>> {
+    self.profile_goal_repository.find_by_profile_id(id).await
+  }
+
+  pub async fn delete_goal(&self, tenant_id: &TenantId, goal_id: &str) -> Result<()> {
+    match self.profile_goal_repository.find(goal_id).await? {
+      Some(goal) if goal.profile_id.tenant_id() == *tenant_id => {
+        self.profile_goal_repository.delete(goal_id).await
+      }
+      _ => bail!("Goal not found: {}", goal_id),
+    }
+  }
+
+  /**
+   * Counts an album newly added to the profile towards its goals, publishing an event for each
+   * milestone reached
+   */
+  #[instrument(skip(self))]
+  pub async fn record_goal_progress(&self, id: &ProfileId, file_name: &FileName) -> Result<()> {
+    let goals = self.profile_goal_repository.find_by_profile_id(id).await?;
+    if goals.is_empty() {
+      return Ok(());
+    }
+    let primary_genres = self
+      .album_interactor
+      .find(file_name)
+      .await?
+      .map(|album| album.primary_genres)
+      .unwrap_or_default();
+    let now = Utc::now().naive_utc();
+    for mut goal in goals {
+      let previous_progress = goal.progress();
+      let milestones = goal.record_album_added(file_name, &primary_genres, now);
+      if goal.progress() == previous_progress {
+        continue;
+      }
+      self.profile_goal_repository.put(goal.clone()).await?;
+      for milestone in milestones {
+        info!(
+          profile_id = id.to_string(),
+          goal_id = goal.id,
+          milestone,
+          "Profile goal milestone reached"
+        );
+        self
+          .event_publisher
+          .publish(
+            Topic::Profile,
+            EventPayloadBuilder::default()
+              .key(format!("{}:{}", goal.id, milestone))
+              .event(Event::ProfileGoalMilestoneReached {
+                profile_id: id.clone(),
+                goal_id: goal.id.clone(),
+                kind: goal.kind,
+                target: goal.target,
+                progress: goal.progress(),
+                milestone,
+              })
+              .build()?,
+          )
+          .await?;
+      }
+    }
+    Ok(())
   }
 
   pub async fn clear_pending_spotify_imports(&self, profile_id: &ProfileId) -> Result<()> {
@@ -0,0 +1,48 @@
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn snapshot_profiles(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let count = app_context
+    .profile_interactor
+    .snapshot_all_profiles()
+    .await
+    .inspect_err(|e| error!(err = e.to_string(), "Failed to snapshot profiles"))?;
+  info!(count, "Snapshotted profiles");
+  Ok(())
+}
+
+pub async fn setup_profile_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::SnapshotProfiles)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(snapshot_profiles))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::SnapshotProfiles)
+        .interval(TimeDelta::try_days(1).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
@@ -1,6 +1,7 @@
 use super::profile::{Profile, ProfileId};
-use crate::files::file_metadata::file_name::FileName;
+use crate::{files::file_metadata::file_name::FileName, tenant::tenant_id::TenantId};
 use anyhow::{bail, Error, Result};
+use async_trait::async_trait;
 use chrono::Utc;
 use futures::future::join_all;
 use rustis::{
@@ -8,14 +9,58 @@ use rustis::{
   client::PooledClientManager,
   commands::{GenericCommands, JsonCommands, JsonGetOptions, SetCondition},
 };
-use std::sync::Arc;
+use std::{fmt::Debug, sync::Arc};
 use tracing::{instrument, warn};
 
-pub struct ProfileRepository {
+#[async_trait]
+pub trait ProfileRepository: Debug {
+  async fn find(&self, id: &ProfileId) -> Result<Option<Profile>>;
+  async fn get_all(&self) -> Result<Vec<Profile>>;
+  async fn exists(&self, id: &ProfileId) -> Result<bool>;
+  async fn insert(&self, id: ProfileId, name: String) -> Result<Profile>;
+  async fn delete(&self, id: &ProfileId) -> Result<()>;
+  async fn is_album_on_profile(&self, id: &ProfileId, album_file_name: &FileName) -> Result<bool>;
+  /**
+   * Sets the album's factor, returning the updated profile and whether the album is new to it
+   */
+  async fn put_album_on_profile(
+    &self,
+    id: &ProfileId,
+    album_file_name: &FileName,
+    factor: u32,
+  ) -> Result<(Profile, bool)>;
+  async fn remove_album_from_profile(
+    &self,
+    id: &ProfileId,
+    album_file_name: &FileName,
+  ) -> Result<()>;
+  async fn set_time_decay(&self, id: &ProfileId, half_life_days: Option<u32>) -> Result<Profile>;
+
+  async fn get(&self, id: &ProfileId) -> Result<Profile> {
+    match self.find(id).await? {
+      Some(profile) => Ok(profile),
+      None => bail!("Profile does not exist"),
+    }
+  }
+
+  async fn get_all_for_tenant(&self, tenant_id: &TenantId) -> Result<Vec<Profile>> {
+    Ok(
+      self
+        .get_all()
+        .await?
+        .into_iter()
+        .filter(|profile| profile.id.tenant_id() == *tenant_id)
+        .collect(),
+    )
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct RedisProfileRepository {
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
 }
 
-impl ProfileRepository {
+impl RedisProfileRepository {
   pub fn key(&self, id: &ProfileId) -> String {
     format!("profile:{}", id.to_string())
   }
@@ -24,7 +69,14 @@ impl ProfileRepository {
     format!("$.albums[\"{}\"]", album_file_name.to_string())
   }
 
-  pub async fn find(&self, id: &ProfileId) -> Result<Option<Profile>> {
+  pub fn profile_album_added_at_path(&self, album_file_name: &FileName) -> String {
+    format!("$.album_added_at[\"{}\"]", album_file_name.to_string())
+  }
+}
+
+#[async_trait]
+impl ProfileRepository for RedisProfileRepository {
+  async fn find(&self, id: &ProfileId) -> Result<Option<Profile>> {
     let connection = self.redis_connection_pool.get().await?;
     let json: Option<String> = connection
       .json_get(self.key(id), JsonGetOptions::default())
@@ -32,15 +84,7 @@ impl ProfileRepository {
     Ok(json.map(|json| serde_json::from_str(&json).unwrap()))
   }
 
-  pub async fn get(&self, id: &ProfileId) -> Result<Profile> {
-    let profile = self.find(id).await?;
-    match profile {
-      Some(profile) => Ok(profile),
-      None => bail!("Profile does not exist"),
-    }
-  }
-
-  pub async fn get_all(&self) -> Result<Vec<Profile>> {
+  async fn get_all(&self) -> Result<Vec<Profile>> {
     let connection = self.redis_connection_pool.get().await?;
     let keys: Vec<String> = connection.keys("profile:*").await?;
     let futures = keys.into_iter().map(|key| async {
@@ -61,13 +105,13 @@ impl ProfileRepository {
     Ok(profiles)
   }
 
-  pub async fn exists(&self, id: &ProfileId) -> Result<bool> {
+  async fn exists(&self, id: &ProfileId) -> Result<bool> {
     let connection = self.redis_connection_pool.get().await?;
     let result: usize = connection.exists(self.key(id)).await?;
     Ok(result == 1)
   }
 
-  pub async fn insert(&self, id: ProfileId, name: String) -> Result<Profile> {
+  async fn insert(&self, id: ProfileId, name: String) -> Result<Profile> {
     if self.exists(&id).await? {
       bail!("Profile already exists")
     }
@@ -76,6 +120,8 @@ impl ProfileRepository {
       name,
       last_updated_at: Utc::now().naive_utc(),
       albums: Default::default(),
+      album_added_at: Default::default(),
+      time_decay_half_life_days: None,
     };
     self
       .redis_connection_pool
@@ -91,7 +137,7 @@ impl ProfileRepository {
     Ok(profile)
   }
 
-  pub async fn delete(&self, id: &ProfileId) -> Result<()> {
+  async fn delete(&self, id: &ProfileId) -> Result<()> {
     if !self.exists(id).await? {
       bail!("Profile does not exist")
     }
@@ -100,11 +146,7 @@ impl ProfileRepository {
     Ok(())
   }
 
-  pub async fn is_album_on_profile(
-    &self,
-    id: &ProfileId,
-    album_file_name: &FileName,
-  ) -> Result<bool> {
+  async fn is_album_on_profile(&self, id: &ProfileId, album_file_name: &FileName) -> Result<bool> {
     if !self.exists(id).await? {
       bail!("Profile does not exist")
     }
@@ -119,7 +161,7 @@ impl ProfileRepository {
   }
 
   #[instrument(skip(self), name = "ProfileRepository::put_album_on_profile")]
-  pub async fn put_album_on_profile(
+  async fn put_album_on_profile(
     &self,
     id: &ProfileId,
     album_file_name: &FileName,
@@ -138,10 +180,24 @@ impl ProfileRepository {
         SetCondition::default(),
       )
       .await?;
+    if new_addition {
+      // Profiles created before additions were tracked don't have the map yet
+      connection
+        .json_set(self.key(id), "$.album_added_at", "{}", SetCondition::NX)
+        .await?;
+      connection
+        .json_set(
+          self.key(id),
+          self.profile_album_added_at_path(album_file_name),
+          serde_json::to_string(&Utc::now().naive_utc())?,
+          SetCondition::default(),
+        )
+        .await?;
+    }
     Ok((self.get(id).await?, new_addition))
   }
 
-  pub async fn remove_album_from_profile(
+  async fn remove_album_from_profile(
     &self,
     id: &ProfileId,
     album_file_name: &FileName,
@@ -153,6 +209,28 @@ impl ProfileRepository {
     connection
       .json_del(self.key(id), self.profile_album_path(album_file_name))
       .await?;
+    connection
+      .json_del(
+        self.key(id),
+        self.profile_album_added_at_path(album_file_name),
+      )
+      .await?;
     Ok(())
   }
+
+  async fn set_time_decay(&self, id: &ProfileId, half_life_days: Option<u32>) -> Result<Profile> {
+    if !self.exists(id).await? {
+      bail!("Profile does not exist")
+    }
+    let connection = self.redis_connection_pool.get().await?;
+    connection
+      .json_set(
+        self.key(id),
+        "$.time_decay_half_life_days",
+        serde_json::to_string(&half_life_days)?,
+        SetCondition::default(),
+      )
+      .await?;
+    self.get(id).await
+  }
 }
@@ -1,18 +1,26 @@
 use super::{
   profile::{Profile, ProfileId},
+  profile_analytics::{DecadeShare, ProfileAnalytics},
+  profile_file_import::ProfileImportFormat,
+  profile_goal::{ProfileGoal, ProfileGoalKind, ProfileGoalStatus},
   profile_interactor::ProfileInteractor,
+  profile_snapshot::{ProfileDiff, ProfileSnapshot},
   profile_summary::ProfileSummary,
 };
 use crate::{
   context::ApplicationContext,
   files::file_metadata::file_name::FileName,
+  music_service::music_service_client::MusicService,
   proto::{
-    self, CreateProfileReply, CreateProfileRequest, DeleteProfileRequest, GetProfileReply,
-    GetProfileRequest, GetProfileSummaryReply, GetProfileSummaryRequest,
-    ImportSavedSpotifyTracksRequest, PutManyAlbumsOnProfileReply, PutManyAlbumsOnProfileRequest,
+    self, CreateProfileReply, CreateProfileRequest, DeleteProfileRequest, GetProfileAnalyticsReply,
+    GetProfileAnalyticsRequest, GetProfileReply, GetProfileRequest, GetProfileSummaryReply,
+    GetProfileSummaryRequest, ImportSavedSpotifyTracksRequest, PutManyAlbumsOnProfileReply,
+    PutManyAlbumsOnProfileRequest,
   },
+  tenant::tenant_id::request_tenant_id,
 };
 use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
 use std::{collections::HashMap, sync::Arc};
 use tonic::{Request, Response, Status};
 use tracing::error;
@@ -20,7 +28,7 @@ use tracing::error;
 impl From<Profile> for proto::Profile {
   fn from(val: Profile) -> Self {
     proto::Profile {
-      id: val.id.to_string(),
+      id: val.id.local_id(),
       name: val.name.clone(),
       last_updated_at: val.last_updated_at.to_string(),
       albums: val
@@ -28,6 +36,93 @@ impl From<Profile> for proto::Profile {
         .into_iter()
         .map(|(k, v)| (k.to_string(), v))
         .collect(),
+      time_decay_half_life_days: val.time_decay_half_life_days,
+    }
+  }
+}
+
+impl From<ProfileSnapshot> for proto::ProfileSnapshotSummary {
+  fn from(val: ProfileSnapshot) -> Self {
+    proto::ProfileSnapshotSummary {
+      id: val.id,
+      profile_id: val.profile_id.local_id(),
+      created_at: val.created_at.to_string(),
+      album_count: val.albums.len() as u32,
+    }
+  }
+}
+
+impl From<ProfileDiff> for proto::DiffProfileSnapshotsReply {
+  fn from(val: ProfileDiff) -> Self {
+    let to_album_factor = |(file_name, factor): (FileName, u32)| proto::ProfileAlbumFactor {
+      file_name: file_name.to_string(),
+      factor,
+    };
+    proto::DiffProfileSnapshotsReply {
+      added: val.added.into_iter().map(to_album_factor).collect(),
+      removed: val.removed.into_iter().map(to_album_factor).collect(),
+      factor_changes: val
+        .factor_changes
+        .into_iter()
+        .map(|change| proto::ProfileFactorChange {
+          file_name: change.file_name.to_string(),
+          previous_factor: change.previous_factor,
+          current_factor: change.current_factor,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl From<proto::ProfileImportFormat> for ProfileImportFormat {
+  fn from(val: proto::ProfileImportFormat) -> Self {
+    match val {
+      proto::ProfileImportFormat::Csv => ProfileImportFormat::Csv,
+      proto::ProfileImportFormat::Discogs => ProfileImportFormat::Discogs,
+    }
+  }
+}
+
+impl From<ProfileGoalKind> for proto::ProfileGoalKind {
+  fn from(val: ProfileGoalKind) -> Self {
+    match val {
+      ProfileGoalKind::NewAlbums => proto::ProfileGoalKind::ProfileGoalNewAlbums,
+      ProfileGoalKind::NewGenres => proto::ProfileGoalKind::ProfileGoalNewGenres,
+    }
+  }
+}
+
+impl From<proto::ProfileGoalKind> for ProfileGoalKind {
+  fn from(val: proto::ProfileGoalKind) -> Self {
+    match val {
+      proto::ProfileGoalKind::ProfileGoalNewAlbums => ProfileGoalKind::NewAlbums,
+      proto::ProfileGoalKind::ProfileGoalNewGenres => ProfileGoalKind::NewGenres,
+    }
+  }
+}
+
+impl From<ProfileGoalStatus> for proto::ProfileGoalStatus {
+  fn from(val: ProfileGoalStatus) -> Self {
+    match val {
+      ProfileGoalStatus::InProgress => proto::ProfileGoalStatus::ProfileGoalInProgress,
+      ProfileGoalStatus::Completed => proto::ProfileGoalStatus::ProfileGoalCompleted,
+      ProfileGoalStatus::Expired => proto::ProfileGoalStatus::ProfileGoalExpired,
+    }
+  }
+}
+
+impl From<ProfileGoal> for proto::ProfileGoal {
+  fn from(val: ProfileGoal) -> Self {
+    proto::ProfileGoal {
+      id: val.id.clone(),
+      profile_id: val.profile_id.local_id(),
+      kind: proto::ProfileGoalKind::from(val.kind) as i32,
+      target: val.target,
+      progress: val.progress(),
+      status: proto::ProfileGoalStatus::from(val.status(Utc::now().naive_utc())) as i32,
+      starts_at: val.starts_at.to_string(),
+      ends_at: val.ends_at.map(|ends_at| ends_at.to_string()),
+      milestones_reached: val.milestones_reached,
     }
   }
 }
@@ -35,7 +130,7 @@ impl From<Profile> for proto::Profile {
 impl From<ProfileSummary> for proto::ProfileSummary {
   fn from(val: ProfileSummary) -> Self {
     proto::ProfileSummary {
-      id: val.id.to_string(),
+      id: val.id.local_id(),
       name: val.name,
       album_count: val.album_count,
       indexed_album_count: val.indexed_album_count,
@@ -48,11 +143,39 @@ impl From<ProfileSummary> for proto::ProfileSummary {
       years: val.years.into_iter().map(Into::into).collect(),
       decades: val.decades.into_iter().map(Into::into).collect(),
       credit_tags: val.credit_tags.into_iter().map(Into::into).collect(),
+      goals: vec![],
+    }
+  }
+}
+
+impl From<DecadeShare> for proto::DecadeShare {
+  fn from(val: DecadeShare) -> Self {
+    proto::DecadeShare {
+      decade: val.decade,
+      factor: val.factor,
+      share: val.share,
+    }
+  }
+}
+
+impl From<ProfileAnalytics> for proto::ProfileAnalytics {
+  fn from(val: ProfileAnalytics) -> Self {
+    proto::ProfileAnalytics {
+      id: val.id.local_id(),
+      indexed_album_count: val.indexed_album_count,
+      decades: val.decades.into_iter().map(Into::into).collect(),
+      genre_entropy: val.genre_entropy,
+      genre_diversity: val.genre_diversity,
+      average_rating: val.average_rating,
+      catalog_average_rating: val.catalog_average_rating,
+      average_rating_delta: val.average_rating_delta,
+      collaborators: val.collaborators.into_iter().map(Into::into).collect(),
     }
   }
 }
 
 pub struct ProfileService {
+  app_context: Arc<ApplicationContext>,
   profile_interactor: Arc<ProfileInteractor>,
 }
 
@@ -60,6 +183,7 @@ impl ProfileService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       profile_interactor: Arc::clone(&app_context.profile_interactor),
+      app_context,
     }
   }
 }
@@ -70,8 +194,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<CreateProfileRequest>,
   ) -> Result<Response<CreateProfileReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("Invalid profile id")
     })?;
@@ -93,8 +218,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<DeleteProfileRequest>,
   ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -113,8 +239,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<GetProfileRequest>,
   ) -> Result<Response<GetProfileReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -141,11 +268,11 @@ impl proto::ProfileService for ProfileService {
 
   async fn get_all_profiles(
     &self,
-    _request: Request<()>,
+    request: Request<()>,
   ) -> Result<Response<proto::GetAllProfilesReply>, Status> {
     let profiles = self
       .profile_interactor
-      .get_all_profiles()
+      .get_tenant_profiles(&request_tenant_id(&request)?)
       .await
       .map_err(|err| {
         error!("failed to get all profiles: {:?}", err);
@@ -161,8 +288,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<GetProfileSummaryRequest>,
   ) -> Result<Response<GetProfileSummaryReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -174,18 +302,52 @@ impl proto::ProfileService for ProfileService {
         error!("failed to get profile summary: {:?}", err);
         Status::internal("failed to get profile summary")
       })?;
+    let goals = self
+      .profile_interactor
+      .get_goals(&id)
+      .await
+      .map_err(|err| {
+        error!("failed to get profile goals: {:?}", err);
+        Status::internal("failed to get profile goals")
+      })?;
+    let mut summary: proto::ProfileSummary = profile_summary.into();
+    summary.goals = goals.into_iter().map(Into::into).collect();
     let reply = GetProfileSummaryReply {
-      summary: Some(profile_summary.into()),
+      summary: Some(summary),
     };
     Ok(Response::new(reply))
   }
 
+  async fn get_profile_analytics(
+    &self,
+    request: Request<GetProfileAnalyticsRequest>,
+  ) -> Result<Response<GetProfileAnalyticsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let analytics = self
+      .profile_interactor
+      .get_profile_analytics(&id)
+      .await
+      .map_err(|err| {
+        error!("failed to get profile analytics: {:?}", err);
+        Status::internal("failed to get profile analytics")
+      })?;
+    Ok(Response::new(GetProfileAnalyticsReply {
+      analytics: Some(analytics.into()),
+    }))
+  }
+
   async fn put_many_albums_on_profile(
     &self,
     request: Request<PutManyAlbumsOnProfileRequest>,
   ) -> Result<Response<PutManyAlbumsOnProfileReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.profile_id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -222,8 +384,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<proto::PutAlbumOnProfileRequest>,
   ) -> Result<Response<proto::PutAlbumOnProfileReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let id: ProfileId = request.profile_id.try_into().map_err(|err| {
+    let id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -253,10 +416,12 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<ImportSavedSpotifyTracksRequest>,
   ) -> Result<Response<()>, Status> {
-    let profile_id = ProfileId::try_from(request.into_inner().profile_id).map_err(|err| {
-      error!("invalid profile id: {:?}", err);
-      Status::invalid_argument("invalid profile id")
-    })?;
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|err| {
+        error!("invalid profile id: {:?}", err);
+        Status::invalid_argument("invalid profile id")
+      })?;
     self
       .profile_interactor
       .import_saved_spotify_tracks(&profile_id)
@@ -269,12 +434,40 @@ impl proto::ProfileService for ProfileService {
     Ok(Response::new(()))
   }
 
+  async fn import_saved_albums(
+    &self,
+    request: Request<proto::ImportSavedAlbumsRequest>,
+  ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let service = MusicService::from(request.service());
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let client = self
+      .app_context
+      .music_service_client(service, &tenant_id)
+      .map_err(|err| Status::failed_precondition(err.to_string()))?;
+    self
+      .profile_interactor
+      .import_music_service_saved_albums(&profile_id, client.as_ref())
+      .await
+      .map_err(|err| {
+        error!("failed to import saved albums: {:?}", err);
+        Status::internal("failed to import saved albums")
+      })?;
+
+    Ok(Response::new(()))
+  }
+
   async fn import_spotify_playlist_tracks(
     &self,
     request: Request<proto::ImportSpotifyPlaylistTracksRequest>,
   ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let inner = request.into_inner();
-    let profile_id = ProfileId::try_from(inner.profile_id).map_err(|err| {
+    let profile_id = ProfileId::scoped(&tenant_id, inner.profile_id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -295,8 +488,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<proto::GetPendingSpotifyImportsRequest>,
   ) -> Result<Response<proto::GetPendingSpotifyImportsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let inner = request.into_inner();
-    let profile_id = ProfileId::try_from(inner.profile_id).map_err(|err| {
+    let profile_id = ProfileId::scoped(&tenant_id, inner.profile_id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -328,7 +522,7 @@ impl proto::ProfileService for ProfileService {
       pending_imports: pending_spotify_imports
         .into_iter()
         .map(|import| proto::PendingSpotifyImport {
-          profile_id: import.profile_id.to_string(),
+          profile_id: import.profile_id.local_id(),
           factor: import.factor,
           album_search_lookup: Some(import.album_search_lookup.into()),
         })
@@ -337,12 +531,128 @@ impl proto::ProfileService for ProfileService {
     Ok(Response::new(reply))
   }
 
+  async fn import_profile_albums(
+    &self,
+    request: Request<proto::ImportProfileAlbumsRequest>,
+  ) -> Result<Response<proto::ImportProfileAlbumsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let inner = request.into_inner();
+    let format = inner.format().into();
+    let profile_id = ProfileId::scoped(&tenant_id, inner.profile_id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let content = String::from_utf8(inner.content)
+      .map_err(|_| Status::invalid_argument("import file must be utf-8 encoded"))?;
+    let rows = self
+      .profile_interactor
+      .import_albums_from_file(&profile_id, format, &content)
+      .await
+      .map_err(|err| {
+        error!("failed to import profile albums: {:?}", err);
+        Status::internal(format!("failed to import profile albums: {}", err))
+      })?;
+    let mut statuses: HashMap<String, u32> = HashMap::new();
+    for row in &rows {
+      *statuses.entry(row.status_string()).or_insert(0) += 1;
+    }
+    let reply = proto::ImportProfileAlbumsReply {
+      count: rows.len() as u32,
+      statuses: statuses
+        .into_iter()
+        .map(|(status, count)| proto::AggregatedStatus { status, count })
+        .collect(),
+      rows: rows
+        .into_iter()
+        .map(|row| proto::ProfileImportRowStatus {
+          line: row.line as u32,
+          status: row.status_string(),
+          artist_name: row.artist_name,
+          album_name: row.album_name,
+          factor: row.factor,
+          album_search_lookup: row.album_search_lookup.map(Into::into),
+          error: row.error,
+        })
+        .collect(),
+    };
+    Ok(Response::new(reply))
+  }
+
+  async fn create_profile_snapshot(
+    &self,
+    request: Request<proto::CreateProfileSnapshotRequest>,
+  ) -> Result<Response<proto::CreateProfileSnapshotReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|err| {
+        error!("invalid profile id: {:?}", err);
+        Status::invalid_argument("invalid profile id")
+      })?;
+    let snapshot = self
+      .profile_interactor
+      .snapshot_profile(&profile_id)
+      .await
+      .map_err(|err| {
+        error!("failed to snapshot profile: {:?}", err);
+        Status::internal("failed to snapshot profile")
+      })?;
+    Ok(Response::new(proto::CreateProfileSnapshotReply {
+      snapshot: snapshot.map(Into::into),
+    }))
+  }
+
+  async fn get_profile_snapshots(
+    &self,
+    request: Request<proto::GetProfileSnapshotsRequest>,
+  ) -> Result<Response<proto::GetProfileSnapshotsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|err| {
+        error!("invalid profile id: {:?}", err);
+        Status::invalid_argument("invalid profile id")
+      })?;
+    let snapshots = self
+      .profile_interactor
+      .get_profile_snapshots(&profile_id)
+      .await
+      .map_err(|err| {
+        error!("failed to get profile snapshots: {:?}", err);
+        Status::internal("failed to get profile snapshots")
+      })?;
+    Ok(Response::new(proto::GetProfileSnapshotsReply {
+      snapshots: snapshots.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn diff_profile_snapshots(
+    &self,
+    request: Request<proto::DiffProfileSnapshotsRequest>,
+  ) -> Result<Response<proto::DiffProfileSnapshotsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let inner = request.into_inner();
+    let diff = self
+      .profile_interactor
+      .diff_profile_snapshots(
+        &tenant_id,
+        &inner.from_snapshot_id,
+        inner.to_snapshot_id.as_deref(),
+      )
+      .await
+      .map_err(|err| {
+        error!("failed to diff profile snapshots: {:?}", err);
+        Status::internal(format!("failed to diff profile snapshots: {}", err))
+      })?
+      .ok_or_else(|| Status::not_found("snapshot not found"))?;
+    Ok(Response::new(diff.into()))
+  }
+
   async fn remove_album_from_profile(
     &self,
     request: Request<proto::RemoveAlbumFromProfileRequest>,
   ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let profile_id = ProfileId::try_from(request.profile_id).map_err(|err| {
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
       error!("invalid profile id: {:?}", err);
       Status::invalid_argument("invalid profile id")
     })?;
@@ -366,8 +676,9 @@ impl proto::ProfileService for ProfileService {
     &self,
     request: Request<proto::ClearPendingSpotifyImportsRequest>,
   ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
-    let profile_id = ProfileId::try_from(request.profile_id).map_err(|err| {
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
       let message = format!("invalid profile id: {:?}", err);
       error!("{}", message);
       Status::invalid_argument(message)
@@ -384,4 +695,100 @@ impl proto::ProfileService for ProfileService {
 
     Ok(Response::new(()))
   }
+
+  async fn create_profile_goal(
+    &self,
+    request: Request<proto::CreateProfileGoalRequest>,
+  ) -> Result<Response<proto::CreateProfileGoalReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let kind = request.kind().into();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let ends_at = request
+      .ends_at
+      .map(|ends_at| NaiveDateTime::parse_from_str(&ends_at, "%Y-%m-%dT%H:%M:%S"))
+      .transpose()
+      .map_err(|err| Status::invalid_argument(format!("invalid goal end: {}", err)))?;
+    if request.target == 0 {
+      return Err(Status::invalid_argument(
+        "goal target must be greater than zero",
+      ));
+    }
+    let goal = self
+      .profile_interactor
+      .create_goal(&profile_id, kind, request.target, ends_at)
+      .await
+      .map_err(|err| {
+        error!("failed to create profile goal: {:?}", err);
+        Status::internal(format!("failed to create profile goal: {}", err))
+      })?;
+    Ok(Response::new(proto::CreateProfileGoalReply {
+      goal: Some(goal.into()),
+    }))
+  }
+
+  async fn get_profile_goals(
+    &self,
+    request: Request<proto::GetProfileGoalsRequest>,
+  ) -> Result<Response<proto::GetProfileGoalsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|err| {
+        error!("invalid profile id: {:?}", err);
+        Status::invalid_argument("invalid profile id")
+      })?;
+    let goals = self
+      .profile_interactor
+      .get_goals(&profile_id)
+      .await
+      .map_err(|err| {
+        error!("failed to get profile goals: {:?}", err);
+        Status::internal("failed to get profile goals")
+      })?;
+    Ok(Response::new(proto::GetProfileGoalsReply {
+      goals: goals.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn delete_profile_goal(
+    &self,
+    request: Request<proto::DeleteProfileGoalRequest>,
+  ) -> Result<Response<()>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    self
+      .profile_interactor
+      .delete_goal(&tenant_id, &request.into_inner().goal_id)
+      .await
+      .map_err(|err| {
+        error!("failed to delete profile goal: {:?}", err);
+        Status::internal("failed to delete profile goal")
+      })?;
+    Ok(Response::new(()))
+  }
+
+  async fn put_profile_time_decay(
+    &self,
+    request: Request<proto::PutProfileTimeDecayRequest>,
+  ) -> Result<Response<proto::PutProfileTimeDecayReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let id = ProfileId::scoped(&tenant_id, request.id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let profile = self
+      .profile_interactor
+      .set_time_decay(&id, request.half_life_days)
+      .await
+      .map_err(|err| {
+        error!("failed to set profile time decay: {:?}", err);
+        Status::invalid_argument(err.to_string())
+      })?;
+    Ok(Response::new(proto::PutProfileTimeDecayReply {
+      profile: Some(profile.into()),
+    }))
+  }
 }
@@ -0,0 +1,108 @@
+use super::profile::{Profile, ProfileId};
+use crate::files::file_metadata::file_name::FileName;
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ulid::Ulid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSnapshot {
+  pub id: String,
+  pub profile_id: ProfileId,
+  pub created_at: NaiveDateTime,
+  pub albums: HashMap<FileName, u32>,
+}
+
+impl ProfileSnapshot {
+  pub fn new(profile: &Profile) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      profile_id: profile.id.clone(),
+      created_at: Utc::now().naive_utc(),
+      albums: profile.albums.clone(),
+    }
+  }
+
+  /**
+   * Changes needed to go from this snapshot's albums to `other`'s
+   */
+  pub fn diff(&self, other: &HashMap<FileName, u32>) -> ProfileDiff {
+    let mut diff = ProfileDiff::default();
+    for (file_name, factor) in &self.albums {
+      match other.get(file_name) {
+        None => diff.removed.push((file_name.clone(), *factor)),
+        Some(current) if current != factor => diff.factor_changes.push(ProfileFactorChange {
+          file_name: file_name.clone(),
+          previous_factor: *factor,
+          current_factor: *current,
+        }),
+        Some(_) => {}
+      }
+    }
+    for (file_name, factor) in other {
+      if !self.albums.contains_key(file_name) {
+        diff.added.push((file_name.clone(), *factor));
+      }
+    }
+    diff.added.sort_by(|a, b| b.1.cmp(&a.1));
+    diff.removed.sort_by(|a, b| b.1.cmp(&a.1));
+    diff.factor_changes.sort_by_key(|change| {
+      std::cmp::Reverse(change.current_factor.abs_diff(change.previous_factor))
+    });
+    diff
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileFactorChange {
+  pub file_name: FileName,
+  pub previous_factor: u32,
+  pub current_factor: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileDiff {
+  pub added: Vec<(FileName, u32)>,
+  pub removed: Vec<(FileName, u32)>,
+  pub factor_changes: Vec<ProfileFactorChange>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_diff() -> Result<()> {
+    let kept = FileName::try_from("release/album/billy-woods/aethiopes")?;
+    let changed = FileName::try_from("release/album/bjork/vulnicura")?;
+    let removed = FileName::try_from("release/album/daft-punk/random-access-memories")?;
+    let added = FileName::try_from("release/album/run-the-jewels/run-the-jewels-2")?;
+    let snapshot = ProfileSnapshot {
+      id: Ulid::new().to_string(),
+      profile_id: ProfileId::try_from("default".to_string())?,
+      created_at: Utc::now().naive_utc(),
+      albums: HashMap::from([
+        (kept.clone(), 3),
+        (changed.clone(), 1),
+        (removed.clone(), 2),
+      ]),
+    };
+    let diff = snapshot.diff(&HashMap::from([
+      (kept, 3),
+      (changed.clone(), 5),
+      (added.clone(), 4),
+    ]));
+    assert_eq!(diff.added, vec![(added, 4)]);
+    assert_eq!(diff.removed, vec![(removed, 2)]);
+    assert_eq!(
+      diff.factor_changes,
+      vec![ProfileFactorChange {
+        file_name: changed,
+        previous_factor: 1,
+        current_factor: 5,
+      }]
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,85 @@
+use super::{profile::ProfileId, profile_snapshot::ProfileSnapshot};
+use crate::helpers::document_store::{DocumentFilter, DocumentStore};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct ProfileSnapshotRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "profile_snapshot";
+
+impl ProfileSnapshotRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, snapshot: ProfileSnapshot) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &snapshot.id.clone(), snapshot, None)
+      .await
+  }
+
+  pub async fn find(&self, id: &str) -> Result<Option<ProfileSnapshot>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<ProfileSnapshot>(COLLECTION, id)
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Snapshots of a profile, oldest first
+   */
+  pub async fn find_by_profile_id(&self, profile_id: &ProfileId) -> Result<Vec<ProfileSnapshot>> {
+    let mut snapshots = self
+      .doc_store
+      .find_many::<ProfileSnapshot>(
+        COLLECTION,
+        DocumentFilter::new()
+          .condition("profile_id", "=", profile_id.to_string())
+          .build(),
+        None,
+      )
+      .await?
+      .documents
+      .into_iter()
+      .map(|doc| doc.document)
+      .collect::<Vec<_>>();
+    snapshots.sort_by_key(|snapshot| snapshot.created_at);
+    Ok(snapshots)
+  }
+
+  pub async fn find_latest_by_profile_id(
+    &self,
+    profile_id: &ProfileId,
+  ) -> Result<Option<ProfileSnapshot>> {
+    Ok(
+      self
+        .doc_store
+        .find_latest::<ProfileSnapshot>(
+          COLLECTION,
+          DocumentFilter::new()
+            .condition("profile_id", "=", profile_id.to_string())
+            .build(),
+          Some(1),
+        )
+        .await?
+        .pop()
+        .map(|doc| doc.document),
+    )
+  }
+
+  pub async fn delete_by_profile_id(&self, profile_id: &ProfileId) -> Result<()> {
+    let keys = self
+      .find_by_profile_id(profile_id)
+      .await?
+      .into_iter()
+      .map(|snapshot| snapshot.id)
+      .collect();
+    self.doc_store.delete_many(COLLECTION, keys).await
+  }
+}
@@ -0,0 +1,267 @@
+use super::{
+  profile::{Profile, ProfileId},
+  profile_repository::ProfileRepository,
+};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use std::sync::Arc;
+use tracing::{error, instrument, warn};
+
+/**
+ * Profiles as JSON documents, for deployments without Redis
+ */
+#[derive(Debug, Clone)]
+pub struct SqliteProfileRepository {
+  pub sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl SqliteProfileRepository {
+  /**
+   * Reads, changes and writes back the profile in one transaction on the write connection, so
+   * concurrent changes to the same profile can't be lost
+   */
+  async fn update<T: Send + 'static>(
+    &self,
+    id: &ProfileId,
+    update: impl FnOnce(&mut Profile) -> T + Send + 'static,
+  ) -> Result<(Profile, T)> {
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let Some(json) = tx
+          .query_row(
+            "SELECT json FROM profiles WHERE id = ?",
+            params![id],
+            |row| row.get::<_, String>(0),
+          )
+          .optional()?
+        else {
+          bail!("Profile does not exist")
+        };
+        let mut profile = serde_json::from_str::<Profile>(&json)?;
+        let result = update(&mut profile);
+        tx.execute(
+          "UPDATE profiles SET json = ? WHERE id = ?",
+          params![serde_json::to_string(&profile)?, id],
+        )?;
+        tx.commit()?;
+        Ok((profile, result))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to update profile");
+        anyhow!("Failed to update profile")
+      })?
+  }
+}
+
+#[async_trait]
+impl ProfileRepository for SqliteProfileRepository {
+  async fn find(&self, id: &ProfileId) -> Result<Option<Profile>> {
+    let id = id.to_string();
+    let json = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        conn
+          .query_row(
+            "SELECT json FROM profiles WHERE id = ?",
+            params![id],
+            |row| row.get::<_, String>(0),
+          )
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find profile");
+        anyhow!("Failed to find profile")
+      })??;
+    Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+  }
+
+  async fn get_all(&self) -> Result<Vec<Profile>> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare("SELECT json FROM profiles")?;
+        let rows = statement
+          .query_map([], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get profiles");
+        anyhow!("Failed to get profiles")
+      })??;
+    Ok(
+      rows
+        .into_iter()
+        .filter_map(|json| match serde_json::from_str(&json) {
+          Ok(profile) => Some(profile),
+          Err(_) => {
+            warn!("Failed to deserialize profile");
+            None
+          }
+        })
+        .collect(),
+    )
+  }
+
+  async fn exists(&self, id: &ProfileId) -> Result<bool> {
+    Ok(self.find(id).await?.is_some())
+  }
+
+  async fn insert(&self, id: ProfileId, name: String) -> Result<Profile> {
+    let profile = Profile {
+      id: id.clone(),
+      name,
+      last_updated_at: Utc::now().naive_utc(),
+      albums: Default::default(),
+      album_added_at: Default::default(),
+      time_decay_half_life_days: None,
+    };
+    let json = serde_json::to_string(&profile)?;
+    let inserted = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "INSERT INTO profiles (id, json) VALUES (?, ?) ON CONFLICT (id) DO NOTHING",
+          params![id.to_string(), json],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to insert profile");
+        anyhow!("Failed to insert profile")
+      })??;
+    if inserted == 0 {
+      bail!("Profile already exists")
+    }
+    Ok(profile)
+  }
+
+  async fn delete(&self, id: &ProfileId) -> Result<()> {
+    let id = id.to_string();
+    let deleted = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| conn.execute("DELETE FROM profiles WHERE id = ?", params![id]))
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete profile");
+        anyhow!("Failed to delete profile")
+      })??;
+    if deleted == 0 {
+      bail!("Profile does not exist")
+    }
+    Ok(())
+  }
+
+  async fn is_album_on_profile(&self, id: &ProfileId, album_file_name: &FileName) -> Result<bool> {
+    Ok(self.get(id).await?.albums.contains_key(album_file_name))
+  }
+
+  #[instrument(skip(self), name = "SqliteProfileRepository::put_album_on_profile")]
+  async fn put_album_on_profile(
+    &self,
+    id: &ProfileId,
+    album_file_name: &FileName,
+    factor: u32,
+  ) -> Result<(Profile, bool)> {
+    let album_file_name = album_file_name.clone();
+    self
+      .update(id, move |profile| {
+        let new_addition = profile
+          .albums
+          .insert(album_file_name.clone(), factor)
+          .is_none();
+        if new_addition {
+          profile
+            .album_added_at
+            .insert(album_file_name, Utc::now().naive_utc());
+        }
+        new_addition
+      })
+      .await
+  }
+
+  async fn remove_album_from_profile(
+    &self,
+    id: &ProfileId,
+    album_file_name: &FileName,
+  ) -> Result<()> {
+    let album_file_name = album_file_name.clone();
+    self
+      .update(id, move |profile| {
+        profile.albums.remove(&album_file_name);
+        profile.album_added_at.remove(&album_file_name);
+      })
+      .await?;
+    Ok(())
+  }
+
+  async fn set_time_decay(&self, id: &ProfileId, half_life_days: Option<u32>) -> Result<Profile> {
+    let (profile, _) = self
+      .update(id, move |profile| {
+        profile.time_decay_half_life_days = half_life_days;
+      })
+      .await?;
+    Ok(profile)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_album_changes() -> Result<()> {
+    let repository = SqliteProfileRepository {
+      sqlite_connection: Arc::new(SqliteConnection::new_for_test().await?),
+    };
+    let id = ProfileId::try_from("test".to_string())?;
+    let album_file_name = FileName::try_from("release/album/artist/a")?;
+    repository.insert(id.clone(), "Test".to_string()).await?;
+    assert!(repository
+      .insert(id.clone(), "Test".to_string())
+      .await
+      .is_err());
+
+    let (profile, new_addition) = repository
+      .put_album_on_profile(&id, &album_file_name, 2)
+      .await?;
+    assert!(new_addition);
+    assert_eq!(profile.albums.get(&album_file_name), Some(&2));
+    assert!(profile.album_added_at.contains_key(&album_file_name));
+    let (_, new_addition) = repository
+      .put_album_on_profile(&id, &album_file_name, 3)
+      .await?;
+    assert!(!new_addition);
+
+    repository
+      .remove_album_from_profile(&id, &album_file_name)
+      .await?;
+    assert!(
+      !repository
+        .is_album_on_profile(&id, &album_file_name)
+        .await?
+    );
+    repository.delete(&id).await?;
+    assert!(repository.find(&id).await?.is_none());
+    Ok(())
+  }
+}
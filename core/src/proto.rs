@@ -1,9 +1,16 @@
 tonic::include_proto!("lute");
 pub use album_service_server::{AlbumService, AlbumServiceServer};
+pub use apple_music_service_server::{AppleMusicService, AppleMusicServiceServer};
 pub use artist_service_server::{ArtistService, ArtistServiceServer};
+pub use auth_service_server::{AuthService, AuthServiceServer};
+pub use backup_service_server::{BackupService, BackupServiceServer};
+pub use bootstrap_service_server::{BootstrapService, BootstrapServiceServer};
+pub use collection_service_server::{CollectionService, CollectionServiceServer};
 pub use crawler_service_server::{CrawlerService, CrawlerServiceServer};
+pub use discogs_service_server::{DiscogsService, DiscogsServiceServer};
 pub use event_service_server::{EventService, EventServiceServer};
 pub use file_service_server::{FileService, FileServiceServer};
+pub use listening_event_service_server::{ListeningEventService, ListeningEventServiceServer};
 pub use lookup_service_server::{LookupService, LookupServiceServer};
 pub use lute_server::{Lute, LuteServer};
 pub use operations_service_server::{OperationsService, OperationsServiceServer};
@@ -11,5 +18,8 @@ pub use parser_service_server::{ParserService, ParserServiceServer};
 pub use profile_service_server::{ProfileService, ProfileServiceServer};
 pub use recommendation_service_server::{RecommendationService, RecommendationServiceServer};
 pub use scheduler_service_server::{SchedulerService, SchedulerServiceServer};
+pub use settings_service_server::{SettingsService, SettingsServiceServer};
 pub use spotify_service_server::{SpotifyService, SpotifyServiceServer};
+pub use tidal_service_server::{TidalService, TidalServiceServer};
+pub use you_tube_music_service_server::{YouTubeMusicService, YouTubeMusicServiceServer};
 pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("lute_descriptor");
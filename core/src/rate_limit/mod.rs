@@ -0,0 +1,2 @@
+pub mod rate_limit_layer;
+pub mod rpc_rate_limiter;
@@ -0,0 +1,121 @@
+use super::rpc_rate_limiter::RpcRateLimiter;
+use crate::auth::api_key::AuthenticatedKey;
+use std::{sync::Arc, time::Duration};
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, HeaderMap, HeaderValue, StatusCode},
+    BoxFuture, Context, Poll, Service,
+  },
+  transport::server::TcpConnectInfo,
+  Status,
+};
+use tower::Layer;
+use tracing::warn;
+
+const RETRY_AFTER_METADATA: &str = "retry-after";
+
+/**
+ * Clients are told to retry after whole seconds, rounded up so they don't come back too early
+ */
+fn retry_after_seconds(wait: Duration) -> u64 {
+  wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
+
+/**
+ * Authenticated requests are limited per key, everything else per peer address. Presented keys
+ * aren't trusted until auth has checked them, or rotating made up keys would dodge the limit.
+ */
+fn client_key<B>(request: &http::Request<B>) -> String {
+  if let Some(key) = request.extensions().get::<AuthenticatedKey>() {
+    return format!("key:{}", key.id);
+  }
+  request
+    .extensions()
+    .get::<TcpConnectInfo>()
+    .and_then(|info| info.remote_addr())
+    .map(|addr| format!("ip:{}", addr.ip()))
+    .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn exhausted_response(headers: &HeaderMap, wait: Duration) -> http::Response<BoxBody> {
+  let retry_after = HeaderValue::from(retry_after_seconds(wait));
+  let is_grpc = headers
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|content_type| content_type.starts_with("application/grpc"));
+  let mut response = if is_grpc {
+    Status::resource_exhausted("Rate limit exceeded").to_http()
+  } else {
+    let mut response = http::Response::new(tonic::body::empty_body());
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    response
+  };
+  response
+    .headers_mut()
+    .insert(RETRY_AFTER_METADATA, retry_after);
+  response
+}
+
+/**
+ * Rejects requests from clients that have used up their token bucket with RESOURCE_EXHAUSTED and
+ * a `retry-after` in seconds. Does nothing unless rate limiting is enabled.
+ */
+#[derive(Clone)]
+pub struct RateLimitLayer {
+  rate_limiter: Option<Arc<RpcRateLimiter>>,
+}
+
+impl RateLimitLayer {
+  pub fn new(rate_limiter: Option<Arc<RpcRateLimiter>>) -> Self {
+    Self { rate_limiter }
+  }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+  type Service = RateLimitMiddleware<S>;
+
+  fn layer(&self, inner: S) -> Self::Service {
+    RateLimitMiddleware {
+      inner,
+      rate_limiter: self.rate_limiter.clone(),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct RateLimitMiddleware<S> {
+  inner: S,
+  rate_limiter: Option<Arc<RpcRateLimiter>>,
+}
+
+impl<S, B> Service<http::Request<B>> for RateLimitMiddleware<S>
+where
+  S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+  S::Future: Send + 'static,
+  B: Send + 'static,
+{
+  type Response = S::Response;
+  type Error = S::Error;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    if let Some(rate_limiter) = &self.rate_limiter {
+      let client_key = client_key(&request);
+      let path = request.uri().path();
+      if let Err(wait) = rate_limiter.check(&client_key, path) {
+        warn!(path, "Rate limit exceeded");
+        let response = exhausted_response(request.headers(), wait);
+        return Box::pin(async move { Ok(response) });
+      }
+    }
+    // The clone isn't necessarily ready, so the ready service is taken and the clone left behind
+    let clone = self.inner.clone();
+    let mut inner = std::mem::replace(&mut self.inner, clone);
+    Box::pin(inner.call(request))
+  }
+}
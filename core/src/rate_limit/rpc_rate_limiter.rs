@@ -0,0 +1,150 @@
+use crate::{rest_gateway::openapi::GatewayRoute, settings::RateLimitSettings};
+use governor::{
+  clock::{Clock, DefaultClock},
+  DefaultKeyedRateLimiter, Quota, RateLimiter,
+};
+use std::{
+  collections::HashMap,
+  num::NonZeroU32,
+  sync::atomic::{AtomicUsize, Ordering},
+  time::Duration,
+};
+
+/**
+ * Idle clients are dropped from the limiters every this many checks, so their state doesn't pile
+ * up
+ */
+const RETAIN_INTERVAL: usize = 1024;
+
+fn non_zero(value: u32) -> NonZeroU32 {
+  NonZeroU32::new(value.max(1)).unwrap()
+}
+
+/**
+ * Token buckets per client, with a separate, smaller budget for expensive RPCs. A request to an
+ * expensive RPC only draws from that budget.
+ */
+pub struct RpcRateLimiter {
+  default_limiter: DefaultKeyedRateLimiter<String>,
+  expensive_limiter: DefaultKeyedRateLimiter<String>,
+  expensive_methods: Vec<String>,
+  /**
+   * RPC each REST gateway path is served by, so a call costs the same budget either way
+   */
+  gateway_methods: HashMap<String, String>,
+  check_count: AtomicUsize,
+}
+
+impl RpcRateLimiter {
+  pub fn new(settings: &RateLimitSettings, gateway_routes: &[GatewayRoute]) -> Self {
+    Self {
+      default_limiter: RateLimiter::keyed(
+        Quota::per_second(non_zero(settings.requests_per_second))
+          .allow_burst(non_zero(settings.burst)),
+      ),
+      expensive_limiter: RateLimiter::keyed(
+        Quota::per_minute(non_zero(settings.expensive_requests_per_minute))
+          .allow_burst(non_zero(settings.expensive_burst)),
+      ),
+      expensive_methods: settings.expensive_methods.clone(),
+      gateway_methods: gateway_routes
+        .iter()
+        .map(|route| {
+          (
+            route.path.clone(),
+            format!("{}/{}", route.service, route.rpc),
+          )
+        })
+        .collect(),
+      check_count: AtomicUsize::new(0),
+    }
+  }
+
+  fn is_expensive(&self, path: &str) -> bool {
+    let method = match self.gateway_methods.get(path) {
+      Some(method) => method.as_str(),
+      None => path.trim_start_matches('/').trim_start_matches("lute."),
+    };
+    self
+      .expensive_methods
+      .iter()
+      .any(|expensive_method| expensive_method == method)
+  }
+
+  /**
+   * Takes a token from the client's bucket for the path, or returns how long until one is free
+   */
+  pub fn check(&self, client_key: &str, path: &str) -> Result<(), Duration> {
+    if self.check_count.fetch_add(1, Ordering::Relaxed) % RETAIN_INTERVAL == 0 {
+      self.default_limiter.retain_recent();
+      self.expensive_limiter.retain_recent();
+    }
+    let limiter = if self.is_expensive(path) {
+      &self.expensive_limiter
+    } else {
+      &self.default_limiter
+    };
+    limiter
+      .check_key(&client_key.to_string())
+      .map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rest_gateway::openapi::gateway_routes;
+  use anyhow::Result;
+
+  #[test]
+  fn test_expensive_methods_have_their_own_budget() {
+    let limiter = RpcRateLimiter::new(
+      &RateLimitSettings {
+        enabled: true,
+        requests_per_second: 1,
+        burst: 2,
+        expensive_methods: vec!["RecommendationService/RecommendAlbums".to_string()],
+        expensive_requests_per_minute: 1,
+        expensive_burst: 1,
+      },
+      &[],
+    );
+    let recommend = "/lute.RecommendationService/RecommendAlbums";
+    let get_album = "/lute.AlbumService/GetAlbum";
+
+    assert!(limiter.check("ip:10.0.0.1", recommend).is_ok());
+    assert!(limiter
+      .check("ip:10.0.0.1", recommend)
+      .is_err_and(|wait| wait > Duration::from_secs(1)));
+    assert!(limiter.check("ip:10.0.0.1", get_album).is_ok());
+    assert!(limiter.check("ip:10.0.0.1", get_album).is_ok());
+    assert!(limiter.check("ip:10.0.0.1", get_album).is_err());
+    assert!(limiter.check("ip:10.0.0.2", get_album).is_ok());
+  }
+
+  #[test]
+  fn test_gateway_routes_share_the_expensive_budget() -> Result<()> {
+    let limiter = RpcRateLimiter::new(
+      &RateLimitSettings {
+        enabled: true,
+        requests_per_second: 10,
+        burst: 10,
+        expensive_methods: vec!["RecommendationService/RecommendAlbums".to_string()],
+        expensive_requests_per_minute: 1,
+        expensive_burst: 1,
+      },
+      &gateway_routes(&[("RecommendationService", "recommendations")])?,
+    );
+
+    assert!(limiter
+      .check("ip:10.0.0.1", "/lute.RecommendationService/RecommendAlbums")
+      .is_ok());
+    assert!(limiter
+      .check("ip:10.0.0.1", "/api/recommendations/recommend-albums")
+      .is_err());
+    assert!(limiter
+      .check("ip:10.0.0.1", "/api/recommendations/assess-album")
+      .is_ok());
+    Ok(())
+  }
+}
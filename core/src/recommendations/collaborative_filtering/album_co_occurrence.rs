@@ -0,0 +1,155 @@
+use crate::files::file_metadata::file_name::FileName;
+use std::collections::{HashMap, HashSet};
+
+/**
+ * Item-item similarity over every profile in the instance. Each album is a sparse vector of its
+ * factors across profiles, and two albums are as similar as the cosine of their vectors.
+ */
+pub struct AlbumCoOccurrence {
+  vectors: HashMap<FileName, HashMap<usize, f64>>,
+  norms: HashMap<FileName, f64>,
+  profiles: Vec<Vec<FileName>>,
+}
+
+impl AlbumCoOccurrence {
+  pub fn new(profiles: Vec<HashMap<FileName, u32>>) -> Self {
+    let mut vectors: HashMap<FileName, HashMap<usize, f64>> = HashMap::new();
+    for (profile_index, albums) in profiles.iter().enumerate() {
+      for (file_name, factor) in albums {
+        vectors
+          .entry(file_name.clone())
+          .or_default()
+          .insert(profile_index, *factor as f64);
+      }
+    }
+    let norms = vectors
+      .iter()
+      .map(|(file_name, vector)| {
+        (
+          file_name.clone(),
+          vector
+            .values()
+            .map(|factor| factor * factor)
+            .sum::<f64>()
+            .sqrt(),
+        )
+      })
+      .collect();
+    Self {
+      vectors,
+      norms,
+      profiles: profiles
+        .into_iter()
+        .map(|albums| albums.into_keys().collect())
+        .collect(),
+    }
+  }
+
+  pub fn shared_profile_count(&self, a: &FileName, b: &FileName) -> usize {
+    match (self.vectors.get(a), self.vectors.get(b)) {
+      (Some(a), Some(b)) => a.keys().filter(|profile| b.contains_key(profile)).count(),
+      _ => 0,
+    }
+  }
+
+  pub fn similarity(&self, a: &FileName, b: &FileName) -> f64 {
+    let (Some(vector_a), Some(vector_b)) = (self.vectors.get(a), self.vectors.get(b)) else {
+      return 0.0;
+    };
+    let dot = vector_a
+      .iter()
+      .filter_map(|(profile, factor)| vector_b.get(profile).map(|other| factor * other))
+      .sum::<f64>();
+    let norm = self.norms.get(a).unwrap_or(&0.0) * self.norms.get(b).unwrap_or(&0.0);
+    if norm == 0.0 {
+      0.0
+    } else {
+      dot / norm
+    }
+  }
+
+  /**
+   * Albums sharing at least one profile with any seed album, excluding the seed itself
+   */
+  pub fn candidates(&self, seed: &HashMap<FileName, u32>) -> HashSet<FileName> {
+    let seed_profiles = seed
+      .keys()
+      .filter_map(|file_name| self.vectors.get(file_name))
+      .flat_map(|vector| vector.keys())
+      .collect::<HashSet<_>>();
+    seed_profiles
+      .into_iter()
+      .flat_map(|profile| self.profiles[*profile].iter())
+      .filter(|file_name| !seed.contains_key(*file_name))
+      .cloned()
+      .collect()
+  }
+
+  /**
+   * Factor-weighted average similarity of the candidate to the seed albums, along with the seed
+   * albums that contributed, most similar first. Pairs sharing fewer than `min_shared_profiles`
+   * profiles count as unrelated.
+   */
+  pub fn score(
+    &self,
+    seed: &HashMap<FileName, u32>,
+    candidate: &FileName,
+    min_shared_profiles: usize,
+  ) -> (f64, Vec<FileName>) {
+    let total_factor = seed.values().map(|factor| *factor as f64).sum::<f64>();
+    if total_factor == 0.0 {
+      return (0.0, vec![]);
+    }
+    let mut contributions = seed
+      .iter()
+      .filter(|(file_name, _)| {
+        self.shared_profile_count(file_name, candidate) >= min_shared_profiles.max(1)
+      })
+      .map(|(file_name, factor)| {
+        (
+          file_name.clone(),
+          *factor as f64 * self.similarity(file_name, candidate),
+        )
+      })
+      .collect::<Vec<_>>();
+    contributions.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let score = contributions.iter().map(|(_, score)| score).sum::<f64>() / total_factor;
+    (
+      score,
+      contributions
+        .into_iter()
+        .map(|(file_name, _)| file_name)
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_score() -> Result<()> {
+    let aethiopes = FileName::try_from("release/album/billy-woods/aethiopes")?;
+    let haram = FileName::try_from("release/album/armand-hammer/haram")?;
+    let vulnicura = FileName::try_from("release/album/bjork/vulnicura")?;
+    let co_occurrence = AlbumCoOccurrence::new(vec![
+      HashMap::from([(aethiopes.clone(), 5), (haram.clone(), 4)]),
+      HashMap::from([(aethiopes.clone(), 3), (haram.clone(), 3)]),
+      HashMap::from([(aethiopes.clone(), 1), (vulnicura.clone(), 5)]),
+    ]);
+    let seed = HashMap::from([(aethiopes.clone(), 1)]);
+
+    assert_eq!(
+      co_occurrence.candidates(&seed),
+      HashSet::from([haram.clone(), vulnicura.clone()])
+    );
+    let (haram_score, haram_sources) = co_occurrence.score(&seed, &haram, 1);
+    let (vulnicura_score, _) = co_occurrence.score(&seed, &vulnicura, 1);
+    assert!(haram_score > vulnicura_score);
+    assert_eq!(haram_sources, vec![aethiopes.clone()]);
+    assert_eq!(co_occurrence.score(&seed, &vulnicura, 2).0, 0.0);
+    Ok(())
+  }
+}
@@ -0,0 +1,236 @@
+use super::album_co_occurrence::AlbumCoOccurrence;
+use crate::{
+  albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  helpers::redisearch::SearchPagination,
+  profile::profile_interactor::ProfileInteractor,
+  recommendations::{
+    seed::AlbumRecommendationSeedContext,
+    types::{
+      AlbumAssessment, AlbumAssessmentContribution, AlbumRecommendation,
+      AlbumRecommendationSettings, RecommendationMethodInteractor,
+    },
+  },
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use derive_builder::Builder;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+const MAX_CANDIDATES: usize = 5000;
+const MAX_EXPLAINED_SEED_ALBUMS: usize = 5;
+
+#[derive(Builder, Clone, Debug)]
+#[builder(setter(into), default)]
+pub struct CollaborativeFilteringAlbumAssessmentSettings {
+  /**
+   * Seed albums sharing fewer profiles than this with a candidate don't count towards its score
+   */
+  pub min_shared_profiles: u32,
+}
+
+impl Default for CollaborativeFilteringAlbumAssessmentSettings {
+  fn default() -> Self {
+    Self {
+      min_shared_profiles: 1,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct CollaborativeFilteringAssessableAlbum(AlbumReadModel);
+
+impl TryFrom<AlbumReadModel> for CollaborativeFilteringAssessableAlbum {
+  type Error = anyhow::Error;
+
+  fn try_from(album_read_model: AlbumReadModel) -> Result<Self, Self::Error> {
+    Ok(Self(album_read_model))
+  }
+}
+
+/**
+ * Recommends albums that are frequently co-rated with the seed across the instance's profiles.
+ * Only useful when there are several profiles, a single profile has nothing to co-occur with.
+ * Shared through the application context so every caller reuses the same co-occurrence table.
+ */
+pub struct CollaborativeFilteringInteractor {
+  album_interactor: Arc<AlbumInteractor>,
+  profile_interactor: Arc<ProfileInteractor>,
+  /**
+   * The last built table, along with the profile albums version it was built from
+   */
+  co_occurrence: Mutex<Option<(u64, Arc<AlbumCoOccurrence>)>>,
+}
+
+impl CollaborativeFilteringInteractor {
+  pub fn new(
+    album_interactor: Arc<AlbumInteractor>,
+    profile_interactor: Arc<ProfileInteractor>,
+  ) -> Self {
+    Self {
+      album_interactor,
+      profile_interactor,
+      co_occurrence: Mutex::new(None),
+    }
+  }
+
+  /**
+   * Rebuilds the table only when a profile's albums changed since it was last built. Holding the
+   * lock while building keeps concurrent callers from all rebuilding at once.
+   */
+  async fn get_co_occurrence(&self) -> Result<Arc<AlbumCoOccurrence>> {
+    let mut cached = self.co_occurrence.lock().await;
+    let version = self.profile_interactor.albums_version();
+    if let Some((cached_version, co_occurrence)) = cached.as_ref() {
+      if *cached_version == version {
+        return Ok(Arc::clone(co_occurrence));
+      }
+    }
+    let co_occurrence = Arc::new(AlbumCoOccurrence::new(
+      self
+        .profile_interactor
+        .get_all_profiles()
+        .await?
+        .into_iter()
+        .map(|profile| profile.albums)
+        .collect(),
+    ));
+    *cached = Some((version, Arc::clone(&co_occurrence)));
+    Ok(co_occurrence)
+  }
+
+  fn assess(
+    &self,
+    co_occurrence: &AlbumCoOccurrence,
+    seed_context: &AlbumRecommendationSeedContext,
+    album: &AlbumReadModel,
+    settings: &CollaborativeFilteringAlbumAssessmentSettings,
+  ) -> AlbumAssessment {
+    let min_shared_profiles = settings.min_shared_profiles as usize;
+    let (similarity, mut seed_albums) = co_occurrence.score(
+      &seed_context.factor_map,
+      &album.file_name,
+      min_shared_profiles,
+    );
+    seed_albums.truncate(MAX_EXPLAINED_SEED_ALBUMS);
+    let mut score = similarity;
+    let mut contributions = vec![AlbumAssessmentContribution {
+      factor: "co_occurrence".to_string(),
+      weight: 1.0,
+      value: similarity,
+      contribution: similarity,
+      matched_items: seed_albums.iter().map(|f| f.to_string()).collect(),
+      novel_items: vec![],
+    }];
+    if let Some(negative) = seed_context
+      .negative
+      .as_ref()
+      .filter(|negative| negative.weight > 0.0)
+    {
+      let (negative_similarity, mut negative_albums) = co_occurrence.score(
+        &negative.context.factor_map,
+        &album.file_name,
+        min_shared_profiles,
+      );
+      negative_albums.truncate(MAX_EXPLAINED_SEED_ALBUMS);
+      score -= negative_similarity * negative.weight as f64;
+      contributions.push(AlbumAssessmentContribution {
+        factor: "negative_seed".to_string(),
+        weight: negative.weight,
+        value: negative_similarity,
+        contribution: -negative_similarity * negative.weight as f64,
+        matched_items: negative_albums.iter().map(|f| f.to_string()).collect(),
+        novel_items: vec![],
+      });
+    }
+    AlbumAssessment {
+      score: score as f32,
+      metadata: Some(HashMap::from([(
+        "co_occurrence_similarity".to_string(),
+        similarity.to_string(),
+      )])),
+      contributions,
+    }
+  }
+}
+
+#[async_trait]
+impl
+  RecommendationMethodInteractor<
+    CollaborativeFilteringAssessableAlbum,
+    CollaborativeFilteringAlbumAssessmentSettings,
+  > for CollaborativeFilteringInteractor
+{
+  #[instrument(
+    name = "CollaborativeFilteringInteractor::assess_album",
+    skip(self, seed_context, album)
+  )]
+  async fn assess_album(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    album: &CollaborativeFilteringAssessableAlbum,
+    settings: CollaborativeFilteringAlbumAssessmentSettings,
+  ) -> Result<AlbumAssessment> {
+    let co_occurrence = self.get_co_occurrence().await?;
+    Ok(self.assess(&co_occurrence, seed_context, &album.0, &settings))
+  }
+
+  #[instrument(
+    name = "CollaborativeFilteringInteractor::recommend_albums",
+    skip(self, seed_context)
+  )]
+  async fn recommend_albums(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    assessment_settings: CollaborativeFilteringAlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+  ) -> Result<Vec<AlbumRecommendation>> {
+    let co_occurrence = self.get_co_occurrence().await?;
+    let min_shared_profiles = assessment_settings.min_shared_profiles as usize;
+    let mut candidates = co_occurrence
+      .candidates(&seed_context.factor_map)
+      .into_iter()
+      .map(|file_name| {
+        let (score, _) =
+          co_occurrence.score(&seed_context.factor_map, &file_name, min_shared_profiles);
+        (file_name, score)
+      })
+      .filter(|(_, score)| *score > 0.0)
+      .collect::<Vec<_>>();
+    if candidates.is_empty() {
+      return Ok(vec![]);
+    }
+    candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    candidates.truncate(MAX_CANDIDATES);
+
+    let mut search_query = recommendation_settings.to_search_query(seed_context)?;
+    search_query.include_file_names = candidates
+      .iter()
+      .map(|(file_name, _)| file_name.clone())
+      .collect();
+    let search_results = self
+      .album_interactor
+      .search(
+        &search_query,
+        Some(&SearchPagination {
+          offset: None,
+          limit: Some(candidates.len()),
+        }),
+      )
+      .await?;
+
+    let mut recommendations = search_results
+      .albums
+      .into_iter()
+      .map(|album| AlbumRecommendation {
+        assessment: self.assess(&co_occurrence, seed_context, &album, &assessment_settings),
+        album,
+        exploratory: false,
+      })
+      .collect::<Vec<_>>();
+    recommendations.sort_by(|a, b| b.assessment.score.total_cmp(&a.assessment.score));
+    recommendations.truncate(recommendation_settings.count as usize);
+    Ok(recommendations)
+  }
+}
@@ -0,0 +1,2 @@
+pub mod album_co_occurrence;
+pub mod collaborative_filtering_interactor;
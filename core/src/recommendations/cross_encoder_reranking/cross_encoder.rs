@@ -0,0 +1,151 @@
+use crate::{albums::album_read_model::AlbumReadModel, settings::CrossEncoderSettings};
+use anyhow::{anyhow, Result};
+use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+use std::{path::PathBuf, sync::Arc};
+use tokio::{sync::OnceCell, task::spawn_blocking};
+use tracing::info;
+
+const DEFAULT_MODEL: &str = "bge-reranker-base";
+const BATCH_SIZE: usize = 32;
+
+fn resolve_model(name: &str) -> Result<RerankerModel> {
+  match name {
+    "bge-reranker-base" => Ok(RerankerModel::BGERerankerBase),
+    "bge-reranker-v2-m3" => Ok(RerankerModel::BGERerankerV2M3),
+    "jina-reranker-v1-turbo-en" => Ok(RerankerModel::JINARerankerV1TurboEn),
+    _ => Err(anyhow!("Unsupported cross-encoder model: {}", name)),
+  }
+}
+
+/**
+ * Scores query/passage pairs with a cross-encoder run locally through ONNX runtime. Like the ONNX
+ * embedding provider, the model is downloaded to the cache directory on first use.
+ */
+pub struct CrossEncoder {
+  model_name: String,
+  model: RerankerModel,
+  cache_dir: PathBuf,
+  reranker: OnceCell<Arc<TextRerank>>,
+}
+
+impl CrossEncoder {
+  pub fn new(settings: &CrossEncoderSettings) -> Result<Self> {
+    let model_name = settings
+      .model
+      .clone()
+      .filter(|model| !model.is_empty())
+      .unwrap_or(DEFAULT_MODEL.to_string());
+    Ok(Self {
+      model: resolve_model(&model_name)?,
+      model_name,
+      cache_dir: PathBuf::from(
+        settings
+          .cache_dir
+          .clone()
+          .unwrap_or(format!("{}/onnx_models", env!("CARGO_MANIFEST_DIR"))),
+      ),
+      reranker: OnceCell::new(),
+    })
+  }
+
+  async fn reranker(&self) -> Result<Arc<TextRerank>> {
+    let reranker = self
+      .reranker
+      .get_or_try_init(|| async {
+        info!(
+          model = self.model_name.as_str(),
+          "Loading ONNX cross-encoder model"
+        );
+        let options = RerankInitOptions::new(self.model.clone())
+          .with_cache_dir(self.cache_dir.clone())
+          .with_show_download_progress(false);
+        let reranker = spawn_blocking(move || TextRerank::try_new(options)).await??;
+        Ok::<_, anyhow::Error>(Arc::new(reranker))
+      })
+      .await?;
+    Ok(Arc::clone(reranker))
+  }
+
+  /**
+   * Relevance of each passage to the query between 0 and 1, in passage order
+   */
+  #[tracing::instrument(name = "CrossEncoder::score", skip_all, fields(count = passages.len()))]
+  pub async fn score(&self, query: String, passages: Vec<String>) -> Result<Vec<f32>> {
+    if passages.is_empty() {
+      return Ok(vec![]);
+    }
+    let reranker = self.reranker().await?;
+    let passage_count = passages.len();
+    let results =
+      spawn_blocking(move || reranker.rerank(query, passages, false, Some(BATCH_SIZE))).await??;
+    let mut scores = vec![0.0; passage_count];
+    for result in results {
+      scores[result.index] = sigmoid(result.score);
+    }
+    Ok(scores)
+  }
+}
+
+/**
+ * Cross-encoders output unbounded logits
+ */
+fn sigmoid(logit: f32) -> f32 {
+  1.0 / (1.0 + (-logit).exp())
+}
+
+/**
+ * The album in prose, from the same facts its embedding body is built from
+ */
+pub fn album_passage(album: &AlbumReadModel) -> String {
+  let mut sentences = vec![format!(
+    "{} by {}",
+    album.name,
+    album.artist_names().join(", ")
+  )];
+  if let Some(release_date) = album.release_date {
+    sentences.push(format!("Released {}", release_date.format("%Y")));
+  }
+  for (label, items) in [
+    ("Primary genres", &album.primary_genres),
+    ("Secondary genres", &album.secondary_genres),
+    ("Descriptors", &album.descriptors),
+  ] {
+    if !items.is_empty() {
+      sentences.push(format!("{}: {}", label, items.join(", ")));
+    }
+  }
+  sentences.join(". ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_read_model::AlbumReadModelArtist;
+  use chrono::NaiveDate;
+
+  #[test]
+  fn test_album_passage() {
+    let album = AlbumReadModel {
+      name: "Loveless".to_string(),
+      artists: vec![AlbumReadModelArtist {
+        name: "My Bloody Valentine".to_string(),
+        ..Default::default()
+      }],
+      release_date: NaiveDate::from_ymd_opt(1991, 11, 4),
+      primary_genres: vec!["Shoegaze".to_string()],
+      descriptors: vec!["noisy".to_string(), "ethereal".to_string()],
+      ..Default::default()
+    };
+    assert_eq!(
+      album_passage(&album),
+      "Loveless by My Bloody Valentine. Released 1991. Primary genres: Shoegaze. Descriptors: noisy, ethereal"
+    );
+  }
+
+  #[test]
+  fn test_sigmoid() {
+    assert_eq!(sigmoid(0.0), 0.5);
+    assert!(sigmoid(8.0) > 0.99);
+    assert!(sigmoid(-8.0) < 0.01);
+  }
+}
@@ -0,0 +1,200 @@
+use super::cross_encoder::{album_passage, CrossEncoder};
+use crate::{
+  albums::album_read_model::AlbumReadModel,
+  recommendations::{
+    embedding_similarity::embedding_similarity_interactor::{
+      EmbeddingSimilarityAlbumAssessmentSettings, EmbeddingSimilarityInteractor,
+    },
+    seed::AlbumRecommendationSeedContext,
+    types::{
+      AlbumAssessment, AlbumRecommendation, AlbumRecommendationSettings,
+      RecommendationMethodInteractor,
+    },
+  },
+};
+use anyhow::{anyhow, Result};
+use std::{
+  cmp::{max, Reverse},
+  collections::HashMap,
+  sync::Arc,
+};
+use tonic::async_trait;
+use tracing::instrument;
+
+/**
+ * Each candidate is scored against this many of the seed's highest factor albums
+ */
+const MAX_SEED_ALBUMS: usize = 5;
+const DEFAULT_MIN_EMBEDDING_CANDIDATE_COUNT: u32 = 50;
+
+#[derive(Clone, Debug)]
+pub struct CrossEncoderRerankedAlbumAssessmentSettings {
+  pub embedding_similarity_settings: EmbeddingSimilarityAlbumAssessmentSettings,
+  pub min_embedding_candidate_count: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CrossEncoderRerankedAssessableAlbum(AlbumReadModel);
+
+impl TryFrom<AlbumReadModel> for CrossEncoderRerankedAssessableAlbum {
+  type Error = anyhow::Error;
+
+  fn try_from(album_read_model: AlbumReadModel) -> Result<Self, Self::Error> {
+    if album_read_model.primary_genres.is_empty() && album_read_model.descriptors.is_empty() {
+      return Err(anyhow!("No genres or descriptors to rerank on"));
+    }
+    Ok(Self(album_read_model))
+  }
+}
+
+pub struct CrossEncoderRerankingInteractor {
+  embedding_similarity_interactor: Arc<EmbeddingSimilarityInteractor>,
+  cross_encoder: Arc<CrossEncoder>,
+}
+
+impl CrossEncoderRerankingInteractor {
+  pub fn new(
+    embedding_similarity_interactor: Arc<EmbeddingSimilarityInteractor>,
+    cross_encoder: Arc<CrossEncoder>,
+  ) -> Self {
+    Self {
+      embedding_similarity_interactor,
+      cross_encoder,
+    }
+  }
+
+  /**
+   * Factor weighted average relevance of each album to the seed's strongest albums, in album order
+   */
+  async fn score_albums(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    albums: &[AlbumReadModel],
+  ) -> Result<Vec<f32>> {
+    let mut seed_albums = seed_context
+      .albums
+      .iter()
+      .map(|album| {
+        (
+          album,
+          seed_context.get_factor(&album.file_name).unwrap_or(1),
+        )
+      })
+      .collect::<Vec<_>>();
+    seed_albums.sort_by_key(|(_, factor)| Reverse(*factor));
+    seed_albums.truncate(MAX_SEED_ALBUMS);
+    if seed_albums.is_empty() {
+      return Err(anyhow!("Seed has no albums to rerank against"));
+    }
+
+    let passages = albums.iter().map(album_passage).collect::<Vec<_>>();
+    let mut weighted_scores = vec![0.0; albums.len()];
+    let mut total_factor = 0.0;
+    for (seed_album, factor) in seed_albums {
+      let scores = self
+        .cross_encoder
+        .score(album_passage(seed_album), passages.clone())
+        .await?;
+      for (weighted_score, score) in weighted_scores.iter_mut().zip(scores) {
+        *weighted_score += score * factor as f32;
+      }
+      total_factor += factor as f32;
+    }
+    Ok(
+      weighted_scores
+        .into_iter()
+        .map(|score| score / total_factor)
+        .collect(),
+    )
+  }
+}
+
+#[async_trait]
+impl
+  RecommendationMethodInteractor<
+    CrossEncoderRerankedAssessableAlbum,
+    CrossEncoderRerankedAlbumAssessmentSettings,
+  > for CrossEncoderRerankingInteractor
+{
+  #[instrument(
+    name = "CrossEncoderRerankingInteractor::assess_album",
+    skip(self, seed_context)
+  )]
+  async fn assess_album(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    album: &CrossEncoderRerankedAssessableAlbum,
+    _settings: CrossEncoderRerankedAlbumAssessmentSettings,
+  ) -> Result<AlbumAssessment> {
+    let score = self
+      .score_albums(seed_context, std::slice::from_ref(&album.0))
+      .await?
+      .pop()
+      .unwrap_or_default();
+    Ok(AlbumAssessment {
+      score,
+      metadata: None,
+      contributions: vec![],
+    })
+  }
+
+  /**
+   * Embedding similarity picks the candidates, which are then ordered by the cross-encoder alone
+   */
+  #[instrument(
+    name = "CrossEncoderRerankingInteractor::recommend_albums",
+    skip(self, seed_context)
+  )]
+  async fn recommend_albums(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    assessment_settings: CrossEncoderRerankedAlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+  ) -> Result<Vec<AlbumRecommendation>> {
+    let count = recommendation_settings.count as usize;
+    let mut candidate_settings = recommendation_settings.clone();
+    candidate_settings.count = max(
+      candidate_settings.count * 2,
+      assessment_settings
+        .min_embedding_candidate_count
+        .unwrap_or(DEFAULT_MIN_EMBEDDING_CANDIDATE_COUNT),
+    );
+    let candidates = self
+      .embedding_similarity_interactor
+      .recommend_albums(
+        seed_context,
+        assessment_settings.embedding_similarity_settings,
+        candidate_settings,
+      )
+      .await?;
+    let albums = candidates
+      .iter()
+      .map(|candidate| candidate.album.clone())
+      .collect::<Vec<_>>();
+    let scores = self.score_albums(seed_context, &albums).await?;
+
+    let mut recommendations = candidates
+      .into_iter()
+      .zip(scores)
+      .enumerate()
+      .map(|(i, (candidate, score))| AlbumRecommendation {
+        album: candidate.album,
+        assessment: AlbumAssessment {
+          score,
+          metadata: Some(HashMap::from([
+            ("embedding_similarity_rank".to_string(), i.to_string()),
+            (
+              "embedding_similarity_score".to_string(),
+              candidate.assessment.score.to_string(),
+            ),
+          ])),
+          contributions: vec![],
+        },
+        exploratory: false,
+      })
+      .collect::<Vec<_>>();
+    recommendations.sort_by(|a, b| b.cmp(a));
+    recommendations.truncate(count);
+    Ok(recommendations)
+  }
+}
@@ -0,0 +1,2 @@
+pub mod cross_encoder;
+pub mod cross_encoder_reranking_interactor;
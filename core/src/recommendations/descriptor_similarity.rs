@@ -0,0 +1,199 @@
+use crate::albums::album_repository::DescriptorCoOccurrences;
+use std::collections::HashMap;
+
+/**
+ * Similar descriptors kept for each descriptor
+ */
+const MAX_SIMILAR_DESCRIPTORS: usize = 10;
+
+/**
+ * Pairs less similar than this are left out
+ */
+const MIN_DESCRIPTOR_SIMILARITY: f64 = 0.1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DescriptorSimilarity {
+  pub descriptor: String,
+  pub similar_descriptor: String,
+  pub similarity: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DescriptorSimilarities {
+  similar_descriptors: HashMap<String, Vec<(String, f64)>>,
+}
+
+impl DescriptorSimilarities {
+  pub fn new(similarities: Vec<DescriptorSimilarity>) -> Self {
+    let mut similar_descriptors: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+    for similarity in similarities {
+      similar_descriptors
+        .entry(similarity.descriptor)
+        .or_default()
+        .push((similarity.similar_descriptor, similarity.similarity));
+    }
+    Self {
+      similar_descriptors,
+    }
+  }
+
+  /**
+   * Similarities are between 0 and 1
+   */
+  pub fn similar_descriptors(&self, descriptor: &str) -> &[(String, f64)] {
+    self
+      .similar_descriptors
+      .get(descriptor)
+      .map_or(&[], |similar| similar.as_slice())
+  }
+}
+
+/**
+ * Embeds each descriptor as its positive pointwise mutual information with every other descriptor
+ * and compares the embeddings by cosine similarity, so descriptors applied alongside the same
+ * others are similar even if they're rarely applied together
+ */
+pub fn compute_descriptor_similarities(
+  co_occurrences: &DescriptorCoOccurrences,
+) -> Vec<DescriptorSimilarity> {
+  let album_count = co_occurrences.album_count as f64;
+  let descriptor_counts = co_occurrences
+    .descriptor_counts
+    .iter()
+    .map(|item| (item.name.as_str(), item.count as f64))
+    .collect::<HashMap<_, _>>();
+
+  let mut embeddings: HashMap<&str, HashMap<&str, f64>> = HashMap::new();
+  for (a, b, count) in &co_occurrences.pair_counts {
+    let (Some(a_count), Some(b_count)) = (
+      descriptor_counts.get(a.as_str()),
+      descriptor_counts.get(b.as_str()),
+    ) else {
+      continue;
+    };
+    let pmi = (*count as f64 * album_count / (a_count * b_count)).ln();
+    if pmi > 0.0 {
+      embeddings
+        .entry(a.as_str())
+        .or_default()
+        .insert(b.as_str(), pmi);
+      embeddings
+        .entry(b.as_str())
+        .or_default()
+        .insert(a.as_str(), pmi);
+    }
+  }
+
+  let norms = embeddings
+    .iter()
+    .map(|(descriptor, embedding)| {
+      (
+        *descriptor,
+        embedding
+          .values()
+          .map(|value| value * value)
+          .sum::<f64>()
+          .sqrt(),
+      )
+    })
+    .collect::<HashMap<_, _>>();
+
+  let mut descriptors = embeddings.keys().copied().collect::<Vec<_>>();
+  descriptors.sort();
+  let mut similar_descriptors: HashMap<&str, Vec<(&str, f64)>> = HashMap::new();
+  for (i, a) in descriptors.iter().enumerate() {
+    for b in &descriptors[i + 1..] {
+      let (a_embedding, b_embedding) = (&embeddings[a], &embeddings[b]);
+      let (smaller, larger) = if a_embedding.len() < b_embedding.len() {
+        (a_embedding, b_embedding)
+      } else {
+        (b_embedding, a_embedding)
+      };
+      let dot_product = smaller
+        .iter()
+        .filter_map(|(dimension, value)| larger.get(dimension).map(|other| value * other))
+        .sum::<f64>();
+      let similarity = dot_product / (norms[a] * norms[b]);
+      if similarity >= MIN_DESCRIPTOR_SIMILARITY {
+        similar_descriptors
+          .entry(*a)
+          .or_default()
+          .push((*b, similarity));
+        similar_descriptors
+          .entry(*b)
+          .or_default()
+          .push((*a, similarity));
+      }
+    }
+  }
+
+  let mut similarities = vec![];
+  for (descriptor, mut similar) in similar_descriptors {
+    similar.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    similar.truncate(MAX_SIMILAR_DESCRIPTORS);
+    similarities.extend(similar.into_iter().map(|(similar_descriptor, similarity)| {
+      DescriptorSimilarity {
+        descriptor: descriptor.to_string(),
+        similar_descriptor: similar_descriptor.to_string(),
+        similarity: similarity.min(1.0),
+      }
+    }));
+  }
+  similarities
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_repository::ItemAndCount;
+
+  fn co_occurrences(albums: Vec<Vec<&str>>) -> DescriptorCoOccurrences {
+    let mut descriptor_counts: HashMap<&str, u32> = HashMap::new();
+    let mut pair_counts: HashMap<(&str, &str), u32> = HashMap::new();
+    for descriptors in &albums {
+      for (i, a) in descriptors.iter().enumerate() {
+        *descriptor_counts.entry(*a).or_default() += 1;
+        for b in &descriptors[i + 1..] {
+          *pair_counts.entry((*a, *b)).or_default() += 1;
+        }
+      }
+    }
+    DescriptorCoOccurrences {
+      album_count: albums.len() as u32,
+      descriptor_counts: descriptor_counts
+        .into_iter()
+        .map(|(name, count)| ItemAndCount {
+          name: name.to_string(),
+          count,
+        })
+        .collect(),
+      pair_counts: pair_counts
+        .into_iter()
+        .map(|((a, b), count)| (a.to_string(), b.to_string(), count))
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn test_compute_descriptor_similarities() {
+    let similarities =
+      DescriptorSimilarities::new(compute_descriptor_similarities(&co_occurrences(vec![
+        vec!["hypnotic", "dark"],
+        vec!["hypnotic", "nocturnal"],
+        vec!["atmospheric", "dark"],
+        vec!["atmospheric", "nocturnal"],
+        vec!["energetic", "happy"],
+        vec!["energetic", "happy"],
+        vec!["happy"],
+      ])));
+    let similar = similarities.similar_descriptors("hypnotic");
+    assert_eq!(similar.len(), 1);
+    assert_eq!(similar[0].0, "atmospheric");
+    assert!((similar[0].1 - 1.0).abs() < 1e-9);
+    assert!(similarities
+      .similar_descriptors("energetic")
+      .iter()
+      .all(|(descriptor, _)| descriptor != "hypnotic"));
+    assert!(similarities.similar_descriptors("unknown").is_empty());
+  }
+}
@@ -0,0 +1,91 @@
+use super::descriptor_similarity::{DescriptorSimilarities, DescriptorSimilarity};
+use crate::sqlite::SqliteConnection;
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+pub struct DescriptorSimilarityRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl DescriptorSimilarityRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Replaces every stored similarity, they're always computed across the whole catalog
+   */
+  #[instrument(skip(self, similarities), fields(count = similarities.len()))]
+  pub async fn put_all(&self, similarities: Vec<DescriptorSimilarity>) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM descriptor_similarities", [])?;
+        for similarity in similarities {
+          tx.execute(
+            "
+            INSERT INTO descriptor_similarities (descriptor, similar_descriptor, similarity)
+            VALUES (?, ?, ?)
+            ",
+            params![
+              similarity.descriptor,
+              similarity.similar_descriptor,
+              similarity.similarity
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put descriptor similarities"
+        );
+        anyhow!("Failed to put descriptor similarities")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_similarities(&self) -> Result<DescriptorSimilarities> {
+    let similarities = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT descriptor, similar_descriptor, similarity
+          FROM descriptor_similarities
+          ORDER BY descriptor, similarity DESC
+          ",
+        )?;
+        let rows = statement
+          .query_map([], |row| {
+            Ok(DescriptorSimilarity {
+              descriptor: row.get(0)?,
+              similar_descriptor: row.get(1)?,
+              similarity: row.get(2)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find descriptor similarities"
+        );
+        anyhow!("Failed to find descriptor similarities")
+      })??;
+
+    Ok(DescriptorSimilarities::new(similarities))
+  }
+}
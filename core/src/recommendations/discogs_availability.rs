@@ -0,0 +1,151 @@
+use super::types::AlbumRecommendation;
+use crate::albums::album_read_model::AlbumReadModel;
+use std::cmp::{Ordering, Reverse};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AlbumRecommendationSort {
+  #[default]
+  Score,
+  /**
+   * Most vinyl copies for sale on Discogs first
+   */
+  DiscogsAvailability,
+  /**
+   * Cheapest vinyl copy for sale on Discogs first
+   */
+  DiscogsLowestPrice,
+}
+
+/**
+ * Albums without a vinyl copy for sale never fit a price cap
+ */
+pub fn is_within_discogs_price(album: &AlbumReadModel, max_lowest_price: Option<f32>) -> bool {
+  max_lowest_price.map_or(true, |max| {
+    album
+      .discogs_release
+      .as_ref()
+      .and_then(|release| release.lowest_price)
+      .is_some_and(|price| price <= max)
+  })
+}
+
+/**
+ * Reorders best-first recommendations. Albums missing what they're sorted on come last, and ties
+ * keep their score order.
+ */
+pub fn sort_recommendations(
+  recommendations: &mut [AlbumRecommendation],
+  sort: AlbumRecommendationSort,
+) {
+  match sort {
+    AlbumRecommendationSort::Score => {}
+    AlbumRecommendationSort::DiscogsAvailability => {
+      recommendations.sort_by_key(|recommendation| {
+        Reverse(
+          recommendation
+            .album
+            .discogs_release
+            .as_ref()
+            .map_or(0, |release| release.num_for_sale),
+        )
+      });
+    }
+    AlbumRecommendationSort::DiscogsLowestPrice => {
+      let lowest_price = |recommendation: &AlbumRecommendation| {
+        recommendation
+          .album
+          .discogs_release
+          .as_ref()
+          .and_then(|release| release.lowest_price)
+      };
+      recommendations.sort_by(|a, b| match (lowest_price(a), lowest_price(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+      });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::albums::album_read_model::AlbumReadModelDiscogsRelease;
+  use anyhow::Result;
+
+  fn recommendation(
+    file_name: &str,
+    lowest_price: Option<f32>,
+    num_for_sale: Option<u32>,
+  ) -> Result<AlbumRecommendation> {
+    let mut recommendation = AlbumRecommendation::new_for_test(file_name, 1.0)?;
+    recommendation.album.discogs_release =
+      num_for_sale.map(|num_for_sale| AlbumReadModelDiscogsRelease {
+        lowest_price,
+        num_for_sale,
+        ..Default::default()
+      });
+    Ok(recommendation)
+  }
+
+  fn file_names(recommendations: &[AlbumRecommendation]) -> Vec<String> {
+    recommendations
+      .iter()
+      .map(|recommendation| recommendation.album.file_name.to_string())
+      .collect()
+  }
+
+  #[test]
+  fn test_sort_recommendations() -> Result<()> {
+    let mut recommendations = vec![
+      recommendation("release/album/a/a", None, None)?,
+      recommendation("release/album/b/b", Some(30.0), Some(2))?,
+      recommendation("release/album/c/c", Some(20.0), Some(5))?,
+      recommendation("release/album/d/d", None, Some(0))?,
+    ];
+
+    sort_recommendations(
+      &mut recommendations,
+      AlbumRecommendationSort::DiscogsLowestPrice,
+    );
+    assert_eq!(
+      file_names(&recommendations),
+      vec![
+        "release/album/c/c",
+        "release/album/b/b",
+        "release/album/a/a",
+        "release/album/d/d"
+      ]
+    );
+
+    sort_recommendations(
+      &mut recommendations,
+      AlbumRecommendationSort::DiscogsAvailability,
+    );
+    assert_eq!(
+      file_names(&recommendations),
+      vec![
+        "release/album/c/c",
+        "release/album/b/b",
+        "release/album/a/a",
+        "release/album/d/d"
+      ]
+    );
+
+    assert!(is_within_discogs_price(
+      &recommendations[0].album,
+      Some(25.0)
+    ));
+    assert!(!is_within_discogs_price(
+      &recommendations[1].album,
+      Some(25.0)
+    ));
+    assert!(!is_within_discogs_price(
+      &recommendations[2].album,
+      Some(25.0)
+    ));
+    assert!(is_within_discogs_price(&recommendations[2].album, None));
+    Ok(())
+  }
+}
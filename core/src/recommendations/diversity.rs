@@ -0,0 +1,138 @@
+use super::types::{AlbumRecommendation, AlbumRecommendationSettings};
+use chrono::Datelike;
+use std::{collections::HashMap, hash::Hash};
+
+fn is_capped<K: Eq + Hash>(counts: &HashMap<K, u32>, keys: &[K], max: Option<u32>) -> bool {
+  max.is_some_and(|max| {
+    keys
+      .iter()
+      .any(|key| counts.get(key).copied().unwrap_or(0) >= max)
+  })
+}
+
+fn increment<K: Eq + Hash>(counts: &mut HashMap<K, u32>, keys: Vec<K>) {
+  for key in keys {
+    *counts.entry(key).or_insert(0) += 1;
+  }
+}
+
+/**
+ * Greedily walks best-first recommendations, skipping any album whose artist, primary genre or
+ * release decade has already hit its cap
+ */
+pub fn apply_diversity_constraints(
+  recommendations: Vec<AlbumRecommendation>,
+  settings: &AlbumRecommendationSettings,
+) -> Vec<AlbumRecommendation> {
+  let mut artist_counts = HashMap::new();
+  let mut genre_counts = HashMap::new();
+  let mut decade_counts = HashMap::new();
+  let mut selected = Vec::new();
+  for recommendation in recommendations {
+    if selected.len() >= settings.count as usize {
+      break;
+    }
+    let album = &recommendation.album;
+    let artists = album
+      .artists
+      .iter()
+      .map(|artist| artist.file_name.clone())
+      .collect::<Vec<_>>();
+    let genres = album.primary_genres.clone();
+    let decades = album
+      .release_date
+      .map(|date| vec![date.year() / 10 * 10])
+      .unwrap_or_default();
+    if is_capped(&artist_counts, &artists, settings.max_albums_per_artist)
+      || is_capped(
+        &genre_counts,
+        &genres,
+        settings.max_albums_per_primary_genre,
+      )
+      || is_capped(&decade_counts, &decades, settings.max_albums_per_decade)
+    {
+      continue;
+    }
+    increment(&mut artist_counts, artists);
+    increment(&mut genre_counts, genres);
+    increment(&mut decade_counts, decades);
+    selected.push(recommendation);
+  }
+  selected
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    albums::album_read_model::AlbumReadModelArtist, files::file_metadata::file_name::FileName,
+  };
+  use anyhow::Result;
+  use chrono::NaiveDate;
+
+  fn recommendation(
+    file_name: &str,
+    artist: &str,
+    genre: &str,
+    year: i32,
+  ) -> Result<AlbumRecommendation> {
+    let mut recommendation = AlbumRecommendation::new_for_test(file_name, 0.0)?;
+    recommendation.album.artists = vec![AlbumReadModelArtist {
+      name: artist.to_string(),
+      file_name: FileName::try_from(format!("artist/{}", artist))?,
+    }];
+    recommendation.album.primary_genres = vec![genre.to_string()];
+    recommendation.album.release_date = NaiveDate::from_ymd_opt(year, 1, 1);
+    Ok(recommendation)
+  }
+
+  #[test]
+  fn test_apply_diversity_constraints() -> Result<()> {
+    let recommendations = vec![
+      recommendation(
+        "release/album/billy-woods/aethiopes",
+        "billy-woods",
+        "Hip Hop",
+        2022,
+      )?,
+      recommendation(
+        "release/album/billy-woods/hiding-places",
+        "billy-woods",
+        "Hip Hop",
+        2019,
+      )?,
+      recommendation(
+        "release/album/armand-hammer/haram",
+        "armand-hammer",
+        "Hip Hop",
+        2021,
+      )?,
+      recommendation("release/album/bjork/vulnicura", "bjork", "Art Pop", 2015)?,
+      recommendation(
+        "release/album/sade/love-deluxe",
+        "sade",
+        "Sophisti-Pop",
+        1992,
+      )?,
+    ];
+    let settings = AlbumRecommendationSettings {
+      count: 3,
+      max_albums_per_artist: Some(1),
+      max_albums_per_decade: Some(1),
+      ..Default::default()
+    };
+    let selected = apply_diversity_constraints(recommendations, &settings)
+      .into_iter()
+      .map(|r| r.album.file_name.to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      selected,
+      vec![
+        "release/album/billy-woods/aethiopes",
+        "release/album/bjork/vulnicura",
+        "release/album/sade/love-deluxe",
+      ]
+    );
+    Ok(())
+  }
+}
@@ -4,7 +4,7 @@ use crate::{
     album_read_model::AlbumReadModel,
     album_search_index::{AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchQueryBuilder},
   },
-  helpers::embedding::average_embedding,
+  helpers::{embedding::average_embedding, math::cosine_similarity},
   recommendations::{
     seed::AlbumRecommendationSeedContext,
     types::{
@@ -15,7 +15,7 @@ use crate::{
 };
 use anyhow::Result;
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::{cmp::max, collections::HashMap, sync::Arc};
 use tracing::{instrument, warn};
 
 pub struct EmbeddingSimilarityInteractor {
@@ -53,6 +53,91 @@ impl EmbeddingSimilarityInteractor {
         .collect(),
     ))
   }
+
+  /**
+   * Average embedding of the negative seed and its weight, if there's a negative seed with any
+   * embedded albums
+   */
+  async fn get_average_negative_seed_embedding(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    settings: &EmbeddingSimilarityAlbumAssessmentSettings,
+  ) -> Result<Option<(Vec<f32>, f32)>> {
+    let Some(negative) = seed_context
+      .negative
+      .as_ref()
+      .filter(|negative| negative.weight > 0.0)
+    else {
+      return Ok(None);
+    };
+    let album_embeddings = self
+      .album_interactor
+      .find_many_embeddings(negative.context.album_file_names(), &settings.embedding_key)
+      .await?;
+    if album_embeddings.is_empty() {
+      return Ok(None);
+    }
+    Ok(Some((
+      average_embedding(
+        album_embeddings
+          .iter()
+          .map(|embedding| {
+            (
+              &embedding.embedding,
+              negative
+                .context
+                .get_factor(&embedding.file_name)
+                .unwrap_or(1),
+            )
+          })
+          .collect(),
+      ),
+      negative.weight,
+    )))
+  }
+
+  /**
+   * Scores are distances, so albums are pushed away by their similarity to the negative seed.
+   * Results are re-sorted closest first.
+   */
+  async fn apply_negative_seed_penalty(
+    &self,
+    mut recommendations: Vec<AlbumRecommendation>,
+    negative_embedding: &[f32],
+    weight: f32,
+    settings: &EmbeddingSimilarityAlbumAssessmentSettings,
+  ) -> Result<Vec<AlbumRecommendation>> {
+    let embeddings = self
+      .album_interactor
+      .find_many_embeddings(
+        recommendations
+          .iter()
+          .map(|recommendation| recommendation.album.file_name.clone())
+          .collect(),
+        &settings.embedding_key,
+      )
+      .await?
+      .into_iter()
+      .map(|embedding| (embedding.file_name, embedding.embedding))
+      .collect::<HashMap<_, _>>();
+    for recommendation in recommendations.iter_mut() {
+      let Some(embedding) = embeddings.get(&recommendation.album.file_name) else {
+        continue;
+      };
+      let negative_similarity = cosine_similarity(embedding, negative_embedding);
+      recommendation.assessment.score += weight * negative_similarity.max(0.0);
+      recommendation
+        .assessment
+        .metadata
+        .get_or_insert_with(HashMap::new)
+        .insert(
+          "negative_seed_similarity".to_string(),
+          negative_similarity.to_string(),
+        );
+    }
+    recommendations.sort_by(|a, b| a.cmp(b));
+    Ok(recommendations)
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -101,14 +186,32 @@ impl
         limit: 1,
       })
       .await?;
-    let (_, score) = search_result.pop().ok_or_else(|| {
+    let (album, score) = search_result.pop().ok_or_else(|| {
       warn!("Embeddings search returned no results");
       anyhow::anyhow!("Embeddings search returned no results")
     })?;
-    Ok(AlbumAssessment {
-      score,
-      metadata: None,
-    })
+    let recommendation = AlbumRecommendation {
+      album,
+      assessment: AlbumAssessment {
+        score,
+        metadata: None,
+        contributions: vec![],
+      },
+      exploratory: false,
+    };
+    match self
+      .get_average_negative_seed_embedding(seed_context, &settings)
+      .await?
+    {
+      Some((negative_embedding, weight)) => Ok(
+        self
+          .apply_negative_seed_penalty(vec![recommendation], &negative_embedding, weight, &settings)
+          .await?
+          .remove(0)
+          .assessment,
+      ),
+      None => Ok(recommendation.assessment),
+    }
   }
 
   #[instrument(
@@ -124,27 +227,51 @@ impl
     let profile_embedding = self
       .get_average_seed_embedding(&seed_context, &assessment_settings)
       .await?;
-    let search_query = recommendation_settings.to_search_query(&seed_context.albums)?;
+    let negative_seed_embedding = self
+      .get_average_negative_seed_embedding(seed_context, &assessment_settings)
+      .await?;
+    let count = recommendation_settings.count as usize;
+    // Over-fetch when penalizing so that albums pushed down by the negative seed can be replaced
+    let limit = match negative_seed_embedding {
+      Some(_) => max(count * 3, 50),
+      None => count,
+    };
+    let search_query = recommendation_settings.to_search_query(seed_context)?;
     let similar_albums = self
       .album_interactor
       .embedding_similarity_search(&AlbumEmbeddingSimilarirtySearchQuery {
         embedding: profile_embedding,
         embedding_key: assessment_settings.embedding_key.clone(),
         filters: search_query,
-        limit: recommendation_settings.count as usize,
+        limit,
       })
       .await?;
-    Ok(
-      similar_albums
-        .into_iter()
-        .map(|(album, score)| AlbumRecommendation {
-          album,
-          assessment: AlbumAssessment {
-            score,
-            metadata: None,
-          },
-        })
-        .collect(),
-    )
+    let recommendations = similar_albums
+      .into_iter()
+      .map(|(album, score)| AlbumRecommendation {
+        album,
+        assessment: AlbumAssessment {
+          score,
+          metadata: None,
+          contributions: vec![],
+        },
+        exploratory: false,
+      })
+      .collect::<Vec<_>>();
+    match negative_seed_embedding {
+      Some((negative_embedding, weight)) => {
+        let mut recommendations = self
+          .apply_negative_seed_penalty(
+            recommendations,
+            &negative_embedding,
+            weight,
+            &assessment_settings,
+          )
+          .await?;
+        recommendations.truncate(count);
+        Ok(recommendations)
+      }
+      None => Ok(recommendations),
+    }
   }
 }
@@ -0,0 +1,180 @@
+use super::types::{AlbumRecommendation, AlbumRecommendationSettings, ScoreOrder};
+use crate::files::file_metadata::file_name::FileName;
+use anyhow::{anyhow, Result};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+
+const DEFAULT_EXPLORATION_TEMPERATURE: f32 = 0.25;
+const DEFAULT_EXPLORATION_MIN_RATING: f32 = 3.0;
+const MIN_EXPLORATION_TEMPERATURE: f32 = 0.01;
+
+/**
+ * Weights candidates by how good their score is relative to the pool, so the temperature means the
+ * same thing whatever scale or direction the assessment method scores on
+ */
+fn exploration_weights(
+  candidates: &[AlbumRecommendation],
+  temperature: f32,
+  score_order: &ScoreOrder,
+) -> Result<Vec<f64>> {
+  if let Some(candidate) = candidates
+    .iter()
+    .find(|candidate| !candidate.assessment.score.is_finite())
+  {
+    return Err(anyhow!(
+      "Cannot explore candidate {} with non-finite score {}",
+      candidate.album.file_name.to_string(),
+      candidate.assessment.score
+    ));
+  }
+  let (min_score, max_score) = candidates.iter().fold(
+    (f32::INFINITY, f32::NEG_INFINITY),
+    |(min, max), candidate| {
+      (
+        min.min(candidate.assessment.score),
+        max.max(candidate.assessment.score),
+      )
+    },
+  );
+  let range = max_score - min_score;
+  Ok(
+    candidates
+      .iter()
+      .map(|candidate| {
+        let normalized = if range > 0.0 {
+          (candidate.assessment.score - min_score) / range
+        } else {
+          1.0
+        };
+        let goodness = match score_order {
+          ScoreOrder::Ascending => 1.0 - normalized,
+          ScoreOrder::Descending => normalized,
+        };
+        // Offset by the best goodness so the best candidate's weight is 1 and nothing overflows
+        (((goodness - 1.0) / temperature) as f64).exp()
+      })
+      .collect(),
+  )
+}
+
+/**
+ * Appends exploratory picks to the already selected recommendations, sampled without replacement
+ * from the remaining candidates that clear the rating gate
+ */
+pub fn fill_exploration_slots<R: Rng + ?Sized>(
+  selected: Vec<AlbumRecommendation>,
+  candidates: Vec<AlbumRecommendation>,
+  settings: &AlbumRecommendationSettings,
+  score_order: &ScoreOrder,
+  rng: &mut R,
+) -> Result<Vec<AlbumRecommendation>> {
+  let slots = settings.exploration_slots() as usize;
+  let temperature = settings
+    .exploration_temperature
+    .unwrap_or(DEFAULT_EXPLORATION_TEMPERATURE)
+    .max(MIN_EXPLORATION_TEMPERATURE);
+  let min_rating = settings
+    .exploration_min_rating
+    .unwrap_or(DEFAULT_EXPLORATION_MIN_RATING);
+  let selected_file_names = selected
+    .iter()
+    .map(|recommendation| recommendation.album.file_name.clone())
+    .collect::<HashSet<FileName>>();
+  let pool = candidates
+    .into_iter()
+    .filter(|candidate| {
+      !selected_file_names.contains(&candidate.album.file_name)
+        && candidate.album.rating >= min_rating
+    })
+    .collect::<Vec<_>>();
+  let weights = exploration_weights(&pool, temperature, score_order)?;
+  let weighted_pool = pool.into_iter().zip(weights).collect::<Vec<_>>();
+  let explored = weighted_pool
+    .choose_multiple_weighted(rng, slots, |(_, weight)| *weight)
+    .map(|picks| {
+      picks
+        .map(|(candidate, _)| AlbumRecommendation {
+          exploratory: true,
+          ..candidate.clone()
+        })
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+  let mut recommendations = selected;
+  recommendations.extend(explored);
+  Ok(recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  fn recommendation(file_name: &str, score: f32, rating: f32) -> Result<AlbumRecommendation> {
+    let mut recommendation = AlbumRecommendation::new_for_test(file_name, score)?;
+    recommendation.album.rating = rating;
+    Ok(recommendation)
+  }
+
+  #[test]
+  fn test_fill_exploration_slots() -> Result<()> {
+    let candidates = vec![
+      recommendation("release/album/billy-woods/aethiopes", 0.9, 3.8)?,
+      recommendation("release/album/armand-hammer/haram", 0.7, 3.6)?,
+      recommendation("release/album/bjork/vulnicura", 0.5, 3.5)?,
+      recommendation("release/album/sade/love-deluxe", 0.4, 2.1)?,
+    ];
+    let settings = AlbumRecommendationSettings {
+      count: 3,
+      exploration_slots: Some(2),
+      ..Default::default()
+    };
+    let recommendations = fill_exploration_slots(
+      candidates[..1].to_vec(),
+      candidates,
+      &settings,
+      &ScoreOrder::Descending,
+      &mut StdRng::seed_from_u64(7),
+    )?;
+    assert_eq!(recommendations.len(), 3);
+    assert!(!recommendations[0].exploratory);
+    let explored = recommendations[1..]
+      .iter()
+      .map(|recommendation| {
+        assert!(recommendation.exploratory);
+        recommendation.album.file_name.to_string()
+      })
+      .collect::<HashSet<_>>();
+    assert_eq!(
+      explored,
+      HashSet::from([
+        "release/album/armand-hammer/haram".to_string(),
+        "release/album/bjork/vulnicura".to_string(),
+      ])
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_exploration_weights_favor_smallest_distance() -> Result<()> {
+    let candidates = vec![
+      recommendation("release/album/billy-woods/aethiopes", 0.1, 3.8)?,
+      recommendation("release/album/armand-hammer/haram", 0.4, 3.6)?,
+      recommendation("release/album/bjork/vulnicura", 0.9, 3.5)?,
+    ];
+    let weights = exploration_weights(&candidates, 0.25, &ScoreOrder::Ascending)?;
+    assert_eq!(weights[0], 1.0);
+    assert!(weights[0] > weights[1] && weights[1] > weights[2]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_exploration_weights_reject_non_finite_scores() -> Result<()> {
+    let candidates = vec![
+      recommendation("release/album/billy-woods/aethiopes", 0.9, 3.8)?,
+      recommendation("release/album/armand-hammer/haram", f32::NAN, 3.6)?,
+    ];
+    assert!(exploration_weights(&candidates, 0.25, &ScoreOrder::Descending).is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,89 @@
+use crate::{files::file_metadata::file_name::FileName, helpers::csv::parse_csv_records};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/**
+ * Albums that no profile should be recommended, e.g. everything already in the owner's library.
+ * Applied to every recommendation request unless it opts out.
+ */
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GlobalExclusion {
+  pub file_names: HashSet<FileName>,
+}
+
+impl GlobalExclusion {
+  pub fn update(&mut self, add: Vec<FileName>, remove: Vec<FileName>) {
+    for file_name in remove {
+      self.file_names.remove(&file_name);
+    }
+    self.file_names.extend(add);
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedGlobalExclusionRow {
+  /**
+   * 1-based line number of the row in the uploaded file
+   */
+  pub line: usize,
+  pub file_name: Result<FileName, String>,
+}
+
+/**
+ * Reads album file names from the `file_name` column, or the first column when there's no
+ * header row
+ */
+pub fn parse_global_exclusion_csv(content: &str) -> Vec<ParsedGlobalExclusionRow> {
+  let mut records = parse_csv_records(content).into_iter().peekable();
+  let header_column = records.peek().and_then(|(_, record)| {
+    record.iter().position(|header| {
+      ["file_name", "file name"].contains(&header.trim().to_lowercase().as_str())
+    })
+  });
+  if header_column.is_some() {
+    records.next();
+  }
+  let column = header_column.unwrap_or(0);
+  records
+    .map(|(line, record)| ParsedGlobalExclusionRow {
+      line,
+      file_name: record
+        .get(column)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| "Missing file name".to_string())
+        .and_then(|value| FileName::try_from(value.to_string()).map_err(|e| e.to_string())),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_parse_global_exclusion_csv() -> Result<()> {
+    let rows = parse_global_exclusion_csv(
+      "Artist,File Name\nbilly woods,release/album/billy-woods/aethiopes\nbjork,\n",
+    );
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].line, 2);
+    assert_eq!(
+      rows[0].file_name,
+      Ok(FileName::try_from("release/album/billy-woods/aethiopes")?)
+    );
+    assert_eq!(rows[1].file_name, Err("Missing file name".to_string()));
+    Ok(())
+  }
+
+  #[test]
+  fn test_parse_global_exclusion_csv_without_header() -> Result<()> {
+    let rows = parse_global_exclusion_csv("release/album/bjork/vulnicura\n");
+    assert_eq!(
+      rows[0].file_name,
+      Ok(FileName::try_from("release/album/bjork/vulnicura")?)
+    );
+    Ok(())
+  }
+}
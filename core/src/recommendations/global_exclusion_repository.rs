@@ -0,0 +1,35 @@
+use super::global_exclusion::GlobalExclusion;
+use crate::{helpers::document_store::DocumentStore, tenant::tenant_id::TenantId};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct GlobalExclusionRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "global_exclusion";
+const KEY: &str = "global";
+
+impl GlobalExclusionRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn get(&self, tenant_id: &TenantId) -> Result<GlobalExclusion> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<GlobalExclusion>(COLLECTION, &tenant_id.scope_key(KEY))
+        .await?
+        .map(|doc| doc.document)
+        .unwrap_or_default(),
+    )
+  }
+
+  pub async fn put(&self, tenant_id: &TenantId, exclusion: GlobalExclusion) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &tenant_id.scope_key(KEY), exclusion, None)
+      .await
+  }
+}
@@ -1,10 +1,35 @@
+pub mod collaborative_filtering;
+pub mod cross_encoder_reranking;
+mod descriptor_similarity;
+mod descriptor_similarity_repository;
+mod discogs_availability;
+mod diversity;
 mod embedding_similarity;
-mod quantile_ranking;
+mod exploration;
+mod global_exclusion;
+mod global_exclusion_repository;
+mod playlist_energy_curve;
+mod profile_compatibility;
+pub mod quantile_ranking;
+mod recommendation_curation;
+mod recommendation_curation_repository;
+mod recommendation_digest;
+pub mod recommendation_digest_jobs;
+mod recommendation_digest_repository;
 pub mod recommendation_event_subscribers;
-mod recommendation_interactor;
+pub mod recommendation_interactor;
 pub mod recommendation_jobs;
+mod recommendation_rationale;
+mod recommendation_rationale_client;
+mod recommendation_rationale_repository;
 pub mod recommendation_service;
+pub mod redis_spotify_track_search_index;
 mod reranked_embedding_similarity;
 pub mod seed;
 pub mod spotify_track_search_index;
-mod types;
+pub mod sqlite_spotify_track_search_index;
+mod track_sequencing;
+pub mod types;
+mod year_in_review;
+pub mod year_in_review_jobs;
+mod year_in_review_repository;
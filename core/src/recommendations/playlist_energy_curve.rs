@@ -0,0 +1,75 @@
+const MIN_TARGET_ENERGY: f32 = 0.2;
+const MAX_TARGET_ENERGY: f32 = 0.9;
+
+/**
+ * How far a track's energy may stray from its position's target before another track from the
+ * same album is preferred
+ */
+pub const TARGET_ENERGY_TOLERANCE: f32 = 0.15;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PlaylistEnergyCurve {
+  #[default]
+  Unshaped,
+  Rising,
+  Falling,
+  /**
+   * Builds up to a peak halfway through, then winds down
+   */
+  Arc,
+}
+
+impl PlaylistEnergyCurve {
+  /**
+   * Energy the track at `position` should have in a playlist of `length` tracks, if the curve
+   * constrains it at all
+   */
+  pub fn target_energy(&self, position: usize, length: usize) -> Option<f32> {
+    let progress = if length > 1 {
+      position as f32 / (length - 1) as f32
+    } else {
+      0.5
+    };
+    let shape = match self {
+      PlaylistEnergyCurve::Unshaped => return None,
+      PlaylistEnergyCurve::Rising => progress,
+      PlaylistEnergyCurve::Falling => 1.0 - progress,
+      PlaylistEnergyCurve::Arc => 1.0 - (2.0 * progress - 1.0).abs(),
+    };
+    Some(MIN_TARGET_ENERGY + (MAX_TARGET_ENERGY - MIN_TARGET_ENERGY) * shape)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn is_close(energy: Option<f32>, expected: f32) -> bool {
+    energy.is_some_and(|energy| (energy - expected).abs() < 1e-6)
+  }
+
+  #[test]
+  fn test_target_energy() {
+    assert_eq!(PlaylistEnergyCurve::Unshaped.target_energy(0, 5), None);
+    assert!(is_close(
+      PlaylistEnergyCurve::Rising.target_energy(0, 5),
+      MIN_TARGET_ENERGY
+    ));
+    assert!(is_close(
+      PlaylistEnergyCurve::Rising.target_energy(4, 5),
+      MAX_TARGET_ENERGY
+    ));
+    assert!(is_close(
+      PlaylistEnergyCurve::Falling.target_energy(0, 5),
+      MAX_TARGET_ENERGY
+    ));
+    assert!(is_close(
+      PlaylistEnergyCurve::Arc.target_energy(2, 5),
+      MAX_TARGET_ENERGY
+    ));
+    assert!(is_close(
+      PlaylistEnergyCurve::Arc.target_energy(4, 5),
+      MIN_TARGET_ENERGY
+    ));
+  }
+}
@@ -0,0 +1,94 @@
+use super::types::AlbumRecommendation;
+use crate::{files::file_metadata::file_name::FileName, helpers::item_with_factor::ItemWithFactor};
+use std::collections::HashMap;
+
+/**
+ * Number of each profile's top genres and descriptors compared for shared ones
+ */
+pub const SHARED_ITEM_DEPTH: usize = 25;
+
+#[derive(Clone, Debug)]
+pub struct ProfileCompatibility {
+  /**
+   * Factor weighted average assessment of the candidate profile's albums against the seed profile
+   */
+  pub score: f32,
+  pub assessed_album_count: u32,
+  pub shared_album_count: u32,
+  pub shared_primary_genres: Vec<String>,
+  pub shared_descriptors: Vec<String>,
+  /**
+   * The candidate profile's albums that best fit the seed profile, best first
+   */
+  pub bridge_albums: Vec<AlbumRecommendation>,
+}
+
+/**
+ * Items in the top `depth` of both lists, ordered by their combined rank. Both lists are expected
+ * to be sorted by descending factor.
+ */
+pub fn shared_items(a: &[ItemWithFactor], b: &[ItemWithFactor], depth: usize) -> Vec<String> {
+  let b_ranks = b
+    .iter()
+    .take(depth)
+    .enumerate()
+    .map(|(rank, item)| (item.item.as_str(), rank))
+    .collect::<HashMap<_, _>>();
+  let mut shared = a
+    .iter()
+    .take(depth)
+    .enumerate()
+    .filter_map(|(rank, item)| {
+      b_ranks
+        .get(item.item.as_str())
+        .map(|b_rank| (item.item.clone(), rank + b_rank))
+    })
+    .collect::<Vec<_>>();
+  shared.sort_by_key(|(_, rank)| *rank);
+  shared.into_iter().map(|(item, _)| item).collect()
+}
+
+pub fn compatibility_score(
+  assessed: &[AlbumRecommendation],
+  factor_map: &HashMap<FileName, u32>,
+) -> f32 {
+  let (weighted_score, total_factor) =
+    assessed
+      .iter()
+      .fold((0.0, 0.0), |(weighted_score, total_factor), assessed| {
+        let factor = *factor_map.get(&assessed.album.file_name).unwrap_or(&1) as f32;
+        (
+          weighted_score + assessed.assessment.score * factor,
+          total_factor + factor,
+        )
+      });
+  if total_factor == 0.0 {
+    0.0
+  } else {
+    weighted_score / total_factor
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn items(names: &[&str]) -> Vec<ItemWithFactor> {
+    names
+      .iter()
+      .enumerate()
+      .map(|(i, name)| ItemWithFactor {
+        item: name.to_string(),
+        factor: (names.len() - i) as u32,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_shared_items() {
+    let a = items(&["Jazz", "Ambient", "Post-Rock", "Techno"]);
+    let b = items(&["Techno", "Post-Rock", "Folk", "Jazz"]);
+    assert_eq!(shared_items(&a, &b, 4), vec!["Jazz", "Post-Rock", "Techno"]);
+    assert_eq!(shared_items(&a, &b, 2), Vec::<String>::new());
+  }
+}
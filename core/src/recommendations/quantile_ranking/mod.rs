@@ -1,4 +1,5 @@
 pub mod bounded_min_heap;
+pub mod personnel_radar;
 pub mod quantile_rank;
 pub mod quantile_rank_assessment;
 pub mod quantile_rank_interactor;
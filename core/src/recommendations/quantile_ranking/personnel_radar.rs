@@ -0,0 +1,181 @@
+use super::quantile_rank::QuantileRanking;
+use crate::{
+  albums::album_read_model::AlbumReadModel, helpers::item_with_factor::ItemWithFactor,
+  recommendations::seed::AlbumRecommendationSeedContext,
+};
+use derive_builder::Builder;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum CreditRoleClass {
+  Producer,
+  Engineer,
+  FeaturedArtist,
+}
+
+impl CreditRoleClass {
+  /**
+   * Normalizes a free-form credit role (e.g. "Co-producer", "Mixing Engineer", "Guest Vocals")
+   * into the role class it belongs to, if any
+   */
+  pub fn from_role(role: &str) -> Option<Self> {
+    let role = role.to_lowercase();
+    if role.contains("produc") {
+      Some(Self::Producer)
+    } else if [
+      "engineer",
+      "mixing",
+      "mixed",
+      "mastering",
+      "mastered",
+      "recording",
+      "recorded",
+    ]
+    .iter()
+    .any(|keyword| role.contains(keyword))
+    {
+      Some(Self::Engineer)
+    } else if role.contains("featur") || role.contains("guest") {
+      Some(Self::FeaturedArtist)
+    } else {
+      None
+    }
+  }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(setter(into), default)]
+pub struct PersonnelRadarRoleWeights {
+  pub producer_weight: u32,
+  pub engineer_weight: u32,
+  pub featured_artist_weight: u32,
+}
+
+impl Default for PersonnelRadarRoleWeights {
+  fn default() -> Self {
+    Self {
+      producer_weight: 3,
+      engineer_weight: 2,
+      featured_artist_weight: 1,
+    }
+  }
+}
+
+impl PersonnelRadarRoleWeights {
+  pub fn get(&self, role_class: CreditRoleClass) -> u32 {
+    match role_class {
+      CreditRoleClass::Producer => self.producer_weight,
+      CreditRoleClass::Engineer => self.engineer_weight,
+      CreditRoleClass::FeaturedArtist => self.featured_artist_weight,
+    }
+  }
+
+  fn max(&self) -> u32 {
+    self
+      .producer_weight
+      .max(self.engineer_weight)
+      .max(self.featured_artist_weight)
+  }
+}
+
+/**
+ * Distinct "{artist_file_name}:{role_class}" tags for an album's credited personnel
+ */
+pub fn personnel_tags(album: &AlbumReadModel) -> Vec<(String, CreditRoleClass)> {
+  let mut seen = HashSet::new();
+  album
+    .credits
+    .iter()
+    .flat_map(|credit| {
+      credit.roles.iter().filter_map(|role| {
+        CreditRoleClass::from_role(role).map(|role_class| {
+          (
+            format!("{}:{}", credit.artist.file_name.to_string(), role_class),
+            role_class,
+          )
+        })
+      })
+    })
+    .filter(|(tag, _)| seen.insert(tag.clone()))
+    .collect()
+}
+
+pub struct PersonnelRadar {
+  ranking: QuantileRanking<ItemWithFactor>,
+  summary_map: HashMap<String, ItemWithFactor>,
+  role_weights: PersonnelRadarRoleWeights,
+}
+
+impl PersonnelRadar {
+  pub fn new(
+    seed_context: &AlbumRecommendationSeedContext,
+    role_weights: PersonnelRadarRoleWeights,
+  ) -> Self {
+    let mut factors: HashMap<String, u32> = HashMap::new();
+    for album in &seed_context.albums {
+      let factor = seed_context.get_factor(&album.file_name).unwrap_or(1);
+      for (tag, _) in personnel_tags(album) {
+        *factors.entry(tag).or_insert(0) += factor;
+      }
+    }
+    let items = factors
+      .into_iter()
+      .map(|(item, factor)| ItemWithFactor { item, factor })
+      .collect::<Vec<_>>();
+    Self {
+      ranking: QuantileRanking::new(&items),
+      summary_map: items
+        .into_iter()
+        .map(|item| (item.item.clone(), item))
+        .collect(),
+      role_weights,
+    }
+  }
+
+  /**
+   * Ranks an album by the most heavily weighted personnel it shares with the seed, scaled by the
+   * weight of the role class they were credited under. Albums sharing no personnel rank 0.
+   */
+  pub fn rank(&self, album: &AlbumReadModel) -> f64 {
+    let max_role_weight = self.role_weights.max();
+    if max_role_weight == 0 {
+      return 0.0;
+    }
+    personnel_tags(album)
+      .into_iter()
+      .filter_map(|(tag, role_class)| {
+        self.summary_map.get(&tag).map(|item| {
+          self.ranking.get_rank(item) * self.role_weights.get(role_class) as f64
+            / max_role_weight as f64
+        })
+      })
+      .fold(0.0, f64::max)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_credit_role_class_from_role() {
+    assert_eq!(
+      CreditRoleClass::from_role("Co-producer"),
+      Some(CreditRoleClass::Producer)
+    );
+    assert_eq!(
+      CreditRoleClass::from_role("Mixing Engineer"),
+      Some(CreditRoleClass::Engineer)
+    );
+    assert_eq!(
+      CreditRoleClass::from_role("Mastering"),
+      Some(CreditRoleClass::Engineer)
+    );
+    assert_eq!(
+      CreditRoleClass::from_role("Guest Vocals"),
+      Some(CreditRoleClass::FeaturedArtist)
+    );
+    assert_eq!(CreditRoleClass::from_role("Drums"), None);
+  }
+}
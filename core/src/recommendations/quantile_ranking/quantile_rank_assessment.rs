@@ -1,17 +1,30 @@
 use super::{
-  quantile_rank::QuantileRanking, quantile_rank_interactor::QuantileRankAlbumAssessmentSettings,
+  personnel_radar::PersonnelRadar, quantile_rank::QuantileRanking,
+  quantile_rank_interactor::QuantileRankAlbumAssessmentSettings,
 };
 use crate::{
   albums::{album_collection_summary::AlbumCollectionSummary, album_read_model::AlbumReadModel},
+  files::file_metadata::file_name::FileName,
+  genres::genre_taxonomy::GenreTaxonomy,
   helpers::{item_with_factor::ItemWithFactor, math::default_if_zero},
-  recommendations::{seed::AlbumRecommendationSeedContext, types::AlbumAssessment},
+  recommendations::{
+    descriptor_similarity::DescriptorSimilarities,
+    seed::AlbumRecommendationSeedContext,
+    types::{AlbumAssessment, AlbumAssessmentContribution},
+  },
 };
 use anyhow::{anyhow, Result};
 use num_traits::Zero;
 use ordered_float::OrderedFloat;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use tracing::warn;
 
+/**
+ * How many levels up or down the genre hierarchy a profile genre can be and still lend partial
+ * credit to an album genre
+ */
+const MAX_GENRE_HIERARCHY_DISTANCE: u32 = 2;
+
 fn create_item_with_factor_map(items: Vec<ItemWithFactor>) -> HashMap<String, ItemWithFactor> {
   items
     .into_iter()
@@ -19,21 +32,87 @@ fn create_item_with_factor_map(items: Vec<ItemWithFactor>) -> HashMap<String, It
     .collect::<HashMap<String, ItemWithFactor>>()
 }
 
+struct FactorRank {
+  rank: f64,
+  matched_items: Vec<String>,
+  novel_items: Vec<String>,
+}
+
+impl FactorRank {
+  fn new(rank: f64) -> Self {
+    Self {
+      rank,
+      matched_items: vec![],
+      novel_items: vec![],
+    }
+  }
+}
+
+/**
+ * Tags partially matching a tag, like its parent and child genres or similar descriptors, along
+ * with the share of their rank it gets
+ */
+type RelatedTags<'a> = &'a dyn Fn(&str) -> Vec<(String, f64)>;
+
+fn no_related_tags(_: &str) -> Vec<(String, f64)> {
+  vec![]
+}
+
+/**
+ * Best partial rank among the profile tags related to the tag
+ */
+fn related_tag_rank(
+  ranking: &QuantileRanking<ItemWithFactor>,
+  profile_tags_map: &HashMap<String, ItemWithFactor>,
+  tag: &str,
+  related_tags: RelatedTags,
+) -> Option<f64> {
+  related_tags(tag)
+    .into_iter()
+    .filter(|(_, credit)| *credit > 0.0)
+    .filter_map(|(related_tag, credit)| {
+      profile_tags_map
+        .get(&related_tag)
+        .map(|item| ranking.get_rank(item) * credit)
+    })
+    .max_by(|a, b| a.total_cmp(b))
+}
+
 fn calculate_average_rank(
   ranking: &QuantileRanking<ItemWithFactor>,
   profile_tags_map: &HashMap<String, ItemWithFactor>,
   album_tags: &[String],
   novelty_score: f64,
-) -> Result<f64> {
+  related_tags: RelatedTags,
+) -> Result<FactorRank> {
   if album_tags.is_empty() {
-    return Ok(novelty_score);
+    return Ok(FactorRank::new(novelty_score));
   }
 
+  let mut matched_items = vec![];
+  let mut novel_items = vec![];
   let ranks = album_tags
     .iter()
     .map(|tag: &String| match profile_tags_map.get(tag) {
-      Some(item) => default_if_zero(ranking.get_rank(item), novelty_score),
-      None => novelty_score,
+      Some(item) => {
+        let rank = ranking.get_rank(item);
+        matched_items.push((tag.clone(), rank));
+        default_if_zero(rank, novelty_score)
+      }
+      None => {
+        let related_rank = related_tag_rank(ranking, profile_tags_map, tag, related_tags)
+          .filter(|rank| *rank > novelty_score);
+        match related_rank {
+          Some(rank) => {
+            matched_items.push((tag.clone(), rank));
+            rank
+          }
+          None => {
+            novel_items.push(tag.clone());
+            novelty_score
+          }
+        }
+      }
     })
     .collect::<Vec<f64>>();
 
@@ -43,21 +122,22 @@ fn calculate_average_rank(
     warn!("rank is NaN");
   }
 
-  Ok(rank)
+  matched_items.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+  Ok(FactorRank {
+    rank,
+    matched_items: matched_items.into_iter().map(|(tag, _)| tag).collect(),
+    novel_items,
+  })
 }
 
-fn compute_ranks<F>(weight: u32, compute_fn: F) -> Result<(f64, Vec<f64>)>
+fn compute_rank<F>(weight: u32, compute_fn: F) -> Result<Option<FactorRank>>
 where
-  F: FnOnce() -> Result<f64>,
+  F: FnOnce() -> Result<FactorRank>,
 {
   if weight.is_zero() {
-    return Ok((0.0, vec![]));
-  }
-
-  match compute_fn() {
-    Ok(rank) => Ok((rank, vec![rank; weight as usize])),
-    Err(err) => Err(err),
+    return Ok(None);
   }
+  compute_fn().map(Some)
 }
 
 pub struct QuantileRankAlbumAssessmentContext {
@@ -68,17 +148,42 @@ pub struct QuantileRankAlbumAssessmentContext {
   rating_count_ranking: QuantileRanking<u32>,
   descriptor_count_ranking: QuantileRanking<u32>,
   credit_tag_ranking: QuantileRanking<ItemWithFactor>,
+  personnel_radar: PersonnelRadar,
+  artist_listen_score_ranking: QuantileRanking<OrderedFloat<f64>>,
+  artist_listen_scores: HashMap<FileName, f64>,
+  genre_taxonomy: Arc<GenreTaxonomy>,
+  descriptor_similarities: Arc<DescriptorSimilarities>,
   settings: QuantileRankAlbumAssessmentSettings,
   primary_genre_summary_map: HashMap<String, ItemWithFactor>,
   secondary_genre_summary_map: HashMap<String, ItemWithFactor>,
   descriptor_summary_map: HashMap<String, ItemWithFactor>,
   credit_tag_summary_map: HashMap<String, ItemWithFactor>,
+  negative_context: Option<(Box<QuantileRankAlbumAssessmentContext>, f32)>,
+}
+
+/**
+ * Only taste features (genres, descriptors, credits) say anything about whether an album resembles
+ * disliked ones, so ratings and novelty are zeroed out when ranking against a negative seed
+ */
+fn negative_seed_settings(
+  settings: &QuantileRankAlbumAssessmentSettings,
+) -> QuantileRankAlbumAssessmentSettings {
+  QuantileRankAlbumAssessmentSettings {
+    rating_weight: 0,
+    rating_count_weight: 0,
+    descriptor_count_weight: 0,
+    listening_history_weight: 0,
+    novelty_score: 0.0,
+    ..settings.clone()
+  }
 }
 
 impl QuantileRankAlbumAssessmentContext {
   pub fn new(
     seed_context: &AlbumRecommendationSeedContext,
     settings: QuantileRankAlbumAssessmentSettings,
+    genre_taxonomy: Arc<GenreTaxonomy>,
+    descriptor_similarities: Arc<DescriptorSimilarities>,
   ) -> Self {
     let rating_ranking = QuantileRanking::new(
       &seed_context
@@ -102,8 +207,45 @@ impl QuantileRankAlbumAssessmentContext {
         .collect::<Vec<_>>(),
     );
     let seed_summary = AlbumCollectionSummary::new(&seed_context.albums, &seed_context.factor_map);
+    let personnel_radar =
+      PersonnelRadar::new(seed_context, settings.personnel_radar_role_weights.clone());
+    let negative_context = seed_context
+      .negative
+      .as_ref()
+      .filter(|negative| negative.weight > 0.0 && !negative.context.albums.is_empty())
+      .filter(|_| {
+        settings.primary_genre_weight
+          + settings.secondary_genre_weight
+          + settings.descriptor_weight
+          + settings.credit_tag_weight
+          + settings.personnel_radar_weight
+          > 0
+      })
+      .map(|negative| {
+        (
+          Box::new(Self::new(
+            &negative.context,
+            negative_seed_settings(&settings),
+            Arc::clone(&genre_taxonomy),
+            Arc::clone(&descriptor_similarities),
+          )),
+          negative.weight,
+        )
+      });
     Self {
+      negative_context,
       settings,
+      personnel_radar,
+      artist_listen_score_ranking: QuantileRanking::new(
+        &seed_context
+          .artist_listen_scores
+          .values()
+          .map(|score| OrderedFloat(*score))
+          .collect::<Vec<_>>(),
+      ),
+      artist_listen_scores: seed_context.artist_listen_scores.clone(),
+      genre_taxonomy,
+      descriptor_similarities,
       primary_genre_ranking: QuantileRanking::new(&seed_summary.primary_genres),
       secondary_genre_ranking: QuantileRanking::new(&seed_summary.secondary_genres),
       descriptor_ranking: QuantileRanking::new(&seed_summary.descriptors),
@@ -118,96 +260,222 @@ impl QuantileRankAlbumAssessmentContext {
     }
   }
 
-  pub fn assess(&self, album: &AlbumReadModel) -> Result<AlbumAssessment> {
-    let (average_primary_genre_rank, mut primary_genre_ranks) =
-      compute_ranks(self.settings.primary_genre_weight, || {
-        calculate_average_rank(
-          &self.primary_genre_ranking,
-          &self.primary_genre_summary_map,
-          &album.primary_genres,
-          self.settings.novelty_score,
-        )
-      })?;
-    let (average_secondary_genre_rank, mut secondary_genre_ranks) =
-      compute_ranks(self.settings.secondary_genre_weight, || {
-        calculate_average_rank(
-          &self.secondary_genre_ranking,
-          &self.secondary_genre_summary_map,
-          &album.secondary_genres,
-          self.settings.novelty_score,
-        )
-      })?;
-    let (average_descriptor_rank, mut descriptor_ranks) =
-      compute_ranks(self.settings.descriptor_weight, || {
-        calculate_average_rank(
-          &self.descriptor_ranking,
-          &self.descriptor_summary_map,
-          &album.descriptors,
-          self.settings.novelty_score,
-        )
-      })?;
-    let (average_credit_tag_rank, mut credit_tag_ranks) =
-      compute_ranks(self.settings.credit_tag_weight, || {
-        calculate_average_rank(
-          &self.credit_tag_ranking,
-          &self.credit_tag_summary_map,
-          &album.credit_tags(),
-          self.settings.novelty_score,
-        )
-      })?;
-    let (rating_rank, mut rating_ranks) = compute_ranks(self.settings.rating_weight, || {
-      Ok(self.rating_ranking.get_rank(&OrderedFloat(album.rating)))
-    })?;
-    let (rating_count_rank, mut rating_count_ranks) =
-      compute_ranks(self.settings.rating_count_weight, || {
-        Ok(self.rating_count_ranking.get_rank(&album.rating_count))
-      })?;
-    let (descriptor_count_rank, mut descriptor_count_ranks) =
-      compute_ranks(self.settings.descriptor_count_weight, || {
-        Ok(
-          self
-            .descriptor_count_ranking
-            .get_rank(&(album.descriptors.len() as u32)),
-        )
-      })?;
+  /**
+   * Rank of the album's most listened artist among the profile's listened artists, zero when none
+   * of its artists have been listened to
+   */
+  fn listening_history_rank(&self, album: &AlbumReadModel) -> FactorRank {
+    let mut listened_artists = album
+      .artists
+      .iter()
+      .filter_map(|artist| {
+        self
+          .artist_listen_scores
+          .get(&artist.file_name)
+          .map(|score| (artist.name.clone(), *score))
+      })
+      .collect::<Vec<_>>();
+    listened_artists.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let rank = listened_artists.first().map_or(0.0, |(_, score)| {
+      self
+        .artist_listen_score_ranking
+        .get_rank(&OrderedFloat(*score))
+    });
+    FactorRank {
+      rank,
+      matched_items: listened_artists.into_iter().map(|(name, _)| name).collect(),
+      novel_items: vec![],
+    }
+  }
 
-    let mut ranks = vec![];
-    ranks.append(&mut primary_genre_ranks);
-    ranks.append(&mut secondary_genre_ranks);
-    ranks.append(&mut descriptor_ranks);
-    ranks.append(&mut credit_tag_ranks);
-    ranks.append(&mut rating_ranks);
-    ranks.append(&mut rating_count_ranks);
-    ranks.append(&mut descriptor_count_ranks);
+  pub fn assess(&self, album: &AlbumReadModel) -> Result<AlbumAssessment> {
+    let settings = &self.settings;
+    let novelty_score = settings.novelty_score;
+    let related_genres = |genre: &str| -> Vec<(String, f64)> {
+      self
+        .genre_taxonomy
+        .related_genres(genre, MAX_GENRE_HIERARCHY_DISTANCE)
+        .into_iter()
+        .map(|(related_genre, distance)| {
+          (
+            related_genre,
+            settings.genre_hierarchy_credit.powi(distance as i32),
+          )
+        })
+        .collect()
+    };
+    let similar_descriptors = |descriptor: &str| -> Vec<(String, f64)> {
+      self
+        .descriptor_similarities
+        .similar_descriptors(descriptor)
+        .iter()
+        .map(|(similar_descriptor, similarity)| {
+          (
+            similar_descriptor.clone(),
+            similarity * settings.descriptor_similarity_credit,
+          )
+        })
+        .collect()
+    };
+    let factors = vec![
+      (
+        "primary_genre",
+        "average_primary_genre_rank",
+        settings.primary_genre_weight,
+        compute_rank(settings.primary_genre_weight, || {
+          calculate_average_rank(
+            &self.primary_genre_ranking,
+            &self.primary_genre_summary_map,
+            &album.primary_genres,
+            novelty_score,
+            &related_genres,
+          )
+        })?,
+      ),
+      (
+        "secondary_genre",
+        "average_secondary_genre_rank",
+        settings.secondary_genre_weight,
+        compute_rank(settings.secondary_genre_weight, || {
+          calculate_average_rank(
+            &self.secondary_genre_ranking,
+            &self.secondary_genre_summary_map,
+            &album.secondary_genres,
+            novelty_score,
+            &related_genres,
+          )
+        })?,
+      ),
+      (
+        "descriptor",
+        "average_descriptor_rank",
+        settings.descriptor_weight,
+        compute_rank(settings.descriptor_weight, || {
+          calculate_average_rank(
+            &self.descriptor_ranking,
+            &self.descriptor_summary_map,
+            &album.descriptors,
+            novelty_score,
+            &similar_descriptors,
+          )
+        })?,
+      ),
+      (
+        "credit_tag",
+        "average_credit_tag_rank",
+        settings.credit_tag_weight,
+        compute_rank(settings.credit_tag_weight, || {
+          calculate_average_rank(
+            &self.credit_tag_ranking,
+            &self.credit_tag_summary_map,
+            &album.credit_tags(),
+            novelty_score,
+            &no_related_tags,
+          )
+        })?,
+      ),
+      (
+        "personnel_radar",
+        "personnel_radar_rank",
+        settings.personnel_radar_weight,
+        compute_rank(settings.personnel_radar_weight, || {
+          Ok(FactorRank::new(self.personnel_radar.rank(album)))
+        })?,
+      ),
+      (
+        "listening_history",
+        "listening_history_rank",
+        settings.listening_history_weight,
+        compute_rank(settings.listening_history_weight, || {
+          Ok(self.listening_history_rank(album))
+        })?,
+      ),
+      (
+        "rating",
+        "rating_rank",
+        settings.rating_weight,
+        compute_rank(settings.rating_weight, || {
+          Ok(FactorRank::new(
+            self.rating_ranking.get_rank(&OrderedFloat(album.rating)),
+          ))
+        })?,
+      ),
+      (
+        "rating_count",
+        "rating_count_rank",
+        settings.rating_count_weight,
+        compute_rank(settings.rating_count_weight, || {
+          Ok(FactorRank::new(
+            self.rating_count_ranking.get_rank(&album.rating_count),
+          ))
+        })?,
+      ),
+      (
+        "descriptor_count",
+        "descriptor_count_rank",
+        settings.descriptor_count_weight,
+        compute_rank(settings.descriptor_count_weight, || {
+          Ok(FactorRank::new(
+            self
+              .descriptor_count_ranking
+              .get_rank(&(album.descriptors.len() as u32)),
+          ))
+        })?,
+      ),
+    ];
 
+    let total_weight = factors
+      .iter()
+      .map(|(_, _, weight, _)| *weight as f64)
+      .sum::<f64>();
     let mut metadata = HashMap::new();
-    metadata.insert(
-      "average_primary_genre_rank".to_string(),
-      average_primary_genre_rank.to_string(),
-    );
-    metadata.insert(
-      "average_secondary_genre_rank".to_string(),
-      average_secondary_genre_rank.to_string(),
-    );
-    metadata.insert(
-      "average_descriptor_rank".to_string(),
-      average_descriptor_rank.to_string(),
-    );
-    metadata.insert(
-      "average_credit_tag_rank".to_string(),
-      average_credit_tag_rank.to_string(),
-    );
-    metadata.insert("rating_rank".to_string(), rating_rank.to_string());
-    metadata.insert(
-      "rating_count_rank".to_string(),
-      rating_count_rank.to_string(),
-    );
-    metadata.insert(
-      "descriptor_count_rank".to_string(),
-      descriptor_count_rank.to_string(),
-    );
+    let mut contributions = vec![];
+    for (factor, metadata_key, weight, rank) in factors {
+      metadata.insert(
+        metadata_key.to_string(),
+        rank.as_ref().map_or(0.0, |rank| rank.rank).to_string(),
+      );
+      if let Some(rank) = rank {
+        contributions.push(AlbumAssessmentContribution {
+          factor: factor.to_string(),
+          weight: weight as f32,
+          value: rank.rank,
+          contribution: rank.rank * weight as f64 / total_weight,
+          matched_items: rank.matched_items,
+          novel_items: rank.novel_items,
+        });
+      }
+    }
+
+    let mut score = contributions
+      .iter()
+      .map(|contribution| contribution.contribution)
+      .sum::<f64>();
+    if total_weight.is_zero() {
+      score = f64::NAN;
+    }
 
-    let score = ranks.iter().sum::<f64>() / ranks.len() as f64;
+    if let Some((negative_context, weight)) = &self.negative_context {
+      let negative_assessment = negative_context.assess(album)?;
+      let negative_score = negative_assessment.score as f64;
+      score -= negative_score * *weight as f64;
+      metadata.insert(
+        "negative_seed_score".to_string(),
+        negative_score.to_string(),
+      );
+      contributions.push(AlbumAssessmentContribution {
+        factor: "negative_seed".to_string(),
+        weight: *weight,
+        value: negative_score,
+        contribution: -negative_score * *weight as f64,
+        matched_items: negative_assessment
+          .contributions
+          .into_iter()
+          .flat_map(|contribution| contribution.matched_items)
+          .collect(),
+        novel_items: vec![],
+      });
+    }
 
     if score.is_nan() {
       Err(anyhow!("score is NaN"))
@@ -215,7 +483,86 @@ impl QuantileRankAlbumAssessmentContext {
       Ok(AlbumAssessment {
         score: score as f32,
         metadata: Some(metadata),
+        contributions,
       })
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn album(name: &str, primary_genre: &str, descriptors: &[&str]) -> Result<AlbumReadModel> {
+    Ok(AlbumReadModel {
+      file_name: FileName::try_from(format!("release/album/artist/{}", name))?,
+      primary_genres: vec![primary_genre.to_string()],
+      descriptors: descriptors.iter().map(|d| d.to_string()).collect(),
+      ..Default::default()
+    })
+  }
+
+  fn seed_context(albums: Vec<AlbumReadModel>) -> AlbumRecommendationSeedContext {
+    let factor_map = albums
+      .iter()
+      .map(|album| (album.file_name.clone(), 1))
+      .collect();
+    AlbumRecommendationSeedContext::new(albums, factor_map)
+  }
+
+  #[test]
+  fn test_assess_contributions() -> Result<()> {
+    let settings = QuantileRankAlbumAssessmentSettings {
+      primary_genre_weight: 1,
+      secondary_genre_weight: 0,
+      descriptor_weight: 3,
+      rating_weight: 0,
+      rating_count_weight: 0,
+      descriptor_count_weight: 0,
+      credit_tag_weight: 0,
+      ..Default::default()
+    };
+    let seed = seed_context(vec![
+      album("souvlaki", "Shoegaze", &["ethereal", "lush"])?,
+      album("loveless", "Shoegaze", &["noisy", "lush"])?,
+    ])
+    .with_negative(
+      seed_context(vec![album("nevermind", "Grunge", &["noisy"])?]),
+      0.5,
+    );
+    let context = QuantileRankAlbumAssessmentContext::new(
+      &seed,
+      settings,
+      Arc::new(GenreTaxonomy::default()),
+      Arc::new(DescriptorSimilarities::default()),
+    );
+    let assessment = context.assess(&album("pygmalion", "Shoegaze", &["lush", "ambient"])?)?;
+
+    assert_eq!(
+      assessment
+        .contributions
+        .iter()
+        .map(|contribution| contribution.factor.as_str())
+        .collect::<Vec<_>>(),
+      vec!["primary_genre", "descriptor", "negative_seed"]
+    );
+    let descriptor = &assessment.contributions[1];
+    assert_eq!(descriptor.weight, 3.0);
+    assert_eq!(descriptor.matched_items, vec!["lush"]);
+    assert_eq!(descriptor.novel_items, vec!["ambient"]);
+    assert!((descriptor.contribution - descriptor.value * 3.0 / 4.0).abs() < 1e-9);
+    let negative_seed = &assessment.contributions[2];
+    assert!(negative_seed.contribution <= 0.0);
+    assert!(
+      (assessment
+        .contributions
+        .iter()
+        .map(|contribution| contribution.contribution)
+        .sum::<f64>()
+        - assessment.score as f64)
+        .abs()
+        < 1e-6
+    );
+    Ok(())
+  }
+}
@@ -1,13 +1,21 @@
 use super::{
-  bounded_min_heap::BoundedMinHeap, quantile_rank_assessment::QuantileRankAlbumAssessmentContext,
+  bounded_min_heap::BoundedMinHeap, personnel_radar::PersonnelRadarRoleWeights,
+  quantile_rank_assessment::QuantileRankAlbumAssessmentContext,
 };
 use crate::{
-  albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  albums::{
+    album_interactor::AlbumInteractor,
+    album_read_model::AlbumReadModel,
+    album_search_index::{AlbumSearchSort, AlbumSearchSortField},
+  },
+  genres::{genre_taxonomy::GenreTaxonomy, genre_taxonomy_repository::GenreTaxonomyRepository},
   helpers::redisearch::SearchPagination,
   recommendations::{
+    descriptor_similarity::DescriptorSimilarities,
+    descriptor_similarity_repository::DescriptorSimilarityRepository,
     seed::AlbumRecommendationSeedContext,
     types::{
-      AlbumAssessment, AlbumRecommendation, AlbumRecommendationSettings,
+      AlbumAssessment, AlbumRecommendation, AlbumRecommendationSettings, AlbumRecommendations,
       RecommendationMethodInteractor,
     },
   },
@@ -16,10 +24,14 @@ use anyhow::Result;
 use async_trait::async_trait;
 use derive_builder::Builder;
 use rayon::{iter::ParallelDrainRange, prelude::ParallelIterator};
-use std::sync::Arc;
-use tokio::sync::mpsc::unbounded_channel;
+use std::{sync::Arc, time::Instant};
+use tokio::{sync::mpsc::unbounded_channel, time::timeout_at};
 use tracing::{instrument, warn};
 
+const TIME_BUDGETED_BATCH_SIZE: usize = 1000;
+const TIME_BUDGETED_SEARCH_PAGE_SIZE: usize = 10000;
+const MAX_CANDIDATE_COUNT: usize = 100000;
+
 #[derive(Builder, Clone, Debug)]
 #[builder(setter(into), default)]
 pub struct QuantileRankAlbumAssessmentSettings {
@@ -31,6 +43,25 @@ pub struct QuantileRankAlbumAssessmentSettings {
   pub novelty_score: f64,
   pub descriptor_count_weight: u32,
   pub credit_tag_weight: u32,
+  /**
+   * Boosts albums sharing credited personnel with highly weighted seed albums. Off by default.
+   */
+  pub personnel_radar_weight: u32,
+  pub personnel_radar_role_weights: PersonnelRadarRoleWeights,
+  /**
+   * Boosts albums by artists the profile has listened to often and recently. Off by default.
+   */
+  pub listening_history_weight: u32,
+  /**
+   * Share of a profile genre's rank an album genre gets for being its parent or child, compounded
+   * for every further level of the genre hierarchy. Zero limits genre matches to exact ones.
+   */
+  pub genre_hierarchy_credit: f64,
+  /**
+   * Share of a profile descriptor's rank, scaled by how similar the descriptors are, that an album
+   * descriptor gets for being similar to it. Zero limits descriptor matches to exact ones.
+   */
+  pub descriptor_similarity_credit: f64,
 }
 
 impl Default for QuantileRankAlbumAssessmentSettings {
@@ -44,37 +75,84 @@ impl Default for QuantileRankAlbumAssessmentSettings {
       novelty_score: 0.2,
       descriptor_count_weight: 2,
       credit_tag_weight: 1,
+      personnel_radar_weight: 0,
+      personnel_radar_role_weights: PersonnelRadarRoleWeights::default(),
+      listening_history_weight: 0,
+      genre_hierarchy_credit: 0.5,
+      descriptor_similarity_credit: 0.5,
     }
   }
 }
 
 pub struct QuantileRankInteractor {
   album_interactor: Arc<AlbumInteractor>,
+  genre_taxonomy_repository: GenreTaxonomyRepository,
+  descriptor_similarity_repository: DescriptorSimilarityRepository,
 }
 
 impl QuantileRankInteractor {
-  pub fn new(album_interactor: Arc<AlbumInteractor>) -> Self {
-    Self { album_interactor }
+  pub fn new(
+    album_interactor: Arc<AlbumInteractor>,
+    genre_taxonomy_repository: GenreTaxonomyRepository,
+    descriptor_similarity_repository: DescriptorSimilarityRepository,
+  ) -> Self {
+    Self {
+      album_interactor,
+      genre_taxonomy_repository,
+      descriptor_similarity_repository,
+    }
   }
 
-  #[instrument(name = "QuantileRankInteractor::rank_albums", skip(self, seed_context))]
-  pub async fn rank_albums(
+  async fn create_assessment_context(
     &self,
     seed_context: &AlbumRecommendationSeedContext,
-    assessment_settings: QuantileRankAlbumAssessmentSettings,
-    recommendation_settings: AlbumRecommendationSettings,
-    albums: Vec<AlbumReadModel>,
-  ) -> Result<Vec<AlbumRecommendation>> {
-    let mut albums = albums;
-    let context = QuantileRankAlbumAssessmentContext::new(seed_context, assessment_settings);
-    let mut result_heap = BoundedMinHeap::new(recommendation_settings.count as usize);
+    settings: QuantileRankAlbumAssessmentSettings,
+  ) -> Result<QuantileRankAlbumAssessmentContext> {
+    let genre_taxonomy = if settings.genre_hierarchy_credit > 0.0 {
+      self.genre_taxonomy_repository.get_taxonomy().await?
+    } else {
+      GenreTaxonomy::default()
+    };
+    let descriptor_similarities = if settings.descriptor_similarity_credit > 0.0 {
+      self
+        .descriptor_similarity_repository
+        .get_similarities()
+        .await?
+    } else {
+      DescriptorSimilarities::default()
+    };
+    Ok(QuantileRankAlbumAssessmentContext::new(
+      seed_context,
+      settings,
+      Arc::new(genre_taxonomy),
+      Arc::new(descriptor_similarities),
+    ))
+  }
+
+  /**
+   * Cheap stand-in for the assessment score, used to assess the most promising candidates first
+   * when working within a time budget
+   */
+  fn prior_score(album: &AlbumReadModel) -> f64 {
+    album.rating as f64 * (album.rating_count as f64).ln_1p()
+  }
+
+  async fn assess_batch(
+    context: Arc<QuantileRankAlbumAssessmentContext>,
+    mut albums: Vec<AlbumReadModel>,
+    result_heap: &mut BoundedMinHeap<AlbumRecommendation>,
+  ) {
     let (recommendation_sender, mut recommendation_receiver) = unbounded_channel();
     rayon::spawn(move || {
       albums
         .par_drain(..)
         .for_each(|album| match context.assess(&album) {
           Ok(assessment) => {
-            if let Err(e) = recommendation_sender.send(AlbumRecommendation { album, assessment }) {
+            if let Err(e) = recommendation_sender.send(AlbumRecommendation {
+              album,
+              assessment,
+              exploratory: false,
+            }) {
               warn!("Error sending recommendation: {}", e);
             }
           }
@@ -86,8 +164,165 @@ impl QuantileRankInteractor {
     while let Some(recommendation) = recommendation_receiver.recv().await {
       result_heap.push(recommendation);
     }
-    let recommendations = result_heap.drain_sorted_desc();
-    Ok(recommendations)
+  }
+
+  /**
+   * Assesses the most promising albums first, in batches, until the deadline passes. Returns how
+   * many were assessed.
+   */
+  async fn assess_until(
+    context: Arc<QuantileRankAlbumAssessmentContext>,
+    mut albums: Vec<AlbumReadModel>,
+    deadline: Instant,
+    result_heap: &mut BoundedMinHeap<AlbumRecommendation>,
+  ) -> usize {
+    albums.sort_by(|a, b| Self::prior_score(b).total_cmp(&Self::prior_score(a)));
+    let candidate_count = albums.len();
+    let mut remaining = albums.into_iter();
+    let mut assessed_count = 0;
+    while assessed_count < candidate_count && Instant::now() < deadline {
+      let batch = remaining
+        .by_ref()
+        .take(TIME_BUDGETED_BATCH_SIZE)
+        .collect::<Vec<_>>();
+      assessed_count += batch.len();
+      Self::assess_batch(Arc::clone(&context), batch, result_heap).await;
+    }
+    assessed_count
+  }
+
+  fn budgeted_recommendations(
+    mut result_heap: BoundedMinHeap<AlbumRecommendation>,
+    assessed_count: usize,
+    candidate_count: Option<usize>,
+  ) -> AlbumRecommendations {
+    let completeness = match candidate_count {
+      Some(0) => 1.0,
+      Some(candidate_count) => assessed_count as f32 / candidate_count as f32,
+      None => 0.0,
+    };
+    if completeness < 1.0 {
+      warn!(
+        assessed_count,
+        candidate_count, "Time budget ran out before all candidates were assessed"
+      );
+    }
+    AlbumRecommendations {
+      recommendations: result_heap.drain_sorted_desc(),
+      completeness,
+    }
+  }
+
+  #[instrument(name = "QuantileRankInteractor::rank_albums", skip(self, seed_context))]
+  pub async fn rank_albums(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    assessment_settings: QuantileRankAlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    albums: Vec<AlbumReadModel>,
+  ) -> Result<AlbumRecommendations> {
+    let context = Arc::new(
+      self
+        .create_assessment_context(seed_context, assessment_settings)
+        .await?,
+    );
+    let mut result_heap = BoundedMinHeap::new(recommendation_settings.count as usize);
+    let Some(deadline) = recommendation_settings.deadline else {
+      Self::assess_batch(context, albums, &mut result_heap).await;
+      return Ok(AlbumRecommendations::complete(
+        result_heap.drain_sorted_desc(),
+      ));
+    };
+    let candidate_count = albums.len();
+    let assessed_count = Self::assess_until(context, albums, deadline, &mut result_heap).await;
+    Ok(Self::budgeted_recommendations(
+      result_heap,
+      assessed_count,
+      Some(candidate_count),
+    ))
+  }
+
+  /**
+   * Without a deadline every candidate is fetched and assessed. With one, candidates are fetched a
+   * page at a time, most rated first, and fetching stops along with assessing once it passes.
+   */
+  pub async fn recommend_albums_within_budget(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    assessment_settings: QuantileRankAlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+  ) -> Result<AlbumRecommendations> {
+    let mut search_query = recommendation_settings.to_search_query(seed_context)?;
+    let Some(deadline) = recommendation_settings.deadline else {
+      let search_results = self
+        .album_interactor
+        .search(
+          &search_query,
+          Some(&SearchPagination {
+            offset: None,
+            limit: Some(MAX_CANDIDATE_COUNT),
+          }),
+        )
+        .await?;
+      return self
+        .rank_albums(
+          seed_context,
+          assessment_settings,
+          recommendation_settings,
+          search_results.albums,
+        )
+        .await;
+    };
+
+    let context = Arc::new(
+      self
+        .create_assessment_context(seed_context, assessment_settings)
+        .await?,
+    );
+    let mut result_heap = BoundedMinHeap::new(recommendation_settings.count as usize);
+    search_query.sort = Some(AlbumSearchSort {
+      field: AlbumSearchSortField::RatingCount,
+      descending: true,
+    });
+    let mut candidate_count = None;
+    let mut fetched_count = 0;
+    let mut assessed_count = 0;
+    while Instant::now() < deadline
+      && candidate_count.map_or(true, |candidate_count| fetched_count < candidate_count)
+    {
+      let Ok(page) = timeout_at(
+        deadline.into(),
+        self.album_interactor.search(
+          &search_query,
+          Some(&SearchPagination {
+            offset: Some(fetched_count),
+            limit: Some(TIME_BUDGETED_SEARCH_PAGE_SIZE),
+          }),
+        ),
+      )
+      .await
+      else {
+        break;
+      };
+      let page = page?;
+      candidate_count = Some(page.total.min(MAX_CANDIDATE_COUNT));
+      if page.albums.is_empty() {
+        break;
+      }
+      fetched_count += page.albums.len();
+      assessed_count += Self::assess_until(
+        Arc::clone(&context),
+        page.albums,
+        deadline,
+        &mut result_heap,
+      )
+      .await;
+    }
+    Ok(Self::budgeted_recommendations(
+      result_heap,
+      assessed_count,
+      candidate_count,
+    ))
   }
 }
 
@@ -121,7 +356,10 @@ impl
     album_read_model: &QuantileRankAssessableAlbum,
     settings: QuantileRankAlbumAssessmentSettings,
   ) -> Result<AlbumAssessment> {
-    QuantileRankAlbumAssessmentContext::new(seed_context, settings).assess(&album_read_model.0)
+    self
+      .create_assessment_context(seed_context, settings)
+      .await?
+      .assess(&album_read_model.0)
   }
 
   #[instrument(
@@ -134,24 +372,11 @@ impl
     assessment_settings: QuantileRankAlbumAssessmentSettings,
     recommendation_settings: AlbumRecommendationSettings,
   ) -> Result<Vec<AlbumRecommendation>> {
-    let search_query = recommendation_settings.to_search_query(&seed_context.albums)?;
-    let search_results = self
-      .album_interactor
-      .search(
-        &search_query,
-        Some(&SearchPagination {
-          offset: None,
-          limit: Some(100000),
-        }),
-      )
-      .await?;
-    self
-      .rank_albums(
-        seed_context,
-        assessment_settings,
-        recommendation_settings,
-        search_results.albums,
-      )
-      .await
+    Ok(
+      self
+        .recommend_albums_within_budget(seed_context, assessment_settings, recommendation_settings)
+        .await?
+        .recommendations,
+    )
   }
 }
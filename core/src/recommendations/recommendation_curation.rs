@@ -0,0 +1,241 @@
+use super::types::{AlbumRecommendation, ScoreOrder};
+use crate::{files::file_metadata::file_name::FileName, profile::profile::ProfileId};
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/**
+ * Albums a profile always wants in its recommendation output, and albums it never wants
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationCuration {
+  pub profile_id: ProfileId,
+  pub pinned: Vec<FileName>,
+  pub excluded: Vec<FileName>,
+  pub updated_at: NaiveDateTime,
+}
+
+impl RecommendationCuration {
+  pub fn new(profile_id: ProfileId) -> Self {
+    Self {
+      profile_id,
+      pinned: vec![],
+      excluded: vec![],
+      updated_at: Utc::now().naive_utc(),
+    }
+  }
+
+  /**
+   * Pinning an album lifts any exclusion on it and vice versa, so the lists never overlap
+   */
+  pub fn update(
+    &mut self,
+    pin: Vec<FileName>,
+    unpin: Vec<FileName>,
+    exclude: Vec<FileName>,
+    unexclude: Vec<FileName>,
+  ) {
+    self
+      .pinned
+      .retain(|file_name| !unpin.contains(file_name) && !exclude.contains(file_name));
+    self
+      .excluded
+      .retain(|file_name| !unexclude.contains(file_name) && !pin.contains(file_name));
+    for file_name in pin {
+      if !self.pinned.contains(&file_name) {
+        self.pinned.push(file_name);
+      }
+    }
+    for file_name in exclude {
+      if !self.excluded.contains(&file_name) {
+        self.excluded.push(file_name);
+      }
+    }
+    self.updated_at = Utc::now().naive_utc();
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CurationMarker {
+  Pinned,
+  Organic,
+  /**
+   * Would have ranked among the organic recommendations had it not been excluded
+   */
+  Excluded,
+}
+
+#[derive(Debug, Clone)]
+pub struct CuratedAlbumRecommendation {
+  pub recommendation: AlbumRecommendation,
+  pub marker: CurationMarker,
+}
+
+/**
+ * Assembles curated output from pinned recommendations (in pin order) followed by organic
+ * recommendations, best first by the method's score order. Pins count towards `count` but are
+ * never dropped for it. Excluded albums that would have made the cut are appended with the
+ * `Excluded` marker.
+ */
+pub fn curate_recommendations(
+  curation: &RecommendationCuration,
+  pinned: Vec<AlbumRecommendation>,
+  mut organic: Vec<AlbumRecommendation>,
+  count: usize,
+  score_order: ScoreOrder,
+) -> Vec<CuratedAlbumRecommendation> {
+  let pinned_file_names = pinned
+    .iter()
+    .map(|recommendation| recommendation.album.file_name.clone())
+    .collect::<HashSet<_>>();
+  let mut curated = pinned
+    .into_iter()
+    .map(|recommendation| CuratedAlbumRecommendation {
+      recommendation,
+      marker: CurationMarker::Pinned,
+    })
+    .collect::<Vec<_>>();
+  let organic_count = count.saturating_sub(curated.len());
+  score_order.sort(&mut organic);
+  let mut excluded = Vec::new();
+  let mut organic_curated = Vec::new();
+  for recommendation in organic {
+    if organic_curated.len() >= organic_count {
+      break;
+    }
+    if pinned_file_names.contains(&recommendation.album.file_name) {
+      continue;
+    }
+    if curation.excluded.contains(&recommendation.album.file_name) {
+      excluded.push(CuratedAlbumRecommendation {
+        recommendation,
+        marker: CurationMarker::Excluded,
+      });
+    } else {
+      organic_curated.push(CuratedAlbumRecommendation {
+        recommendation,
+        marker: CurationMarker::Organic,
+      });
+    }
+  }
+  curated.extend(organic_curated);
+  curated.extend(excluded);
+  curated
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_curate_recommendations() -> Result<()> {
+    let mut curation = RecommendationCuration::new(ProfileId::try_from("default".to_string())?);
+    curation.update(
+      vec![FileName::try_from("release/album/bjork/vulnicura")?],
+      vec![],
+      vec![FileName::try_from("release/album/daft-punk/discovery")?],
+      vec![],
+    );
+    let curated = curate_recommendations(
+      &curation,
+      vec![AlbumRecommendation::new_for_test(
+        "release/album/bjork/vulnicura",
+        0.1,
+      )?],
+      vec![
+        AlbumRecommendation::new_for_test("release/album/billy-woods/aethiopes", 0.7)?,
+        AlbumRecommendation::new_for_test("release/album/daft-punk/discovery", 0.9)?,
+        AlbumRecommendation::new_for_test("release/album/bjork/vulnicura", 0.8)?,
+        AlbumRecommendation::new_for_test("release/album/run-the-jewels/run-the-jewels-2", 0.5)?,
+        AlbumRecommendation::new_for_test("release/album/sade/love-deluxe", 0.3)?,
+      ],
+      3,
+      ScoreOrder::Descending,
+    );
+    let summary = curated
+      .iter()
+      .map(|c| (c.recommendation.album.file_name.to_string(), c.marker))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        (
+          "release/album/bjork/vulnicura".to_string(),
+          CurationMarker::Pinned
+        ),
+        (
+          "release/album/billy-woods/aethiopes".to_string(),
+          CurationMarker::Organic
+        ),
+        (
+          "release/album/run-the-jewels/run-the-jewels-2".to_string(),
+          CurationMarker::Organic
+        ),
+        (
+          "release/album/daft-punk/discovery".to_string(),
+          CurationMarker::Excluded
+        ),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_curate_embedding_similarity_recommendations() -> Result<()> {
+    let mut curation = RecommendationCuration::new(ProfileId::try_from("default".to_string())?);
+    curation.update(
+      vec![],
+      vec![],
+      vec![FileName::try_from("release/album/daft-punk/discovery")?],
+      vec![],
+    );
+    let curated = curate_recommendations(
+      &curation,
+      vec![],
+      vec![
+        AlbumRecommendation::new_for_test("release/album/sade/love-deluxe", 0.62)?,
+        AlbumRecommendation::new_for_test("release/album/daft-punk/discovery", 0.08)?,
+        AlbumRecommendation::new_for_test("release/album/billy-woods/aethiopes", 0.21)?,
+        AlbumRecommendation::new_for_test("release/album/run-the-jewels/run-the-jewels-2", 0.15)?,
+      ],
+      2,
+      ScoreOrder::Ascending,
+    );
+    let summary = curated
+      .iter()
+      .map(|c| (c.recommendation.album.file_name.to_string(), c.marker))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        (
+          "release/album/run-the-jewels/run-the-jewels-2".to_string(),
+          CurationMarker::Organic
+        ),
+        (
+          "release/album/billy-woods/aethiopes".to_string(),
+          CurationMarker::Organic
+        ),
+        (
+          "release/album/daft-punk/discovery".to_string(),
+          CurationMarker::Excluded
+        ),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_pinning_lifts_exclusion() -> Result<()> {
+    let file_name = FileName::try_from("release/album/bjork/vulnicura")?;
+    let mut curation = RecommendationCuration::new(ProfileId::try_from("default".to_string())?);
+    curation.update(vec![], vec![], vec![file_name.clone()], vec![]);
+    curation.update(vec![file_name.clone()], vec![], vec![], vec![]);
+    assert_eq!(curation.pinned, vec![file_name]);
+    assert!(curation.excluded.is_empty());
+    Ok(())
+  }
+}
@@ -0,0 +1,34 @@
+use super::recommendation_curation::RecommendationCuration;
+use crate::{helpers::document_store::DocumentStore, profile::profile::ProfileId};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct RecommendationCurationRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "recommendation_curation";
+
+impl RecommendationCurationRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn get(&self, profile_id: &ProfileId) -> Result<RecommendationCuration> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<RecommendationCuration>(COLLECTION, &profile_id.to_string())
+        .await?
+        .map(|doc| doc.document)
+        .unwrap_or_else(|| RecommendationCuration::new(profile_id.clone())),
+    )
+  }
+
+  pub async fn put(&self, curation: RecommendationCuration) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &curation.profile_id.to_string(), curation, None)
+      .await
+  }
+}
@@ -0,0 +1,49 @@
+use super::recommendation_curation::{CuratedAlbumRecommendation, CurationMarker};
+use crate::{files::file_metadata::file_name::FileName, profile::profile::ProfileId};
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use ulid::Ulid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationDigestItem {
+  pub file_name: FileName,
+  pub name: String,
+  pub artists: Vec<String>,
+  pub score: f32,
+  pub marker: CurationMarker,
+}
+
+/**
+ * A point-in-time snapshot of a profile's curated recommendations
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationDigest {
+  pub id: String,
+  pub profile_id: ProfileId,
+  pub created_at: NaiveDateTime,
+  pub items: Vec<RecommendationDigestItem>,
+}
+
+impl RecommendationDigest {
+  /**
+   * Keeps every curated recommendation with its marker, including excluded albums that would have
+   * ranked, so a digest shows what curation changed
+   */
+  pub fn new(profile_id: ProfileId, recommendations: Vec<CuratedAlbumRecommendation>) -> Self {
+    Self {
+      id: Ulid::new().to_string(),
+      profile_id,
+      created_at: Utc::now().naive_utc(),
+      items: recommendations
+        .into_iter()
+        .map(|curated| RecommendationDigestItem {
+          marker: curated.marker,
+          score: curated.recommendation.assessment.score,
+          artists: curated.recommendation.album.artist_names(),
+          name: curated.recommendation.album.name,
+          file_name: curated.recommendation.album.file_name,
+        })
+        .collect(),
+    }
+  }
+}
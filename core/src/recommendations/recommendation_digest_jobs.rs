@@ -0,0 +1,115 @@
+use super::{
+  recommendation_digest::RecommendationDigest, recommendation_interactor::RecommendationInteractor,
+};
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  profile::profile::ProfileId,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::TimeDelta;
+use reqwest::Client;
+use std::sync::Arc;
+use tracing::{error, info};
+
+async fn deliver_digest(webhook_url: &str, digest: &RecommendationDigest) -> Result<()> {
+  Client::new()
+    .post(webhook_url)
+    .json(digest)
+    .send()
+    .await?
+    .error_for_status()?;
+  Ok(())
+}
+
+/**
+ * Creates a digest for every configured profile. A failing profile doesn't hold up the others.
+ */
+async fn create_recommendation_digests(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let settings = &app_context.settings.recommendation_digest;
+  let recommendation_interactor = RecommendationInteractor::new(Arc::clone(&app_context));
+  for profile_id in &settings.profile_ids {
+    let profile_id = match ProfileId::try_from(profile_id.clone()) {
+      Ok(profile_id) => profile_id,
+      Err(e) => {
+        error!(
+          profile_id = profile_id.as_str(),
+          error = e.to_string(),
+          "Invalid digest profile id"
+        );
+        continue;
+      }
+    };
+    let digest = match recommendation_interactor
+      .create_recommendation_digest(&profile_id, settings.count, settings.retention_count)
+      .await
+    {
+      Ok(digest) => digest,
+      Err(e) => {
+        error!(
+          profile_id = profile_id.to_string(),
+          error = e.to_string(),
+          "Failed to create recommendation digest"
+        );
+        continue;
+      }
+    };
+    info!(
+      profile_id = profile_id.to_string(),
+      items = digest.items.len(),
+      "Created recommendation digest"
+    );
+    if let Some(webhook_url) = &settings.webhook_url {
+      if let Err(e) = deliver_digest(webhook_url, &digest).await {
+        error!(
+          profile_id = profile_id.to_string(),
+          error = e.to_string(),
+          "Failed to deliver recommendation digest"
+        );
+      }
+    }
+  }
+  Ok(())
+}
+
+pub async fn setup_recommendation_digest_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if app_context
+    .settings
+    .recommendation_digest
+    .profile_ids
+    .is_empty()
+  {
+    return Ok(());
+  }
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CreateRecommendationDigests)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(create_recommendation_digests))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::CreateRecommendationDigests)
+        .interval(
+          TimeDelta::try_days(app_context.settings.recommendation_digest.interval_days as i64)
+            .unwrap(),
+        )
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
@@ -0,0 +1,104 @@
+use super::recommendation_digest::RecommendationDigest;
+use crate::{
+  helpers::document_store::{DocumentFilter, DocumentStore},
+  profile::profile::ProfileId,
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct RecommendationDigestRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "recommendation_digest";
+
+impl RecommendationDigestRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, digest: RecommendationDigest) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &digest.id.clone(), digest, None)
+      .await
+  }
+
+  /**
+   * Digests of a profile, newest first
+   */
+  pub async fn find_by_profile_id(
+    &self,
+    profile_id: &ProfileId,
+    limit: Option<usize>,
+  ) -> Result<Vec<RecommendationDigest>> {
+    Ok(
+      self
+        .doc_store
+        .find_latest::<RecommendationDigest>(
+          COLLECTION,
+          DocumentFilter::new()
+            .condition("profile_id", "=", profile_id.to_string())
+            .build(),
+          limit,
+        )
+        .await?
+        .into_iter()
+        .map(|doc| doc.document)
+        .collect(),
+    )
+  }
+
+  /**
+   * Deletes all but the `keep` newest digests of a profile
+   */
+  pub async fn delete_all_but_latest(&self, profile_id: &ProfileId, keep: usize) -> Result<()> {
+    let stale_keys = self
+      .find_by_profile_id(profile_id, None)
+      .await?
+      .into_iter()
+      .skip(keep)
+      .map(|digest| digest.id)
+      .collect::<Vec<_>>();
+    if stale_keys.is_empty() {
+      return Ok(());
+    }
+    self.doc_store.delete_many(COLLECTION, stale_keys).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sqlite::SqliteConnection;
+
+  #[tokio::test]
+  async fn test_delete_all_but_latest() -> Result<()> {
+    let repository = RecommendationDigestRepository::new(Arc::new(DocumentStore::new(Arc::new(
+      SqliteConnection::new_for_test().await?,
+    ))));
+    let profile_id = ProfileId::try_from("test".to_string())?;
+    let mut ids = vec![];
+    for _ in 0..3 {
+      let digest = RecommendationDigest::new(profile_id.clone(), vec![]);
+      ids.push(digest.id.clone());
+      repository.put(digest).await?;
+      // Ids only order digests created in different milliseconds
+      tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    repository.delete_all_but_latest(&profile_id, 2).await?;
+    let digests = repository.find_by_profile_id(&profile_id, None).await?;
+    assert_eq!(
+      digests.into_iter().map(|d| d.id).collect::<Vec<_>>(),
+      vec![ids[2].clone(), ids[1].clone()]
+    );
+    assert_eq!(
+      repository
+        .find_by_profile_id(&profile_id, Some(1))
+        .await?
+        .len(),
+      1
+    );
+    Ok(())
+  }
+}
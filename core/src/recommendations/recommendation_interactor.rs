@@ -1,61 +1,136 @@
 use super::{
+  collaborative_filtering::collaborative_filtering_interactor::{
+    CollaborativeFilteringAlbumAssessmentSettings, CollaborativeFilteringAssessableAlbum,
+    CollaborativeFilteringInteractor,
+  },
+  cross_encoder_reranking::cross_encoder_reranking_interactor::{
+    CrossEncoderRerankedAlbumAssessmentSettings, CrossEncoderRerankedAssessableAlbum,
+    CrossEncoderRerankingInteractor,
+  },
+  descriptor_similarity_repository::DescriptorSimilarityRepository,
+  discogs_availability::{is_within_discogs_price, sort_recommendations},
+  diversity::apply_diversity_constraints,
   embedding_similarity::embedding_similarity_interactor::{
     EmbeddingSimilarityAlbumAssessmentSettings, EmbeddingSimilarityAssessableAlbum,
     EmbeddingSimilarityInteractor,
   },
+  exploration::fill_exploration_slots,
+  global_exclusion::{parse_global_exclusion_csv, GlobalExclusion, ParsedGlobalExclusionRow},
+  global_exclusion_repository::GlobalExclusionRepository,
+  playlist_energy_curve::{PlaylistEnergyCurve, TARGET_ENERGY_TOLERANCE},
+  profile_compatibility::{
+    compatibility_score, shared_items, ProfileCompatibility, SHARED_ITEM_DEPTH,
+  },
   quantile_ranking::quantile_rank_interactor::{
     QuantileRankAlbumAssessmentSettings, QuantileRankAssessableAlbum, QuantileRankInteractor,
   },
+  recommendation_curation::{
+    curate_recommendations, CuratedAlbumRecommendation, RecommendationCuration,
+  },
+  recommendation_curation_repository::RecommendationCurationRepository,
+  recommendation_digest::RecommendationDigest,
+  recommendation_digest_repository::RecommendationDigestRepository,
+  recommendation_rationale::{seed_hash, RecommendationRationale},
+  recommendation_rationale_client::RecommendationRationaleClient,
+  recommendation_rationale_repository::RecommendationRationaleRepository,
   reranked_embedding_similarity::reranked_embedding_similarity_interactor::{
     RerankedEmbeddingSimilarityAlbumAssessmentSettings, RerankedEmbeddingSimilarityAssessableAlbum,
     RerankedEmbeddingSimilarityInteractor,
   },
   seed::{AlbumRecommendationSeed, AlbumRecommendationSeedContext},
   spotify_track_search_index::{
+    SpotifyTrackAudioFeature, SpotifyTrackAudioFeatureRange,
     SpotifyTrackEmbeddingSimilaritySearchQuery, SpotifyTrackQuery, SpotifyTrackQueryBuilder,
-    SpotifyTrackSearchIndex, SpotifyTrackSearchResult,
+    SpotifyTrackSearchIndex, SpotifyTrackSearchRecord, SpotifyTrackSearchResult,
   },
+  track_sequencing::{select_tracks, sequence_tracks, RankedTrack, TrackSequencingSettings},
   types::{
-    AlbumAssessment, AlbumRecommendation, AlbumRecommendationSettings,
-    RecommendationMethodInteractor,
+    AlbumAssessment, AlbumRecommendation, AlbumRecommendationSettings, AlbumRecommendations,
+    RecommendationMethodInteractor, ScoreOrder,
   },
+  year_in_review::{albums_added_in, YearInReview},
+  year_in_review_repository::YearInReviewRepository,
 };
 use crate::{
-  albums::{album_interactor::AlbumInteractor, album_read_model::AlbumReadModel},
+  albums::{
+    album_collection_summary::AlbumCollectionSummary, album_interactor::AlbumInteractor,
+    album_read_model::AlbumReadModel,
+  },
+  collections::collection_repository::CollectionRepository,
   context::ApplicationContext,
   files::file_metadata::file_name::FileName,
+  genres::genre_taxonomy_repository::GenreTaxonomyRepository,
   helpers::{embedding::average_embedding, redisearch::SearchPagination},
+  listening::listening_event_interactor::ListeningEventInteractor,
+  lookup::BandcampLookupInteractor,
+  music_service::music_service_client::{
+    MusicService, MusicServiceClient, MusicServiceTrack, MusicServiceTrackQuery,
+  },
   profile::{
     profile::{Profile, ProfileId},
     profile_interactor::ProfileInteractor,
   },
-  spotify::spotify_client::{SpotifyClient, SpotifyTrackReference},
+  spotify::spotify_client::{SpotifyClient, SpotifyPlaylistSyncMode, SpotifyTrackReference},
+  tenant::tenant_id::TenantId,
 };
-use anyhow::Result;
-use futures::future::join_all;
-use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use futures::{future::join_all, stream, StreamExt};
+use std::{collections::HashMap, sync::Arc};
+use tokio::spawn;
+use tracing::warn;
+
+const DIVERSITY_OVERFETCH_FACTOR: u32 = 4;
+const MIN_DIVERSITY_CANDIDATES: u32 = 100;
+const TRACK_CANDIDATES_PER_ALBUM: usize = 5;
+const COMPATIBILITY_ASSESSMENT_CONCURRENCY: usize = 16;
 
+#[derive(Clone)]
 pub enum AlbumAssessmentSettings {
   QuantileRank(QuantileRankAlbumAssessmentSettings),
   EmbeddingSimilarity(EmbeddingSimilarityAlbumAssessmentSettings),
   RerankedEmbeddingSimilarity(RerankedEmbeddingSimilarityAlbumAssessmentSettings),
+  CollaborativeFiltering(CollaborativeFilteringAlbumAssessmentSettings),
+  CrossEncoderReranked(CrossEncoderRerankedAlbumAssessmentSettings),
+}
+
+impl AlbumAssessmentSettings {
+  pub fn score_order(&self) -> ScoreOrder {
+    match self {
+      AlbumAssessmentSettings::EmbeddingSimilarity(_) => ScoreOrder::Ascending,
+      _ => ScoreOrder::Descending,
+    }
+  }
 }
 
 pub struct RecommendationInteractor {
   quantile_rank_interactor: Arc<QuantileRankInteractor>,
   embedding_similarity_interactor: Arc<EmbeddingSimilarityInteractor>,
   reranked_embedding_similarity_interactor: RerankedEmbeddingSimilarityInteractor,
+  collaborative_filtering_interactor: Arc<CollaborativeFilteringInteractor>,
+  cross_encoder_reranking_interactor: Option<CrossEncoderRerankingInteractor>,
   album_interactor: Arc<AlbumInteractor>,
+  bandcamp_lookup_interactor: Option<Arc<BandcampLookupInteractor>>,
   profile_interactor: Arc<ProfileInteractor>,
-  spotify_track_search_index: Arc<SpotifyTrackSearchIndex>,
+  collection_repository: CollectionRepository,
+  listening_event_interactor: ListeningEventInteractor,
+  spotify_track_search_index: Arc<dyn SpotifyTrackSearchIndex + Send + Sync>,
   spotify_client: Arc<SpotifyClient>,
+  curation_repository: RecommendationCurationRepository,
+  digest_repository: RecommendationDigestRepository,
+  global_exclusion_repository: GlobalExclusionRepository,
+  year_in_review_repository: YearInReviewRepository,
+  rationale_client: Option<RecommendationRationaleClient>,
+  rationale_repository: RecommendationRationaleRepository,
 }
 
 impl RecommendationInteractor {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
-    let quantile_rank_interactor = Arc::new(QuantileRankInteractor::new(Arc::clone(
-      &app_context.album_interactor,
-    )));
+    let quantile_rank_interactor = Arc::new(QuantileRankInteractor::new(
+      Arc::clone(&app_context.album_interactor),
+      GenreTaxonomyRepository::new(Arc::clone(&app_context.sqlite_connection)),
+      DescriptorSimilarityRepository::new(Arc::clone(&app_context.sqlite_connection)),
+    ));
     let embedding_similarity_interactor = Arc::new(EmbeddingSimilarityInteractor::new(Arc::clone(
       &app_context.album_interactor,
     )));
@@ -63,17 +138,73 @@ impl RecommendationInteractor {
       Arc::clone(&embedding_similarity_interactor),
       Arc::clone(&quantile_rank_interactor),
     );
+    let cross_encoder_reranking_interactor =
+      app_context.cross_encoder.as_ref().map(|cross_encoder| {
+        CrossEncoderRerankingInteractor::new(
+          Arc::clone(&embedding_similarity_interactor),
+          Arc::clone(cross_encoder),
+        )
+      });
     Self {
       quantile_rank_interactor,
       embedding_similarity_interactor,
       reranked_embedding_similarity_interactor,
+      collaborative_filtering_interactor: Arc::clone(
+        &app_context.collaborative_filtering_interactor,
+      ),
+      cross_encoder_reranking_interactor,
       album_interactor: Arc::clone(&app_context.album_interactor),
+      bandcamp_lookup_interactor: app_context.bandcamp_lookup_interactor.clone(),
       profile_interactor: Arc::clone(&app_context.profile_interactor),
+      collection_repository: CollectionRepository::new(Arc::clone(&app_context.doc_store)),
+      listening_event_interactor: ListeningEventInteractor::new(Arc::clone(&app_context)),
       spotify_track_search_index: Arc::clone(&app_context.spotify_track_search_index),
       spotify_client: Arc::clone(&app_context.spotify_client),
+      curation_repository: RecommendationCurationRepository::new(Arc::clone(
+        &app_context.doc_store,
+      )),
+      digest_repository: RecommendationDigestRepository::new(Arc::clone(&app_context.doc_store)),
+      global_exclusion_repository: GlobalExclusionRepository::new(Arc::clone(
+        &app_context.doc_store,
+      )),
+      year_in_review_repository: YearInReviewRepository::new(Arc::clone(&app_context.doc_store)),
+      rationale_client: app_context
+        .settings
+        .recommendation_rationale
+        .as_ref()
+        .map(RecommendationRationaleClient::new),
+      rationale_repository: RecommendationRationaleRepository::new(Arc::clone(
+        &app_context.doc_store,
+      )),
     }
   }
 
+  /**
+   * Runs in the background so recommendations don't wait on a staleness check per album. Best
+   * effort, a failed lookup shouldn't fail the recommendations.
+   */
+  fn enqueue_bandcamp_lookups(&self, albums: Vec<&AlbumReadModel>) {
+    let Some(bandcamp_lookup_interactor) = self.bandcamp_lookup_interactor.clone() else {
+      return;
+    };
+    let albums = albums
+      .into_iter()
+      .filter(|album| album.bandcamp_url.is_none())
+      .cloned()
+      .collect::<Vec<_>>();
+    if albums.is_empty() {
+      return;
+    }
+    spawn(async move {
+      if let Err(e) = bandcamp_lookup_interactor
+        .enqueue_many(albums.iter().collect())
+        .await
+      {
+        warn!(err = e.to_string(), "Failed to enqueue Bandcamp lookups");
+      }
+    });
+  }
+
   async fn get_profile_and_albums(
     &self,
     profile_id: &ProfileId,
@@ -88,17 +219,24 @@ impl RecommendationInteractor {
     Ok((profile, albums))
   }
 
-  async fn build_seed_context(
+  async fn build_single_seed_context(
     &self,
     seed: AlbumRecommendationSeed,
   ) -> Result<AlbumRecommendationSeedContext> {
     match seed {
       AlbumRecommendationSeed::Profile(profile_id) => {
         let (profile, albums) = self.get_profile_and_albums(&profile_id).await?;
-        Ok(AlbumRecommendationSeedContext::new(
-          albums,
-          profile.albums.clone(),
-        ))
+        let artist_listen_scores = self
+          .listening_event_interactor
+          .find_artist_listen_scores(&profile_id)
+          .await?;
+        Ok(
+          AlbumRecommendationSeedContext::new(
+            albums,
+            profile.decayed_albums(Utc::now().naive_utc()),
+          )
+          .with_artist_listen_scores(artist_listen_scores),
+        )
       }
       AlbumRecommendationSeed::Albums(factor_map) => {
         let albums = self
@@ -109,6 +247,80 @@ impl RecommendationInteractor {
           .collect();
         Ok(AlbumRecommendationSeedContext::new(albums, factor_map))
       }
+      AlbumRecommendationSeed::Collection { id, tenant_id } => {
+        let collection = self
+          .collection_repository
+          .find(&id)
+          .await?
+          .filter(|collection| collection.profile_id.tenant_id() == tenant_id)
+          .ok_or_else(|| anyhow!("Collection not found: {}", id))?;
+        let albums = self
+          .album_interactor
+          .find_many(collection.album_file_names.clone())
+          .await?
+          .into_values()
+          .collect();
+        Ok(AlbumRecommendationSeedContext::new(
+          albums,
+          collection
+            .album_file_names
+            .into_iter()
+            .map(|file_name| (file_name, 1))
+            .collect(),
+        ))
+      }
+      AlbumRecommendationSeed::Blend(_) => Err(anyhow!("Blended seeds cannot be nested")),
+      AlbumRecommendationSeed::WithNegative { .. } => Err(anyhow!(
+        "Negative seeds can only be attached to the top-level seed"
+      )),
+    }
+  }
+
+  async fn build_positive_seed_context(
+    &self,
+    seed: AlbumRecommendationSeed,
+  ) -> Result<AlbumRecommendationSeedContext> {
+    match seed {
+      AlbumRecommendationSeed::Blend(seeds) => {
+        if seeds.is_empty() {
+          return Err(anyhow!("Blended seed must contain at least one seed"));
+        }
+        let contexts = join_all(seeds.into_iter().map(|weighted| async move {
+          Ok::<_, anyhow::Error>((
+            self.build_single_seed_context(weighted.seed).await?,
+            weighted.weight,
+          ))
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+        Ok(AlbumRecommendationSeedContext::merge(contexts))
+      }
+      seed => self.build_single_seed_context(seed).await,
+    }
+  }
+
+  async fn build_seed_context(
+    &self,
+    seed: AlbumRecommendationSeed,
+  ) -> Result<AlbumRecommendationSeedContext> {
+    match seed {
+      AlbumRecommendationSeed::WithNegative {
+        seed,
+        negative,
+        weight,
+      } => {
+        if weight < 0.0 {
+          return Err(anyhow!(
+            "Negative seed weight must not be negative, got {}",
+            weight
+          ));
+        }
+        let seed_context = self.build_positive_seed_context(*seed).await?;
+        let negative_context = self.build_single_seed_context(*negative).await?;
+        Ok(seed_context.with_negative(negative_context, weight))
+      }
+      seed => self.build_positive_seed_context(seed).await,
     }
   }
 
@@ -120,12 +332,78 @@ impl RecommendationInteractor {
   ) -> Result<AlbumAssessment> {
     let seed_context = self.build_seed_context(seed).await?;
     let album = self.album_interactor.get(album_file_name).await?;
+    self
+      .assess_album_with_seed_context(&seed_context, album, settings)
+      .await
+  }
+
+  /**
+   * A paragraph on why the album suits the seed, generated once per seed and album then cached
+   */
+  pub async fn get_recommendation_rationale(
+    &self,
+    seed: AlbumRecommendationSeed,
+    album_file_name: &FileName,
+    settings: AlbumAssessmentSettings,
+  ) -> Result<RecommendationRationale> {
+    let rationale_client = self
+      .rationale_client
+      .as_ref()
+      .ok_or_else(|| anyhow!("Recommendation rationales are not configured"))?;
+    let seed_context = self.build_seed_context(seed).await?;
+    let seed_hash = seed_hash(&seed_context)?;
+    if let Some(rationale) = self
+      .rationale_repository
+      .find(&seed_hash, album_file_name)
+      .await?
+    {
+      return Ok(rationale);
+    }
+
+    let album = self.album_interactor.get(album_file_name).await?;
+    let assessment = self
+      .assess_album_with_seed_context(&seed_context, album.clone(), settings)
+      .await?;
+    let recommendation = AlbumRecommendation {
+      album,
+      assessment,
+      exploratory: false,
+    };
+    let rationale = RecommendationRationale {
+      seed_hash,
+      album_file_name: album_file_name.clone(),
+      rationale: rationale_client
+        .generate(&seed_context, &recommendation)
+        .await?,
+      model: rationale_client.model.clone(),
+      created_at: Utc::now().naive_utc(),
+    };
+    self
+      .rationale_repository
+      .put(rationale.clone(), rationale_client.cache_ttl)
+      .await?;
+    Ok(rationale)
+  }
+
+  fn cross_encoder_reranking_interactor(&self) -> Result<&CrossEncoderRerankingInteractor> {
+    self
+      .cross_encoder_reranking_interactor
+      .as_ref()
+      .ok_or_else(|| anyhow!("Cross-encoder reranking is not configured"))
+  }
+
+  async fn assess_album_with_seed_context(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    album: AlbumReadModel,
+    settings: AlbumAssessmentSettings,
+  ) -> Result<AlbumAssessment> {
     match settings {
       AlbumAssessmentSettings::QuantileRank(settings) => {
         self
           .quantile_rank_interactor
           .assess_album(
-            &seed_context,
+            seed_context,
             &QuantileRankAssessableAlbum::try_from(album)?,
             settings,
           )
@@ -135,7 +413,7 @@ impl RecommendationInteractor {
         self
           .embedding_similarity_interactor
           .assess_album(
-            &seed_context,
+            seed_context,
             &EmbeddingSimilarityAssessableAlbum::try_from(album)?,
             settings,
           )
@@ -145,67 +423,532 @@ impl RecommendationInteractor {
         self
           .reranked_embedding_similarity_interactor
           .assess_album(
-            &seed_context,
+            seed_context,
             &RerankedEmbeddingSimilarityAssessableAlbum::try_from(album)?,
             settings,
           )
           .await
       }
+      AlbumAssessmentSettings::CollaborativeFiltering(settings) => {
+        self
+          .collaborative_filtering_interactor
+          .assess_album(
+            seed_context,
+            &CollaborativeFilteringAssessableAlbum::try_from(album)?,
+            settings,
+          )
+          .await
+      }
+      AlbumAssessmentSettings::CrossEncoderReranked(settings) => {
+        self
+          .cross_encoder_reranking_interactor()?
+          .assess_album(
+            seed_context,
+            &CrossEncoderRerankedAssessableAlbum::try_from(album)?,
+            settings,
+          )
+          .await
+      }
     }
   }
 
   async fn recommend_albums_with_seed_context(
+    &self,
+    tenant_id: &TenantId,
+    assessment_settings: AlbumAssessmentSettings,
+    mut recommendation_settings: AlbumRecommendationSettings,
+    seed_context: &AlbumRecommendationSeedContext,
+  ) -> Result<AlbumRecommendations> {
+    if !recommendation_settings.include_globally_excluded {
+      recommendation_settings.exclude_file_names.extend(
+        self
+          .global_exclusion_repository
+          .get(tenant_id)
+          .await?
+          .file_names,
+      );
+    }
+    let sort = recommendation_settings.sort;
+    let mut recommendations = self
+      .recommend_constrained_albums(assessment_settings, recommendation_settings, seed_context)
+      .await?;
+    sort_recommendations(&mut recommendations.recommendations, sort);
+    Ok(recommendations)
+  }
+
+  /**
+   * Overfetches when candidates are filtered or explored after they're assessed, so enough are left
+   * to fill the count
+   */
+  async fn recommend_constrained_albums(
+    &self,
+    assessment_settings: AlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    seed_context: &AlbumRecommendationSeedContext,
+  ) -> Result<AlbumRecommendations> {
+    let exploration_slots = recommendation_settings.exploration_slots();
+    if !recommendation_settings.has_post_assessment_constraints() && exploration_slots == 0 {
+      return self
+        .recommend_albums_by_method(assessment_settings, recommendation_settings, seed_context)
+        .await;
+    }
+    let score_order = assessment_settings.score_order();
+    let mut candidates = self
+      .recommend_albums_by_method(
+        assessment_settings,
+        AlbumRecommendationSettings {
+          count: (recommendation_settings
+            .count
+            .saturating_mul(DIVERSITY_OVERFETCH_FACTOR))
+          .max(MIN_DIVERSITY_CANDIDATES),
+          ..recommendation_settings.clone()
+        },
+        seed_context,
+      )
+      .await?;
+    candidates.recommendations.retain(|recommendation| {
+      is_within_discogs_price(
+        &recommendation.album,
+        recommendation_settings.max_discogs_lowest_price,
+      )
+    });
+    let exploitation_settings = AlbumRecommendationSettings {
+      count: recommendation_settings.count - exploration_slots,
+      ..recommendation_settings.clone()
+    };
+    let selected =
+      apply_diversity_constraints(candidates.recommendations.clone(), &exploitation_settings);
+    Ok(AlbumRecommendations {
+      recommendations: fill_exploration_slots(
+        selected,
+        candidates.recommendations,
+        &recommendation_settings,
+        &score_order,
+        &mut rand::thread_rng(),
+      )?,
+      completeness: candidates.completeness,
+    })
+  }
+
+  /**
+   * Only quantile ranking takes a time budget, the other methods are bounded by a single vector
+   * search and always complete
+   */
+  async fn recommend_albums_by_method(
     &self,
     assessment_settings: AlbumAssessmentSettings,
     recommendation_settings: AlbumRecommendationSettings,
     seed_context: &AlbumRecommendationSeedContext,
-  ) -> Result<Vec<AlbumRecommendation>> {
+  ) -> Result<AlbumRecommendations> {
+    if recommendation_settings.deadline.is_some()
+      && !matches!(
+        assessment_settings,
+        AlbumAssessmentSettings::QuantileRank(_)
+      )
+    {
+      return Err(anyhow!(
+        "Time budgets are only supported by quantile rank assessments"
+      ));
+    }
     match assessment_settings {
       AlbumAssessmentSettings::QuantileRank(settings) => {
         self
           .quantile_rank_interactor
-          .recommend_albums(seed_context, settings, recommendation_settings)
-          .await
-      }
-      AlbumAssessmentSettings::EmbeddingSimilarity(settings) => {
-        self
-          .embedding_similarity_interactor
-          .recommend_albums(seed_context, settings, recommendation_settings)
-          .await
-      }
-      AlbumAssessmentSettings::RerankedEmbeddingSimilarity(settings) => {
-        self
-          .reranked_embedding_similarity_interactor
-          .recommend_albums(seed_context, settings, recommendation_settings)
+          .recommend_albums_within_budget(seed_context, settings, recommendation_settings)
           .await
       }
+      AlbumAssessmentSettings::EmbeddingSimilarity(settings) => self
+        .embedding_similarity_interactor
+        .recommend_albums(seed_context, settings, recommendation_settings)
+        .await
+        .map(AlbumRecommendations::complete),
+      AlbumAssessmentSettings::RerankedEmbeddingSimilarity(settings) => self
+        .reranked_embedding_similarity_interactor
+        .recommend_albums(seed_context, settings, recommendation_settings)
+        .await
+        .map(AlbumRecommendations::complete),
+      AlbumAssessmentSettings::CollaborativeFiltering(settings) => self
+        .collaborative_filtering_interactor
+        .recommend_albums(seed_context, settings, recommendation_settings)
+        .await
+        .map(AlbumRecommendations::complete),
+      AlbumAssessmentSettings::CrossEncoderReranked(settings) => self
+        .cross_encoder_reranking_interactor()?
+        .recommend_albums(seed_context, settings, recommendation_settings)
+        .await
+        .map(AlbumRecommendations::complete),
     }
   }
 
   pub async fn recommend_albums(
     &self,
+    tenant_id: &TenantId,
     seed: AlbumRecommendationSeed,
     assessment_settings: AlbumAssessmentSettings,
     recommendation_settings: AlbumRecommendationSettings,
-  ) -> Result<Vec<AlbumRecommendation>> {
+  ) -> Result<AlbumRecommendations> {
     let seed_context = self.build_seed_context(seed).await?;
-    self
+    let recommendations = self
       .recommend_albums_with_seed_context(
+        tenant_id,
         assessment_settings,
         recommendation_settings,
         &seed_context,
       )
-      .await
+      .await?;
+    self.enqueue_bandcamp_lookups(
+      recommendations
+        .recommendations
+        .iter()
+        .map(|recommendation| &recommendation.album)
+        .collect(),
+    );
+    Ok(recommendations)
   }
 
-  pub async fn draft_spotify_playlist(
+  pub async fn get_recommendation_curation(
     &self,
-    seed: AlbumRecommendationSeed,
+    profile_id: &ProfileId,
+  ) -> Result<RecommendationCuration> {
+    self.curation_repository.get(profile_id).await
+  }
+
+  pub async fn update_recommendation_curation(
+    &self,
+    profile_id: &ProfileId,
+    pin: Vec<FileName>,
+    unpin: Vec<FileName>,
+    exclude: Vec<FileName>,
+    unexclude: Vec<FileName>,
+  ) -> Result<RecommendationCuration> {
+    let mut curation = self.curation_repository.get(profile_id).await?;
+    curation.update(pin, unpin, exclude, unexclude);
+    self.curation_repository.put(curation.clone()).await?;
+    Ok(curation)
+  }
+
+  pub async fn get_global_exclusion(&self, tenant_id: &TenantId) -> Result<GlobalExclusion> {
+    self.global_exclusion_repository.get(tenant_id).await
+  }
+
+  pub async fn update_global_exclusion(
+    &self,
+    tenant_id: &TenantId,
+    add: Vec<FileName>,
+    remove: Vec<FileName>,
+  ) -> Result<GlobalExclusion> {
+    let mut exclusion = self.global_exclusion_repository.get(tenant_id).await?;
+    exclusion.update(add, remove);
+    self
+      .global_exclusion_repository
+      .put(tenant_id, exclusion.clone())
+      .await?;
+    Ok(exclusion)
+  }
+
+  /**
+   * Adds every valid file name in the CSV to the global exclusion list. Invalid rows are reported
+   * back rather than failing the import.
+   */
+  pub async fn import_global_exclusion_csv(
+    &self,
+    tenant_id: &TenantId,
+    content: &str,
+  ) -> Result<Vec<ParsedGlobalExclusionRow>> {
+    let rows = parse_global_exclusion_csv(content);
+    self
+      .update_global_exclusion(
+        tenant_id,
+        rows
+          .iter()
+          .filter_map(|row| row.file_name.clone().ok())
+          .collect(),
+        vec![],
+      )
+      .await?;
+    Ok(rows)
+  }
+
+  /**
+   * Recommends albums for a profile with its pin/exclude lists applied. Pinned albums that
+   * haven't been crawled yet are skipped.
+   */
+  pub async fn recommend_curated_albums(
+    &self,
+    profile_id: &ProfileId,
     assessment_settings: AlbumAssessmentSettings,
     recommendation_settings: AlbumRecommendationSettings,
-  ) -> Result<Vec<SpotifyTrackReference>> {
-    let seed_context = self.build_seed_context(seed).await?;
-    let profile_tracks = self
+  ) -> Result<Vec<CuratedAlbumRecommendation>> {
+    let curation = self.curation_repository.get(profile_id).await?;
+    let seed_context = self
+      .build_seed_context(AlbumRecommendationSeed::Profile(profile_id.clone()))
+      .await?;
+    let count = recommendation_settings.count;
+    let organic = self
+      .recommend_albums_with_seed_context(
+        &profile_id.tenant_id(),
+        assessment_settings.clone(),
+        AlbumRecommendationSettings {
+          count: count + (curation.pinned.len() + curation.excluded.len()) as u32,
+          ..recommendation_settings
+        },
+        &seed_context,
+      )
+      .await?
+      .recommendations;
+    let mut pinned_albums = self
+      .album_interactor
+      .find_many(curation.pinned.clone())
+      .await?;
+    let mut pinned = Vec::new();
+    for file_name in &curation.pinned {
+      let Some(album) = pinned_albums.remove(file_name) else {
+        warn!(
+          file_name = file_name.to_string(),
+          "Pinned album not found, skipping"
+        );
+        continue;
+      };
+      let assessment = self
+        .assess_album_with_seed_context(&seed_context, album.clone(), assessment_settings.clone())
+        .await?;
+      pinned.push(AlbumRecommendation {
+        album,
+        assessment,
+        exploratory: false,
+      });
+    }
+    let recommendations = curate_recommendations(
+      &curation,
+      pinned,
+      organic,
+      count as usize,
+      assessment_settings.score_order(),
+    );
+    self.enqueue_bandcamp_lookups(
+      recommendations
+        .iter()
+        .map(|curated| &curated.recommendation.album)
+        .collect(),
+    );
+    Ok(recommendations)
+  }
+
+  /**
+   * Computes and stores a fresh digest of a profile's curated recommendations, keeping only the
+   * `retention_count` newest digests
+   */
+  pub async fn create_recommendation_digest(
+    &self,
+    profile_id: &ProfileId,
+    count: u32,
+    retention_count: u32,
+  ) -> Result<RecommendationDigest> {
+    let recommendations = self
+      .recommend_curated_albums(
+        profile_id,
+        AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+        AlbumRecommendationSettings {
+          count,
+          ..Default::default()
+        },
+      )
+      .await?;
+    let digest = RecommendationDigest::new(profile_id.clone(), recommendations);
+    self.digest_repository.put(digest.clone()).await?;
+    self
+      .digest_repository
+      .delete_all_but_latest(profile_id, retention_count as usize)
+      .await?;
+    Ok(digest)
+  }
+
+  pub async fn list_recommendation_digests(
+    &self,
+    profile_id: &ProfileId,
+    limit: Option<usize>,
+  ) -> Result<Vec<RecommendationDigest>> {
+    self
+      .digest_repository
+      .find_by_profile_id(profile_id, limit)
+      .await
+  }
+
+  /**
+   * Assesses the candidate profile's albums with the seed profile as the seed. The better the
+   * candidate's albums fit the seed's taste the higher the score, and the best fitting of them
+   * bridge both tastes. Albums that can't be assessed, e.g. for a missing embedding, are skipped.
+   */
+  pub async fn assess_profile_compatibility(
+    &self,
+    seed_profile_id: &ProfileId,
+    candidate_profile_id: &ProfileId,
+    settings: AlbumAssessmentSettings,
+    bridge_album_count: usize,
+  ) -> Result<ProfileCompatibility> {
+    let seed_context = self
+      .build_single_seed_context(AlbumRecommendationSeed::Profile(seed_profile_id.clone()))
+      .await?;
+    let (candidate_profile, candidate_albums) =
+      self.get_profile_and_albums(candidate_profile_id).await?;
+    let seed_summary = AlbumCollectionSummary::new(&seed_context.albums, &seed_context.factor_map);
+    let candidate_summary =
+      AlbumCollectionSummary::new(&candidate_albums, &candidate_profile.albums);
+    let shared_album_count = candidate_profile
+      .albums
+      .keys()
+      .filter(|file_name| seed_context.factor_map.contains_key(file_name))
+      .count() as u32;
+
+    let mut assessed = stream::iter(candidate_albums)
+      .map(|album| {
+        let seed_context = &seed_context;
+        let settings = settings.clone();
+        async move {
+          self
+            .assess_album_with_seed_context(seed_context, album.clone(), settings)
+            .await
+            .inspect_err(|e| {
+              warn!(
+                err = e.to_string(),
+                file_name = album.file_name.to_string(),
+                "Failed to assess album for profile compatibility"
+              )
+            })
+            .ok()
+            .map(|assessment| AlbumRecommendation {
+              album,
+              assessment,
+              exploratory: false,
+            })
+        }
+      })
+      .buffer_unordered(COMPATIBILITY_ASSESSMENT_CONCURRENCY)
+      .filter_map(|assessed| async move { assessed })
+      .collect::<Vec<_>>()
+      .await;
+    assessed.sort_by(|a, b| b.cmp(a));
+
+    Ok(ProfileCompatibility {
+      score: compatibility_score(&assessed, &candidate_profile.albums),
+      assessed_album_count: assessed.len() as u32,
+      shared_album_count,
+      shared_primary_genres: shared_items(
+        &seed_summary.primary_genres,
+        &candidate_summary.primary_genres,
+        SHARED_ITEM_DEPTH,
+      ),
+      shared_descriptors: shared_items(
+        &seed_summary.descriptors,
+        &candidate_summary.descriptors,
+        SHARED_ITEM_DEPTH,
+      ),
+      bridge_albums: assessed.into_iter().take(bridge_album_count).collect(),
+    })
+  }
+
+  /**
+   * Builds and stores the profile's report for the year, replacing an earlier one. Next year's
+   * recommendations are seeded by the albums added during the year and leave out albums already on
+   * the profile.
+   */
+  pub async fn create_year_in_review(
+    &self,
+    profile_id: &ProfileId,
+    year: i32,
+    recommendation_count: u32,
+  ) -> Result<YearInReview> {
+    let (profile, _) = self.get_profile_and_albums(profile_id).await?;
+    let added = albums_added_in(&profile, year);
+    let albums = self
+      .album_interactor
+      .find_many(added.keys().cloned().collect())
+      .await?
+      .into_values()
+      .collect::<Vec<_>>();
+    let recommendations = if added.is_empty() || recommendation_count == 0 {
+      vec![]
+    } else {
+      self
+        .recommend_albums(
+          &profile_id.tenant_id(),
+          AlbumRecommendationSeed::Albums(added),
+          AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+          AlbumRecommendationSettings {
+            count: recommendation_count,
+            exclude_file_names: profile.album_file_names(),
+            ..Default::default()
+          },
+        )
+        .await?
+        .recommendations
+    };
+    let report = YearInReview::new(&profile, year, &albums, recommendations);
+    self.year_in_review_repository.put(report.clone()).await?;
+    Ok(report)
+  }
+
+  pub async fn find_year_in_review(
+    &self,
+    profile_id: &ProfileId,
+    year: i32,
+  ) -> Result<Option<YearInReview>> {
+    self.year_in_review_repository.find(profile_id, year).await
+  }
+
+  pub async fn list_year_in_reviews(&self, profile_id: &ProfileId) -> Result<Vec<YearInReview>> {
+    self
+      .year_in_review_repository
+      .find_by_profile_id(profile_id)
+      .await
+  }
+
+  /**
+   * The album's track closest to the profile's sound, preferring tracks near the target energy
+   * when there is one
+   */
+  async fn find_playlist_track(
+    &self,
+    profile_embedding: &[f32],
+    album_file_name: &FileName,
+    target_energy: Option<f32>,
+  ) -> Result<Option<SpotifyTrackSearchRecord>> {
+    let search = |audio_feature_ranges: Vec<SpotifyTrackAudioFeatureRange>| async move {
+      let track = self
+        .spotify_track_search_index
+        .embedding_similarity_search(&SpotifyTrackEmbeddingSimilaritySearchQuery {
+          embedding: profile_embedding.to_vec(),
+          filters: SpotifyTrackQueryBuilder::default()
+            .include_album_file_names(vec![album_file_name.clone()])
+            .audio_feature_ranges(audio_feature_ranges)
+            .build()?,
+          limit: 1,
+        })
+        .await?;
+      Ok::<_, anyhow::Error>(track.into_iter().next().map(|(track, _)| track))
+    };
+    if let Some(target_energy) = target_energy {
+      let track = search(vec![SpotifyTrackAudioFeatureRange {
+        feature: SpotifyTrackAudioFeature::Energy,
+        min: Some(target_energy - TARGET_ENERGY_TOLERANCE),
+        max: Some(target_energy + TARGET_ENERGY_TOLERANCE),
+      }])
+      .await?;
+      if track.is_some() {
+        return Ok(track);
+      }
+    }
+    search(vec![]).await
+  }
+
+  /**
+   * Average embedding of the seed albums' indexed tracks, weighted by album factor
+   */
+  async fn seed_track_embedding(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+  ) -> Result<Vec<f32>> {
+    let seed_tracks = self
       .spotify_track_search_index
       .search(
         &SpotifyTrackQueryBuilder::default()
@@ -214,8 +957,8 @@ impl RecommendationInteractor {
         None,
       )
       .await?;
-    let profile_embedding = average_embedding(
-      profile_tracks
+    Ok(average_embedding(
+      seed_tracks
         .tracks
         .iter()
         .map(|track| {
@@ -225,35 +968,108 @@ impl RecommendationInteractor {
           )
         })
         .collect::<Vec<_>>(),
-    );
+    ))
+  }
+
+  /**
+   * Ranks the indexed tracks of the recommended albums, then selects and sequences them into a
+   * playlist. A track's score is its similarity to the seed's sound scaled by its album's place in
+   * the recommendations, so it doesn't depend on the scale of the assessment method's scores.
+   */
+  pub async fn recommend_tracks(
+    &self,
+    tenant_id: &TenantId,
+    seed: AlbumRecommendationSeed,
+    assessment_settings: AlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    sequencing_settings: TrackSequencingSettings,
+  ) -> Result<Vec<RankedTrack>> {
+    let seed_context = self.build_seed_context(seed).await?;
+    let seed_embedding = self.seed_track_embedding(&seed_context).await?;
     let recommendations = self
       .recommend_albums_with_seed_context(
+        tenant_id,
         assessment_settings,
         recommendation_settings,
         &seed_context,
       )
+      .await?
+      .recommendations;
+    if recommendations.is_empty() {
+      return Ok(vec![]);
+    }
+    let album_weights = recommendations
+      .iter()
+      .enumerate()
+      .map(|(position, recommendation)| {
+        (
+          recommendation.album.file_name.clone(),
+          1.0 - position as f32 / recommendations.len() as f32,
+        )
+      })
+      .collect::<HashMap<_, _>>();
+    let candidates = self
+      .spotify_track_search_index
+      .embedding_similarity_search(&SpotifyTrackEmbeddingSimilaritySearchQuery {
+        embedding: seed_embedding,
+        filters: SpotifyTrackQueryBuilder::default()
+          .include_album_file_names(album_weights.keys().cloned().collect::<Vec<_>>())
+          .build()?,
+        limit: recommendations.len() * TRACK_CANDIDATES_PER_ALBUM,
+      })
       .await?;
+    let ranked_tracks = candidates
+      .into_iter()
+      .filter_map(|(track, distance)| {
+        let album_weight = album_weights.get(&track.album_file_name)?;
+        Some(RankedTrack {
+          // Cosine distance ranges from 0 to 2
+          score: album_weight * (1.0 - distance / 2.0),
+          track,
+        })
+      })
+      .collect::<Vec<_>>();
+    Ok(sequence_tracks(
+      select_tracks(ranked_tracks, &sequencing_settings),
+      &sequencing_settings,
+    ))
+  }
+
+  pub async fn draft_spotify_playlist(
+    &self,
+    tenant_id: &TenantId,
+    seed: AlbumRecommendationSeed,
+    assessment_settings: AlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    energy_curve: PlaylistEnergyCurve,
+  ) -> Result<Vec<SpotifyTrackReference>> {
+    let seed_context = self.build_seed_context(seed).await?;
+    let profile_embedding = self.seed_track_embedding(&seed_context).await?;
+    let recommendations = self
+      .recommend_albums_with_seed_context(
+        tenant_id,
+        assessment_settings,
+        recommendation_settings,
+        &seed_context,
+      )
+      .await?
+      .recommendations;
     let recommendation_tracks = join_all(
       recommendations
         .iter()
-        .map(|recommendation| async {
-          let track = self
-            .spotify_track_search_index
-            .embedding_similarity_search(&SpotifyTrackEmbeddingSimilaritySearchQuery {
-              embedding: profile_embedding.clone(),
-              filters: SpotifyTrackQueryBuilder::default()
-                .include_album_file_names(vec![recommendation.album.file_name.clone()])
-                .build()?,
-              limit: 1,
-            })
-            .await?;
-          Ok(track.into_iter().next())
+        .enumerate()
+        .map(|(position, recommendation)| {
+          self.find_playlist_track(
+            &profile_embedding,
+            &recommendation.album.file_name,
+            energy_curve.target_energy(position, recommendations.len()),
+          )
         })
         .collect::<Vec<_>>(),
     )
     .await
     .into_iter()
-    .filter_map(|result| result.map(|r| r.map(|(t, _)| t.into())).transpose())
+    .filter_map(|result| result.map(|r| r.map(Into::into)).transpose())
     .collect::<Result<Vec<SpotifyTrackReference>>>()?;
 
     Ok(recommendation_tracks)
@@ -261,17 +1077,26 @@ impl RecommendationInteractor {
 
   pub async fn create_spotify_playlist(
     &self,
+    tenant_id: &TenantId,
     seed: AlbumRecommendationSeed,
     assessment_settings: AlbumAssessmentSettings,
     recommendation_settings: AlbumRecommendationSettings,
+    energy_curve: PlaylistEnergyCurve,
     name: String,
     description: Option<String>,
   ) -> Result<(String, Vec<SpotifyTrackReference>)> {
     let playlist_draft = self
-      .draft_spotify_playlist(seed, assessment_settings, recommendation_settings)
+      .draft_spotify_playlist(
+        tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+      )
       .await?;
     let playlist_id = self
       .spotify_client
+      .for_tenant(tenant_id)
       .create_playlist(
         name,
         description,
@@ -285,6 +1110,103 @@ impl RecommendationInteractor {
     Ok((playlist_id, playlist_draft))
   }
 
+  /**
+   * Drafts a playlist from the Spotify track index and recreates it on the given service by
+   * matching each track. Tracks the service doesn't carry are left out.
+   */
+  pub async fn export_playlist(
+    &self,
+    tenant_id: &TenantId,
+    client: &dyn MusicServiceClient,
+    seed: AlbumRecommendationSeed,
+    assessment_settings: AlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    energy_curve: PlaylistEnergyCurve,
+    name: String,
+    description: Option<String>,
+  ) -> Result<(String, Vec<MusicServiceTrack>)> {
+    let playlist_draft = self
+      .draft_spotify_playlist(
+        tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+      )
+      .await?;
+    let mut tracks = vec![];
+    for draft_track in playlist_draft {
+      let artist_names = draft_track
+        .artists
+        .into_iter()
+        .map(|artist| artist.name)
+        .collect::<Vec<_>>();
+      if client.service() == MusicService::Spotify {
+        tracks.push(MusicServiceTrack {
+          id: draft_track.spotify_id,
+          name: draft_track.name,
+          artist_names,
+          isrc: None,
+        });
+        continue;
+      }
+      let query = MusicServiceTrackQuery {
+        name: draft_track.name,
+        artist_names,
+        isrc: None,
+      };
+      match client.find_track(&query).await? {
+        Some(track) => tracks.push(track),
+        None => warn!(
+          name = query.name.as_str(),
+          "No matching track found, skipping"
+        ),
+      }
+    }
+    let playlist_id = client
+      .create_playlist(
+        name,
+        description,
+        tracks.iter().map(|track| track.id.clone()).collect(),
+      )
+      .await?;
+    Ok((playlist_id, tracks))
+  }
+
+  pub async fn sync_spotify_playlist(
+    &self,
+    tenant_id: &TenantId,
+    seed: AlbumRecommendationSeed,
+    assessment_settings: AlbumAssessmentSettings,
+    recommendation_settings: AlbumRecommendationSettings,
+    energy_curve: PlaylistEnergyCurve,
+    playlist_id: &str,
+    mode: SpotifyPlaylistSyncMode,
+  ) -> Result<Vec<SpotifyTrackReference>> {
+    let playlist_draft = self
+      .draft_spotify_playlist(
+        tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+      )
+      .await?;
+    self
+      .spotify_client
+      .for_tenant(tenant_id)
+      .sync_playlist(
+        playlist_id,
+        playlist_draft
+          .iter()
+          .map(|t| t.spotify_id.clone())
+          .collect(),
+        mode,
+      )
+      .await?;
+    Ok(playlist_draft)
+  }
+
   pub async fn search_spotify_track(
     &self,
     query: &SpotifyTrackQuery,
@@ -1,4 +1,8 @@
-use super::spotify_track_search_index::SpotifyTrackSearchRecord;
+use super::{
+  descriptor_similarity::compute_descriptor_similarities,
+  descriptor_similarity_repository::DescriptorSimilarityRepository,
+  spotify_track_search_index::SpotifyTrackSearchRecord,
+};
 use crate::{
   albums::album_read_model::AlbumReadModel,
   batch_job_executor,
@@ -15,9 +19,49 @@ use crate::{
 use anyhow::{anyhow, Result};
 use chrono::{Duration, TimeDelta};
 use std::{collections::HashMap, sync::Arc};
-use tokio::spawn;
+use tokio::{spawn, task::spawn_blocking};
 use tracing::{error, info, warn};
 
+/**
+ * Counts a bulk job's Spotify requests against the batch window, if one is configured. Counting
+ * is best effort, it isn't worth failing the job over.
+ */
+async fn record_spotify_batch_requests(app_context: &ApplicationContext, count: u32) {
+  if let Some(batch_window) = app_context.spotify_batch_window.as_ref() {
+    if let Err(e) = batch_window.record_requests(count).await {
+      error!(e = e.to_string(), "Failed to record spotify batch requests");
+    }
+  }
+}
+
+async fn enforce_spotify_batch_window(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  match app_context.spotify_batch_window.as_ref() {
+    Some(batch_window) => batch_window.enforce().await,
+    None => Ok(()),
+  }
+}
+
+async fn compute_catalog_descriptor_similarities(
+  _: Job,
+  app_context: Arc<ApplicationContext>,
+) -> Result<()> {
+  let co_occurrences = app_context
+    .album_interactor
+    .get_descriptor_co_occurrences()
+    .await?;
+  info!(
+    albums = co_occurrences.album_count,
+    descriptors = co_occurrences.descriptor_counts.len(),
+    pairs = co_occurrences.pair_counts.len(),
+    "Computing descriptor similarities"
+  );
+  let similarities =
+    spawn_blocking(move || compute_descriptor_similarities(&co_occurrences)).await?;
+  DescriptorSimilarityRepository::new(Arc::clone(&app_context.sqlite_connection))
+    .put_all(similarities)
+    .await
+}
+
 async fn index_spotify_tracks(jobs: Vec<Job>, app_context: Arc<ApplicationContext>) -> Result<()> {
   let track_records = jobs
     .into_iter()
@@ -39,9 +83,10 @@ async fn index_spotify_tracks(jobs: Vec<Job>, app_context: Arc<ApplicationContex
     .map(|r| r.spotify_id.clone())
     .collect::<Vec<_>>();
 
-  let embeddings = app_context
+  record_spotify_batch_requests(&app_context, 1).await;
+  let mut features = app_context
     .spotify_client
-    .get_tracks_feature_embeddings(track_ids)
+    .get_tracks_features(track_ids)
     .await
     .inspect_err(|e| {
       error!(e = e.to_string(), "Failed to get spotify track records");
@@ -63,14 +108,23 @@ async fn index_spotify_tracks(jobs: Vec<Job>, app_context: Arc<ApplicationContex
       }
     })?;
 
-  info!("Got embeddings for {} tracks", embeddings.len());
+  info!("Got features for {} tracks", features.len());
 
   let records = track_records
     .into_iter()
-    .zip(embeddings)
-    .map(|(record, (_, embedding))| SpotifyTrackSearchRecord {
-      embedding,
-      ..record
+    .filter_map(|record| {
+      let Some((embedding, audio_features)) = features.remove(&record.spotify_id) else {
+        warn!(
+          spotify_id = record.spotify_id.as_str(),
+          "No audio features found for track, skipping"
+        );
+        return None;
+      };
+      Some(SpotifyTrackSearchRecord {
+        embedding,
+        audio_features: Some(audio_features),
+        ..record
+      })
     })
     .collect::<Vec<_>>();
 
@@ -158,6 +212,7 @@ pub async fn fetch_spotify_tracks_by_album_ids(
     .map(|a| (a.spotify_id.clone().unwrap(), a))
     .collect::<HashMap<_, _>>();
 
+  record_spotify_batch_requests(&app_context, 1).await;
   let album_pages = app_context
     .spotify_client
     .get_album_pages(albums_by_spotify_id.keys().cloned().collect::<Vec<_>>())
@@ -221,6 +276,8 @@ pub async fn fetch_spotify_tracks_by_album_search(
 ) -> Result<()> {
   let album = job.payload::<AlbumReadModel>()?;
 
+  // A search, then the matched album's tracks
+  record_spotify_batch_requests(&app_context, 2).await;
   let spotify_album = app_context
     .spotify_client
     .find_album(&album)
@@ -289,5 +346,52 @@ pub async fn setup_recommendation_jobs(app_context: Arc<ApplicationContext>) ->
         .build()?,
     )
     .await;
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::ComputeDescriptorSimilarities)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(compute_catalog_descriptor_similarities))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::ComputeDescriptorSimilarities)
+        .interval(TimeDelta::try_weeks(1).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  if let Some(batch_window) = app_context.spotify_batch_window.as_ref() {
+    // Pause right away rather than letting a batch through when starting outside the window
+    batch_window.enforce().await?;
+
+    app_context
+      .scheduler
+      .register(
+        JobProcessorBuilder::default()
+          .name(JobName::EnforceSpotifyBatchWindow)
+          .app_context(Arc::clone(&app_context))
+          .executor(job_executor!(enforce_spotify_batch_window))
+          .build()?,
+      )
+      .await;
+
+    app_context
+      .scheduler
+      .put(
+        JobParametersBuilder::default()
+          .name(JobName::EnforceSpotifyBatchWindow)
+          .interval(TimeDelta::try_minutes(5).unwrap())
+          .build()?,
+      )
+      .await?;
+  }
   Ok(())
 }
@@ -0,0 +1,216 @@
+use super::{
+  cross_encoder_reranking::cross_encoder::album_passage,
+  seed::AlbumRecommendationSeedContext,
+  types::{AlbumAssessmentContribution, AlbumRecommendation},
+};
+use crate::files::file_metadata::file_name::FileName;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use data_encoding::BASE64URL_NOPAD;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const PROMPT_SEED_ALBUM_COUNT: usize = 10;
+const PROMPT_MATCHED_ITEM_COUNT: usize = 5;
+
+pub const SYSTEM_PROMPT: &str = "You explain music recommendations. Given the albums a listener \
+  loves and an album recommended to them, write one paragraph of at most four sentences on why \
+  the listener may enjoy the recommendation. Only use the details provided, and don't mention \
+  scores or weights.";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecommendationRationale {
+  pub seed_hash: String,
+  pub album_file_name: FileName,
+  pub rationale: String,
+  pub model: String,
+  pub created_at: NaiveDateTime,
+}
+
+impl RecommendationRationale {
+  pub fn id(seed_hash: &str, album_file_name: &FileName) -> String {
+    format!("{}:{}", seed_hash, album_file_name)
+  }
+}
+
+fn sorted_factors(seed_context: &AlbumRecommendationSeedContext) -> Vec<(String, u32)> {
+  let mut factors = seed_context
+    .factor_map
+    .iter()
+    .map(|(file_name, factor)| (file_name.to_string(), *factor))
+    .collect::<Vec<_>>();
+  factors.sort();
+  factors
+}
+
+/**
+ * Identifies the seed by its albums and factors, so equivalent seeds share cached rationales
+ * however they were built
+ */
+pub fn seed_hash(seed_context: &AlbumRecommendationSeedContext) -> Result<String> {
+  let negative = seed_context
+    .negative
+    .as_ref()
+    .map(|negative| (sorted_factors(&negative.context), negative.weight));
+  let key = serde_json::to_string(&(sorted_factors(seed_context), negative))?;
+  let hash = Sha256::digest(key.as_bytes());
+  Ok(BASE64URL_NOPAD.encode(&hash))
+}
+
+fn describe_contribution(contribution: &AlbumAssessmentContribution) -> Option<String> {
+  if contribution.matched_items.is_empty() {
+    return None;
+  }
+  Some(format!(
+    "Shared {}: {}",
+    contribution.factor.replace('_', " "),
+    contribution
+      .matched_items
+      .iter()
+      .take(PROMPT_MATCHED_ITEM_COUNT)
+      .cloned()
+      .collect::<Vec<_>>()
+      .join(", ")
+  ))
+}
+
+/**
+ * The seed's highest factor albums, the recommendation, and what it has in common with the seed
+ */
+pub fn user_prompt(
+  seed_context: &AlbumRecommendationSeedContext,
+  recommendation: &AlbumRecommendation,
+) -> String {
+  let mut seed_albums = seed_context
+    .albums
+    .iter()
+    .map(|album| {
+      (
+        seed_context.get_factor(&album.file_name).unwrap_or(1),
+        album,
+      )
+    })
+    .collect::<Vec<_>>();
+  seed_albums.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+  let mut lines = vec!["Albums the listener loves:".to_string()];
+  lines.extend(
+    seed_albums
+      .into_iter()
+      .take(PROMPT_SEED_ALBUM_COUNT)
+      .map(|(_, album)| format!("- {}", album_passage(album))),
+  );
+  lines.push(String::new());
+  lines.push(format!(
+    "Recommended album: {}",
+    album_passage(&recommendation.album)
+  ));
+  lines.extend(
+    recommendation
+      .assessment
+      .contributions
+      .iter()
+      .filter_map(describe_contribution),
+  );
+  lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    albums::album_read_model::AlbumReadModel,
+    recommendations::{seed::NegativeSeedContext, types::AlbumAssessment},
+  };
+  use std::collections::HashMap;
+
+  fn album(file_name: &str, name: &str) -> AlbumReadModel {
+    AlbumReadModel {
+      file_name: FileName::try_from(file_name.to_string()).unwrap(),
+      name: name.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_seed_hash() {
+    let loveless = album("release/album/my-bloody-valentine/loveless", "Loveless");
+    let souvlaki = album("release/album/slowdive/souvlaki", "Souvlaki");
+    let context = AlbumRecommendationSeedContext::new(
+      vec![loveless.clone(), souvlaki.clone()],
+      HashMap::from([
+        (loveless.file_name.clone(), 3),
+        (souvlaki.file_name.clone(), 1),
+      ]),
+    );
+    let reordered = AlbumRecommendationSeedContext::new(
+      vec![souvlaki.clone(), loveless.clone()],
+      HashMap::from([
+        (souvlaki.file_name.clone(), 1),
+        (loveless.file_name.clone(), 3),
+      ]),
+    );
+    assert_eq!(seed_hash(&context).unwrap(), seed_hash(&reordered).unwrap());
+
+    let with_negative = AlbumRecommendationSeedContext {
+      negative: Some(NegativeSeedContext {
+        context: Box::new(AlbumRecommendationSeedContext::new(
+          vec![souvlaki.clone()],
+          HashMap::from([(souvlaki.file_name.clone(), 1)]),
+        )),
+        weight: 0.5,
+      }),
+      ..context.clone()
+    };
+    assert_ne!(
+      seed_hash(&context).unwrap(),
+      seed_hash(&with_negative).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_user_prompt() {
+    let loveless = album("release/album/my-bloody-valentine/loveless", "Loveless");
+    let souvlaki = album("release/album/slowdive/souvlaki", "Souvlaki");
+    let context = AlbumRecommendationSeedContext::new(
+      vec![souvlaki.clone(), loveless.clone()],
+      HashMap::from([
+        (loveless.file_name.clone(), 3),
+        (souvlaki.file_name.clone(), 1),
+      ]),
+    );
+    let recommendation = AlbumRecommendation {
+      album: album("release/album/ride/nowhere", "Nowhere"),
+      assessment: AlbumAssessment {
+        score: 0.9,
+        metadata: None,
+        contributions: vec![
+          AlbumAssessmentContribution {
+            factor: "primary_genre".to_string(),
+            weight: 1.0,
+            value: 0.9,
+            contribution: 0.5,
+            matched_items: vec!["Shoegaze".to_string()],
+            novel_items: vec![],
+          },
+          AlbumAssessmentContribution {
+            factor: "rating".to_string(),
+            weight: 1.0,
+            value: 0.5,
+            contribution: 0.1,
+            matched_items: vec![],
+            novel_items: vec![],
+          },
+        ],
+      },
+      exploratory: false,
+    };
+    let prompt = user_prompt(&context, &recommendation);
+    let lines = prompt.lines().collect::<Vec<_>>();
+    assert!(lines[1].starts_with("- Loveless"));
+    assert!(lines[2].starts_with("- Souvlaki"));
+    assert!(lines[4].starts_with("Recommended album: Nowhere"));
+    assert_eq!(lines[5], "Shared primary genre: Shoegaze");
+    assert_eq!(lines.len(), 6);
+  }
+}
@@ -0,0 +1,79 @@
+use super::{
+  recommendation_rationale::{user_prompt, SYSTEM_PROMPT},
+  seed::AlbumRecommendationSeedContext,
+  types::AlbumRecommendation,
+};
+use crate::settings::RecommendationRationaleSettings;
+use anyhow::{anyhow, Result};
+use async_openai::{
+  config::OpenAIConfig,
+  types::{
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    CreateChatCompletionRequestArgs,
+  },
+  Client,
+};
+use chrono::Duration;
+use tracing::instrument;
+
+const MAX_TOKENS: u16 = 300;
+const DEFAULT_CACHE_TTL_DAYS: u32 = 30;
+
+/**
+ * Chat completions against any OpenAI-compatible endpoint, e.g. OpenAI itself, Ollama or vLLM
+ */
+pub struct RecommendationRationaleClient {
+  client: Client<OpenAIConfig>,
+  pub model: String,
+  pub cache_ttl: Duration,
+}
+
+impl RecommendationRationaleClient {
+  pub fn new(settings: &RecommendationRationaleSettings) -> Self {
+    let mut config = OpenAIConfig::new().with_api_base(&settings.base_url);
+    if let Some(api_key) = &settings.api_key {
+      config = config.with_api_key(api_key);
+    }
+    Self {
+      client: Client::with_config(config),
+      model: settings.model.clone(),
+      cache_ttl: Duration::days(
+        settings
+          .cache_ttl_days
+          .unwrap_or(DEFAULT_CACHE_TTL_DAYS)
+          .into(),
+      ),
+    }
+  }
+
+  #[instrument(name = "RecommendationRationaleClient::generate", skip_all)]
+  pub async fn generate(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+    recommendation: &AlbumRecommendation,
+  ) -> Result<String> {
+    let request = CreateChatCompletionRequestArgs::default()
+      .model(&self.model)
+      .max_tokens(MAX_TOKENS)
+      .messages([
+        ChatCompletionRequestSystemMessageArgs::default()
+          .content(SYSTEM_PROMPT)
+          .build()?
+          .into(),
+        ChatCompletionRequestUserMessageArgs::default()
+          .content(user_prompt(seed_context, recommendation))
+          .build()?
+          .into(),
+      ])
+      .build()?;
+    let response = self.client.chat().create(request).await?;
+    response
+      .choices
+      .into_iter()
+      .next()
+      .and_then(|choice| choice.message.content)
+      .map(|content| content.trim().to_string())
+      .filter(|content| !content.is_empty())
+      .ok_or_else(|| anyhow!("Empty rationale returned by {}", self.model))
+  }
+}
@@ -0,0 +1,46 @@
+use super::recommendation_rationale::RecommendationRationale;
+use crate::{files::file_metadata::file_name::FileName, helpers::document_store::DocumentStore};
+use anyhow::Result;
+use chrono::Duration;
+use std::sync::Arc;
+
+pub struct RecommendationRationaleRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "recommendation_rationale";
+
+impl RecommendationRationaleRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  pub async fn put(&self, rationale: RecommendationRationale, ttl: Duration) -> Result<()> {
+    self
+      .doc_store
+      .put(
+        COLLECTION,
+        &RecommendationRationale::id(&rationale.seed_hash, &rationale.album_file_name),
+        rationale,
+        Some(ttl),
+      )
+      .await
+  }
+
+  pub async fn find(
+    &self,
+    seed_hash: &str,
+    album_file_name: &FileName,
+  ) -> Result<Option<RecommendationRationale>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<RecommendationRationale>(
+          COLLECTION,
+          &RecommendationRationale::id(seed_hash, album_file_name),
+        )
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+}
@@ -1,25 +1,57 @@
 use super::{
+  collaborative_filtering::collaborative_filtering_interactor::{
+    CollaborativeFilteringAlbumAssessmentSettings,
+    CollaborativeFilteringAlbumAssessmentSettingsBuilder,
+  },
+  cross_encoder_reranking::cross_encoder_reranking_interactor::CrossEncoderRerankedAlbumAssessmentSettings,
+  discogs_availability::AlbumRecommendationSort,
   embedding_similarity::embedding_similarity_interactor::EmbeddingSimilarityAlbumAssessmentSettings,
-  quantile_ranking::quantile_rank_interactor::{
-    QuantileRankAlbumAssessmentSettings, QuantileRankAlbumAssessmentSettingsBuilder,
+  global_exclusion::GlobalExclusion,
+  playlist_energy_curve::PlaylistEnergyCurve,
+  profile_compatibility::ProfileCompatibility,
+  quantile_ranking::{
+    personnel_radar::{PersonnelRadarRoleWeights, PersonnelRadarRoleWeightsBuilder},
+    quantile_rank_interactor::{
+      QuantileRankAlbumAssessmentSettings, QuantileRankAlbumAssessmentSettingsBuilder,
+    },
   },
+  recommendation_curation::{CuratedAlbumRecommendation, CurationMarker, RecommendationCuration},
+  recommendation_digest::{RecommendationDigest, RecommendationDigestItem},
   recommendation_interactor::{AlbumAssessmentSettings, RecommendationInteractor},
+  recommendation_rationale::RecommendationRationale,
   reranked_embedding_similarity::reranked_embedding_similarity_interactor::RerankedEmbeddingSimilarityAlbumAssessmentSettings,
-  seed::AlbumRecommendationSeed,
-  spotify_track_search_index::{SpotifyTrackQuery, SpotifyTrackSearchResult},
-  types::{AlbumRecommendation, AlbumRecommendationSettings},
+  seed::{AlbumRecommendationSeed, WeightedAlbumRecommendationSeed, DEFAULT_NEGATIVE_SEED_WEIGHT},
+  spotify_track_search_index::{
+    SpotifyTrackAudioFeature, SpotifyTrackAudioFeatureRange, SpotifyTrackQuery,
+    SpotifyTrackSearchResult, SpotifyTrackSort,
+  },
+  track_sequencing::{RankedTrack, TrackSequencingSettings},
+  types::{
+    AlbumAssessment, AlbumAssessmentContribution, AlbumRecommendation, AlbumRecommendationSettings,
+  },
+  year_in_review::{YearInReview, YearInReviewAlbum},
 };
 use crate::{
-  context::ApplicationContext, files::file_metadata::file_name::FileName,
-  profile::profile::ProfileId, proto, spotify::spotify_client::SpotifyTrackReference,
+  context::ApplicationContext,
+  files::file_metadata::file_name::FileName,
+  music_service::music_service_client::MusicService,
+  profile::profile::ProfileId,
+  proto,
+  spotify::spotify_client::{SpotifyPlaylistSyncMode, SpotifyTrackReference},
+  tenant::tenant_id::{request_tenant_id, TenantId},
 };
 use anyhow::{anyhow, Error, Result};
 use num_traits::Num;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+  collections::HashMap,
+  sync::Arc,
+  time::{Duration, Instant},
+};
 use tonic::{async_trait, Request, Response, Status};
 use tracing::error;
 
 pub struct RecommendationService {
+  app_context: Arc<ApplicationContext>,
   recommendation_interactor: RecommendationInteractor,
 }
 
@@ -27,6 +59,7 @@ impl RecommendationService {
   pub fn new(app_context: Arc<ApplicationContext>) -> Self {
     Self {
       recommendation_interactor: RecommendationInteractor::new(Arc::clone(&app_context)),
+      app_context,
     }
   }
 }
@@ -39,6 +72,34 @@ fn default_if_zero<T: Num>(value: T, default: T) -> T {
   }
 }
 
+impl TryFrom<proto::PersonnelRadarRoleWeights> for PersonnelRadarRoleWeights {
+  type Error = Error;
+
+  fn try_from(value: proto::PersonnelRadarRoleWeights) -> Result<Self, Self::Error> {
+    let mut builder = PersonnelRadarRoleWeightsBuilder::default();
+    if let Some(producer_weight) = value.producer_weight {
+      builder.producer_weight(producer_weight);
+    }
+    if let Some(engineer_weight) = value.engineer_weight {
+      builder.engineer_weight(engineer_weight);
+    }
+    if let Some(featured_artist_weight) = value.featured_artist_weight {
+      builder.featured_artist_weight(featured_artist_weight);
+    }
+    Ok(builder.build()?)
+  }
+}
+
+impl From<PersonnelRadarRoleWeights> for proto::PersonnelRadarRoleWeights {
+  fn from(value: PersonnelRadarRoleWeights) -> Self {
+    Self {
+      producer_weight: Some(value.producer_weight),
+      engineer_weight: Some(value.engineer_weight),
+      featured_artist_weight: Some(value.featured_artist_weight),
+    }
+  }
+}
+
 impl TryFrom<proto::QuantileRankAlbumAssessmentSettings> for QuantileRankAlbumAssessmentSettings {
   type Error = Error;
 
@@ -68,6 +129,29 @@ impl TryFrom<proto::QuantileRankAlbumAssessmentSettings> for QuantileRankAlbumAs
     if let Some(credit_tag_weight) = value.credit_tag_weight {
       builder.credit_tag_weight(credit_tag_weight);
     }
+    if let Some(personnel_radar_weight) = value.personnel_radar_weight {
+      builder.personnel_radar_weight(personnel_radar_weight);
+    }
+    if let Some(listening_history_weight) = value.listening_history_weight {
+      builder.listening_history_weight(listening_history_weight);
+    }
+    if let Some(genre_hierarchy_credit) = value.genre_hierarchy_credit {
+      if !(0.0..=1.0).contains(&genre_hierarchy_credit) {
+        return Err(anyhow!("Genre hierarchy credit must be between 0 and 1"));
+      }
+      builder.genre_hierarchy_credit(genre_hierarchy_credit as f64);
+    }
+    if let Some(descriptor_similarity_credit) = value.descriptor_similarity_credit {
+      if !(0.0..=1.0).contains(&descriptor_similarity_credit) {
+        return Err(anyhow!(
+          "Descriptor similarity credit must be between 0 and 1"
+        ));
+      }
+      builder.descriptor_similarity_credit(descriptor_similarity_credit as f64);
+    }
+    if let Some(role_weights) = value.personnel_radar_role_weights {
+      builder.personnel_radar_role_weights(PersonnelRadarRoleWeights::try_from(role_weights)?);
+    }
     Ok(builder.build()?)
   }
 }
@@ -107,6 +191,42 @@ impl TryFrom<proto::RerankedEmbeddingSimilarityAlbumAssessmentSettings>
   }
 }
 
+impl TryFrom<proto::CollaborativeFilteringAlbumAssessmentSettings>
+  for CollaborativeFilteringAlbumAssessmentSettings
+{
+  type Error = Error;
+
+  fn try_from(
+    value: proto::CollaborativeFilteringAlbumAssessmentSettings,
+  ) -> Result<Self, Self::Error> {
+    let mut builder = CollaborativeFilteringAlbumAssessmentSettingsBuilder::default();
+    if let Some(min_shared_profiles) = value.min_shared_profiles {
+      builder.min_shared_profiles(min_shared_profiles);
+    }
+    Ok(builder.build()?)
+  }
+}
+
+impl TryFrom<proto::CrossEncoderRerankedAlbumAssessmentSettings>
+  for CrossEncoderRerankedAlbumAssessmentSettings
+{
+  type Error = Error;
+
+  fn try_from(
+    value: proto::CrossEncoderRerankedAlbumAssessmentSettings,
+  ) -> Result<Self, Self::Error> {
+    let embedding_similarity_settings = EmbeddingSimilarityAlbumAssessmentSettings::from(
+      value
+        .embedding_similarity_settings
+        .ok_or_else(|| anyhow!("Embedding similarity settings not provided"))?,
+    );
+    Ok(Self {
+      embedding_similarity_settings,
+      min_embedding_candidate_count: value.min_embedding_candidate_count,
+    })
+  }
+}
+
 impl TryFrom<proto::AlbumAssessmentSettings> for AlbumAssessmentSettings {
   type Error = Error;
 
@@ -123,6 +243,16 @@ impl TryFrom<proto::AlbumAssessmentSettings> for AlbumAssessmentSettings {
       )) => Ok(Self::RerankedEmbeddingSimilarity(
         RerankedEmbeddingSimilarityAlbumAssessmentSettings::try_from(settings)?,
       )),
+      Some(proto::album_assessment_settings::Settings::CollaborativeFilteringSettings(
+        settings,
+      )) => Ok(Self::CollaborativeFiltering(
+        CollaborativeFilteringAlbumAssessmentSettings::try_from(settings)?,
+      )),
+      Some(proto::album_assessment_settings::Settings::CrossEncoderRerankedSettings(settings)) => {
+        Ok(Self::CrossEncoderReranked(
+          CrossEncoderRerankedAlbumAssessmentSettings::try_from(settings)?,
+        ))
+      }
 
       None => Err(anyhow::anyhow!("Settings not provided")),
     }
@@ -150,18 +280,84 @@ impl TryFrom<proto::AlbumRecommendationSettings> for AlbumRecommendationSettings
       min_release_year: value.min_release_year,
       max_release_year: value.max_release_year,
       exclude_known_artists: value.exclude_known_artists,
+      max_albums_per_artist: value.max_albums_per_artist.filter(|max| *max > 0),
+      max_albums_per_primary_genre: value.max_albums_per_primary_genre.filter(|max| *max > 0),
+      max_albums_per_decade: value.max_albums_per_decade.filter(|max| *max > 0),
+      deadline: value
+        .time_budget_ms
+        .filter(|budget| *budget > 0)
+        .map(|budget| Instant::now() + Duration::from_millis(budget as u64)),
+      exclude_file_names: parse_file_names(value.exclude_file_names)?,
+      include_globally_excluded: value.include_globally_excluded.unwrap_or(false),
+      exploration_slots: value.exploration_slots.filter(|slots| *slots > 0),
+      exploration_temperature: value
+        .exploration_temperature
+        .filter(|temperature| *temperature > 0.0),
+      exploration_min_rating: value.exploration_min_rating,
+      max_discogs_lowest_price: value.max_discogs_lowest_price,
+      sort: value.sort().into(),
     })
   }
 }
 
+impl From<proto::AlbumRecommendationSort> for AlbumRecommendationSort {
+  fn from(value: proto::AlbumRecommendationSort) -> Self {
+    match value {
+      proto::AlbumRecommendationSort::AlbumRecommendationSortScore => {
+        AlbumRecommendationSort::Score
+      }
+      proto::AlbumRecommendationSort::AlbumRecommendationSortDiscogsAvailability => {
+        AlbumRecommendationSort::DiscogsAvailability
+      }
+      proto::AlbumRecommendationSort::AlbumRecommendationSortDiscogsLowestPrice => {
+        AlbumRecommendationSort::DiscogsLowestPrice
+      }
+    }
+  }
+}
+
+impl From<AlbumAssessmentContribution> for proto::AlbumAssessmentContribution {
+  fn from(value: AlbumAssessmentContribution) -> Self {
+    Self {
+      factor: value.factor,
+      weight: value.weight,
+      value: value.value,
+      contribution: value.contribution,
+      matched_items: value.matched_items,
+      novel_items: value.novel_items,
+    }
+  }
+}
+
+impl From<AlbumAssessment> for proto::AlbumAssessment {
+  fn from(value: AlbumAssessment) -> Self {
+    Self {
+      score: value.score,
+      metadata: value.metadata.unwrap_or_default(),
+      contributions: value.contributions.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl From<ProfileCompatibility> for proto::AssessProfileCompatibilityReply {
+  fn from(value: ProfileCompatibility) -> Self {
+    Self {
+      score: value.score,
+      assessed_album_count: value.assessed_album_count,
+      shared_album_count: value.shared_album_count,
+      shared_primary_genres: value.shared_primary_genres,
+      shared_descriptors: value.shared_descriptors,
+      bridge_albums: value.bridge_albums.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
 impl From<AlbumRecommendation> for proto::AlbumRecommendation {
   fn from(value: AlbumRecommendation) -> Self {
     Self {
       album: Some(value.album.into()),
-      assessment: Some(proto::AlbumAssessment {
-        score: value.assessment.score,
-        metadata: value.assessment.metadata.unwrap_or_default(),
-      }),
+      assessment: Some(value.assessment.into()),
+      exploratory: value.exploratory,
     }
   }
 }
@@ -177,6 +373,105 @@ impl From<QuantileRankAlbumAssessmentSettings> for proto::QuantileRankAlbumAsses
       rating_count_weight: Some(value.rating_count_weight),
       descriptor_count_weight: Some(value.descriptor_count_weight),
       credit_tag_weight: Some(value.credit_tag_weight),
+      personnel_radar_weight: Some(value.personnel_radar_weight),
+      personnel_radar_role_weights: Some(value.personnel_radar_role_weights.into()),
+      listening_history_weight: Some(value.listening_history_weight),
+      genre_hierarchy_credit: Some(value.genre_hierarchy_credit as f32),
+      descriptor_similarity_credit: Some(value.descriptor_similarity_credit as f32),
+    }
+  }
+}
+
+impl From<proto::SpotifyTrackAudioFeature> for SpotifyTrackAudioFeature {
+  fn from(value: proto::SpotifyTrackAudioFeature) -> Self {
+    match value {
+      proto::SpotifyTrackAudioFeature::SpotifyTrackEnergy => SpotifyTrackAudioFeature::Energy,
+      proto::SpotifyTrackAudioFeature::SpotifyTrackDanceability => {
+        SpotifyTrackAudioFeature::Danceability
+      }
+      proto::SpotifyTrackAudioFeature::SpotifyTrackTempo => SpotifyTrackAudioFeature::Tempo,
+      proto::SpotifyTrackAudioFeature::SpotifyTrackValence => SpotifyTrackAudioFeature::Valence,
+    }
+  }
+}
+
+impl From<proto::SpotifyTrackAudioFeatureRange> for SpotifyTrackAudioFeatureRange {
+  fn from(value: proto::SpotifyTrackAudioFeatureRange) -> Self {
+    Self {
+      feature: value.feature().into(),
+      min: value.min,
+      max: value.max,
+    }
+  }
+}
+
+impl From<proto::SpotifyTrackSort> for SpotifyTrackSort {
+  fn from(value: proto::SpotifyTrackSort) -> Self {
+    Self {
+      feature: value.feature().into(),
+      descending: value.descending,
+    }
+  }
+}
+
+impl From<proto::PlaylistEnergyCurve> for PlaylistEnergyCurve {
+  fn from(value: proto::PlaylistEnergyCurve) -> Self {
+    match value {
+      proto::PlaylistEnergyCurve::PlaylistEnergyUnshaped => PlaylistEnergyCurve::Unshaped,
+      proto::PlaylistEnergyCurve::PlaylistEnergyRising => PlaylistEnergyCurve::Rising,
+      proto::PlaylistEnergyCurve::PlaylistEnergyFalling => PlaylistEnergyCurve::Falling,
+      proto::PlaylistEnergyCurve::PlaylistEnergyArc => PlaylistEnergyCurve::Arc,
+    }
+  }
+}
+
+impl TryFrom<proto::TrackSequencingSettings> for TrackSequencingSettings {
+  type Error = Error;
+
+  fn try_from(value: proto::TrackSequencingSettings) -> Result<Self, Self::Error> {
+    let defaults = TrackSequencingSettings::default();
+    let smoothness = value.smoothness.unwrap_or(defaults.smoothness);
+    if !(0.0..=1.0).contains(&smoothness) {
+      return Err(anyhow!("Smoothness must be between 0 and 1"));
+    }
+    let max_tracks_per_album = value
+      .max_tracks_per_album
+      .map_or(defaults.max_tracks_per_album, |max| max as usize);
+    if max_tracks_per_album == 0 {
+      return Err(anyhow!("At least one track per album must be allowed"));
+    }
+    Ok(Self {
+      energy_curve: value.energy_curve().into(),
+      target_duration_ms: value
+        .target_duration_minutes
+        .map(|minutes| minutes as u64 * 60 * 1000)
+        .or(defaults.target_duration_ms),
+      max_tracks_per_album,
+      smoothness,
+    })
+  }
+}
+
+impl From<RankedTrack> for proto::TrackRecommendation {
+  fn from(val: RankedTrack) -> Self {
+    let audio_features = val.track.audio_features.clone();
+    proto::TrackRecommendation {
+      album_file_name: val.track.album_file_name.to_string(),
+      score: val.score,
+      energy: audio_features.as_ref().map(|features| features.energy),
+      tempo: audio_features.as_ref().map(|features| features.tempo),
+      track: Some(SpotifyTrackReference::from(val.track).into()),
+    }
+  }
+}
+
+impl From<proto::SpotifyPlaylistSyncMode> for SpotifyPlaylistSyncMode {
+  fn from(value: proto::SpotifyPlaylistSyncMode) -> Self {
+    match value {
+      proto::SpotifyPlaylistSyncMode::SpotifyPlaylistSyncReplace => {
+        SpotifyPlaylistSyncMode::Replace
+      }
+      proto::SpotifyPlaylistSyncMode::SpotifyPlaylistSyncAppend => SpotifyPlaylistSyncMode::Append,
     }
   }
 }
@@ -190,6 +485,12 @@ impl From<proto::SpotifyTrackIndexQuery> for SpotifyTrackQuery {
         .into_iter()
         .filter_map(|name| FileName::try_from(name).ok())
         .collect(),
+      audio_feature_ranges: value
+        .audio_feature_ranges
+        .into_iter()
+        .map(Into::into)
+        .collect(),
+      sort: value.sort.map(Into::into),
     }
   }
 }
@@ -208,11 +509,125 @@ impl From<SpotifyTrackSearchResult> for proto::SearchSpotifyTrackIndexReply {
   }
 }
 
+impl From<RecommendationCuration> for proto::RecommendationCuration {
+  fn from(val: RecommendationCuration) -> Self {
+    proto::RecommendationCuration {
+      profile_id: val.profile_id.local_id(),
+      pinned: val.pinned.into_iter().map(|f| f.to_string()).collect(),
+      excluded: val.excluded.into_iter().map(|f| f.to_string()).collect(),
+      updated_at: val.updated_at.to_string(),
+    }
+  }
+}
+
+impl From<RecommendationDigestItem> for proto::RecommendationDigestItem {
+  fn from(val: RecommendationDigestItem) -> Self {
+    proto::RecommendationDigestItem {
+      file_name: val.file_name.to_string(),
+      name: val.name,
+      artists: val.artists,
+      score: val.score,
+      marker: proto::CurationMarker::from(val.marker).into(),
+    }
+  }
+}
+
+impl From<RecommendationDigest> for proto::RecommendationDigest {
+  fn from(val: RecommendationDigest) -> Self {
+    proto::RecommendationDigest {
+      id: val.id,
+      profile_id: val.profile_id.local_id(),
+      created_at: val.created_at.to_string(),
+      items: val.items.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl From<YearInReviewAlbum> for proto::YearInReviewAlbum {
+  fn from(val: YearInReviewAlbum) -> Self {
+    proto::YearInReviewAlbum {
+      file_name: val.file_name.to_string(),
+      name: val.name,
+      artists: val.artists,
+      release_date: val.release_date.map(|date| date.to_string()),
+    }
+  }
+}
+
+impl From<YearInReview> for proto::YearInReview {
+  fn from(val: YearInReview) -> Self {
+    proto::YearInReview {
+      profile_id: val.profile_id.local_id(),
+      year: val.year,
+      created_at: val.created_at.to_string(),
+      album_count: val.album_count,
+      primary_genres: val.primary_genres.into_iter().map(Into::into).collect(),
+      descriptors: val.descriptors.into_iter().map(Into::into).collect(),
+      oldest_release: val.oldest_release.map(Into::into),
+      newest_release: val.newest_release.map(Into::into),
+      recommendations: val
+        .recommendations
+        .into_iter()
+        .map(|recommendation| proto::YearInReviewRecommendation {
+          album: Some(recommendation.album.into()),
+          score: recommendation.score,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl From<RecommendationRationale> for proto::RecommendationRationale {
+  fn from(val: RecommendationRationale) -> Self {
+    proto::RecommendationRationale {
+      file_name: val.album_file_name.to_string(),
+      rationale: val.rationale,
+      model: val.model,
+      created_at: val.created_at.to_string(),
+    }
+  }
+}
+
+impl From<CurationMarker> for proto::CurationMarker {
+  fn from(val: CurationMarker) -> Self {
+    match val {
+      CurationMarker::Pinned => proto::CurationMarker::Pinned,
+      CurationMarker::Organic => proto::CurationMarker::Organic,
+      CurationMarker::Excluded => proto::CurationMarker::Excluded,
+    }
+  }
+}
+
+impl From<CuratedAlbumRecommendation> for proto::CuratedAlbumRecommendation {
+  fn from(val: CuratedAlbumRecommendation) -> Self {
+    proto::CuratedAlbumRecommendation {
+      recommendation: Some(val.recommendation.into()),
+      marker: proto::CurationMarker::from(val.marker).into(),
+    }
+  }
+}
+
+fn parse_file_names(file_names: Vec<String>) -> Result<Vec<FileName>> {
+  file_names.into_iter().map(FileName::try_from).collect()
+}
+
+impl From<GlobalExclusion> for proto::GlobalExclusion {
+  fn from(val: GlobalExclusion) -> Self {
+    let mut file_names = val
+      .file_names
+      .into_iter()
+      .map(|file_name| file_name.to_string())
+      .collect::<Vec<_>>();
+    file_names.sort();
+    proto::GlobalExclusion { file_names }
+  }
+}
+
 impl TryFrom<proto::AlbumRecommendationSeed> for AlbumRecommendationSeed {
   type Error = anyhow::Error;
 
   fn try_from(value: proto::AlbumRecommendationSeed) -> Result<Self> {
-    match value.value {
+    let seed = match value.value {
       Some(proto::album_recommendation_seed::Value::ProfileId(profile_id)) => {
         Ok(Self::Profile(ProfileId::try_from(profile_id)?))
       }
@@ -223,7 +638,39 @@ impl TryFrom<proto::AlbumRecommendationSeed> for AlbumRecommendationSeed {
           .map(|(name, factor)| Ok((FileName::try_from(name)?, factor)))
           .collect::<Result<HashMap<FileName, u32>>>()?,
       )),
+      Some(proto::album_recommendation_seed::Value::CollectionId(id)) => Ok(Self::Collection {
+        id,
+        tenant_id: TenantId::default(),
+      }),
+      Some(proto::album_recommendation_seed::Value::Blend(blend)) => Ok(Self::Blend(
+        blend
+          .seeds
+          .into_iter()
+          .map(|weighted| {
+            WeightedAlbumRecommendationSeed::new(
+              AlbumRecommendationSeed::try_from(
+                *weighted
+                  .seed
+                  .ok_or_else(|| anyhow!("Blended seed entry is missing a seed"))?,
+              )?,
+              weighted.weight.unwrap_or(1.0),
+            )
+          })
+          .collect::<Result<Vec<_>>>()?,
+      )),
       None => Err(anyhow!("Seed not provided")),
+    }?;
+    match value.negative {
+      Some(negative) => Ok(Self::WithNegative {
+        seed: Box::new(seed),
+        negative: Box::new(AlbumRecommendationSeed::try_from(
+          *negative
+            .seed
+            .ok_or_else(|| anyhow!("Negative seed is missing a seed"))?,
+        )?),
+        weight: negative.weight.unwrap_or(DEFAULT_NEGATIVE_SEED_WEIGHT),
+      }),
+      None => Ok(seed),
     }
   }
 }
@@ -234,15 +681,18 @@ impl proto::RecommendationService for RecommendationService {
     &self,
     request: Request<proto::AssessAlbumRequest>,
   ) -> Result<Response<proto::AssessAlbumReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
     let seed_request = request.seed.ok_or_else(|| {
       error!("Seed not provided");
       Status::invalid_argument("Seed not provided")
     })?;
-    let seed = AlbumRecommendationSeed::try_from(seed_request).map_err(|e| {
-      error!(error = e.to_string(), "Invalid seed");
-      Status::invalid_argument(e.to_string())
-    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
     let file_name = FileName::try_from(request.file_name).map_err(|e| {
       error!(error = e.to_string(), "Invalid album file name");
       Status::invalid_argument(e.to_string())
@@ -263,10 +713,7 @@ impl proto::RecommendationService for RecommendationService {
         Status::internal(e.to_string())
       })?;
     Ok(Response::new(proto::AssessAlbumReply {
-      assessment: Some(proto::AlbumAssessment {
-        score: assessment.score,
-        metadata: assessment.metadata.unwrap_or(HashMap::new()),
-      }),
+      assessment: Some(assessment.into()),
     }))
   }
 
@@ -274,15 +721,18 @@ impl proto::RecommendationService for RecommendationService {
     &self,
     request: Request<proto::RecommendAlbumsRequest>,
   ) -> Result<Response<proto::RecommendAlbumsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
     let seed_request = request.seed.ok_or_else(|| {
       error!("Seed not provided");
       Status::invalid_argument("Seed not provided")
     })?;
-    let seed = AlbumRecommendationSeed::try_from(seed_request).map_err(|e| {
-      error!(error = e.to_string(), "Invalid seed");
-      Status::invalid_argument(e.to_string())
-    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
     let assessment_settings = match request.assessment_settings {
       Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
         error!(error = e.to_string(), "Invalid settings");
@@ -299,14 +749,25 @@ impl proto::RecommendationService for RecommendationService {
     };
     let recommendations = self
       .recommendation_interactor
-      .recommend_albums(seed, assessment_settings, recommendation_settings)
+      .recommend_albums(
+        &tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+      )
       .await
       .map_err(|e| {
         error!(error = e.to_string(), "Failed to recommend albums");
         Status::internal(e.to_string())
       })?;
     Ok(Response::new(proto::RecommendAlbumsReply {
-      recommendations: recommendations.into_iter().map(Into::into).collect(),
+      is_complete: recommendations.is_complete(),
+      completeness: recommendations.completeness,
+      recommendations: recommendations
+        .recommendations
+        .into_iter()
+        .map(Into::into)
+        .collect(),
     }))
   }
 
@@ -325,15 +786,19 @@ impl proto::RecommendationService for RecommendationService {
     &self,
     request: Request<proto::DraftSpotifyPlaylistRequest>,
   ) -> Result<Response<proto::DraftSpotifyPlaylistReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
+    let energy_curve = PlaylistEnergyCurve::from(request.energy_curve());
     let seed_request = request.seed.ok_or_else(|| {
       error!("Seed not provided");
       Status::invalid_argument("Seed not provided")
     })?;
-    let seed = AlbumRecommendationSeed::try_from(seed_request).map_err(|e| {
-      error!(error = e.to_string(), "Invalid seed");
-      Status::invalid_argument(e.to_string())
-    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
     let assessment_settings = match request.assessment_settings {
       Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
         error!(error = e.to_string(), "Invalid settings");
@@ -350,7 +815,13 @@ impl proto::RecommendationService for RecommendationService {
     };
     let tracks = self
       .recommendation_interactor
-      .draft_spotify_playlist(seed, assessment_settings, recommendation_settings)
+      .draft_spotify_playlist(
+        &tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+      )
       .await
       .map_err(|e| {
         error!(error = e.to_string(), "Failed to draft Spotify playlist");
@@ -362,19 +833,83 @@ impl proto::RecommendationService for RecommendationService {
     }))
   }
 
+  async fn recommend_tracks(
+    &self,
+    request: Request<proto::RecommendTracksRequest>,
+  ) -> Result<Response<proto::RecommendTracksReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let seed_request = request.seed.ok_or_else(|| {
+      error!("Seed not provided");
+      Status::invalid_argument("Seed not provided")
+    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let assessment_settings = match request.assessment_settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let recommendation_settings = match request.recommendation_settings {
+      Some(settings) => AlbumRecommendationSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumRecommendationSettings::default(),
+    };
+    let sequencing_settings = match request.sequencing_settings {
+      Some(settings) => TrackSequencingSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid sequencing settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => TrackSequencingSettings::default(),
+    };
+    let tracks = self
+      .recommendation_interactor
+      .recommend_tracks(
+        &tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        sequencing_settings,
+      )
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to recommend tracks");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::RecommendTracksReply {
+      duration_ms: tracks
+        .iter()
+        .map(|ranked| ranked.track.duration_ms.unwrap_or(0))
+        .sum(),
+      tracks: tracks.into_iter().map(Into::into).collect(),
+    }))
+  }
+
   async fn create_spotify_playlist(
     &self,
     request: Request<proto::CreateSpotifyPlaylistRequest>,
   ) -> Result<Response<proto::CreateSpotifyPlaylistReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
     let request = request.into_inner();
+    let energy_curve = PlaylistEnergyCurve::from(request.energy_curve());
     let seed_request = request.seed.ok_or_else(|| {
       error!("Seed not provided");
       Status::invalid_argument("Seed not provided")
     })?;
-    let seed = AlbumRecommendationSeed::try_from(seed_request).map_err(|e| {
-      error!(error = e.to_string(), "Invalid seed");
-      Status::invalid_argument(e.to_string())
-    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
     let assessment_settings = match request.assessment_settings {
       Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
         error!(error = e.to_string(), "Invalid settings");
@@ -394,9 +929,11 @@ impl proto::RecommendationService for RecommendationService {
     let (playlist_id, tracks) = self
       .recommendation_interactor
       .create_spotify_playlist(
+        &tenant_id,
         seed,
         assessment_settings,
         recommendation_settings,
+        energy_curve,
         name,
         description,
       )
@@ -412,6 +949,119 @@ impl proto::RecommendationService for RecommendationService {
     }))
   }
 
+  async fn export_playlist(
+    &self,
+    request: Request<proto::ExportPlaylistRequest>,
+  ) -> Result<Response<proto::ExportPlaylistReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let energy_curve = PlaylistEnergyCurve::from(request.energy_curve());
+    let client = self
+      .app_context
+      .music_service_client(MusicService::from(request.service()), &tenant_id)
+      .map_err(|e| Status::failed_precondition(e.to_string()))?;
+    let seed_request = request.seed.ok_or_else(|| {
+      error!("Seed not provided");
+      Status::invalid_argument("Seed not provided")
+    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let assessment_settings = match request.assessment_settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let recommendation_settings = match request.recommendation_settings {
+      Some(settings) => AlbumRecommendationSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumRecommendationSettings::default(),
+    };
+    let (playlist_id, tracks) = self
+      .recommendation_interactor
+      .export_playlist(
+        &tenant_id,
+        client.as_ref(),
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+        request.name,
+        request.description,
+      )
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to export playlist");
+        Status::internal(e.to_string())
+      })?;
+
+    Ok(Response::new(proto::ExportPlaylistReply {
+      playlist_id,
+      tracks: tracks.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn sync_spotify_playlist(
+    &self,
+    request: Request<proto::SyncSpotifyPlaylistRequest>,
+  ) -> Result<Response<proto::SyncSpotifyPlaylistReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let energy_curve = PlaylistEnergyCurve::from(request.energy_curve());
+    let mode = SpotifyPlaylistSyncMode::from(request.mode());
+    let seed_request = request.seed.ok_or_else(|| {
+      error!("Seed not provided");
+      Status::invalid_argument("Seed not provided")
+    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let assessment_settings = match request.assessment_settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let recommendation_settings = match request.recommendation_settings {
+      Some(settings) => AlbumRecommendationSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumRecommendationSettings::default(),
+    };
+    let tracks = self
+      .recommendation_interactor
+      .sync_spotify_playlist(
+        &tenant_id,
+        seed,
+        assessment_settings,
+        recommendation_settings,
+        energy_curve,
+        &request.playlist_id,
+        mode,
+      )
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to sync Spotify playlist");
+        Status::internal(e.to_string())
+      })?;
+
+    Ok(Response::new(proto::SyncSpotifyPlaylistReply {
+      tracks: tracks.into_iter().map(Into::into).collect(),
+    }))
+  }
+
   async fn search_spotify_track_index(
     &self,
     request: Request<proto::SearchSpotifyTrackIndexRequest>,
@@ -433,4 +1083,370 @@ impl proto::RecommendationService for RecommendationService {
       })?;
     Ok(Response::new(result.into()))
   }
+
+  async fn get_recommendation_curation(
+    &self,
+    request: Request<proto::GetRecommendationCurationRequest>,
+  ) -> Result<Response<proto::RecommendationCurationReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|e| {
+        error!(error = e.to_string(), "Invalid profile id");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let curation = self
+      .recommendation_interactor
+      .get_recommendation_curation(&profile_id)
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to get recommendation curation"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::RecommendationCurationReply {
+      curation: Some(curation.into()),
+    }))
+  }
+
+  async fn assess_profile_compatibility(
+    &self,
+    request: Request<proto::AssessProfileCompatibilityRequest>,
+  ) -> Result<Response<proto::AssessProfileCompatibilityReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let parse_profile_id = |id: String| {
+      ProfileId::scoped(&tenant_id, id).map_err(|e| {
+        error!(error = e.to_string(), "Invalid profile id");
+        Status::invalid_argument(e.to_string())
+      })
+    };
+    let seed_profile_id = parse_profile_id(request.seed_profile_id)?;
+    let candidate_profile_id = parse_profile_id(request.candidate_profile_id)?;
+    let settings: AlbumAssessmentSettings = match request.settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let compatibility = self
+      .recommendation_interactor
+      .assess_profile_compatibility(
+        &seed_profile_id,
+        &candidate_profile_id,
+        settings,
+        request.bridge_album_count.unwrap_or(10) as usize,
+      )
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to assess profile compatibility"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(compatibility.into()))
+  }
+
+  async fn create_year_in_review(
+    &self,
+    request: Request<proto::CreateYearInReviewRequest>,
+  ) -> Result<Response<proto::YearInReviewReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|e| {
+      error!(error = e.to_string(), "Invalid profile id");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let report = self
+      .recommendation_interactor
+      .create_year_in_review(
+        &profile_id,
+        request.year,
+        request.recommendation_count.unwrap_or(
+          self
+            .app_context
+            .settings
+            .year_in_review
+            .recommendation_count,
+        ),
+      )
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to create year in review");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::YearInReviewReply {
+      report: Some(report.into()),
+    }))
+  }
+
+  async fn get_year_in_review(
+    &self,
+    request: Request<proto::GetYearInReviewRequest>,
+  ) -> Result<Response<proto::YearInReviewReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|e| {
+      error!(error = e.to_string(), "Invalid profile id");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let report = self
+      .recommendation_interactor
+      .find_year_in_review(&profile_id, request.year)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to get year in review");
+        Status::internal(e.to_string())
+      })?
+      .ok_or_else(|| Status::not_found("Year in review not found"))?;
+    Ok(Response::new(proto::YearInReviewReply {
+      report: Some(report.into()),
+    }))
+  }
+
+  async fn list_year_in_reviews(
+    &self,
+    request: Request<proto::ListYearInReviewsRequest>,
+  ) -> Result<Response<proto::ListYearInReviewsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let profile_id =
+      ProfileId::scoped(&tenant_id, request.into_inner().profile_id).map_err(|e| {
+        error!(error = e.to_string(), "Invalid profile id");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let reports = self
+      .recommendation_interactor
+      .list_year_in_reviews(&profile_id)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to list year in reviews");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::ListYearInReviewsReply {
+      reports: reports.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn list_recommendation_digests(
+    &self,
+    request: Request<proto::ListRecommendationDigestsRequest>,
+  ) -> Result<Response<proto::ListRecommendationDigestsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|e| {
+      error!(error = e.to_string(), "Invalid profile id");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let digests = self
+      .recommendation_interactor
+      .list_recommendation_digests(&profile_id, request.limit.map(|limit| limit as usize))
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to list recommendation digests"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::ListRecommendationDigestsReply {
+      digests: digests.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn update_recommendation_curation(
+    &self,
+    request: Request<proto::UpdateRecommendationCurationRequest>,
+  ) -> Result<Response<proto::RecommendationCurationReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|e| {
+      error!(error = e.to_string(), "Invalid profile id");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let parse = |file_names: Vec<String>| {
+      parse_file_names(file_names).map_err(|e| {
+        error!(error = e.to_string(), "Invalid album file name");
+        Status::invalid_argument(e.to_string())
+      })
+    };
+    let pin = parse(request.pin)?;
+    let unpin = parse(request.unpin)?;
+    let exclude = parse(request.exclude)?;
+    let unexclude = parse(request.unexclude)?;
+    let curation = self
+      .recommendation_interactor
+      .update_recommendation_curation(&profile_id, pin, unpin, exclude, unexclude)
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to update recommendation curation"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::RecommendationCurationReply {
+      curation: Some(curation.into()),
+    }))
+  }
+
+  async fn get_global_exclusion(
+    &self,
+    request: Request<()>,
+  ) -> Result<Response<proto::GlobalExclusionReply>, Status> {
+    let exclusion = self
+      .recommendation_interactor
+      .get_global_exclusion(&request_tenant_id(&request)?)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to get global exclusion");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::GlobalExclusionReply {
+      exclusion: Some(exclusion.into()),
+    }))
+  }
+
+  async fn update_global_exclusion(
+    &self,
+    request: Request<proto::UpdateGlobalExclusionRequest>,
+  ) -> Result<Response<proto::GlobalExclusionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let parse = |file_names: Vec<String>| {
+      parse_file_names(file_names).map_err(|e| {
+        error!(error = e.to_string(), "Invalid album file name");
+        Status::invalid_argument(e.to_string())
+      })
+    };
+    let add = parse(request.add)?;
+    let remove = parse(request.remove)?;
+    let exclusion = self
+      .recommendation_interactor
+      .update_global_exclusion(&tenant_id, add, remove)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to update global exclusion");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::GlobalExclusionReply {
+      exclusion: Some(exclusion.into()),
+    }))
+  }
+
+  async fn import_global_exclusion(
+    &self,
+    request: Request<proto::ImportGlobalExclusionRequest>,
+  ) -> Result<Response<proto::ImportGlobalExclusionReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let content = String::from_utf8(request.into_inner().content)
+      .map_err(|_| Status::invalid_argument("Import file must be utf-8 encoded"))?;
+    let rows = self
+      .recommendation_interactor
+      .import_global_exclusion_csv(&tenant_id, &content)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to import global exclusion");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::ImportGlobalExclusionReply {
+      imported: rows.iter().filter(|row| row.file_name.is_ok()).count() as u32,
+      rows: rows
+        .into_iter()
+        .map(|row| {
+          let (file_name, error) = match row.file_name {
+            Ok(file_name) => (Some(file_name.to_string()), None),
+            Err(error) => (None, Some(error)),
+          };
+          proto::GlobalExclusionImportRowStatus {
+            line: row.line as u32,
+            file_name,
+            error,
+          }
+        })
+        .collect(),
+    }))
+  }
+
+  async fn recommend_curated_albums(
+    &self,
+    request: Request<proto::RecommendCuratedAlbumsRequest>,
+  ) -> Result<Response<proto::RecommendCuratedAlbumsReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let profile_id = ProfileId::scoped(&tenant_id, request.profile_id).map_err(|e| {
+      error!(error = e.to_string(), "Invalid profile id");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let assessment_settings = match request.assessment_settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let recommendation_settings = match request.recommendation_settings {
+      Some(settings) => AlbumRecommendationSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumRecommendationSettings::default(),
+    };
+    let recommendations = self
+      .recommendation_interactor
+      .recommend_curated_albums(&profile_id, assessment_settings, recommendation_settings)
+      .await
+      .map_err(|e| {
+        error!(error = e.to_string(), "Failed to recommend curated albums");
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::RecommendCuratedAlbumsReply {
+      recommendations: recommendations.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn get_recommendation_rationale(
+    &self,
+    request: Request<proto::GetRecommendationRationaleRequest>,
+  ) -> Result<Response<proto::GetRecommendationRationaleReply>, Status> {
+    let tenant_id = request_tenant_id(&request)?;
+    let request = request.into_inner();
+    let seed_request = request.seed.ok_or_else(|| {
+      error!("Seed not provided");
+      Status::invalid_argument("Seed not provided")
+    })?;
+    let seed = AlbumRecommendationSeed::try_from(seed_request)
+      .map(|seed| seed.scoped_to(&tenant_id))
+      .map_err(|e| {
+        error!(error = e.to_string(), "Invalid seed");
+        Status::invalid_argument(e.to_string())
+      })?;
+    let file_name = FileName::try_from(request.file_name).map_err(|e| {
+      error!(error = e.to_string(), "Invalid album file name");
+      Status::invalid_argument(e.to_string())
+    })?;
+    let settings: AlbumAssessmentSettings = match request.settings {
+      Some(settings) => AlbumAssessmentSettings::try_from(settings).map_err(|e| {
+        error!(error = e.to_string(), "Invalid settings");
+        Status::invalid_argument(e.to_string())
+      })?,
+      None => AlbumAssessmentSettings::QuantileRank(QuantileRankAlbumAssessmentSettings::default()),
+    };
+    let rationale = self
+      .recommendation_interactor
+      .get_recommendation_rationale(seed, &file_name, settings)
+      .await
+      .map_err(|e| {
+        error!(
+          error = e.to_string(),
+          "Failed to get recommendation rationale"
+        );
+        Status::internal(e.to_string())
+      })?;
+    Ok(Response::new(proto::GetRecommendationRationaleReply {
+      rationale: Some(rationale.into()),
+    }))
+  }
 }
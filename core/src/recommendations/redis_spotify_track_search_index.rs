@@ -0,0 +1,208 @@
+use super::spotify_track_search_index::{
+  SpotifyTrackEmbeddingSimilaritySearchQuery, SpotifyTrackQuery, SpotifyTrackSearchIndex,
+  SpotifyTrackSearchRecord, SpotifyTrackSearchResult,
+};
+use crate::helpers::{
+  embedding::embedding_to_bytes,
+  redisearch::{SearchIndexVersionManager, SearchPagination},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use rustis::{
+  bb8::Pool,
+  client::PooledClientManager,
+  commands::{
+    FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes, FtIndexDataType,
+    FtSearchOptions, FtVectorDistanceMetric, FtVectorFieldAlgorithm, FtVectorType, JsonCommands,
+    SearchCommands, SetCondition, SortOrder,
+  },
+};
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+pub struct RedisSpotifyTrackSearchIndex {
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  version_manager: SearchIndexVersionManager,
+}
+
+const NAMESPACE: &str = "spotify_track";
+pub const INDEX_VERSION: u32 = 3;
+
+impl RedisSpotifyTrackSearchIndex {
+  pub fn new(redis_connection_pool: Arc<Pool<PooledClientManager>>) -> Self {
+    Self {
+      version_manager: SearchIndexVersionManager::new(
+        Arc::clone(&redis_connection_pool),
+        INDEX_VERSION,
+        "spotify_track_idx".to_string(),
+      ),
+      redis_connection_pool,
+    }
+  }
+}
+
+#[async_trait]
+impl SpotifyTrackSearchIndex for RedisSpotifyTrackSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
+    self
+      .version_manager
+      .setup_index(
+        FtCreateOptions::default()
+          .on(FtIndexDataType::Json)
+          .prefix(format!("{}:", NAMESPACE)),
+        vec![
+          FtFieldSchema::identifier("$.spotify_id")
+            .as_attribute("spotify_id")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.name")
+            .as_attribute("name")
+            .field_type(FtFieldType::Text),
+          FtFieldSchema::identifier("$.album_file_name")
+            .as_attribute("album_file_name")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.album.spotify_id")
+            .as_attribute("album_spotify_id")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.album.name")
+            .as_attribute("album_name")
+            .field_type(FtFieldType::Text),
+          FtFieldSchema::identifier("$.artists[*].spotify_id")
+            .as_attribute("artist_spotify_id")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.artists[*].name")
+            .as_attribute("artist_name")
+            .field_type(FtFieldType::Text),
+          FtFieldSchema::identifier("$.duration_ms")
+            .as_attribute("duration_ms")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.audio_features.energy")
+            .as_attribute("energy")
+            .field_type(FtFieldType::Numeric)
+            .sortable(),
+          FtFieldSchema::identifier("$.audio_features.danceability")
+            .as_attribute("danceability")
+            .field_type(FtFieldType::Numeric)
+            .sortable(),
+          FtFieldSchema::identifier("$.audio_features.tempo")
+            .as_attribute("tempo")
+            .field_type(FtFieldType::Numeric)
+            .sortable(),
+          FtFieldSchema::identifier("$.audio_features.valence")
+            .as_attribute("valence")
+            .field_type(FtFieldType::Numeric)
+            .sortable(),
+          FtFieldSchema::identifier("$.embedding")
+            .as_attribute("embedding")
+            .field_type(FtFieldType::Vector(Some(FtVectorFieldAlgorithm::Flat(
+              FtFlatVectorFieldAttributes::new(
+                FtVectorType::Float32,
+                9,
+                FtVectorDistanceMetric::Cosine,
+              ),
+            )))),
+        ],
+      )
+      .await
+  }
+
+  async fn put(&self, record: SpotifyTrackSearchRecord) -> Result<()> {
+    self
+      .redis_connection_pool
+      .get()
+      .await?
+      .json_set(
+        format!("{}:{}", NAMESPACE, record.spotify_id),
+        "$",
+        serde_json::to_string(&record)?,
+        SetCondition::default(),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  async fn search(
+    &self,
+    query: &SpotifyTrackQuery,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<SpotifyTrackSearchResult> {
+    let limit = pagination.and_then(|p| p.limit).unwrap_or(100000);
+    let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
+    let mut options = FtSearchOptions::default().limit(offset, limit);
+    if let Some(sort) = &query.sort {
+      options = options.sortby(
+        sort.feature.attribute(),
+        if sort.descending {
+          SortOrder::Desc
+        } else {
+          SortOrder::Asc
+        },
+      );
+    }
+    let result = self
+      .redis_connection_pool
+      .get()
+      .await?
+      .ft_search(
+        self.version_manager.latest_index_name(),
+        query.to_ft_search_query(),
+        options,
+      )
+      .await?;
+
+    let tracks = result
+      .results
+      .into_iter()
+      .filter_map(|r| match r.values.try_into() {
+        Ok(track) => Some(track),
+        Err(e) => {
+          warn!("Failed to deserialize SpotifyTrackSearchRecord: {}", e);
+          None
+        }
+      })
+      .collect::<Vec<_>>();
+
+    Ok(SpotifyTrackSearchResult {
+      tracks,
+      total: result.total_results,
+    })
+  }
+
+  #[instrument(skip(self))]
+  async fn embedding_similarity_search(
+    &self,
+    query: &SpotifyTrackEmbeddingSimilaritySearchQuery,
+  ) -> Result<Vec<(SpotifyTrackSearchRecord, f32)>> {
+    let search_result = self
+      .redis_connection_pool
+      .get()
+      .await?
+      .ft_search(
+        self.version_manager.latest_index_name(),
+        query.to_ft_search_query(),
+        FtSearchOptions::default()
+          .params(("BLOB", embedding_to_bytes(&query.embedding)))
+          .dialect(2)
+          .limit(0, query.limit)
+          .sortby("distance", SortOrder::Asc),
+      )
+      .await?;
+    let results = search_result
+      .results
+      .into_iter()
+      .filter_map(|row| {
+        let distance = row
+          .values
+          .first()
+          .map(|(_, distance)| distance.parse::<f32>().ok())??;
+        let track = row
+          .values
+          .get(1)
+          .and_then(|(_, json)| serde_json::from_str::<SpotifyTrackSearchRecord>(json).ok())?;
+        Some((track, distance))
+      })
+      .collect::<Vec<_>>();
+    Ok(results)
+  }
+}
@@ -144,7 +144,8 @@ impl
         recommendation_settings,
         similar_albums,
       )
-      .await?;
+      .await?
+      .recommendations;
 
     for recommendation in recommendations.iter_mut() {
       if recommendation.assessment.metadata.is_none() {
@@ -1,24 +1,175 @@
 use crate::{
   albums::album_read_model::AlbumReadModel, files::file_metadata::file_name::FileName,
-  profile::profile::ProfileId,
+  profile::profile::ProfileId, tenant::tenant_id::TenantId,
 };
+use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug)]
 pub enum AlbumRecommendationSeed {
   Profile(ProfileId),
   Albums(HashMap<FileName, u32>),
+  /**
+   * The albums of a collection, each with a factor of 1. The tenant is checked against the
+   * collection's profile when the seed is built.
+   */
+  Collection {
+    id: String,
+    tenant_id: TenantId,
+  },
+  /**
+   * Several profile or album seeds combined into one, each scaled by its weight
+   */
+  Blend(Vec<WeightedAlbumRecommendationSeed>),
+  /**
+   * A seed paired with a profile or album set of disliked albums. Albums similar to the negative
+   * seed are penalized in proportion to its weight.
+   */
+  WithNegative {
+    seed: Box<AlbumRecommendationSeed>,
+    negative: Box<AlbumRecommendationSeed>,
+    weight: f32,
+  },
+}
+
+impl AlbumRecommendationSeed {
+  /**
+   * Points the seed's profiles at the tenant's profiles of the same name
+   */
+  pub fn scoped_to(self, tenant_id: &TenantId) -> Self {
+    match self {
+      Self::Profile(profile_id) => Self::Profile(profile_id.with_tenant(tenant_id)),
+      Self::Albums(albums) => Self::Albums(albums),
+      Self::Collection { id, .. } => Self::Collection {
+        id,
+        tenant_id: tenant_id.clone(),
+      },
+      Self::Blend(seeds) => Self::Blend(
+        seeds
+          .into_iter()
+          .map(|weighted| WeightedAlbumRecommendationSeed {
+            seed: weighted.seed.scoped_to(tenant_id),
+            weight: weighted.weight,
+          })
+          .collect(),
+      ),
+      Self::WithNegative {
+        seed,
+        negative,
+        weight,
+      } => Self::WithNegative {
+        seed: Box::new(seed.scoped_to(tenant_id)),
+        negative: Box::new(negative.scoped_to(tenant_id)),
+        weight,
+      },
+    }
+  }
+}
+
+pub const DEFAULT_NEGATIVE_SEED_WEIGHT: f32 = 0.5;
+
+#[derive(Clone, Debug)]
+pub struct WeightedAlbumRecommendationSeed {
+  pub seed: AlbumRecommendationSeed,
+  pub weight: f32,
+}
+
+impl WeightedAlbumRecommendationSeed {
+  pub fn new(seed: AlbumRecommendationSeed, weight: f32) -> Result<Self> {
+    if !weight.is_finite() || weight <= 0.0 {
+      return Err(anyhow!(
+        "Seed weights must be positive and finite, got {}",
+        weight
+      ));
+    }
+    Ok(Self { seed, weight })
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct NegativeSeedContext {
+  pub context: Box<AlbumRecommendationSeedContext>,
+  pub weight: f32,
 }
 
 #[derive(Clone, Debug)]
 pub struct AlbumRecommendationSeedContext {
   pub albums: Vec<AlbumReadModel>,
   pub factor_map: HashMap<FileName, u32>,
+  pub negative: Option<NegativeSeedContext>,
+  /**
+   * Recency weighted listen counts per artist, only known for profile seeds with a listening
+   * history
+   */
+  pub artist_listen_scores: HashMap<FileName, f64>,
 }
 
 impl AlbumRecommendationSeedContext {
   pub fn new(albums: Vec<AlbumReadModel>, factor_map: HashMap<FileName, u32>) -> Self {
-    Self { albums, factor_map }
+    Self {
+      albums,
+      factor_map,
+      negative: None,
+      artist_listen_scores: HashMap::new(),
+    }
+  }
+
+  pub fn with_artist_listen_scores(self, artist_listen_scores: HashMap<FileName, f64>) -> Self {
+    Self {
+      artist_listen_scores,
+      ..self
+    }
+  }
+
+  pub fn with_negative(self, negative: AlbumRecommendationSeedContext, weight: f32) -> Self {
+    Self {
+      negative: Some(NegativeSeedContext {
+        context: Box::new(negative),
+        weight,
+      }),
+      ..self
+    }
+  }
+
+  /**
+   * Seed albums of both polarities, none of which should be recommended
+   */
+  pub fn excluded_file_names(&self) -> Vec<FileName> {
+    let mut file_names = self.album_file_names();
+    if let Some(negative) = &self.negative {
+      file_names.extend(negative.context.album_file_names());
+    }
+    file_names
+  }
+
+  /**
+   * Merges weighted seed contexts. An album's factor is the weighted sum of its factors across
+   * contexts, rounded and floored at 1 so that every seed album keeps some influence. Artist
+   * listen scores are summed the same way, unrounded.
+   */
+  pub fn merge(contexts: Vec<(Self, f32)>) -> Self {
+    let mut albums: HashMap<FileName, AlbumReadModel> = HashMap::new();
+    let mut weighted_factors: HashMap<FileName, f32> = HashMap::new();
+    let mut artist_listen_scores: HashMap<FileName, f64> = HashMap::new();
+    for (context, weight) in contexts {
+      for (file_name, score) in context.artist_listen_scores {
+        *artist_listen_scores.entry(file_name).or_insert(0.0) += score * weight as f64;
+      }
+      for (file_name, factor) in context.factor_map {
+        *weighted_factors.entry(file_name).or_insert(0.0) += factor as f32 * weight;
+      }
+      for album in context.albums {
+        albums.entry(album.file_name.clone()).or_insert(album);
+      }
+    }
+    Self::new(
+      albums.into_values().collect(),
+      weighted_factors
+        .into_iter()
+        .map(|(file_name, factor)| (file_name, (factor.round() as u32).max(1)))
+        .collect(),
+    )
+    .with_artist_listen_scores(artist_listen_scores)
   }
 
   pub fn album_file_names(&self) -> Vec<FileName> {
@@ -33,3 +184,44 @@ impl AlbumRecommendationSeedContext {
     self.factor_map.get(file_name).copied()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_merge_sums_weighted_factors() -> Result<()> {
+    let shared = FileName::try_from("release/album/billy-woods/aethiopes")?;
+    let only_first = FileName::try_from("release/album/bjork/vulnicura")?;
+    let only_second = FileName::try_from("release/album/run-the-jewels/run-the-jewels-2")?;
+    let merged = AlbumRecommendationSeedContext::merge(vec![
+      (
+        AlbumRecommendationSeedContext::new(
+          vec![],
+          HashMap::from([(shared.clone(), 2), (only_first.clone(), 4)]),
+        ),
+        1.5,
+      ),
+      (
+        AlbumRecommendationSeedContext::new(
+          vec![],
+          HashMap::from([(shared.clone(), 3), (only_second.clone(), 1)]),
+        ),
+        0.25,
+      ),
+    ]);
+    assert_eq!(merged.get_factor(&shared), Some(4));
+    assert_eq!(merged.get_factor(&only_first), Some(6));
+    assert_eq!(merged.get_factor(&only_second), Some(1));
+    Ok(())
+  }
+
+  #[test]
+  fn test_weighted_seed_rejects_invalid_weights() {
+    let seed = AlbumRecommendationSeed::Albums(HashMap::new());
+    for weight in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+      assert!(WeightedAlbumRecommendationSeed::new(seed.clone(), weight).is_err());
+    }
+    assert!(WeightedAlbumRecommendationSeed::new(seed, 0.5).is_ok());
+  }
+}
@@ -1,25 +1,14 @@
 use crate::{
   files::file_metadata::file_name::FileName,
-  helpers::{
-    embedding::embedding_to_bytes,
-    redisearch::{get_tag_query, SearchIndexVersionManager, SearchPagination},
+  helpers::redisearch::{get_num_range_query, get_tag_query, SearchPagination},
+  spotify::spotify_client::{
+    SpotifyAlbumReference, SpotifyArtistReference, SpotifyTrackAudioFeatures, SpotifyTrackReference,
   },
-  spotify::spotify_client::{SpotifyAlbumReference, SpotifyArtistReference, SpotifyTrackReference},
 };
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use derive_builder::Builder;
-use rustis::{
-  bb8::Pool,
-  client::PooledClientManager,
-  commands::{
-    FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes, FtIndexDataType,
-    FtSearchOptions, FtVectorDistanceMetric, FtVectorFieldAlgorithm, FtVectorType, JsonCommands,
-    SearchCommands, SetCondition, SortOrder,
-  },
-};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tracing::{instrument, warn};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpotifyTrackSearchRecord {
@@ -30,6 +19,8 @@ pub struct SpotifyTrackSearchRecord {
   pub artists: Vec<SpotifyArtistReference>,
   pub embedding: Vec<f32>,
   pub duration_ms: Option<u32>,
+  #[serde(default)]
+  pub audio_features: Option<SpotifyTrackAudioFeatures>,
 }
 
 impl From<SpotifyTrackSearchRecord> for SpotifyTrackReference {
@@ -58,6 +49,7 @@ impl SpotifyTrackSearchRecord {
       artists: track.artists,
       embedding,
       duration_ms: track.duration_ms,
+      audio_features: None,
     }
   }
 }
@@ -81,11 +73,51 @@ pub struct SpotifyTrackSearchResult {
   pub total: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpotifyTrackAudioFeature {
+  Energy,
+  Danceability,
+  Tempo,
+  Valence,
+}
+
+impl SpotifyTrackAudioFeature {
+  pub fn attribute(&self) -> &'static str {
+    match self {
+      SpotifyTrackAudioFeature::Energy => "energy",
+      SpotifyTrackAudioFeature::Danceability => "danceability",
+      SpotifyTrackAudioFeature::Tempo => "tempo",
+      SpotifyTrackAudioFeature::Valence => "valence",
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpotifyTrackAudioFeatureRange {
+  pub feature: SpotifyTrackAudioFeature,
+  pub min: Option<f32>,
+  pub max: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpotifyTrackSort {
+  pub feature: SpotifyTrackAudioFeature,
+  pub descending: bool,
+}
+
 #[derive(Default, Builder, Debug)]
 #[builder(setter(into), default)]
 pub struct SpotifyTrackQuery {
   pub include_spotify_ids: Vec<String>,
   pub include_album_file_names: Vec<FileName>,
+  /**
+   * Tracks indexed before audio features were fetched never match a range
+   */
+  pub audio_feature_ranges: Vec<SpotifyTrackAudioFeatureRange>,
+  /**
+   * Ignored by embedding similarity searches, which are always ordered by distance
+   */
+  pub sort: Option<SpotifyTrackSort>,
 }
 
 impl SpotifyTrackQuery {
@@ -96,6 +128,13 @@ impl SpotifyTrackQuery {
       "@album_file_name",
       &self.include_album_file_names,
     ));
+    for range in &self.audio_feature_ranges {
+      query.push_str(&get_num_range_query(
+        &format!("@{}", range.feature.attribute()),
+        range.min,
+        range.max,
+      ));
+    }
     query.trim().to_string()
   }
 }
@@ -123,159 +162,20 @@ pub struct SpotifyTrackSimilairtySearchQuery {
   pub filters: SpotifyTrackQuery,
 }
 
-pub struct SpotifyTrackSearchIndex {
-  redis_connection_pool: Arc<Pool<PooledClientManager>>,
-  version_manager: SearchIndexVersionManager,
-}
-
-const NAMESPACE: &str = "spotify_track";
-const INDEX_VERSION: u32 = 2;
-
-impl SpotifyTrackSearchIndex {
-  pub fn new(redis_connection_pool: Arc<Pool<PooledClientManager>>) -> Self {
-    Self {
-      version_manager: SearchIndexVersionManager::new(
-        Arc::clone(&redis_connection_pool),
-        INDEX_VERSION,
-        "spotify_track_idx".to_string(),
-      ),
-      redis_connection_pool,
-    }
-  }
-
-  pub async fn setup_index(&self) -> Result<()> {
-    self
-      .version_manager
-      .setup_index(
-        FtCreateOptions::default()
-          .on(FtIndexDataType::Json)
-          .prefix(format!("{}:", NAMESPACE)),
-        vec![
-          FtFieldSchema::identifier("$.spotify_id")
-            .as_attribute("spotify_id")
-            .field_type(FtFieldType::Tag),
-          FtFieldSchema::identifier("$.name")
-            .as_attribute("name")
-            .field_type(FtFieldType::Text),
-          FtFieldSchema::identifier("$.album_file_name")
-            .as_attribute("album_file_name")
-            .field_type(FtFieldType::Tag),
-          FtFieldSchema::identifier("$.album.spotify_id")
-            .as_attribute("album_spotify_id")
-            .field_type(FtFieldType::Tag),
-          FtFieldSchema::identifier("$.album.name")
-            .as_attribute("album_name")
-            .field_type(FtFieldType::Text),
-          FtFieldSchema::identifier("$.artists[*].spotify_id")
-            .as_attribute("artist_spotify_id")
-            .field_type(FtFieldType::Tag),
-          FtFieldSchema::identifier("$.artists[*].name")
-            .as_attribute("artist_name")
-            .field_type(FtFieldType::Text),
-          FtFieldSchema::identifier("$.duration_ms")
-            .as_attribute("duration_ms")
-            .field_type(FtFieldType::Numeric),
-          FtFieldSchema::identifier("$.embedding")
-            .as_attribute("embedding")
-            .field_type(FtFieldType::Vector(Some(FtVectorFieldAlgorithm::Flat(
-              FtFlatVectorFieldAttributes::new(
-                FtVectorType::Float32,
-                9,
-                FtVectorDistanceMetric::Cosine,
-              ),
-            )))),
-        ],
-      )
-      .await
-  }
-
-  pub async fn put(&self, record: SpotifyTrackSearchRecord) -> Result<()> {
-    self
-      .redis_connection_pool
-      .get()
-      .await?
-      .json_set(
-        format!("{}:{}", NAMESPACE, record.spotify_id),
-        "$",
-        serde_json::to_string(&record)?,
-        SetCondition::default(),
-      )
-      .await?;
-
-    Ok(())
-  }
-
-  #[instrument(skip(self))]
-  pub async fn search(
+#[async_trait]
+pub trait SpotifyTrackSearchIndex {
+  async fn setup_index(&self) -> Result<()>;
+  async fn put(&self, record: SpotifyTrackSearchRecord) -> Result<()>;
+  async fn search(
     &self,
     query: &SpotifyTrackQuery,
     pagination: Option<&SearchPagination>,
-  ) -> Result<SpotifyTrackSearchResult> {
-    let limit = pagination.and_then(|p| p.limit).unwrap_or(100000);
-    let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
-    let result = self
-      .redis_connection_pool
-      .get()
-      .await?
-      .ft_search(
-        self.version_manager.latest_index_name(),
-        query.to_ft_search_query(),
-        FtSearchOptions::default().limit(offset, limit),
-      )
-      .await?;
-
-    let tracks = result
-      .results
-      .into_iter()
-      .filter_map(|r| match r.values.try_into() {
-        Ok(track) => Some(track),
-        Err(e) => {
-          warn!("Failed to deserialize SpotifyTrackSearchRecord: {}", e);
-          None
-        }
-      })
-      .collect::<Vec<_>>();
-
-    Ok(SpotifyTrackSearchResult {
-      tracks,
-      total: result.total_results,
-    })
-  }
-
-  #[instrument(skip(self))]
-  pub async fn embedding_similarity_search(
+  ) -> Result<SpotifyTrackSearchResult>;
+  /**
+   * Tracks closest to the query's embedding by cosine distance, nearest first
+   */
+  async fn embedding_similarity_search(
     &self,
     query: &SpotifyTrackEmbeddingSimilaritySearchQuery,
-  ) -> Result<Vec<(SpotifyTrackSearchRecord, f32)>> {
-    let search_result = self
-      .redis_connection_pool
-      .get()
-      .await?
-      .ft_search(
-        self.version_manager.latest_index_name(),
-        query.to_ft_search_query(),
-        FtSearchOptions::default()
-          .params(("BLOB", embedding_to_bytes(&query.embedding)))
-          .dialect(2)
-          .limit(0, query.limit)
-          .sortby("distance", SortOrder::Asc),
-      )
-      .await?;
-    let results = search_result
-      .results
-      .into_iter()
-      .filter_map(|row| {
-        let distance = row
-          .values
-          .first()
-          .map(|(_, distance)| distance.parse::<f32>().ok())??;
-        let track = row
-          .values
-          .get(1)
-          .and_then(|(_, json)| serde_json::from_str::<SpotifyTrackSearchRecord>(json).ok())?;
-        Some((track, distance))
-      })
-      .collect::<Vec<_>>();
-    Ok(results)
-  }
+  ) -> Result<Vec<(SpotifyTrackSearchRecord, f32)>>;
 }
@@ -0,0 +1,301 @@
+use super::spotify_track_search_index::{
+  SpotifyTrackEmbeddingSimilaritySearchQuery, SpotifyTrackQuery, SpotifyTrackSearchIndex,
+  SpotifyTrackSearchRecord, SpotifyTrackSearchResult,
+};
+use crate::{
+  helpers::{
+    embedding::{embedding_from_bytes, embedding_to_bytes},
+    math::cosine_similarity,
+    redisearch::SearchPagination,
+  },
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusqlite::{params, params_from_iter, types::Value, ToSql};
+use std::{rc::Rc, sync::Arc};
+use tracing::{error, instrument, warn};
+
+enum FilterParam {
+  Real(f64),
+  List(Vec<String>),
+}
+
+impl FilterParam {
+  fn into_sql(self) -> Box<dyn ToSql> {
+    match self {
+      FilterParam::Real(value) => Box::new(value),
+      FilterParam::List(values) => Box::new(Rc::new(
+        values.into_iter().map(Value::from).collect::<Vec<Value>>(),
+      )),
+    }
+  }
+}
+
+fn into_sql_params(params: Vec<FilterParam>) -> Vec<Box<dyn ToSql>> {
+  params.into_iter().map(|p| p.into_sql()).collect()
+}
+
+impl SpotifyTrackQuery {
+  /**
+   * The where clause and its params. Tracks without audio features have null feature values, so
+   * they never match a range.
+   */
+  fn to_sqlite_filter(&self) -> (String, Vec<FilterParam>) {
+    let mut conditions = vec![];
+    let mut params = vec![];
+    if !self.include_spotify_ids.is_empty() {
+      conditions.push("spotify_id IN rarray(?)".to_string());
+      params.push(FilterParam::List(self.include_spotify_ids.clone()));
+    }
+    if !self.include_album_file_names.is_empty() {
+      conditions.push("album_file_name IN rarray(?)".to_string());
+      params.push(FilterParam::List(
+        self
+          .include_album_file_names
+          .iter()
+          .map(|file_name| file_name.to_string())
+          .collect(),
+      ));
+    }
+    for range in &self.audio_feature_ranges {
+      let column = format!(
+        "json_extract(json, '$.audio_features.{}')",
+        range.feature.attribute()
+      );
+      if let Some(min) = range.min {
+        conditions.push(format!("{} >= ?", column));
+        params.push(FilterParam::Real(min as f64));
+      }
+      if let Some(max) = range.max {
+        conditions.push(format!("{} <= ?", column));
+        params.push(FilterParam::Real(max as f64));
+      }
+    }
+    let where_clause = if conditions.is_empty() {
+      "".to_string()
+    } else {
+      format!("WHERE {}", conditions.join(" AND "))
+    };
+    (where_clause, params)
+  }
+
+  fn to_sqlite_order_by(&self) -> String {
+    match &self.sort {
+      Some(sort) => format!(
+        "ORDER BY json_extract(json, '$.audio_features.{}') {} NULLS LAST, spotify_id",
+        sort.feature.attribute(),
+        if sort.descending { "DESC" } else { "ASC" }
+      ),
+      None => "ORDER BY spotify_id".to_string(),
+    }
+  }
+}
+
+/**
+ * Spotify tracks for deployments without Redis. Embedding similarity search is a brute-force scan
+ * over the tracks matching the filters, which are almost always restricted to a few albums.
+ */
+pub struct SqliteSpotifyTrackSearchIndex {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl SqliteSpotifyTrackSearchIndex {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+}
+
+fn parse_records(rows: Vec<String>) -> Vec<SpotifyTrackSearchRecord> {
+  rows
+    .into_iter()
+    .filter_map(|json| match serde_json::from_str(&json) {
+      Ok(track) => Some(track),
+      Err(e) => {
+        warn!("Failed to deserialize SpotifyTrackSearchRecord: {}", e);
+        None
+      }
+    })
+    .collect()
+}
+
+#[async_trait]
+impl SpotifyTrackSearchIndex for SqliteSpotifyTrackSearchIndex {
+  async fn setup_index(&self) -> Result<()> {
+    // Schema is managed by migrations
+    Ok(())
+  }
+
+  async fn put(&self, record: SpotifyTrackSearchRecord) -> Result<()> {
+    let json = serde_json::to_string(&record)?;
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO spotify_tracks (spotify_id, album_file_name, json, embedding)
+          VALUES (?, ?, ?, ?)
+          ON CONFLICT (spotify_id) DO UPDATE SET
+            album_file_name = excluded.album_file_name,
+            json = excluded.json,
+            embedding = excluded.embedding
+          ",
+          params![
+            record.spotify_id,
+            record.album_file_name.to_string(),
+            json,
+            embedding_to_bytes(&record.embedding)
+          ],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put spotify track");
+        anyhow!("Failed to put spotify track")
+      })??;
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  async fn search(
+    &self,
+    query: &SpotifyTrackQuery,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<SpotifyTrackSearchResult> {
+    let (where_clause, params) = query.to_sqlite_filter();
+    let order_by = query.to_sqlite_order_by();
+    let limit = pagination.and_then(|p| p.limit).unwrap_or(100000);
+    let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
+    let (rows, total) = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let params = into_sql_params(params);
+        let total = conn.query_row(
+          &format!("SELECT COUNT(*) FROM spotify_tracks {}", where_clause),
+          params_from_iter(params.iter()),
+          |row| row.get::<_, usize>(0),
+        )?;
+        let mut statement = conn.prepare(&format!(
+          "SELECT json FROM spotify_tracks {} {} LIMIT {} OFFSET {}",
+          where_clause, order_by, limit, offset
+        ))?;
+        let rows = statement
+          .query_map(params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>((rows, total))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to search spotify tracks");
+        anyhow!("Failed to search spotify tracks")
+      })??;
+    Ok(SpotifyTrackSearchResult {
+      tracks: parse_records(rows),
+      total,
+    })
+  }
+
+  #[instrument(skip(self))]
+  async fn embedding_similarity_search(
+    &self,
+    query: &SpotifyTrackEmbeddingSimilaritySearchQuery,
+  ) -> Result<Vec<(SpotifyTrackSearchRecord, f32)>> {
+    let (where_clause, params) = query.filters.to_sqlite_filter();
+    let embedding = query.embedding.clone();
+    let limit = query.limit;
+    let mut candidates = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let params = into_sql_params(params);
+        let mut statement = conn.prepare(&format!(
+          "SELECT json, embedding FROM spotify_tracks {}",
+          where_clause
+        ))?;
+        let rows = statement
+          .query_map(params_from_iter(params.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(
+          rows
+            .into_iter()
+            .map(|(json, bytes)| {
+              let distance = 1.0 - cosine_similarity(&embedding, &embedding_from_bytes(&bytes));
+              (json, distance)
+            })
+            .collect::<Vec<_>>(),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to search spotify tracks by embedding"
+        );
+        anyhow!("Failed to search spotify tracks by embedding")
+      })??;
+    candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    candidates.truncate(limit);
+    Ok(
+      candidates
+        .into_iter()
+        .filter_map(|(json, distance)| {
+          serde_json::from_str::<SpotifyTrackSearchRecord>(&json)
+            .ok()
+            .map(|track| (track, distance))
+        })
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::recommendations::spotify_track_search_index::{
+    SpotifyTrackAudioFeature, SpotifyTrackAudioFeatureRange, SpotifyTrackSort,
+  };
+
+  #[test]
+  fn test_to_sqlite_filter() {
+    let query = SpotifyTrackQuery {
+      include_spotify_ids: vec!["a".to_string()],
+      audio_feature_ranges: vec![SpotifyTrackAudioFeatureRange {
+        feature: SpotifyTrackAudioFeature::Energy,
+        min: Some(0.5),
+        max: None,
+      }],
+      ..Default::default()
+    };
+    let (where_clause, params) = query.to_sqlite_filter();
+    assert_eq!(
+      where_clause,
+      "WHERE spotify_id IN rarray(?) AND json_extract(json, '$.audio_features.energy') >= ?"
+    );
+    assert_eq!(params.len(), 2);
+    assert_eq!(SpotifyTrackQuery::default().to_sqlite_filter().0, "");
+  }
+
+  #[test]
+  fn test_to_sqlite_order_by() {
+    let query = SpotifyTrackQuery {
+      sort: Some(SpotifyTrackSort {
+        feature: SpotifyTrackAudioFeature::Tempo,
+        descending: true,
+      }),
+      ..Default::default()
+    };
+    assert_eq!(
+      query.to_sqlite_order_by(),
+      "ORDER BY json_extract(json, '$.audio_features.tempo') DESC NULLS LAST, spotify_id"
+    );
+  }
+}
@@ -0,0 +1,219 @@
+use super::{
+  playlist_energy_curve::PlaylistEnergyCurve, spotify_track_search_index::SpotifyTrackSearchRecord,
+};
+use crate::files::file_metadata::file_name::FileName;
+use std::collections::HashMap;
+
+/**
+ * Tempo differences are scaled down by this many BPM so they weigh about as much as energy
+ * differences in a transition
+ */
+const TEMPO_TRANSITION_SCALE: f32 = 60.0;
+
+/**
+ * Cost of a transition or target miss involving a track without audio features
+ */
+const UNKNOWN_AUDIO_FEATURES_COST: f32 = 0.5;
+
+#[derive(Clone, Debug)]
+pub struct TrackSequencingSettings {
+  /**
+   * Tracks are added, best first, until the playlist is at least this long. Every candidate is used
+   * when unset.
+   */
+  pub target_duration_ms: Option<u64>,
+  pub max_tracks_per_album: usize,
+  pub energy_curve: PlaylistEnergyCurve,
+  /**
+   * Between 0 and 1, how much the next track is picked for a gentle change in energy and tempo
+   * from the previous one rather than for hitting the energy curve
+   */
+  pub smoothness: f32,
+}
+
+impl Default for TrackSequencingSettings {
+  fn default() -> Self {
+    Self {
+      target_duration_ms: Some(60 * 60 * 1000),
+      max_tracks_per_album: 2,
+      energy_curve: PlaylistEnergyCurve::Unshaped,
+      smoothness: 0.5,
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct RankedTrack {
+  pub track: SpotifyTrackSearchRecord,
+  pub score: f32,
+}
+
+fn transition_cost(from: &SpotifyTrackSearchRecord, to: &SpotifyTrackSearchRecord) -> f32 {
+  match (&from.audio_features, &to.audio_features) {
+    (Some(from), Some(to)) => {
+      (from.energy - to.energy).abs() + (from.tempo - to.tempo).abs() / TEMPO_TRANSITION_SCALE
+    }
+    _ => UNKNOWN_AUDIO_FEATURES_COST,
+  }
+}
+
+fn target_cost(track: &SpotifyTrackSearchRecord, target_energy: Option<f32>) -> f32 {
+  match (target_energy, &track.audio_features) {
+    (None, _) => 0.0,
+    (Some(target_energy), Some(features)) => (features.energy - target_energy).abs(),
+    (Some(_), None) => UNKNOWN_AUDIO_FEATURES_COST,
+  }
+}
+
+/**
+ * Picks the best scoring tracks, at most `max_tracks_per_album` from each album, until the target
+ * duration is reached
+ */
+pub fn select_tracks(
+  mut tracks: Vec<RankedTrack>,
+  settings: &TrackSequencingSettings,
+) -> Vec<RankedTrack> {
+  tracks.sort_by(|a, b| b.score.total_cmp(&a.score));
+  let mut album_counts: HashMap<FileName, usize> = HashMap::new();
+  let mut duration_ms = 0;
+  let mut selected = vec![];
+  for track in tracks {
+    if settings
+      .target_duration_ms
+      .is_some_and(|target_duration_ms| duration_ms >= target_duration_ms)
+    {
+      break;
+    }
+    let album_count = album_counts
+      .entry(track.track.album_file_name.clone())
+      .or_insert(0);
+    if *album_count >= settings.max_tracks_per_album {
+      continue;
+    }
+    *album_count += 1;
+    duration_ms += track.track.duration_ms.unwrap_or(0) as u64;
+    selected.push(track);
+  }
+  selected
+}
+
+/**
+ * Orders tracks one position at a time, picking the remaining track that best balances the energy
+ * curve's target for the position against a smooth transition from the previous track. Tracks
+ * by the same album aren't placed back to back when there's another choice.
+ */
+pub fn sequence_tracks(
+  tracks: Vec<RankedTrack>,
+  settings: &TrackSequencingSettings,
+) -> Vec<RankedTrack> {
+  let smoothness = settings.smoothness.clamp(0.0, 1.0);
+  let length = tracks.len();
+  let mut remaining = tracks;
+  let mut sequence: Vec<RankedTrack> = Vec::with_capacity(length);
+  for position in 0..length {
+    let target_energy = settings.energy_curve.target_energy(position, length);
+    let previous = sequence.last().map(|ranked| &ranked.track);
+    let cost = |ranked: &RankedTrack| {
+      let transition = previous.map_or(0.0, |previous| transition_cost(previous, &ranked.track));
+      let same_album =
+        previous.is_some_and(|previous| previous.album_file_name == ranked.track.album_file_name);
+      (
+        same_album,
+        target_cost(&ranked.track, target_energy) * (1.0 - smoothness) + transition * smoothness,
+      )
+    };
+    let next = remaining
+      .iter()
+      .enumerate()
+      .min_by(|(_, a), (_, b)| {
+        let (a_same_album, a_cost) = cost(a);
+        let (b_same_album, b_cost) = cost(b);
+        a_same_album
+          .cmp(&b_same_album)
+          .then(a_cost.total_cmp(&b_cost))
+      })
+      .map(|(index, _)| index);
+    if let Some(index) = next {
+      sequence.push(remaining.swap_remove(index));
+    }
+  }
+  sequence
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::spotify::spotify_client::{
+    SpotifyAlbumReference, SpotifyAlbumType, SpotifyTrackAudioFeatures,
+  };
+
+  fn ranked_track(id: &str, album: &str, score: f32, energy: f32) -> RankedTrack {
+    RankedTrack {
+      track: SpotifyTrackSearchRecord {
+        spotify_id: id.to_string(),
+        name: id.to_string(),
+        album_file_name: FileName::try_from(format!("release/album/artist/{}", album)).unwrap(),
+        album: SpotifyAlbumReference {
+          spotify_id: album.to_string(),
+          name: album.to_string(),
+          album_type: SpotifyAlbumType::Album,
+        },
+        artists: vec![],
+        embedding: vec![],
+        duration_ms: Some(4 * 60 * 1000),
+        audio_features: Some(SpotifyTrackAudioFeatures {
+          energy,
+          danceability: 0.5,
+          tempo: 120.0,
+          valence: 0.5,
+        }),
+      },
+      score,
+    }
+  }
+
+  fn ids(tracks: &[RankedTrack]) -> Vec<&str> {
+    tracks
+      .iter()
+      .map(|ranked| ranked.track.spotify_id.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn test_select_tracks_caps_albums_and_duration() {
+    let settings = TrackSequencingSettings {
+      target_duration_ms: Some(10 * 60 * 1000),
+      max_tracks_per_album: 1,
+      ..Default::default()
+    };
+    let selected = select_tracks(
+      vec![
+        ranked_track("a1", "a", 0.9, 0.5),
+        ranked_track("a2", "a", 0.8, 0.5),
+        ranked_track("b1", "b", 0.7, 0.5),
+        ranked_track("c1", "c", 0.6, 0.5),
+        ranked_track("d1", "d", 0.5, 0.5),
+      ],
+      &settings,
+    );
+    assert_eq!(ids(&selected), vec!["a1", "b1", "c1"]);
+  }
+
+  #[test]
+  fn test_sequence_tracks_follows_energy_curve() {
+    let settings = TrackSequencingSettings {
+      energy_curve: PlaylistEnergyCurve::Rising,
+      smoothness: 0.0,
+      ..Default::default()
+    };
+    let sequence = sequence_tracks(
+      vec![
+        ranked_track("high", "a", 0.9, 0.9),
+        ranked_track("low", "b", 0.8, 0.2),
+        ranked_track("mid", "c", 0.7, 0.55),
+      ],
+      &settings,
+    );
+    assert_eq!(ids(&sequence), vec!["low", "mid", "high"]);
+  }
+}
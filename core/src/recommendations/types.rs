@@ -1,12 +1,15 @@
-use crate::albums::{
-  album_read_model::AlbumReadModel,
-  album_search_index::{AlbumSearchQuery, AlbumSearchQueryBuilder},
+use crate::{
+  albums::{
+    album_read_model::AlbumReadModel,
+    album_search_index::{AlbumSearchQuery, AlbumSearchQueryBuilder},
+  },
+  files::file_metadata::file_name::FileName,
 };
 use anyhow::Result;
 use async_trait::async_trait;
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Ordering, collections::HashMap, time::Instant};
 
-use super::seed::AlbumRecommendationSeedContext;
+use super::{discogs_availability::AlbumRecommendationSort, seed::AlbumRecommendationSeedContext};
 
 #[derive(Clone, Debug)]
 pub struct AlbumRecommendationSettings {
@@ -22,6 +25,39 @@ pub struct AlbumRecommendationSettings {
   pub min_release_year: Option<u32>,
   pub max_release_year: Option<u32>,
   pub exclude_known_artists: Option<bool>,
+  pub max_albums_per_artist: Option<u32>,
+  pub max_albums_per_primary_genre: Option<u32>,
+  pub max_albums_per_decade: Option<u32>,
+  /**
+   * When the request's time budget runs out, set as the request arrives so that building the seed
+   * and searching for candidates count against it too. Once passed, the best albums assessed so
+   * far are returned instead of the full ranking.
+   */
+  pub deadline: Option<Instant>,
+  pub exclude_file_names: Vec<FileName>,
+  /**
+   * Opts out of the global exclusion list, which is otherwise added to `exclude_file_names`
+   */
+  pub include_globally_excluded: bool,
+  /**
+   * Slots in each batch filled by sampling lower-ranked candidates instead of taking the top
+   * scores
+   */
+  pub exploration_slots: Option<u32>,
+  /**
+   * Lower temperatures favour the best of the lower-ranked candidates, higher ones approach
+   * uniform sampling
+   */
+  pub exploration_temperature: Option<f32>,
+  /**
+   * Minimum album rating for a candidate to be explored
+   */
+  pub exploration_min_rating: Option<f32>,
+  /**
+   * Leaves out albums without a vinyl copy for sale on Discogs at or under this price
+   */
+  pub max_discogs_lowest_price: Option<f32>,
+  pub sort: AlbumRecommendationSort,
 }
 
 impl Default for AlbumRecommendationSettings {
@@ -39,18 +75,49 @@ impl Default for AlbumRecommendationSettings {
       exclude_known_artists: Some(true),
       include_descriptors: vec![],
       exclude_descriptors: vec![],
+      max_albums_per_artist: None,
+      max_albums_per_primary_genre: None,
+      max_albums_per_decade: None,
+      deadline: None,
+      exclude_file_names: vec![],
+      include_globally_excluded: false,
+      exploration_slots: None,
+      exploration_temperature: None,
+      exploration_min_rating: None,
+      max_discogs_lowest_price: None,
+      sort: AlbumRecommendationSort::Score,
     }
   }
 }
 impl AlbumRecommendationSettings {
-  pub fn to_search_query(&self, seed_albums: &[AlbumReadModel]) -> Result<AlbumSearchQuery> {
-    let album_file_names = seed_albums
-      .iter()
-      .map(|album| album.file_name.clone())
-      .collect::<Vec<_>>();
+  pub fn has_diversity_constraints(&self) -> bool {
+    self.max_albums_per_artist.is_some()
+      || self.max_albums_per_primary_genre.is_some()
+      || self.max_albums_per_decade.is_some()
+  }
+
+  /**
+   * Whether candidates are filtered after they're assessed, so more have to be fetched than are
+   * returned
+   */
+  pub fn has_post_assessment_constraints(&self) -> bool {
+    self.has_diversity_constraints() || self.max_discogs_lowest_price.is_some()
+  }
+
+  pub fn exploration_slots(&self) -> u32 {
+    self.exploration_slots.unwrap_or(0).min(self.count)
+  }
+
+  pub fn to_search_query(
+    &self,
+    seed_context: &AlbumRecommendationSeedContext,
+  ) -> Result<AlbumSearchQuery> {
+    let seed_albums = &seed_context.albums;
+    let mut exclude_file_names = seed_context.excluded_file_names();
+    exclude_file_names.extend(self.exclude_file_names.clone());
     let mut search_query_builder = AlbumSearchQueryBuilder::default();
     search_query_builder
-      .exclude_file_names(album_file_names)
+      .exclude_file_names(exclude_file_names)
       .include_primary_genres(self.include_primary_genres.clone())
       .include_secondary_genres(self.include_secondary_genres.clone())
       .include_languages(self.include_languages.clone())
@@ -75,16 +142,90 @@ impl AlbumRecommendationSettings {
   }
 }
 
+/**
+ * How much a single factor moved an album's score, used to explain recommendations
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlbumAssessmentContribution {
+  pub factor: String,
+  pub weight: f32,
+  /**
+   * The factor's own score, e.g. a percentile for quantile ranking
+   */
+  pub value: f64,
+  /**
+   * Signed amount the factor added to the final score
+   */
+  pub contribution: f64,
+  /**
+   * Album tags shared with the seed, strongest first
+   */
+  pub matched_items: Vec<String>,
+  /**
+   * Album tags the seed hasn't seen, scored with the novelty score
+   */
+  pub novel_items: Vec<String>,
+}
+
+/**
+ * Recommendations along with the fraction of candidates assessed to produce them, which is below
+ * 1 only when a time budget ran out first
+ */
+#[derive(Clone, Debug)]
+pub struct AlbumRecommendations {
+  pub recommendations: Vec<AlbumRecommendation>,
+  pub completeness: f32,
+}
+
+impl AlbumRecommendations {
+  pub fn complete(recommendations: Vec<AlbumRecommendation>) -> Self {
+    Self {
+      recommendations,
+      completeness: 1.0,
+    }
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.completeness >= 1.0
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct AlbumAssessment {
   pub score: f32,
   pub metadata: Option<HashMap<String, String>>,
+  pub contributions: Vec<AlbumAssessmentContribution>,
 }
 
 #[derive(Clone, Debug)]
 pub struct AlbumRecommendation {
   pub album: AlbumReadModel,
   pub assessment: AlbumAssessment,
+  /**
+   * Sampled into an exploration slot rather than ranked in
+   */
+  pub exploratory: bool,
+}
+
+impl AlbumRecommendation {
+  /**
+   * A recommendation for an otherwise empty album, for tests
+   */
+  #[cfg(test)]
+  pub fn new_for_test(file_name: &str, score: f32) -> Result<Self> {
+    Ok(Self {
+      album: AlbumReadModel {
+        file_name: FileName::try_from(file_name)?,
+        ..Default::default()
+      },
+      assessment: AlbumAssessment {
+        score,
+        metadata: None,
+        contributions: vec![],
+      },
+      exploratory: false,
+    })
+  }
 }
 
 impl PartialEq for AlbumRecommendation {
@@ -111,6 +252,30 @@ impl Ord for AlbumRecommendation {
   }
 }
 
+/**
+ * Which way an assessment method's scores improve
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreOrder {
+  /**
+   * Lower is better, e.g. embedding distances
+   */
+  Ascending,
+  Descending,
+}
+
+impl ScoreOrder {
+  /**
+   * Orders recommendations best first
+   */
+  pub fn sort(&self, recommendations: &mut [AlbumRecommendation]) {
+    match self {
+      ScoreOrder::Ascending => recommendations.sort_by(|a, b| a.cmp(b)),
+      ScoreOrder::Descending => recommendations.sort_by(|a, b| b.cmp(a)),
+    }
+  }
+}
+
 #[async_trait]
 pub trait RecommendationMethodInteractor<
   TAssessableAlbum: TryFrom<AlbumReadModel>,
@@ -0,0 +1,156 @@
+use super::types::AlbumRecommendation;
+use crate::{
+  albums::{album_collection_summary::AlbumCollectionSummary, album_read_model::AlbumReadModel},
+  files::file_metadata::file_name::FileName,
+  helpers::item_with_factor::ItemWithFactor,
+  profile::profile::{Profile, ProfileId},
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const TOP_ITEM_COUNT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YearInReviewAlbum {
+  pub file_name: FileName,
+  pub name: String,
+  pub artists: Vec<String>,
+  pub release_date: Option<NaiveDate>,
+}
+
+impl From<&AlbumReadModel> for YearInReviewAlbum {
+  fn from(album: &AlbumReadModel) -> Self {
+    Self {
+      file_name: album.file_name.clone(),
+      name: album.name.clone(),
+      artists: album.artist_names(),
+      release_date: album.release_date,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YearInReviewRecommendation {
+  pub album: YearInReviewAlbum,
+  pub score: f32,
+}
+
+/**
+ * What a profile took in over a calendar year, along with recommendations seeded by it for the
+ * year after. Only albums still on the profile with a known added date count towards the year.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YearInReview {
+  pub id: String,
+  pub profile_id: ProfileId,
+  pub year: i32,
+  pub created_at: NaiveDateTime,
+  pub album_count: u32,
+  pub primary_genres: Vec<ItemWithFactor>,
+  pub descriptors: Vec<ItemWithFactor>,
+  pub oldest_release: Option<YearInReviewAlbum>,
+  pub newest_release: Option<YearInReviewAlbum>,
+  pub recommendations: Vec<YearInReviewRecommendation>,
+}
+
+impl YearInReview {
+  pub fn id(profile_id: &ProfileId, year: i32) -> String {
+    format!("{}:{}", profile_id.to_string(), year)
+  }
+
+  /**
+   * `albums` are the profile's albums added during the year
+   */
+  pub fn new(
+    profile: &Profile,
+    year: i32,
+    albums: &[AlbumReadModel],
+    recommendations: Vec<AlbumRecommendation>,
+  ) -> Self {
+    let summary = AlbumCollectionSummary::new(albums, &albums_added_in(profile, year));
+    let dated = albums
+      .iter()
+      .filter_map(|album| album.release_date.map(|release_date| (release_date, album)));
+    let oldest_release = dated
+      .clone()
+      .min_by_key(|(release_date, _)| *release_date)
+      .map(|(_, album)| album.into());
+    let newest_release = dated
+      .max_by_key(|(release_date, _)| *release_date)
+      .map(|(_, album)| album.into());
+
+    Self {
+      id: Self::id(&profile.id, year),
+      profile_id: profile.id.clone(),
+      year,
+      created_at: Utc::now().naive_utc(),
+      album_count: albums.len() as u32,
+      primary_genres: summary
+        .primary_genres
+        .into_iter()
+        .take(TOP_ITEM_COUNT)
+        .collect(),
+      descriptors: summary
+        .descriptors
+        .into_iter()
+        .take(TOP_ITEM_COUNT)
+        .collect(),
+      oldest_release,
+      newest_release,
+      recommendations: recommendations
+        .into_iter()
+        .map(|recommendation| YearInReviewRecommendation {
+          album: (&recommendation.album).into(),
+          score: recommendation.assessment.score,
+        })
+        .collect(),
+    }
+  }
+}
+
+/**
+ * Factors of the profile's albums added during the year
+ */
+pub fn albums_added_in(profile: &Profile, year: i32) -> HashMap<FileName, u32> {
+  profile
+    .albums
+    .iter()
+    .filter(|(file_name, _)| {
+      profile
+        .album_added_at
+        .get(file_name)
+        .is_some_and(|added_at| added_at.year() == year)
+    })
+    .map(|(file_name, factor)| (file_name.clone(), *factor))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::Result;
+
+  #[test]
+  fn test_albums_added_in() -> Result<()> {
+    let added_at = |date: &str| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S");
+    let in_year = FileName::try_from("release/album/bjork/vulnicura")?;
+    let earlier = FileName::try_from("release/album/fka-twigs/lp1")?;
+    let undated = FileName::try_from("release/album/daft-punk/random-access-memories")?;
+    let removed = FileName::try_from("release/album/radiohead/kid-a")?;
+    let profile = Profile {
+      albums: HashMap::from([(in_year.clone(), 3), (earlier.clone(), 1), (undated, 1)]),
+      album_added_at: HashMap::from([
+        (in_year.clone(), added_at("2024-06-01T00:00:00")?),
+        (earlier, added_at("2023-12-31T23:59:59")?),
+        (removed, added_at("2024-02-01T00:00:00")?),
+      ]),
+      ..Default::default()
+    };
+    assert_eq!(
+      albums_added_in(&profile, 2024),
+      HashMap::from([(in_year, 3)])
+    );
+    Ok(())
+  }
+}
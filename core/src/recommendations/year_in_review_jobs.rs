@@ -0,0 +1,100 @@
+use super::recommendation_interactor::RecommendationInteractor;
+use crate::{
+  context::ApplicationContext,
+  job_executor,
+  profile::profile::ProfileId,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobExecutorFn, JobParametersBuilder, JobProcessorBuilder},
+    scheduler_repository::Job,
+  },
+};
+use anyhow::Result;
+use chrono::{Datelike, TimeDelta, Utc};
+use std::sync::Arc;
+use tracing::{error, info};
+
+/**
+ * Creates last year's report for every configured profile that doesn't have one yet, so a report
+ * shows up the first day of the year and a missed day is caught up on the next
+ */
+async fn create_year_in_reviews(_: Job, app_context: Arc<ApplicationContext>) -> Result<()> {
+  let settings = &app_context.settings.year_in_review;
+  let year = Utc::now().year() - 1;
+  let recommendation_interactor = RecommendationInteractor::new(Arc::clone(&app_context));
+  for profile_id in &settings.profile_ids {
+    let profile_id = match ProfileId::try_from(profile_id.clone()) {
+      Ok(profile_id) => profile_id,
+      Err(e) => {
+        error!(
+          profile_id = profile_id.as_str(),
+          error = e.to_string(),
+          "Invalid year in review profile id"
+        );
+        continue;
+      }
+    };
+    match recommendation_interactor
+      .find_year_in_review(&profile_id, year)
+      .await
+    {
+      Ok(Some(_)) => continue,
+      Ok(None) => {}
+      Err(e) => {
+        error!(
+          profile_id = profile_id.to_string(),
+          error = e.to_string(),
+          "Failed to find year in review"
+        );
+        continue;
+      }
+    }
+    match recommendation_interactor
+      .create_year_in_review(&profile_id, year, settings.recommendation_count)
+      .await
+    {
+      Ok(report) => info!(
+        profile_id = profile_id.to_string(),
+        year,
+        album_count = report.album_count,
+        "Created year in review"
+      ),
+      Err(e) => error!(
+        profile_id = profile_id.to_string(),
+        year,
+        error = e.to_string(),
+        "Failed to create year in review"
+      ),
+    }
+  }
+  Ok(())
+}
+
+pub async fn setup_year_in_review_jobs(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if app_context.settings.year_in_review.profile_ids.is_empty() {
+    return Ok(());
+  }
+
+  app_context
+    .scheduler
+    .register(
+      JobProcessorBuilder::default()
+        .name(JobName::CreateYearInReviews)
+        .app_context(Arc::clone(&app_context))
+        .executor(job_executor!(create_year_in_reviews))
+        .build()?,
+    )
+    .await;
+
+  app_context
+    .scheduler
+    .put(
+      JobParametersBuilder::default()
+        .name(JobName::CreateYearInReviews)
+        .interval(TimeDelta::try_days(1).unwrap())
+        .build()?,
+    )
+    .await?;
+
+  Ok(())
+}
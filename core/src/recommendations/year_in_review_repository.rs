@@ -0,0 +1,61 @@
+use super::year_in_review::YearInReview;
+use crate::{
+  helpers::document_store::{DocumentFilter, DocumentStore},
+  profile::profile::ProfileId,
+};
+use anyhow::Result;
+use std::sync::Arc;
+
+pub struct YearInReviewRepository {
+  doc_store: Arc<DocumentStore>,
+}
+
+const COLLECTION: &str = "year_in_review";
+
+impl YearInReviewRepository {
+  pub fn new(doc_store: Arc<DocumentStore>) -> Self {
+    Self { doc_store }
+  }
+
+  /**
+   * Replaces the profile's earlier report for the same year
+   */
+  pub async fn put(&self, report: YearInReview) -> Result<()> {
+    self
+      .doc_store
+      .put(COLLECTION, &report.id.clone(), report, None)
+      .await
+  }
+
+  pub async fn find(&self, profile_id: &ProfileId, year: i32) -> Result<Option<YearInReview>> {
+    Ok(
+      self
+        .doc_store
+        .find_by_key::<YearInReview>(COLLECTION, &YearInReview::id(profile_id, year))
+        .await?
+        .map(|doc| doc.document),
+    )
+  }
+
+  /**
+   * Reports of a profile, latest year first
+   */
+  pub async fn find_by_profile_id(&self, profile_id: &ProfileId) -> Result<Vec<YearInReview>> {
+    let mut reports = self
+      .doc_store
+      .find_many::<YearInReview>(
+        COLLECTION,
+        DocumentFilter::new()
+          .condition("profile_id", "=", profile_id.to_string())
+          .build(),
+        None,
+      )
+      .await?
+      .documents
+      .into_iter()
+      .map(|doc| doc.document)
+      .collect::<Vec<_>>();
+    reports.sort_by(|a, b| b.year.cmp(&a.year));
+    Ok(reports)
+  }
+}
@@ -1,6 +1,10 @@
 use crate::{
-  albums::redis_album_search_index::RedisAlbumSearchIndex, context::ApplicationContext,
-  recommendations::spotify_track_search_index::SpotifyTrackSearchIndex, settings::RedisSettings,
+  context::ApplicationContext,
+  recommendations::{
+    redis_spotify_track_search_index::RedisSpotifyTrackSearchIndex,
+    spotify_track_search_index::SpotifyTrackSearchIndex,
+  },
+  settings::{RedisSettings, StorageMode},
 };
 use anyhow::Result;
 use rustis::{
@@ -8,7 +12,7 @@ use rustis::{
   client::PooledClientManager,
 };
 use std::{sync::Arc, time::Duration};
-use tracing::error;
+use tracing::{error, info};
 
 #[derive(Debug)]
 struct RedisConnectionErrorSink;
@@ -23,29 +27,36 @@ impl ErrorSink<rustis::Error> for RedisConnectionErrorSink {
   }
 }
 
+/**
+ * In sqlite storage mode the pool is built without connecting, so Redis is only required if a
+ * Redis-backed feature is actually used.
+ */
 pub async fn build_redis_connection_pool(
   redis_settings: RedisSettings,
+  storage_mode: StorageMode,
 ) -> Result<Pool<PooledClientManager>> {
   let error_sink = RedisConnectionErrorSink {};
-  Pool::builder()
-    .min_idle(Some(1))
+  let manager = PooledClientManager::new(redis_settings.url.as_str())?;
+  let builder = Pool::builder()
     .max_size(redis_settings.max_pool_size)
     .connection_timeout(Duration::from_secs(30))
-    .error_sink(Box::new(error_sink))
-    .build(PooledClientManager::new(redis_settings.url.as_str())?)
-    .await
-    .map_err(|e| e.into())
+    .error_sink(Box::new(error_sink));
+  match storage_mode {
+    StorageMode::Redis => builder
+      .min_idle(Some(1))
+      .build(manager)
+      .await
+      .map_err(|e| e.into()),
+    StorageMode::Sqlite => Ok(builder.build_unchecked(manager)),
+  }
 }
 
 pub async fn setup_redis_indexes(app_context: Arc<ApplicationContext>) -> Result<()> {
-  RedisAlbumSearchIndex::new(
-    Arc::clone(&app_context.redis_connection_pool),
-    Arc::clone(&app_context.embedding_provider_interactor),
-  )
-  .setup_index()
-  .await?;
-
-  SpotifyTrackSearchIndex::new(Arc::clone(&app_context.redis_connection_pool))
+  if app_context.settings.storage.mode == StorageMode::Sqlite {
+    info!("Skipping redis index setup in sqlite storage mode");
+    return Ok(());
+  }
+  RedisSpotifyTrackSearchIndex::new(Arc::clone(&app_context.redis_connection_pool))
     .setup_index()
     .await?;
 
@@ -0,0 +1,131 @@
+use crate::{
+  albums::redis_album_search_index::LegacyEmbeddingsMigration, context::ApplicationContext,
+  settings::StorageMode,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use rustis::{
+  bb8::Pool,
+  client::PooledClientManager,
+  commands::{GenericCommands, ScanOptions},
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+const BATCH_SIZE: usize = 500;
+
+/**
+ * A one-off change to the shape of documents in Redis. Keys matching the pattern are scanned in
+ * batches and handed to `migrate`, which must be safe to run again on keys it has already seen.
+ */
+#[async_trait]
+pub trait RedisMigration {
+  fn name(&self) -> &'static str;
+  fn key_pattern(&self) -> String;
+  async fn migrate(&self, keys: Vec<String>) -> Result<()>;
+}
+
+fn completed_key(name: &str) -> String {
+  format!("redis_migration:{}:completed_at", name)
+}
+
+fn cursor_key(name: &str) -> String {
+  format!("redis_migration:{}:cursor", name)
+}
+
+fn registered_migrations(
+  app_context: &Arc<ApplicationContext>,
+) -> Vec<Box<dyn RedisMigration + Send + Sync>> {
+  vec![Box::new(LegacyEmbeddingsMigration::new(Arc::clone(
+    &app_context.redis_connection_pool,
+  )))]
+}
+
+/**
+ * Runs a migration to completion, resuming from the last recorded scan cursor if a previous run
+ * was interrupted.
+ */
+async fn run_redis_migration(
+  app_context: &Arc<ApplicationContext>,
+  redis_connection_pool: &Pool<PooledClientManager>,
+  migration: &(dyn RedisMigration + Send + Sync),
+) -> Result<()> {
+  let name = migration.name();
+  let mut cursor = app_context
+    .kv
+    .get::<u64>(&cursor_key(name))
+    .await?
+    .unwrap_or(0);
+  let mut migrated_count = 0;
+  info!(migration = name, cursor, "Running redis migration");
+  loop {
+    let (next_cursor, keys): (u64, Vec<String>) = redis_connection_pool
+      .get()
+      .await?
+      .scan(
+        cursor,
+        ScanOptions::default()
+          .match_pattern(migration.key_pattern())
+          .count(BATCH_SIZE),
+      )
+      .await?;
+    migrated_count += keys.len();
+    if !keys.is_empty() {
+      migration.migrate(keys).await?;
+    }
+    cursor = next_cursor;
+    if cursor == 0 {
+      break;
+    }
+    app_context.kv.set(&cursor_key(name), cursor, None).await?;
+    info!(
+      migration = name,
+      migrated_count, "Redis migration batch complete"
+    );
+  }
+  app_context
+    .kv
+    .set(&completed_key(name), Utc::now().naive_utc(), None)
+    .await?;
+  app_context.kv.delete(&cursor_key(name)).await?;
+  info!(migration = name, migrated_count, "Redis migration complete");
+  Ok(())
+}
+
+/**
+ * Runs every registered migration that hasn't completed yet, in registration order. A failed
+ * migration stops the ones after it, and is picked up again on the next startup.
+ */
+pub async fn run_redis_migrations(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if app_context.settings.storage.mode == StorageMode::Sqlite {
+    info!("Skipping redis migrations in sqlite storage mode");
+    return Ok(());
+  }
+  for migration in registered_migrations(&app_context) {
+    let name = migration.name();
+    if app_context
+      .kv
+      .get::<NaiveDateTime>(&completed_key(name))
+      .await?
+      .is_some()
+    {
+      continue;
+    }
+    if let Err(e) = run_redis_migration(
+      &app_context,
+      &app_context.redis_connection_pool,
+      migration.as_ref(),
+    )
+    .await
+    {
+      error!(
+        migration = name,
+        error = e.to_string(),
+        "Redis migration failed"
+      );
+      return Err(e);
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,274 @@
+use crate::proto::FILE_DESCRIPTOR_SET;
+use anyhow::Result;
+use prost::Message;
+use prost_types::{
+  field_descriptor_proto::{Label, Type},
+  DescriptorProto, FieldDescriptorProto, FileDescriptorSet,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+const PACKAGE_PREFIX: &str = ".lute.";
+const EMPTY_TYPE: &str = ".google.protobuf.Empty";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatewayRoute {
+  pub service: String,
+  pub rpc: String,
+  pub path: String,
+  /**
+   * RPCs without a request body are served on GET, the rest on POST
+   */
+  pub is_get: bool,
+  pub request_type: String,
+  pub response_type: String,
+}
+
+pub fn to_kebab_case(value: &str) -> String {
+  let mut kebab = String::with_capacity(value.len() + 4);
+  for (i, c) in value.chars().enumerate() {
+    if c.is_ascii_uppercase() && i > 0 {
+      kebab.push('-');
+    }
+    kebab.push(c.to_ascii_lowercase());
+  }
+  kebab
+}
+
+fn to_camel_case(value: &str) -> String {
+  let mut camel = String::with_capacity(value.len());
+  let mut upper = false;
+  for c in value.chars() {
+    if c == '_' {
+      upper = true;
+    } else if upper {
+      camel.push(c.to_ascii_uppercase());
+      upper = false;
+    } else {
+      camel.push(c);
+    }
+  }
+  camel
+}
+
+fn schema_name(type_name: &str) -> String {
+  type_name.trim_start_matches(PACKAGE_PREFIX).to_string()
+}
+
+fn schema_ref(type_name: &str) -> Value {
+  if type_name == EMPTY_TYPE {
+    return json!({ "type": "object" });
+  }
+  json!({ "$ref": format!("#/components/schemas/{}", schema_name(type_name)) })
+}
+
+fn descriptor_set() -> Result<FileDescriptorSet> {
+  Ok(FileDescriptorSet::decode(FILE_DESCRIPTOR_SET)?)
+}
+
+/**
+ * Routes for the unary RPCs of the given proto services, each paired with the path segment it's
+ * served under
+ */
+pub fn gateway_routes(services: &[(&str, &str)]) -> Result<Vec<GatewayRoute>> {
+  let descriptor_set = descriptor_set()?;
+  let mut routes = vec![];
+  for file in descriptor_set.file.iter() {
+    for service in file.service.iter() {
+      let Some((_, segment)) = services.iter().find(|(name, _)| *name == service.name()) else {
+        continue;
+      };
+      for method in service.method.iter() {
+        if method.client_streaming() || method.server_streaming() {
+          continue;
+        }
+        routes.push(GatewayRoute {
+          service: service.name().to_string(),
+          rpc: method.name().to_string(),
+          path: format!("/api/{}/{}", segment, to_kebab_case(method.name())),
+          is_get: method.input_type() == EMPTY_TYPE,
+          request_type: method.input_type().to_string(),
+          response_type: method.output_type().to_string(),
+        });
+      }
+    }
+  }
+  Ok(routes)
+}
+
+struct SchemaBuilder {
+  map_entries: HashMap<String, DescriptorProto>,
+  schemas: Map<String, Value>,
+}
+
+impl SchemaBuilder {
+  fn field_type_schema(&self, field: &FieldDescriptorProto) -> Value {
+    match field.r#type() {
+      Type::Double | Type::Float => json!({ "type": "number" }),
+      Type::Int64
+      | Type::Uint64
+      | Type::Int32
+      | Type::Fixed64
+      | Type::Fixed32
+      | Type::Uint32
+      | Type::Sfixed32
+      | Type::Sfixed64
+      | Type::Sint32
+      | Type::Sint64 => json!({ "type": "integer" }),
+      Type::Bool => json!({ "type": "boolean" }),
+      Type::String => json!({ "type": "string" }),
+      // Serialized as a byte array rather than the proto3 JSON base64 string
+      Type::Bytes => json!({ "type": "array", "items": { "type": "integer" } }),
+      // Enums are carried as their numeric values
+      Type::Enum => json!({ "type": "integer" }),
+      Type::Message | Type::Group => schema_ref(field.type_name()),
+    }
+  }
+
+  fn field_schema(&self, field: &FieldDescriptorProto) -> Value {
+    if let Some(entry) = self.map_entries.get(field.type_name()) {
+      let value_schema = entry
+        .field
+        .iter()
+        .find(|field| field.name() == "value")
+        .map(|field| self.field_type_schema(field))
+        .unwrap_or(json!({}));
+      return json!({ "type": "object", "additionalProperties": value_schema });
+    }
+    let schema = self.field_type_schema(field);
+    if field.label() == Label::Repeated {
+      json!({ "type": "array", "items": schema })
+    } else {
+      schema
+    }
+  }
+
+  fn add_message(&mut self, prefix: &str, message: &DescriptorProto) {
+    let name = format!("{}{}", prefix, message.name());
+    if message
+      .options
+      .as_ref()
+      .is_some_and(|options| options.map_entry())
+    {
+      self
+        .map_entries
+        .insert(format!("{}{}", PACKAGE_PREFIX, name), message.clone());
+      return;
+    }
+    // Nested types go first, so map entries are known before the fields using them
+    for nested in message.nested_type.iter() {
+      self.add_message(&format!("{}.", name), nested);
+    }
+
+    let mut properties = Map::new();
+    let mut oneofs: Vec<Map<String, Value>> = vec![Map::new(); message.oneof_decl.len()];
+    for field in message.field.iter() {
+      let schema = self.field_schema(field);
+      match field.oneof_index {
+        // Synthetic oneofs back proto3 optional fields, which are plain nullable fields in JSON
+        Some(index) if !field.proto3_optional() => {
+          oneofs[index as usize].insert(field.json_name().to_string(), schema);
+        }
+        _ => {
+          properties.insert(field.json_name().to_string(), schema);
+        }
+      }
+    }
+    for (oneof, variants) in message.oneof_decl.iter().zip(oneofs) {
+      if variants.is_empty() {
+        continue;
+      }
+      properties.insert(
+        to_camel_case(oneof.name()),
+        json!({ "type": "object", "properties": variants, "maxProperties": 1 }),
+      );
+    }
+    self
+      .schemas
+      .insert(name, json!({ "type": "object", "properties": properties }));
+  }
+}
+
+/**
+ * OpenAPI 3 description of the gateway, with schemas for every message in the proto package
+ */
+pub fn generate_openapi_spec(routes: &[GatewayRoute]) -> Result<Value> {
+  let descriptor_set = descriptor_set()?;
+  let mut builder = SchemaBuilder {
+    map_entries: HashMap::new(),
+    schemas: Map::new(),
+  };
+  for file in descriptor_set.file.iter() {
+    if file.package() != "lute" {
+      continue;
+    }
+    for message in file.message_type.iter() {
+      builder.add_message("", message);
+    }
+  }
+
+  let mut paths = Map::new();
+  for route in routes {
+    let response = json!({
+      "200": {
+        "description": "OK",
+        "content": { "application/json": { "schema": schema_ref(&route.response_type) } }
+      }
+    });
+    let mut operation = json!({
+      "operationId": format!("{}.{}", route.service, route.rpc),
+      "tags": [route.service],
+      "responses": response,
+    });
+    if !route.is_get {
+      operation["requestBody"] = json!({
+        "content": { "application/json": { "schema": schema_ref(&route.request_type) } }
+      });
+    }
+    let mut path = Map::new();
+    path.insert(
+      if route.is_get { "get" } else { "post" }.to_string(),
+      operation,
+    );
+    paths.insert(route.path.clone(), Value::Object(path));
+  }
+
+  Ok(json!({
+    "openapi": "3.0.3",
+    "info": { "title": "Lute", "version": env!("CARGO_PKG_VERSION") },
+    "paths": paths,
+    "components": { "schemas": builder.schemas },
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_gateway_routes_and_spec() -> Result<()> {
+    let routes = gateway_routes(&[("AlbumService", "albums")])?;
+    let search = routes
+      .iter()
+      .find(|route| route.rpc == "SearchAlbums")
+      .unwrap();
+    assert_eq!(search.path, "/api/albums/search-albums");
+    assert!(!search.is_get);
+    assert!(routes
+      .iter()
+      .find(|route| route.rpc == "GetMonitor")
+      .is_some_and(|route| route.is_get));
+    // Client streaming isn't served over plain HTTP
+    assert!(!routes
+      .iter()
+      .any(|route| route.rpc == "BulkUploadAlbumEmbeddings"));
+
+    let spec = generate_openapi_spec(&routes)?;
+    assert!(spec["paths"]["/api/albums/search-albums"]["post"].is_object());
+    let query = &spec["components"]["schemas"]["SearchAlbumsRequest"]["properties"]["query"];
+    assert_eq!(query["$ref"], "#/components/schemas/AlbumSearchQuery");
+    let predicate = &spec["components"]["schemas"]["AlbumSearchPredicate"]["properties"];
+    assert!(predicate["predicate"]["properties"]["primaryGenre"].is_object());
+    Ok(())
+  }
+}
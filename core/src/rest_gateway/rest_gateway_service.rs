@@ -0,0 +1,290 @@
+use super::openapi::{gateway_routes, generate_openapi_spec, GatewayRoute};
+use crate::{
+  albums::album_service::AlbumService,
+  auth::api_key::AuthenticatedKey,
+  context::ApplicationContext,
+  lookup::LookupService,
+  proto::{AlbumService as _, LookupService as _, RecommendationService as _},
+  recommendations::recommendation_service::RecommendationService,
+};
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use std::{convert::Infallible, fmt::Display, future::Future, sync::Arc};
+use tonic::{
+  body::BoxBody,
+  codegen::{
+    http::{self, header, Method, StatusCode},
+    Body, BoxFuture, Bytes, Context, Poll, Service,
+  },
+  metadata::MetadataMap,
+  server::NamedService,
+  Code, Extensions, Request, Response, Status,
+};
+use tracing::error;
+
+/**
+ * Proto services exposed over JSON, with the path segment each is served under
+ */
+const GATEWAY_SERVICES: [(&str, &str); 3] = [
+  ("AlbumService", "albums"),
+  ("LookupService", "lookup"),
+  ("RecommendationService", "recommendations"),
+];
+
+struct RestGateway {
+  routes: Vec<GatewayRoute>,
+  openapi_spec: Vec<u8>,
+  album_service: AlbumService,
+  lookup_service: LookupService,
+  recommendation_service: RecommendationService,
+}
+
+/**
+ * Serves the unary RPCs of the main services as JSON over plain HTTP at `/api/...`, so they can
+ * be called with curl or from a browser. RPCs without a request are served on GET and the rest on
+ * POST with the request message as the body, e.g. `POST /api/albums/search-albums`. The OpenAPI
+ * spec is served at `/api/openapi.json`.
+ */
+#[derive(Clone)]
+pub struct RestGatewayService {
+  gateway: Arc<RestGateway>,
+}
+
+impl RestGatewayService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Result<Self> {
+    let routes = gateway_routes(&GATEWAY_SERVICES)?;
+    let openapi_spec = serde_json::to_vec(&generate_openapi_spec(&routes)?)?;
+    Ok(Self {
+      gateway: Arc::new(RestGateway {
+        routes,
+        openapi_spec,
+        album_service: AlbumService::new(Arc::clone(&app_context)),
+        lookup_service: LookupService::new(Arc::clone(&app_context)),
+        recommendation_service: RecommendationService::new(app_context),
+      }),
+    })
+  }
+
+  pub fn routes(&self) -> &[GatewayRoute] {
+    &self.gateway.routes
+  }
+}
+
+impl NamedService for RestGatewayService {
+  const NAME: &'static str = "api";
+}
+
+fn response(status: StatusCode, content: Vec<u8>) -> http::Response<BoxBody> {
+  let body = tonic::transport::Body::from(content)
+    .map_err(|e| Status::internal(e.to_string()))
+    .boxed_unsync();
+  let mut response = http::Response::new(body);
+  *response.status_mut() = status;
+  response
+    .headers_mut()
+    .insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+  response
+}
+
+fn error_response(status: StatusCode, message: impl Display) -> http::Response<BoxBody> {
+  response(
+    status,
+    json!({ "message": message.to_string() })
+      .to_string()
+      .into_bytes(),
+  )
+}
+
+pub fn status_code(code: Code) -> StatusCode {
+  match code {
+    Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+    Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+    Code::PermissionDenied => StatusCode::FORBIDDEN,
+    Code::NotFound => StatusCode::NOT_FOUND,
+    Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+    Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+    Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+    Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+    _ => StatusCode::INTERNAL_SERVER_ERROR,
+  }
+}
+
+/**
+ * What the gateway passes on from the HTTP request to the RPC handler, as tonic would for a gRPC
+ * call
+ */
+struct GatewayRequest {
+  metadata: MetadataMap,
+  authenticated_key: Option<AuthenticatedKey>,
+  content: Vec<u8>,
+}
+
+impl GatewayRequest {
+  fn into_request<T>(self, message: T) -> Request<T> {
+    let mut extensions = Extensions::new();
+    if let Some(authenticated_key) = self.authenticated_key {
+      extensions.insert(authenticated_key);
+    }
+    Request::from_parts(self.metadata, extensions, message)
+  }
+}
+
+/**
+ * Runs an RPC handler on a JSON request, an empty body standing in for the default message
+ */
+async fn call<Req, Res, F, Fut>(request: GatewayRequest, handler: F) -> http::Response<BoxBody>
+where
+  Req: DeserializeOwned + Default,
+  Res: Serialize,
+  F: FnOnce(Request<Req>) -> Fut,
+  Fut: Future<Output = Result<Response<Res>, Status>>,
+{
+  let message = if request.content.is_empty() {
+    Req::default()
+  } else {
+    match serde_json::from_slice::<Req>(&request.content) {
+      Ok(message) => message,
+      Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    }
+  };
+  match handler(request.into_request(message)).await {
+    Ok(reply) => match serde_json::to_vec(reply.get_ref()) {
+      Ok(content) => response(StatusCode::OK, content),
+      Err(e) => {
+        error!(err = e.to_string(), "Failed to serialize gateway response");
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, e)
+      }
+    },
+    Err(status) => error_response(status_code(status.code()), status.message()),
+  }
+}
+
+macro_rules! dispatch {
+  ($rpc:expr, $request:expr, $service:expr, { $($name:literal => $method:ident),* $(,)? }) => {
+    match $rpc {
+      $($name => call($request, |request| $service.$method(request)).await,)*
+      _ => error_response(StatusCode::NOT_IMPLEMENTED, "RPC isn't served by the gateway"),
+    }
+  };
+}
+
+impl RestGateway {
+  async fn handle(&self, route: &GatewayRoute, request: GatewayRequest) -> http::Response<BoxBody> {
+    let rpc = route.rpc.as_str();
+    match route.service.as_str() {
+      "AlbumService" => dispatch!(rpc, request, self.album_service, {
+        "GetMonitor" => get_monitor,
+        "GetAlbum" => get_album,
+        "GetManyAlbums" => get_many_albums,
+        "FilterExistingAlbums" => filter_existing_albums,
+        "SearchAlbums" => search_albums,
+        "GetSearchBoostProfiles" => get_search_boost_profiles,
+        "PutSearchBoostProfile" => put_search_boost_profile,
+        "DeleteSearchBoostProfile" => delete_search_boost_profile,
+        "GetEmbeddingKeys" => get_embedding_keys,
+        "FindSimilarAlbums" => find_similar_albums,
+        "GetAlbumEmbeddingProjection" => get_album_embedding_projection,
+        "GetAlbumClusters" => get_album_clusters,
+        "ClusterAlbums" => cluster_albums,
+        "SearchAlbumsByNaturalLanguage" => search_albums_by_natural_language,
+        "FindSpotifyAlbum" => find_spotify_album,
+      }),
+      "LookupService" => dispatch!(rpc, request, self.lookup_service, {
+        "LookupAlbum" => lookup_album,
+        "GetAggregatedAlbumSearchStatuses" => get_aggregated_album_search_statuses,
+        "PutListLookup" => put_list_lookup,
+        "DeleteListLookup" => delete_list_lookup,
+        "StartArtistIngestion" => start_artist_ingestion,
+        "GetArtistIngestion" => get_artist_ingestion,
+        "LookupMusicBrainzId" => lookup_music_brainz_id,
+        "EnqueueMusicBrainzLookups" => enqueue_music_brainz_lookups,
+      }),
+      "RecommendationService" => dispatch!(rpc, request, self.recommendation_service, {
+        "AssessAlbum" => assess_album,
+        "RecommendAlbums" => recommend_albums,
+        "DefaultQuantileRankAlbumAssessmentSettings" => default_quantile_rank_album_assessment_settings,
+        "DraftSpotifyPlaylist" => draft_spotify_playlist,
+        "CreateSpotifyPlaylist" => create_spotify_playlist,
+        "SyncSpotifyPlaylist" => sync_spotify_playlist,
+        "ExportPlaylist" => export_playlist,
+        "SearchSpotifyTrackIndex" => search_spotify_track_index,
+        "GetRecommendationCuration" => get_recommendation_curation,
+        "UpdateRecommendationCuration" => update_recommendation_curation,
+        "GetGlobalExclusion" => get_global_exclusion,
+        "UpdateGlobalExclusion" => update_global_exclusion,
+        "ImportGlobalExclusion" => import_global_exclusion,
+        "RecommendCuratedAlbums" => recommend_curated_albums,
+        "ListRecommendationDigests" => list_recommendation_digests,
+        "AssessProfileCompatibility" => assess_profile_compatibility,
+        "CreateYearInReview" => create_year_in_review,
+        "GetYearInReview" => get_year_in_review,
+        "ListYearInReviews" => list_year_in_reviews,
+        "GetRecommendationRationale" => get_recommendation_rationale,
+      }),
+      _ => error_response(StatusCode::NOT_FOUND, "Unknown service"),
+    }
+  }
+}
+
+async fn read_body<B>(body: B) -> Result<Vec<u8>, B::Error>
+where
+  B: Body<Data = Bytes>,
+{
+  let mut body = Box::pin(body);
+  let mut content = Vec::new();
+  while let Some(chunk) = body.data().await {
+    content.extend_from_slice(&chunk?);
+  }
+  Ok(content)
+}
+
+impl<B> Service<http::Request<B>> for RestGatewayService
+where
+  B: Body<Data = Bytes> + Send + 'static,
+  B::Error: Display,
+{
+  type Response = http::Response<BoxBody>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, request: http::Request<B>) -> Self::Future {
+    let gateway = Arc::clone(&self.gateway);
+    Box::pin(async move {
+      let (parts, body) = request.into_parts();
+      let path = parts.uri.path();
+      if path == "/api/openapi.json" && parts.method == Method::GET {
+        return Ok(response(StatusCode::OK, gateway.openapi_spec.clone()));
+      }
+      let Some(route) = gateway.routes.iter().find(|route| route.path == path) else {
+        return Ok(error_response(StatusCode::NOT_FOUND, "Unknown route"));
+      };
+      let expected_method = if route.is_get {
+        Method::GET
+      } else {
+        Method::POST
+      };
+      if parts.method != expected_method {
+        return Ok(error_response(
+          StatusCode::METHOD_NOT_ALLOWED,
+          format!("{} expects {}", route.path, expected_method),
+        ));
+      }
+      let content = match read_body(body).await {
+        Ok(content) => content,
+        Err(e) => return Ok(error_response(StatusCode::BAD_REQUEST, e)),
+      };
+      let request = GatewayRequest {
+        metadata: MetadataMap::from_headers(parts.headers),
+        authenticated_key: parts.extensions.get::<AuthenticatedKey>().cloned(),
+        content,
+      };
+      Ok(gateway.handle(route, request).await)
+    })
+  }
+}
@@ -1,26 +1,46 @@
 use crate::{
   albums::album_service::AlbumService,
+  apple_music::apple_music_service::AppleMusicService,
   artists::artist_service::ArtistService,
+  auth::{auth_layer::AuthLayer, auth_service::AuthService},
+  backups::backup_service::BackupService,
+  collections::collection_service::CollectionService,
   context::ApplicationContext,
+  cover_images::cover_image_http_service::CoverImageHttpService,
   crawler::crawler_service::CrawlerService,
-  events::event_service::EventService,
+  discogs::discogs_service::DiscogsService,
+  events::{bootstrap_service::BootstrapService, event_service::EventService},
   files::file_service::FileService,
+  graphql::graphql_http_service::GraphQlHttpService,
+  health::{
+    health_http_service::HealthHttpService, health_interactor::HealthInteractor,
+    health_reporter::run_health_reporter,
+  },
+  listening::listening_event_service::ListeningEventService,
   lookup::LookupService,
   ops::OperationsService,
   parser::parser_service::ParserService,
   profile::profile_service::ProfileService,
   proto::{
-    AlbumServiceServer, ArtistServiceServer, CrawlerServiceServer, EventServiceServer,
-    FileServiceServer, HealthCheckReply, LookupServiceServer, Lute, LuteServer,
-    OperationsServiceServer, ParserServiceServer, ProfileServiceServer,
-    RecommendationServiceServer, SchedulerServiceServer, SpotifyServiceServer, FILE_DESCRIPTOR_SET,
+    AlbumServiceServer, AppleMusicServiceServer, ArtistServiceServer, AuthServiceServer,
+    BackupServiceServer, BootstrapServiceServer, CollectionServiceServer, CrawlerServiceServer,
+    DiscogsServiceServer, EventServiceServer, FileServiceServer, HealthCheckReply,
+    ListeningEventServiceServer, LookupServiceServer, Lute, LuteServer, OperationsServiceServer,
+    ParserServiceServer, ProfileServiceServer, RecommendationServiceServer, SchedulerServiceServer,
+    SettingsServiceServer, SpotifyServiceServer, TidalServiceServer, YouTubeMusicServiceServer,
+    FILE_DESCRIPTOR_SET,
   },
+  rate_limit::{rate_limit_layer::RateLimitLayer, rpc_rate_limiter::RpcRateLimiter},
   recommendations::recommendation_service::RecommendationService,
+  rest_gateway::rest_gateway_service::RestGatewayService,
+  runtime_settings::settings_service::SettingsService,
   scheduler::scheduler_service::SchedulerService,
   spotify::spotify_service::SpotifyService,
+  tidal::tidal_service::TidalService,
+  youtube_music::youtube_music_service::YouTubeMusicService,
 };
 use anyhow::Result;
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{task::spawn, task::JoinHandle};
 use tonic::{transport::Server, Request, Response, Status};
 use tonic_tracing_opentelemetry::middleware::{filters, server::OtelGrpcLayer};
@@ -57,11 +77,46 @@ impl RpcServer {
       .unwrap();
     let addr = self.addr();
     info!(address = addr.to_string(), "Starting RPC server");
+    let graphql_service = self
+      .app_context
+      .settings
+      .graphql
+      .enabled
+      .then(|| GraphQlHttpService::new(Arc::clone(&self.app_context)));
+    let rest_gateway_service = RestGatewayService::new(Arc::clone(&self.app_context)).unwrap();
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    spawn(run_health_reporter(
+      Arc::new(HealthInteractor::new(Arc::clone(&self.app_context))),
+      health_reporter,
+      Duration::from_secs(self.app_context.settings.health.check_interval_seconds),
+    ));
+    let rate_limit_layer =
+      RateLimitLayer::new(self.app_context.settings.rate_limit.enabled.then(|| {
+        Arc::new(RpcRateLimiter::new(
+          &self.app_context.settings.rate_limit,
+          rest_gateway_service.routes(),
+        ))
+      }));
+    let auth_layer = AuthLayer::new(
+      self
+        .app_context
+        .settings
+        .auth
+        .enabled
+        .then(|| Arc::clone(&self.app_context.api_key_interactor)),
+    );
     let server = Server::builder()
       .trace_fn(|_| tracing::info_span!("lute::rpc"))
       .layer(OtelGrpcLayer::default().filter(filters::reject_healthcheck))
+      .layer(auth_layer)
+      .layer(rate_limit_layer)
       .accept_http1(true)
       .add_service(reflection_service)
+      .add_service(health_service)
+      .add_service(HealthHttpService::new(Arc::clone(&self.app_context)))
+      .add_service(CoverImageHttpService::new(Arc::clone(&self.app_context)))
+      .add_optional_service(graphql_service)
+      .add_service(rest_gateway_service)
       .add_service(tonic_web::enable(LuteServer::new(LuteService {})))
       .add_service(tonic_web::enable(FileServiceServer::new(FileService::new(
         Arc::clone(&self.app_context),
@@ -80,6 +135,24 @@ impl RpcServer {
       .add_service(tonic_web::enable(SpotifyServiceServer::new(
         SpotifyService::new(Arc::clone(&self.app_context)),
       )))
+      .add_service(tonic_web::enable(TidalServiceServer::new(
+        TidalService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(AppleMusicServiceServer::new(
+        AppleMusicService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(YouTubeMusicServiceServer::new(
+        YouTubeMusicService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(DiscogsServiceServer::new(
+        DiscogsService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(CollectionServiceServer::new(
+        CollectionService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(ListeningEventServiceServer::new(
+        ListeningEventService::new(Arc::clone(&self.app_context)),
+      )))
       .add_service(tonic_web::enable(OperationsServiceServer::new(
         OperationsService::new(Arc::clone(&self.app_context)),
       )))
@@ -98,8 +171,21 @@ impl RpcServer {
       .add_service(tonic_web::enable(EventServiceServer::new(
         EventService::new(Arc::clone(&self.app_context)),
       )))
+      .add_service(tonic_web::enable(
+        BootstrapServiceServer::new(BootstrapService::new(Arc::clone(&self.app_context)))
+          .max_encoding_message_size(max_message_size),
+      ))
       .add_service(tonic_web::enable(SchedulerServiceServer::new(
         SchedulerService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(AuthServiceServer::new(AuthService::new(
+        Arc::clone(&self.app_context),
+      ))))
+      .add_service(tonic_web::enable(BackupServiceServer::new(
+        BackupService::new(Arc::clone(&self.app_context)),
+      )))
+      .add_service(tonic_web::enable(SettingsServiceServer::new(
+        SettingsService::new(Arc::clone(&self.app_context)),
       )));
 
     spawn(async move {
@@ -0,0 +1,2 @@
+pub mod runtime_settings;
+pub mod settings_service;
@@ -0,0 +1,132 @@
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  settings::{CrawlerRateLimitSettings, Settings, SpotifyBatchWindowSettings},
+};
+use anyhow::{bail, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const OVERRIDES_KEY: &str = "settings:overrides";
+
+/**
+ * Settings that can be changed while the server runs. Unset fields keep the configured value.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsOverrides {
+  pub crawler_rate_limit: Option<CrawlerRateLimitSettings>,
+  pub crawler_refresh_daily_budget: Option<u32>,
+  /**
+   * Only adjusts a batch window configured at startup
+   */
+  pub spotify_batch_window: Option<SpotifyBatchWindowSettings>,
+  /**
+   * Embedding providers whose generation jobs are paused
+   */
+  pub disabled_embedding_providers: Option<Vec<String>>,
+}
+
+impl SettingsOverrides {
+  pub fn validate(&self) -> Result<()> {
+    if let Some(rate_limit) = &self.crawler_rate_limit {
+      if rate_limit.window_seconds == 0 {
+        bail!("Crawler rate limit window must be at least a second");
+      }
+    }
+    if let Some(batch_window) = &self.spotify_batch_window {
+      if batch_window.start_hour > 23 || batch_window.end_hour > 23 {
+        bail!("Spotify batch window hours must be between 0 and 23");
+      }
+    }
+    Ok(())
+  }
+
+  pub fn apply(&self, settings: &Settings) -> Settings {
+    let mut settings = settings.clone();
+    if let Some(rate_limit) = &self.crawler_rate_limit {
+      settings.crawler.rate_limit = rate_limit.clone();
+    }
+    if let Some(daily_budget) = self.crawler_refresh_daily_budget {
+      settings.crawler.refresh.daily_budget = daily_budget;
+    }
+    if let (Some(batch_window), Some(_)) =
+      (&self.spotify_batch_window, &settings.spotify.batch_window)
+    {
+      settings.spotify.batch_window = Some(batch_window.clone());
+    }
+    settings
+  }
+
+  pub fn disabled_embedding_providers(&self) -> Vec<String> {
+    self
+      .disabled_embedding_providers
+      .clone()
+      .unwrap_or_default()
+  }
+}
+
+/**
+ * Configured settings with the overrides saved in the key-value store applied. Readers that should
+ * pick up changes without a restart go through here instead of holding on to `Settings`.
+ */
+pub struct RuntimeSettings {
+  settings: Arc<Settings>,
+  kv: Arc<KeyValueStore>,
+}
+
+impl RuntimeSettings {
+  pub fn new(settings: Arc<Settings>, kv: Arc<KeyValueStore>) -> Self {
+    Self { settings, kv }
+  }
+
+  pub async fn get_overrides(&self) -> Result<SettingsOverrides> {
+    Ok(self.kv.get(OVERRIDES_KEY).await?.unwrap_or_default())
+  }
+
+  pub async fn put_overrides(&self, overrides: &SettingsOverrides) -> Result<()> {
+    overrides.validate()?;
+    self.kv.set(OVERRIDES_KEY, overrides.clone(), None).await
+  }
+
+  pub async fn get(&self) -> Result<Settings> {
+    Ok(self.get_overrides().await?.apply(&self.settings))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_apply_overrides() {
+    let mut settings = Settings::default();
+    settings.crawler.rate_limit.max_requests = 100;
+    settings.crawler.refresh.daily_budget = 500;
+
+    let overrides = SettingsOverrides {
+      crawler_rate_limit: Some(CrawlerRateLimitSettings {
+        window_seconds: 3600,
+        max_requests: 20,
+      }),
+      spotify_batch_window: Some(SpotifyBatchWindowSettings {
+        start_hour: 1,
+        end_hour: 5,
+        max_requests: 1000,
+      }),
+      ..Default::default()
+    };
+    let applied = overrides.apply(&settings);
+    assert_eq!(applied.crawler.rate_limit.max_requests, 20);
+    assert_eq!(applied.crawler.refresh.daily_budget, 500);
+    assert_eq!(applied.spotify.batch_window, None);
+
+    assert!(SettingsOverrides {
+      crawler_rate_limit: Some(CrawlerRateLimitSettings {
+        window_seconds: 0,
+        max_requests: 20,
+      }),
+      ..Default::default()
+    }
+    .validate()
+    .is_err());
+  }
+}
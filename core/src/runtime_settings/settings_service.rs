@@ -0,0 +1,217 @@
+use super::runtime_settings::SettingsOverrides;
+use crate::{
+  context::ApplicationContext,
+  proto::{self, GetSettingsReply, UpdateSettingsRequest},
+  scheduler::{job_name::JobName, scheduler::JobParametersBuilder},
+  settings::{CrawlerRateLimitSettings, Settings, SpotifyBatchWindowSettings},
+};
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+impl From<CrawlerRateLimitSettings> for proto::CrawlerRateLimit {
+  fn from(val: CrawlerRateLimitSettings) -> Self {
+    proto::CrawlerRateLimit {
+      window_seconds: val.window_seconds,
+      max_requests: val.max_requests,
+    }
+  }
+}
+
+impl From<proto::CrawlerRateLimit> for CrawlerRateLimitSettings {
+  fn from(val: proto::CrawlerRateLimit) -> Self {
+    CrawlerRateLimitSettings {
+      window_seconds: val.window_seconds,
+      max_requests: val.max_requests,
+    }
+  }
+}
+
+impl From<SpotifyBatchWindowSettings> for proto::SpotifyBatchWindow {
+  fn from(val: SpotifyBatchWindowSettings) -> Self {
+    proto::SpotifyBatchWindow {
+      start_hour: val.start_hour,
+      end_hour: val.end_hour,
+      max_requests: val.max_requests,
+    }
+  }
+}
+
+impl From<proto::SpotifyBatchWindow> for SpotifyBatchWindowSettings {
+  fn from(val: proto::SpotifyBatchWindow) -> Self {
+    SpotifyBatchWindowSettings {
+      start_hour: val.start_hour,
+      end_hour: val.end_hour,
+      max_requests: val.max_requests,
+    }
+  }
+}
+
+impl From<SettingsOverrides> for proto::SettingsOverrides {
+  fn from(val: SettingsOverrides) -> Self {
+    proto::SettingsOverrides {
+      crawler_rate_limit: val.crawler_rate_limit.map(Into::into),
+      crawler_refresh_daily_budget: val.crawler_refresh_daily_budget,
+      spotify_batch_window: val.spotify_batch_window.map(Into::into),
+      disabled_embedding_providers: val
+        .disabled_embedding_providers
+        .map(|values| proto::StringList { values }),
+    }
+  }
+}
+
+impl From<proto::SettingsOverrides> for SettingsOverrides {
+  fn from(val: proto::SettingsOverrides) -> Self {
+    SettingsOverrides {
+      crawler_rate_limit: val.crawler_rate_limit.map(Into::into),
+      crawler_refresh_daily_budget: val.crawler_refresh_daily_budget,
+      spotify_batch_window: val.spotify_batch_window.map(Into::into),
+      disabled_embedding_providers: val.disabled_embedding_providers.map(|list| list.values),
+    }
+  }
+}
+
+fn to_runtime_settings(
+  settings: Settings,
+  overrides: &SettingsOverrides,
+) -> proto::RuntimeSettings {
+  proto::RuntimeSettings {
+    crawler_rate_limit: Some(settings.crawler.rate_limit.into()),
+    crawler_refresh_daily_budget: settings.crawler.refresh.daily_budget,
+    spotify_batch_window: settings.spotify.batch_window.map(Into::into),
+    disabled_embedding_providers: overrides.disabled_embedding_providers(),
+  }
+}
+
+pub struct SettingsService {
+  app_context: Arc<ApplicationContext>,
+}
+
+impl SettingsService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self { app_context }
+  }
+
+  async fn get_settings_reply(&self) -> Result<GetSettingsReply> {
+    let overrides = self.app_context.runtime_settings.get_overrides().await?;
+    let settings = overrides.apply(&self.app_context.settings);
+    let mut embedding_providers = self
+      .app_context
+      .embedding_provider_interactor
+      .providers
+      .keys()
+      .cloned()
+      .collect::<Vec<_>>();
+    embedding_providers.sort();
+    Ok(GetSettingsReply {
+      effective: Some(to_runtime_settings(settings, &overrides)),
+      overrides: Some(overrides.into()),
+      embedding_providers,
+    })
+  }
+
+  /**
+   * Saves the overrides, then brings the parts of the server that don't read settings on every use
+   * in line with them: the crawler's request window job and the embedding generation processors
+   */
+  async fn apply_overrides(&self, overrides: SettingsOverrides) -> Result<()> {
+    let runtime_settings = &self.app_context.runtime_settings;
+    let previous = runtime_settings.get().await?;
+    let previous_overrides = runtime_settings.get_overrides().await?;
+    runtime_settings.put_overrides(&overrides).await?;
+    let current = runtime_settings.get().await?;
+
+    let window_seconds = current.crawler.rate_limit.window_seconds;
+    if previous.crawler.rate_limit.window_seconds != window_seconds {
+      let window = TimeDelta::try_seconds(window_seconds as i64).unwrap();
+      self
+        .app_context
+        .scheduler
+        .put(
+          JobParametersBuilder::default()
+            .name(JobName::ResetCrawlerRequestWindow)
+            .interval(window)
+            .next_execution(Utc::now().naive_utc() + window)
+            .build()?,
+        )
+        .await?;
+    }
+
+    let disabled = overrides.disabled_embedding_providers();
+    let previously_disabled = previous_overrides.disabled_embedding_providers();
+    for (name, provider) in self
+      .app_context
+      .embedding_provider_interactor
+      .providers
+      .iter()
+    {
+      let job_name = provider.job_name();
+      if disabled.contains(name) && !previously_disabled.contains(name) {
+        self
+          .app_context
+          .scheduler
+          .pause_processor(&job_name, None)
+          .await?;
+      } else if !disabled.contains(name) && previously_disabled.contains(name) {
+        self
+          .app_context
+          .scheduler
+          .resume_processor(&job_name)
+          .await?;
+      }
+    }
+
+    info!("Settings overrides updated");
+    Ok(())
+  }
+}
+
+#[tonic::async_trait]
+impl proto::SettingsService for SettingsService {
+  async fn get_settings(&self, _: Request<()>) -> Result<Response<GetSettingsReply>, Status> {
+    let reply = self.get_settings_reply().await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Failed to get settings")
+    })?;
+    Ok(Response::new(reply))
+  }
+
+  /**
+   * Replaces every override, so fields left out go back to their configured values
+   */
+  async fn update_settings(
+    &self,
+    request: Request<UpdateSettingsRequest>,
+  ) -> Result<Response<GetSettingsReply>, Status> {
+    let overrides = SettingsOverrides::from(request.into_inner().overrides.unwrap_or_default());
+    overrides
+      .validate()
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self.apply_overrides(overrides).await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Failed to update settings")
+    })?;
+    let reply = self.get_settings_reply().await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Failed to get settings")
+    })?;
+    Ok(Response::new(reply))
+  }
+
+  async fn reset_settings(&self, _: Request<()>) -> Result<Response<GetSettingsReply>, Status> {
+    self
+      .apply_overrides(SettingsOverrides::default())
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Failed to reset settings")
+      })?;
+    let reply = self.get_settings_reply().await.map_err(|e| {
+      error!("Error: {:?}", e);
+      Status::internal("Failed to get settings")
+    })?;
+    Ok(Response::new(reply))
+  }
+}
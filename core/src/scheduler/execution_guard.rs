@@ -0,0 +1,36 @@
+use super::scheduler_repository::{Job, SchedulerRepository};
+use anyhow::Result;
+use std::sync::Arc;
+
+/**
+ * Keeps a retried run from repeating work when its claim expired after the work was done. Job
+ * processors skip committed runs and commit each run once its executor succeeds.
+ */
+pub struct ExecutionGuard {
+  token: String,
+  scheduler_repository: Arc<SchedulerRepository>,
+}
+
+impl ExecutionGuard {
+  pub fn new(job: &Job, scheduler_repository: Arc<SchedulerRepository>) -> Self {
+    Self {
+      token: job.execution_token(),
+      scheduler_repository,
+    }
+  }
+
+  pub fn token(&self) -> &str {
+    &self.token
+  }
+
+  pub async fn is_committed(&self) -> Result<bool> {
+    self
+      .scheduler_repository
+      .is_run_committed(&self.token)
+      .await
+  }
+
+  pub async fn commit(&self) -> Result<()> {
+    self.scheduler_repository.commit_run(&self.token).await
+  }
+}
@@ -6,6 +6,7 @@ use strum_macros;
 pub enum JobName {
   ResetCrawlerRequestWindow,
   CrawlNewAlbums,
+  RefreshStaleFiles,
   ChangeEventSubscriberStatus,
   DeleteExpiredKVItems,
   IndexSpotifyTracks,
@@ -16,4 +17,25 @@ pub enum JobName {
   GenerateOpenAIEmbeddings,
   GenerateVoyageAIEmbeddings,
   GenerateOllamaEmbeddings,
+  GenerateOnnxEmbeddings,
+  ImportLastFmTopAlbums,
+  SyncLastFmScrobbles,
+  SyncListenBrainzListens,
+  SnapshotProfiles,
+  CheckDocumentStoreQuotas,
+  CheckEventSubscriberLag,
+  CreateRecommendationDigests,
+  LookupMusicBrainzId,
+  LookupDiscogsRelease,
+  RefreshDiscogsPrices,
+  CacheCoverImage,
+  EnforceSpotifyBatchWindow,
+  ComputeDescriptorSimilarities,
+  DetectAlbumDuplicates,
+  CreateBackup,
+  EnforceFileRetention,
+  ExpireLookup,
+  CreateYearInReviews,
+  ClusterAlbums,
+  PruneCrawlHistory,
 }
@@ -1,3 +1,4 @@
+pub mod execution_guard;
 pub mod job_name;
 pub mod scheduler;
 pub mod scheduler_repository;
@@ -1,6 +1,7 @@
 use super::{
+  execution_guard::ExecutionGuard,
   job_name::JobName,
-  scheduler_repository::{Job, SchedulerRepository},
+  scheduler_repository::{Job, JobRun, SchedulerRepository},
 };
 use crate::{
   context::ApplicationContext,
@@ -262,15 +263,32 @@ impl JobProcessor {
           match job_receiver.await {
             Ok(jobs) => {
               if !jobs.is_empty() {
-                if let Err(e) = executor
-                  .execute(jobs.clone(), Arc::clone(&app_context))
+                let runs = uncommitted_runs(&jobs, &scheduler_repo).await;
+                match executor
+                  .execute(
+                    runs.iter().map(|(job, _)| job.clone()).collect(),
+                    Arc::clone(&app_context),
+                  )
                   .await
                 {
-                  error!(
-                    message = e.to_string(),
-                    job_name = job_name.to_string(),
-                    "Failed to execute jobs"
-                  );
+                  Ok(()) => {
+                    for (_, guard) in &runs {
+                      if let Err(e) = guard.commit().await {
+                        error!(
+                          message = e.to_string(),
+                          token = guard.token(),
+                          "Failed to commit job run"
+                        );
+                      }
+                    }
+                  }
+                  Err(e) => {
+                    error!(
+                      message = e.to_string(),
+                      job_name = job_name.to_string(),
+                      "Failed to execute jobs"
+                    );
+                  }
                 }
 
                 if let Err(e) = scheduler_repo.update_jobs_after_execution(jobs).await {
@@ -301,6 +319,39 @@ impl JobProcessor {
   }
 }
 
+/**
+ * Leaves out jobs whose run already did its work before the claim expired, so a retry doesn't
+ * repeat it. A job whose commit status can't be read is run again.
+ */
+async fn uncommitted_runs(
+  jobs: &[Job],
+  scheduler_repository: &Arc<SchedulerRepository>,
+) -> Vec<(Job, ExecutionGuard)> {
+  let mut runs = vec![];
+  for job in jobs {
+    let guard = ExecutionGuard::new(job, Arc::clone(scheduler_repository));
+    match guard.is_committed().await {
+      Ok(true) => {
+        info!(
+          job_id = job.id.as_str(),
+          token = guard.token(),
+          "Skipping job run that already committed"
+        );
+      }
+      Ok(false) => runs.push((job.clone(), guard)),
+      Err(e) => {
+        error!(
+          message = e.to_string(),
+          token = guard.token(),
+          "Failed to get job run commit status"
+        );
+        runs.push((job.clone(), guard));
+      }
+    }
+  }
+  runs
+}
+
 pub struct SchedulerMonitor {}
 pub struct Scheduler {
   scheduler_repository: Arc<SchedulerRepository>,
@@ -329,6 +380,13 @@ impl Scheduler {
     self.scheduler_repository.delete_all_jobs().await
   }
 
+  pub async fn raise_priority(&self, job_id: &str, priority: Priority) -> Result<()> {
+    self
+      .scheduler_repository
+      .raise_priority(job_id, priority)
+      .await
+  }
+
   pub async fn delete_jobs_by_name(&self, job_name: JobName) -> Result<()> {
     self
       .scheduler_repository
@@ -377,6 +435,35 @@ impl Scheduler {
       .await
   }
 
+  /**
+   * Reports runs left unfinished by a previous process, must run before processors start
+   */
+  pub async fn recover_interrupted_runs(&self) -> Result<Vec<JobRun>> {
+    let runs = self.scheduler_repository.mark_interrupted_runs().await?;
+    for run in &runs {
+      warn!(
+        job_id = run.job_id.as_str(),
+        job_name = run.job_name.to_string(),
+        token = run.token.as_str(),
+        attempts = run.attempts,
+        committed = run.committed_at.is_some(),
+        payload = run
+          .payload
+          .as_ref()
+          .map(|p| String::from_utf8_lossy(p).to_string()),
+        "Job run was interrupted"
+      );
+    }
+    if !runs.is_empty() {
+      info!(count = runs.len(), "Recovered interrupted job runs");
+    }
+    Ok(runs)
+  }
+
+  pub async fn get_interrupted_runs(&self) -> Result<Vec<JobRun>> {
+    self.scheduler_repository.find_interrupted_runs().await
+  }
+
   pub async fn get_processor_status(&self, job_name: &JobName) -> Result<JobProcessorStatus> {
     self.processor_status_repository.get_status(job_name).await
   }
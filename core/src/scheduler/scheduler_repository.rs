@@ -5,7 +5,7 @@ use chrono::{Duration, NaiveDateTime, TimeDelta, Utc};
 use rusqlite::{params, types::Value};
 use serde::de::DeserializeOwned;
 use std::{collections::HashMap, rc::Rc, str::FromStr, sync::Arc};
-use tracing::{error, instrument};
+use tracing::{error, instrument, warn};
 
 #[derive(Clone)]
 pub struct SchedulerRepository {
@@ -26,6 +26,18 @@ pub struct Job {
 }
 
 impl Job {
+  /**
+   * Identifies a single scheduled execution of the job, stays the same when a run is retried
+   * after its claim expires
+   */
+  pub fn execution_token(&self) -> String {
+    format!(
+      "{}:{}",
+      self.id,
+      self.next_execution.and_utc().timestamp_millis()
+    )
+  }
+
   pub fn payload<T: DeserializeOwned>(&self) -> Result<T> {
     self
       .payload
@@ -36,6 +48,37 @@ impl Job {
   }
 }
 
+#[derive(Debug, Clone)]
+pub struct JobRun {
+  pub token: String,
+  pub job_id: String,
+  pub job_name: JobName,
+  pub payload: Option<Vec<u8>>,
+  pub attempts: u32,
+  pub started_at: NaiveDateTime,
+  pub committed_at: Option<NaiveDateTime>,
+  pub interrupted_at: Option<NaiveDateTime>,
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for JobRun {
+  type Error = rusqlite::Error;
+
+  fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+    Ok(JobRun {
+      token: row.get(0)?,
+      job_id: row.get(1)?,
+      job_name: JobName::from_str(row.get::<_, String>(2)?.as_str()).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+      })?,
+      payload: row.get(3)?,
+      attempts: row.get(4)?,
+      started_at: row.get(5)?,
+      committed_at: row.get(6)?,
+      interrupted_at: row.get(7)?,
+    })
+  }
+}
+
 impl SchedulerRepository {
   pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
     Self { sqlite_connection }
@@ -316,11 +359,217 @@ impl SchedulerRepository {
           chrono::Utc::now().naive_utc(),
         )
         .await?;
+      for run in self.start_runs(&jobs).await? {
+        if run.attempts > 1 {
+          warn!(
+            job_id = run.job_id.as_str(),
+            token = run.token.as_str(),
+            attempts = run.attempts,
+            committed = run.committed_at.is_some(),
+            "Retrying job after an interrupted run"
+          );
+        }
+      }
     }
 
     Ok(jobs)
   }
 
+  #[instrument(skip_all, name = "SchedulerRepository::start_runs", fields(count = jobs.len()))]
+  pub async fn start_runs(&self, jobs: &[Job]) -> Result<Vec<JobRun>> {
+    let records = jobs
+      .iter()
+      .map(|job| {
+        (
+          job.execution_token(),
+          job.id.clone(),
+          job.name.to_string(),
+          job.payload.clone(),
+        )
+      })
+      .collect::<Vec<_>>();
+    let runs = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let mut runs = Vec::new();
+        {
+          let mut statement = tx.prepare(
+            "
+            INSERT INTO scheduler_job_runs (token, job_id, job_name, payload, started_at)
+            VALUES (?, ?, ?, ?, datetime('now'))
+            ON CONFLICT (token) DO UPDATE SET
+              attempts = attempts + 1,
+              started_at = excluded.started_at,
+              interrupted_at = NULL
+            RETURNING
+              token,
+              job_id,
+              job_name,
+              payload,
+              attempts,
+              started_at,
+              committed_at,
+              interrupted_at
+            ",
+          )?;
+          for (token, job_id, job_name, payload) in records {
+            let run = statement.query_row(params![token, job_id, job_name, payload], |row| {
+              JobRun::try_from(row)
+            })?;
+            runs.push(run);
+          }
+        }
+        tx.commit()?;
+        Ok::<_, rusqlite::Error>(runs)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to start job runs");
+        anyhow!("Failed to start job runs")
+      })??;
+
+    Ok(runs)
+  }
+
+  #[instrument(skip(self), name = "SchedulerRepository::is_run_committed")]
+  pub async fn is_run_committed(&self, token: &str) -> Result<bool> {
+    let token = token.to_string();
+    let committed = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        conn.query_row(
+          "
+          SELECT EXISTS (
+            SELECT 1 FROM scheduler_job_runs WHERE token = ? AND committed_at IS NOT NULL
+          )
+          ",
+          [token],
+          |row| row.get::<_, bool>(0),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get job run commit status"
+        );
+        anyhow!("Failed to get job run commit status")
+      })??;
+
+    Ok(committed)
+  }
+
+  #[instrument(skip(self), name = "SchedulerRepository::commit_run")]
+  pub async fn commit_run(&self, token: &str) -> Result<()> {
+    let token = token.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "UPDATE scheduler_job_runs SET committed_at = datetime('now') WHERE token = ?",
+          [token],
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to commit job run");
+        anyhow!("Failed to commit job run")
+      })??;
+
+    Ok(())
+  }
+
+  /**
+   * Marks every unfinished run as interrupted and returns the newly interrupted ones. Only call
+   * this before any processor has started, otherwise live runs will be reported.
+   */
+  #[instrument(skip(self), name = "SchedulerRepository::mark_interrupted_runs")]
+  pub async fn mark_interrupted_runs(&self) -> Result<Vec<JobRun>> {
+    let runs = self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          UPDATE scheduler_job_runs
+          SET interrupted_at = datetime('now')
+          WHERE interrupted_at IS NULL
+          RETURNING
+            token,
+            job_id,
+            job_name,
+            payload,
+            attempts,
+            started_at,
+            committed_at,
+            interrupted_at
+          ",
+        )?;
+        let rows = statement
+          .query_map([], |row| JobRun::try_from(row))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to mark interrupted job runs"
+        );
+        anyhow!("Failed to mark interrupted job runs")
+      })??;
+
+    Ok(runs)
+  }
+
+  #[instrument(skip(self), name = "SchedulerRepository::find_interrupted_runs")]
+  pub async fn find_interrupted_runs(&self) -> Result<Vec<JobRun>> {
+    let runs = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT
+            token,
+            job_id,
+            job_name,
+            payload,
+            attempts,
+            started_at,
+            committed_at,
+            interrupted_at
+          FROM scheduler_job_runs
+          WHERE interrupted_at IS NOT NULL
+          ORDER BY interrupted_at DESC
+          ",
+        )?;
+        let rows = statement
+          .query_map([], |row| JobRun::try_from(row))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find interrupted job runs"
+        );
+        anyhow!("Failed to find interrupted job runs")
+      })??;
+
+    Ok(runs)
+  }
+
   #[instrument(skip(self), name = "SchedulerRepository::count_jobs_by_name")]
   pub async fn count_jobs_by_name(&self, job_name: JobName) -> Result<usize> {
     let count = self
@@ -568,8 +817,10 @@ impl SchedulerRepository {
       .write()
       .await?
       .interact(move |conn| {
-        let mut statement = conn.prepare("DELETE FROM scheduler_jobs WHERE id = ?")?;
-        statement.execute([job_id])?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM scheduler_jobs WHERE id = ?", [&job_id])?;
+        tx.execute("DELETE FROM scheduler_job_runs WHERE job_id = ?", [&job_id])?;
+        tx.commit()?;
         Ok(())
       })
       .await
@@ -579,6 +830,35 @@ impl SchedulerRepository {
       })?
   }
 
+  /**
+   * Raises the priority of a job that is waiting to run, leaving claimed jobs and jobs already at
+   * or above the priority alone
+   */
+  #[instrument(skip(self), name = "SchedulerRepository::raise_priority")]
+  pub async fn raise_priority(&self, job_id: &str, priority: Priority) -> Result<()> {
+    let job_id = job_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          UPDATE scheduler_jobs
+          SET priority = ?
+          WHERE id = ? AND claimed_at IS NULL AND priority > ?
+          ",
+          params![priority as u32, job_id, priority as u32],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to raise job priority");
+        anyhow!("Failed to raise job priority")
+      })?
+  }
+
   #[instrument(skip(self), name = "SchedulerRepository::delete_all_jobs")]
   pub async fn delete_all_jobs(&self) -> Result<()> {
     self
@@ -586,8 +866,10 @@ impl SchedulerRepository {
       .write()
       .await?
       .interact(move |conn| {
-        let mut statement = conn.prepare("DELETE FROM scheduler_jobs")?;
-        statement.execute([])?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM scheduler_jobs", [])?;
+        tx.execute("DELETE FROM scheduler_job_runs", [])?;
+        tx.commit()?;
         Ok(())
       })
       .await
@@ -604,8 +886,16 @@ impl SchedulerRepository {
       .write()
       .await?
       .interact(move |conn| {
-        let mut statement = conn.prepare("DELETE FROM scheduler_jobs WHERE name = ?")?;
-        statement.execute([job_name.to_string()])?;
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM scheduler_jobs WHERE name = ?",
+          [job_name.to_string()],
+        )?;
+        tx.execute(
+          "DELETE FROM scheduler_job_runs WHERE job_name = ?",
+          [job_name.to_string()],
+        )?;
+        tx.commit()?;
         Ok(())
       })
       .await
@@ -655,6 +945,10 @@ impl SchedulerRepository {
       .interact(move |conn| {
         let tx = conn.transaction()?;
         for job in jobs {
+          tx.execute(
+            "DELETE FROM scheduler_job_runs WHERE token = ?",
+            [job.execution_token()],
+          )?;
           if let Some(interval_seconds) = job.interval_seconds {
             let next_execution = last_execution
               + TimeDelta::try_seconds(interval_seconds as i64).expect("Invalid interval");
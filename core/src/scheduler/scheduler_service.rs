@@ -1,7 +1,7 @@
 use super::{
   job_name::JobName,
   scheduler::{JobParametersBuilder, JobProcessorStatus},
-  scheduler_repository::Job,
+  scheduler_repository::{Job, JobRun},
 };
 use crate::{context::ApplicationContext, proto};
 use chrono::{NaiveDateTime, TimeDelta};
@@ -24,6 +24,21 @@ impl From<Job> for proto::Job {
   }
 }
 
+impl From<JobRun> for proto::JobRun {
+  fn from(val: JobRun) -> Self {
+    proto::JobRun {
+      token: val.token,
+      job_id: val.job_id,
+      job_name: val.job_name.to_string(),
+      payload: val.payload,
+      attempts: val.attempts,
+      started_at: val.started_at.to_string(),
+      committed_at: val.committed_at.map(|d| d.to_string()),
+      interrupted_at: val.interrupted_at.map(|d| d.to_string()),
+    }
+  }
+}
+
 impl From<JobProcessorStatus> for i32 {
   fn from(val: JobProcessorStatus) -> Self {
     match val {
@@ -154,6 +169,22 @@ impl proto::SchedulerService for SchedulerService {
     }))
   }
 
+  async fn get_interrupted_job_runs(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::GetInterruptedJobRunsReply>, Status> {
+    let runs = self
+      .app_context
+      .scheduler
+      .get_interrupted_runs()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+
+    Ok(Response::new(proto::GetInterruptedJobRunsReply {
+      runs: runs.into_iter().map(|r| r.into()).collect(),
+    }))
+  }
+
   async fn put_job(&self, request: Request<proto::PutJobRequest>) -> Result<Response<()>, Status> {
     let params = request.into_inner();
     let mut builder = JobParametersBuilder::default();
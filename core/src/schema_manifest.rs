@@ -0,0 +1,500 @@
+use crate::{
+  albums::{album_read_model::EMBEDDING_BODY_VERSION, redis_album_search_index},
+  context::ApplicationContext,
+  events::event_repository::EventRepository,
+  recommendations::redis_spotify_track_search_index,
+  sqlite::latest_migration_version,
+};
+use anyhow::{anyhow, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaVersions {
+  pub sqlite: u32,
+  pub album_index: u32,
+  pub spotify_track_index: u32,
+  pub album_embedding_body: u32,
+}
+
+/**
+ * Every combination of schema versions that has shipped, oldest first. A release that bumps any
+ * version appends the combination it expects, and upgrades walk this list in order.
+ */
+pub const SCHEMA_MANIFEST: &[SchemaVersions] = &[
+  SchemaVersions {
+    sqlite: 25,
+    album_index: 8,
+    spotify_track_index: 2,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 25,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 26,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 27,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 28,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 29,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 30,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 31,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 32,
+    album_index: 8,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 33,
+    album_index: 9,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 34,
+    album_index: 9,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 35,
+    album_index: 9,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 36,
+    album_index: 9,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 37,
+    album_index: 9,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 38,
+    album_index: 10,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 39,
+    album_index: 10,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 40,
+    album_index: 10,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 41,
+    album_index: 10,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 42,
+    album_index: 10,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 42,
+    album_index: 11,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 43,
+    album_index: 11,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 44,
+    album_index: 11,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 45,
+    album_index: 12,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 47,
+    album_index: 12,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 48,
+    album_index: 12,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+  SchemaVersions {
+    sqlite: 48,
+    album_index: 13,
+    spotify_track_index: 3,
+    album_embedding_body: 1,
+  },
+];
+
+const APPLIED_VERSIONS_KEY: &str = "schema_manifest:applied";
+const UPGRADE_PROGRESS_KEY: &str = "schema_manifest:upgrade_progress";
+
+pub fn compiled_schema_versions() -> SchemaVersions {
+  SchemaVersions {
+    sqlite: latest_migration_version(),
+    album_index: redis_album_search_index::INDEX_VERSION,
+    spotify_track_index: redis_spotify_track_search_index::INDEX_VERSION,
+    album_embedding_body: EMBEDDING_BODY_VERSION,
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaUpgradeStep {
+  MigrateSqlite(u32),
+  SetupAlbumIndex,
+  SetupSpotifyTrackIndex,
+  RegenerateAlbumEmbeddings,
+}
+
+impl SchemaUpgradeStep {
+  pub fn describe(&self) -> String {
+    match self {
+      SchemaUpgradeStep::MigrateSqlite(version) => format!("Migrate sqlite to {}", version),
+      SchemaUpgradeStep::SetupAlbumIndex => "Set up album index".to_string(),
+      SchemaUpgradeStep::SetupSpotifyTrackIndex => "Set up spotify track index".to_string(),
+      SchemaUpgradeStep::RegenerateAlbumEmbeddings => "Regenerate album embeddings".to_string(),
+    }
+  }
+}
+
+/**
+ * Steps that take a deployment from `from` to `to`, walking each intermediate manifest entry.
+ * Fails if either end isn't in the manifest or `to` precedes `from`, since downgrades aren't
+ * supported.
+ */
+pub fn plan_schema_upgrade(
+  manifest: &[SchemaVersions],
+  from: &SchemaVersions,
+  to: &SchemaVersions,
+) -> Result<Vec<SchemaUpgradeStep>> {
+  let position = |versions: &SchemaVersions| {
+    manifest
+      .iter()
+      .position(|entry| entry == versions)
+      .ok_or_else(|| anyhow!("Schema versions {:?} are not in the manifest", versions))
+  };
+  let (from_position, to_position) = (position(from)?, position(to)?);
+  if to_position < from_position {
+    return Err(anyhow!(
+      "Cannot downgrade schema from {:?} to {:?}",
+      from,
+      to
+    ));
+  }
+  let mut steps = Vec::new();
+  for window in manifest[from_position..=to_position].windows(2) {
+    let (previous, next) = (&window[0], &window[1]);
+    if next.sqlite != previous.sqlite {
+      steps.push(SchemaUpgradeStep::MigrateSqlite(next.sqlite));
+    }
+  }
+  if to.album_index != from.album_index {
+    steps.push(SchemaUpgradeStep::SetupAlbumIndex);
+  }
+  if to.spotify_track_index != from.spotify_track_index {
+    steps.push(SchemaUpgradeStep::SetupSpotifyTrackIndex);
+  }
+  if to.album_embedding_body != from.album_embedding_body {
+    steps.push(SchemaUpgradeStep::RegenerateAlbumEmbeddings);
+  }
+  Ok(steps)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaStatus {
+  UpToDate,
+  UpgradePending {
+    applied: SchemaVersions,
+    compiled: SchemaVersions,
+  },
+}
+
+async fn initialize_schema(app_context: &Arc<ApplicationContext>) -> Result<SchemaStatus> {
+  app_context.sqlite_connection.migrate_to_latest().await?;
+  app_context
+    .kv
+    .set(APPLIED_VERSIONS_KEY, compiled_schema_versions(), None)
+    .await?;
+  Ok(SchemaStatus::UpToDate)
+}
+
+/**
+ * Startup guard, run before anything migrates sqlite. Refuses to run against a combination of
+ * versions this build doesn't know, which covers both drift between stores and a rollback to an
+ * older build. A known but older combination is reported as pending, and startup runs the
+ * coordinated upgrade to take every store forward in order before anything else uses them. Only a
+ * new database, or one from before the manifest, is migrated straight to the latest version.
+ */
+pub async fn check_schema_versions(app_context: Arc<ApplicationContext>) -> Result<SchemaStatus> {
+  let compiled = compiled_schema_versions();
+  if SCHEMA_MANIFEST.last() != Some(&compiled) {
+    return Err(anyhow!(
+      "Schema versions {:?} are missing from the manifest",
+      compiled
+    ));
+  }
+  let sqlite_version = app_context.sqlite_connection.current_version().await?;
+  if sqlite_version == 0 {
+    info!("New database, initializing schema at the current build's versions");
+    return initialize_schema(&app_context).await;
+  }
+  let Some(applied) = app_context
+    .kv
+    .get::<SchemaVersions>(APPLIED_VERSIONS_KEY)
+    .await?
+  else {
+    info!("No applied schema versions recorded, assuming the current build's");
+    return initialize_schema(&app_context).await;
+  };
+  if sqlite_version != applied.sqlite {
+    return Err(anyhow!(
+      "Sqlite is at version {}, but the applied schema versions expect {}",
+      sqlite_version,
+      applied.sqlite
+    ));
+  }
+  if applied == compiled {
+    return Ok(SchemaStatus::UpToDate);
+  }
+  plan_schema_upgrade(SCHEMA_MANIFEST, &applied, &compiled)?;
+  warn!(
+    applied = ?applied,
+    compiled = ?compiled,
+    "Coordinated schema upgrade pending"
+  );
+  Ok(SchemaStatus::UpgradePending { applied, compiled })
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SchemaUpgradeProgress {
+  pub steps: Vec<String>,
+  pub completed_steps: usize,
+  pub running: bool,
+  pub error: Option<String>,
+}
+
+pub async fn get_applied_schema_versions(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Option<SchemaVersions>> {
+  app_context.kv.get(APPLIED_VERSIONS_KEY).await
+}
+
+pub async fn get_schema_upgrade_progress(
+  app_context: Arc<ApplicationContext>,
+) -> Result<Option<SchemaUpgradeProgress>> {
+  app_context.kv.get(UPGRADE_PROGRESS_KEY).await
+}
+
+/**
+ * Marks an upgrade left running by a previous process as interrupted
+ */
+pub async fn recover_schema_upgrade_progress(app_context: Arc<ApplicationContext>) -> Result<()> {
+  if let Some(mut progress) = get_schema_upgrade_progress(Arc::clone(&app_context)).await? {
+    if progress.running {
+      warn!("Schema upgrade was interrupted by a restart");
+      progress.running = false;
+      progress.error = Some("Interrupted by a restart".to_string());
+      app_context
+        .kv
+        .set(UPGRADE_PROGRESS_KEY, progress, None)
+        .await?;
+    }
+  }
+  Ok(())
+}
+
+async fn run_schema_upgrade_step(
+  app_context: &Arc<ApplicationContext>,
+  step: &SchemaUpgradeStep,
+) -> Result<()> {
+  match step {
+    SchemaUpgradeStep::MigrateSqlite(version) => {
+      app_context
+        .sqlite_connection
+        .migrate_to_version(*version)
+        .await
+    }
+    SchemaUpgradeStep::SetupAlbumIndex => app_context.album_interactor.setup_search_index().await,
+    SchemaUpgradeStep::SetupSpotifyTrackIndex => {
+      app_context.spotify_track_search_index.setup_index().await
+    }
+    SchemaUpgradeStep::RegenerateAlbumEmbeddings => {
+      let event_repository = EventRepository::new(Arc::clone(&app_context.sqlite_connection));
+      for provider_name in app_context.embedding_provider_interactor.providers.keys() {
+        event_repository
+          .delete_cursor(&format!("schedule_album_embedding_jobs:{}", provider_name))
+          .await?;
+      }
+      Ok(())
+    }
+  }
+}
+
+/**
+ * Runs the coordinated upgrade from the applied versions to this build's, recording progress
+ * after every step. The applied versions are only advanced once every step has succeeded.
+ */
+pub async fn run_schema_upgrade(app_context: Arc<ApplicationContext>) -> Result<()> {
+  let compiled = compiled_schema_versions();
+  let applied = app_context
+    .kv
+    .get::<SchemaVersions>(APPLIED_VERSIONS_KEY)
+    .await?
+    .unwrap_or(compiled);
+  let steps = plan_schema_upgrade(SCHEMA_MANIFEST, &applied, &compiled)?;
+  let mut progress = SchemaUpgradeProgress {
+    steps: steps.iter().map(|step| step.describe()).collect(),
+    completed_steps: 0,
+    running: true,
+    error: None,
+  };
+  app_context
+    .kv
+    .set(UPGRADE_PROGRESS_KEY, progress.clone(), None)
+    .await?;
+  for step in &steps {
+    info!(
+      step = step.describe().as_str(),
+      "Running schema upgrade step"
+    );
+    if let Err(e) = run_schema_upgrade_step(&app_context, step).await {
+      error!(
+        step = step.describe().as_str(),
+        error = e.to_string(),
+        "Schema upgrade step failed"
+      );
+      progress.running = false;
+      progress.error = Some(e.to_string());
+      app_context
+        .kv
+        .set(UPGRADE_PROGRESS_KEY, progress, None)
+        .await?;
+      return Err(e);
+    }
+    progress.completed_steps += 1;
+    app_context
+      .kv
+      .set(UPGRADE_PROGRESS_KEY, progress.clone(), None)
+      .await?;
+  }
+  app_context
+    .kv
+    .set(APPLIED_VERSIONS_KEY, compiled, None)
+    .await?;
+  progress.running = false;
+  app_context
+    .kv
+    .set(UPGRADE_PROGRESS_KEY, progress, None)
+    .await?;
+  info!("Schema upgrade complete");
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn versions(sqlite: u32, album_index: u32, album_embedding_body: u32) -> SchemaVersions {
+    SchemaVersions {
+      sqlite,
+      album_index,
+      spotify_track_index: 1,
+      album_embedding_body,
+    }
+  }
+
+  #[test]
+  fn test_plan_schema_upgrade() -> Result<()> {
+    let manifest = vec![versions(1, 1, 1), versions(2, 1, 1), versions(3, 2, 2)];
+    assert_eq!(
+      plan_schema_upgrade(&manifest, &manifest[0], &manifest[2])?,
+      vec![
+        SchemaUpgradeStep::MigrateSqlite(2),
+        SchemaUpgradeStep::MigrateSqlite(3),
+        SchemaUpgradeStep::SetupAlbumIndex,
+        SchemaUpgradeStep::RegenerateAlbumEmbeddings,
+      ]
+    );
+    assert!(plan_schema_upgrade(&manifest, &manifest[2], &manifest[2])?.is_empty());
+    assert!(plan_schema_upgrade(&manifest, &manifest[2], &manifest[0]).is_err());
+    assert!(plan_schema_upgrade(&manifest, &versions(9, 9, 9), &manifest[2]).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_compiled_schema_versions_are_latest_manifest_entry() {
+    assert_eq!(SCHEMA_MANIFEST.last(), Some(&compiled_schema_versions()));
+  }
+
+  #[test]
+  fn test_manifest_covers_every_sqlite_migration() {
+    for window in SCHEMA_MANIFEST.windows(2) {
+      let step = window[1].sqlite - window[0].sqlite;
+      assert!(
+        step <= 1,
+        "Sqlite versions {} to {} skip a migration",
+        window[0].sqlite,
+        window[1].sqlite
+      );
+    }
+    assert_eq!(
+      SCHEMA_MANIFEST.last().map(|versions| versions.sqlite),
+      Some(latest_migration_version())
+    );
+  }
+}
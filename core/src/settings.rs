@@ -1,28 +1,29 @@
 use chrono::TimeDelta;
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct RedisSettings {
   pub url: String,
   pub max_pool_size: u32,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct SqliteSettings {
   pub dir: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct FileTtlDaysSettings {
   pub artist: u32,
   pub album: u32,
   pub search: u32,
   pub chart: u32,
   pub list_segment: u32,
+  pub genre_tree: u32,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ContentStoreSettings {
   pub region: String,
   pub endpoint: String,
@@ -31,19 +32,78 @@ pub struct ContentStoreSettings {
   pub bucket: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FileRedactionSettings {
+  /**
+   * Elements matching any of these CSS selectors are stripped from pages before they are stored.
+   * Comma separated in the environment, so selector groups need one entry per selector.
+   */
+  pub selectors: Vec<String>,
+  /**
+   * Recorded on every file stored with redaction applied. Bump it when the selectors change.
+   */
+  pub version: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FileRetentionSettings {
+  /**
+   * Versions of each file's content kept in the content store, the current one included. Earlier
+   * versions are only archived when this is above one.
+   */
+  pub versions: u32,
+  /**
+   * Content is deleted this many days after it was saved, once it has parsed successfully. File
+   * metadata stays, so the page isn't crawled again until it goes stale.
+   */
+  pub max_age_days: Option<u32>,
+  pub interval_hours: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct FileSettings {
   pub ttl_days: FileTtlDaysSettings,
   pub content_store: ContentStoreSettings,
+  pub redaction: Option<FileRedactionSettings>,
+  pub retention: FileRetentionSettings,
+  /**
+   * Downloads album covers into the content store and serves thumbnails of them from the RPC
+   * server, instead of linking to the hosts they were crawled from
+   */
+  #[serde(default)]
+  pub cache_cover_images: bool,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct CrawlerRateLimitSettings {
   pub window_seconds: u32,
   pub max_requests: u32,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+/**
+ * How many days after its last crawl a page of each type is refreshed. Types left unset are only
+ * recrawled on demand.
+ */
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CrawlerFreshnessPolicySettings {
+  pub album: Option<u32>,
+  pub artist: Option<u32>,
+  pub chart: Option<u32>,
+  pub list_segment: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CrawlerRefreshSettings {
+  pub enabled: bool,
+  pub interval_minutes: u32,
+  /**
+   * Most stale pages enqueued for a refresh per UTC day, across every page type
+   */
+  pub daily_budget: u32,
+  pub max_age_days: CrawlerFreshnessPolicySettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct CrawlerProxySettings {
   pub host: String,
   pub port: u32,
@@ -51,7 +111,7 @@ pub struct CrawlerProxySettings {
   pub password: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct CrawlerSettings {
   pub proxy: Option<CrawlerProxySettings>,
   pub pool_size: u32,
@@ -59,59 +119,519 @@ pub struct CrawlerSettings {
   pub max_queue_size: u32,
   pub wait_time_seconds: u32,
   pub rate_limit: CrawlerRateLimitSettings,
+  pub refresh: CrawlerRefreshSettings,
+  /**
+   * Crawl attempts older than this are pruned from the crawl history daily
+   */
+  pub history_retention_days: u32,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct TracingSettings {
-  pub otel_collector_endpoint: String,
+  /**
+   * OTLP gRPC endpoint spans are exported to, e.g. a Jaeger or Tempo collector. Spans are only
+   * logged when unset.
+   */
+  pub otel_collector_endpoint: Option<String>,
+  /**
+   * Sent with every export, for collectors that need auth
+   */
+  pub otel_collector_headers: Option<HashMap<String, String>>,
+  /**
+   * Percent of root traces exported. Child spans follow their parent's decision.
+   */
+  pub sample_percent: u32,
   pub host_name: String,
   pub service_name: String,
   pub service_namespace: String,
   pub resource_labels: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct SpotifySettings {
   pub client_id: String,
   pub client_secret: String,
   pub redirect_uri: String,
+  /**
+   * Confines bulk track matching and audio feature fetches to off-peak hours. They run whenever
+   * there's work when unset.
+   */
+  pub batch_window: Option<SpotifyBatchWindowSettings>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SpotifyBatchWindowSettings {
+  /**
+   * UTC hours the window opens and closes at, a window wraps past midnight when it closes before
+   * it opens
+   */
+  pub start_hour: u32,
+  pub end_hour: u32,
+  /**
+   * Spotify requests the bulk jobs may make in a single window
+   */
+  pub max_requests: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AppleMusicSettings {
+  /**
+   * MusicKit developer token, a JWT signed with the team's private key
+   */
+  pub developer_token: String,
+  /**
+   * Catalog storefront used for searches. Defaults to us.
+   */
+  pub storefront: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TidalSettings {
+  pub client_id: String,
+  pub redirect_uri: String,
+  /**
+   * Catalog region used for searches. Defaults to US.
+   */
+  pub country_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct YouTubeMusicSettings {
+  pub client_id: String,
+  pub client_secret: String,
+  pub redirect_uri: String,
+  /**
+   * YouTube Data API units available per day. Defaults to the standard allocation of 10000.
+   */
+  pub daily_quota: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LastFmImportSource {
+  /**
+   * Re-imports the most played albums for `period` on every sync
+   */
+  #[default]
+  TopAlbums,
+  /**
+   * Syncs new scrobbles on every sync, weighting albums by their cumulative scrobble counts
+   */
+  Scrobbles,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LastFmSettings {
+  pub api_key: String,
+  pub username: String,
+  /**
+   * Profile the user's albums are imported into, created if it doesn't exist
+   */
+  pub profile_id: String,
+  /**
+   * One of overall, 7day, 1month, 3month, 6month, 12month. Defaults to overall.
+   */
+  pub period: Option<String>,
+  pub limit: Option<u32>,
+  pub sync_interval_hours: Option<u32>,
+  /**
+   * Defaults to top_albums
+   */
+  pub source: Option<LastFmImportSource>,
+  /**
+   * Caps how far back the first scrobble sync reaches. Defaults to 10000 scrobbles.
+   */
+  pub max_initial_scrobbles: Option<u32>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ListenBrainzSettings {
+  pub user_token: String,
+  pub username: String,
+  /**
+   * Profile the user's listens are synced into, created if it doesn't exist
+   */
+  pub profile_id: String,
+  pub sync_interval_minutes: Option<u32>,
+  /**
+   * Caps how far back the first sync reaches. Defaults to 10000 listens.
+   */
+  pub max_initial_listens: Option<u32>,
+  /**
+   * Albums whose listen counts are kept between syncs, the least recently played are dropped past
+   * it. Defaults to 5000.
+   */
+  pub max_tracked_albums: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DiscogsSettings {
+  /**
+   * Personal access token from the Discogs developer settings
+   */
+  pub token: String,
+  /**
+   * Marketplace currency, e.g. USD, EUR or GBP. Defaults to USD.
+   */
+  pub currency: Option<String>,
+  /**
+   * Looks up every newly saved album that has no Discogs release yet
+   */
+  #[serde(default)]
+  pub auto_lookup: bool,
+  pub refresh_interval_hours: Option<u32>,
+  /**
+   * Marketplace prices older than this are refreshed. Defaults to 7 days.
+   */
+  pub max_price_age_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MusicBrainzSettings {
+  /**
+   * MusicBrainz rejects anonymous clients, e.g. "lute/1.0 ( me@example.com )"
+   */
+  pub user_agent: String,
+  /**
+   * Looks up every newly saved album that has no MusicBrainz id yet
+   */
+  #[serde(default)]
+  pub auto_lookup: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ParserSettings {
   pub concurrency: u16,
   pub retry_concurrency: u16,
+  /**
+   * Where parser fixtures are captured to and validated from, defaults to the repo's test fixtures
+   */
+  pub fixture_dir: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct OpenAISettings {
   pub api_key: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct VoyageAISettings {
   pub api_key: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct OllamaSettings {
   pub url: Option<String>,
   pub models: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OnnxSettings {
+  /**
+   * One of all-minilm-l6-v2, bge-small-en-v1.5, bge-base-en-v1.5, nomic-embed-text-v1.5.
+   * Defaults to all-minilm-l6-v2.
+   */
+  pub model: Option<String>,
+  /**
+   * Where downloaded models are kept. Defaults to onnx_models in `sqlite.dir`.
+   */
+  pub cache_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RecommendationRationaleSettings {
+  /**
+   * Base URL of an OpenAI-compatible API, e.g. https://api.openai.com/v1 or
+   * http://localhost:11434/v1 for Ollama
+   */
+  pub base_url: String,
+  pub api_key: Option<String>,
+  pub model: String,
+  /**
+   * How long a generated rationale is served from the cache. Defaults to 30 days.
+   */
+  pub cache_ttl_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NaturalLanguageSearchSettings {
+  /**
+   * Base URL of an OpenAI-compatible API used to refine the rule-based query translation
+   */
+  pub base_url: String,
+  pub api_key: Option<String>,
+  pub model: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CrossEncoderSettings {
+  /**
+   * One of bge-reranker-base, bge-reranker-v2-m3, jina-reranker-v1-turbo-en.
+   * Defaults to bge-reranker-base.
+   */
+  pub model: Option<String>,
+  /**
+   * Where downloaded models are kept. Defaults to onnx_models in `sqlite.dir`.
+   */
+  pub cache_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct EmbeddingProviderSettings {
   pub openai: Option<OpenAISettings>,
   pub voyageai: Option<VoyageAISettings>,
   pub ollama: Option<OllamaSettings>,
+  pub onnx: Option<OnnxSettings>,
+  /**
+   * Name of the provider used when a request doesn't specify an embedding key
+   */
+  pub default: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ElasticSearchSettings {
   pub url: String,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LookupLaneSettings {
+  pub concurrency: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LookupLanesSettings {
+  pub interactive: LookupLaneSettings,
+  pub background: LookupLaneSettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LookupSettings {
+  pub lanes: LookupLanesSettings,
+  /**
+   * Searches Bandcamp for recommended albums and stores the link of the matching release
+   */
+  #[serde(default)]
+  pub resolve_bandcamp_links: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+  #[default]
+  Redis,
+  /**
+   * Runs without Redis, keeping albums, files, profiles and Spotify tracks in SQLite. Intended for
+   * small single-binary deployments.
+   */
+  Sqlite,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct EventSettings {
+  /**
+   * Event payloads at least this large are zstd compressed. 0 disables compression.
+   */
+  pub compression_threshold_bytes: usize,
+  pub compression_level: i32,
+  pub lag_check_interval_minutes: u32,
+  /**
+   * Subscribers further behind their streams than this many events are alerted on
+   */
+  pub max_lag_events: Option<u64>,
+  /**
+   * Subscribers whose oldest unprocessed event is older than this are alerted on
+   */
+  pub max_lag_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DocumentStoreQuotaSettings {
+  pub max_rows: Option<u64>,
+  pub max_bytes: Option<u64>,
+  /**
+   * Percent of writes kept once the quota is exceeded. Unset keeps writing everything.
+   */
+  pub sample_percent: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DocumentStoreSettings {
+  /**
+   * Soft quotas keyed by collection, checked periodically rather than on write
+   */
+  pub quotas: HashMap<String, DocumentStoreQuotaSettings>,
+  pub quota_check_interval_minutes: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct StorageSettings {
+  pub mode: StorageMode,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct QdrantSettings {
+  pub url: String,
+  pub api_key: Option<String>,
+  pub collection_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumSearchIndexBackend {
+  #[default]
+  Redis,
+  Elasticsearch,
+  Sqlite,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumEmbeddingStore {
+  /**
+   * Embeddings are stored alongside albums in the search index backend
+   */
+  #[default]
+  #[serde(alias = "redis")]
+  Backend,
+  /**
+   * Embeddings are stored in Qdrant. Album documents are stored in the SQLite index when the
+   * backend is Redis, so neither is held in Redis.
+   */
+  Qdrant,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AlbumSearchIndexSettings {
+  pub backend: AlbumSearchIndexBackend,
+  pub embedding_store: AlbumEmbeddingStore,
+  /**
+   * Boost profile applied to searches that don't name one
+   */
+  pub default_boost_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RecommendationDigestSettings {
+  /**
+   * Profiles that get a digest. None do by default.
+   */
+  pub profile_ids: Vec<String>,
+  pub count: u32,
+  pub interval_days: u32,
+  /**
+   * Each digest is POSTed here as JSON when set
+   */
+  pub webhook_url: Option<String>,
+  /**
+   * Digests kept per profile, older ones are deleted as new ones are created
+   */
+  pub retention_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct YearInReviewSettings {
+  /**
+   * Profiles that get a report for the past year once it ends. Reports can be created for any
+   * profile on demand.
+   */
+  pub profile_ids: Vec<String>,
+  pub recommendation_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AlbumClusteringSettings {
+  /**
+   * Embeddings the scheduled run clusters, scheduled clustering is off when unset
+   */
+  pub embedding_key: Option<String>,
+  pub cluster_count: u32,
+  pub max_iterations: u32,
+  pub interval_days: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AuthSettings {
+  /**
+   * Requires an API key on every request to the RPC server, besides health checks and cover
+   * thumbnails
+   */
+  pub enabled: bool,
+  /**
+   * Key with the admin scope that's always accepted, for creating the first stored keys
+   */
+  pub admin_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GraphQlSettings {
+  /**
+   * Serves the GraphQL API at `/graphql/` on the RPC server
+   */
+  pub enabled: bool,
+  /**
+   * Deepest selection a query may nest, which bounds how far album -> artists -> albums fans out
+   */
+  pub max_depth: usize,
+  /**
+   * Most fields a query may select in total, counting every field of every nested object once
+   */
+  pub max_complexity: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RateLimitSettings {
+  /**
+   * Limits requests to the RPC server per API key, or per peer IP for requests without one
+   */
+  pub enabled: bool,
+  pub requests_per_second: u32,
+  pub burst: u32,
+  /**
+   * RPCs with their own, smaller budget, as `Service/Method`
+   */
+  pub expensive_methods: Vec<String>,
+  pub expensive_requests_per_minute: u32,
+  pub expensive_burst: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HealthSettings {
+  /**
+   * How often the gRPC health status is refreshed
+   */
+  pub check_interval_seconds: u64,
+  /**
+   * Each dependency check fails if it takes longer than this
+   */
+  pub check_timeout_seconds: u64,
+  /**
+   * An event subscriber that hasn't finished a poll in this long is considered dead. Polls run
+   * their handlers, so this needs to cover the slowest batch.
+   */
+  pub subscriber_stall_seconds: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BackupSettings {
+  /**
+   * Where backup archives are written
+   */
+  pub dir: String,
+  /**
+   * Schedules a backup this often, scheduled backups are off when unset
+   */
+  pub interval_hours: Option<u32>,
+  /**
+   * Archives kept in `dir`, older ones are deleted after every backup
+   */
+  pub retain: usize,
+  /**
+   * Keeps credentials in the archived settings instead of redacting them
+   */
+  pub include_secrets: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Settings {
   pub crawler: CrawlerSettings,
   pub file: FileSettings,
@@ -119,10 +639,34 @@ pub struct Settings {
   pub redis: RedisSettings,
   pub sqlite: SqliteSettings,
   pub spotify: SpotifySettings,
+  pub tidal: Option<TidalSettings>,
+  pub apple_music: Option<AppleMusicSettings>,
+  pub youtube_music: Option<YouTubeMusicSettings>,
+  pub lastfm: Option<LastFmSettings>,
+  pub listenbrainz: Option<ListenBrainzSettings>,
+  pub musicbrainz: Option<MusicBrainzSettings>,
+  pub discogs: Option<DiscogsSettings>,
   pub tracing: TracingSettings,
   pub parser: ParserSettings,
   pub embedding_provider: EmbeddingProviderSettings,
+  pub cross_encoder: Option<CrossEncoderSettings>,
+  pub recommendation_rationale: Option<RecommendationRationaleSettings>,
+  pub natural_language_search: Option<NaturalLanguageSearchSettings>,
   pub elasticsearch: ElasticSearchSettings,
+  pub album_search_index: AlbumSearchIndexSettings,
+  pub qdrant: Option<QdrantSettings>,
+  pub lookup: LookupSettings,
+  pub storage: StorageSettings,
+  pub events: EventSettings,
+  pub doc_store: DocumentStoreSettings,
+  pub recommendation_digest: RecommendationDigestSettings,
+  pub year_in_review: YearInReviewSettings,
+  pub album_clustering: AlbumClusteringSettings,
+  pub graphql: GraphQlSettings,
+  pub auth: AuthSettings,
+  pub rate_limit: RateLimitSettings,
+  pub health: HealthSettings,
+  pub backup: BackupSettings,
 }
 
 impl Settings {
@@ -132,7 +676,11 @@ impl Settings {
         config::Environment::default()
           .try_parsing(true)
           .list_separator(",")
-          .with_list_parse_key("embedding_provider.ollama.models"),
+          .with_list_parse_key("embedding_provider.ollama.models")
+          .with_list_parse_key("file.redaction.selectors")
+          .with_list_parse_key("recommendation_digest.profile_ids")
+          .with_list_parse_key("year_in_review.profile_ids")
+          .with_list_parse_key("rate_limit.expensive_methods"),
       )
       .set_default("port", 80)?
       .set_default("file.ttl_days.artist", 7)?
@@ -140,8 +688,12 @@ impl Settings {
       .set_default("file.ttl_days.chart", 7)?
       .set_default("file.ttl_days.search", 7)?
       .set_default("file.ttl_days.list_segment", 7)?
+      .set_default("file.ttl_days.genre_tree", 90)?
       .set_default("file.content_store.key", None::<String>)?
       .set_default("file.content_store.secret", None::<String>)?
+      .set_default("file.retention.versions", 1)?
+      .set_default("file.retention.max_age_days", None::<u32>)?
+      .set_default("file.retention.interval_hours", 24)?
       .set_default("crawler.pool_size", 10)?
       .set_default(
         "crawler.claim_ttl_seconds",
@@ -154,13 +706,213 @@ impl Settings {
         TimeDelta::try_days(1).unwrap().num_seconds(),
       )?
       .set_default("crawler.rate_limit.max_requests", 500)?
+      .set_default("crawler.history_retention_days", 90)?
+      .set_default("crawler.refresh.enabled", false)?
+      .set_default("crawler.refresh.interval_minutes", 60)?
+      .set_default("crawler.refresh.daily_budget", 200)?
+      .set_default("crawler.refresh.max_age_days.album", 180)?
+      .set_default("crawler.refresh.max_age_days.artist", None::<u32>)?
+      .set_default("crawler.refresh.max_age_days.chart", 7)?
+      .set_default("crawler.refresh.max_age_days.list_segment", None::<u32>)?
       .set_default("parser.concurrency", 20)?
       .set_default("parser.retry_concurrency", 20)?
       .set_default("tracing.service_name", "core")?
       .set_default("tracing.service_namespace", "lute")?
       .set_default("tracing.resource_labels", HashMap::<String, String>::new())?
+      .set_default("tracing.sample_percent", 100)?
       .set_default("sqlite.dir", env!("CARGO_MANIFEST_DIR"))?
+      .set_default("album_search_index.backend", "redis")?
+      .set_default("album_search_index.embedding_store", "backend")?
+      .set_default("lookup.lanes.interactive.concurrency", 100)?
+      .set_default("lookup.lanes.background.concurrency", 10)?
+      .set_default("storage.mode", "redis")?
+      .set_default("redis.url", "redis://localhost:6379")?
+      .set_default("redis.max_pool_size", 10)?
+      .set_default("events.compression_threshold_bytes", 16 * 1024)?
+      .set_default("events.compression_level", 3)?
+      .set_default("events.lag_check_interval_minutes", 5)?
+      .set_default("events.max_lag_events", 10_000)?
+      .set_default("events.max_lag_minutes", 60)?
+      .set_default("doc_store.quota_check_interval_minutes", 60)?
+      .set_default("doc_store.quotas.parser_failure.max_rows", 100_000)?
+      .set_default("doc_store.quotas.parser_failure.sample_percent", 10)?
+      .set_default("recommendation_digest.profile_ids", Vec::<String>::new())?
+      .set_default("recommendation_digest.count", 20)?
+      .set_default("recommendation_digest.interval_days", 7)?
+      .set_default("recommendation_digest.webhook_url", None::<String>)?
+      .set_default("recommendation_digest.retention_count", 52)?
+      .set_default("year_in_review.profile_ids", Vec::<String>::new())?
+      .set_default("year_in_review.recommendation_count", 25)?
+      .set_default("album_clustering.embedding_key", None::<String>)?
+      .set_default("album_clustering.cluster_count", 50)?
+      .set_default("album_clustering.max_iterations", 50)?
+      .set_default("album_clustering.interval_days", 7)?
+      .set_default("graphql.enabled", false)?
+      .set_default("graphql.max_depth", 8)?
+      .set_default("graphql.max_complexity", 1000)?
+      .set_default("auth.enabled", false)?
+      .set_default("auth.admin_key", None::<String>)?
+      .set_default("rate_limit.enabled", false)?
+      .set_default("rate_limit.requests_per_second", 50)?
+      .set_default("rate_limit.burst", 100)?
+      .set_default(
+        "rate_limit.expensive_methods",
+        vec![
+          "RecommendationService/RecommendAlbums",
+          "RecommendationService/RecommendCuratedAlbums",
+          "RecommendationService/AssessProfileCompatibility",
+          "RecommendationService/CreateYearInReview",
+          "RecommendationService/GetRecommendationRationale",
+          "EventService/Stream",
+          "EventService/Replay",
+          "BootstrapService/Bootstrap",
+          "AlbumService/RecrawlAlbums",
+          "AlbumService/GetAlbumEmbeddingProjection",
+          "AlbumService/ClusterAlbums",
+          "AlbumService/SearchAlbumsByNaturalLanguage",
+        ],
+      )?
+      .set_default("rate_limit.expensive_requests_per_minute", 30)?
+      .set_default("rate_limit.expensive_burst", 5)?
+      .set_default("health.check_interval_seconds", 10)?
+      .set_default("health.check_timeout_seconds", 5)?
+      .set_default("health.subscriber_stall_seconds", 900)?
+      .set_default(
+        "backup.dir",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/backups"),
+      )?
+      .set_default("backup.interval_hours", None::<u32>)?
+      .set_default("backup.retain", 7)?
+      .set_default("backup.include_secrets", false)?
       .build()?
-      .try_deserialize()
+      .try_deserialize::<Settings>()?
+      .validate()
+  }
+
+  /**
+   * Rejects values that would stall the app rather than fail loudly at startup
+   */
+  fn validate(self) -> Result<Self, config::ConfigError> {
+    // Zero concurrency deadlocks a lane and zero intervals busy-loop the scheduler
+    let minimums = [
+      (
+        "lookup.lanes.interactive.concurrency",
+        Some(self.lookup.lanes.interactive.concurrency),
+      ),
+      (
+        "lookup.lanes.background.concurrency",
+        Some(self.lookup.lanes.background.concurrency),
+      ),
+      (
+        "file.retention.interval_hours",
+        Some(self.file.retention.interval_hours),
+      ),
+      (
+        "crawler.refresh.interval_minutes",
+        Some(self.crawler.refresh.interval_minutes),
+      ),
+      (
+        "crawler.history_retention_days",
+        Some(self.crawler.history_retention_days),
+      ),
+      (
+        "events.lag_check_interval_minutes",
+        Some(self.events.lag_check_interval_minutes),
+      ),
+      (
+        "doc_store.quota_check_interval_minutes",
+        Some(self.doc_store.quota_check_interval_minutes),
+      ),
+      (
+        "recommendation_digest.interval_days",
+        Some(self.recommendation_digest.interval_days),
+      ),
+      (
+        "recommendation_digest.retention_count",
+        Some(self.recommendation_digest.retention_count),
+      ),
+      (
+        "album_clustering.interval_days",
+        Some(self.album_clustering.interval_days),
+      ),
+      ("backup.interval_hours", self.backup.interval_hours),
+      (
+        "lastfm.sync_interval_hours",
+        self
+          .lastfm
+          .as_ref()
+          .and_then(|lastfm| lastfm.sync_interval_hours),
+      ),
+      (
+        "listenbrainz.sync_interval_minutes",
+        self
+          .listenbrainz
+          .as_ref()
+          .and_then(|listenbrainz| listenbrainz.sync_interval_minutes),
+      ),
+      (
+        "discogs.refresh_interval_hours",
+        self
+          .discogs
+          .as_ref()
+          .and_then(|discogs| discogs.refresh_interval_hours),
+      ),
+    ];
+    if let Some((key, _)) = minimums
+      .iter()
+      .find(|(_, value)| value.is_some_and(|value| value < 1))
+    {
+      return Err(config::ConfigError::Message(format!(
+        "{} must be at least 1",
+        key
+      )));
+    }
+    Ok(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn valid_settings() -> Settings {
+    let mut settings = Settings::default();
+    settings.lookup.lanes.interactive.concurrency = 1;
+    settings.lookup.lanes.background.concurrency = 1;
+    settings.file.retention.interval_hours = 1;
+    settings.crawler.refresh.interval_minutes = 1;
+    settings.crawler.history_retention_days = 1;
+    settings.events.lag_check_interval_minutes = 1;
+    settings.doc_store.quota_check_interval_minutes = 1;
+    settings.recommendation_digest.interval_days = 1;
+    settings.recommendation_digest.retention_count = 1;
+    settings.album_clustering.interval_days = 1;
+    settings
+  }
+
+  #[test]
+  fn test_validate_rejects_zero_lane_concurrency() {
+    let mut settings = valid_settings();
+    assert!(settings.clone().validate().is_ok());
+    settings.lookup.lanes.background.concurrency = 0;
+    assert!(settings.validate().is_err());
+  }
+
+  #[test]
+  fn test_validate_rejects_zero_intervals() {
+    let mut settings = valid_settings();
+    settings.events.lag_check_interval_minutes = 0;
+    assert!(settings.validate().is_err());
+
+    let mut settings = valid_settings();
+    settings.lastfm = Some(LastFmSettings {
+      sync_interval_hours: Some(0),
+      ..Default::default()
+    });
+    assert!(settings.clone().validate().is_err());
+    settings.lastfm = Some(LastFmSettings::default());
+    assert!(settings.clone().validate().is_ok());
+    settings.backup.interval_hours = Some(0);
+    assert!(settings.validate().is_err());
   }
 }
@@ -1,3 +1,4 @@
+pub mod spotify_batch_window;
 pub mod spotify_client;
 pub mod spotify_credential_repository;
 pub mod spotify_service;
@@ -0,0 +1,222 @@
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  runtime_settings::runtime_settings::RuntimeSettings,
+  scheduler::{
+    job_name::JobName,
+    scheduler::{JobProcessorStatus, Scheduler},
+  },
+  settings::SpotifyBatchWindowSettings,
+};
+use anyhow::Result;
+use chrono::{NaiveDateTime, TimeDelta, Timelike, Utc};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/**
+ * Jobs that work through the Spotify backlog in bulk, as opposed to requests made on behalf of a
+ * user
+ */
+pub const SPOTIFY_BATCH_JOB_NAMES: [JobName; 3] = [
+  JobName::FetchSpotifyTracksByAlbumSearch,
+  JobName::FetchSpotifyTracksByAlbumIds,
+  JobName::IndexSpotifyTracks,
+];
+
+fn is_in_window(settings: &SpotifyBatchWindowSettings, hour: u32) -> bool {
+  if settings.start_hour < settings.end_hour {
+    hour >= settings.start_hour && hour < settings.end_hour
+  } else {
+    hour >= settings.start_hour || hour < settings.end_hour
+  }
+}
+
+/**
+ * Start of the window `now` falls in, which keys that window's request budget
+ */
+fn current_window_start(
+  settings: &SpotifyBatchWindowSettings,
+  now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+  if !is_in_window(settings, now.hour()) {
+    return None;
+  }
+  let start = now.date().and_hms_opt(settings.start_hour, 0, 0)?;
+  if now.hour() >= settings.start_hour {
+    Some(start)
+  } else {
+    Some(start - TimeDelta::try_days(1)?)
+  }
+}
+
+fn next_window_start(
+  settings: &SpotifyBatchWindowSettings,
+  now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+  let start = now.date().and_hms_opt(settings.start_hour, 0, 0)?;
+  if now < start {
+    Some(start)
+  } else {
+    Some(start + TimeDelta::try_days(1)?)
+  }
+}
+
+fn budget_key(window_start: NaiveDateTime) -> String {
+  format!(
+    "spotify_batch_window_requests:{}",
+    window_start.format("%Y-%m-%dT%H")
+  )
+}
+
+/**
+ * Keeps the bulk Spotify jobs inside the configured window by pausing their processors until the
+ * next one opens, once the window closes or its request budget runs out. Pauses expire on their
+ * own, so the backlog picks up where it left off each night: unprocessed jobs stay queued and the
+ * budget spent so far is kept per window.
+ */
+pub struct SpotifyBatchWindow {
+  settings: SpotifyBatchWindowSettings,
+  runtime_settings: Arc<RuntimeSettings>,
+  kv: Arc<KeyValueStore>,
+  scheduler: Arc<Scheduler>,
+}
+
+impl SpotifyBatchWindow {
+  pub fn new(
+    settings: SpotifyBatchWindowSettings,
+    runtime_settings: Arc<RuntimeSettings>,
+    kv: Arc<KeyValueStore>,
+    scheduler: Arc<Scheduler>,
+  ) -> Self {
+    Self {
+      settings,
+      runtime_settings,
+      kv,
+      scheduler,
+    }
+  }
+
+  /**
+   * The configured window with any runtime override applied
+   */
+  async fn get_settings(&self) -> Result<SpotifyBatchWindowSettings> {
+    Ok(
+      self
+        .runtime_settings
+        .get()
+        .await?
+        .spotify
+        .batch_window
+        .unwrap_or_else(|| self.settings.clone()),
+    )
+  }
+
+  async fn used_requests(&self, window_start: NaiveDateTime) -> Result<u32> {
+    let used = self.kv.increment(&budget_key(window_start), 0).await?;
+    Ok(used.max(0) as u32)
+  }
+
+  /**
+   * Counts requests made by a bulk job against the current window's budget
+   */
+  pub async fn record_requests(&self, count: u32) -> Result<()> {
+    let settings = self.get_settings().await?;
+    let now = Utc::now().naive_utc();
+    if let Some(window_start) = current_window_start(&settings, now) {
+      self
+        .kv
+        .increment(&budget_key(window_start), count as i64)
+        .await?;
+    }
+    self.enforce().await
+  }
+
+  pub async fn enforce(&self) -> Result<()> {
+    let settings = self.get_settings().await?;
+    let now = Utc::now().naive_utc();
+    if let Some(window_start) = current_window_start(&settings, now) {
+      let used = self.used_requests(window_start).await?;
+      if used < settings.max_requests {
+        return Ok(());
+      }
+      info!(
+        used,
+        max = settings.max_requests,
+        "Spotify batch window budget spent"
+      );
+    }
+    let Some(resume_at) = next_window_start(&settings, now) else {
+      warn!("Invalid Spotify batch window hours, bulk jobs aren't confined");
+      return Ok(());
+    };
+    for job_name in SPOTIFY_BATCH_JOB_NAMES.iter() {
+      // Processors that are already paused, by hand or for a rate limit, are left alone
+      if let JobProcessorStatus::Paused = self.scheduler.get_processor_status(job_name).await? {
+        continue;
+      }
+      info!(
+        job_name = job_name.to_string(),
+        resume_at = resume_at.to_string(),
+        "Pausing Spotify batch job until the next window"
+      );
+      self
+        .scheduler
+        .pause_processor(job_name, Some(resume_at - now))
+        .await?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+
+  fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, day)
+      .unwrap()
+      .and_hms_opt(hour, minute, 0)
+      .unwrap()
+  }
+
+  fn window(start_hour: u32, end_hour: u32) -> SpotifyBatchWindowSettings {
+    SpotifyBatchWindowSettings {
+      start_hour,
+      end_hour,
+      max_requests: 1000,
+    }
+  }
+
+  #[test]
+  fn test_window_wrapping_midnight() {
+    let settings = window(22, 6);
+    assert_eq!(
+      current_window_start(&settings, at(10, 23, 15)),
+      Some(at(10, 22, 0))
+    );
+    assert_eq!(
+      current_window_start(&settings, at(11, 2, 45)),
+      Some(at(10, 22, 0))
+    );
+    assert_eq!(current_window_start(&settings, at(11, 12, 0)), None);
+    assert_eq!(
+      next_window_start(&settings, at(11, 12, 0)),
+      Some(at(11, 22, 0))
+    );
+    assert_eq!(
+      next_window_start(&settings, at(11, 23, 0)),
+      Some(at(12, 22, 0))
+    );
+  }
+
+  #[test]
+  fn test_window_within_day() {
+    let settings = window(1, 5);
+    assert_eq!(
+      current_window_start(&settings, at(10, 3, 0)),
+      Some(at(10, 1, 0))
+    );
+    assert_eq!(current_window_start(&settings, at(10, 5, 0)), None);
+    assert_eq!(current_window_start(&settings, at(10, 0, 59)), None);
+  }
+}
@@ -2,10 +2,17 @@ use super::spotify_credential_repository::{
   SpotifyCredentialRepository, SpotifyCredentials, SCOPES,
 };
 use crate::{
-  albums::album_read_model::AlbumReadModel, helpers::key_value_store::KeyValueStore, proto,
+  albums::album_read_model::AlbumReadModel,
+  helpers::key_value_store::KeyValueStore,
+  music_service::music_service_client::{
+    MusicService, MusicServiceAlbum, MusicServiceClient, MusicServiceTrack, MusicServiceTrackQuery,
+  },
+  proto,
   settings::SpotifySettings,
+  tenant::tenant_id::TenantId,
 };
 use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
 use chrono::{DateTime, TimeDelta, Utc};
 use futures::stream::TryStreamExt;
 use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
@@ -18,11 +25,14 @@ use rspotify::{
     SavedTrack, SearchResult, SearchType, SimplifiedAlbum, SimplifiedArtist, SimplifiedTrack,
     TrackId,
   },
-  prelude::{BaseClient, OAuthClient},
+  prelude::{BaseClient, Id, OAuthClient},
   AuthCodeSpotify, ClientError, Credentials, OAuth, Token,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
 use strsim::jaro_winkler;
 use thiserror::Error;
 use tokio::sync::mpsc::unbounded_channel;
@@ -345,6 +355,48 @@ impl TryFrom<FullTrack> for SpotifyTrack {
   }
 }
 
+/**
+ * The subset of Spotify's audio features that are indexed for filtering and sorting tracks
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpotifyTrackAudioFeatures {
+  pub energy: f32,
+  pub danceability: f32,
+  pub tempo: f32,
+  pub valence: f32,
+}
+
+impl From<&AudioFeatures> for SpotifyTrackAudioFeatures {
+  fn from(features: &AudioFeatures) -> Self {
+    Self {
+      energy: features.energy,
+      danceability: features.danceability,
+      tempo: features.tempo,
+      valence: features.valence,
+    }
+  }
+}
+
+/**
+ * Spotify caps playlist item additions and removals at 100 tracks per request
+ */
+const PLAYLIST_ITEMS_BATCH_SIZE: usize = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SpotifyPlaylistSyncMode {
+  /**
+   * Drops tracks that are no longer recommended and adds the new ones at the end. Tracks that are
+   * kept stay where they are, so a listener partway through the playlist isn't thrown back to
+   * the start.
+   */
+  #[default]
+  Replace,
+  /**
+   * Only adds tracks that aren't already in the playlist
+   */
+  Append,
+}
+
 fn get_features_embedding(features: AudioFeatures) -> Vec<f32> {
   vec![
     features.acousticness,
@@ -412,7 +464,18 @@ impl SpotifyClient {
   pub fn new(settings: &SpotifySettings, kv: Arc<KeyValueStore>) -> Self {
     Self {
       settings: settings.clone(),
-      spotify_credential_repository: SpotifyCredentialRepository::new(kv),
+      spotify_credential_repository: SpotifyCredentialRepository::new(kv, TenantId::default()),
+    }
+  }
+
+  /**
+   * A client acting with the tenant's Spotify credentials. Catalog lookups made outside of a
+   * request use the default tenant's.
+   */
+  pub fn for_tenant(&self, tenant_id: &TenantId) -> Self {
+    Self {
+      settings: self.settings.clone(),
+      spotify_credential_repository: self.spotify_credential_repository.for_tenant(tenant_id),
     }
   }
 
@@ -656,10 +719,13 @@ impl SpotifyClient {
     Ok(albums)
   }
 
-  pub async fn get_tracks_feature_embeddings(
+  /**
+   * Feature embeddings along with indexable audio features, keyed by track id
+   */
+  pub async fn get_tracks_features(
     &self,
     track_uris: Vec<String>,
-  ) -> Result<HashMap<String, Vec<f32>>> {
+  ) -> Result<HashMap<String, (Vec<f32>, SpotifyTrackAudioFeatures)>> {
     let mut features = HashMap::new();
     if let Some(results) = self
       .tracks_features(
@@ -671,7 +737,11 @@ impl SpotifyClient {
       .await?
     {
       features = results.into_iter().fold(HashMap::new(), |mut acc, f| {
-        acc.insert(f.id.to_string(), get_features_embedding(f));
+        let audio_features = SpotifyTrackAudioFeatures::from(&f);
+        acc.insert(
+          f.id.to_string(),
+          (get_features_embedding(f), audio_features),
+        );
         acc
       });
     }
@@ -701,16 +771,178 @@ impl SpotifyClient {
         description.as_deref(),
       )
       .await?;
-    client
-      .playlist_add_items(
-        playlist.id.clone(),
-        track_uris
-          .iter()
-          .filter_map(|uri| TrackId::from_uri(uri).ok().map(PlayableId::Track))
-          .collect::<Vec<_>>(),
-        None,
-      )
+    self
+      .add_playlist_items(&client, &playlist.id, &track_uris)
       .await?;
     Ok(playlist.id.to_string())
   }
+
+  async fn add_playlist_items(
+    &self,
+    client: &AuthCodeSpotify,
+    playlist_id: &PlaylistId<'_>,
+    track_uris: &[String],
+  ) -> Result<()> {
+    for chunk in track_uris.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+      client
+        .playlist_add_items(
+          playlist_id.clone(),
+          chunk
+            .iter()
+            .filter_map(|uri| TrackId::from_uri(uri).ok().map(PlayableId::Track))
+            .collect::<Vec<_>>(),
+          None,
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn remove_playlist_items(
+    &self,
+    client: &AuthCodeSpotify,
+    playlist_id: &PlaylistId<'_>,
+    track_uris: &[String],
+  ) -> Result<()> {
+    for chunk in track_uris.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+      client
+        .playlist_remove_all_occurrences_of_items(
+          playlist_id.clone(),
+          chunk
+            .iter()
+            .filter_map(|uri| TrackId::from_uri(uri).ok().map(PlayableId::Track))
+            .collect::<Vec<_>>(),
+          None,
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  /**
+   * Brings an existing playlist in line with `track_uris` without recreating it
+   */
+  pub async fn sync_playlist(
+    &self,
+    playlist_id: &str,
+    track_uris: Vec<String>,
+    mode: SpotifyPlaylistSyncMode,
+  ) -> Result<()> {
+    let client = self.client().await?;
+    let playlist_id = PlaylistId::from_id_or_uri(playlist_id)?;
+    let existing_uris = self
+      .get_playlist_tracks(playlist_id.id())
+      .await?
+      .into_iter()
+      .map(|track| track.spotify_id)
+      .collect::<HashSet<_>>();
+    if mode == SpotifyPlaylistSyncMode::Replace {
+      let target_uris = track_uris.iter().collect::<HashSet<_>>();
+      let stale_uris = existing_uris
+        .iter()
+        .filter(|uri| !target_uris.contains(uri))
+        .cloned()
+        .collect::<Vec<_>>();
+      self
+        .remove_playlist_items(&client, &playlist_id, &stale_uris)
+        .await?;
+    }
+    let mut added_uris = HashSet::new();
+    let new_uris = track_uris
+      .into_iter()
+      .filter(|uri| !existing_uris.contains(uri) && added_uris.insert(uri.clone()))
+      .collect::<Vec<_>>();
+    self
+      .add_playlist_items(&client, &playlist_id, &new_uris)
+      .await?;
+    info!(
+      playlist_id = playlist_id.id(),
+      added = new_uris.len(),
+      "Synced playlist"
+    );
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl MusicServiceClient for SpotifyClient {
+  fn service(&self) -> MusicService {
+    MusicService::Spotify
+  }
+
+  async fn is_authorized(&self) -> bool {
+    SpotifyClient::is_authorized(self).await
+  }
+
+  /**
+   * Spotify's library is track based, so saved albums are the albums of saved tracks
+   */
+  async fn get_saved_albums(&self) -> Result<Vec<MusicServiceAlbum>> {
+    let mut albums: HashMap<String, MusicServiceAlbum> = HashMap::new();
+    for track in self.get_saved_tracks().await? {
+      albums
+        .entry(track.album.spotify_id.clone())
+        .or_insert_with(|| MusicServiceAlbum {
+          id: track.album.spotify_id,
+          name: track.album.name,
+          artist_names: track
+            .artists
+            .into_iter()
+            .map(|artist| artist.name)
+            .collect(),
+        });
+    }
+    Ok(albums.into_values().collect())
+  }
+
+  async fn find_track(&self, query: &MusicServiceTrackQuery) -> Result<Option<MusicServiceTrack>> {
+    self.wait_for_rate_limit().await;
+    let search_text = match &query.isrc {
+      Some(isrc) => format!("isrc:{}", isrc),
+      None => query.search_text(),
+    };
+    let result = self
+      .client()
+      .await?
+      .search(
+        search_text.as_str(),
+        SearchType::Track,
+        None,
+        None,
+        Some(10),
+        None,
+      )
+      .await
+      .map_err(map_spotify_error)?;
+    let SearchResult::Tracks(page) = result else {
+      return Ok(None);
+    };
+    Ok(
+      page
+        .items
+        .into_iter()
+        .filter_map(|track| {
+          Some(MusicServiceTrack {
+            id: track.id?.to_string(),
+            name: track.name,
+            artist_names: track
+              .artists
+              .into_iter()
+              .map(|artist| artist.name)
+              .collect(),
+            isrc: track.external_ids.get("isrc").cloned(),
+          })
+        })
+        .find(|track| query.is_match(track)),
+    )
+  }
+
+  async fn create_playlist(
+    &self,
+    name: String,
+    description: Option<String>,
+    track_ids: Vec<String>,
+  ) -> Result<String> {
+    SpotifyClient::create_playlist(self, name, description, track_ids).await
+  }
 }
@@ -1,4 +1,4 @@
-use crate::helpers::key_value_store::KeyValueStore;
+use crate::{helpers::key_value_store::KeyValueStore, tenant::tenant_id::TenantId};
 use anyhow::Result;
 use chrono::{NaiveDateTime, Utc};
 use lazy_static::lazy_static;
@@ -6,8 +6,13 @@ use rspotify::scopes;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, sync::Arc};
 
+/**
+ * Spotify credentials of one tenant
+ */
+#[derive(Clone)]
 pub struct SpotifyCredentialRepository {
   kv: Arc<KeyValueStore>,
+  tenant_id: TenantId,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,19 +40,27 @@ impl SpotifyCredentials {
 }
 
 impl SpotifyCredentialRepository {
-  pub fn new(kv: Arc<KeyValueStore>) -> Self {
-    Self { kv }
+  pub fn new(kv: Arc<KeyValueStore>, tenant_id: TenantId) -> Self {
+    Self { kv, tenant_id }
+  }
+
+  pub fn for_tenant(&self, tenant_id: &TenantId) -> Self {
+    Self::new(Arc::clone(&self.kv), tenant_id.clone())
+  }
+
+  fn key(&self) -> String {
+    self.tenant_id.scope_key(KEY)
   }
 
   pub async fn put(&self, credentials: &SpotifyCredentials) -> Result<()> {
-    self.kv.set(KEY, credentials, None).await
+    self.kv.set(&self.key(), credentials, None).await
   }
 
   pub async fn get(&self) -> Result<Option<SpotifyCredentials>> {
-    self.kv.get::<SpotifyCredentials>(KEY).await
+    self.kv.get::<SpotifyCredentials>(&self.key()).await
   }
 
   pub async fn delete(&self) -> Result<()> {
-    self.kv.delete(KEY).await
+    self.kv.delete(&self.key()).await
   }
 }
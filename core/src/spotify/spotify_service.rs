@@ -2,6 +2,7 @@ use super::spotify_client::{SpotifyAlbumType, SpotifyClient, SpotifyTrack};
 use crate::{
   context::ApplicationContext,
   proto::{self, HandleAuthorizationCodeRequest, IsAuthorizedReply},
+  tenant::tenant_id::request_tenant_id,
 };
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
@@ -17,6 +18,10 @@ impl SpotifyService {
       spotify_client: Arc::clone(&app_context.spotify_client),
     }
   }
+
+  fn tenant_client<T>(&self, request: &Request<T>) -> Result<SpotifyClient, Status> {
+    Ok(self.spotify_client.for_tenant(&request_tenant_id(request)?))
+  }
 }
 
 impl From<SpotifyTrack> for proto::SpotifyTrack {
@@ -49,9 +54,12 @@ impl From<SpotifyTrack> for proto::SpotifyTrack {
 
 #[tonic::async_trait]
 impl proto::SpotifyService for SpotifyService {
-  async fn is_authorized(&self, _: Request<()>) -> Result<Response<IsAuthorizedReply>, Status> {
+  async fn is_authorized(
+    &self,
+    request: Request<()>,
+  ) -> Result<Response<IsAuthorizedReply>, Status> {
     let reply = IsAuthorizedReply {
-      authorized: self.spotify_client.is_authorized().await,
+      authorized: self.tenant_client(&request)?.is_authorized().await,
     };
     Ok(Response::new(reply))
   }
@@ -74,7 +82,7 @@ impl proto::SpotifyService for SpotifyService {
     request: Request<HandleAuthorizationCodeRequest>,
   ) -> std::result::Result<Response<()>, Status> {
     self
-      .spotify_client
+      .tenant_client(&request)?
       .receive_auth_code(&request.into_inner().code)
       .await
       .map_err(|e| {
@@ -87,10 +95,10 @@ impl proto::SpotifyService for SpotifyService {
 
   async fn get_saved_tracks(
     &self,
-    _: Request<()>,
+    request: Request<()>,
   ) -> Result<Response<proto::GetSavedTracksReply>, Status> {
     let tracks = self
-      .spotify_client
+      .tenant_client(&request)?
       .get_saved_tracks()
       .await
       .map_err(|e| {
@@ -108,9 +116,9 @@ impl proto::SpotifyService for SpotifyService {
     &self,
     request: Request<proto::GetPlaylistTracksRequest>,
   ) -> Result<Response<proto::GetPlaylistTracksReply>, Status> {
+    let client = self.tenant_client(&request)?;
     let playlist_id = request.into_inner().playlist_id;
-    let tracks = self
-      .spotify_client
+    let tracks = client
       .get_playlist_tracks(&playlist_id)
       .await
       .map_err(|e| {
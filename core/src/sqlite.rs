@@ -14,6 +14,13 @@ lazy_static! {
   static ref MIGRATIONS: Migrations<'static> = Migrations::from_directory(&MIGRATIONS_DIR).unwrap();
 }
 
+/**
+ * Version the database ends up at after migrating to latest, one per migration directory
+ */
+pub fn latest_migration_version() -> u32 {
+  MIGRATIONS_DIR.dirs().count() as u32
+}
+
 #[derive(Clone, Debug)]
 pub struct SqliteConnection {
   read_pool: Arc<Pool>,
@@ -64,13 +71,11 @@ impl SqliteConnection {
       anyhow::anyhow!("Failed to initialize SQLite connection: {:?}", e)
     })?;
 
-    let sqlite_connection = Self {
+    // Migrations are left to the schema manifest check, which has to see the version first
+    Ok(Self {
       read_pool: Arc::new(read_pool),
       write_pool: Arc::new(write_pool),
-    };
-    sqlite_connection.migrate_to_latest().await?;
-
-    Ok(sqlite_connection)
+    })
   }
 
   pub async fn migrate_to_latest(&self) -> Result<()> {
@@ -106,6 +111,62 @@ impl SqliteConnection {
       })?
   }
 
+  pub async fn current_version(&self) -> Result<u32> {
+    self
+      .read()
+      .await?
+      .interact(|conn| conn.pragma_query_value(None, "user_version", |row| row.get::<_, u32>(0)))
+      .await
+      .map_err(|e| {
+        error!("Failed to get SQLite version: {:?}", e);
+        anyhow::anyhow!("Failed to get SQLite version: {:?}", e)
+      })?
+      .map_err(|e| anyhow::anyhow!("Failed to get SQLite version: {:?}", e))
+  }
+
+  /**
+   * Takes the write lock and rolls back straight away, so nothing is written
+   */
+  pub async fn check_writable(&self) -> Result<()> {
+    self
+      .write()
+      .await?
+      .interact(|conn| conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;"))
+      .await
+      .map_err(|e| anyhow::anyhow!("Failed to check SQLite writability: {:?}", e))?
+      .map_err(|e| anyhow::anyhow!("SQLite database is not writable: {:?}", e))
+  }
+
+  /**
+   * Writes a consistent, compacted copy of the database to `path`, which must not exist yet.
+   * Writers aren't blocked while it runs.
+   */
+  pub async fn vacuum_into(&self, path: &Path) -> Result<()> {
+    let path = path.to_string_lossy().to_string();
+    self
+      .read()
+      .await?
+      .interact(move |conn| conn.execute("VACUUM INTO ?", [path]))
+      .await
+      .map_err(|e| anyhow::anyhow!("Failed to copy SQLite database: {:?}", e))?
+      .map_err(|e| anyhow::anyhow!("Failed to copy SQLite database: {:?}", e))?;
+    Ok(())
+  }
+
+  /**
+   * A migrated database in a fresh temporary directory, for tests
+   */
+  #[cfg(test)]
+  pub async fn new_for_test() -> Result<Self> {
+    let dir = std::env::temp_dir().join(format!("lute-test-{}", ulid::Ulid::new()));
+    std::fs::create_dir_all(&dir)?;
+    let mut settings = Settings::default();
+    settings.sqlite.dir = dir.to_string_lossy().to_string();
+    let connection = Self::new(Arc::new(settings)).await?;
+    connection.migrate_to_latest().await?;
+    Ok(connection)
+  }
+
   #[instrument(skip(self), name = "acquire-sqlite-read-connection")]
   pub async fn read(&self) -> Result<Object> {
     self.read_pool.get().await.map_err(|e| {
@@ -0,0 +1,202 @@
+use crate::auth::api_key::{ApiKeyScope, AuthenticatedKey};
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use tonic::{codegen::http, Request, Status};
+
+lazy_static! {
+  static ref TENANT_ID_RE: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_-]{2,80}$").unwrap();
+}
+
+pub const TENANT_METADATA_KEY: &str = "x-lute-tenant";
+
+const DEFAULT_TENANT_ID: &str = "default";
+const TENANT_KEY_PREFIX: &str = "tenant";
+
+/**
+ * The person a request acts for. Requests without a tenant act for the default tenant, which owns
+ * everything stored before tenants existed.
+ */
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone)]
+pub struct TenantId(String);
+
+impl Default for TenantId {
+  fn default() -> Self {
+    Self(DEFAULT_TENANT_ID.to_string())
+  }
+}
+
+impl TryFrom<String> for TenantId {
+  type Error = anyhow::Error;
+
+  fn try_from(value: String) -> Result<Self> {
+    if TENANT_ID_RE.is_match(&value) {
+      Ok(Self(value))
+    } else {
+      bail!("Invalid tenant id: {}", value)
+    }
+  }
+}
+
+impl ToString for TenantId {
+  fn to_string(&self) -> String {
+    self.0.clone()
+  }
+}
+
+impl TenantId {
+  pub fn is_default(&self) -> bool {
+    self.0 == DEFAULT_TENANT_ID
+  }
+
+  /**
+   * Storage key of a tenant's copy of `key`. The default tenant keeps the unprefixed keys, so
+   * existing data migrates into it without being rewritten.
+   */
+  pub fn scope_key(&self, key: &str) -> String {
+    if self.is_default() {
+      key.to_string()
+    } else {
+      format!("{}:{}:{}", TENANT_KEY_PREFIX, self.0, key)
+    }
+  }
+
+  /**
+   * Splits a key made by `scope_key` into its tenant and the unscoped key
+   */
+  pub fn unscope_key(key: &str) -> (Self, &str) {
+    key
+      .strip_prefix(&format!("{}:", TENANT_KEY_PREFIX))
+      .and_then(|rest| rest.split_once(':'))
+      .map(|(tenant_id, key)| (Self(tenant_id.to_string()), key))
+      .unwrap_or_else(|| (Self::default(), key))
+  }
+}
+
+/**
+ * The tenant a request acts for. A key bound to a tenant always acts for it, and a header naming
+ * another tenant is rejected. Unbound keys act for the default tenant, with only admin keys able to
+ * name another. Without auth nothing vouches for the request, so the header is taken as is.
+ */
+fn resolve_tenant_id(
+  requested: Option<TenantId>,
+  authenticated_key: Option<&AuthenticatedKey>,
+) -> Result<TenantId, Status> {
+  let Some(authenticated_key) = authenticated_key else {
+    return Ok(requested.unwrap_or_default());
+  };
+  match (&authenticated_key.tenant_id, requested) {
+    (Some(bound), Some(requested)) if *bound != requested => Err(Status::permission_denied(
+      format!("API key is bound to tenant {}", bound.to_string()),
+    )),
+    (Some(bound), _) => Ok(bound.clone()),
+    (None, Some(requested))
+      if requested.is_default() || authenticated_key.scope == ApiKeyScope::Admin =>
+    {
+      Ok(requested)
+    }
+    (None, Some(_)) => Err(Status::permission_denied(
+      "Only admin keys can act for a tenant they aren't bound to",
+    )),
+    (None, None) => Ok(TenantId::default()),
+  }
+}
+
+fn parse_requested_tenant_id(value: Option<&str>) -> Result<Option<TenantId>, Status> {
+  value
+    .map(|tenant_id| TenantId::try_from(tenant_id.to_string()))
+    .transpose()
+    .map_err(|err| Status::invalid_argument(err.to_string()))
+}
+
+pub fn request_tenant_id<T>(request: &Request<T>) -> Result<TenantId, Status> {
+  let requested = parse_requested_tenant_id(
+    request
+      .metadata()
+      .get(TENANT_METADATA_KEY)
+      .and_then(|value| value.to_str().ok()),
+  )?;
+  resolve_tenant_id(requested, request.extensions().get::<AuthenticatedKey>())
+}
+
+/**
+ * For services connected once for the whole instance, whose account belongs to the default tenant
+ */
+pub fn require_default_tenant<T>(request: &Request<T>, service: &str) -> Result<(), Status> {
+  if request_tenant_id(request)?.is_default() {
+    Ok(())
+  } else {
+    Err(Status::permission_denied(format!(
+      "{} is only connected for the default tenant",
+      service
+    )))
+  }
+}
+
+/**
+ * `request_tenant_id` for the plain HTTP endpoints, which take the tenant from the same header
+ */
+pub fn http_request_tenant_id<B>(request: &http::Request<B>) -> Result<TenantId, Status> {
+  let requested = parse_requested_tenant_id(
+    request
+      .headers()
+      .get(TENANT_METADATA_KEY)
+      .and_then(|value| value.to_str().ok()),
+  )?;
+  resolve_tenant_id(requested, request.extensions().get::<AuthenticatedKey>())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_scope_key() -> Result<()> {
+    let default = TenantId::default();
+    assert_eq!(
+      default.scope_key("spotify:credentials"),
+      "spotify:credentials"
+    );
+    assert_eq!(
+      TenantId::unscope_key("spotify:credentials"),
+      (default, "spotify:credentials")
+    );
+
+    let alice = TenantId::try_from("alice".to_string())?;
+    let key = alice.scope_key("spotify:credentials");
+    assert_eq!(key, "tenant:alice:spotify:credentials");
+    assert_eq!(TenantId::unscope_key(&key), (alice, "spotify:credentials"));
+    assert!(TenantId::try_from("a:b".to_string()).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_resolve_tenant_id() -> Result<()> {
+    let alice = TenantId::try_from("alice".to_string())?;
+    let bob = TenantId::try_from("bob".to_string())?;
+    let key = |scope, tenant_id| AuthenticatedKey {
+      id: "key".to_string(),
+      scope,
+      tenant_id,
+    };
+    let alice_key = key(ApiKeyScope::ReadOnly, Some(alice.clone()));
+    let unbound_key = key(ApiKeyScope::ReadOnly, None);
+    let admin_key = key(ApiKeyScope::Admin, None);
+
+    assert_eq!(resolve_tenant_id(Some(bob.clone()), None)?, bob);
+    assert_eq!(resolve_tenant_id(None, Some(&alice_key))?, alice);
+    assert_eq!(
+      resolve_tenant_id(Some(alice.clone()), Some(&alice_key))?,
+      alice
+    );
+    assert!(resolve_tenant_id(Some(bob.clone()), Some(&alice_key)).is_err());
+    assert_eq!(
+      resolve_tenant_id(None, Some(&unbound_key))?,
+      TenantId::default()
+    );
+    assert!(resolve_tenant_id(Some(bob.clone()), Some(&unbound_key)).is_err());
+    assert_eq!(resolve_tenant_id(Some(bob.clone()), Some(&admin_key))?, bob);
+    Ok(())
+  }
+}
@@ -0,0 +1,3 @@
+pub mod tidal_client;
+pub mod tidal_credential_repository;
+pub mod tidal_service;
@@ -0,0 +1,493 @@
+use super::tidal_credential_repository::{TidalCredentialRepository, TidalCredentials};
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  music_service::music_service_client::{
+    MusicService, MusicServiceAlbum, MusicServiceClient, MusicServiceTrack, MusicServiceTrackQuery,
+  },
+  settings::TidalSettings,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{TimeDelta, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use reqwest::{Client, Method, RequestBuilder, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tracing::{info, warn};
+use ulid::Ulid;
+
+lazy_static! {
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(2u32)));
+}
+
+const AUTHORIZE_URL: &str = "https://login.tidal.com/authorize";
+const TOKEN_URL: &str = "https://auth.tidal.com/v1/oauth2/token";
+const API_HOST: &str = "https://openapi.tidal.com";
+const API_URL: &str = "https://openapi.tidal.com/v2";
+const SCOPES: &str = "collection.read playlists.read playlists.write search.read user.read";
+const DEFAULT_COUNTRY_CODE: &str = "US";
+const JSON_API_CONTENT_TYPE: &str = "application/vnd.api+json";
+const ALBUMS_BATCH_SIZE: usize = 20;
+/**
+ * Tidal caps playlist item additions at 20 tracks per request
+ */
+const PLAYLIST_ITEMS_BATCH_SIZE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+struct TidalTokenResponse {
+  access_token: String,
+  refresh_token: Option<String>,
+  expires_in: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonApiResource {
+  id: String,
+  #[serde(default)]
+  attributes: Value,
+  #[serde(default)]
+  relationships: Value,
+}
+
+impl JsonApiResource {
+  fn attribute(&self, name: &str) -> Option<String> {
+    self.attributes.get(name)?.as_str().map(String::from)
+  }
+
+  fn related_ids(&self, relationship: &str) -> Vec<String> {
+    self
+      .relationships
+      .get(relationship)
+      .and_then(|relationship| relationship.get("data"))
+      .and_then(|data| data.as_array())
+      .map(|identifiers| {
+        identifiers
+          .iter()
+          .filter_map(|identifier| identifier.get("id")?.as_str().map(String::from))
+          .collect()
+      })
+      .unwrap_or_default()
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonApiLinks {
+  next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonApiDocument<T> {
+  data: T,
+  #[serde(default)]
+  included: Vec<JsonApiResource>,
+  links: Option<JsonApiLinks>,
+}
+
+impl<T> JsonApiDocument<T> {
+  fn included_by_id(&self) -> HashMap<&str, &JsonApiResource> {
+    self
+      .included
+      .iter()
+      .map(|resource| (resource.id.as_str(), resource))
+      .collect()
+  }
+}
+
+/**
+ * Pagination links are relative and may or may not include the API version
+ */
+fn resolve_link(link: &str) -> String {
+  if link.starts_with("http") {
+    link.to_string()
+  } else if link.starts_with("/v2/") {
+    format!("{}{}", API_HOST, link)
+  } else {
+    format!("{}{}", API_URL, link)
+  }
+}
+
+fn get_code_challenge(code_verifier: &str) -> String {
+  BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()))
+}
+
+pub struct TidalClient {
+  client: Client,
+  settings: TidalSettings,
+  credential_repository: TidalCredentialRepository,
+}
+
+impl TidalClient {
+  pub fn new(settings: TidalSettings, kv: Arc<KeyValueStore>) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+      credential_repository: TidalCredentialRepository::new(kv),
+    }
+  }
+
+  fn country_code(&self) -> &str {
+    self
+      .settings
+      .country_code
+      .as_deref()
+      .unwrap_or(DEFAULT_COUNTRY_CODE)
+  }
+
+  pub async fn is_authorized(&self) -> bool {
+    matches!(self.credential_repository.get().await, Ok(Some(_)))
+  }
+
+  /**
+   * Tidal requires PKCE, so each authorization attempt stores a fresh code verifier
+   */
+  pub async fn get_authorize_url(&self) -> Result<String> {
+    let code_verifier = format!("{}{}{}", Ulid::new(), Ulid::new(), Ulid::new());
+    self
+      .credential_repository
+      .put_code_verifier(&code_verifier)
+      .await?;
+    let url = Url::parse_with_params(
+      AUTHORIZE_URL,
+      &[
+        ("response_type", "code"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("redirect_uri", self.settings.redirect_uri.as_str()),
+        ("scope", SCOPES),
+        ("code_challenge_method", "S256"),
+        (
+          "code_challenge",
+          get_code_challenge(&code_verifier).as_str(),
+        ),
+      ],
+    )?;
+    Ok(url.to_string())
+  }
+
+  async fn request_token(&self, params: &[(&str, &str)]) -> Result<TidalTokenResponse> {
+    Ok(
+      self
+        .client
+        .post(TOKEN_URL)
+        .form(params)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TidalTokenResponse>()
+        .await?,
+    )
+  }
+
+  async fn get_current_user_id(&self, access_token: &str) -> Result<String> {
+    let document: JsonApiDocument<JsonApiResource> = self
+      .send(
+        self
+          .api_request(Method::GET, &format!("{}/users/me", API_URL), access_token)
+          .await,
+      )
+      .await?;
+    Ok(document.data.id)
+  }
+
+  pub async fn receive_auth_code(&self, code: &str) -> Result<TidalCredentials> {
+    let code_verifier = self
+      .credential_repository
+      .get_code_verifier()
+      .await?
+      .ok_or_else(|| anyhow!("No Tidal authorization in progress"))?;
+    let token = self
+      .request_token(&[
+        ("grant_type", "authorization_code"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("code", code),
+        ("redirect_uri", self.settings.redirect_uri.as_str()),
+        ("code_verifier", code_verifier.as_str()),
+      ])
+      .await?;
+    let credentials = TidalCredentials {
+      user_id: self.get_current_user_id(&token.access_token).await?,
+      refresh_token: token
+        .refresh_token
+        .ok_or_else(|| anyhow!("Refresh token missing"))?,
+      access_token: token.access_token,
+      expires_at: Utc::now().naive_utc() + TimeDelta::try_seconds(token.expires_in).unwrap(),
+    };
+    self.credential_repository.put(&credentials).await?;
+    Ok(credentials)
+  }
+
+  async fn credentials(&self) -> Result<TidalCredentials> {
+    let credentials = self
+      .credential_repository
+      .get()
+      .await?
+      .ok_or_else(|| anyhow!("Credentials not found"))?;
+    if !credentials.is_expired() {
+      return Ok(credentials);
+    }
+    let token = self
+      .request_token(&[
+        ("grant_type", "refresh_token"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("refresh_token", credentials.refresh_token.as_str()),
+      ])
+      .await?;
+    let credentials = TidalCredentials {
+      access_token: token.access_token,
+      refresh_token: token.refresh_token.unwrap_or(credentials.refresh_token),
+      expires_at: Utc::now().naive_utc() + TimeDelta::try_seconds(token.expires_in).unwrap(),
+      user_id: credentials.user_id,
+    };
+    self.credential_repository.put(&credentials).await?;
+    Ok(credentials)
+  }
+
+  async fn api_request(&self, method: Method, url: &str, access_token: &str) -> RequestBuilder {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(500)))
+      .await;
+    let request = self
+      .client
+      .request(method, url)
+      .bearer_auth(access_token)
+      .header("accept", JSON_API_CONTENT_TYPE);
+    if url.contains("countryCode=") {
+      request
+    } else {
+      request.query(&[("countryCode", self.country_code())])
+    }
+  }
+
+  async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+    Ok(
+      request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<T>()
+        .await?,
+    )
+  }
+
+  async fn get_saved_album_ids(&self, credentials: &TidalCredentials) -> Result<Vec<String>> {
+    let mut album_ids = vec![];
+    let mut url = Some(format!(
+      "{}/userCollections/{}/relationships/albums",
+      API_URL, credentials.user_id
+    ));
+    while let Some(next_url) = url {
+      let document: JsonApiDocument<Vec<JsonApiResource>> = self
+        .send(
+          self
+            .api_request(Method::GET, &next_url, &credentials.access_token)
+            .await,
+        )
+        .await?;
+      album_ids.extend(document.data.into_iter().map(|resource| resource.id));
+      url = document
+        .links
+        .and_then(|links| links.next)
+        .map(|link| resolve_link(&link));
+    }
+    Ok(album_ids)
+  }
+
+  async fn get_albums(
+    &self,
+    credentials: &TidalCredentials,
+    album_ids: &[String],
+  ) -> Result<Vec<MusicServiceAlbum>> {
+    let mut albums = vec![];
+    for chunk in album_ids.chunks(ALBUMS_BATCH_SIZE) {
+      let document: JsonApiDocument<Vec<JsonApiResource>> = self
+        .send(
+          self
+            .api_request(
+              Method::GET,
+              &format!("{}/albums", API_URL),
+              &credentials.access_token,
+            )
+            .await
+            .query(&[
+              ("filter[id]", chunk.join(",")),
+              ("include", "artists".into()),
+            ]),
+        )
+        .await?;
+      let included = document.included_by_id();
+      albums.extend(document.data.iter().filter_map(|album| {
+        Some(MusicServiceAlbum {
+          id: album.id.clone(),
+          name: album.attribute("title")?,
+          artist_names: album
+            .related_ids("artists")
+            .iter()
+            .filter_map(|id| included.get(id.as_str())?.attribute("name"))
+            .collect(),
+        })
+      }));
+    }
+    Ok(albums)
+  }
+
+  async fn search_tracks(
+    &self,
+    credentials: &TidalCredentials,
+    query: &MusicServiceTrackQuery,
+  ) -> Result<Vec<MusicServiceTrack>> {
+    let request = match &query.isrc {
+      Some(isrc) => self
+        .api_request(
+          Method::GET,
+          &format!("{}/tracks", API_URL),
+          &credentials.access_token,
+        )
+        .await
+        .query(&[("filter[isrc]", isrc.as_str()), ("include", "artists")]),
+      None => {
+        let search_text = query.search_text();
+        let mut url = Url::parse(API_URL)?;
+        url
+          .path_segments_mut()
+          .map_err(|_| anyhow!("Invalid Tidal API URL"))?
+          .extend([
+            "searchResults",
+            search_text.as_str(),
+            "relationships",
+            "tracks",
+          ]);
+        self
+          .api_request(Method::GET, url.as_str(), &credentials.access_token)
+          .await
+          .query(&[("include", "tracks")])
+      }
+    };
+    let document: JsonApiDocument<Vec<JsonApiResource>> = self.send(request).await?;
+    let included = document.included_by_id();
+    Ok(
+      document
+        .data
+        .iter()
+        .filter_map(|identifier| {
+          // Search results only reference tracks, which are then found among the included resources
+          let track = if identifier.attributes.is_null() {
+            *included.get(identifier.id.as_str())?
+          } else {
+            identifier
+          };
+          Some(MusicServiceTrack {
+            id: track.id.clone(),
+            name: track.attribute("title")?,
+            artist_names: track
+              .related_ids("artists")
+              .iter()
+              .filter_map(|id| included.get(id.as_str())?.attribute("name"))
+              .collect(),
+            isrc: track.attribute("isrc"),
+          })
+        })
+        .collect(),
+    )
+  }
+}
+
+#[async_trait]
+impl MusicServiceClient for TidalClient {
+  fn service(&self) -> MusicService {
+    MusicService::Tidal
+  }
+
+  async fn is_authorized(&self) -> bool {
+    TidalClient::is_authorized(self).await
+  }
+
+  async fn get_saved_albums(&self) -> Result<Vec<MusicServiceAlbum>> {
+    let credentials = self.credentials().await?;
+    let album_ids = self.get_saved_album_ids(&credentials).await?;
+    let albums = self.get_albums(&credentials, &album_ids).await?;
+    info!(count = albums.len(), "Fetched Tidal saved albums");
+    Ok(albums)
+  }
+
+  async fn find_track(&self, query: &MusicServiceTrackQuery) -> Result<Option<MusicServiceTrack>> {
+    let credentials = self.credentials().await?;
+    let track = self
+      .search_tracks(&credentials, query)
+      .await?
+      .into_iter()
+      .find(|track| query.is_match(track));
+    if track.is_none() && query.isrc.is_some() {
+      return self
+        .find_track(&MusicServiceTrackQuery {
+          isrc: None,
+          ..query.clone()
+        })
+        .await;
+    }
+    Ok(track)
+  }
+
+  async fn create_playlist(
+    &self,
+    name: String,
+    description: Option<String>,
+    track_ids: Vec<String>,
+  ) -> Result<String> {
+    let credentials = self.credentials().await?;
+    let playlist: JsonApiDocument<JsonApiResource> = self
+      .send(
+        self
+          .api_request(
+            Method::POST,
+            &format!("{}/playlists", API_URL),
+            &credentials.access_token,
+          )
+          .await
+          .header("content-type", JSON_API_CONTENT_TYPE)
+          .json(&json!({
+            "data": {
+              "type": "playlists",
+              "attributes": {
+                "name": name,
+                "description": description.unwrap_or_default(),
+                "accessType": "PUBLIC",
+              },
+            },
+          })),
+      )
+      .await?;
+    let playlist_id = playlist.data.id;
+    for chunk in track_ids.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+      let response = self
+        .api_request(
+          Method::POST,
+          &format!("{}/playlists/{}/relationships/items", API_URL, playlist_id),
+          &credentials.access_token,
+        )
+        .await
+        .header("content-type", JSON_API_CONTENT_TYPE)
+        .json(&json!({
+          "data": chunk
+            .iter()
+            .map(|id| json!({ "id": id, "type": "tracks" }))
+            .collect::<Vec<_>>(),
+        }))
+        .send()
+        .await?;
+      if let Err(e) = response.error_for_status() {
+        warn!(
+          playlist_id = playlist_id.as_str(),
+          error = e.to_string(),
+          "Failed to add tracks to Tidal playlist"
+        );
+        return Err(e.into());
+      }
+    }
+    Ok(playlist_id)
+  }
+}
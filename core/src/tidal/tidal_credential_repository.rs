@@ -0,0 +1,59 @@
+use crate::helpers::key_value_store::KeyValueStore;
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+pub struct TidalCredentialRepository {
+  kv: Arc<KeyValueStore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TidalCredentials {
+  pub access_token: String,
+  pub refresh_token: String,
+  pub expires_at: NaiveDateTime,
+  pub user_id: String,
+}
+
+const KEY: &str = "tidal:credentials";
+const CODE_VERIFIER_KEY: &str = "tidal:code_verifier";
+const CODE_VERIFIER_TTL: Duration = Duration::from_secs(60 * 15);
+
+impl TidalCredentials {
+  pub fn is_expired(&self) -> bool {
+    self.expires_at < Utc::now().naive_utc()
+  }
+}
+
+impl TidalCredentialRepository {
+  pub fn new(kv: Arc<KeyValueStore>) -> Self {
+    Self { kv }
+  }
+
+  pub async fn put(&self, credentials: &TidalCredentials) -> Result<()> {
+    self.kv.set(KEY, credentials, None).await
+  }
+
+  pub async fn get(&self) -> Result<Option<TidalCredentials>> {
+    self.kv.get::<TidalCredentials>(KEY).await
+  }
+
+  pub async fn delete(&self) -> Result<()> {
+    self.kv.delete(KEY).await
+  }
+
+  /**
+   * The PKCE verifier of the authorization in progress, kept until the code is exchanged
+   */
+  pub async fn put_code_verifier(&self, code_verifier: &str) -> Result<()> {
+    self
+      .kv
+      .set(CODE_VERIFIER_KEY, code_verifier, Some(CODE_VERIFIER_TTL))
+      .await
+  }
+
+  pub async fn get_code_verifier(&self) -> Result<Option<String>> {
+    self.kv.get::<String>(CODE_VERIFIER_KEY).await
+  }
+}
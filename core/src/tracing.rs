@@ -1,18 +1,34 @@
 use crate::settings::TracingSettings;
 use anyhow::Result;
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{trace::Config, Resource};
+use opentelemetry_sdk::{
+  trace::{Config, Sampler, Tracer},
+  Resource,
+};
 use std::io;
 use std::time::Duration;
+use tonic::metadata::{MetadataKey, MetadataMap};
 use tracing::info;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::{EnvFilter, Registry};
 
-pub fn setup_tracing(tracing_settings: &TracingSettings) -> Result<()> {
+fn sample_ratio(sample_percent: u32) -> f64 {
+  sample_percent.min(100) as f64 / 100.0
+}
+
+fn otel_tracer(endpoint: &str, tracing_settings: &TracingSettings) -> Result<Tracer> {
+  let mut metadata = MetadataMap::new();
+  if let Some(headers) = &tracing_settings.otel_collector_headers {
+    for (key, value) in headers {
+      metadata.insert(MetadataKey::from_bytes(key.as_bytes())?, value.parse()?);
+    }
+  }
+
   let otlp_exporter = opentelemetry_otlp::new_exporter()
     .tonic()
     .with_timeout(Duration::from_secs(3))
-    .with_endpoint(&tracing_settings.otel_collector_endpoint);
+    .with_endpoint(endpoint)
+    .with_metadata(metadata);
 
   let mut resource_labels = vec![
     opentelemetry::KeyValue::new(
@@ -31,16 +47,30 @@ pub fn setup_tracing(tracing_settings: &TracingSettings) -> Result<()> {
     )
   }
 
-  let trace_config = Config::default().with_resource(Resource::new(resource_labels));
+  let trace_config = Config::default()
+    .with_resource(Resource::new(resource_labels))
+    .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+      sample_ratio(tracing_settings.sample_percent),
+    ))));
+
+  Ok(
+    opentelemetry_otlp::new_pipeline()
+      .tracing()
+      .with_exporter(otlp_exporter)
+      .with_trace_config(trace_config)
+      .install_simple()?,
+  )
+}
 
-  let tracer = opentelemetry_otlp::new_pipeline()
-    .tracing()
-    .with_exporter(otlp_exporter)
-    .with_trace_config(trace_config)
-    .install_simple()?;
+pub fn setup_tracing(tracing_settings: &TracingSettings) -> Result<()> {
+  let tracer = tracing_settings
+    .otel_collector_endpoint
+    .as_ref()
+    .map(|endpoint| otel_tracer(endpoint, tracing_settings))
+    .transpose()?;
 
   let registry = Registry::default()
-    .with(tracing_opentelemetry::layer().with_tracer(tracer))
+    .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
     .with(
       tracing_subscriber::fmt::layer()
         .json()
@@ -50,7 +80,11 @@ pub fn setup_tracing(tracing_settings: &TracingSettings) -> Result<()> {
 
   tracing::subscriber::set_global_default(registry).expect("setting default subscriber failed");
 
-  info!("Tracing initialized");
+  info!(
+    otel_collector_endpoint = ?tracing_settings.otel_collector_endpoint,
+    sample_percent = tracing_settings.sample_percent,
+    "Tracing initialized"
+  );
 
   Ok(())
 }
@@ -0,0 +1,3 @@
+pub mod youtube_music_client;
+pub mod youtube_music_credential_repository;
+pub mod youtube_music_service;
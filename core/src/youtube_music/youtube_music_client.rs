@@ -0,0 +1,441 @@
+use super::youtube_music_credential_repository::{
+  YouTubeMusicCredentialRepository, YouTubeMusicCredentials, YouTubeMusicQuotaUsage,
+};
+use crate::{
+  helpers::key_value_store::KeyValueStore,
+  music_service::music_service_client::{
+    MusicService, MusicServiceAlbum, MusicServiceClient, MusicServiceTrack, MusicServiceTrackQuery,
+  },
+  settings::YouTubeMusicSettings,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use chrono_tz::America::Los_Angeles;
+use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
+use htmlescape::decode_html;
+use lazy_static::lazy_static;
+use nonzero::nonzero;
+use regex::Regex;
+use reqwest::{Client, Method, RequestBuilder, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+lazy_static! {
+  static ref RATE_LIMITER: DefaultDirectRateLimiter =
+    RateLimiter::direct(Quota::per_second(nonzero!(5u32)));
+  static ref VIDEO_TITLE_DECORATION: Regex = Regex::new(
+    r"(?i)\s*[\(\[][^\)\]]*\b(official|audio|video|lyrics?|visuali[sz]er|hq|hd)\b[^\)\]]*[\)\]]"
+  )
+  .unwrap();
+}
+
+const AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const API_URL: &str = "https://www.googleapis.com/youtube/v3";
+const SCOPE: &str = "https://www.googleapis.com/auth/youtube";
+const MUSIC_VIDEO_CATEGORY_ID: &str = "10";
+const SEARCH_LIMIT: &str = "5";
+const TRACK_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/**
+ * Unit costs from the YouTube Data API quota calculator
+ */
+const DEFAULT_DAILY_QUOTA: u32 = 10_000;
+const SEARCH_COST: u32 = 100;
+const PLAYLIST_INSERT_COST: u32 = 50;
+const PLAYLIST_ITEM_INSERT_COST: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+struct GoogleTokenResponse {
+  access_token: String,
+  refresh_token: Option<String>,
+  expires_in: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResultId {
+  video_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResultSnippet {
+  title: String,
+  channel_title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+  id: SearchResultId,
+  snippet: SearchResultSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+  #[serde(default)]
+  items: Vec<SearchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaylistResponse {
+  id: String,
+}
+
+/**
+ * The quota resets at midnight Pacific time
+ */
+fn quota_date(now: DateTime<Utc>) -> NaiveDate {
+  now.with_timezone(&Los_Angeles).date_naive()
+}
+
+/**
+ * Auto-generated "Topic" channels carry the artist's name, and uploads often decorate titles with
+ * the artist and tags like "(Official Audio)", none of which are part of the track name
+ */
+fn parse_video_title(title: &str, channel_title: &str) -> (String, String) {
+  let title = decode_html(title).unwrap_or_else(|_| title.to_string());
+  let artist_name = channel_title
+    .strip_suffix(" - Topic")
+    .unwrap_or(channel_title)
+    .to_string();
+  let name = title
+    .split_once(" - ")
+    .filter(|(prefix, _)| prefix.eq_ignore_ascii_case(&artist_name))
+    .map(|(_, name)| name)
+    .unwrap_or(&title);
+  let name = VIDEO_TITLE_DECORATION
+    .replace_all(name, "")
+    .trim()
+    .to_string();
+  (name, artist_name)
+}
+
+pub struct YouTubeMusicClient {
+  client: Client,
+  settings: YouTubeMusicSettings,
+  kv: Arc<KeyValueStore>,
+  credential_repository: YouTubeMusicCredentialRepository,
+  quota_lock: Mutex<()>,
+}
+
+impl YouTubeMusicClient {
+  pub fn new(settings: YouTubeMusicSettings, kv: Arc<KeyValueStore>) -> Self {
+    Self {
+      client: Client::new(),
+      settings,
+      credential_repository: YouTubeMusicCredentialRepository::new(Arc::clone(&kv)),
+      kv,
+      quota_lock: Mutex::new(()),
+    }
+  }
+
+  pub async fn is_authorized(&self) -> bool {
+    matches!(self.credential_repository.get().await, Ok(Some(_)))
+  }
+
+  pub fn get_authorize_url(&self) -> Result<String> {
+    let url = Url::parse_with_params(
+      AUTHORIZE_URL,
+      &[
+        ("response_type", "code"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("redirect_uri", self.settings.redirect_uri.as_str()),
+        ("scope", SCOPE),
+        ("access_type", "offline"),
+        ("prompt", "consent"),
+      ],
+    )?;
+    Ok(url.to_string())
+  }
+
+  async fn request_token(&self, params: &[(&str, &str)]) -> Result<GoogleTokenResponse> {
+    Ok(
+      self
+        .client
+        .post(TOKEN_URL)
+        .form(params)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<GoogleTokenResponse>()
+        .await?,
+    )
+  }
+
+  pub async fn receive_auth_code(&self, code: &str) -> Result<YouTubeMusicCredentials> {
+    let token = self
+      .request_token(&[
+        ("grant_type", "authorization_code"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("client_secret", self.settings.client_secret.as_str()),
+        ("code", code),
+        ("redirect_uri", self.settings.redirect_uri.as_str()),
+      ])
+      .await?;
+    let credentials = YouTubeMusicCredentials {
+      refresh_token: token
+        .refresh_token
+        .ok_or_else(|| anyhow!("Refresh token missing"))?,
+      access_token: token.access_token,
+      expires_at: Utc::now().naive_utc() + TimeDelta::try_seconds(token.expires_in).unwrap(),
+    };
+    self.credential_repository.put(&credentials).await?;
+    Ok(credentials)
+  }
+
+  async fn credentials(&self) -> Result<YouTubeMusicCredentials> {
+    let credentials = self
+      .credential_repository
+      .get()
+      .await?
+      .ok_or_else(|| anyhow!("Credentials not found"))?;
+    if !credentials.is_expired() {
+      return Ok(credentials);
+    }
+    let token = self
+      .request_token(&[
+        ("grant_type", "refresh_token"),
+        ("client_id", self.settings.client_id.as_str()),
+        ("client_secret", self.settings.client_secret.as_str()),
+        ("refresh_token", credentials.refresh_token.as_str()),
+      ])
+      .await?;
+    let credentials = YouTubeMusicCredentials {
+      access_token: token.access_token,
+      refresh_token: token.refresh_token.unwrap_or(credentials.refresh_token),
+      expires_at: Utc::now().naive_utc() + TimeDelta::try_seconds(token.expires_in).unwrap(),
+    };
+    self.credential_repository.put(&credentials).await?;
+    Ok(credentials)
+  }
+
+  /**
+   * Units are spent whether or not a request succeeds, so they're reserved up front and a batch
+   * that wouldn't fit in what's left of the day is refused before any of it is sent
+   */
+  async fn reserve_quota(&self, units: u32) -> Result<()> {
+    let _lock = self.quota_lock.lock().await;
+    let today = quota_date(Utc::now());
+    let used = self
+      .credential_repository
+      .get_quota_usage()
+      .await?
+      .filter(|usage| usage.date == today)
+      .map(|usage| usage.units)
+      .unwrap_or(0);
+    let daily_quota = self.settings.daily_quota.unwrap_or(DEFAULT_DAILY_QUOTA);
+    if used + units > daily_quota {
+      bail!(
+        "YouTube Data API quota exhausted, {} of {} units left today",
+        daily_quota.saturating_sub(used),
+        daily_quota
+      );
+    }
+    self
+      .credential_repository
+      .put_quota_usage(&YouTubeMusicQuotaUsage {
+        date: today,
+        units: used + units,
+      })
+      .await
+  }
+
+  async fn api_request(&self, method: Method, path: &str, access_token: &str) -> RequestBuilder {
+    RATE_LIMITER
+      .until_ready_with_jitter(Jitter::up_to(Duration::from_millis(200)))
+      .await;
+    self
+      .client
+      .request(method, format!("{}/{}", API_URL, path))
+      .bearer_auth(access_token)
+  }
+
+  async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+    Ok(
+      request
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<T>()
+        .await?,
+    )
+  }
+
+  async fn search_tracks(&self, query: &MusicServiceTrackQuery) -> Result<Vec<MusicServiceTrack>> {
+    let credentials = self.credentials().await?;
+    self.reserve_quota(SEARCH_COST).await?;
+    let response: SearchResponse = self
+      .send(
+        self
+          .api_request(Method::GET, "search", &credentials.access_token)
+          .await
+          .query(&[
+            ("part", "snippet"),
+            ("type", "video"),
+            ("videoCategoryId", MUSIC_VIDEO_CATEGORY_ID),
+            ("maxResults", SEARCH_LIMIT),
+            ("q", query.search_text().as_str()),
+          ]),
+      )
+      .await?;
+    Ok(
+      response
+        .items
+        .into_iter()
+        .filter_map(|result| {
+          let (name, artist_name) =
+            parse_video_title(&result.snippet.title, &result.snippet.channel_title);
+          Some(MusicServiceTrack {
+            id: result.id.video_id?,
+            name,
+            artist_names: vec![artist_name],
+            isrc: None,
+          })
+        })
+        .collect(),
+    )
+  }
+}
+
+#[async_trait]
+impl MusicServiceClient for YouTubeMusicClient {
+  fn service(&self) -> MusicService {
+    MusicService::YouTubeMusic
+  }
+
+  async fn is_authorized(&self) -> bool {
+    YouTubeMusicClient::is_authorized(self).await
+  }
+
+  async fn get_saved_albums(&self) -> Result<Vec<MusicServiceAlbum>> {
+    bail!("The YouTube Data API does not expose YouTube Music libraries")
+  }
+
+  /**
+   * Searches are the most expensive call, so matches are cached. YouTube has no ISRCs, so tracks
+   * are always matched by name.
+   */
+  async fn find_track(&self, query: &MusicServiceTrackQuery) -> Result<Option<MusicServiceTrack>> {
+    let cache_key = format!("youtube_music:track:{}", query.search_text().to_lowercase());
+    if let Some(track) = self.kv.get::<MusicServiceTrack>(&cache_key).await? {
+      return Ok(Some(track));
+    }
+    let query = MusicServiceTrackQuery {
+      isrc: None,
+      ..query.clone()
+    };
+    let track = self
+      .search_tracks(&query)
+      .await?
+      .into_iter()
+      .find(|track| query.is_match(track));
+    if let Some(track) = &track {
+      self
+        .kv
+        .set(&cache_key, track, Some(TRACK_CACHE_TTL))
+        .await?;
+    }
+    Ok(track)
+  }
+
+  async fn create_playlist(
+    &self,
+    name: String,
+    description: Option<String>,
+    track_ids: Vec<String>,
+  ) -> Result<String> {
+    let credentials = self.credentials().await?;
+    self
+      .reserve_quota(PLAYLIST_INSERT_COST + PLAYLIST_ITEM_INSERT_COST * track_ids.len() as u32)
+      .await?;
+    let playlist: PlaylistResponse = self
+      .send(
+        self
+          .api_request(Method::POST, "playlists", &credentials.access_token)
+          .await
+          .query(&[("part", "snippet,status")])
+          .json(&json!({
+            "snippet": {
+              "title": name,
+              "description": description.unwrap_or_default(),
+            },
+            "status": {
+              "privacyStatus": "private",
+            },
+          })),
+      )
+      .await?;
+    // Items can only be added one at a time, and in order
+    for track_id in &track_ids {
+      let response = self
+        .api_request(Method::POST, "playlistItems", &credentials.access_token)
+        .await
+        .query(&[("part", "snippet")])
+        .json(&json!({
+          "snippet": {
+            "playlistId": playlist.id,
+            "resourceId": {
+              "kind": "youtube#video",
+              "videoId": track_id,
+            },
+          },
+        }))
+        .send()
+        .await?;
+      if let Err(e) = response.error_for_status() {
+        warn!(
+          playlist_id = playlist.id.as_str(),
+          error = e.to_string(),
+          "Failed to add track to YouTube playlist"
+        );
+        return Err(e.into());
+      }
+    }
+    info!(
+      playlist_id = playlist.id.as_str(),
+      count = track_ids.len(),
+      "Created YouTube playlist"
+    );
+    Ok(playlist.id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  #[test]
+  fn test_quota_date() {
+    // 07:30 UTC is the previous day in Pacific standard time, but not in daylight time
+    assert_eq!(
+      quota_date(Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap()),
+      NaiveDate::from_ymd_opt(2024, 1, 14).unwrap()
+    );
+    assert_eq!(
+      quota_date(Utc.with_ymd_and_hms(2024, 7, 15, 7, 30, 0).unwrap()),
+      NaiveDate::from_ymd_opt(2024, 7, 15).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_parse_video_title() {
+    assert_eq!(
+      parse_video_title("Jóga", "Björk - Topic"),
+      ("Jóga".to_string(), "Björk".to_string())
+    );
+    assert_eq!(
+      parse_video_title("Sade - No Ordinary Love (Official Music Video)", "Sade"),
+      ("No Ordinary Love".to_string(), "Sade".to_string())
+    );
+    assert_eq!(
+      parse_video_title("Don&#39;t Stop [HD]", "SomeChannel"),
+      ("Don't Stop".to_string(), "SomeChannel".to_string())
+    );
+  }
+}
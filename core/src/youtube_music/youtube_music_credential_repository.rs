@@ -0,0 +1,60 @@
+use crate::helpers::key_value_store::KeyValueStore;
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub struct YouTubeMusicCredentialRepository {
+  kv: Arc<KeyValueStore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YouTubeMusicCredentials {
+  pub access_token: String,
+  pub refresh_token: String,
+  pub expires_at: NaiveDateTime,
+}
+
+/**
+ * YouTube Data API units spent on a quota day
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YouTubeMusicQuotaUsage {
+  pub date: NaiveDate,
+  pub units: u32,
+}
+
+const KEY: &str = "youtube_music:credentials";
+const QUOTA_USAGE_KEY: &str = "youtube_music:quota_usage";
+
+impl YouTubeMusicCredentials {
+  pub fn is_expired(&self) -> bool {
+    self.expires_at < Utc::now().naive_utc()
+  }
+}
+
+impl YouTubeMusicCredentialRepository {
+  pub fn new(kv: Arc<KeyValueStore>) -> Self {
+    Self { kv }
+  }
+
+  pub async fn put(&self, credentials: &YouTubeMusicCredentials) -> Result<()> {
+    self.kv.set(KEY, credentials, None).await
+  }
+
+  pub async fn get(&self) -> Result<Option<YouTubeMusicCredentials>> {
+    self.kv.get::<YouTubeMusicCredentials>(KEY).await
+  }
+
+  pub async fn delete(&self) -> Result<()> {
+    self.kv.delete(KEY).await
+  }
+
+  pub async fn get_quota_usage(&self) -> Result<Option<YouTubeMusicQuotaUsage>> {
+    self.kv.get::<YouTubeMusicQuotaUsage>(QUOTA_USAGE_KEY).await
+  }
+
+  pub async fn put_quota_usage(&self, usage: &YouTubeMusicQuotaUsage) -> Result<()> {
+    self.kv.set(QUOTA_USAGE_KEY, usage, None).await
+  }
+}
@@ -0,0 +1,71 @@
+use super::youtube_music_client::YouTubeMusicClient;
+use crate::{
+  context::ApplicationContext,
+  proto::{self, HandleAuthorizationCodeRequest, IsAuthorizedReply},
+  tenant::tenant_id::require_default_tenant,
+};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::error;
+
+pub struct YouTubeMusicService {
+  pub youtube_music_client: Option<Arc<YouTubeMusicClient>>,
+}
+
+impl YouTubeMusicService {
+  pub fn new(app_context: Arc<ApplicationContext>) -> Self {
+    Self {
+      youtube_music_client: app_context.youtube_music_client.clone(),
+    }
+  }
+
+  fn client<T>(&self, request: &Request<T>) -> Result<&YouTubeMusicClient, Status> {
+    require_default_tenant(request, "YouTube Music")?;
+    self
+      .youtube_music_client
+      .as_deref()
+      .ok_or_else(|| Status::failed_precondition("YouTube Music is not configured"))
+  }
+}
+
+#[tonic::async_trait]
+impl proto::YouTubeMusicService for YouTubeMusicService {
+  async fn is_authorized(
+    &self,
+    request: Request<()>,
+  ) -> Result<Response<IsAuthorizedReply>, Status> {
+    let reply = IsAuthorizedReply {
+      authorized: self.client(&request)?.is_authorized().await,
+    };
+    Ok(Response::new(reply))
+  }
+
+  async fn get_authorization_url(
+    &self,
+    request: Request<()>,
+  ) -> Result<Response<proto::GetAuthorizationUrlReply>, Status> {
+    let reply = proto::GetAuthorizationUrlReply {
+      url: self.client(&request)?.get_authorize_url().map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Internal server error")
+      })?,
+    };
+    Ok(Response::new(reply))
+  }
+
+  async fn handle_authorization_code(
+    &self,
+    request: Request<HandleAuthorizationCodeRequest>,
+  ) -> Result<Response<()>, Status> {
+    self
+      .client(&request)?
+      .receive_auth_code(&request.into_inner().code)
+      .await
+      .map_err(|e| {
+        error!("Error: {:?}", e);
+        Status::internal("Internal server error")
+      })?;
+
+    Ok(Response::new(()))
+  }
+}
@@ -0,0 +1,19 @@
+use lute::parser::parser_fixtures::{validate_fixtures, DEFAULT_FIXTURE_DIR};
+use std::path::Path;
+
+#[test]
+fn test_parsers_match_recorded_fixtures() {
+  let reports = validate_fixtures(Path::new(DEFAULT_FIXTURE_DIR)).unwrap();
+  let failures = reports
+    .iter()
+    .filter(|report| !report.is_ok())
+    .map(|report| report.to_string())
+    .collect::<Vec<_>>();
+  assert!(
+    failures.is_empty(),
+    "{} of {} parser fixtures failed:\n{}",
+    failures.len(),
+    reports.len(),
+    failures.join("\n")
+  );
+}
@@ -0,0 +1,26 @@
+const { diffCorpus } = require("./shared/lute");
+
+// Usage: npm run diff_corpus -- http://mirror:22000 [--sync]
+(async () => {
+  const peerAddress = process.argv[2];
+  if (!peerAddress) {
+    console.error("Usage: diff_corpus <peer address> [--sync]");
+    process.exit(1);
+  }
+  const scheduleSyncs = process.argv.includes("--sync");
+  const diff = await diffCorpus(peerAddress, scheduleSyncs);
+
+  for (const [label, fileNames] of [
+    ["Missing locally", diff.missingLocally],
+    ["Missing on peer", diff.missingOnPeer],
+    ["Differing", diff.differing],
+  ]) {
+    console.log(`${label}: ${fileNames.length}`);
+    for (const fileName of fileNames) {
+      console.log(`  ${fileName}`);
+    }
+  }
+  if (scheduleSyncs) {
+    console.log(`Scheduled ${diff.syncsScheduled} crawls`);
+  }
+})();
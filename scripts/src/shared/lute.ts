@@ -20,6 +20,10 @@ export const client = {
     coreUrl,
     ChannelCredentials.createInsecure()
   ),
+  operations: new lute.OperationsServiceClient(
+    coreUrl,
+    ChannelCredentials.createInsecure()
+  ),
 };
 
 export const getAlbumMonitor = async () => {
@@ -40,3 +44,9 @@ export const parseFileOnContentStore = async (fileName: string) => {
     new lute.ParseFileOnContentStoreRequest({ fileName })
   );
 };
+
+export const diffCorpus = async (peerAddress: string, scheduleSyncs: boolean) => {
+  return client.operations.DiffCorpus(
+    new lute.DiffCorpusRequest({ peerAddress, scheduleSyncs })
+  );
+};
@@ -3,6 +3,7 @@ import { RecommendationSettingsForm } from "./pages/recommendations/types";
 import { SimilarAlbumsForm } from "./pages/similar-albums/types";
 import {
   AlbumServiceClient,
+  OperationsServiceClient,
   ProfileServiceClient,
   RecommendationServiceClient,
   SpotifyServiceClient,
@@ -20,18 +21,21 @@ import {
   CreateSpotifyPlaylistRequest,
   DeleteProfileRequest,
   DraftSpotifyPlaylistRequest,
+  EmbeddingCacheStats,
   EmbeddingSimilarityAlbumAssessmentSettings,
   FindSimilarAlbumsRequest,
   GetAlbumRequest,
   GetManyAlbumsRequest,
   GetPendingSpotifyImportsReply,
   GetPendingSpotifyImportsRequest,
+  GetProfileAnalyticsRequest,
   GetProfileRequest,
   GetProfileSummaryRequest,
   HandleAuthorizationCodeRequest,
   ImportSavedSpotifyTracksRequest,
   ImportSpotifyPlaylistTracksRequest,
   Profile,
+  ProfileAnalytics,
   ProfileSummary,
   PutAlbumOnProfileRequest,
   QuantileRankAlbumAssessmentSettings,
@@ -49,6 +53,7 @@ const client = {
   spotify: new SpotifyServiceClient(coreUrl),
   profile: new ProfileServiceClient(coreUrl),
   album: new AlbumServiceClient(coreUrl),
+  operations: new OperationsServiceClient(coreUrl),
   recommendation: new RecommendationServiceClient(coreUrl),
 };
 
@@ -296,6 +301,15 @@ export const getProfileSummary = async (
   return response.getSummary();
 };
 
+export const getProfileAnalytics = async (
+  id: string,
+): Promise<ProfileAnalytics | undefined> => {
+  const request = new GetProfileAnalyticsRequest();
+  request.setId(id);
+  const response = await client.profile.getProfileAnalytics(request, null);
+  return response.getAnalytics();
+};
+
 export const createProfile = async (
   id: string,
   name: string,
@@ -494,6 +508,16 @@ export const getAlbumMonitor = async (): Promise<AlbumMonitor> => {
   return response.getMonitor()!;
 };
 
+export const getEmbeddingCacheStats = async (): Promise<
+  EmbeddingCacheStats[]
+> => {
+  const response = await client.operations.getEmbeddingCacheStats(
+    new Empty(),
+    null,
+  );
+  return response.getStatsList();
+};
+
 export const findSimilarAlbums = async ({
   fileName,
   embeddingKey,
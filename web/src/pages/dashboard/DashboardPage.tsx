@@ -1,4 +1,10 @@
-import { Container, Grid, Card as MantineCard, Text } from "@mantine/core";
+import {
+  Container,
+  Grid,
+  Card as MantineCard,
+  Table,
+  Text,
+} from "@mantine/core";
 import {
   Bar,
   BarChart,
@@ -10,6 +16,7 @@ import {
   YAxis,
 } from "recharts";
 import { Card } from "../../components";
+import { EmbeddingCacheStats } from "../../proto/lute_pb";
 import { useRemoteContext } from "../../remote-context";
 
 interface AlbumsByYearChartItem {
@@ -65,8 +72,52 @@ const CountCard = ({ title, count }: { title: string; count: number }) => {
   );
 };
 
+const formatBytes = (bytes: number) => {
+  const units = ["B", "KB", "MB", "GB"];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+};
+
+const EmbeddingCacheCard = ({ stats }: { stats: EmbeddingCacheStats[] }) => (
+  <Card label="Embedding Cache" contentPt="sm">
+    <Table>
+      <Table.Thead>
+        <Table.Tr>
+          <Table.Th>Provider</Table.Th>
+          <Table.Th>Hits</Table.Th>
+          <Table.Th>Misses</Table.Th>
+          <Table.Th>Hit Rate</Table.Th>
+          <Table.Th>Provider Calls</Table.Th>
+          <Table.Th>Cached</Table.Th>
+          <Table.Th>Stored</Table.Th>
+          <Table.Th>Est. Cost Avoided</Table.Th>
+        </Table.Tr>
+      </Table.Thead>
+      <Table.Tbody>
+        {stats.map((item) => (
+          <Table.Tr key={item.getEmbeddingKey()}>
+            <Table.Td>{item.getEmbeddingKey()}</Table.Td>
+            <Table.Td>{item.getHits().toLocaleString()}</Table.Td>
+            <Table.Td>{item.getMisses().toLocaleString()}</Table.Td>
+            <Table.Td>{(item.getHitRate() * 100).toFixed(1)}%</Table.Td>
+            <Table.Td>{item.getProviderCalls().toLocaleString()}</Table.Td>
+            <Table.Td>{item.getCachedCount().toLocaleString()}</Table.Td>
+            <Table.Td>{formatBytes(item.getBytesStored())}</Table.Td>
+            <Table.Td>${item.getEstimatedCostAvoided().toFixed(2)}</Table.Td>
+          </Table.Tr>
+        ))}
+      </Table.Tbody>
+    </Table>
+  </Card>
+);
+
 export const Component = () => {
-  const { albumMonitor } = useRemoteContext();
+  const { albumMonitor, embeddingCacheStats } = useRemoteContext();
 
   return (
     <div
@@ -196,6 +247,9 @@ export const Component = () => {
               </ResponsiveContainer>
             </Card>
           </Grid.Col>
+          <Grid.Col span={12}>
+            <EmbeddingCacheCard stats={embeddingCacheStats} />
+          </Grid.Col>
         </Grid>
       </Container>
     </div>
@@ -2,32 +2,41 @@ import { useRouteLoaderData } from "react-router-dom";
 import {
   getAlbumMonitor,
   getAllProfiles,
+  getEmbeddingCacheStats,
   getEmbeddingKeys,
   getIsSpotifyAuthenticated,
 } from "./client";
-import { AlbumMonitor, Profile } from "./proto/lute_pb";
+import { AlbumMonitor, EmbeddingCacheStats, Profile } from "./proto/lute_pb";
 
 export interface AppRemoteContext {
   isSpotifyAuthenticated: boolean;
   profiles: Profile[];
   albumMonitor: AlbumMonitor;
   embeddingKeys: string[];
+  embeddingCacheStats: EmbeddingCacheStats[];
 }
 
 export const getRemoteContext = async (): Promise<AppRemoteContext> => {
-  const [isSpotifyAuthenticated, profiles, albumMonitor, embeddingKeys] =
-    await Promise.all([
-      getIsSpotifyAuthenticated(),
-      getAllProfiles(),
-      getAlbumMonitor(),
-      getEmbeddingKeys(),
-    ]);
+  const [
+    isSpotifyAuthenticated,
+    profiles,
+    albumMonitor,
+    embeddingKeys,
+    embeddingCacheStats,
+  ] = await Promise.all([
+    getIsSpotifyAuthenticated(),
+    getAllProfiles(),
+    getAlbumMonitor(),
+    getEmbeddingKeys(),
+    getEmbeddingCacheStats(),
+  ]);
 
   return {
     isSpotifyAuthenticated,
     profiles,
     albumMonitor,
     embeddingKeys,
+    embeddingCacheStats,
   };
 };
 